@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use picori::yaz0::{self, CompressOptions, Strategy};
+
+fn compress(c: &mut Criterion) {
+    let inputs: [(&str, Vec<u8>); 3] = [
+        (
+            "test1",
+            include_bytes!("../assets/tests/yaz0/test1.output").to_vec(),
+        ),
+        ("zeros", vec![0; 0x100000]),
+        (
+            "noise",
+            (0..0x100000u32)
+                .map(|x| (x.wrapping_mul(0x9E3779B1) >> 24) as u8)
+                .collect(),
+        ),
+    ];
+    let strategies = [
+        ("greedy", Strategy::Greedy),
+        ("lazy", Strategy::Lazy),
+        ("optimal", Strategy::Optimal),
+        ("nintendo", Strategy::Nintendo),
+    ];
+
+    let mut group = c.benchmark_group("yaz0_compress");
+    group.sample_size(10);
+    for (input, data) in &inputs {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        for (name, strategy) in strategies {
+            let options = CompressOptions::new().strategy(strategy);
+            group.bench_with_input(BenchmarkId::new(name, input), data, |b, data| {
+                b.iter(|| yaz0::compress_with(data, &options))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn decompress(c: &mut Criterion) {
+    let compressed = include_bytes!("../assets/tests/yaz0/test1.input");
+    let size = yaz0::header(&mut std::io::Cursor::new(compressed))
+        .unwrap()
+        .decompressed_size as usize;
+
+    let mut group = c.benchmark_group("yaz0_decompress");
+    group.throughput(Throughput::Bytes(size as u64));
+    group.bench_function("test1", |b| {
+        b.iter(|| {
+            let mut cursor = std::io::Cursor::new(compressed);
+            let size = yaz0::Header::decompressed_size(&mut cursor).unwrap();
+            yaz0::decompress(&mut cursor, size).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compress, decompress);
+criterion_main!(benches);
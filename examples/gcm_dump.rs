@@ -45,13 +45,21 @@ fn hex2(value: u8) -> String { format!("\x1b[36m{:#04x}\x1b[0m", value) }
 
 fn hex8(value: u32) -> String { format!("\x1b[36m{:#010x}\x1b[0m", value) }
 
+fn hex16(value: u64) -> String { format!("\x1b[36m{:#018x}\x1b[0m", value) }
+
 fn num(value: u32) -> String { format!("\x1b[36m{}\x1b[0m", value) }
 
 fn output_boot(boot: &gcm::Boot) {
     println!("boot.bin:");
     println!("  console: {}", match boot.console {
         gcm::ConsoleType::GameCube => "GameCube",
+        gcm::ConsoleType::Wii => "Wii",
     });
+    println!(
+        "  game id: \x1b[36m{}\x1b[0m ({:?})",
+        boot.game_id(),
+        boot.game_id().region()
+    );
     println!(
         "  game code: {} {}",
         hex2(boot.game_code[0]),
@@ -97,13 +105,13 @@ fn output_bi2(bi2: &gcm::Bi2) {
         .map(|x| (*x.0, *x.1))
         .collect::<Vec<_>>();
 
-    options.sort_by(|a, b| a.0.cmp(&b.0));
+    options.sort_by_key(|a| a.0);
     for (i, value) in options {
         println!("  [{:04x}]: {} ({})", i.index(), hex8(value), num(value));
     }
 }
 
-fn output_data(data: &Vec<u8>, width: usize) {
+fn output_data(data: &[u8], width: usize) {
     for (j, line) in data.chunks(width).enumerate() {
         print!("{:06x}: ", j * 32);
         for byte in line {
@@ -139,7 +147,7 @@ fn output_fst(fst: &gcm::Fst) {
                     "{:indent$}\x1b[32m{}\x1b[0m offset: {} size: {}",
                     "",
                     name,
-                    hex8(offset),
+                    hex16(offset),
                     hex8(size),
                     indent = indent * 2
                 );
@@ -182,18 +190,18 @@ fn main() {
     let gcm = Gcm::from_binary(&mut file).unwrap();
 
     if dump_boot {
-        output_boot(&gcm.boot());
+        output_boot(gcm.boot());
     }
 
     if dump_bi2 {
-        output_bi2(&gcm.bi2());
+        output_bi2(gcm.bi2());
     }
 
     if dump_apploader {
-        output_apploader(&gcm.apploader(), data, width);
+        output_apploader(gcm.apploader(), data, width);
     }
 
     if dump_fst {
-        output_fst(&gcm.fst());
+        output_fst(gcm.fst());
     }
 }
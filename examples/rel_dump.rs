@@ -200,7 +200,7 @@ fn output_relocations(rel: &Rel) {
     }
 }
 
-fn output_data(data: &Vec<u8>, width: usize) {
+fn output_data(data: &[u8], width: usize) {
     for (j, line) in data.chunks(width).enumerate() {
         print!("{:06x}: ", j * 32);
         for byte in line {
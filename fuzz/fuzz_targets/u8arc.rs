@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+extern crate picori;
+
+fuzz_target!(|data: &[u8]| {
+    let reader = std::io::Cursor::new(&data);
+    if let Ok(u8arc) = picori::u8arc::U8Reader::new(reader) {
+        let _ = u8arc.files().count();
+    }
+});
@@ -21,4 +21,5 @@ fn main() {
 
     println!("cargo:rerun-if-changed=scripts/shift_jis.rs");
     println!("cargo:rerun-if-changed=scripts/build.rs");
+    println!("cargo:rerun-if-changed=assets/build");
 }
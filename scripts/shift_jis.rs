@@ -116,6 +116,7 @@ fn generate_table(
     name: &'static str,
     version: Version,
     data: &[Data],
+    encode: bool,
 ) -> Result<(), Error> {
     let data = data
         .iter()
@@ -150,8 +151,8 @@ fn generate_table(
             })
             .collect::<Vec<_>>();
 
-        u1.sort_by(|a, b| a.0.cmp(&b.0));
-        u2.sort_by(|a, b| a.0.cmp(&b.0));
+        u1.sort_by_key(|a| a.0);
+        u2.sort_by_key(|a| a.0);
 
         let first = u1.iter().map(|x| x.0).chain(u2.iter().map(|x| x.0)).min();
         let last = u1.iter().map(|x| x.0).chain(u2.iter().map(|x| x.0)).max();
@@ -197,11 +198,65 @@ fn generate_table(
     if !double_lookup.is_empty() {
         double_lookup.gen_table(format!("{name}_UTF8_D"), &mut buffer)?;
     }
+    if encode {
+        // (unicode, code) pairs sorted by unicode for encoding, the lowest code is
+        // used for characters with multiple codes
+        let mut encode_lookup = data
+            .iter()
+            .filter(|x| matches!(x.byte0, 0x81..=0x9F | 0xE0..=0xFC))
+            .filter_map(|x| match x.value {
+                Value::Unicode1(u) => Some((u, (x.byte0 as u32) << 8 | x.byte1 as u32)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        encode_lookup.sort();
+        encode_lookup.dedup_by_key(|x| x.0);
+        encode_lookup.gen_table(format!("{name}_ENCODE"), &mut buffer)?;
+    }
+
+    Ok(())
+}
+
+/// Generate the (code, unicode) tables of the vendor extensions, sorted by
+/// code, one table for each extension.
+fn generate_extensions(path: &Path, extensions: &str) -> Result<(), Error> {
+    let mut output_file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(path)?;
+
+    let mut buffer = BufWriter::new(&mut output_file);
+
+    for (tag, name) in [
+        ("[NEC]", "SJIS_NEC"),
+        ("[NEC-IBM]", "SJIS_NEC_IBM"),
+        ("[IBM]", "SJIS_IBM"),
+    ] {
+        let mut table = Vec::<(u32, u32)>::new();
+        for line in extensions.lines().filter(|x| !x.starts_with('#')) {
+            let parts: Vec<&str> = line.split('#').collect();
+            assert!(parts.len() == 2, "invalid line");
+            if !parts[1].trim_end().ends_with(tag) {
+                continue;
+            }
+
+            let before: Vec<&str> = parts[0].split(' ').filter(|s| !s.is_empty()).collect();
+            assert!(before.len() == 2, "invalid line");
+            let unicode = parse_unicode(before[1])?;
+            assert!(unicode.len() == 1, "invalid unicode");
+            table.push((parse_hex(before[0])?, unicode[0]));
+        }
+
+        table.sort();
+        table.gen_table(name.to_string(), &mut buffer)?;
+    }
 
     Ok(())
 }
 
 static SHIFT_JIS_1983_2004: &str = include_str!("../assets/build/shift-jis.txt");
+static SHIFT_JIS_EXTENSIONS: &str = include_str!("../assets/build/shift-jis-extensions.txt");
 
 pub fn generate() -> Result<(), Error> {
     let data = SHIFT_JIS_1983_2004
@@ -213,9 +268,11 @@ pub fn generate() -> Result<(), Error> {
 
     let dir = env::var_os("OUT_DIR").unwrap();
     let path_1997 = Path::new(&dir).join("shift_jis_1997.rs");
-    generate_table(&path_1997, "SJIS_1997", Version::Since1997, &data)?;
+    generate_table(&path_1997, "SJIS_1997", Version::Since1997, &data, true)?;
     let path_2004 = Path::new(&dir).join("shift_jis_2004.rs");
-    generate_table(&path_2004, "SJIS_2004", Version::Since2004, &data)?;
+    generate_table(&path_2004, "SJIS_2004", Version::Since2004, &data, false)?;
+    let path_extensions = Path::new(&dir).join("shift_jis_extensions.rs");
+    generate_extensions(&path_extensions, &SHIFT_JIS_EXTENSIONS.replace('\t', " "))?;
 
     Ok(())
 }
@@ -0,0 +1,177 @@
+//! AFC audio streams (`.afc`).
+//!
+//! AFC is the 4-bit ADPCM variant of JAudio, used by JSystem titles (e.g.,
+//! The Legend of Zelda: The Wind Waker) for streamed tracks and by
+//! [AST][crate::ast] streams. Samples are encoded in frames of 9 bytes, a
+//! header (scale and coefficient index) and 16 4-bit samples, see
+//! [`decode_frame`]. Unlike [DSP-ADPCM][crate::dsp], the coefficients are
+//! fixed.
+//!
+//! A `.afc` file is a header followed by the ADPCM data of two channels,
+//! interleaved every frame.
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 4    | Size of the data                              |
+//! | `0x04` | 4    | Number of samples                             |
+//! | `0x08` | 2    | Sample rate                                   |
+//! | `0x0A` | 6    | Unknown                                       |
+//! | `0x10` | 4    | Loop flag                                     |
+//! | `0x14` | 4    | Loop start (sample)                           |
+//! | `0x18` | 8    | Padding                                       |
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("stream.afc")?;
+//!     let afc = picori::afc::Afc::from_binary(&mut file)?;
+//!     let samples = afc.decode()?;
+//!     println!("{} samples at {} Hz", samples.len(), afc.header.sample_rate);
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation};
+use crate::Result;
+
+/// Number of samples of an ADPCM frame.
+pub const SAMPLES_PER_FRAME: usize = 16;
+
+/// Size of an ADPCM frame.
+pub const BYTES_PER_FRAME: usize = 9;
+
+/// Number of channels of a `.afc` stream.
+pub const CHANNELS: usize = 2;
+
+/// Coefficients of the ADPCM frames.
+const COEFFICIENTS: [[i32; 2]; 16] = [
+    [0x0000, 0x0000],
+    [0x0800, 0x0000],
+    [0x0000, 0x0800],
+    [0x0400, 0x0400],
+    [0x1000, -0x0800],
+    [0x0E00, -0x0600],
+    [0x0C00, -0x0400],
+    [0x1200, -0x0A00],
+    [0x1068, -0x08C8],
+    [0x12C0, -0x08FC],
+    [0x1400, -0x0C00],
+    [0x0800, -0x0800],
+    [0x0400, -0x0400],
+    [-0x0400, 0x0400],
+    [-0x0400, 0x0000],
+    [-0x0800, 0x0000],
+];
+
+/// Decode the ADPCM `frame` (16 samples) with the `history` (previous and
+/// second previous sample) to `output`. Returns the history after the frame,
+/// i.e., the history to decode the next frame of the same channel.
+pub fn decode_frame(
+    frame: &[u8; BYTES_PER_FRAME],
+    history: [i16; 2],
+    output: &mut Vec<i16>,
+) -> [i16; 2] {
+    let scale = 1 << (frame[0] >> 4);
+    let [coefficient1, coefficient2] = COEFFICIENTS[(frame[0] & 0x0F) as usize];
+    let [mut hist1, mut hist2] = history.map(|x| x as i32);
+    for i in 0..SAMPLES_PER_FRAME {
+        let byte = frame[1 + i / 2];
+        let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+        let nibble = (((nibble << 4) as i8) >> 4) as i32;
+        let sample = ((nibble * scale) << 11) + coefficient1 * hist1 + coefficient2 * hist2;
+        let sample = (sample >> 11).clamp(-0x8000, 0x7FFF);
+        hist2 = hist1;
+        hist1 = sample;
+        output.push(sample as i16);
+    }
+    [hist1 as i16, hist2 as i16]
+}
+
+/// AFC header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Size of the data.
+    pub size: u32,
+
+    /// Number of samples (per channel).
+    pub sample_count: u32,
+
+    /// Sample rate.
+    pub sample_rate: u16,
+
+    /// Loop flag, `true` if the stream loops.
+    pub looping: bool,
+
+    /// Loop start (sample). The loop ends at the last sample.
+    pub loop_start: u32,
+}
+
+impl Header {
+    /// Parse AFC header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let size = input.bu32()?;
+        let sample_count = input.bu32()?;
+        let sample_rate = input.bu16()?;
+        let _unknown = input.u8_array::<6>()?;
+        let looping = input.bu32()? != 0;
+        let loop_start = input.bu32()?;
+        let _padding = input.u8_array::<8>()?;
+        ensure!(
+            (size as usize).is_multiple_of(BYTES_PER_FRAME * CHANNELS),
+            ParseProblem::InvalidHeader("AFC data size (not whole frames)", Location::current())
+        );
+
+        Ok(Self {
+            size,
+            sample_count,
+            sample_rate,
+            looping,
+            loop_start,
+        })
+    }
+}
+
+/// AFC audio stream (`.afc`).
+#[derive(Debug, Clone)]
+pub struct Afc {
+    /// Header.
+    pub header: Header,
+
+    /// ADPCM data (interleaved every frame).
+    pub data: Vec<u8>,
+}
+
+impl Afc {
+    /// Parse AFC stream from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let header = Header::from_binary(input)?;
+        let mut data = vec![0; header.size as usize];
+        input.read_into(&mut data)?;
+        Ok(Self { header, data })
+    }
+
+    /// Decode the stream to PCM16 samples (interleaved).
+    pub fn decode(&self) -> Result<Vec<i16>> {
+        let mut decoded = vec![Vec::new(); CHANNELS];
+        let mut history = [[0_i16; 2]; CHANNELS];
+        let (frames, _) = self.data.as_chunks::<BYTES_PER_FRAME>();
+        for (i, frame) in frames.iter().enumerate() {
+            let channel = i % CHANNELS;
+            history[channel] = decode_frame(frame, history[channel], &mut decoded[channel]);
+        }
+
+        let samples = self.header.sample_count as usize;
+        ensure!(
+            decoded.iter().all(|x| x.len() >= samples),
+            ParseProblem::InvalidRange("AFC sample count", Location::current())
+        );
+        Ok((0..samples)
+            .flat_map(|i| decoded.iter().map(move |x| x[i]))
+            .collect())
+    }
+}
@@ -3,15 +3,26 @@
 //! [ASCII][`Ascii`] is a 7-bit encoding designed for information interchange in
 //! English. Bytes with the eighth bit set are considered invalid and will cause
 //! an [`InvalidByte`][`crate::error::DecodingProblem::InvalidByte`] to be
-//! returned.
+//! returned. Encoding with [`Ascii::encode`] fails with an
+//! [`InvalidCharacter`][`crate::error::EncodingProblem::InvalidCharacter`] for
+//! characters above `0x7F`.
 
-use std::borrow::Borrow;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+#[cfg(feature = "std")]
 use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
 
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
 use crate::error::{DecodingProblem::*, EncodingProblem};
-use crate::helper::{ParseStringEncoding, Parser, ProblemLocation};
+use crate::helper::ProblemLocation;
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
 use crate::Result;
 
 /// [ASCII][`Ascii`] encoding.
@@ -23,7 +34,7 @@ where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter: <I as IntoIterator>::IntoIter,
+    iter: Tracked<<I as IntoIterator>::IntoIter>,
     _marker: PhantomData<&'x ()>,
 }
 
@@ -34,11 +45,21 @@ where
 {
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
-            iter: iter.into_iter(),
+            iter: Tracked::new(iter.into_iter()),
             _marker: PhantomData,
         }
     }
 
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
     fn decode_byte(byte: u8) -> Option<char> {
         match byte {
             // ASCII character
@@ -57,19 +78,46 @@ where
     type Item = Result<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.iter.offset();
         if let Some(byte) = self.iter.next() {
             let byte = byte.borrow();
-            Some(match Self::decode_byte(*byte) {
+            let result = match Self::decode_byte(*byte) {
                 Some(c) => Ok(c),
                 None => Err(InvalidByte(*byte, Location::current()).into()),
-            })
+            };
+            Some(self.iter.locate(offset, result))
         } else {
             None
         }
     }
 }
 
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize { self.iter.offset() }
+}
+
 impl Ascii {
+    /// Encode all characters of `data`. Fails with the first character that
+    /// is not [ASCII][`Ascii`] and its byte index in `data`. No NULL byte is
+    /// appended.
+    pub fn encode(data: &str) -> Result<Vec<u8>> {
+        data.char_indices()
+            .map(|(i, c)| Self::encode_char(c, i))
+            .collect()
+    }
+
+    fn encode_char(c: char, index: usize) -> Result<u8> {
+        if c.is_ascii() {
+            Ok(c as u8)
+        } else {
+            Err(EncodingProblem::InvalidCharacter(c, index, Location::current()).into())
+        }
+    }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -90,6 +138,12 @@ impl Ascii {
         Self::iter(iter).collect()
     }
 
+    /// Decode all bytes of `data` like [`Ascii::all`], but ASCII-only data is
+    /// borrowed without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, &[], |data| Self::all(data))
+    }
+
     /// Decode the first string (until a NULL character is reached) from the
     /// given iterator.
     pub fn first<I>(iter: I) -> Result<String>
@@ -104,6 +158,55 @@ impl Ascii {
             })
             .collect()
     }
+
+    /// Decode all bytes into a string like [`Ascii::all`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`Ascii::first`], but invalid bytes are
+    /// replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`Ascii::all`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`Ascii::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
 }
 
 /// Extension trait for iterators of bytes and adds the helper function
@@ -126,6 +229,7 @@ where
 {
 }
 
+#[cfg(feature = "std")]
 impl ParseStringEncoding for Ascii {
     fn parse_str<I>(iter: I) -> Result<String>
     where
@@ -137,17 +241,11 @@ impl ParseStringEncoding for Ascii {
 
     fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
         let mut i = 0;
-        for c in data.chars().peekable() {
+        for (index, c) in data.char_indices() {
             if i >= buffer.len() {
                 return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
             }
-            let u = c as u32;
-            if u > 0x7f {
-                return Err(
-                    EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into(),
-                );
-            }
-            buffer[i] = c as u8;
+            buffer[i] = Self::encode_char(c, index)?;
             i += 1;
         }
         Ok(i)
@@ -158,10 +256,7 @@ impl ParseStringEncoding for Ascii {
         let iter = buffer
             .bytes()
             .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
+            .filter_map(|x| x.ok());
         Self::first(iter)
     }
 }
@@ -179,4 +274,26 @@ mod tests {
         let data = b"abc\0def";
         assert_eq!(Ascii::parse_str(data).unwrap(), "abc".to_string());
     }
+
+    #[test]
+    fn lossy() {
+        assert_eq!(Ascii::all_lossy(b"a\x80b\0c"), "a\u{fffd}b\0c");
+        assert_eq!(Ascii::first_lossy(b"a\x80b\0c"), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(Ascii::encode("abc\0~").unwrap(), b"abc\0~");
+        match Ascii::encode("abé!") {
+            Err(crate::Error::Encoding(EncodingProblem::InvalidCharacter(c, index, _))) => {
+                assert_eq!(c, 'é');
+                assert_eq!(index, 2);
+            },
+            x => panic!("unexpected result: {x:?}"),
+        }
+
+        let mut buffer = [0; 4];
+        assert_eq!(Ascii::write_str("abc", &mut buffer).unwrap(), 3);
+        assert!(Ascii::write_str("aé", &mut buffer).is_err());
+    }
 }
@@ -0,0 +1,270 @@
+//! ASH0 decompression.
+//!
+//! ASH0 is used by the Wii system menu and some channels. The header holds
+//! the magic "ASH0", the decompressed size, and the offset of the second bit
+//! stream. Both bit streams are big-endian 32-bit words read from the most
+//! significant bit. The first stream starts with the Huffman tree of the
+//! 9-bit symbols (literal bytes and lengths of back-references) followed by
+//! the symbols, the second with the Huffman tree of the 11-bit distances
+//! followed by the distances.
+//!
+//! ## Decompression
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("data.ash")?;
+//!     let header = picori::ash::Header::from_binary(&mut file)?;
+//!     let decompressed = picori::ash::decompress(&mut file, &header)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::compression::Decompress;
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// ASH0 magic number representing the four characters "ASH0".
+pub(crate) const MAGIC: u32 = 0x41534830;
+
+/// Number of bits of a symbol.
+const SYMBOL_BITS: u32 = 9;
+
+/// Number of bits of a distance.
+const DISTANCE_BITS: u32 = 11;
+
+/// ASH0 header.
+pub struct Header {
+    /// ASH0 magic (0x41534830).
+    pub magic: u32,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+    /// Offset of the distance bit stream (relative to the header).
+    pub distance_offset: u32,
+}
+
+impl Header {
+    /// Reads an ASH0 header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        Ok(Header {
+            magic: input.bu32()?,
+            decompressed_size: input.bu32()? & 0x00FFFFFF,
+            distance_offset: input.bu32()?,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.magic == MAGIC && self.distance_offset >= 0xC }
+}
+
+/// Bit stream of big-endian 32-bit words.
+struct BitReader {
+    offset: u64,
+    word:   u32,
+    bits:   u32,
+}
+
+impl BitReader {
+    fn new(offset: u64) -> Self {
+        Self {
+            offset,
+            word: 0,
+            bits: 0,
+        }
+    }
+
+    fn bit<D: Parser + Seeker>(&mut self, input: &mut D) -> Result<u32> {
+        if self.bits == 0 {
+            input.goto(self.offset)?;
+            self.word = input.bu32()?;
+            self.offset += 4;
+            self.bits = 32;
+        }
+        let bit = self.word >> 31;
+        self.word <<= 1;
+        self.bits -= 1;
+        Ok(bit)
+    }
+
+    fn bits<D: Parser + Seeker>(&mut self, input: &mut D, count: u32) -> Result<u32> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = value << 1 | self.bit(input)?;
+        }
+        Ok(value)
+    }
+}
+
+/// Huffman tree with leaves of `width` bits. Internal nodes are numbered
+/// from `1 << width`, so values below are leaves.
+struct Tree {
+    width: u32,
+    root:  usize,
+    left:  Vec<usize>,
+    right: Vec<usize>,
+}
+
+impl Tree {
+    /// Reads the tree in pre-order: a set bit is an internal node followed by
+    /// its left and right subtree, a cleared bit is a leaf followed by its
+    /// value.
+    fn read<D: Parser + Seeker>(input: &mut D, stream: &mut BitReader, width: u32) -> Result<Self> {
+        let leaves = 1_usize << width;
+        let mut tree = Self {
+            width,
+            root: 0,
+            left: vec![0; leaves],
+            right: vec![0; leaves],
+        };
+
+        // (node, whether the left child is set) of the unfinished nodes
+        let mut stack: Vec<(usize, bool)> = Vec::new();
+        loop {
+            if stream.bit(input)? == 1 {
+                ensure!(
+                    tree.left.len() < 2 * leaves,
+                    InvalidData("Too many tree nodes", Location::current())
+                );
+                stack.push((tree.left.len(), false));
+                tree.left.push(0);
+                tree.right.push(0);
+                continue;
+            }
+            let mut value = stream.bits(input, width)? as usize;
+
+            // attach the subtree to the innermost unfinished node
+            loop {
+                match stack.pop() {
+                    None => {
+                        tree.root = value;
+                        return Ok(tree);
+                    },
+                    Some((node, false)) => {
+                        tree.left[node] = value;
+                        stack.push((node, true));
+                        break;
+                    },
+                    Some((node, true)) => {
+                        tree.right[node] = value;
+                        value = node;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Reads the next value: a set bit selects the right child.
+    fn decode<D: Parser + Seeker>(&self, input: &mut D, stream: &mut BitReader) -> Result<usize> {
+        let mut node = self.root;
+        while node >= 1 << self.width {
+            node = if stream.bit(input)? == 1 {
+                self.right[node]
+            } else {
+                self.left[node]
+            };
+        }
+        Ok(node)
+    }
+}
+
+/// Check if the given data is compressed with ASH0 by looking for the ASH0
+/// magic.
+pub fn is_ash<D: Parser + Seeker>(input: &mut D) -> bool {
+    let mut check = || -> Result<bool> {
+        let base = input.position()?;
+        let header = Header::from_binary(input)?;
+        input.goto(base)?;
+        Ok(header.is_valid())
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Decompresses the data into a new allocated [`Vec`]. `input` must be
+/// positioned directly after the `header`.
+pub fn decompress<D: Parser + Seeker>(input: &mut D, header: &Header) -> Result<Vec<u8>> {
+    let mut output = vec![0; header.decompressed_size as usize];
+    decompress_into(input, header, output.as_mut_slice())?;
+    Ok(output)
+}
+
+/// Decompresses the data into the beginning of the given buffer, which must
+/// be large enough to hold the decompressed data. `input` must be positioned
+/// directly after the `header`. Returns the number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(
+    input: &mut D,
+    header: &Header,
+    destination: &mut [u8],
+) -> Result<usize> {
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid magic", Location::current())
+    );
+
+    let written = header.decompressed_size as usize;
+    ensure!(
+        destination.len() >= written,
+        InvalidDecompressedSize(Location::current())
+    );
+    let destination = &mut destination[..written];
+
+    // offsets are relative to the start of the header
+    let base = input.position()? - 0xC;
+    let mut symbols = BitReader::new(base + 0xC);
+    let mut distances = BitReader::new(base + header.distance_offset as u64);
+    let symbol_tree = Tree::read(input, &mut symbols, SYMBOL_BITS)?;
+    let distance_tree = Tree::read(input, &mut distances, DISTANCE_BITS)?;
+
+    let size = destination.len();
+    let mut dest = 0;
+    while dest < size {
+        let symbol = symbol_tree.decode(input, &mut symbols)?;
+        if symbol < 0x100 {
+            destination[dest] = symbol as u8;
+            dest += 1;
+        } else {
+            let length = symbol - 0x100 + 3;
+            let offset = distance_tree.decode(input, &mut distances)?;
+            ensure!(offset < dest, UnexpectedEndOfData(Location::current()));
+            ensure!(
+                dest + length <= size,
+                InvalidDecompressedSize(Location::current())
+            );
+            let base = dest - (offset + 1);
+            for n in 0..length {
+                destination[dest] = destination[base + n];
+                dest += 1;
+            }
+        }
+    }
+
+    Ok(written)
+}
+
+/// ASH0 codec, see [`crate::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ash;
+
+impl Decompress for Ash {
+    const NAME: &'static str = "ASH0";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_ash(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let header = Header::from_binary(input)?;
+        decompress(input, &header)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let header = Header::from_binary(input)?;
+        decompress_into(input, &header, destination)
+    }
+}
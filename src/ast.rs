@@ -0,0 +1,236 @@
+//! AST audio streams (`.ast`).
+//!
+//! AST is the streamed music format of JAudio, used by first-party GameCube and
+//! Wii games. A stream is a header followed by blocks (`BLCK`), and every
+//! block contains the data of each channel one after another (block
+//! interleaved). The samples are either 16-bit PCM or 4-bit [AFC][crate::afc]
+//! ADPCM.
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 4    | Magic (`STRM`)                                |
+//! | `0x04` | 4    | Size of all blocks                            |
+//! | `0x08` | 2    | Format (`0` for ADPCM, `1` for PCM16)         |
+//! | `0x0A` | 2    | Bits per sample                               |
+//! | `0x0C` | 2    | Number of channels                            |
+//! | `0x0E` | 2    | Loop flag (`0xFFFF` if the stream loops)      |
+//! | `0x10` | 4    | Sample rate                                   |
+//! | `0x14` | 4    | Number of samples                             |
+//! | `0x18` | 4    | Loop start (sample)                           |
+//! | `0x1C` | 4    | Loop end (sample)                             |
+//! | `0x20` | 4    | Block size (per channel)                      |
+//! | `0x24` | 28   | Unknown                                       |
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("stream.ast")?;
+//!     let ast = picori::ast::Ast::from_binary(&mut file)?;
+//!     let samples = ast.decode()?;
+//!     if ast.header.looping {
+//!         println!("loop {}..{}", ast.header.loop_start, ast.header.loop_end);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation};
+use crate::{afc, Result};
+
+/// Magic of AST streams (`STRM`).
+pub const MAGIC: u32 = 0x5354524D;
+
+/// Magic of AST blocks (`BLCK`).
+pub const BLOCK_MAGIC: u32 = 0x424C434B;
+
+/// Sample format of an AST stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 4-bit [AFC][crate::afc] ADPCM.
+    Adpcm,
+
+    /// 16-bit PCM (big-endian).
+    Pcm16,
+}
+
+/// AST header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Size of all blocks.
+    pub size: u32,
+
+    /// Sample format.
+    pub format: Format,
+
+    /// Bits per sample.
+    pub bits_per_sample: u16,
+
+    /// Number of channels.
+    pub channels: u16,
+
+    /// Loop flag, `true` if the stream loops.
+    pub looping: bool,
+
+    /// Sample rate.
+    pub sample_rate: u32,
+
+    /// Number of samples (per channel).
+    pub sample_count: u32,
+
+    /// Loop start (sample).
+    pub loop_start: u32,
+
+    /// Loop end (sample).
+    pub loop_end: u32,
+
+    /// Size of a block (per channel), except the last block.
+    pub block_size: u32,
+}
+
+impl Header {
+    /// Parse AST header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let magic = input.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected STRM", Location::current())
+        );
+
+        let size = input.bu32()?;
+        let format = match input.bu16()? {
+            0 => Format::Adpcm,
+            1 => Format::Pcm16,
+            _ => Err(ParseProblem::InvalidData(
+                "unknown AST format",
+                Location::current(),
+            ))?,
+        };
+        let bits_per_sample = input.bu16()?;
+        let channels = input.bu16()?;
+        ensure!(
+            channels > 0,
+            ParseProblem::InvalidRange("AST channels", Location::current())
+        );
+        let looping = input.bu16()? == 0xFFFF;
+        let sample_rate = input.bu32()?;
+        let sample_count = input.bu32()?;
+        let loop_start = input.bu32()?;
+        let loop_end = input.bu32()?;
+        let block_size = input.bu32()?;
+        let _unknown = input.u8_array::<0x1C>()?;
+
+        Ok(Self {
+            size,
+            format,
+            bits_per_sample,
+            channels,
+            looping,
+            sample_rate,
+            sample_count,
+            loop_start,
+            loop_end,
+            block_size,
+        })
+    }
+}
+
+/// Block of an AST stream.
+#[derive(Debug, Clone)]
+pub struct Block {
+    /// Data of each channel.
+    pub channels: Vec<Vec<u8>>,
+}
+
+impl Block {
+    /// Parse AST block with `channels` channels.
+    pub fn from_binary<D: Parser>(input: &mut D, channels: usize) -> Result<Self> {
+        let magic = input.bu32()?;
+        ensure!(
+            magic == BLOCK_MAGIC,
+            ParseProblem::InvalidMagic("expected BLCK", Location::current())
+        );
+
+        let size = input.bu32()? as usize;
+        let _history = input.u8_array::<0x18>()?;
+        let channels = (0..channels)
+            .map(|_| {
+                let mut data = vec![0; size];
+                input.read_into(&mut data)?;
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { channels })
+    }
+}
+
+/// AST audio stream.
+#[derive(Debug, Clone)]
+pub struct Ast {
+    /// Header.
+    pub header: Header,
+
+    /// Blocks.
+    pub blocks: Vec<Block>,
+}
+
+impl Ast {
+    /// Parse AST stream from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let header = Header::from_binary(input)?;
+        let channels = header.channels as usize;
+
+        let mut blocks = Vec::new();
+        let mut remaining = header.size as usize;
+        while remaining > 0 {
+            let block = Block::from_binary(input, channels)?;
+            let size = 0x20 + block.channels[0].len() * channels;
+            ensure!(
+                size <= remaining && !block.channels[0].is_empty(),
+                ParseProblem::InvalidRange("AST block size", Location::current())
+            );
+            remaining -= size;
+            blocks.push(block);
+        }
+
+        Ok(Self { header, blocks })
+    }
+
+    /// Decode the stream to PCM16 samples (interleaved).
+    pub fn decode(&self) -> Result<Vec<i16>> {
+        let channels = self.header.channels as usize;
+        let mut decoded = vec![Vec::new(); channels];
+        let mut history = vec![[0_i16; 2]; channels];
+        for block in self.blocks.iter() {
+            for (channel, data) in block.channels.iter().enumerate() {
+                let output = &mut decoded[channel];
+                match self.header.format {
+                    Format::Adpcm => {
+                        let (frames, _) = data.as_chunks::<{ afc::BYTES_PER_FRAME }>();
+                        for frame in frames {
+                            history[channel] = afc::decode_frame(frame, history[channel], output);
+                        }
+                    },
+                    Format::Pcm16 => output.extend(
+                        data.chunks_exact(2)
+                            .map(|x| i16::from_be_bytes([x[0], x[1]])),
+                    ),
+                }
+            }
+        }
+
+        let samples = self.header.sample_count as usize;
+        ensure!(
+            decoded.iter().all(|x| x.len() >= samples),
+            ParseProblem::InvalidRange("AST sample count", Location::current())
+        );
+        Ok((0..samples)
+            .flat_map(|i| decoded.iter().map(move |x| x[i]))
+            .collect())
+    }
+}
@@ -0,0 +1,463 @@
+//! BCSV (`JMapInfo`) tables (`.bcsv`, `.tbl`).
+//!
+//! BCSV is the table format of JSystem games (`JMapInfo`), e.g., the stage and
+//! object parameters of Super Mario Galaxy. A table is a header, the field
+//! descriptions, the entries (rows) with a fixed size (the stride), and a
+//! string table.
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 4    | Number of entries                             |
+//! | `0x04` | 4    | Number of fields                              |
+//! | `0x08` | 4    | Offset of the entries                         |
+//! | `0x0C` | 4    | Size of an entry (stride)                     |
+//! | `0x10` | 12   | Fields (name hash, mask, offset, shift, type) |
+//!
+//! Fields are identified by the [hash][`hash`] of their name, the names
+//! themselves are not stored. The value of a field is read at its offset in
+//! the entry, masked and shifted (integers), see [`Bcsv::get`] and [`Row`].
+//! Tables are written with [`Bcsv::to_binary`], new fields are added with
+//! [`Bcsv::add_field`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::encoding::Encoding;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("scenariodata.bcsv")?;
+//!     let table = picori::bcsv::Bcsv::from_binary(&mut file, Encoding::ShiftJis1997)?;
+//!     for row in table.rows() {
+//!         println!("{:?}", row.get_by_name("ScenarioName"));
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::panic::Location;
+
+use crate::encoding::Encoding;
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::Result;
+
+/// Hash of the field name `name` (as used by Super Mario Galaxy).
+pub fn hash(name: &str) -> u32 {
+    name.bytes().fold(0_u32, |hash, c| {
+        hash.wrapping_mul(31).wrapping_add(c as i8 as u32)
+    })
+}
+
+/// Hash of the field name `name` as used by older `JMapInfo` tables, e.g., of
+/// Luigi's Mansion.
+pub fn legacy_hash(name: &str) -> u32 {
+    name.bytes()
+        .fold(0_u64, |hash, c| ((hash << 8) + c as u64) % 33554393) as u32
+}
+
+/// Type of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    /// 32-bit integer.
+    Int,
+
+    /// String embedded in the entry (32 bytes).
+    EmbeddedString,
+
+    /// 32-bit float.
+    Float,
+
+    /// 32-bit integer (second variant).
+    Int2,
+
+    /// 16-bit integer.
+    Short,
+
+    /// 8-bit integer.
+    Char,
+
+    /// String in the string table (offset in the entry).
+    String,
+}
+
+impl FieldType {
+    /// Get the field type of the type ID `id`.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(FieldType::Int),
+            1 => Some(FieldType::EmbeddedString),
+            2 => Some(FieldType::Float),
+            3 => Some(FieldType::Int2),
+            4 => Some(FieldType::Short),
+            5 => Some(FieldType::Char),
+            6 => Some(FieldType::String),
+            _ => None,
+        }
+    }
+
+    /// Get the type ID of the field type.
+    pub fn id(&self) -> u8 {
+        match self {
+            FieldType::Int => 0,
+            FieldType::EmbeddedString => 1,
+            FieldType::Float => 2,
+            FieldType::Int2 => 3,
+            FieldType::Short => 4,
+            FieldType::Char => 5,
+            FieldType::String => 6,
+        }
+    }
+
+    /// Number of bytes of a value in the entry.
+    pub fn size(&self) -> usize {
+        match self {
+            FieldType::EmbeddedString => 32,
+            FieldType::Short => 2,
+            FieldType::Char => 1,
+            _ => 4,
+        }
+    }
+}
+
+/// Field (column) of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// Hash of the field name, see [`hash`].
+    pub hash: u32,
+
+    /// Mask of the value (integers).
+    pub mask: u32,
+
+    /// Offset of the value in the entry.
+    pub offset: u16,
+
+    /// Shift of the value (integers), applied after the mask.
+    pub shift: u8,
+
+    /// Type.
+    pub kind: FieldType,
+}
+
+/// Value of a field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Integer ([`FieldType::Int`] and [`FieldType::Int2`]).
+    Int(i32),
+
+    /// Float.
+    Float(f32),
+
+    /// 16-bit integer.
+    Short(i16),
+
+    /// 8-bit integer.
+    Char(u8),
+
+    /// String ([`FieldType::String`] and [`FieldType::EmbeddedString`]).
+    String(String),
+}
+
+impl Value {
+    /// Get the value as an integer, [`None`] for floats and strings.
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Value::Int(value) => Some(*value),
+            Value::Short(value) => Some(*value as i32),
+            Value::Char(value) => Some(*value as i32),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a float, [`None`] if the value is not a float.
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a string, [`None`] if the value is not a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// BCSV table.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bcsv {
+    /// Fields (columns).
+    pub fields: Vec<Field>,
+
+    /// Entries (rows), the value of each field.
+    pub entries: Vec<Vec<Value>>,
+
+    /// Size of an entry (stride).
+    pub entry_size: u32,
+}
+
+/// Row of a [`Bcsv`] table, the values of an entry with access by field.
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'x> {
+    fields: &'x [Field],
+    values: &'x [Value],
+}
+
+impl<'x> Row<'x> {
+    /// Get the value of the field with the name hash `hash`.
+    pub fn get(&self, hash: u32) -> Option<&'x Value> {
+        let index = self.fields.iter().position(|x| x.hash == hash)?;
+        self.values.get(index)
+    }
+
+    /// Get the value of the field `name`, see [`hash`].
+    pub fn get_by_name(&self, name: &str) -> Option<&'x Value> { self.get(hash(name)) }
+
+    /// Get the values (in the order of the fields).
+    pub fn values(&self) -> &'x [Value] { self.values }
+}
+
+impl Bcsv {
+    /// Parse BCSV table from binary stream, strings are decoded with
+    /// `encoding` (usually [Shift JIS 1997][`Encoding::ShiftJis1997`]). The
+    /// string table extends to the end of `input`.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D, encoding: Encoding) -> Result<Self> {
+        let base = input.position()?;
+        let [entry_count, field_count, data_offset, entry_size] = input.bu32_array::<4>()?;
+
+        let mut fields = Vec::with_capacity(field_count.min(0x1000) as usize);
+        for _ in 0..field_count {
+            let hash = input.bu32()?;
+            let mask = input.bu32()?;
+            let offset = input.bu16()?;
+            let shift = input.u8()?;
+            let kind = FieldType::from_id(input.u8()?).ok_or(ParseProblem::InvalidData(
+                "unknown BCSV field type",
+                Location::current(),
+            ))?;
+            ensure!(
+                offset as usize + kind.size() <= entry_size as usize,
+                ParseProblem::InvalidRange("BCSV field (outside of entry)", Location::current())
+            );
+            ensure!(
+                shift < 32,
+                ParseProblem::InvalidData("BCSV field shift (too large)", Location::current())
+            );
+            fields.push(Field {
+                hash,
+                mask,
+                offset,
+                shift,
+                kind,
+            });
+        }
+
+        input.goto(base + data_offset as u64)?;
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+        let size = (entry_count as usize)
+            .checked_mul(entry_size as usize)
+            .filter(|x| *x <= data.len() && (entry_size > 0 || entry_count == 0))
+            .ok_or(ParseProblem::InvalidRange(
+                "BCSV entries (outside of input)",
+                Location::current(),
+            ))?;
+        let strings = data.split_off(size);
+
+        let entries = (0..entry_count as usize)
+            .map(|index| {
+                let entry = &data[index * entry_size as usize..][..entry_size as usize];
+                fields
+                    .iter()
+                    .map(|field| Self::value(field, entry, &strings, encoding))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            fields,
+            entries,
+            entry_size,
+        })
+    }
+
+    /// Read the value of `field` from `entry`.
+    fn value(field: &Field, entry: &[u8], strings: &[u8], encoding: Encoding) -> Result<Value> {
+        let data = &entry[field.offset as usize..][..field.kind.size()];
+        let integer = || {
+            let raw = data.iter().fold(0_u32, |x, y| (x << 8) | *y as u32);
+            (raw & field.mask).checked_shr(field.shift as u32).unwrap_or(0)
+        };
+        Ok(match field.kind {
+            FieldType::Int | FieldType::Int2 => Value::Int(integer() as i32),
+            FieldType::Short => Value::Short(integer() as i16),
+            FieldType::Char => Value::Char(integer() as u8),
+            FieldType::Float => {
+                Value::Float(f32::from_bits(u32::from_be_bytes(data.try_into().unwrap())))
+            },
+            FieldType::EmbeddedString => Value::String(encoding.first(data)?),
+            FieldType::String => {
+                let offset = u32::from_be_bytes(data.try_into().unwrap()) as usize;
+                let string = strings.get(offset..).ok_or(ParseProblem::InvalidRange(
+                    "BCSV string offset (outside of string table)",
+                    Location::current(),
+                ))?;
+                Value::String(encoding.first(string)?)
+            },
+        })
+    }
+
+    /// Get the index of the field with the name hash `hash`.
+    pub fn field(&self, hash: u32) -> Option<usize> {
+        self.fields.iter().position(|x| x.hash == hash)
+    }
+
+    /// Get the row `index`.
+    pub fn row(&self, index: usize) -> Option<Row<'_>> {
+        self.entries.get(index).map(|values| Row {
+            fields: &self.fields,
+            values,
+        })
+    }
+
+    /// Iterate over all rows.
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.entries.iter().map(|values| Row {
+            fields: &self.fields,
+            values,
+        })
+    }
+
+    /// Get the value of the field with the name hash `hash` of the row `index`.
+    pub fn get(&self, index: usize, hash: u32) -> Option<&Value> { self.row(index)?.get(hash) }
+
+    /// Create an empty table.
+    pub fn new() -> Self { Self::default() }
+
+    /// Add the field `name` of type `kind` after the other fields (aligned to
+    /// its size) and grow the entry size. Existing entries get the default
+    /// value of the field. Returns the index of the field.
+    pub fn add_field(&mut self, name: &str, kind: FieldType) -> usize {
+        let size = kind.size().min(4);
+        let offset = self.stride().next_multiple_of(size as u32);
+        let mask = match kind {
+            FieldType::Short => 0xFFFF,
+            FieldType::Char => 0xFF,
+            _ => 0xFFFFFFFF,
+        };
+        self.fields.push(Field {
+            hash: hash(name),
+            mask,
+            offset: offset as u16,
+            shift: 0,
+            kind,
+        });
+        self.entry_size = self.entry_size.max(self.stride());
+
+        let value = match kind {
+            FieldType::Int | FieldType::Int2 => Value::Int(0),
+            FieldType::Float => Value::Float(0.0),
+            FieldType::Short => Value::Short(0),
+            FieldType::Char => Value::Char(0),
+            FieldType::String | FieldType::EmbeddedString => Value::String(String::new()),
+        };
+        self.entries.iter_mut().for_each(|x| x.push(value.clone()));
+        self.fields.len() - 1
+    }
+
+    /// Get the smallest entry size (stride) containing all fields, aligned to
+    /// 4 bytes.
+    pub fn stride(&self) -> u32 {
+        self.fields
+            .iter()
+            .map(|x| (x.offset as usize + x.kind.size()) as u32)
+            .max()
+            .unwrap_or(0)
+            .next_multiple_of(4)
+    }
+
+    /// Write BCSV table to binary stream, strings are encoded with `encoding`.
+    /// The entry size is the [stride][`Bcsv::stride`] unless
+    /// [`Bcsv::entry_size`] is larger. Strings of the string table are
+    /// deduplicated.
+    pub fn to_binary<W: Writer>(&self, output: &mut W, encoding: Encoding) -> Result<()> {
+        let entry_size = self.entry_size.max(self.stride()) as usize;
+        let mut data = vec![0; self.entries.len() * entry_size];
+        let mut strings = Vec::new();
+        let mut offsets = HashMap::new();
+        for (entry, values) in data.chunks_exact_mut(entry_size).zip(self.entries.iter()) {
+            ensure!(
+                values.len() == self.fields.len(),
+                BuildProblem::InvalidData("BCSV entry (number of values)", Location::current())
+            );
+            for (field, value) in self.fields.iter().zip(values.iter()) {
+                let data = &mut entry[field.offset as usize..][..field.kind.size()];
+                let integer = match (field.kind, value) {
+                    (FieldType::Int | FieldType::Int2, Value::Int(x)) => *x as u32,
+                    (FieldType::Short, Value::Short(x)) => *x as u16 as u32,
+                    (FieldType::Char, Value::Char(x)) => *x as u32,
+                    (FieldType::Float, Value::Float(x)) => {
+                        data.copy_from_slice(&x.to_bits().to_be_bytes());
+                        continue;
+                    },
+                    (FieldType::EmbeddedString, Value::String(x)) => {
+                        let encoded = encoding.encode_into(data, x)?;
+                        ensure!(
+                            encoded == x.len(),
+                            BuildProblem::InvalidData(
+                                "BCSV embedded string does not fit",
+                                Location::current()
+                            )
+                        );
+                        continue;
+                    },
+                    (FieldType::String, Value::String(x)) => {
+                        let offset = match offsets.get(x) {
+                            Some(offset) => *offset,
+                            None => {
+                                let offset = strings.len() as u32;
+                                strings.extend(encoding.encode(x)?);
+                                strings.push(0);
+                                offsets.insert(x, offset);
+                                offset
+                            },
+                        };
+                        data.copy_from_slice(&offset.to_be_bytes());
+                        continue;
+                    },
+                    _ => Err(BuildProblem::InvalidData(
+                        "BCSV value does not match the field type",
+                        Location::current(),
+                    ))?,
+                };
+
+                // fields can share bits of the same integer
+                let raw = data.iter().fold(0_u32, |x, y| (x << 8) | *y as u32);
+                let integer = integer.checked_shl(field.shift as u32).unwrap_or(0);
+                let raw = (raw & !field.mask) | (integer & field.mask);
+                let size = data.len();
+                data.copy_from_slice(&raw.to_be_bytes()[4 - size..]);
+            }
+        }
+
+        let data_offset = 0x10 + self.fields.len() * 12;
+        output.bu32(self.entries.len() as u32)?;
+        output.bu32(self.fields.len() as u32)?;
+        output.bu32(data_offset as u32)?;
+        output.bu32(entry_size as u32)?;
+        for field in self.fields.iter() {
+            output.bu32(field.hash)?;
+            output.bu32(field.mask)?;
+            output.bu16(field.offset)?;
+            output.u8(field.shift)?;
+            output.u8(field.kind.id())?;
+        }
+        output.u8_array(&data)?;
+        output.u8_array(&strings)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,418 @@
+//! BLO screen layouts (`.blo`).
+//!
+//! BLO (`SCRN`) is the 2D screen layout format of JSystem (`J2DScreen`), used
+//! for the menus and HUDs of, e.g., The Wind Waker and Super Mario Sunshine.
+//! The fonts (BFN) and textures ([BTI][crate::j3d::Texture]) are separate
+//! files referenced by name. A layout (`blo1`) consists of sections:
+//!
+//! * `INF1` - [Screen][`Screen`] size and background color.
+//! * `PAN1`, `PIC1`, `WIN1`, and `TBX1` - [Panes][`Pane`] (null, picture,
+//!   window, and text box panes). The children of a pane are enclosed in `BGN1`
+//!   and `END1`.
+//! * `EXT1` - End of the layout.
+//!
+//! Use [`Blo::resources`] to list the textures, palettes, and fonts used by
+//! the layout.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("main.blo")?;
+//!     let blo = picori::blo::Blo::from_binary(&mut file)?;
+//!     for resource in blo.resources() {
+//!         println!("{}", resource.name);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::{Result, ShiftJis1997};
+
+/// [BLO][`crate::blo`] magic (`SCRN`).
+pub const MAGIC: [u8; 4] = *b"SCRN";
+
+/// Type of a [BLO][`crate::blo`] layout (`blo1`).
+pub const KIND: [u8; 4] = *b"blo1";
+
+/// Size of the header.
+pub const HEADER_SIZE: u64 = 0x20;
+
+/// Screen size and background color (`INF1`).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Screen {
+    /// Width.
+    pub width: u16,
+
+    /// Height.
+    pub height: u16,
+
+    /// Background color (RGBA).
+    pub color: [u8; 4],
+}
+
+/// Reference to a resource (texture, palette, or font) by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// Kind of the reference, `0` for none, `1` to `4` select where the
+    /// resource is searched (e.g., the archive of the layout).
+    pub kind: u8,
+
+    /// Name (path) of the resource, e.g., `font/rock_24_20_4i_usa.bfn`.
+    pub name: String,
+}
+
+impl Resource {
+    /// Returns `true` if the reference is empty.
+    pub fn is_none(&self) -> bool { self.kind == 0 }
+
+    fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let kind = input.u8()?;
+        let length = input.u8()? as usize;
+        let name = ShiftJis1997::first(input.read_as_vec(length)?)?;
+        Ok(Self { kind, name })
+    }
+}
+
+/// Picture pane (`PIC1`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    /// Texture.
+    pub texture: Resource,
+
+    /// Palette (for color indexed textures).
+    pub palette: Resource,
+
+    /// Binding (the edges of the texture bound to the edges of the pane).
+    pub binding: u8,
+}
+
+/// Window pane (`WIN1`), a frame of four corner textures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    /// Content rectangle (X, Y, width, and height).
+    pub content: [i16; 4],
+
+    /// Textures of the corners (top left, top right, bottom left, and bottom
+    /// right).
+    pub textures: [Resource; 4],
+
+    /// Palette (for color indexed textures).
+    pub palette: Resource,
+}
+
+/// Text box pane (`TBX1`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextBox {
+    /// Font.
+    pub font: Resource,
+
+    /// Colors of the top and the bottom of the characters (RGBA).
+    pub colors: [[u8; 4]; 2],
+
+    /// Horizontal and vertical binding.
+    pub binding: [u8; 2],
+
+    /// Character spacing.
+    pub char_spacing: i16,
+
+    /// Line spacing.
+    pub line_spacing: i16,
+
+    /// Font size (width and height).
+    pub font_size: [u16; 2],
+
+    /// Text.
+    pub text: String,
+}
+
+/// Kind of a [`Pane`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaneKind {
+    /// Null pane (`PAN1`), groups other panes.
+    Null,
+
+    /// Picture pane (`PIC1`).
+    Picture(Picture),
+
+    /// Window pane (`WIN1`).
+    Window(Window),
+
+    /// Text box pane (`TBX1`).
+    TextBox(TextBox),
+}
+
+/// Pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pane {
+    /// Kind of the pane.
+    pub kind: PaneKind,
+
+    /// Tag (name) of the pane, e.g., `ti_0`.
+    pub tag: [u8; 4],
+
+    /// Visibility.
+    pub visible: bool,
+
+    /// Position (X and Y).
+    pub position: [i16; 2],
+
+    /// Size (width and height).
+    pub size: [i16; 2],
+
+    /// Rotation (in degrees), `0` if not stored.
+    pub rotation: u16,
+
+    /// Origin of the rotation (0 to 8, top left to bottom right).
+    pub anchor: u8,
+
+    /// Alpha, `0xFF` if not stored.
+    pub alpha: u8,
+
+    /// Multiply the alpha with the alpha of the parent, `true` if not stored.
+    pub inherit_alpha: bool,
+
+    /// Children.
+    pub children: Vec<Pane>,
+}
+
+impl Pane {
+    /// Find the pane with the tag `tag` (this pane or a descendant).
+    pub fn find(&self, tag: &[u8; 4]) -> Option<&Pane> {
+        match &self.tag == tag {
+            true => Some(self),
+            false => self.children.iter().find_map(|x| x.find(tag)),
+        }
+    }
+
+    /// Get the resources referenced by the pane (not by its children), empty
+    /// references are skipped.
+    pub fn resources(&self) -> impl Iterator<Item = &Resource> {
+        let resources: Vec<&Resource> = match &self.kind {
+            PaneKind::Null => vec![],
+            PaneKind::Picture(x) => vec![&x.texture, &x.palette],
+            PaneKind::Window(x) => x.textures.iter().chain([&x.palette]).collect(),
+            PaneKind::TextBox(x) => vec![&x.font],
+        };
+        resources.into_iter().filter(|x| !x.is_none())
+    }
+}
+
+/// BLO screen layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blo {
+    /// Screen size and background color.
+    pub screen: Screen,
+
+    /// Pane tree, the root panes.
+    pub panes: Vec<Pane>,
+}
+
+impl Blo {
+    /// Parse BLO screen layout from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: SCRN", Location::current())
+        );
+        let kind = input.u8_array::<4>()?;
+        ensure!(
+            kind == KIND,
+            ParseProblem::InvalidHeader("expected: blo1", Location::current())
+        );
+        let _file_size = input.bu32()?;
+        let section_count = input.bu32()?;
+
+        let mut screen = Screen::default();
+        // the children of the open panes, the last pane is the parent of the
+        // next `BGN1`
+        let mut panes = vec![Vec::<Pane>::new()];
+        let mut offset = base + HEADER_SIZE;
+        for _ in 0..section_count {
+            input.goto(offset)?;
+            let magic = input.u8_array::<4>()?;
+            let size = input.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidHeader("BLO section size", Location::current())
+            );
+            input.goto(offset)?;
+            let data = input.read_as_vec(size as usize)?;
+            match &magic {
+                b"INF1" => {
+                    let mut input = Cursor::new(&data[8..]);
+                    screen = Screen {
+                        width:  input.bu16()?,
+                        height: input.bu16()?,
+                        color:  input.u8_array::<4>()?,
+                    };
+                },
+                b"PAN1" | b"PIC1" | b"WIN1" | b"TBX1" => {
+                    let pane = Self::pane_from(&magic, &data)?;
+                    panes.last_mut().unwrap().push(pane);
+                },
+                b"BGN1" => {
+                    ensure!(
+                        !panes.last().unwrap().is_empty(),
+                        ParseProblem::InvalidData(
+                            "BLO hierarchy (unbalanced)",
+                            Location::current()
+                        )
+                    );
+                    panes.push(Vec::new());
+                },
+                b"END1" => {
+                    ensure!(
+                        panes.len() > 1,
+                        ParseProblem::InvalidData(
+                            "BLO hierarchy (unbalanced)",
+                            Location::current()
+                        )
+                    );
+                    let children = panes.pop().unwrap();
+                    panes.last_mut().unwrap().last_mut().unwrap().children = children;
+                },
+                b"EXT1" => break,
+                _ => Err(ParseProblem::InvalidData(
+                    "unknown BLO section",
+                    Location::current(),
+                ))?,
+            }
+            offset += size as u64;
+        }
+        ensure!(
+            panes.len() == 1,
+            ParseProblem::InvalidData("BLO hierarchy (unbalanced)", Location::current())
+        );
+
+        Ok(Self {
+            screen,
+            panes: panes.pop().unwrap(),
+        })
+    }
+
+    /// Find the pane with the tag `tag`.
+    pub fn pane(&self, tag: &[u8; 4]) -> Option<&Pane> {
+        self.panes.iter().find_map(|x| x.find(tag))
+    }
+
+    /// Get the resources referenced by all panes (in depth-first order),
+    /// empty references are skipped.
+    pub fn resources(&self) -> impl Iterator<Item = &Resource> {
+        let mut stack = self.panes.iter().rev().collect::<Vec<_>>();
+        std::iter::from_fn(move || {
+            let pane = stack.pop()?;
+            stack.extend(pane.children.iter().rev());
+            Some(pane)
+        })
+        .flat_map(|x| x.resources())
+    }
+
+    fn pane_from(magic: &[u8; 4], data: &[u8]) -> Result<Pane> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        // the number of parameters includes the visibility, the tag, the
+        // position, and the size, the remaining parameters are optional
+        let count = input.u8()?;
+        ensure!(
+            count >= 6,
+            ParseProblem::InvalidData("BLO pane (parameter count)", Location::current())
+        );
+        let visible = input.u8()? != 0;
+        let _padding = input.bu16()?;
+        let tag = input.u8_array::<4>()?;
+        let [x, y, width, height] = input.bu16_array::<4>()?.map(|x| x as i16);
+        let optional = (count - 6) as usize;
+        let rotation = match optional > 0 {
+            true => input.bu16()?,
+            false => 0,
+        };
+        let mut values = [0, 0xFF, 1];
+        for value in values.iter_mut().take(optional.saturating_sub(1)) {
+            *value = input.u8()?;
+        }
+        let [anchor, alpha, inherit_alpha] = values;
+        // the pane data is aligned to 4 bytes
+        let position = Seeker::position(&mut input)?;
+        input.goto(position.next_multiple_of(4))?;
+
+        let kind = match magic {
+            b"PIC1" => {
+                let _count = input.u8()?;
+                let texture = Resource::from_binary(&mut input)?;
+                let palette = Resource::from_binary(&mut input)?;
+                let binding = input.u8()?;
+                PaneKind::Picture(Picture {
+                    texture,
+                    palette,
+                    binding,
+                })
+            },
+            b"WIN1" => {
+                let _count = input.u8()?;
+                let content = input.bu16_array::<4>()?.map(|x| x as i16);
+                let textures = [
+                    Resource::from_binary(&mut input)?,
+                    Resource::from_binary(&mut input)?,
+                    Resource::from_binary(&mut input)?,
+                    Resource::from_binary(&mut input)?,
+                ];
+                let palette = Resource::from_binary(&mut input)?;
+                PaneKind::Window(Window {
+                    content,
+                    textures,
+                    palette,
+                })
+            },
+            b"TBX1" => {
+                let _count = input.u8()?;
+                let font = Resource::from_binary(&mut input)?;
+                let colors = [input.u8_array::<4>()?, input.u8_array::<4>()?];
+                let binding = [input.u8()?, input.u8()?];
+                let char_spacing = input.bu16()? as i16;
+                let line_spacing = input.bu16()? as i16;
+                let font_size = input.bu16_array::<2>()?;
+                let length = input.bu16()? as usize;
+                let start = Seeker::position(&mut input)? as usize;
+                let text = data
+                    .get(start..start + length)
+                    .ok_or(ParseProblem::InvalidRange(
+                        "BLO text (outside of section)",
+                        Location::current(),
+                    ))?;
+                let text = ShiftJis1997::first(text)?;
+                PaneKind::TextBox(TextBox {
+                    font,
+                    colors,
+                    binding,
+                    char_spacing,
+                    line_spacing,
+                    font_size,
+                    text,
+                })
+            },
+            _ => PaneKind::Null,
+        };
+
+        Ok(Pane {
+            kind,
+            tag,
+            visible,
+            position: [x, y],
+            size: [width, height],
+            rotation,
+            anchor,
+            alpha,
+            inherit_alpha: inherit_alpha != 0,
+            children: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,470 @@
+//! BMG message files (`.bmg`).
+//!
+//! BMG is the message format of JSystem and most later first-party games, the
+//! text of every dialog, menu, and sign. A file is a header followed by
+//! sections, each starting with a magic and its size:
+//!
+//! * `INF1` - Message table, the offset of each message in `DAT1` followed by
+//!   its attributes (e.g., the speaker, text box, or sound).
+//! * `DAT1` - Message text, NULL-terminated strings.
+//! * `MID1` - Message IDs (optional), see [`Bmg::find`].
+//!
+//! Other sections, e.g., the flow sections `FLW1` and `FLI1`, are preserved
+//! as [raw sections][`Section`], so a file survives
+//! [parsing][`Bmg::from_binary`] and [writing][`Bmg::to_binary`].
+//!
+//! The text is encoded with the encoding of the header (see
+//! [`Bmg::encoding_from_id`]) and contains escape sequences for control codes
+//! (e.g., colors, pauses, or the player name). An escape sequence starts with
+//! the character `0x1A`, followed by its size (in bytes, including the
+//! `0x1A` and the size), a group, a tag, and arguments. Messages are decoded
+//! into [tokens][`Token`], text and [escape sequences][`Escape`], so the
+//! escape sequences are preserved exactly.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("message.bmg")?;
+//!     let bmg = picori::bmg::Bmg::from_binary(&mut file)?;
+//!     for (index, message) in bmg.messages.iter().enumerate() {
+//!         println!("{}: {}", index, message.text());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Read;
+use std::panic::Location;
+
+use crate::encoding::Encoding;
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::Result;
+
+/// Magic of BMG files (`MESGbmg1`).
+pub const MAGIC: [u8; 8] = *b"MESGbmg1";
+
+/// Character starting an escape sequence.
+pub const ESCAPE: u16 = 0x1A;
+
+/// Escape sequence (control code) of a message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Escape {
+    /// Group of the escape sequence.
+    pub group: u8,
+
+    /// Tag of the escape sequence (in the group).
+    pub tag: u16,
+
+    /// Arguments.
+    pub arguments: Vec<u8>,
+}
+
+/// Token of a message, text or an escape sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Token {
+    /// Text.
+    Text(String),
+
+    /// Escape sequence.
+    Escape(Escape),
+}
+
+/// Message of a BMG file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Message {
+    /// Message ID (of the `MID1` section), [`None`] if the file has no `MID1`
+    /// section.
+    pub id: Option<u32>,
+
+    /// Attributes of the message (of the `INF1` entry after the offset).
+    pub attributes: Vec<u8>,
+
+    /// Text and escape sequences.
+    pub tokens: Vec<Token>,
+}
+
+impl Message {
+    /// Get the text of the message, without the escape sequences.
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .filter_map(|x| match x {
+                Token::Text(text) => Some(text.as_str()),
+                Token::Escape(_) => None,
+            })
+            .collect()
+    }
+
+    /// Encode the tokens of the message with `encoding` (without the NULL
+    /// character).
+    fn to_binary(&self, output: &mut Vec<u8>, encoding: Encoding) -> Result<()> {
+        for token in self.tokens.iter() {
+            match token {
+                Token::Text(text) => output.extend(encoding.encode(text)?),
+                Token::Escape(escape) => {
+                    let (marker, width): (&[u8], _) = match encoding {
+                        Encoding::Utf16Be => (&[0x00, 0x1A], 2),
+                        _ => (&[0x1A], 1),
+                    };
+                    let size = width + 4 + escape.arguments.len();
+                    ensure!(
+                        size <= u8::MAX as usize,
+                        BuildProblem::InvalidData(
+                            "BMG escape sequence (too large)",
+                            Location::current()
+                        )
+                    );
+                    output.extend_from_slice(marker);
+                    output.extend_from_slice(&[size as u8, escape.group]);
+                    output.extend_from_slice(&escape.tag.to_be_bytes());
+                    output.extend_from_slice(&escape.arguments);
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Section of a BMG file that is preserved as is, e.g., the flow sections
+/// `FLW1` and `FLI1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Section {
+    /// Magic.
+    pub magic: [u8; 4],
+
+    /// Data (after the magic and size, including the padding).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+}
+
+/// BMG message file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bmg {
+    /// Encoding of the text.
+    pub encoding: Encoding,
+
+    /// Encoding ID of the header, see [`Bmg::encoding_from_id`].
+    pub encoding_id: u8,
+
+    /// Group (file) ID of the `INF1` section.
+    pub group: u16,
+
+    /// Default color of the `INF1` section.
+    pub default_color: u8,
+
+    /// Size of the attributes of each message.
+    pub attribute_size: usize,
+
+    /// Messages (in the order of the `INF1` section).
+    pub messages: Vec<Message>,
+
+    /// Format of the `MID1` section (the 6 bytes after the number of IDs),
+    /// [`None`] if the file has no `MID1` section.
+    pub id_format: Option<[u8; 6]>,
+
+    /// Other sections, e.g., `FLW1` and `FLI1` (in the order of the file).
+    pub sections: Vec<Section>,
+}
+
+/// Number of bytes of the character starting at `data[0]`.
+fn char_size(encoding: Encoding, data: &[u8]) -> usize {
+    match (encoding, data[0]) {
+        (Encoding::Utf16Be, _) => 2,
+        (Encoding::ShiftJis1997 | Encoding::ShiftJis2004, 0x81..=0x9F | 0xE0..=0xFC) => 2,
+        (Encoding::EucJp, 0x8F) => 3,
+        (Encoding::EucJp, 0x8E | 0xA1..=0xFE) => 2,
+        _ => 1,
+    }
+}
+
+/// Decode the tokens of the message at the start of `data` (until the NULL
+/// character).
+fn tokens(encoding: Encoding, data: &[u8]) -> Result<Vec<Token>> {
+    let width = match encoding {
+        Encoding::Utf16Be => 2,
+        _ => 1,
+    };
+    let mut tokens = Vec::new();
+    let (mut start, mut index) = (0, 0);
+    loop {
+        let c = match data.get(index..index + width) {
+            Some([c]) => *c as u16,
+            Some([high, low]) => u16::from_be_bytes([*high, *low]),
+            _ => Err(ParseProblem::InvalidRange(
+                "BMG message (not terminated)",
+                Location::current(),
+            ))?,
+        };
+        if c != 0 && c != ESCAPE {
+            index += char_size(encoding, &data[index..]);
+            continue;
+        }
+
+        if start < index {
+            tokens.push(Token::Text(encoding.all(&data[start..index])?));
+        }
+        if c == 0 {
+            return Ok(tokens);
+        }
+
+        let size = *data.get(index + width).unwrap_or(&0) as usize;
+        let escape = data
+            .get(index..index + size)
+            .ok_or(ParseProblem::InvalidRange(
+                "BMG escape sequence",
+                Location::current(),
+            ))?;
+        ensure!(
+            size >= width + 4,
+            ParseProblem::InvalidData("BMG escape sequence (too small)", Location::current())
+        );
+        tokens.push(Token::Escape(Escape {
+            group:     escape[width + 1],
+            tag:       u16::from_be_bytes([escape[width + 2], escape[width + 3]]),
+            arguments: escape[width + 4..].to_vec(),
+        }));
+        index += size;
+        start = index;
+    }
+}
+
+impl Bmg {
+    /// Get the encoding of the encoding ID of the header, `0` (unspecified)
+    /// and `1` for [Windows-1252][`Encoding::Windows1252`], `2` for [UTF-16
+    /// BE][`Encoding::Utf16Be`], and `3` for [Shift JIS
+    /// 1997][`Encoding::ShiftJis1997`]. UTF-8 (`4`) is not supported.
+    pub fn encoding_from_id(id: u8) -> Option<Encoding> {
+        match id {
+            0 | 1 => Some(Encoding::Windows1252),
+            2 => Some(Encoding::Utf16Be),
+            3 => Some(Encoding::ShiftJis1997),
+            _ => None,
+        }
+    }
+
+    /// Parse BMG file from binary stream. The text is decoded with the
+    /// encoding of the header.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        Self::parse(input, None)
+    }
+
+    /// Parse BMG file from binary stream like [`Bmg::from_binary`], but
+    /// decode the text with `encoding`, e.g., for games that do not specify
+    /// the encoding in the header.
+    pub fn from_binary_with_encoding<D: Parser + Seeker>(
+        input: &mut D,
+        encoding: Encoding,
+    ) -> Result<Self> {
+        Self::parse(input, Some(encoding))
+    }
+
+    fn parse<D: Parser + Seeker>(input: &mut D, encoding: Option<Encoding>) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<8>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected MESGbmg1", Location::current())
+        );
+        let _size = input.bu32()?;
+        let section_count = input.bu32()?;
+        let encoding_id = input.u8()?;
+        let _padding = input.u8_array::<15>()?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Self::encoding_from_id(encoding_id).ok_or(ParseProblem::InvalidHeader(
+                "unsupported BMG encoding",
+                Location::current(),
+            ))?,
+        };
+
+        let mut info = None;
+        let mut data = None;
+        let mut ids = None;
+        let mut sections = Vec::new();
+        let mut offset = base + 0x20;
+        for _ in 0..section_count {
+            input.goto(offset)?;
+            let magic = input.u8_array::<4>()?;
+            let size = input.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidRange("BMG section size", Location::current())
+            );
+            // the size is not trusted, the data is read without allocating it
+            // first
+            let mut section = Vec::new();
+            input
+                .by_ref()
+                .take(size as u64 - 8)
+                .read_to_end(&mut section)?;
+            ensure!(
+                section.len() == size as usize - 8,
+                ParseProblem::InvalidRange("BMG section (truncated)", Location::current())
+            );
+            match &magic {
+                b"INF1" => info = Some(section),
+                b"DAT1" => data = Some(section),
+                b"MID1" => ids = Some(section),
+                _ => sections.push(Section {
+                    magic,
+                    data: section,
+                }),
+            }
+            offset += size as u64;
+        }
+
+        let info = info.ok_or(ParseProblem::InvalidData(
+            "BMG without INF1 section",
+            Location::current(),
+        ))?;
+        let data = data.ok_or(ParseProblem::InvalidData(
+            "BMG without DAT1 section",
+            Location::current(),
+        ))?;
+        ensure!(
+            info.len() >= 8,
+            ParseProblem::InvalidRange("BMG INF1 section", Location::current())
+        );
+        let count = u16::from_be_bytes([info[0], info[1]]) as usize;
+        let entry_size = u16::from_be_bytes([info[2], info[3]]) as usize;
+        let group = u16::from_be_bytes([info[4], info[5]]);
+        let default_color = info[6];
+        ensure!(
+            entry_size >= 4 && 8 + count * entry_size <= info.len(),
+            ParseProblem::InvalidRange("BMG INF1 entries", Location::current())
+        );
+
+        let mut messages = info[8..8 + count * entry_size]
+            .chunks_exact(entry_size)
+            .map(|entry| {
+                let offset = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                let text = data
+                    .get(offset as usize..)
+                    .ok_or(ParseProblem::InvalidRange(
+                        "BMG message offset (outside of DAT1)",
+                        Location::current(),
+                    ))?;
+                Ok(Message {
+                    id:         None,
+                    attributes: entry[4..].to_vec(),
+                    tokens:     tokens(encoding, text)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let id_format = match ids {
+            Some(ids) => {
+                ensure!(
+                    ids.len() >= 8
+                        && u16::from_be_bytes([ids[0], ids[1]]) as usize == count
+                        && 8 + count * 4 <= ids.len(),
+                    ParseProblem::InvalidRange("BMG MID1 entries", Location::current())
+                );
+                for (message, id) in messages.iter_mut().zip(ids[8..].chunks_exact(4)) {
+                    message.id = Some(u32::from_be_bytes([id[0], id[1], id[2], id[3]]));
+                }
+                Some([ids[2], ids[3], ids[4], ids[5], ids[6], ids[7]])
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            encoding,
+            encoding_id,
+            group,
+            default_color,
+            attribute_size: entry_size - 4,
+            messages,
+            id_format,
+            sections,
+        })
+    }
+
+    /// Find the message with the message ID `id` (of the `MID1` section).
+    pub fn find(&self, id: u32) -> Option<&Message> {
+        self.messages.iter().find(|x| x.id == Some(id))
+    }
+
+    /// Write BMG file to binary stream. The sections are written in the order
+    /// `INF1`, `DAT1`, `MID1` (if [`Bmg::id_format`] is set), and the other
+    /// [sections][`Bmg::sections`]. `DAT1` starts with an empty string used by
+    /// all empty messages, followed by the other messages in order.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let width = match self.encoding {
+            Encoding::Utf16Be => 2,
+            _ => 1,
+        };
+        let entry_size = 4 + self.attribute_size;
+        ensure!(
+            entry_size <= u16::MAX as usize && self.messages.len() <= u16::MAX as usize,
+            BuildProblem::InvalidData("too many BMG messages", Location::current())
+        );
+
+        let mut info = Vec::new();
+        info.extend_from_slice(&(self.messages.len() as u16).to_be_bytes());
+        info.extend_from_slice(&(entry_size as u16).to_be_bytes());
+        info.extend_from_slice(&self.group.to_be_bytes());
+        info.extend_from_slice(&[self.default_color, 0]);
+        let mut data = vec![0; width];
+        for message in self.messages.iter() {
+            ensure!(
+                message.attributes.len() == self.attribute_size,
+                BuildProblem::InvalidData("BMG message attributes size", Location::current())
+            );
+            let offset = match message.tokens.is_empty() {
+                true => 0,
+                false => {
+                    let offset = data.len();
+                    message.to_binary(&mut data, self.encoding)?;
+                    data.extend(core::iter::repeat_n(0, width));
+                    offset
+                },
+            };
+            info.extend_from_slice(&(offset as u32).to_be_bytes());
+            info.extend_from_slice(&message.attributes);
+        }
+
+        let mut sections = vec![(*b"INF1", info), (*b"DAT1", data)];
+        if let Some(format) = self.id_format {
+            let mut ids = Vec::new();
+            ids.extend_from_slice(&(self.messages.len() as u16).to_be_bytes());
+            ids.extend_from_slice(&format);
+            for message in self.messages.iter() {
+                let id = message.id.ok_or(BuildProblem::InvalidData(
+                    "BMG message without ID",
+                    Location::current(),
+                ))?;
+                ids.extend_from_slice(&id.to_be_bytes());
+            }
+            sections.push((*b"MID1", ids));
+        }
+        for section in self.sections.iter() {
+            sections.push((section.magic, section.data.clone()));
+        }
+        for (_, data) in sections.iter_mut() {
+            data.resize((8 + data.len()).next_multiple_of(0x20) - 8, 0);
+        }
+
+        let size = 0x20 + sections.iter().map(|(_, x)| 8 + x.len()).sum::<usize>();
+        output.u8_array(&MAGIC)?;
+        output.bu32(size as u32)?;
+        output.bu32(sections.len() as u32)?;
+        output.u8(self.encoding_id)?;
+        output.u8_array(&[0; 15])?;
+        for (magic, data) in sections.iter() {
+            output.u8_array(magic)?;
+            output.bu32(8 + data.len() as u32)?;
+            output.u8_array(data)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+//! GameCube banners (`opening.bnr`).
+//!
+//! The banner is shown by the GameCube IPL (and the Wii disc channel) and
+//! consists of a 96×32 image and the name, maker, and description of the game.
+//! There are two versions of the format:
+//!
+//! * `BNR1` - Used by games from Japan and America, with one description.
+//! * `BNR2` - Used by games from Europe, with one description per language (see
+//!   [`Language`]).
+//!
+//! | Offset   | Size     | Description                              |
+//! |----------|----------|------------------------------------------|
+//! | `0x0000` | 4        | Magic (`BNR1` or `BNR2`)                 |
+//! | `0x0004` | 0x1C     | Padding                                  |
+//! | `0x0020` | 0x1800   | Image (96×32 RGB5A3, 4×4 tiles)          |
+//! | `0x1820` | 0x140    | [`Description`] (six for `BNR2`)         |
+//!
+//! The text is not tagged with an encoding, it is [Shift JIS
+//! 1997][`crate::ShiftJis1997`] for Japanese games and
+//! [Windows-1252][`crate::Windows1252`] for all other games, see
+//! [`Bnr::encoding`].
+//!
+//! Banners are parsed with [`Bnr::from_binary`] and written with
+//! [`Bnr::to_binary`], e.g., to create custom banners with [`Bnr::new`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::gcm::Region;
+//!
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("opening.bnr")?;
+//!     let bnr = picori::Bnr::from_binary(&mut file, Region::America)?;
+//!     if let Some(description) = bnr.description() {
+//!         println!("{}", description.full_name);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::BuildProblem;
+use crate::gcm::Region;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Writer};
+use crate::texture::{self, Format};
+use crate::{Encoding, Result};
+
+/// Width of the banner image.
+pub const WIDTH: usize = 96;
+
+/// Height of the banner image.
+pub const HEIGHT: usize = 32;
+
+/// Size of a `BNR1` banner.
+pub const BNR1_SIZE: usize = 0x1960;
+
+/// Size of a `BNR2` banner.
+pub const BNR2_SIZE: usize = 0x1FA0;
+
+/// Version of the banner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Version {
+    /// `BNR1`, one description.
+    Bnr1,
+
+    /// `BNR2`, six descriptions (one per [`Language`]).
+    Bnr2,
+}
+
+impl Version {
+    /// Magic of the version.
+    pub fn magic(&self) -> &'static [u8; 4] {
+        match self {
+            Version::Bnr1 => b"BNR1",
+            Version::Bnr2 => b"BNR2",
+        }
+    }
+
+    /// Number of descriptions.
+    pub fn descriptions(&self) -> usize {
+        match self {
+            Version::Bnr1 => 1,
+            Version::Bnr2 => 6,
+        }
+    }
+
+    /// Size of the banner.
+    pub fn size(&self) -> usize {
+        match self {
+            Version::Bnr1 => BNR1_SIZE,
+            Version::Bnr2 => BNR2_SIZE,
+        }
+    }
+}
+
+/// Language of a `BNR2` [`Description`], in the order they are stored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Language {
+    /// English (index `0`).
+    English,
+    /// German (index `1`).
+    German,
+    /// French (index `2`).
+    French,
+    /// Spanish (index `3`).
+    Spanish,
+    /// Italian (index `4`).
+    Italian,
+    /// Dutch (index `5`).
+    Dutch,
+}
+
+impl Language {
+    /// Get the index of the [`Description`] for the language.
+    pub fn index(&self) -> usize { *self as usize }
+}
+
+/// Text of a banner.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Description {
+    /// Short name of the game (0x20 bytes).
+    pub name: String,
+
+    /// Short name of the maker (0x20 bytes).
+    pub maker: String,
+
+    /// Full name of the game (0x40 bytes).
+    pub full_name: String,
+
+    /// Full name of the maker (0x40 bytes).
+    pub full_maker: String,
+
+    /// Description of the game (0x80 bytes), lines are separated by `\n`.
+    pub description: String,
+}
+
+impl Description {
+    fn from_binary<D: Parser>(input: &mut D, encoding: Encoding) -> Result<Self> {
+        Ok(Self {
+            name:        encoding.first(&input.u8_array::<0x20>()?)?,
+            maker:       encoding.first(&input.u8_array::<0x20>()?)?,
+            full_name:   encoding.first(&input.u8_array::<0x40>()?)?,
+            full_maker:  encoding.first(&input.u8_array::<0x40>()?)?,
+            description: encoding.first(&input.u8_array::<0x80>()?)?,
+        })
+    }
+
+    fn to_binary<W: Writer>(&self, output: &mut W, encoding: Encoding) -> Result<()> {
+        let fields = [
+            (&self.name, 0x20),
+            (&self.maker, 0x20),
+            (&self.full_name, 0x40),
+            (&self.full_maker, 0x40),
+            (&self.description, 0x80),
+        ];
+        for (field, size) in fields {
+            let mut buffer = [0u8; 0x80];
+            let consumed = encoding.encode_into(&mut buffer[..size], field)?;
+            ensure!(
+                consumed == field.len(),
+                BuildProblem::InvalidData("banner text does not fit", Location::current())
+            );
+            output.u8_array(&buffer[..size])?;
+        }
+        Ok(())
+    }
+}
+
+/// GameCube banner (`opening.bnr`).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bnr {
+    /// Version of the banner.
+    pub version: Version,
+
+    /// Image as RGBA8, [`WIDTH`]×[`HEIGHT`] pixels row by row.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub image: Vec<u8>,
+
+    /// Descriptions, one for `BNR1` and one per [`Language`] for `BNR2`.
+    pub descriptions: Vec<Description>,
+}
+
+impl Bnr {
+    /// Create a banner with a transparent image and empty descriptions.
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            image: vec![0; WIDTH * HEIGHT * 4],
+            descriptions: vec![Description::default(); version.descriptions()],
+        }
+    }
+
+    /// Get the encoding of the text of banners of games from `region`, i.e.,
+    /// [Shift JIS 1997][`Encoding::ShiftJis1997`] for Japanese games and
+    /// [Windows-1252][`Encoding::Windows1252`] for all other games.
+    pub fn encoding(region: Region) -> Encoding {
+        match region {
+            Region::Japan => Encoding::ShiftJis1997,
+            _ => Encoding::Windows1252,
+        }
+    }
+
+    /// Parse banner from binary stream. The text is decoded with the
+    /// [encoding][`Bnr::encoding`] of `region`, i.e., the region of the game.
+    pub fn from_binary<D: Parser>(input: &mut D, region: Region) -> Result<Self> {
+        let version = match &input.u8_array::<4>()? {
+            b"BNR1" => Version::Bnr1,
+            b"BNR2" => Version::Bnr2,
+            _ => Err(ParseProblem::InvalidMagic(
+                "expected BNR1 or BNR2",
+                Location::current(),
+            ))?,
+        };
+        let _padding = input.u8_array::<0x1C>()?;
+        let image = input.u8_array::<{ WIDTH * HEIGHT * 2 }>()?;
+        let image = texture::decode(Format::Rgb5a3, &image, WIDTH, HEIGHT, None)?;
+
+        let encoding = Self::encoding(region);
+        let descriptions = (0..version.descriptions())
+            .map(|_| Description::from_binary(input, encoding))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            version,
+            image,
+            descriptions,
+        })
+    }
+
+    /// Write banner to binary stream. The text is encoded with the
+    /// [encoding][`Bnr::encoding`] of `region`. Returns an error if the image
+    /// is not [`WIDTH`]×[`HEIGHT`] pixels, if the number of descriptions does
+    /// not match the version, or if the text does not fit or can not be
+    /// encoded.
+    pub fn to_binary<W: Writer>(&self, output: &mut W, region: Region) -> Result<()> {
+        ensure!(
+            self.descriptions.len() == self.version.descriptions(),
+            BuildProblem::InvalidData("invalid number of descriptions", Location::current())
+        );
+
+        output.u8_array(self.version.magic())?;
+        output.u8_array(&[0; 0x1C])?;
+        output.u8_array(&texture::encode(
+            Format::Rgb5a3,
+            &self.image,
+            WIDTH,
+            HEIGHT,
+        )?)?;
+
+        let encoding = Self::encoding(region);
+        for description in self.descriptions.iter() {
+            description.to_binary(output, encoding)?;
+        }
+        Ok(())
+    }
+
+    /// Get the first description, i.e., the only description of `BNR1`
+    /// banners and the English description of `BNR2` banners. Returns
+    /// [`None`] if [`Bnr::descriptions`] is empty.
+    pub fn description(&self) -> Option<&Description> { self.descriptions.first() }
+
+    /// Get the description for `language`. Returns [`None`] for `BNR1`
+    /// banners, use [`Bnr::description`] instead.
+    pub fn localized(&self, language: Language) -> Option<&Description> {
+        match self.version {
+            Version::Bnr1 => None,
+            Version::Bnr2 => self.descriptions.get(language.index()),
+        }
+    }
+
+    /// Get the mutable description for `language`, see [`Bnr::localized`].
+    pub fn localized_mut(&mut self, language: Language) -> Option<&mut Description> {
+        match self.version {
+            Version::Bnr1 => None,
+            Version::Bnr2 => self.descriptions.get_mut(language.index()),
+        }
+    }
+}
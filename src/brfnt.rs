@@ -0,0 +1,503 @@
+//! BRFNT fonts (`.brfnt`).
+//!
+//! BRFNT (`RFNT`) is the bitmap font format of the NintendoWare for Revolution
+//! library used by most Wii games. A font consists of sections:
+//!
+//! * `FINF` - [Font information][`FontInfo`], e.g., the line height, the
+//!   default character width, and the character encoding.
+//! * `TGLP` - [Glyph sheets][`GlyphSheets`], textures with the glyphs arranged
+//!   in a grid of cells.
+//! * `CWDH` - [Character widths][`WidthBlock`] by glyph index.
+//! * `CMAP` - [Character maps][`CodeMap`] from character codes to glyph
+//!   indices.
+//!
+//! Use [`Brfnt::glyph_index`] to look up the glyph of a character code and
+//! [`Brfnt::glyph`] to extract the image and the width of a glyph.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("font.brfnt")?;
+//!     let font = picori::brfnt::Brfnt::from_binary(&mut file)?;
+//!     if let Some(glyph) = font.glyph('A' as u16)? {
+//!         println!("A: {} pixels wide", glyph.width.char_width);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::texture::{self, Format};
+use crate::Result;
+
+/// [BRFNT][`crate::brfnt`] magic (`RFNT`).
+pub const MAGIC: [u8; 4] = *b"RFNT";
+
+/// Character encoding of a font.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FontEncoding {
+    /// UTF-8 (`0`).
+    Utf8,
+
+    /// UTF-16 (`1`).
+    Utf16,
+
+    /// Shift JIS (`2`).
+    ShiftJis,
+
+    /// Windows-1252 (`3`).
+    Windows1252,
+}
+
+impl TryFrom<u8> for FontEncoding {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(FontEncoding::Utf8),
+            1 => Ok(FontEncoding::Utf16),
+            2 => Ok(FontEncoding::ShiftJis),
+            3 => Ok(FontEncoding::Windows1252),
+            _ => Err(
+                ParseProblem::InvalidHeader("unknown font encoding", Location::current()).into(),
+            ),
+        }
+    }
+}
+
+/// Width of a character.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CharWidth {
+    /// Space to the left of the glyph.
+    pub left: i8,
+
+    /// Width of the glyph.
+    pub glyph_width: u8,
+
+    /// Width of the character (the advance to the next character).
+    pub char_width: i8,
+}
+
+impl CharWidth {
+    fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        Ok(Self {
+            left:        input.u8()? as i8,
+            glyph_width: input.u8()?,
+            char_width:  input.u8()? as i8,
+        })
+    }
+}
+
+/// Font information (`FINF`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontInfo {
+    /// Font type.
+    pub font_type: u8,
+
+    /// Line feed (height of a line).
+    pub line_feed: i8,
+
+    /// Glyph index of the character used for characters without a glyph.
+    pub alternate_index: u16,
+
+    /// Width of characters without a [width block][`WidthBlock`].
+    pub default_width: CharWidth,
+
+    /// Character encoding.
+    pub encoding: FontEncoding,
+
+    /// Height of the font.
+    pub height: u8,
+
+    /// Width of the font.
+    pub width: u8,
+
+    /// Ascent of the font.
+    pub ascent: u8,
+}
+
+/// Glyph sheets (`TGLP`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphSheets {
+    /// Width of a glyph cell.
+    pub cell_width: u8,
+
+    /// Height of a glyph cell.
+    pub cell_height: u8,
+
+    /// Position of the baseline in a cell.
+    pub baseline: i8,
+
+    /// Maximum width of a character.
+    pub max_char_width: u8,
+
+    /// Texture format of the sheets.
+    pub format: Format,
+
+    /// Number of cells per row.
+    pub rows: u16,
+
+    /// Number of cells per column.
+    pub lines: u16,
+
+    /// Width of a sheet.
+    pub width: u16,
+
+    /// Height of a sheet.
+    pub height: u16,
+
+    /// Texture data of the sheets.
+    pub sheets: Vec<Vec<u8>>,
+}
+
+/// Character widths (`CWDH`) of the glyph indices `start..=end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidthBlock {
+    /// First glyph index.
+    pub start: u16,
+
+    /// Last glyph index.
+    pub end: u16,
+
+    /// Widths of the glyphs.
+    pub widths: Vec<CharWidth>,
+}
+
+/// Mapping from character codes to glyph indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mapping {
+    /// The glyph index is the character code minus the first code, plus the
+    /// offset (`0`).
+    Direct(u16),
+
+    /// Glyph index of each character code, `0xFFFF` for none (`1`).
+    Table(Vec<u16>),
+
+    /// Pairs of character code and glyph index (`2`).
+    Scan(Vec<(u16, u16)>),
+}
+
+/// Character map (`CMAP`) of the character codes `start..=end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeMap {
+    /// First character code.
+    pub start: u16,
+
+    /// Last character code.
+    pub end: u16,
+
+    /// Mapping of the character codes.
+    pub mapping: Mapping,
+}
+
+impl CodeMap {
+    /// Get the glyph index of the character `code`.
+    pub fn glyph_index(&self, code: u16) -> Option<u16> {
+        if code < self.start || code > self.end {
+            return None;
+        }
+        let index = match &self.mapping {
+            Mapping::Direct(offset) => (code - self.start).checked_add(*offset)?,
+            Mapping::Table(table) => *table.get((code - self.start) as usize)?,
+            Mapping::Scan(pairs) => pairs.iter().find(|x| x.0 == code)?.1,
+        };
+        (index != 0xFFFF).then_some(index)
+    }
+}
+
+/// Glyph of a character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Glyph index.
+    pub index: u16,
+
+    /// Image of the cell (RGBA8, [`GlyphSheets::cell_width`] ×
+    /// [`GlyphSheets::cell_height`]).
+    pub image: Vec<u8>,
+
+    /// Width of the character.
+    pub width: CharWidth,
+}
+
+/// BRFNT font.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Brfnt {
+    /// Version, e.g., `0x0104`.
+    pub version: u16,
+
+    /// Font information.
+    pub info: FontInfo,
+
+    /// Glyph sheets.
+    pub sheets: GlyphSheets,
+
+    /// Character widths.
+    pub widths: Vec<WidthBlock>,
+
+    /// Character maps.
+    pub maps: Vec<CodeMap>,
+}
+
+impl Brfnt {
+    /// Parse BRFNT font from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: RFNT", Location::current())
+        );
+        let bom = input.bu16()?;
+        ensure!(
+            bom == 0xFEFF,
+            ParseProblem::InvalidHeader("BRFNT byte order (little-endian)", Location::current())
+        );
+        let version = input.bu16()?;
+        let _file_size = input.bu32()?;
+        let header_size = input.bu16()?;
+        let section_count = input.bu16()?;
+
+        let mut info = None;
+        let mut sheets = None;
+        let mut widths = Vec::new();
+        let mut maps = Vec::new();
+        let mut offset = base + header_size as u64;
+        for _ in 0..section_count {
+            input.goto(offset)?;
+            let magic = input.u8_array::<4>()?;
+            let size = input.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidHeader("BRFNT section size", Location::current())
+            );
+            match &magic {
+                b"FINF" => info = Some(Self::info(input)?),
+                b"TGLP" => sheets = Some(Self::sheets(input, base)?),
+                b"CWDH" => widths.push(Self::widths(input)?),
+                b"CMAP" => maps.push(Self::map(input)?),
+                _ => {},
+            }
+            offset += size as u64;
+        }
+
+        Ok(Self {
+            version,
+            info: info.ok_or(ParseProblem::InvalidData(
+                "BRFNT font information (FINF missing)",
+                Location::current(),
+            ))?,
+            sheets: sheets.ok_or(ParseProblem::InvalidData(
+                "BRFNT glyph sheets (TGLP missing)",
+                Location::current(),
+            ))?,
+            widths,
+            maps,
+        })
+    }
+
+    fn info<D: Parser>(input: &mut D) -> Result<FontInfo> {
+        let font_type = input.u8()?;
+        let line_feed = input.u8()? as i8;
+        let alternate_index = input.bu16()?;
+        let default_width = CharWidth::from_binary(input)?;
+        let encoding = FontEncoding::try_from(input.u8()?)?;
+        let _offsets = input.bu32_array::<3>()?;
+        let height = input.u8()?;
+        let width = input.u8()?;
+        let ascent = input.u8()?;
+
+        Ok(FontInfo {
+            font_type,
+            line_feed,
+            alternate_index,
+            default_width,
+            encoding,
+            height,
+            width,
+            ascent,
+        })
+    }
+
+    fn sheets<D: Parser + Seeker>(input: &mut D, base: u64) -> Result<GlyphSheets> {
+        let cell_width = input.u8()?;
+        let cell_height = input.u8()?;
+        let baseline = input.u8()? as i8;
+        let max_char_width = input.u8()?;
+        let sheet_size = input.bu32()?;
+        let sheet_count = input.bu16()?;
+        let sheet_format = input.bu16()?;
+        ensure!(
+            sheet_format & 0x8000 == 0,
+            ParseProblem::InvalidData(
+                "compressed BRFNT glyph sheets (unsupported)",
+                Location::current()
+            )
+        );
+        let format = Format::from_id(sheet_format as u32).ok_or(ParseProblem::InvalidHeader(
+            "unknown BRFNT sheet format",
+            Location::current(),
+        ))?;
+        let rows = input.bu16()?;
+        let lines = input.bu16()?;
+        let width = input.bu16()?;
+        let height = input.bu16()?;
+        let data_offset = input.bu32()?;
+
+        input.goto(base + data_offset as u64)?;
+        let sheets = (0..sheet_count)
+            .map(|_| input.read_as_vec(sheet_size as usize))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GlyphSheets {
+            cell_width,
+            cell_height,
+            baseline,
+            max_char_width,
+            format,
+            rows,
+            lines,
+            width,
+            height,
+            sheets,
+        })
+    }
+
+    fn widths<D: Parser>(input: &mut D) -> Result<WidthBlock> {
+        let start = input.bu16()?;
+        let end = input.bu16()?;
+        let _next = input.bu32()?;
+        ensure!(
+            start <= end,
+            ParseProblem::InvalidRange("BRFNT width block", Location::current())
+        );
+        let widths = (start..=end)
+            .map(|_| CharWidth::from_binary(input))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(WidthBlock { start, end, widths })
+    }
+
+    fn map<D: Parser>(input: &mut D) -> Result<CodeMap> {
+        let start = input.bu16()?;
+        let end = input.bu16()?;
+        let method = input.bu16()?;
+        let _reserved = input.bu16()?;
+        let _next = input.bu32()?;
+        ensure!(
+            start <= end,
+            ParseProblem::InvalidRange("BRFNT character map", Location::current())
+        );
+        let mapping = match method {
+            0 => Mapping::Direct(input.bu16()?),
+            1 => Mapping::Table(
+                (start..=end)
+                    .map(|_| input.bu16())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            2 => {
+                let count = input.bu16()?;
+                Mapping::Scan(
+                    (0..count)
+                        .map(|_| Ok((input.bu16()?, input.bu16()?)))
+                        .collect::<Result<Vec<_>>>()?,
+                )
+            },
+            _ => Err(ParseProblem::InvalidData(
+                "unknown BRFNT mapping method",
+                Location::current(),
+            ))?,
+        };
+        Ok(CodeMap {
+            start,
+            end,
+            mapping,
+        })
+    }
+
+    /// Get the glyph index of the character `code`, [`None`] if the font has
+    /// no glyph for the character.
+    pub fn glyph_index(&self, code: u16) -> Option<u16> {
+        self.maps.iter().find_map(|x| x.glyph_index(code))
+    }
+
+    /// Iterate over all characters (character code and glyph index) of the
+    /// font.
+    pub fn characters(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.maps.iter().flat_map(|map| {
+            (map.start..=map.end).filter_map(move |code| Some((code, map.glyph_index(code)?)))
+        })
+    }
+
+    /// Get the width of the glyph `index`, the [default
+    /// width][`FontInfo::default_width`] if there is no width block for the
+    /// glyph.
+    pub fn char_width(&self, index: u16) -> CharWidth {
+        self.widths
+            .iter()
+            .find(|x| (x.start..=x.end).contains(&index))
+            .map(|x| x.widths[(index - x.start) as usize])
+            .unwrap_or(self.info.default_width)
+    }
+
+    /// Decode the sheet `index` (RGBA8, [`GlyphSheets::width`] ×
+    /// [`GlyphSheets::height`]).
+    pub fn sheet(&self, index: usize) -> Result<Vec<u8>> {
+        let sheets = &self.sheets;
+        let data = sheets.sheets.get(index).ok_or(ParseProblem::InvalidRange(
+            "BRFNT sheet index",
+            Location::current(),
+        ))?;
+        texture::decode(
+            sheets.format,
+            data,
+            sheets.width as usize,
+            sheets.height as usize,
+            None,
+        )
+    }
+
+    /// Extract the glyph of the character `code`, [`None`] if the font has no
+    /// glyph for the character.
+    pub fn glyph(&self, code: u16) -> Result<Option<Glyph>> {
+        match self.glyph_index(code) {
+            Some(index) => Ok(Some(self.glyph_by_index(index)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Extract the glyph `index`. The cells are separated by one pixel.
+    pub fn glyph_by_index(&self, index: u16) -> Result<Glyph> {
+        let sheets = &self.sheets;
+        let per_sheet = sheets.rows as usize * sheets.lines as usize;
+        ensure!(
+            per_sheet > 0,
+            ParseProblem::InvalidData("BRFNT sheet without cells", Location::current())
+        );
+        let sheet = self.sheet(index as usize / per_sheet)?;
+        let cell = index as usize % per_sheet;
+        let (cell_width, cell_height) = (sheets.cell_width as usize, sheets.cell_height as usize);
+        let x = (cell % sheets.rows as usize) * (cell_width + 1) + 1;
+        let y = (cell / sheets.rows as usize) * (cell_height + 1) + 1;
+        let width = sheets.width as usize;
+        ensure!(
+            x + cell_width <= width && y + cell_height <= sheets.height as usize,
+            ParseProblem::InvalidRange("BRFNT cell (outside of sheet)", Location::current())
+        );
+
+        let mut image = Vec::with_capacity(cell_width * cell_height * 4);
+        for row in y..y + cell_height {
+            image.extend_from_slice(
+                &sheet[(row * width + x) * 4..(row * width + x + cell_width) * 4],
+            );
+        }
+        Ok(Glyph {
+            index,
+            image,
+            width: self.char_width(index),
+        })
+    }
+}
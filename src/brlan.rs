@@ -0,0 +1,360 @@
+//! BRLAN layout animations (`.brlan`).
+//!
+//! BRLAN (`RLAN`) animates the panes and materials of a [BRLYT
+//! layout][crate::brlyt], e.g., the looping animation of a channel banner. An
+//! animation consists of sections:
+//!
+//! * `pat1` - Animation groups (not parsed).
+//! * `pai1` - [Animated elements][`AnimatedElement`] (panes or materials by
+//!   name) and the names of the [textures][`Brlan::textures`] of texture
+//!   pattern animations.
+//!
+//! Every element has one or more [animations][`Animation`] (e.g., the
+//! transformation of the pane or the texture SRT of the material) with
+//! [keys][`Keys`] for the animated values.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("banner_loop.brlan")?;
+//!     let animation = picori::brlan::Brlan::from_binary(&mut file)?;
+//!     for element in animation.elements.iter() {
+//!         println!("{}: {} animations", element.name, element.animations.len());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::j3d_animation::{Keyframe, Track};
+use crate::{Ascii, Encoding, Result};
+
+/// [BRLAN][`crate::brlan`] magic (`RLAN`).
+pub const MAGIC: [u8; 4] = *b"RLAN";
+
+/// Kind of an [`Animation`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationKind {
+    /// Pane translation, rotation, scale, and size (`RLPA`).
+    PaneSrt,
+
+    /// Material texture SRT (`RLTS`).
+    TextureSrt,
+
+    /// Pane visibility (`RLVI`).
+    Visibility,
+
+    /// Pane vertex colors and alpha (`RLVC`).
+    VertexColor,
+
+    /// Material colors (`RLMC`).
+    MaterialColor,
+
+    /// Texture pattern, the index of the texture (`RLTP`).
+    TexturePattern,
+
+    /// Indirect texture SRT (`RLIM`).
+    IndirectSrt,
+}
+
+impl AnimationKind {
+    /// Get the magic of the animation kind.
+    pub fn magic(&self) -> [u8; 4] {
+        match self {
+            AnimationKind::PaneSrt => *b"RLPA",
+            AnimationKind::TextureSrt => *b"RLTS",
+            AnimationKind::Visibility => *b"RLVI",
+            AnimationKind::VertexColor => *b"RLVC",
+            AnimationKind::MaterialColor => *b"RLMC",
+            AnimationKind::TexturePattern => *b"RLTP",
+            AnimationKind::IndirectSrt => *b"RLIM",
+        }
+    }
+}
+
+impl TryFrom<[u8; 4]> for AnimationKind {
+    type Error = crate::Error;
+
+    fn try_from(value: [u8; 4]) -> Result<Self> {
+        match &value {
+            b"RLPA" => Ok(AnimationKind::PaneSrt),
+            b"RLTS" => Ok(AnimationKind::TextureSrt),
+            b"RLVI" => Ok(AnimationKind::Visibility),
+            b"RLVC" => Ok(AnimationKind::VertexColor),
+            b"RLMC" => Ok(AnimationKind::MaterialColor),
+            b"RLTP" => Ok(AnimationKind::TexturePattern),
+            b"RLIM" => Ok(AnimationKind::IndirectSrt),
+            _ => Err(
+                ParseProblem::InvalidData("unknown BRLAN animation", Location::current()).into(),
+            ),
+        }
+    }
+}
+
+/// Keys of an [`AnimationTarget`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Keys {
+    /// Step keys (frame and value), for visibility and texture patterns.
+    Step(Vec<(f32, u16)>),
+
+    /// Hermite keys.
+    Hermite(Track),
+}
+
+impl Keys {
+    /// Number of keys.
+    pub fn len(&self) -> usize {
+        match self {
+            Keys::Step(keys) => keys.len(),
+            Keys::Hermite(track) => track.keyframes.len(),
+        }
+    }
+
+    /// Returns `true` if there are no keys.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Get the value at the frame `frame`.
+    pub fn value(&self, frame: f32) -> f32 {
+        match self {
+            Keys::Step(keys) => {
+                let index = keys.partition_point(|x| x.0 <= frame);
+                keys.get(index.saturating_sub(1))
+                    .map_or(0.0, |x| x.1 as f32)
+            },
+            Keys::Hermite(track) => track.value(frame),
+        }
+    }
+}
+
+/// Animated value of an [`Animation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationTarget {
+    /// Index, e.g., the texture SRT of the material.
+    pub index: u8,
+
+    /// Animated value, e.g., `0` to `9` for the translation (XYZ), rotation
+    /// (XYZ), scale (XY), and size (XY) of [`AnimationKind::PaneSrt`].
+    pub target: u8,
+
+    /// Keys.
+    pub keys: Keys,
+}
+
+/// Animation of an [`AnimatedElement`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    /// Kind of the animation.
+    pub kind: AnimationKind,
+
+    /// Animated values.
+    pub targets: Vec<AnimationTarget>,
+}
+
+/// Animated pane or material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedElement {
+    /// Name of the pane or the material.
+    pub name: String,
+
+    /// The element is a material (instead of a pane).
+    pub is_material: bool,
+
+    /// Animations.
+    pub animations: Vec<Animation>,
+}
+
+/// BRLAN layout animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Brlan {
+    /// Version, e.g., `0x0008`.
+    pub version: u16,
+
+    /// Length in frames.
+    pub frame_count: u16,
+
+    /// The animation loops.
+    pub looping: bool,
+
+    /// Names of the textures of [texture pattern
+    /// animations][`AnimationKind::TexturePattern`].
+    pub textures: Vec<String>,
+
+    /// Animated panes and materials.
+    pub elements: Vec<AnimatedElement>,
+}
+
+impl Brlan {
+    /// Parse BRLAN layout animation from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: RLAN", Location::current())
+        );
+        let bom = input.bu16()?;
+        ensure!(
+            bom == 0xFEFF,
+            ParseProblem::InvalidHeader("BRLAN byte order (little-endian)", Location::current())
+        );
+        let version = input.bu16()?;
+        let _file_size = input.bu32()?;
+        let header_size = input.bu16()?;
+        let section_count = input.bu16()?;
+
+        let mut animation = None;
+        let mut offset = base + header_size as u64;
+        for _ in 0..section_count {
+            input.goto(offset)?;
+            let magic = input.u8_array::<4>()?;
+            let size = input.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidHeader("BRLAN section size", Location::current())
+            );
+            if &magic == b"pai1" {
+                input.goto(offset)?;
+                let data = input.read_as_vec(size as usize)?;
+                animation = Some(Self::animation(version, &data)?);
+            }
+            offset += size as u64;
+        }
+
+        Ok(animation.ok_or(ParseProblem::InvalidData(
+            "BRLAN animation (pai1 missing)",
+            Location::current(),
+        ))?)
+    }
+
+    /// Find the animated element named `name`.
+    pub fn element(&self, name: &str) -> Option<&AnimatedElement> {
+        self.elements.iter().find(|x| x.name == name)
+    }
+
+    fn animation(version: u16, data: &[u8]) -> Result<Self> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let frame_count = input.bu16()?;
+        let looping = input.u8()? != 0;
+        let _padding = input.u8()?;
+        let texture_count = input.bu16()?;
+        let element_count = input.bu16()?;
+        let elements_offset = input.bu32()? as u64;
+
+        // texture names, relative to the offset table
+        let textures = (0..texture_count)
+            .map(|_| {
+                let offset = 0x14 + input.bu32()? as usize;
+                let name = data.get(offset..).ok_or(ParseProblem::InvalidRange(
+                    "BRLAN texture name (outside of section)",
+                    Location::current(),
+                ))?;
+                Encoding::Ascii.first(name)
+            })
+            .collect::<Result<_>>()?;
+
+        input.goto(elements_offset)?;
+        let offsets = (0..element_count)
+            .map(|_| input.bu32().map(|x| x as u64))
+            .collect::<Result<Vec<_>>>()?;
+        let elements = offsets
+            .into_iter()
+            .map(|offset| Self::element_at(&mut input, offset))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            version,
+            frame_count,
+            looping,
+            textures,
+            elements,
+        })
+    }
+
+    fn element_at(input: &mut Cursor<&[u8]>, offset: u64) -> Result<AnimatedElement> {
+        input.goto(offset)?;
+        let name = input.str_fixed::<20, Ascii>()?;
+        let count = input.u8()?;
+        let is_material = input.u8()? != 0;
+        let _padding = input.bu16()?;
+        let tags = (0..count)
+            .map(|_| input.bu32().map(|x| offset + x as u64))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut animations = Vec::with_capacity(tags.len());
+        for tag in tags {
+            input.goto(tag)?;
+            let kind = AnimationKind::try_from(input.u8_array::<4>()?)?;
+            let count = input.u8()?;
+            let _padding = input.u8_array::<3>()?;
+            let targets = (0..count)
+                .map(|_| input.bu32().map(|x| tag + x as u64))
+                .collect::<Result<Vec<_>>>()?;
+            let targets = targets
+                .into_iter()
+                .map(|target| Self::target_at(input, target))
+                .collect::<Result<_>>()?;
+            animations.push(Animation { kind, targets });
+        }
+
+        Ok(AnimatedElement {
+            name,
+            is_material,
+            animations,
+        })
+    }
+
+    fn target_at(input: &mut Cursor<&[u8]>, offset: u64) -> Result<AnimationTarget> {
+        input.goto(offset)?;
+        let index = input.u8()?;
+        let target = input.u8()?;
+        let kind = input.u8()?;
+        let _padding = input.u8()?;
+        let count = input.bu16()?;
+        let _padding = input.bu16()?;
+        let keys_offset = input.bu32()? as u64;
+
+        input.goto(offset + keys_offset)?;
+        let keys = match kind {
+            1 => Keys::Step(
+                (0..count)
+                    .map(|_| {
+                        let frame = f32::from_bits(input.bu32()?);
+                        let value = input.bu16()?;
+                        let _padding = input.bu16()?;
+                        Ok((frame, value))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            2 => Keys::Hermite(Track {
+                keyframes: (0..count)
+                    .map(|_| {
+                        let [time, value, slope] = input.bu32_array::<3>()?.map(f32::from_bits);
+                        Ok(Keyframe {
+                            time,
+                            value,
+                            tangent_in: slope,
+                            tangent_out: slope,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            }),
+            _ => Err(ParseProblem::InvalidData(
+                "BRLAN key type",
+                Location::current(),
+            ))?,
+        };
+
+        Ok(AnimationTarget {
+            index,
+            target,
+            keys,
+        })
+    }
+}
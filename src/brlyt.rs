@@ -0,0 +1,573 @@
+//! BRLYT layouts (`.brlyt`).
+//!
+//! BRLYT (`RLYT`) is the 2D layout format of the NintendoWare for Revolution
+//! library, used for menus, HUDs, and the banners and icons of Wii channels.
+//! The [animations][crate::brlan] of a layout are stored in separate BRLAN
+//! files. A layout consists of sections:
+//!
+//! * `lyt1` - [Layout][`Layout`] size.
+//! * `txl1` - Names of the [textures][`Brlyt::textures`] (TPL files).
+//! * `fnl1` - Names of the [fonts][`Brlyt::fonts`] (BRFNT files).
+//! * `mat1` - [Materials][`Material`].
+//! * `pan1`, `pic1`, `txt1`, `wnd1`, and `bnd1` - [Panes][`Pane`] (null,
+//!   picture, text, window, and bounding panes). The children of a pane are
+//!   enclosed in `pas1` and `pae1`.
+//! * `grp1` - [Groups][`Group`] of panes, nested with `grs1` and `gre1`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("banner.brlyt")?;
+//!     let layout = picori::brlyt::Brlyt::from_binary(&mut file)?;
+//!     for pane in layout.panes.iter() {
+//!         println!("{} ({} children)", pane.name, pane.children.len());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::{Ascii, Encoding, Result};
+
+/// [BRLYT][`crate::brlyt`] magic (`RLYT`).
+pub const MAGIC: [u8; 4] = *b"RLYT";
+
+/// Layout size (`lyt1`).
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Layout {
+    /// The origin of the layout is the center (instead of the top left).
+    pub centered: bool,
+
+    /// Width.
+    pub width: f32,
+
+    /// Height.
+    pub height: f32,
+}
+
+/// Texture of a [`Material`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextureMap {
+    /// Index into [`Brlyt::textures`].
+    pub texture: u16,
+
+    /// Wrap mode of the S coordinate.
+    pub wrap_s: u8,
+
+    /// Wrap mode of the T coordinate.
+    pub wrap_t: u8,
+}
+
+/// Texture coordinate transformation of a [`Material`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureSrt {
+    /// Translation.
+    pub translation: [f32; 2],
+
+    /// Rotation (in degrees).
+    pub rotation: f32,
+
+    /// Scale.
+    pub scale: [f32; 2],
+}
+
+/// Material (`mat1`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    /// Name of the material.
+    pub name: String,
+
+    /// TEV color registers 0 to 2 (foreground, background, and register 2),
+    /// 16-bit RGBA.
+    pub colors: [[i16; 4]; 3],
+
+    /// TEV constant colors (RGBA8).
+    pub constant_colors: [[u8; 4]; 4],
+
+    /// Flags, the number of the texture maps (bits 0-3), the texture SRTs
+    /// (bits 4-7), the texture coordinate generators (bits 8-11), and the
+    /// presence of the other (not parsed) settings.
+    pub flags: u32,
+
+    /// Textures.
+    pub texture_maps: Vec<TextureMap>,
+
+    /// Texture coordinate transformations.
+    pub texture_srts: Vec<TextureSrt>,
+
+    /// Texture coordinate generators (type, source, matrix, and padding).
+    pub texture_coord_gens: Vec<[u8; 4]>,
+
+    /// Other settings (e.g., TEV stages and blend mode), see
+    /// [`Material::flags`].
+    pub data: Vec<u8>,
+}
+
+/// Picture pane (`pic1`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    /// Colors of the vertices (top left, top right, bottom left, and bottom
+    /// right), RGBA8.
+    pub vertex_colors: [[u8; 4]; 4],
+
+    /// Index of the material.
+    pub material: u16,
+
+    /// Texture coordinates of the vertices, one set per texture.
+    pub texture_coords: Vec<[[f32; 2]; 4]>,
+}
+
+/// Text pane (`txt1`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    /// Size of the text buffer (in bytes).
+    pub buffer_size: u16,
+
+    /// Index of the material.
+    pub material: u16,
+
+    /// Index of the font.
+    pub font: u16,
+
+    /// Position of the text in the pane.
+    pub position: u8,
+
+    /// Alignment of the lines.
+    pub alignment: u8,
+
+    /// Text.
+    pub text: String,
+
+    /// Colors of the top and bottom of the text (RGBA8).
+    pub colors: [[u8; 4]; 2],
+
+    /// Size of the characters.
+    pub font_size: [f32; 2],
+
+    /// Space between the characters.
+    pub char_spacing: f32,
+
+    /// Space between the lines.
+    pub line_spacing: f32,
+}
+
+/// Kind of a [`Pane`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaneKind {
+    /// Null pane (`pan1`).
+    Null,
+
+    /// Picture pane (`pic1`).
+    Picture(Picture),
+
+    /// Text pane (`txt1`).
+    Text(Text),
+
+    /// Window pane (`wnd1`), the data after the pane (not parsed).
+    Window(Vec<u8>),
+
+    /// Bounding pane (`bnd1`).
+    Bounding,
+}
+
+/// Pane of a layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pane {
+    /// Kind of the pane.
+    pub kind: PaneKind,
+
+    /// Flags, bit 0 is visibility.
+    pub flags: u8,
+
+    /// Origin (0 to 8, top left to bottom right).
+    pub origin: u8,
+
+    /// Alpha.
+    pub alpha: u8,
+
+    /// Name of the pane.
+    pub name: String,
+
+    /// User data.
+    pub user_data: [u8; 8],
+
+    /// Translation.
+    pub translation: [f32; 3],
+
+    /// Rotation (in degrees).
+    pub rotation: [f32; 3],
+
+    /// Scale.
+    pub scale: [f32; 2],
+
+    /// Width.
+    pub width: f32,
+
+    /// Height.
+    pub height: f32,
+
+    /// Children.
+    pub children: Vec<Pane>,
+}
+
+impl Pane {
+    /// Returns `true` if the pane is visible.
+    pub fn is_visible(&self) -> bool { self.flags & 1 != 0 }
+
+    /// Find the pane named `name` (this pane or a descendant).
+    pub fn find(&self, name: &str) -> Option<&Pane> {
+        match self.name == name {
+            true => Some(self),
+            false => self.children.iter().find_map(|x| x.find(name)),
+        }
+    }
+}
+
+/// Group of panes (`grp1`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// Name of the group.
+    pub name: String,
+
+    /// Names of the panes.
+    pub panes: Vec<String>,
+
+    /// Child groups.
+    pub children: Vec<Group>,
+}
+
+/// BRLYT layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Brlyt {
+    /// Version, e.g., `0x000A`.
+    pub version: u16,
+
+    /// Layout size.
+    pub layout: Layout,
+
+    /// Names of the textures.
+    pub textures: Vec<String>,
+
+    /// Names of the fonts.
+    pub fonts: Vec<String>,
+
+    /// Materials.
+    pub materials: Vec<Material>,
+
+    /// Pane tree, the root panes (usually a single pane named `RootPane`).
+    pub panes: Vec<Pane>,
+
+    /// Group tree, the root groups (usually a single group named
+    /// `RootGroup`).
+    pub groups: Vec<Group>,
+}
+
+impl Brlyt {
+    /// Parse BRLYT layout from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: RLYT", Location::current())
+        );
+        let bom = input.bu16()?;
+        ensure!(
+            bom == 0xFEFF,
+            ParseProblem::InvalidHeader("BRLYT byte order (little-endian)", Location::current())
+        );
+        let version = input.bu16()?;
+        let _file_size = input.bu32()?;
+        let header_size = input.bu16()?;
+        let section_count = input.bu16()?;
+
+        let mut layout = Layout::default();
+        let mut textures = Vec::new();
+        let mut fonts = Vec::new();
+        let mut materials = Vec::new();
+        // the children of the open panes (and groups), the last pane (or group)
+        // is the parent of the next `pas1` (`grs1`)
+        let mut panes = vec![Vec::<Pane>::new()];
+        let mut groups = vec![Vec::<Group>::new()];
+        let mut offset = base + header_size as u64;
+        for _ in 0..section_count {
+            input.goto(offset)?;
+            let magic = input.u8_array::<4>()?;
+            let size = input.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidHeader("BRLYT section size", Location::current())
+            );
+            input.goto(offset)?;
+            let data = input.read_as_vec(size as usize)?;
+            match &magic {
+                b"lyt1" => layout = Self::layout(&data)?,
+                b"txl1" => textures = Self::names(&data)?,
+                b"fnl1" => fonts = Self::names(&data)?,
+                b"mat1" => materials = Self::materials(&data)?,
+                b"pan1" | b"pic1" | b"txt1" | b"wnd1" | b"bnd1" => {
+                    let pane = Self::pane_from(&magic, &data)?;
+                    panes.last_mut().unwrap().push(pane);
+                },
+                b"grp1" => {
+                    let group = Self::group(&data)?;
+                    groups.last_mut().unwrap().push(group);
+                },
+                b"pas1" => panes.push(Vec::new()),
+                b"grs1" => groups.push(Vec::new()),
+                b"pae1" => {
+                    let children = close(&mut panes)?;
+                    panes.last_mut().unwrap().last_mut().unwrap().children = children;
+                },
+                b"gre1" => {
+                    let children = close(&mut groups)?;
+                    groups.last_mut().unwrap().last_mut().unwrap().children = children;
+                },
+                _ => {},
+            }
+            offset += size as u64;
+        }
+        ensure!(
+            panes.len() == 1 && groups.len() == 1,
+            ParseProblem::InvalidData("BRLYT hierarchy (unbalanced)", Location::current())
+        );
+
+        Ok(Self {
+            version,
+            layout,
+            textures,
+            fonts,
+            materials,
+            panes: panes.pop().unwrap(),
+            groups: groups.pop().unwrap(),
+        })
+    }
+
+    /// Find the pane named `name`.
+    pub fn pane(&self, name: &str) -> Option<&Pane> { self.panes.iter().find_map(|x| x.find(name)) }
+
+    /// Get the name of the texture of the texture map `index` of the material
+    /// `material`.
+    pub fn texture_name(&self, material: usize, index: usize) -> Option<&str> {
+        let map = self.materials.get(material)?.texture_maps.get(index)?;
+        self.textures.get(map.texture as usize).map(|x| x.as_str())
+    }
+
+    fn layout(data: &[u8]) -> Result<Layout> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let centered = input.u8()? != 0;
+        let _padding = input.u8_array::<3>()?;
+        let width = f32::from_bits(input.bu32()?);
+        let height = f32::from_bits(input.bu32()?);
+        Ok(Layout {
+            centered,
+            width,
+            height,
+        })
+    }
+
+    /// Parse the names of `txl1` and `fnl1` (offsets relative to the offset
+    /// table).
+    fn names(data: &[u8]) -> Result<Vec<String>> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let count = input.bu16()?;
+        let _padding = input.bu16()?;
+        (0..count)
+            .map(|_| {
+                let offset = 0x0C + input.bu32()? as usize;
+                let _padding = input.bu32()?;
+                let name = data.get(offset..).ok_or(ParseProblem::InvalidRange(
+                    "BRLYT name (outside of section)",
+                    Location::current(),
+                ))?;
+                Encoding::Ascii.first(name)
+            })
+            .collect()
+    }
+
+    fn materials(data: &[u8]) -> Result<Vec<Material>> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let count = input.bu16()?;
+        let _padding = input.bu16()?;
+        let offsets = (0..count)
+            .map(|_| input.bu32().map(|x| x as usize))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut materials = Vec::with_capacity(offsets.len());
+        for (i, offset) in offsets.iter().enumerate() {
+            let end = offsets.get(i + 1).copied().unwrap_or(data.len());
+            input.goto(*offset as u64)?;
+            let name = input.str_fixed::<20, Ascii>()?;
+            let mut colors = [[0_i16; 4]; 3];
+            for color in colors.iter_mut() {
+                *color = input.bu16_array::<4>()?.map(|x| x as i16);
+            }
+            let mut constant_colors = [[0; 4]; 4];
+            for color in constant_colors.iter_mut() {
+                *color = input.u8_array::<4>()?;
+            }
+            let flags = input.bu32()?;
+
+            let texture_maps = (0..flags & 0xF)
+                .map(|_| {
+                    let texture = input.bu16()?;
+                    let wrap_s = input.u8()?;
+                    let wrap_t = input.u8()?;
+                    Ok(TextureMap {
+                        texture,
+                        wrap_s,
+                        wrap_t,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            let texture_srts = (0..(flags >> 4) & 0xF)
+                .map(|_| {
+                    let values = input.bu32_array::<5>()?.map(f32::from_bits);
+                    Ok(TextureSrt {
+                        translation: [values[0], values[1]],
+                        rotation:    values[2],
+                        scale:       [values[3], values[4]],
+                    })
+                })
+                .collect::<Result<_>>()?;
+            let texture_coord_gens = (0..(flags >> 8) & 0xF)
+                .map(|_| input.u8_array::<4>())
+                .collect::<Result<_>>()?;
+
+            let start = Seeker::position(&mut input)? as usize;
+            let data = data.get(start..end).ok_or(ParseProblem::InvalidRange(
+                "BRLYT material (outside of section)",
+                Location::current(),
+            ))?;
+            materials.push(Material {
+                name,
+                colors,
+                constant_colors,
+                flags,
+                texture_maps,
+                texture_srts,
+                texture_coord_gens,
+                data: data.to_vec(),
+            });
+        }
+        Ok(materials)
+    }
+
+    fn pane_from(magic: &[u8; 4], data: &[u8]) -> Result<Pane> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let flags = input.u8()?;
+        let origin = input.u8()?;
+        let alpha = input.u8()?;
+        let _padding = input.u8()?;
+        let name = input.str_fixed::<16, Ascii>()?;
+        let user_data = input.u8_array::<8>()?;
+        let transform = input.bu32_array::<10>()?.map(f32::from_bits);
+
+        let kind = match magic {
+            b"pic1" => {
+                let mut vertex_colors = [[0; 4]; 4];
+                for color in vertex_colors.iter_mut() {
+                    *color = input.u8_array::<4>()?;
+                }
+                let material = input.bu16()?;
+                let count = input.u8()?;
+                let _padding = input.u8()?;
+                let texture_coords = (0..count)
+                    .map(|_| {
+                        let values = input.bu32_array::<8>()?.map(f32::from_bits);
+                        Ok([0, 1, 2, 3].map(|i| [values[2 * i], values[2 * i + 1]]))
+                    })
+                    .collect::<Result<_>>()?;
+                PaneKind::Picture(Picture {
+                    vertex_colors,
+                    material,
+                    texture_coords,
+                })
+            },
+            b"txt1" => {
+                let buffer_size = input.bu16()?;
+                let length = input.bu16()? as usize;
+                let material = input.bu16()?;
+                let font = input.bu16()?;
+                let position = input.u8()?;
+                let alignment = input.u8()?;
+                let _padding = input.bu16()?;
+                let text_offset = input.bu32()? as usize;
+                let colors = [input.u8_array::<4>()?, input.u8_array::<4>()?];
+                let values = input.bu32_array::<4>()?.map(f32::from_bits);
+                let text = data.get(text_offset..text_offset + length).ok_or(
+                    ParseProblem::InvalidRange(
+                        "BRLYT text (outside of section)",
+                        Location::current(),
+                    ),
+                )?;
+                PaneKind::Text(Text {
+                    buffer_size,
+                    material,
+                    font,
+                    position,
+                    alignment,
+                    text: Encoding::Utf16Be.first(text)?,
+                    colors,
+                    font_size: [values[0], values[1]],
+                    char_spacing: values[2],
+                    line_spacing: values[3],
+                })
+            },
+            b"wnd1" => PaneKind::Window(data[Seeker::position(&mut input)? as usize..].to_vec()),
+            b"bnd1" => PaneKind::Bounding,
+            _ => PaneKind::Null,
+        };
+
+        Ok(Pane {
+            kind,
+            flags,
+            origin,
+            alpha,
+            name,
+            user_data,
+            translation: [transform[0], transform[1], transform[2]],
+            rotation: [transform[3], transform[4], transform[5]],
+            scale: [transform[6], transform[7]],
+            width: transform[8],
+            height: transform[9],
+            children: Vec::new(),
+        })
+    }
+
+    fn group(data: &[u8]) -> Result<Group> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let name = input.str_fixed::<16, Ascii>()?;
+        let count = input.bu16()?;
+        let _padding = input.bu16()?;
+        let panes = (0..count)
+            .map(|_| input.str_fixed::<16, Ascii>())
+            .collect::<Result<_>>()?;
+        Ok(Group {
+            name,
+            panes,
+            children: Vec::new(),
+        })
+    }
+}
+
+/// Close the innermost level of `stack` (`pae1` and `gre1`), returns the
+/// children of the last node of the parent level.
+fn close<T>(stack: &mut Vec<Vec<T>>) -> Result<Vec<T>> {
+    ensure!(
+        stack.len() > 1 && !stack[stack.len() - 2].is_empty(),
+        ParseProblem::InvalidData("BRLYT hierarchy (unbalanced)", Location::current())
+    );
+    Ok(stack.pop().unwrap())
+}
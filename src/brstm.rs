@@ -0,0 +1,366 @@
+//! BRSTM audio streams (`.brstm`).
+//!
+//! BRSTM is the streamed audio format of the Wii (NintendoWare). A stream
+//! starts with a header pointing to three chunks:
+//!
+//! * `HEAD` - Stream information (codec, sample rate, loop, and block layout)
+//!   and the [DSP-ADPCM][`crate::dsp`] coefficients of each channel.
+//! * `ADPC` - Seek table, the ADPCM history of each channel at the start of
+//!   every block.
+//! * `DATA` - Samples, block interleaved, i.e., every block contains the data
+//!   of each channel one after another.
+//!
+//! [`Brstm::decode`] decodes the whole stream and [`Brstm::decode_from`]
+//! starts at any sample with the history from the seek table.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("stream.brstm")?;
+//!     let brstm = picori::brstm::Brstm::from_binary(&mut file)?;
+//!     let samples = brstm.decode_from(brstm.info.loop_start as usize)?;
+//!     println!("{} samples after the loop start", samples.len());
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::SeekFrom;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::{dsp, Result};
+
+/// Magic of BRSTM streams (`RSTM`).
+pub const MAGIC: u32 = 0x5253544D;
+
+/// Sample format of a BRSTM stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// 8-bit PCM.
+    Pcm8,
+
+    /// 16-bit PCM (big-endian).
+    Pcm16,
+
+    /// 4-bit [DSP-ADPCM][`crate::dsp`].
+    Adpcm,
+}
+
+/// Stream information of the `HEAD` chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Sample format.
+    pub codec: Codec,
+
+    /// Loop flag, `true` if the stream loops.
+    pub looping: bool,
+
+    /// Number of channels.
+    pub channels: u8,
+
+    /// Sample rate.
+    pub sample_rate: u16,
+
+    /// Loop start (sample).
+    pub loop_start: u32,
+
+    /// Number of samples (per channel).
+    pub sample_count: u32,
+
+    /// Offset of the samples from the start of the stream.
+    pub data_offset: u32,
+
+    /// Number of blocks.
+    pub block_count: u32,
+
+    /// Size of a block (per channel).
+    pub block_size: u32,
+
+    /// Number of samples of a block.
+    pub block_samples: u32,
+
+    /// Size of the last block (per channel), without padding.
+    pub final_block_size: u32,
+
+    /// Number of samples of the last block.
+    pub final_block_samples: u32,
+
+    /// Size of the last block (per channel), with padding.
+    pub final_block_padded_size: u32,
+
+    /// Number of samples of a seek table entry.
+    pub seek_samples: u32,
+
+    /// Size of a seek table entry.
+    pub seek_bytes: u32,
+}
+
+/// ADPCM information of a channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelInfo {
+    /// Coefficients (8 pairs).
+    pub coefficients: [i16; 16],
+
+    /// Gain (unused).
+    pub gain: u16,
+
+    /// Initial predictor and scale.
+    pub predictor_scale: u16,
+
+    /// Initial history (previous and second previous sample).
+    pub history: [i16; 2],
+
+    /// Predictor and scale at the loop start.
+    pub loop_predictor_scale: u16,
+
+    /// History at the loop start.
+    pub loop_history: [i16; 2],
+}
+
+/// BRSTM audio stream.
+#[derive(Debug, Clone)]
+pub struct Brstm {
+    /// Stream information.
+    pub info: StreamInfo,
+
+    /// ADPCM information of each channel.
+    pub channels: Vec<ChannelInfo>,
+
+    /// ADPCM history of each channel at the start of each block.
+    pub seek_table: Vec<Vec<[i16; 2]>>,
+
+    /// Samples (block interleaved).
+    pub data: Vec<u8>,
+}
+
+/// Read the offset of a reference (`0x01000000` followed by the offset).
+fn reference<D: Parser>(input: &mut D) -> Result<u64> {
+    let _kind = input.bu32()?;
+    Ok(input.bu32()? as u64)
+}
+
+impl Brstm {
+    /// Parse BRSTM stream from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected RSTM", Location::current())
+        );
+        let bom = input.bu16()?;
+        ensure!(
+            bom == 0xFEFF,
+            ParseProblem::InvalidHeader("expected big-endian BOM", Location::current())
+        );
+        let _version = input.bu16()?;
+        let _file_size = input.bu32()?;
+        let _header_size = input.bu16()?;
+        let _chunk_count = input.bu16()?;
+        let [head_offset, _, adpc_offset, _, data_offset, _] = input.bu32_array::<6>()?;
+
+        // HEAD: stream information, track information, channel information
+        input.goto(base + head_offset as u64)?;
+        let magic = input.bu32()?;
+        ensure!(
+            magic == 0x48454144,
+            ParseProblem::InvalidMagic("expected HEAD", Location::current())
+        );
+        let _size = input.bu32()?;
+        let head = base + head_offset as u64 + 8;
+        let stream_offset = reference(input)?;
+        let _track_offset = reference(input)?;
+        let channel_offset = reference(input)?;
+
+        input.goto(head + stream_offset)?;
+        let info = StreamInfo {
+            codec: match input.u8()? {
+                0 => Codec::Pcm8,
+                1 => Codec::Pcm16,
+                2 => Codec::Adpcm,
+                _ => Err(ParseProblem::InvalidData(
+                    "unknown BRSTM codec",
+                    Location::current(),
+                ))?,
+            },
+            looping: input.u8()? != 0,
+            channels: input.u8()?,
+            sample_rate: {
+                let _padding = input.u8()?;
+                input.bu16()?
+            },
+            loop_start: {
+                let _padding = input.bu16()?;
+                input.bu32()?
+            },
+            sample_count: input.bu32()?,
+            data_offset: input.bu32()?,
+            block_count: input.bu32()?,
+            block_size: input.bu32()?,
+            block_samples: input.bu32()?,
+            final_block_size: input.bu32()?,
+            final_block_samples: input.bu32()?,
+            final_block_padded_size: input.bu32()?,
+            seek_samples: input.bu32()?,
+            seek_bytes: input.bu32()?,
+        };
+        let count = info.channels as usize;
+        ensure!(
+            count > 0 && info.block_count > 0,
+            ParseProblem::InvalidRange("BRSTM channels or blocks", Location::current())
+        );
+
+        input.goto(head + channel_offset)?;
+        ensure!(
+            input.u8()? as usize == count,
+            ParseProblem::InvalidData("BRSTM channel count", Location::current())
+        );
+        let _padding = input.u8_array::<3>()?;
+        let offsets = (0..count)
+            .map(|_| reference(input))
+            .collect::<Result<Vec<_>>>()?;
+        let mut channels = Vec::with_capacity(count);
+        for offset in offsets {
+            input.goto(head + offset)?;
+            let adpcm_offset = reference(input)?;
+            input.goto(head + adpcm_offset)?;
+            channels.push(ChannelInfo {
+                coefficients: input.bu16_array::<16>()?.map(|x| x as i16),
+                gain: input.bu16()?,
+                predictor_scale: input.bu16()?,
+                history: input.bu16_array::<2>()?.map(|x| x as i16),
+                loop_predictor_scale: input.bu16()?,
+                loop_history: input.bu16_array::<2>()?.map(|x| x as i16),
+            });
+        }
+
+        // ADPC: history of each channel at the start of each block
+        let mut seek_table = Vec::new();
+        if info.codec == Codec::Adpcm {
+            input.goto(base + adpc_offset as u64)?;
+            let magic = input.bu32()?;
+            ensure!(
+                magic == 0x41445043,
+                ParseProblem::InvalidMagic("expected ADPC", Location::current())
+            );
+            let _size = input.bu32()?;
+            for _ in 0..info.block_count {
+                let entry = (0..count)
+                    .map(|_| Ok(input.bu16_array::<2>()?.map(|x| x as i16)))
+                    .collect::<Result<Vec<_>>>()?;
+                seek_table.push(entry);
+            }
+        }
+
+        // DATA: samples of each block
+        input.goto(base + data_offset as u64)?;
+        let magic = input.bu32()?;
+        ensure!(
+            magic == 0x44415441,
+            ParseProblem::InvalidMagic("expected DATA", Location::current())
+        );
+        let end = input.seek(SeekFrom::End(0))?;
+        let start = base + info.data_offset as u64;
+        let size = (info.block_count as usize - 1)
+            .checked_mul(info.block_size as usize)
+            .and_then(|x| x.checked_add(info.final_block_padded_size as usize))
+            .and_then(|x| x.checked_mul(count))
+            .filter(|x| start.saturating_add(*x as u64) <= end)
+            .ok_or(ParseProblem::InvalidRange(
+                "BRSTM samples (outside of input)",
+                Location::current(),
+            ))?;
+        input.goto(start)?;
+        let mut data = vec![0; size];
+        input.read_into(&mut data)?;
+
+        Ok(Self {
+            info,
+            channels,
+            seek_table,
+            data,
+        })
+    }
+
+    /// Get the data of `channel` of `block`. Returns the data and the number
+    /// of samples.
+    pub fn block(&self, block: usize, channel: usize) -> Option<(&[u8], usize)> {
+        let info = &self.info;
+        let count = info.channels as usize;
+        if block >= info.block_count as usize || channel >= count {
+            return None;
+        }
+
+        let start = block * info.block_size as usize * count;
+        let (size, samples) = if block + 1 == info.block_count as usize {
+            (info.final_block_padded_size, info.final_block_samples)
+        } else {
+            (info.block_size, info.block_samples)
+        };
+        let start = start + channel * size as usize;
+        Some((&self.data[start..start + size as usize], samples as usize))
+    }
+
+    /// Decode the stream to PCM16 samples (interleaved).
+    pub fn decode(&self) -> Result<Vec<i16>> { self.decode_from(0) }
+
+    /// Decode the stream from the sample `start` to the end to PCM16 samples
+    /// (interleaved). Decoding starts at the block of `start` with the ADPCM
+    /// history of the seek table.
+    pub fn decode_from(&self, start: usize) -> Result<Vec<i16>> {
+        let info = &self.info;
+        let count = info.channels as usize;
+        let samples = info.sample_count as usize;
+        ensure!(
+            start <= samples && info.block_samples > 0,
+            ParseProblem::InvalidRange("BRSTM sample", Location::current())
+        );
+
+        let first = start / info.block_samples as usize;
+        let mut decoded = vec![Vec::new(); count];
+        for (channel, output) in decoded.iter_mut().enumerate() {
+            let mut history = match first {
+                0 => self.channels[channel].history,
+                _ => self.seek_table.get(first).map_or([0, 0], |x| x[channel]),
+            };
+            for block in first..info.block_count as usize {
+                let (data, block_samples) = self.block(block, channel).unwrap();
+                match info.codec {
+                    Codec::Adpcm => {
+                        let coefficients = &self.channels[channel].coefficients;
+                        history = dsp::decode_adpcm_into(
+                            data,
+                            coefficients,
+                            history,
+                            block_samples,
+                            output,
+                        )?;
+                    },
+                    Codec::Pcm16 => output.extend(
+                        data.chunks_exact(2)
+                            .take(block_samples)
+                            .map(|x| i16::from_be_bytes([x[0], x[1]])),
+                    ),
+                    Codec::Pcm8 => output.extend(
+                        data.iter()
+                            .take(block_samples)
+                            .map(|x| (*x as i8 as i16) << 8),
+                    ),
+                }
+            }
+        }
+
+        let skip = start - first * info.block_samples as usize;
+        let end = samples - first * info.block_samples as usize;
+        ensure!(
+            decoded.iter().all(|x| x.len() >= end),
+            ParseProblem::InvalidRange("BRSTM sample count", Location::current())
+        );
+        Ok((skip..end)
+            .flat_map(|i| decoded.iter().map(move |x| x[i]))
+            .collect())
+    }
+}
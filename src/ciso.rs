@@ -38,12 +38,22 @@
 use std::io::Write;
 use std::panic::Location;
 
-use crate::helper::{ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
 use crate::Result;
 
 /// [CISO][`crate::ciso`] magic number representing the four characters "CISO".
 static MAGIC: u32 = 0x4349534F;
 
+/// Size of the [CISO][`crate::ciso`] header.
+const HEADER_SIZE: usize = 0x8000;
+
+/// Maximum number of blocks in a [CISO][`crate::ciso`] file.
+pub const MAX_BLOCKS: usize = HEADER_SIZE - 8;
+
+/// Default block size used when building [CISO][`crate::ciso`] files.
+pub const DEFAULT_BLOCK_SIZE: usize = 0x200000;
+
 #[derive(Debug)]
 struct Header {
     block_size: usize,
@@ -66,7 +76,7 @@ impl Header {
             .into());
         }
 
-        let block_map = input.u8_array::<{ 0x8000 - 8 }>()?;
+        let block_map = input.u8_array::<MAX_BLOCKS>()?;
         let Some(last_block_index) = block_map
             .iter()
             .enumerate()
@@ -170,3 +180,84 @@ impl<'reader, 'x, D: Parser + Seeker> Iterator for BlockIterator<'reader, 'x, D>
         }
     }
 }
+
+/// Writer for [CISO][`crate::ciso`] files. Data is written block by block,
+/// blocks that only contain zero bytes are omitted. The header is written when
+/// calling [`CisoWriter::finish`].
+pub struct CisoWriter<'writer, W: Writer + Seeker> {
+    writer:     &'writer mut W,
+    base:       u64,
+    block_size: usize,
+    block_map:  Vec<u8>,
+    buffer:     Vec<u8>,
+}
+
+impl<'writer, W: Writer + Seeker> CisoWriter<'writer, W> {
+    /// Create a new [CISO][`crate::ciso`] writer with the given block size.
+    pub fn new(writer: &'writer mut W, block_size: usize) -> Result<Self> {
+        ensure!(
+            block_size > 0 && block_size <= 0x8000000,
+            BuildProblem::InvalidData("0 < block size <= 0x8000000", Location::current())
+        );
+
+        // reserve space for the header
+        let base = writer.position()?;
+        writer.u8_array(&[0; HEADER_SIZE])?;
+        Ok(Self {
+            writer,
+            base,
+            block_size,
+            block_map: Vec::new(),
+            buffer: Vec::with_capacity(block_size),
+        })
+    }
+
+    fn write_block(&mut self) -> Result<()> {
+        ensure!(
+            self.block_map.len() < MAX_BLOCKS,
+            BuildProblem::InvalidData("too many blocks", Location::current())
+        );
+
+        if self.buffer.iter().all(|x| *x == 0) {
+            self.block_map.push(0);
+        } else {
+            self.buffer.resize(self.block_size, 0);
+            self.writer.u8_array(&self.buffer)?;
+            self.block_map.push(1);
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Write data to the [CISO][`crate::ciso`] file.
+    pub fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        let mut data = data;
+        while !data.is_empty() {
+            let length = data.len().min(self.block_size - self.buffer.len());
+            self.buffer.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if self.buffer.len() == self.block_size {
+                self.write_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the last (partial) block and the header. Trailing zero blocks
+    /// are not stored, as the header has no notion of the total size.
+    pub fn finish(mut self) -> Result<()> {
+        if !self.buffer.is_empty() {
+            self.write_block()?;
+        }
+
+        let end = self.writer.position()?;
+        let mut block_map = self.block_map;
+        block_map.resize(MAX_BLOCKS, 0);
+        self.writer.goto(self.base)?;
+        self.writer.bu32(MAGIC)?;
+        self.writer.lu32(self.block_size as u32)?;
+        self.writer.u8_array(&block_map)?;
+        self.writer.goto(end)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+//! Traits shared by all compression formats.
+//!
+//! Each format provides a codec type implementing [`Decompress`] and, if the
+//! crate can create the format, [`Compress`]:
+//!
+//! | Format | Codec | Compress |
+//! |--------|-------|----------|
+//! | [Yaz0][crate::yaz0] | [`Yaz0`][crate::yaz0::Yaz0] | yes |
+//! | [Yay0][crate::yay0] | [`Yay0`][crate::yay0::Yay0] | no |
+//! | [LZ10/LZ11][crate::lz] | [`Lz`][crate::lz::Lz] | yes |
+//! | [ASH0][crate::ash] | [`Ash`][crate::ash::Ash] | no |
+//! | [Huffman][crate::huffman] | [`Huffman`][crate::huffman::Huffman] | no |
+//! | [RLE][crate::rle] | [`Rle`][crate::rle::Rle] | yes |
+//!
+//! [`Decompress`] is generic over the reader. Every [`Decompress`] is also a
+//! [`Decompressor`], its object-safe variant working on byte slices, so code
+//! can take `&dyn Decompressor` and users can plug in their own codecs.
+//! [`Compress`] is object-safe itself.
+//!
+//! [`decompress_auto`] detects the format of the data and decompresses it
+//! with the matching codec. With the `parallel` feature, `compress_many`
+//! compresses many files at once using all cores.
+//!
+//! ## Example
+//!
+//! ```
+//! # use picori::Result;
+//! use picori::compression::{Compress, Decompressor};
+//! use picori::{lz, yaz0};
+//!
+//! fn main() -> Result<()> {
+//!     let codecs: [&dyn Decompressor; 2] = [&yaz0::Yaz0::default(), &lz::Lz::default()];
+//!     let compressed = lz::Lz::default().compress(b"abcabcabcabc")?;
+//!     let codec = codecs.iter().find(|x| x.detect(&compressed)).unwrap();
+//!     assert_eq!(codec.name(), "LZ");
+//!     assert_eq!(codec.decompress_bytes(&compressed)?, b"abcabcabcabc");
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Cursor, SeekFrom};
+use std::panic::Location;
+
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::lz::Kind;
+use crate::{ash, huffman, lz, rle, yay0, yaz0, Result};
+
+/// Decompression of a format.
+pub trait Decompress {
+    /// Name of the format.
+    const NAME: &'static str;
+
+    /// Check if the data at the current position of `input` is compressed
+    /// with this format (usually by looking at the magic). The position of
+    /// `input` is not changed.
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool;
+
+    /// Decompresses the data (including the header) at the current position
+    /// of `input` into a new allocated [`Vec`].
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>>;
+
+    /// Decompresses the data (including the header) at the current position
+    /// of `input` into the beginning of `destination`, e.g., to reuse a
+    /// buffer for many files. Returns the number of bytes written.
+    ///
+    /// The default implementation copies the result of
+    /// [`Decompress::decompress`]; the codecs of this crate decompress
+    /// directly into `destination`.
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let data = self.decompress(input)?;
+        ensure!(
+            destination.len() >= data.len(),
+            InvalidDecompressedSize(Location::current())
+        );
+        destination[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+}
+
+/// Compression of a format.
+pub trait Compress {
+    /// Compresses `data` (including the header) into a new allocated [`Vec`].
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Object-safe variant of [`Decompress`], implemented for all [`Decompress`]
+/// types.
+pub trait Decompressor {
+    /// Name of the format.
+    fn name(&self) -> &'static str;
+
+    /// Check if `data` is compressed with this format.
+    fn detect(&self, data: &[u8]) -> bool;
+
+    /// Decompresses `data` (including the header) into a new allocated
+    /// [`Vec`].
+    fn decompress_bytes(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decompresses `data` (including the header) into the beginning of
+    /// `destination`. Returns the number of bytes written.
+    fn decompress_bytes_into(&self, data: &[u8], destination: &mut [u8]) -> Result<usize>;
+}
+
+impl<T: Decompress> Decompressor for T {
+    fn name(&self) -> &'static str { T::NAME }
+
+    fn detect(&self, data: &[u8]) -> bool { self.is_compressed(&mut Cursor::new(data)) }
+
+    fn decompress_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.decompress(&mut Cursor::new(data))
+    }
+
+    fn decompress_bytes_into(&self, data: &[u8], destination: &mut [u8]) -> Result<usize> {
+        self.decompress_into(&mut Cursor::new(data), destination)
+    }
+}
+
+/// Compresses each of `files` with `codec`, using all cores (`parallel`
+/// feature). The results are in the order of `files`.
+#[cfg(feature = "parallel")]
+pub fn compress_many<C: Compress + Sync + ?Sized>(
+    codec: &C,
+    files: &[&[u8]],
+) -> Result<Vec<Vec<u8>>> {
+    use rayon::prelude::*;
+
+    files.par_iter().map(|x| codec.compress(x)).collect()
+}
+
+/// Compression format found by [`decompress_auto`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// [Yaz0][crate::yaz0].
+    Yaz0,
+    /// [Yaz0][crate::yaz0] with the `Yaz1` magic.
+    Yaz1,
+    /// [Yay0][crate::yay0].
+    Yay0,
+    /// [ASH0][crate::ash].
+    Ash0,
+    /// [LZ10/LZ11][crate::lz] preceded by the `LZ77` magic.
+    Lz77(Kind),
+    /// [LZ10/LZ11][crate::lz] with a plain header.
+    Lz(Kind),
+    /// [Huffman][crate::huffman] with 4-bit or 8-bit symbols.
+    Huffman(u32),
+    /// [RLE][crate::rle].
+    Rle,
+}
+
+/// Largest ratio of the decompressed size to the compressed size of the
+/// formats with a plain header (identified by the type byte `id`).
+fn max_ratio(id: u8) -> u64 {
+    match id {
+        // 17 bytes (flags and 8 references) for 8 * 18 bytes
+        0x10 => 9,
+        // 1 bit per (4-bit or 8-bit) symbol
+        0x24 | 0x28 => 8,
+        // 2 bytes for a run of 130 bytes
+        rle::ID => 65,
+        // LZ11 references are up to 0x10110 bytes, no useful bound
+        _ => 256,
+    }
+}
+
+/// Detects the compression format of the data at the current position of
+/// `input` and decompresses it. Returns the format and the decompressed data,
+/// or [`None`] if no format was detected (`input` is left unchanged).
+///
+/// `Yaz0`, `Yaz1`, `Yay0`, `ASH0`, and `LZ77` are detected by their magic;
+/// errors of these formats are returned. Otherwise the type byte of the plain
+/// headers of the LZ, Huffman, and RLE formats is checked. As a single byte is
+/// easily matched by accident, data that fails to decompress with these formats
+/// is treated as not compressed, as is data with a decompressed size the
+/// input is too short for.
+pub fn decompress_auto<D: Parser + Seeker>(input: &mut D) -> Result<Option<(Format, Vec<u8>)>> {
+    let base = input.position()?;
+    let magic = match input.bu32() {
+        Ok(magic) => magic,
+        Err(_) => {
+            input.goto(base)?;
+            return Ok(None);
+        },
+    };
+    input.goto(base)?;
+
+    match magic {
+        yaz0::MAGIC | yaz0::MAGIC_YAZ1 => {
+            let format = match magic {
+                yaz0::MAGIC => Format::Yaz0,
+                _ => Format::Yaz1,
+            };
+            let size = yaz0::Header::decompressed_size(input)?;
+            return Ok(Some((format, yaz0::decompress(input, size)?)));
+        },
+        yay0::MAGIC => {
+            let header = yay0::Header::from_binary(input)?;
+            return Ok(Some((Format::Yay0, yay0::decompress(input, &header)?)));
+        },
+        ash::MAGIC => {
+            let header = ash::Header::from_binary(input)?;
+            return Ok(Some((Format::Ash0, ash::decompress(input, &header)?)));
+        },
+        lz::LZ77_MAGIC => {
+            let header = lz::Header::from_binary(input)?;
+            if let Some(kind) = header.kind {
+                return Ok(Some((Format::Lz77(kind), lz::decompress(input, &header)?)));
+            }
+            input.goto(base)?;
+            return Ok(None);
+        },
+        _ => {},
+    }
+
+    // reject sizes that the remaining input can not decompress to before the
+    // decompressed data is allocated
+    let available = input.seek(SeekFrom::End(0))?.saturating_sub(base);
+    input.goto(base)?;
+    let plausible = |size: u32| size as u64 <= available * max_ratio((magic >> 24) as u8);
+
+    let mut plain = || -> Result<Option<(Format, Vec<u8>)>> {
+        Ok(match (magic >> 24) as u8 {
+            0x10 | 0x11 => {
+                let header = lz::Header::from_binary(input)?;
+                if !plausible(header.decompressed_size) {
+                    return Ok(None);
+                }
+                let kind = header.kind.unwrap();
+                Some((Format::Lz(kind), lz::decompress(input, &header)?))
+            },
+            0x24 | 0x28 => {
+                let header = huffman::Header::from_binary(input)?;
+                if !plausible(header.decompressed_size) {
+                    return Ok(None);
+                }
+                let format = Format::Huffman(header.symbol_bits());
+                Some((format, huffman::decompress(input, &header)?))
+            },
+            rle::ID => {
+                let header = rle::Header::from_binary(input)?;
+                if !plausible(header.decompressed_size) {
+                    return Ok(None);
+                }
+                Some((Format::Rle, rle::decompress(input, &header)?))
+            },
+            _ => None,
+        })
+    };
+
+    match plain() {
+        Ok(Some(result)) => Ok(Some(result)),
+        _ => {
+            input.goto(base)?;
+            Ok(None)
+        },
+    }
+}
@@ -0,0 +1,187 @@
+//! Convert disc images between container formats.
+//!
+//! [`convert`] reads a disc image in any supported [`Format`] (detected by
+//! magic) and writes it in the requested output [`Format`]. The image is
+//! streamed block by block (see [`crate::disc::reader`]), so the whole image
+//! never has to be in memory.
+//!
+//! Supported formats:
+//!
+//! * [`Format::Iso`] - Plain disc image (`.iso`/`.gcm`).
+//! * [`Format::Ciso`] - [Compact ISO][`crate::ciso`].
+//! * [`Format::Wbfs`] - [Wii Backup File System][`crate::wbfs`].
+//! * [`Format::Gcz`] - [Dolphin GCZ][`crate::gcz`] (requires the `gcz`
+//!   feature).
+//!
+//! RVZ/WIA images are not supported.
+//!
+//! [WBFS][`crate::wbfs`] has no stored image size, an image converted from
+//! [WBFS][`crate::wbfs`] ends with the last used WBFS sector.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! # use picori::disc::Format;
+//! fn main() -> Result<()> {
+//!     let mut input = File::open("game.ciso")?;
+//!     let mut output = File::create("game.iso")?;
+//!     picori::disc::convert_with_progress(
+//!         &mut input,
+//!         &mut output,
+//!         Format::Iso,
+//!         |done, total| {
+//!             println!("{}/{}", done, total);
+//!         },
+//!     )?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::ciso::{self, CisoWriter};
+use crate::disc::reader::open_reader;
+use crate::disc::DiscKind;
+use crate::wbfs::{self, WbfsWriter};
+use crate::helper::{Parser, Seeker, Writer};
+use crate::Result;
+
+/// Disc image container format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Plain disc image.
+    Iso,
+
+    /// [Compact ISO][`crate::ciso`].
+    Ciso,
+
+    /// [Wii Backup File System][`crate::wbfs`].
+    Wbfs,
+
+    /// [Dolphin GCZ][`crate::gcz`].
+    #[cfg(feature = "gcz")]
+    Gcz,
+}
+
+impl Format {
+    /// Detect the format of the disc image by its magic. Images without a
+    /// known magic are assumed to be plain images. The input position is
+    /// restored afterwards.
+    pub fn detect<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let position = input.position()?;
+        let mut magic = [0u8; 4];
+        let length = input.read(&mut magic)?;
+        input.goto(position)?;
+
+        Ok(match (length, magic) {
+            (4, [b'C', b'I', b'S', b'O']) => Format::Ciso,
+            (4, [b'W', b'B', b'F', b'S']) => Format::Wbfs,
+            #[cfg(feature = "gcz")]
+            (4, [0x01, 0xC0, 0x0B, 0xB1]) => Format::Gcz,
+            _ => Format::Iso,
+        })
+    }
+}
+
+/// Block by block sink of the output image.
+enum Sink<'writer, W: Writer + Seeker> {
+    Iso(&'writer mut W),
+    Ciso(CisoWriter<'writer, W>),
+    Wbfs(WbfsWriter<'writer, W>),
+    #[cfg(feature = "gcz")]
+    Gcz(crate::gcz::GczWriter<'writer, W>),
+}
+
+impl<'writer, W: Writer + Seeker> Sink<'writer, W> {
+    fn new(
+        writer: &'writer mut W,
+        format: Format,
+        _total_size: u64,
+        _kind: Option<DiscKind>,
+    ) -> Result<Self> {
+        Ok(match format {
+            Format::Iso => Sink::Iso(writer),
+            Format::Ciso => Sink::Ciso(CisoWriter::new(writer, ciso::DEFAULT_BLOCK_SIZE)?),
+            Format::Wbfs => Sink::Wbfs(WbfsWriter::new(writer, wbfs::DEFAULT_SECTOR_SIZE)?),
+            #[cfg(feature = "gcz")]
+            Format::Gcz => Sink::Gcz(crate::gcz::GczWriter::new(
+                writer,
+                // sub type of the header, `1` for Wii images
+                (_kind == Some(DiscKind::Wii)) as u32,
+                _total_size,
+                crate::gcz::DEFAULT_BLOCK_SIZE,
+            )?),
+        })
+    }
+
+    fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Sink::Iso(writer) => writer.u8_array(data),
+            Sink::Ciso(writer) => writer.write_data(data),
+            Sink::Wbfs(writer) => writer.write_data(data),
+            #[cfg(feature = "gcz")]
+            Sink::Gcz(writer) => writer.write_data(data),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Sink::Iso(_) => Ok(()),
+            Sink::Ciso(writer) => writer.finish(),
+            Sink::Wbfs(writer) => writer.finish(),
+            #[cfg(feature = "gcz")]
+            Sink::Gcz(writer) => writer.finish(),
+        }
+    }
+}
+
+/// Convert the disc image `input` (in any supported format) to `format` and
+/// write it to `output`.
+pub fn convert<D, W>(input: &mut D, output: &mut W, format: Format) -> Result<()>
+where
+    D: Parser + Seeker,
+    W: Writer + Seeker,
+{
+    convert_with_progress(input, output, format, |_, _| {})
+}
+
+/// Convert the disc image `input` (in any supported format) to `format` and
+/// write it to `output`. `progress` is called after every block with the number
+/// of bytes processed so far and the total size of the image.
+pub fn convert_with_progress<D, W, F>(
+    input: &mut D,
+    output: &mut W,
+    format: Format,
+    mut progress: F,
+) -> Result<()>
+where
+    D: Parser + Seeker,
+    W: Writer + Seeker,
+    F: FnMut(u64, u64),
+{
+    let mut source = open_reader(input)?;
+    let total_size = source.size();
+    let block_count = total_size.div_ceil(source.block_size());
+
+    // the first block is read before creating the sink to detect the disc kind
+    let mut first = match block_count {
+        0 => None,
+        _ => Some(source.read_block(0)?),
+    };
+    let kind = first.as_deref().and_then(DiscKind::from_header);
+    let mut sink = Sink::new(output, format, total_size, kind)?;
+
+    let mut done = 0;
+    progress(done, total_size);
+    for index in 0..block_count {
+        let data = match first.take() {
+            Some(data) => data,
+            None => source.read_block(index)?,
+        };
+        sink.write_data(&data)?;
+        done += data.len() as u64;
+        progress(done, total_size);
+    }
+
+    sink.finish()
+}
@@ -0,0 +1,143 @@
+//! Hash disc images (requires the `hash` feature).
+//!
+//! Disc image databases (e.g., Redump) identify dumps by their CRC32, MD5, and
+//! SHA-1 digests. [`hash`] calculates all three digests while reading the
+//! image only once. [`hash_parallel`] does the same but updates the digests on
+//! separate threads, which is faster for large images when the input is not the
+//! bottleneck.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.iso")?;
+//!     let digests = picori::disc::hash(&mut file)?;
+//!     println!("crc32: {:08x}", digests.crc32);
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{ErrorKind, Read};
+
+use md5::Md5;
+use sha1::{Digest, Sha1};
+
+use crate::{Error, Result};
+
+/// Size of the chunks the input is read in.
+pub const CHUNK_SIZE: usize = 0x100000;
+
+/// Digests of a disc image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Digests {
+    /// Total number of bytes hashed.
+    pub size: u64,
+
+    /// CRC32 digest.
+    pub crc32: u32,
+
+    /// MD5 digest.
+    pub md5: [u8; 16],
+
+    /// SHA-1 digest.
+    pub sha1: [u8; 20],
+}
+
+/// Incremental hasher calculating all [`Digests`] at once.
+#[derive(Clone, Default)]
+pub struct Hasher {
+    size:  u64,
+    crc32: crc32fast::Hasher,
+    md5:   Md5,
+    sha1:  Sha1,
+}
+
+impl Hasher {
+    /// Create a new hasher.
+    pub fn new() -> Self { Self::default() }
+
+    /// Update all digests with `data`.
+    pub fn update(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        self.crc32.update(data);
+        self.md5.update(data);
+        self.sha1.update(data);
+    }
+
+    /// Update all digests with `data`, each on a separate thread.
+    pub fn update_parallel(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        let Self {
+            crc32, md5, sha1, ..
+        } = self;
+        std::thread::scope(|scope| {
+            scope.spawn(|| crc32.update(data));
+            scope.spawn(|| md5.update(data));
+            sha1.update(data);
+        });
+    }
+
+    /// Finish hashing and return the [`Digests`].
+    pub fn finalize(self) -> Digests {
+        Digests {
+            size:  self.size,
+            crc32: self.crc32.finalize(),
+            md5:   self.md5.finalize().into(),
+            sha1:  self.sha1.finalize().into(),
+        }
+    }
+}
+
+/// Read the next chunk into `buffer`. Returns the number of bytes read, which
+/// is only less than the buffer size at the end of the input.
+fn read_chunk<R: Read>(input: &mut R, buffer: &mut [u8]) -> Result<usize> {
+    let mut length = 0;
+    while length < buffer.len() {
+        match input.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(n) => length += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(Error::Io(e)),
+        }
+    }
+    Ok(length)
+}
+
+/// Hash the input from the current position until the end and return the
+/// [`Digests`].
+pub fn hash<R: Read>(input: &mut R) -> Result<Digests> {
+    let mut hasher = Hasher::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        let length = read_chunk(input, &mut buffer)?;
+        if length == 0 {
+            break;
+        }
+        hasher.update(&buffer[..length]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Hash the input from the current position until the end and return the
+/// [`Digests`]. The digests are updated in parallel, and the next chunk is read
+/// while the previous one is being hashed.
+pub fn hash_parallel<R: Read>(input: &mut R) -> Result<Digests> {
+    let mut hasher = Hasher::new();
+    let mut current = vec![0u8; CHUNK_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE];
+
+    let mut length = read_chunk(input, &mut current)?;
+    while length > 0 {
+        length = std::thread::scope(|scope| {
+            let data = &current[..length];
+            let hasher = &mut hasher;
+            scope.spawn(move || hasher.update_parallel(data));
+            read_chunk(input, &mut next)
+        })?;
+        std::mem::swap(&mut current, &mut next);
+    }
+
+    Ok(hasher.finalize())
+}
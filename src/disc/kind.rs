@@ -0,0 +1,81 @@
+//! Disc kind detection.
+//!
+//! GameCube and Wii discs are identified by a magic word in the
+//! [`Boot`][`crate::gcm::Boot`] header: `0xC2339F3D` at offset `0x1C` for
+//! GameCube discs and `0x5D1C9EA3` at offset `0x18` for Wii discs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! # use picori::disc::DiscKind;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.iso")?;
+//!     match DiscKind::detect(&mut file)? {
+//!         Some(DiscKind::GameCube) => println!("GameCube disc"),
+//!         Some(DiscKind::Wii) => println!("Wii disc"),
+//!         None => println!("not a disc image"),
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Read;
+
+use crate::gcm::boot::{GAMECUBE_MAGIC, WII_MAGIC};
+use crate::gcm::ConsoleType;
+use crate::helper::{Parser, Seeker};
+use crate::Result;
+
+/// Number of bytes needed to detect the [`DiscKind`].
+pub const DETECT_SIZE: usize = 0x20;
+
+/// Kind of a disc image, determined by its magic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DiscKind {
+    /// Nintendo GameCube disc.
+    GameCube,
+
+    /// Nintendo Wii disc (or decrypted Wii partition data).
+    Wii,
+}
+
+impl DiscKind {
+    /// Detect the [`DiscKind`] from the first [`DETECT_SIZE`] bytes of a disc
+    /// image. Returns `None` if neither magic is present.
+    pub fn from_header(header: &[u8]) -> Option<Self> {
+        let word = |offset: usize| {
+            header
+                .get(offset..offset + 4)
+                .map(|x| u32::from_be_bytes([x[0], x[1], x[2], x[3]]))
+        };
+
+        if word(0x18) == Some(WII_MAGIC) {
+            Some(DiscKind::Wii)
+        } else if word(0x1C) == Some(GAMECUBE_MAGIC) {
+            Some(DiscKind::GameCube)
+        } else {
+            None
+        }
+    }
+
+    /// Detect the [`DiscKind`] of the disc image at the current position of
+    /// `input`. Returns `None` if neither magic is present. The input position
+    /// is restored afterwards.
+    pub fn detect<D: Parser + Seeker>(input: &mut D) -> Result<Option<Self>> {
+        let position = input.position()?;
+        let mut header = Vec::with_capacity(DETECT_SIZE);
+        input.take(DETECT_SIZE as u64).read_to_end(&mut header)?;
+        input.goto(position)?;
+        Ok(Self::from_header(&header))
+    }
+
+    /// Get the [`ConsoleType`] of the disc.
+    pub fn console(&self) -> ConsoleType {
+        match self {
+            DiscKind::GameCube => ConsoleType::GameCube,
+            DiscKind::Wii => ConsoleType::Wii,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Disc image utilities.
+//!
+//! This module contains functionality that works on whole disc images
+//! ([GCM][`crate::gcm`] and [Wii][`crate::wii`]), independent of the format of
+//! the content.
+//!
+//! * [`convert`][mod@convert] - Convert disc images between container formats.
+//! * [`reader`] - Read disc images block by block independent of the container
+//!   format.
+//! * [`kind`] - Detect GameCube and Wii disc images by their magic.
+//! * [`hash`][mod@hash] - Hash disc images (CRC32, MD5, and SHA-1) in a single
+//!   pass (requires the `hash` feature).
+
+pub mod convert;
+#[cfg(feature = "hash")]
+pub mod hash;
+pub mod kind;
+pub mod reader;
+
+#[doc(inline)]
+pub use convert::{convert, convert_with_progress, Format};
+#[doc(inline)]
+#[cfg(feature = "hash")]
+pub use hash::{hash, hash_parallel, Digests};
+#[doc(inline)]
+pub use kind::DiscKind;
+#[doc(inline)]
+pub use reader::{open, open_reader, DiscReader, DiscStream, IsoReader};
@@ -0,0 +1,238 @@
+//! Block addressed disc image readers.
+//!
+//! Disc images come in different containers (plain, [CISO][`crate::ciso`],
+//! [GCZ][`crate::gcz`], [WBFS][`crate::wbfs`]) that all store the image as a
+//! sequence of blocks. [`DiscReader`] abstracts over these backends and
+//! [`DiscStream`] turns any [`DiscReader`] into a seekable stream of the
+//! decompressed image. All parsers that read from a stream (e.g.,
+//! [`Gcm::from_binary`][`crate::Gcm::from_binary`] and
+//! [`Wii::from_binary`][`crate::Wii::from_binary`]) therefore work with every
+//! backend, and [`Gcm::from_disc`][`crate::Gcm::from_disc`] and
+//! [`Wii::from_disc`][`crate::Wii::from_disc`] take a [`DiscReader`] directly.
+//!
+//! Plain images on the host file system, including raw devices (e.g.,
+//! `/dev/sr0`), are read with [`IsoReader`]. RVZ/WIA images are not supported.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.ciso")?;
+//!     let mut stream = picori::disc::open(&mut file)?;
+//!     let gcm = picori::Gcm::from_binary(&mut stream)?;
+//!     println!("{}", gcm.boot().game_name);
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::ciso::CisoReader;
+use crate::disc::Format;
+use crate::helper::{Parser, Reader, Seeker};
+use crate::wbfs::WbfsReader;
+use crate::Result;
+
+/// Block size of [`IsoReader`] (the size of a Wii disc sector).
+pub const ISO_BLOCK_SIZE: u64 = 0x8000;
+
+/// Block addressed reader of a disc image backend.
+pub trait DiscReader {
+    /// Size of the blocks returned by [`DiscReader::read_block`].
+    fn block_size(&self) -> u64;
+
+    /// Total size of the (decompressed) disc image.
+    fn size(&self) -> u64;
+
+    /// Read the block at index `index`. Every block is
+    /// [`DiscReader::block_size`] bytes, except the last block which may be
+    /// truncated to the total size.
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>>;
+}
+
+impl<R: DiscReader + ?Sized> DiscReader for &mut R {
+    fn block_size(&self) -> u64 { (**self).block_size() }
+
+    fn size(&self) -> u64 { (**self).size() }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> { (**self).read_block(index) }
+}
+
+impl<R: DiscReader + ?Sized> DiscReader for Box<R> {
+    fn block_size(&self) -> u64 { (**self).block_size() }
+
+    fn size(&self) -> u64 { (**self).size() }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> { (**self).read_block(index) }
+}
+
+/// [`DiscReader`] for plain disc images (`.iso`/`.gcm`) and raw devices.
+pub struct IsoReader<D: Parser + Seeker> {
+    reader: D,
+    base:   u64,
+    size:   u64,
+}
+
+impl<D: Parser + Seeker> IsoReader<D> {
+    /// Create a new reader for the image starting at the current position of
+    /// `reader` and extending to the end of the stream.
+    pub fn new(mut reader: D) -> Result<Self> {
+        let base = reader.position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.goto(base)?;
+        Ok(Self {
+            reader,
+            base,
+            size: end.saturating_sub(base),
+        })
+    }
+}
+
+impl<D: Parser + Seeker> DiscReader for IsoReader<D> {
+    fn block_size(&self) -> u64 { ISO_BLOCK_SIZE }
+
+    fn size(&self) -> u64 { self.size }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> {
+        let start = index * ISO_BLOCK_SIZE;
+        let length = self.size.saturating_sub(start).min(ISO_BLOCK_SIZE);
+        self.reader.goto(self.base + start)?;
+        self.reader.read_as_vec(length as usize)
+    }
+}
+
+impl<D: Parser + Seeker> DiscReader for CisoReader<'_, D> {
+    fn block_size(&self) -> u64 { CisoReader::block_size(self) as u64 }
+
+    fn size(&self) -> u64 { self.total_size() as u64 }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> {
+        CisoReader::read_block(self, index as usize)
+    }
+}
+
+impl<D: Parser + Seeker> DiscReader for WbfsReader<'_, D> {
+    fn block_size(&self) -> u64 { WbfsReader::block_size(self) as u64 }
+
+    fn size(&self) -> u64 { self.total_size() }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> {
+        WbfsReader::read_block(self, index as usize)
+    }
+}
+
+#[cfg(feature = "gcz")]
+impl<D: Parser + Seeker> DiscReader for crate::gcz::GczReader<'_, D> {
+    fn block_size(&self) -> u64 { crate::gcz::GczReader::block_size(self) as u64 }
+
+    fn size(&self) -> u64 { self.total_size() }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> {
+        crate::gcz::GczReader::read_block(self, index as usize)
+    }
+}
+
+/// Seekable stream over the decompressed image of a [`DiscReader`]. The last
+/// read block is cached.
+pub struct DiscStream<R: DiscReader> {
+    reader:   R,
+    position: u64,
+    cached:   Option<(u64, Vec<u8>)>,
+}
+
+impl<R: DiscReader> DiscStream<R> {
+    /// Create a new stream starting at the beginning of the image.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            position: 0,
+            cached: None,
+        }
+    }
+
+    /// Get a reference to the underlying [`DiscReader`].
+    pub fn get_ref(&self) -> &R { &self.reader }
+
+    /// Consume the stream and return the underlying [`DiscReader`].
+    pub fn into_inner(self) -> R { self.reader }
+
+    fn block(&mut self, index: u64) -> Result<&[u8]> {
+        if self.cached.as_ref().map(|x| x.0) != Some(index) {
+            let data = self.reader.read_block(index)?;
+            self.cached = Some((index, data));
+        }
+
+        Ok(&self.cached.as_ref().unwrap().1)
+    }
+}
+
+impl<R: DiscReader> Read for DiscStream<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.reader.size() || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_size = self.reader.block_size();
+        let index = self.position / block_size;
+        let offset = (self.position % block_size) as usize;
+        let data = self.block(index).map_err(std::io::Error::other)?;
+        if offset >= data.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "disc block is shorter than expected",
+            ));
+        }
+
+        let size = buf.len().min(data.len().saturating_sub(offset));
+        buf[..size].copy_from_slice(&data[offset..offset + size]);
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl<R: DiscReader> Seek for DiscStream<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+            SeekFrom::End(n) => self.reader.size().checked_add_signed(n),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<R: DiscReader> Reader for DiscStream<R> {}
+impl<R: DiscReader> Seeker for DiscStream<R> {}
+impl<R: DiscReader> Parser for DiscStream<R> {}
+
+/// Open the disc image `input` with the [`DiscReader`] backend matching its
+/// [`Format`] (detected by magic).
+pub fn open_reader<'r, D: Parser + Seeker>(input: &'r mut D) -> Result<Box<dyn DiscReader + 'r>> {
+    Ok(match Format::detect(input)? {
+        Format::Iso => Box::new(IsoReader::new(input)?),
+        Format::Ciso => Box::new(CisoReader::new(input)?),
+        Format::Wbfs => Box::new(WbfsReader::new(input)?),
+        #[cfg(feature = "gcz")]
+        Format::Gcz => Box::new(crate::gcz::GczReader::new(input)?),
+    })
+}
+
+/// Open the disc image `input` (in any supported [`Format`]) as a seekable
+/// stream of the decompressed image.
+pub fn open<'r, D: Parser + Seeker>(
+    input: &'r mut D,
+) -> Result<DiscStream<Box<dyn DiscReader + 'r>>> {
+    Ok(DiscStream::new(open_reader(input)?))
+}
@@ -29,6 +29,7 @@ use crate::Result;
 
 /// Dolphin executable header.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     /// Offset of the text sections.
     pub text_offset: [u32; 7],
@@ -60,6 +61,7 @@ pub struct Header {
 
 /// Dolphin executable section kind.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SectionKind {
     /// Text section, e.g. `.init`, `.text`, etc.
     Text,
@@ -74,6 +76,7 @@ pub enum SectionKind {
 
 /// Dolphin executable section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Section {
     /// The kind of section this is (text, data, or bss).
     pub kind: SectionKind,
@@ -96,6 +99,7 @@ pub struct Section {
 
     /// The section data. For `.bss` sections ([`SectionKind::Bss`]), this
     /// will be an empty vector.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
 
     /// The offset of the section in the `Dolphin Executable`.
@@ -109,6 +113,7 @@ pub struct Section {
 /// [`__rom_copy_info`][`RomCopyInfo`] is used to copy each entry from the ROM
 /// to the RAM.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RomCopyInfo {
     /// Read Only Memory (ROM) address of the section.
     pub rom_address: u32,
@@ -122,6 +127,7 @@ pub struct RomCopyInfo {
 
 /// List of [`RomCopyInfo`][`RomCopyInfo`] entries.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RomCopyInfoList {
     /// The offset of `__rom_copy_info` in the `.init` section.
     pub offset: u32,
@@ -139,6 +145,7 @@ pub struct RomCopyInfoList {
 /// startup the [`__bss_init_info`][`BssInitInfo`] is used to zero out the
 /// `.bss` section in RAM.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BssInitInfo {
     /// Random Access Memory (RAM) address of the section.
     pub ram_address: u32,
@@ -149,6 +156,7 @@ pub struct BssInitInfo {
 
 /// List of [`BssInitInfo`][`BssInitInfo`] entries.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BssInitInfoList {
     /// The offset of `__bss_init_info` in the `.init` section.
     pub offset: u32,
@@ -159,6 +167,7 @@ pub struct BssInitInfoList {
 
 /// `.dol` file object.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dol {
     /// Header.
     pub header: Header,
@@ -292,7 +301,7 @@ impl Section {
     }
 
     fn read_data<D: Parser + Seeker>(&mut self, reader: &mut D, base: u64) -> Result<()> {
-        if self.size > 0 && self.offset.is_some() {
+        if let (true, Some(offset)) = (self.size > 0, self.offset) {
             ensure!(
                 self.size <= 0x2000000,
                 ParseProblem::InvalidRange(
@@ -301,7 +310,7 @@ impl Section {
                 )
             );
 
-            reader.goto(base + self.offset.unwrap() as u64)?;
+            reader.goto(base + offset as u64)?;
             self.data = reader.read_as_vec(self.size as usize)?;
         }
 
@@ -345,6 +354,50 @@ impl Section {
     }
 }
 
+/// [`Section`] with an owned name, the `&'static str` of [`Section::name`]
+/// can only be deserialized with [`Section::guess_name`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerdeSection {
+    kind:         SectionKind,
+    name:         String,
+    address:      u32,
+    size:         u32,
+    aligned_size: u32,
+    offset:       Option<u32>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Section {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+
+        let section = SerdeSection::deserialize(deserializer)?;
+        let count = match section.kind {
+            SectionKind::Text => 7,
+            SectionKind::Data => 11,
+            SectionKind::Bss => 3,
+        };
+        let name = (0..count)
+            .map(|x| Section::guess_name(section.kind, x))
+            .find(|x| *x == section.name)
+            .ok_or_else(|| {
+                D::Error::invalid_value(Unexpected::Str(&section.name), &"DOL section name")
+            })?;
+        Ok(Self {
+            kind: section.kind,
+            name,
+            address: section.address,
+            size: section.size,
+            aligned_size: section.aligned_size,
+            data: Vec::new(),
+            offset: section.offset,
+        })
+    }
+}
+
 impl Dol {
     /// Parse [`Dol`] from binary stream.
     ///
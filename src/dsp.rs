@@ -0,0 +1,251 @@
+//! DSP-ADPCM audio (`.dsp`).
+//!
+//! DSP-ADPCM is the audio codec of the GameCube and Wii DSP and the base of
+//! most sound data, e.g., sound effects and [streams][crate::brstm]. Samples
+//! are encoded in frames of 8 bytes, a header (coefficient index and scale)
+//! and 14 4-bit samples, see [`decode_adpcm`].
+//!
+//! A `.dsp` file is a header (one channel) followed by the ADPCM data.
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 4    | Number of samples                             |
+//! | `0x04` | 4    | Number of nibbles (including frame headers)   |
+//! | `0x08` | 4    | Sample rate                                   |
+//! | `0x0C` | 2    | Loop flag                                     |
+//! | `0x0E` | 2    | Format (`0` for ADPCM)                        |
+//! | `0x10` | 4    | Loop start (nibble address)                   |
+//! | `0x14` | 4    | Loop end (nibble address)                     |
+//! | `0x18` | 4    | Current address (nibble address)              |
+//! | `0x1C` | 32   | Coefficients (8 pairs)                        |
+//! | `0x3C` | 2    | Gain                                          |
+//! | `0x3E` | 2    | Initial predictor and scale                   |
+//! | `0x40` | 4    | Initial history                               |
+//! | `0x44` | 2    | Loop predictor and scale                      |
+//! | `0x46` | 4    | Loop history                                  |
+//! | `0x4A` | 22   | Padding                                       |
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("sound.dsp")?;
+//!     let dsp = picori::dsp::Dsp::from_binary(&mut file)?;
+//!     let samples = dsp.decode()?;
+//!     println!("{} samples at {} Hz", samples.len(), dsp.header.sample_rate);
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Read;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation};
+use crate::Result;
+
+/// Number of samples of an ADPCM frame.
+pub const SAMPLES_PER_FRAME: usize = 14;
+
+/// Size of an ADPCM frame.
+pub const BYTES_PER_FRAME: usize = 8;
+
+/// DSP-ADPCM header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Number of samples.
+    pub sample_count: u32,
+
+    /// Number of nibbles, including the frame headers.
+    pub nibble_count: u32,
+
+    /// Sample rate.
+    pub sample_rate: u32,
+
+    /// Loop flag, `true` if the sound loops.
+    pub looping: bool,
+
+    /// Format, `0` for ADPCM.
+    pub format: u16,
+
+    /// Loop start (nibble address).
+    pub loop_start_address: u32,
+
+    /// Loop end (nibble address).
+    pub loop_end_address: u32,
+
+    /// Current (start) address (nibble address).
+    pub current_address: u32,
+
+    /// Coefficients (8 pairs).
+    pub coefficients: [i16; 16],
+
+    /// Gain (unused).
+    pub gain: u16,
+
+    /// Initial predictor and scale.
+    pub predictor_scale: u16,
+
+    /// Initial history (previous and second previous sample).
+    pub history: [i16; 2],
+
+    /// Predictor and scale at the loop start.
+    pub loop_predictor_scale: u16,
+
+    /// History at the loop start.
+    pub loop_history: [i16; 2],
+}
+
+/// Convert the nibble address `address` to a sample index.
+fn address_to_sample(address: u32) -> u32 {
+    let frame = address / 16;
+    let nibble = (address % 16).max(2) - 2;
+    frame * SAMPLES_PER_FRAME as u32 + nibble
+}
+
+impl Header {
+    /// Parse DSP-ADPCM header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let sample_count = input.bu32()?;
+        let nibble_count = input.bu32()?;
+        let sample_rate = input.bu32()?;
+        let looping = input.bu16()? != 0;
+        let format = input.bu16()?;
+        ensure!(
+            format == 0,
+            ParseProblem::InvalidData("unsupported DSP format (not ADPCM)", Location::current())
+        );
+        let loop_start_address = input.bu32()?;
+        let loop_end_address = input.bu32()?;
+        let current_address = input.bu32()?;
+        let coefficients = input.bu16_array::<16>()?.map(|x| x as i16);
+        let gain = input.bu16()?;
+        let predictor_scale = input.bu16()?;
+        let history = input.bu16_array::<2>()?.map(|x| x as i16);
+        let loop_predictor_scale = input.bu16()?;
+        let loop_history = input.bu16_array::<2>()?.map(|x| x as i16);
+        let _padding = input.u8_array::<22>()?;
+
+        Ok(Self {
+            sample_count,
+            nibble_count,
+            sample_rate,
+            looping,
+            format,
+            loop_start_address,
+            loop_end_address,
+            current_address,
+            coefficients,
+            gain,
+            predictor_scale,
+            history,
+            loop_predictor_scale,
+            loop_history,
+        })
+    }
+
+    /// Loop start as a sample index.
+    pub fn loop_start(&self) -> u32 { address_to_sample(self.loop_start_address) }
+
+    /// Loop end as a sample index (inclusive).
+    pub fn loop_end(&self) -> u32 { address_to_sample(self.loop_end_address) }
+}
+
+/// DSP-ADPCM sound (`.dsp`).
+#[derive(Debug, Clone)]
+pub struct Dsp {
+    /// Header.
+    pub header: Header,
+
+    /// ADPCM data.
+    pub data: Vec<u8>,
+}
+
+impl Dsp {
+    /// Parse DSP-ADPCM sound from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let header = Header::from_binary(input)?;
+        // the size is not trusted, the data is read without allocating it first
+        let size = header.nibble_count.div_ceil(2) as usize;
+        let mut data = Vec::new();
+        input.by_ref().take(size as u64).read_to_end(&mut data)?;
+        ensure!(
+            data.len() == size,
+            ParseProblem::InvalidRange("ADPCM data (truncated)", Location::current())
+        );
+        Ok(Self { header, data })
+    }
+
+    /// Decode the sound to PCM16 samples.
+    pub fn decode(&self) -> Result<Vec<i16>> {
+        decode_adpcm(
+            &self.data,
+            &self.header.coefficients,
+            self.header.history,
+            self.header.sample_count as usize,
+        )
+    }
+}
+
+/// Decode `samples` samples of the DSP-ADPCM `data` (one channel) with the
+/// `coefficients` (8 pairs) and the initial `history` (previous and second
+/// previous sample) to PCM16.
+pub fn decode_adpcm(
+    data: &[u8],
+    coefficients: &[i16; 16],
+    history: [i16; 2],
+    samples: usize,
+) -> Result<Vec<i16>> {
+    check_size(data, samples)?;
+    let mut output = Vec::with_capacity(samples);
+    decode_adpcm_into(data, coefficients, history, samples, &mut output)?;
+    Ok(output)
+}
+
+/// Check that `data` has at least `samples` samples.
+fn check_size(data: &[u8], samples: usize) -> Result<()> {
+    if samples > 0 {
+        let last = samples - 1;
+        let size = last / SAMPLES_PER_FRAME * BYTES_PER_FRAME + 2 + (last % SAMPLES_PER_FRAME) / 2;
+        ensure!(
+            size <= data.len(),
+            ParseProblem::InvalidRange("ADPCM data (too small)", Location::current())
+        );
+    }
+    Ok(())
+}
+
+/// Decode `samples` samples of the DSP-ADPCM `data` to `output`, see
+/// [`decode_adpcm`]. Returns the history after the last sample, i.e., the
+/// history to continue decoding the next block of the same channel.
+pub fn decode_adpcm_into(
+    data: &[u8],
+    coefficients: &[i16; 16],
+    history: [i16; 2],
+    samples: usize,
+    output: &mut Vec<i16>,
+) -> Result<[i16; 2]> {
+    check_size(data, samples)?;
+
+    // computed in `i64`, the sum overflows `i32` for large coefficients and scales
+    let [mut hist1, mut hist2] = history.map(|x| x as i64);
+    for i in 0..samples {
+        let frame = &data[i / SAMPLES_PER_FRAME * BYTES_PER_FRAME..];
+        let index = ((frame[0] >> 4) & 0x07) as usize;
+        let scale = 1_i64 << (frame[0] & 0x0F);
+        let byte = frame[1 + (i % SAMPLES_PER_FRAME) / 2];
+        let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+        let nibble = (((nibble << 4) as i8) >> 4) as i64;
+
+        let coefficient1 = coefficients[index * 2] as i64;
+        let coefficient2 = coefficients[index * 2 + 1] as i64;
+        let sample = ((nibble * scale) << 11) + 1024 + coefficient1 * hist1 + coefficient2 * hist2;
+        let sample = (sample >> 11).clamp(-0x8000, 0x7FFF);
+        hist2 = hist1;
+        hist1 = sample;
+        output.push(sample as i16);
+    }
+
+    Ok([hist1 as i16, hist2 as i16])
+}
@@ -0,0 +1,295 @@
+//! DZB collision meshes (`.dzb`).
+//!
+//! DZB is the collision format of The Legend of Zelda: The Wind Waker (and
+//! other games based on the same engine), stored as `dzb/room.dzb` in the
+//! stage and room archives. The header is a table of counts and offsets of
+//! the blocks:
+//!
+//! | Block      | Entry size | Description                                             |
+//! |------------|------------|---------------------------------------------------------|
+//! | Vertices   | `0x0C`     | Positions (XYZ)                                         |
+//! | Triangles  | `0x0A`     | [Triangles][`Triangle`] (vertices, property, and group) |
+//! | Blocks     | `0x02`     | First triangle of the blocks of the octree leaves       |
+//! | Octree     | `0x14`     | [Octree nodes][`OctreeNode`]                            |
+//! | Groups     | `0x34`     | [Groups][`Group`] (named parts of the mesh)             |
+//! | Properties | `0x10`     | [Properties][`Property`] (sound, camera, exit, ...)     |
+//!
+//! Use [`Dzb::triangles`] to iterate over the triangles with their positions,
+//! property, and group, e.g., to visualize the collision.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("room.dzb")?;
+//!     let dzb = picori::dzb::Dzb::from_binary(&mut file)?;
+//!     for triangle in dzb.triangles() {
+//!         println!("{:?} {:08x?}", triangle.positions, triangle.property.words);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::{Result, ShiftJis1997};
+
+/// Size of the header.
+pub const HEADER_SIZE: usize = 0x34;
+
+/// Triangle of the collision mesh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Triangle {
+    /// Indices of the vertices.
+    pub vertices: [u16; 3],
+
+    /// Index of the property.
+    pub property: u16,
+
+    /// Index of the group.
+    pub group: u16,
+}
+
+/// Node of the octree (spatial index of the triangles).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OctreeNode {
+    /// Flags, bit 0 is set for leaves.
+    pub flags: u8,
+
+    /// Index of the parent node, `0xFFFF` for the root.
+    pub parent: u16,
+
+    /// Indices of the child nodes (`0xFFFF` for none), for leaves the first
+    /// entry is the index of the block.
+    pub children: [u16; 8],
+}
+
+impl OctreeNode {
+    /// Returns `true` if the node is a leaf.
+    pub fn is_leaf(&self) -> bool { self.flags & 1 != 0 }
+}
+
+/// Group, a named part of the mesh (e.g., a room or a moving platform).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// Name of the group.
+    pub name: String,
+
+    /// Scale.
+    pub scale: [f32; 3],
+
+    /// Rotation (`0x10000` is a full turn).
+    pub rotation: [u16; 3],
+
+    /// Translation.
+    pub translation: [f32; 3],
+
+    /// Index of the parent group, `0xFFFF` for the root.
+    pub parent: u16,
+
+    /// Index of the next sibling group, `0xFFFF` for none.
+    pub next_sibling: u16,
+
+    /// Index of the first child group, `0xFFFF` for none.
+    pub first_child: u16,
+
+    /// Room of the group, `0xFFFF` for none.
+    pub room: u16,
+
+    /// Index of the first vertex of the group.
+    pub first_vertex: u16,
+
+    /// Index of the root octree node of the group, `0xFFFF` for none.
+    pub octree: u16,
+
+    /// Terrain and room table flags.
+    pub flags: u32,
+}
+
+/// Property of a triangle, bit fields with the exit, the camera, the sound
+/// (e.g., grass or stone), the ground code (e.g., lava or ladder), and more.
+/// The meaning of the bits is game specific.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Bit fields.
+    pub words: [u32; 4],
+}
+
+/// Triangle with its positions, property, and group ([`Dzb::triangles`]).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CollisionTriangle<'a> {
+    /// Index of the triangle.
+    pub index: usize,
+
+    /// Positions of the vertices.
+    pub positions: [[f32; 3]; 3],
+
+    /// Property.
+    pub property: &'a Property,
+
+    /// Group, `None` if the index is out of range.
+    pub group: Option<&'a Group>,
+}
+
+/// DZB collision mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dzb {
+    /// Positions of the vertices.
+    pub vertices: Vec<[f32; 3]>,
+
+    /// Triangles.
+    pub triangles: Vec<Triangle>,
+
+    /// First triangle of the blocks (referenced by the octree leaves), a
+    /// block ends at the first triangle of the next block.
+    pub blocks: Vec<u16>,
+
+    /// Octree nodes.
+    pub octree: Vec<OctreeNode>,
+
+    /// Groups.
+    pub groups: Vec<Group>,
+
+    /// Properties.
+    pub properties: Vec<Property>,
+}
+
+impl Dzb {
+    /// Parse DZB collision mesh from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let header = input.bu32_array::<12>()?;
+        let table = |index: usize| (header[2 * index], base + header[2 * index + 1] as u64);
+
+        let (count, offset) = table(0);
+        input.goto(offset)?;
+        let vertices = (0..count)
+            .map(|_| Ok(input.bu32_array::<3>()?.map(f32::from_bits)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let (count, offset) = table(1);
+        input.goto(offset)?;
+        let triangles = (0..count)
+            .map(|_| {
+                let vertices = input.bu16_array::<3>()?;
+                let property = input.bu16()?;
+                let group = input.bu16()?;
+                Ok(Triangle {
+                    vertices,
+                    property,
+                    group,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (count, offset) = table(2);
+        input.goto(offset)?;
+        let blocks = (0..count)
+            .map(|_| input.bu16())
+            .collect::<Result<Vec<_>>>()?;
+
+        let (count, offset) = table(3);
+        input.goto(offset)?;
+        let octree = (0..count)
+            .map(|_| {
+                let flags = input.u8()?;
+                let _padding = input.u8()?;
+                let parent = input.bu16()?;
+                let children = input.bu16_array::<8>()?;
+                Ok(OctreeNode {
+                    flags,
+                    parent,
+                    children,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (count, offset) = table(4);
+        let mut groups = Vec::with_capacity(count as usize);
+        for i in 0..count as u64 {
+            input.goto(offset + i * 0x34)?;
+            let name_offset = input.bu32()?;
+            let scale = input.bu32_array::<3>()?.map(f32::from_bits);
+            let rotation = input.bu16_array::<3>()?;
+            let _padding = input.bu16()?;
+            let translation = input.bu32_array::<3>()?.map(f32::from_bits);
+            let [parent, next_sibling, first_child, room, first_vertex, octree] =
+                input.bu16_array::<6>()?;
+            let flags = input.bu32()?;
+            input.goto(base + name_offset as u64)?;
+            let name = input.str::<ShiftJis1997>()?;
+            groups.push(Group {
+                name,
+                scale,
+                rotation,
+                translation,
+                parent,
+                next_sibling,
+                first_child,
+                room,
+                first_vertex,
+                octree,
+                flags,
+            });
+        }
+
+        let (count, offset) = table(5);
+        input.goto(offset)?;
+        let properties = (0..count)
+            .map(|_| {
+                Ok(Property {
+                    words: input.bu32_array::<4>()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for triangle in triangles.iter() {
+            ensure!(
+                triangle
+                    .vertices
+                    .iter()
+                    .all(|x| (*x as usize) < vertices.len()),
+                ParseProblem::InvalidData("DZB triangle vertex index", Location::current())
+            );
+            ensure!(
+                (triangle.property as usize) < properties.len(),
+                ParseProblem::InvalidData("DZB triangle property index", Location::current())
+            );
+        }
+
+        Ok(Self {
+            vertices,
+            triangles,
+            blocks,
+            octree,
+            groups,
+            properties,
+        })
+    }
+
+    /// Iterate over the triangles with their positions, property, and group.
+    pub fn triangles(&self) -> impl Iterator<Item = CollisionTriangle<'_>> {
+        self.triangles
+            .iter()
+            .enumerate()
+            .map(|(index, triangle)| CollisionTriangle {
+                index,
+                positions: triangle.vertices.map(|x| self.vertices[x as usize]),
+                property: &self.properties[triangle.property as usize],
+                group: self.groups.get(triangle.group as usize),
+            })
+    }
+
+    /// Get the indices of the triangles of the block `index`.
+    pub fn block(&self, index: usize) -> Option<std::ops::Range<usize>> {
+        let start = *self.blocks.get(index)? as usize;
+        let end = self
+            .blocks
+            .get(index + 1)
+            .map_or(self.triangles.len(), |x| *x as usize);
+        Some(start..end.max(start))
+    }
+}
@@ -0,0 +1,326 @@
+//! DZR and DZS stage data (`.dzr` and `.dzs`).
+//!
+//! DZR (room) and DZS (stage) files of The Legend of Zelda: The Wind Waker
+//! (and other games based on the same engine) place the entities of the
+//! stages. Both use the same format: a chunk count followed by the chunk
+//! headers (magic, number of entries, and offset of the entries).
+//!
+//! The entries of the following chunks are parsed into [typed
+//! records][`Entities`], the entries of all other chunks (e.g., paths,
+//! cameras, and lighting) are kept as raw data:
+//!
+//! | Chunks                                | Entry size | Records         |
+//! |---------------------------------------|------------|-----------------|
+//! | `ACTR`, `ACT0`-`ACTb`, `PLYR`, `TGOB` | `0x20`     | [`Actor`]       |
+//! | `SCOB`, `SCO0`-`SCOb`, `TGSC`, `TGDR` | `0x24`     | [`ScaledActor`] |
+//! | `TRES`, `TRE0`-`TREb`                 | `0x20`     | [`Treasure`]    |
+//! | `SCLS`                                | `0x0C`     | [`Exit`]        |
+//!
+//! Chunks ending in `0`-`9`, `a`, or `b` belong to a [layer][`Chunk::layer`]
+//! (loaded depending on the progress of the game).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::dzx::{Dzx, Entities};
+//!
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("room.dzr")?;
+//!     let dzr = Dzx::from_binary(&mut file)?;
+//!     for chunk in dzr.chunks.iter() {
+//!         if let Entities::Actors(actors) = &chunk.entities {
+//!             for actor in actors.iter() {
+//!                 println!("{} {:08x} {:?}", actor.name, actor.params, actor.position);
+//!             }
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::{Ascii, Result};
+
+/// Actor (`ACTR`, `PLYR`, and `TGOB`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Actor {
+    /// Name of the actor.
+    pub name: String,
+
+    /// Parameters (actor specific).
+    pub params: u32,
+
+    /// Position.
+    pub position: [f32; 3],
+
+    /// Rotation (`0x10000` is a full turn), the X and Z rotation are actor
+    /// specific parameters for some actors.
+    pub rotation: [u16; 3],
+
+    /// Enemy number (for enemies), `0xFFFF` for none.
+    pub enemy_number: u16,
+}
+
+impl Actor {
+    fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let name = input.str_fixed::<8, Ascii>()?;
+        let params = input.bu32()?;
+        let position = input.bu32_array::<3>()?.map(f32::from_bits);
+        let rotation = input.bu16_array::<3>()?;
+        let enemy_number = input.bu16()?;
+        Ok(Self {
+            name,
+            params,
+            position,
+            rotation,
+            enemy_number,
+        })
+    }
+}
+
+/// Actor with scale (`SCOB`, `TGSC`, and `TGDR`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaledActor {
+    /// Actor.
+    pub actor: Actor,
+
+    /// Scale (`10` is the original size).
+    pub scale: [u8; 3],
+}
+
+/// Treasure chest (`TRES`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Treasure {
+    /// Name of the actor.
+    pub name: String,
+
+    /// Parameters (actor specific).
+    pub params: u32,
+
+    /// Position.
+    pub position: [f32; 3],
+
+    /// Room of the chest.
+    pub room: u16,
+
+    /// Rotation around the Y axis (`0x10000` is a full turn).
+    pub rotation_y: u16,
+
+    /// Item in the chest.
+    pub item: u8,
+
+    /// Flag set after the chest is opened.
+    pub flag: u8,
+}
+
+/// Exit (`SCLS`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exit {
+    /// Name of the destination stage.
+    pub stage: String,
+
+    /// Spawn point in the destination.
+    pub spawn: u8,
+
+    /// Room of the destination.
+    pub room: u8,
+
+    /// Fade type.
+    pub fade: u8,
+}
+
+/// Entries of a [`Chunk`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entities {
+    /// Actors.
+    Actors(Vec<Actor>),
+
+    /// Actors with scale.
+    ScaledActors(Vec<ScaledActor>),
+
+    /// Treasure chests.
+    Treasures(Vec<Treasure>),
+
+    /// Exits.
+    Exits(Vec<Exit>),
+
+    /// Data of the entries of other chunks (until the data of the next
+    /// chunk).
+    Raw(Vec<u8>),
+}
+
+/// Chunk of DZR or DZS file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Magic, e.g., `ACTR`.
+    pub magic: [u8; 4],
+
+    /// Number of entries.
+    pub count: u32,
+
+    /// Entries.
+    pub entities: Entities,
+}
+
+impl Chunk {
+    /// Get the layer (`0` to `11`) of a layered chunk, e.g., `ACT3`.
+    pub fn layer(&self) -> Option<u8> {
+        let layered = matches!(&self.magic[..3], b"ACT" | b"SCO" | b"TRE");
+        match (layered, self.magic[3]) {
+            (true, x @ b'0'..=b'9') => Some(x - b'0'),
+            (true, x @ b'a'..=b'b') => Some(x - b'a' + 10),
+            _ => None,
+        }
+    }
+}
+
+/// DZR room or DZS stage data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dzx {
+    /// Chunks.
+    pub chunks: Vec<Chunk>,
+}
+
+impl Dzx {
+    /// Parse DZR or DZS file from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let count = input.bu32()?;
+        let headers = (0..count)
+            .map(|_| {
+                let magic = input.u8_array::<4>()?;
+                let count = input.bu32()?;
+                let offset = input.bu32()?;
+                Ok((magic, count, offset))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let end = input.seek(std::io::SeekFrom::End(0))? - base;
+        let mut chunks = Vec::with_capacity(headers.len());
+        for (magic, count, offset) in headers.iter().copied() {
+            ensure!(
+                (offset as u64) <= end,
+                ParseProblem::InvalidRange("DZx chunk offset", Location::current())
+            );
+            input.goto(base + offset as u64)?;
+            let entities = match Self::kind(&magic) {
+                Some(Kind::Actor) => Entities::Actors(
+                    (0..count)
+                        .map(|_| Actor::from_binary(input))
+                        .collect::<Result<_>>()?,
+                ),
+                Some(Kind::ScaledActor) => Entities::ScaledActors(
+                    (0..count)
+                        .map(|_| {
+                            let actor = Actor::from_binary(input)?;
+                            let scale = input.u8_array::<3>()?;
+                            let _padding = input.u8()?;
+                            Ok(ScaledActor { actor, scale })
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                Some(Kind::Treasure) => Entities::Treasures(
+                    (0..count)
+                        .map(|_| {
+                            let name = input.str_fixed::<8, Ascii>()?;
+                            let params = input.bu32()?;
+                            let position = input.bu32_array::<3>()?.map(f32::from_bits);
+                            let room = input.bu16()?;
+                            let rotation_y = input.bu16()?;
+                            let item = input.u8()?;
+                            let flag = input.u8()?;
+                            let _padding = input.bu16()?;
+                            Ok(Treasure {
+                                name,
+                                params,
+                                position,
+                                room,
+                                rotation_y,
+                                item,
+                                flag,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                Some(Kind::Exit) => Entities::Exits(
+                    (0..count)
+                        .map(|_| {
+                            let stage = input.str_fixed::<8, Ascii>()?;
+                            let [spawn, room, fade, _] = input.u8_array::<4>()?;
+                            Ok(Exit {
+                                stage,
+                                spawn,
+                                room,
+                                fade,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                None => {
+                    // the entries end at the data of the next chunk
+                    let next = headers
+                        .iter()
+                        .map(|x| x.2 as u64)
+                        .filter(|x| *x > offset as u64)
+                        .min()
+                        .unwrap_or(end);
+                    let size = match count {
+                        0 => 0,
+                        _ => next - offset as u64,
+                    };
+                    Entities::Raw(input.read_as_vec(size as usize)?)
+                },
+            };
+            chunks.push(Chunk {
+                magic,
+                count,
+                entities,
+            });
+        }
+        Ok(Self { chunks })
+    }
+
+    /// Get the chunks with the magic `magic`.
+    pub fn find_chunks<'a>(&'a self, magic: &'a [u8; 4]) -> impl Iterator<Item = &'a Chunk> {
+        self.chunks.iter().filter(move |x| &x.magic == magic)
+    }
+
+    /// Get all actors (of all layers), including the actors with scale.
+    pub fn actors(&self) -> impl Iterator<Item = &Actor> {
+        self.chunks.iter().flat_map(|x| {
+            let (actors, scaled): (&[Actor], &[ScaledActor]) = match &x.entities {
+                Entities::Actors(actors) => (actors, &[]),
+                Entities::ScaledActors(actors) => (&[], actors),
+                _ => (&[], &[]),
+            };
+            actors.iter().chain(scaled.iter().map(|x| &x.actor))
+        })
+    }
+
+    fn kind(magic: &[u8; 4]) -> Option<Kind> {
+        let layer = matches!(magic[3], b'0'..=b'9' | b'a'..=b'b');
+        match (&magic[..3], magic) {
+            (_, b"ACTR" | b"PLYR" | b"TGOB") => Some(Kind::Actor),
+            (_, b"SCOB" | b"TGSC" | b"TGDR") => Some(Kind::ScaledActor),
+            (_, b"TRES") => Some(Kind::Treasure),
+            (_, b"SCLS") => Some(Kind::Exit),
+            (b"ACT", _) if layer => Some(Kind::Actor),
+            (b"SCO", _) if layer => Some(Kind::ScaledActor),
+            (b"TRE", _) if layer => Some(Kind::Treasure),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of the typed records of a chunk.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    Actor,
+    ScaledActor,
+    Treasure,
+    Exit,
+}
@@ -0,0 +1,984 @@
+//! Runtime selection of string [`Encoding`]s.
+//!
+//! The encoding modules ([ASCII][`crate::Ascii`], [JIS X
+//! 0201][`crate::JisX0201`], [Shift JIS 1997][`crate::ShiftJis1997`], [Shift
+//! JIS 2004][`crate::ShiftJis2004`], [EUC-JP][`crate::EucJp`], [UTF-16
+//! BE][`crate::Utf16Be`], and [Windows-1252][`crate::Windows1252`]) are
+//! selected at compile time. Some formats, e.g., the [FST][`crate::gcm::Fst`]
+//! string table, use different encodings depending on the game and
+//! [`Encoding`] allows choosing the encoding at runtime.
+//!
+//! [`CharReader`] decodes the text read from a [`Read`] with an [`Encoding`],
+//! and [`ErrorPolicy`] chooses how invalid bytes are handled. [`EncodeWriter`]
+//! encodes the text written to it.
+//!
+//! The decoders report where a decoding error occurs with
+//! [`AtOffset`][`crate::error::DecodingProblem::AtOffset`], the byte offset of
+//! the invalid character and the number of valid characters before it.
+//! [`CharOffsets`] yields the byte offset of every decoded character.
+//!
+//! ## Example
+//!
+//! ```
+//! # use picori::Result;
+//! # use picori::Encoding;
+//! fn main() -> Result<()> {
+//!     let name = Encoding::ShiftJis1997.first(b"\x83\x51\x81\x5B\x83\x80.arc\0")?;
+//!     assert_eq!(name, "ゲーム.arc");
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{ErrorKind, Read, Write};
+
+use crate::error::{DecodingProblem, EncodingProblem, ParseProblem};
+#[cfg(feature = "std")]
+use crate::gcm::Region;
+use crate::helper::{ensure, ProblemLocation};
+use crate::{
+    Ascii, Error, EucJp, JisX0201, Result, ShiftJis1997, ShiftJis2004, Utf16Be, Windows1252,
+};
+
+/// How decoders handle invalid bytes, see the `policy` function of the
+/// decoders, e.g., [`crate::shift_jis_1997::Decoder::policy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ErrorPolicy {
+    /// Return the error, the decoder continues after the invalid bytes.
+    #[default]
+    Strict,
+
+    /// Replace the invalid bytes with the character, e.g.,
+    /// [`char::REPLACEMENT_CHARACTER`] (see also [`Lossy`]).
+    Replace(char),
+
+    /// Skip the invalid bytes.
+    Skip,
+
+    /// End the decoding at the first invalid bytes, without an error.
+    StopAtError,
+}
+
+/// Iterator adapter that handles decoding errors according to an
+/// [`ErrorPolicy`].
+pub struct WithPolicy<I> {
+    iter:    I,
+    policy:  ErrorPolicy,
+    stopped: bool,
+}
+
+impl<I> WithPolicy<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    pub(crate) fn new(iter: I, policy: ErrorPolicy) -> Self {
+        Self {
+            iter,
+            policy,
+            stopped: false,
+        }
+    }
+}
+
+impl<I> WithPolicy<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    /// Returns an iterator over the lines, without the line ending (`\n` or
+    /// `\r\n`).
+    pub fn lines(self) -> Lines<Self> { Lines { iter: self } }
+}
+
+impl<I> Iterator for WithPolicy<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Result<char>> {
+        if self.stopped {
+            return None;
+        }
+
+        loop {
+            let error = match self.iter.next()? {
+                Ok(c) => return Some(Ok(c)),
+                Err(error) => error,
+            };
+            match self.policy {
+                ErrorPolicy::Strict => return Some(Err(error)),
+                ErrorPolicy::Replace(c) => return Some(Ok(c)),
+                ErrorPolicy::Skip => continue,
+                ErrorPolicy::StopAtError => {
+                    self.stopped = true;
+                    return None;
+                },
+            }
+        }
+    }
+}
+
+/// Iterator adapter that replaces decoding errors with U+FFFD (REPLACEMENT
+/// CHARACTER), created by the `lossy` function of the decoders, e.g.,
+/// [`crate::shift_jis_1997::Decoder::lossy`].
+pub struct Lossy<I> {
+    iter: I,
+}
+
+impl<I> Lossy<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    pub(crate) fn new(iter: I) -> Self { Self { iter } }
+}
+
+impl<I> Iterator for Lossy<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.iter.next()?;
+        Some(c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+/// Decoders that know the byte offset of the next character in the input.
+pub trait CharOffset {
+    /// The byte offset of the next character.
+    fn char_offset(&self) -> usize;
+}
+
+/// Iterator adapter that yields the byte offset of each character together
+/// with the character, created by the `char_offsets` function of the
+/// decoders, e.g., [`crate::shift_jis_1997::Decoder::char_offsets`]. The
+/// offsets can be used to point back into the input, e.g., to re-insert
+/// translated text.
+pub struct CharOffsets<I> {
+    iter: I,
+}
+
+impl<I> CharOffsets<I>
+where
+    I: Iterator<Item = Result<char>> + CharOffset,
+{
+    pub(crate) fn new(iter: I) -> Self { Self { iter } }
+}
+
+impl<I> Iterator for CharOffsets<I>
+where
+    I: Iterator<Item = Result<char>> + CharOffset,
+{
+    type Item = Result<(usize, char)>;
+
+    fn next(&mut self) -> Option<Result<(usize, char)>> {
+        let offset = self.iter.char_offset();
+        let c = self.iter.next()?;
+        Some(c.map(|c| (offset, c)))
+    }
+}
+
+/// Byte iterator used by the decoders that counts the bytes read and the
+/// characters decoded, to report where decoding errors occur.
+pub(crate) struct Tracked<I> {
+    iter:   I,
+    offset: usize,
+    chars:  usize,
+}
+
+impl<I> Tracked<I> {
+    pub(crate) fn new(iter: I) -> Self {
+        Self {
+            iter,
+            offset: 0,
+            chars: 0,
+        }
+    }
+
+    /// The offset of the next byte.
+    pub(crate) fn offset(&self) -> usize { self.offset }
+
+    /// Counts the decoded character, or adds the position to the decoding
+    /// error of the character starting at `offset`.
+    pub(crate) fn locate(&mut self, offset: usize, result: Result<char>) -> Result<char> {
+        match result {
+            Ok(c) => {
+                self.chars += 1;
+                Ok(c)
+            },
+            Err(e) => Err(at_offset(e, offset, self.chars)),
+        }
+    }
+}
+
+impl<I> Iterator for Tracked<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let byte = self.iter.next()?;
+        self.offset += 1;
+        Some(byte)
+    }
+}
+
+/// Borrows `data` as a string if all bytes are ASCII characters that decode to
+/// themselves, i.e., not one of `excluded`, otherwise decodes it with
+/// `decode`.
+pub(crate) fn decode_cow<'x>(
+    data: &'x [u8],
+    excluded: &[u8],
+    decode: impl FnOnce(&[u8]) -> Result<String>,
+) -> Result<Cow<'x, str>> {
+    if data.iter().all(|x| x.is_ascii() && !excluded.contains(x)) {
+        // ASCII is valid UTF-8
+        Ok(Cow::Borrowed(core::str::from_utf8(data).unwrap()))
+    } else {
+        decode(data).map(Cow::Owned)
+    }
+}
+
+/// Adds the byte `offset` and the number of valid characters before it to a
+/// decoding error.
+fn at_offset(error: Error, offset: usize, chars: usize) -> Error {
+    match error {
+        Error::Decoding(problem) => {
+            DecodingProblem::AtOffset(offset, chars, Box::new(problem)).into()
+        },
+        error => error,
+    }
+}
+
+/// String encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Encoding {
+    /// [ASCII][`crate::Ascii`].
+    #[default]
+    Ascii,
+
+    /// [JIS X 0201][`crate::JisX0201`].
+    JisX0201,
+
+    /// [Shift JIS 1997][`crate::ShiftJis1997`].
+    ShiftJis1997,
+
+    /// [Shift JIS 2004][`crate::ShiftJis2004`].
+    ShiftJis2004,
+
+    /// [EUC-JP][`crate::EucJp`].
+    EucJp,
+
+    /// [UTF-16 BE][`crate::Utf16Be`].
+    Utf16Be,
+
+    /// [Windows-1252][`crate::Windows1252`].
+    Windows1252,
+}
+
+impl Encoding {
+    /// Get the encoding usually used for strings of games from `region`, i.e.,
+    /// [Shift JIS 1997][`Encoding::ShiftJis1997`] for Japanese games,
+    /// [Windows-1252][`Encoding::Windows1252`] for European and Australian
+    /// games, and [ASCII][`Encoding::Ascii`] for all other games.
+    #[cfg(feature = "std")]
+    pub fn from_region(region: Region) -> Self {
+        match region {
+            Region::Japan => Encoding::ShiftJis1997,
+            Region::Europe | Region::Australia => Encoding::Windows1252,
+            _ => Encoding::Ascii,
+        }
+    }
+
+    /// Decode the first string (until a NULL character is reached) from
+    /// `data`.
+    pub fn first(&self, data: &[u8]) -> Result<String> {
+        match self {
+            Encoding::Ascii => Ascii::first(data),
+            Encoding::JisX0201 => JisX0201::first(data),
+            Encoding::ShiftJis1997 => ShiftJis1997::first(data),
+            Encoding::ShiftJis2004 => ShiftJis2004::first(data),
+            Encoding::EucJp => EucJp::first(data),
+            Encoding::Utf16Be => Utf16Be::first(data),
+            Encoding::Windows1252 => Windows1252::first(data),
+        }
+    }
+
+    /// Decode all bytes of `data` into a string.
+    pub fn all(&self, data: &[u8]) -> Result<String> {
+        match self {
+            Encoding::Ascii => Ascii::all(data),
+            Encoding::JisX0201 => JisX0201::all(data),
+            Encoding::ShiftJis1997 => ShiftJis1997::all(data),
+            Encoding::ShiftJis2004 => ShiftJis2004::all(data),
+            Encoding::EucJp => EucJp::all(data),
+            Encoding::Utf16Be => Utf16Be::all(data),
+            Encoding::Windows1252 => Windows1252::all(data),
+        }
+    }
+
+    /// Decode all bytes of `data` like [`Encoding::all`], but ASCII-only data
+    /// is borrowed without allocating if the encoding decodes it unchanged.
+    pub fn decode_cow<'x>(&self, data: &'x [u8]) -> Result<Cow<'x, str>> {
+        match self {
+            Encoding::Ascii => Ascii::decode_cow(data),
+            Encoding::JisX0201 => JisX0201::decode_cow(data),
+            Encoding::ShiftJis1997 => ShiftJis1997::decode_cow(data),
+            Encoding::ShiftJis2004 => ShiftJis2004::decode_cow(data),
+            Encoding::EucJp => EucJp::decode_cow(data),
+            Encoding::Utf16Be => Utf16Be::all(data).map(Cow::Owned),
+            Encoding::Windows1252 => Windows1252::decode_cow(data),
+        }
+    }
+
+    /// Encode all characters of `data`. Fails with the first character that
+    /// can not be encoded and its byte index in `data`. [Shift JIS
+    /// 2004][`Encoding::ShiftJis2004`] is encoded like [Shift JIS
+    /// 1997][`Encoding::ShiftJis1997`], the characters added by JIS X 0213
+    /// can not be encoded.
+    pub fn encode(&self, data: &str) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(data.len());
+        for (index, c) in data.char_indices() {
+            self.encode_char(c, index, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Encode `data` into the fixed-size field `output`, e.g., the title of a
+    /// banner. The string is truncated at a character boundary if it does not
+    /// fit and is always followed by a NULL character, the rest of `output` is
+    /// filled with zeros. Returns the number of bytes of `data` that were
+    /// encoded, i.e., less than `data.len()` if the string was truncated.
+    pub fn encode_into(&self, output: &mut [u8], data: &str) -> Result<usize> {
+        let terminator = self.terminator();
+        ensure!(
+            output.len() >= terminator,
+            EncodingProblem::BufferTooSmall(Location::current())
+        );
+
+        let limit = output.len() - terminator;
+        let mut encoded = Vec::with_capacity(output.len());
+        let (mut consumed, mut length) = (0, 0);
+        for (index, c) in data.char_indices() {
+            self.encode_char(c, index, &mut encoded)?;
+            if encoded.len() > limit {
+                break;
+            }
+            consumed = index + c.len_utf8();
+            length = encoded.len();
+        }
+
+        output[..length].copy_from_slice(&encoded[..length]);
+        output[length..].fill(0);
+        Ok(consumed)
+    }
+
+    /// Number of bytes of the NULL character.
+    fn terminator(&self) -> usize {
+        match self {
+            Encoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    /// Decode the string at each of the `offsets` of the string table `data`,
+    /// e.g., the names of the FST or the strings of a
+    /// symbol table. Returns the offset and string pairs.
+    pub fn strings_at<I>(&self, data: &[u8], offsets: I) -> Result<Vec<(usize, String)>>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        offsets
+            .into_iter()
+            .map(|offset| {
+                let string = data.get(offset..).ok_or(ParseProblem::InvalidRange(
+                    "string offset (outside of table)",
+                    Location::current(),
+                ))?;
+                Ok((offset, self.first(string)?))
+            })
+            .collect()
+    }
+
+    /// Decode all NULL-terminated strings of the string table `data`. Returns
+    /// the offset and string pairs, including empty strings, e.g., of padding
+    /// at the end of the table. The last string does not need to be
+    /// NULL-terminated.
+    pub fn strings(&self, data: &[u8]) -> Result<Vec<(usize, String)>> {
+        let terminator = self.terminator();
+        let mut strings = Vec::new();
+        let mut start = 0;
+        while start < data.len() {
+            let length = data[start..]
+                .chunks(terminator)
+                .position(|x| x.len() == terminator && x.iter().all(|x| *x == 0))
+                .map_or(data.len() - start, |x| x * terminator);
+            let end = start + length;
+            strings.push((start, self.all(&data[start..end])?));
+            start = end + terminator;
+        }
+        Ok(strings)
+    }
+
+    /// Check that every character of `data` can be encoded and decodes back
+    /// to the same character, e.g., to validate a translated script before
+    /// inserting it. Fails with the byte index and character of every
+    /// character that can not be encoded.
+    pub fn can_encode(&self, data: &str) -> core::result::Result<(), Vec<(usize, char)>> {
+        let mut output = Vec::new();
+        let invalid = data
+            .char_indices()
+            .filter(|(index, c)| {
+                output.clear();
+                self.encode_char(*c, *index, &mut output).is_err()
+                    || !self.all(&output).is_ok_and(|x| x.chars().eq([*c]))
+            })
+            .collect::<Vec<_>>();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Encode a single character, which is at `index` of the encoded string,
+    /// and append it to `output`.
+    fn encode_char(&self, c: char, index: usize, output: &mut Vec<u8>) -> Result<()> {
+        let encoded = match self {
+            Encoding::Ascii => c.is_ascii().then_some((c as u8, None)),
+            Encoding::JisX0201 => JisX0201::encode_char(c).map(|x| (x, None)),
+            Encoding::ShiftJis1997 | Encoding::ShiftJis2004 => ShiftJis1997::encode_char(c).ok(),
+            Encoding::EucJp => EucJp::encode_char(c),
+            Encoding::Windows1252 => Windows1252::encode_char(c).map(|x| (x, None)),
+            Encoding::Utf16Be => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    output.extend(unit.to_be_bytes());
+                }
+                return Ok(());
+            },
+        };
+        let (first, second) = encoded
+            .ok_or_else(|| EncodingProblem::InvalidCharacter(c, index, Location::current()))?;
+        output.push(first);
+        output.extend(second);
+        Ok(())
+    }
+
+    /// Decode the first string from `data` like [`Encoding::first`], invalid
+    /// bytes are handled according to `policy`.
+    pub fn first_with(&self, data: &[u8], policy: ErrorPolicy) -> Result<String> {
+        match self {
+            Encoding::Ascii => Ascii::first_with(data, policy),
+            Encoding::JisX0201 => JisX0201::first_with(data, policy),
+            Encoding::ShiftJis1997 => ShiftJis1997::first_with(data, policy),
+            Encoding::ShiftJis2004 => ShiftJis2004::first_with(data, policy),
+            Encoding::EucJp => EucJp::first_with(data, policy),
+            Encoding::Utf16Be => Utf16Be::first_with(data, policy),
+            Encoding::Windows1252 => Windows1252::first_with(data, policy),
+        }
+    }
+
+    /// Decode all bytes of `data` like [`Encoding::all`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn all_with(&self, data: &[u8], policy: ErrorPolicy) -> Result<String> {
+        match self {
+            Encoding::Ascii => Ascii::all_with(data, policy),
+            Encoding::JisX0201 => JisX0201::all_with(data, policy),
+            Encoding::ShiftJis1997 => ShiftJis1997::all_with(data, policy),
+            Encoding::ShiftJis2004 => ShiftJis2004::all_with(data, policy),
+            Encoding::EucJp => EucJp::all_with(data, policy),
+            Encoding::Utf16Be => Utf16Be::all_with(data, policy),
+            Encoding::Windows1252 => Windows1252::all_with(data, policy),
+        }
+    }
+
+    /// Decode the first string from `data` like [`Encoding::first`], but
+    /// invalid bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy(&self, data: &[u8]) -> String {
+        match self {
+            Encoding::Ascii => Ascii::first_lossy(data),
+            Encoding::JisX0201 => JisX0201::first_lossy(data),
+            Encoding::ShiftJis1997 => ShiftJis1997::first_lossy(data),
+            Encoding::ShiftJis2004 => ShiftJis2004::first_lossy(data),
+            Encoding::EucJp => EucJp::first_lossy(data),
+            Encoding::Utf16Be => Utf16Be::first_lossy(data),
+            Encoding::Windows1252 => Windows1252::first_lossy(data),
+        }
+    }
+
+    /// Decode all bytes of `data` like [`Encoding::all`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy(&self, data: &[u8]) -> String {
+        match self {
+            Encoding::Ascii => Ascii::all_lossy(data),
+            Encoding::JisX0201 => JisX0201::all_lossy(data),
+            Encoding::ShiftJis1997 => ShiftJis1997::all_lossy(data),
+            Encoding::ShiftJis2004 => ShiftJis2004::all_lossy(data),
+            Encoding::EucJp => EucJp::all_lossy(data),
+            Encoding::Utf16Be => Utf16Be::all_lossy(data),
+            Encoding::Windows1252 => Windows1252::all_lossy(data),
+        }
+    }
+}
+
+/// Number of bytes [`CharReader`] reads at once.
+#[cfg(feature = "std")]
+const READ_SIZE: usize = 0x1000;
+
+/// Decoder that reads the bytes from a [`Read`], e.g., to decode huge files
+/// without loading them into memory. Multi-byte characters that are split
+/// across reads are decoded once the rest of the character has been read.
+///
+/// ```
+/// # use picori::Result;
+/// use picori::encoding::CharReader;
+/// use picori::Encoding;
+///
+/// # fn main() -> Result<()> {
+/// let file = std::io::Cursor::new(b"\x83\x51\x81\x5B\x83\x80\r\nabc");
+/// let lines = CharReader::new(file, Encoding::ShiftJis1997)
+///     .lines()
+///     .collect::<Result<Vec<_>>>()?;
+/// assert_eq!(lines, ["ゲーム", "abc"]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub struct CharReader<R: Read> {
+    reader:   R,
+    encoding: Encoding,
+    buffer:   Vec<u8>,
+    chars:    VecDeque<Result<char>>,
+    eof:      bool,
+    offset:   usize,
+    decoded:  usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> CharReader<R> {
+    /// Creates a decoder of the bytes read from `reader` with `encoding`.
+    pub fn new(reader: R, encoding: Encoding) -> Self {
+        Self {
+            reader,
+            encoding,
+            buffer: Vec::new(),
+            chars: VecDeque::new(),
+            eof: false,
+            offset: 0,
+            decoded: 0,
+        }
+    }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Returns an iterator over the lines, without the line ending (`\n` or
+    /// `\r\n`).
+    pub fn lines(self) -> Lines<Self> { Lines { iter: self } }
+
+    /// Reads more bytes and decodes all complete characters.
+    fn fill(&mut self) -> Result<()> {
+        let length = self.buffer.len();
+        self.buffer.resize(length + READ_SIZE, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buffer[length..]) {
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        self.buffer.truncate(length + *read.as_ref().unwrap_or(&0));
+        self.eof = matches!(read, Ok(0));
+        read?;
+
+        let consumed = Cell::new(0);
+        let bytes = self
+            .buffer
+            .iter()
+            .inspect(|_| consumed.set(consumed.get() + 1));
+        let pending = self.chars.len();
+        let (length, eof, chars) = (self.buffer.len(), self.eof, &mut self.chars);
+        let decoded = match self.encoding {
+            Encoding::Ascii => decode_buffer(Ascii::iter(bytes), &consumed, length, eof, chars),
+            Encoding::JisX0201 => {
+                decode_buffer(JisX0201::iter(bytes), &consumed, length, eof, chars)
+            },
+            Encoding::ShiftJis1997 => {
+                decode_buffer(ShiftJis1997::iter(bytes), &consumed, length, eof, chars)
+            },
+            Encoding::ShiftJis2004 => {
+                decode_buffer(ShiftJis2004::iter(bytes), &consumed, length, eof, chars)
+            },
+            Encoding::EucJp => decode_buffer(EucJp::iter(bytes), &consumed, length, eof, chars),
+            Encoding::Utf16Be => decode_buffer(Utf16Be::iter(bytes), &consumed, length, eof, chars),
+            Encoding::Windows1252 => {
+                decode_buffer(Windows1252::iter(bytes), &consumed, length, eof, chars)
+            },
+        };
+        self.buffer.drain(..decoded);
+
+        // the positions of decoding errors are relative to the buffer
+        let (offset, valid) = (self.offset, self.decoded);
+        for c in self.chars.range_mut(pending..) {
+            match c {
+                Ok(_) => self.decoded += 1,
+                Err(Error::Decoding(DecodingProblem::AtOffset(x, y, _))) => {
+                    *x += offset;
+                    *y += valid;
+                },
+                Err(_) => {},
+            }
+        }
+        self.offset += decoded;
+        Ok(())
+    }
+}
+
+/// Decodes all characters of `decoder`, which reads `length` bytes and counts
+/// them in `consumed`, into `chars`. Returns the number of bytes decoded, i.e.,
+/// without the bytes of an incomplete character at the end (unless `eof` is
+/// set).
+#[cfg(feature = "std")]
+fn decode_buffer<D>(
+    mut decoder: D,
+    consumed: &Cell<usize>,
+    length: usize,
+    eof: bool,
+    chars: &mut VecDeque<Result<char>>,
+) -> usize
+where
+    D: Iterator<Item = Result<char>>,
+{
+    loop {
+        let start = consumed.get();
+        match decoder.next() {
+            None => return length,
+            Some(Err(Error::Decoding(e)))
+                if !eof && matches!(e.problem(), DecodingProblem::UnexpectedEndOfData(_)) =>
+            {
+                return start
+            },
+            Some(c) => chars.push_back(c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for CharReader<R> {
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Result<char>> {
+        while self.chars.is_empty() {
+            if self.eof {
+                return None;
+            }
+            if let Err(e) = self.fill() {
+                self.eof = true;
+                return Some(Err(e));
+            }
+        }
+        self.chars.pop_front()
+    }
+}
+
+/// Iterator over the lines of decoded characters, created by
+/// [`CharReader::lines`] or [`WithPolicy::lines`].
+pub struct Lines<I> {
+    iter: I,
+}
+
+impl<I> Iterator for Lines<I>
+where
+    I: Iterator<Item = Result<char>>,
+{
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Result<String>> {
+        let mut line = None::<String>;
+        loop {
+            match self.iter.next() {
+                None => return line.map(Ok),
+                Some(Ok('\n')) => {
+                    let mut line = line.unwrap_or_default();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                    return Some(Ok(line));
+                },
+                Some(Ok(c)) => line.get_or_insert_with(String::new).push(c),
+                Some(Err(e)) => {
+                    // the line with the invalid bytes is returned as the error
+                    for c in self.iter.by_ref() {
+                        if matches!(c, Ok('\n')) {
+                            break;
+                        }
+                    }
+                    return Some(Err(e));
+                },
+            }
+        }
+    }
+}
+
+/// Encoder that writes the encoded text to a [`Write`]. The data written to
+/// it must be UTF-8, e.g., with [`write!`]. A character that is split across
+/// writes is encoded once the rest of the character has been written.
+///
+/// ```
+/// # use picori::Result;
+/// use std::io::Write;
+///
+/// use picori::encoding::EncodeWriter;
+/// use picori::Encoding;
+///
+/// # fn main() -> Result<()> {
+/// let mut writer = EncodeWriter::new(Vec::new(), Encoding::ShiftJis1997);
+/// write!(writer, "{}.arc", "ゲーム")?;
+/// assert_eq!(writer.finish()?, b"\x83\x51\x81\x5B\x83\x80.arc");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub struct EncodeWriter<W: Write> {
+    writer:   W,
+    encoding: Encoding,
+    pending:  Vec<u8>,
+    position: usize,
+    output:   Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> EncodeWriter<W> {
+    /// Creates an encoder with `encoding` that writes to `writer`.
+    pub fn new(writer: W, encoding: Encoding) -> Self {
+        Self {
+            writer,
+            encoding,
+            pending: Vec::new(),
+            position: 0,
+            output: Vec::new(),
+        }
+    }
+
+    /// Finishes the encoding and returns the underlying writer. Fails if the
+    /// data ends with an incomplete UTF-8 character.
+    pub fn finish(mut self) -> Result<W> {
+        ensure!(
+            self.pending.is_empty(),
+            DecodingProblem::UnexpectedEndOfData(Location::current())
+        );
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Encodes all complete characters of the pending data.
+    fn encode(&mut self) -> Result<()> {
+        let (valid, rest) = match std::str::from_utf8(&self.pending) {
+            Ok(valid) => (valid, &[][..]),
+            Err(e) => {
+                let (valid, rest) = self.pending.split_at(e.valid_up_to());
+                // an incomplete character at the end is kept until the rest
+                // of it is written
+                ensure!(
+                    e.error_len().is_none(),
+                    DecodingProblem::InvalidByte(rest[0], Location::current())
+                );
+                (std::str::from_utf8(valid).unwrap(), rest)
+            },
+        };
+
+        self.output.clear();
+        for (index, c) in valid.char_indices() {
+            self.encoding
+                .encode_char(c, self.position + index, &mut self.output)?;
+        }
+        self.writer.write_all(&self.output)?;
+        self.position += valid.len();
+        self.pending = rest.to_vec();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // the data is only kept if it was encoded, so a failed write can be
+        // followed by other writes
+        let length = self.pending.len();
+        self.pending.extend_from_slice(buf);
+        if let Err(e) = self.encode() {
+            self.pending.truncate(length);
+            return Err(std::io::Error::new(ErrorKind::InvalidData, e));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> { self.writer.flush() }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Reader that returns one byte per read.
+    struct Slow<'x>(&'x [u8]);
+
+    impl Read for Slow<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = self.0.len().min(buf.len()).min(1);
+            buf[..length].copy_from_slice(&self.0[..length]);
+            self.0 = &self.0[length..];
+            Ok(length)
+        }
+    }
+
+    #[test]
+    fn encode_writer() {
+        let mut writer = EncodeWriter::new(Vec::new(), Encoding::ShiftJis1997);
+        // split the characters across writes
+        for byte in "ゲーム\nab亜".bytes() {
+            writer.write_all(&[byte]).unwrap();
+        }
+        assert_eq!(
+            writer.finish().unwrap(),
+            b"\x83\x51\x81\x5B\x83\x80\nab\x88\x9f"
+        );
+
+        let mut writer = EncodeWriter::new(Vec::new(), Encoding::Ascii);
+        assert!(write!(writer, "abc").is_ok());
+        assert!(write!(writer, "dé").is_err());
+        // the failed write is dropped
+        assert!(write!(writer, "d").is_ok());
+        assert_eq!(writer.finish().unwrap(), b"abcd");
+        let mut writer = EncodeWriter::new(Vec::new(), Encoding::Ascii);
+        writer.write_all(&"é".as_bytes()[..1]).unwrap();
+        assert!(writer.finish().is_err());
+
+        assert_eq!(Encoding::JisX0201.encode("¥ｱ").unwrap(), b"\x5c\xb1");
+        assert_eq!(
+            Encoding::Utf16Be.encode("a😀").unwrap(),
+            b"\x00a\xD8\x3D\xDE\x00"
+        );
+        assert!(Encoding::JisX0201.encode("\\").is_err());
+        assert_eq!(Encoding::Ascii.can_encode("aé"), Err(vec![(1, 'é')]));
+        assert!(Encoding::Windows1252.can_encode("aé€").is_ok());
+    }
+
+    #[test]
+    fn decode_cow() {
+        let encoding = Encoding::ShiftJis1997;
+        assert!(matches!(
+            encoding.decode_cow(b"main.dol"),
+            Ok(Cow::Borrowed("main.dol"))
+        ));
+        assert!(matches!(encoding.decode_cow(b"a\\b"), Ok(Cow::Owned(x)) if x == "a¥b"));
+        assert!(matches!(encoding.decode_cow(b"\x88\x9f"), Ok(Cow::Owned(x)) if x == "亜"));
+        assert!(encoding.decode_cow(b"a\xff").is_err());
+        assert!(matches!(
+            Encoding::Ascii.decode_cow(b"a\\b"),
+            Ok(Cow::Borrowed(_))
+        ));
+        assert!(matches!(
+            Encoding::Utf16Be.decode_cow(b"\0a"),
+            Ok(Cow::Owned(_))
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        let data = b"root\0a.bin\0\0\x83\x51";
+        let strings = Encoding::ShiftJis1997.strings(data).unwrap();
+        let expected = [(0, "root"), (5, "a.bin"), (11, ""), (12, "ゲ")];
+        assert!(strings.iter().map(|(i, x)| (*i, x.as_str())).eq(expected));
+
+        let strings = Encoding::ShiftJis1997.strings_at(data, [5, 7]).unwrap();
+        assert_eq!(strings, [(5, "a.bin".to_string()), (7, "bin".to_string())]);
+        assert!(Encoding::Ascii.strings_at(data, [15]).is_err());
+
+        // the NULL character of UTF-16 is aligned
+        let data = b"\0a\x01\0\0\0\0b";
+        let strings = Encoding::Utf16Be.strings(data).unwrap();
+        assert_eq!(strings, [(0, "a\u{100}".to_string()), (6, "b".to_string())]);
+    }
+
+    #[test]
+    fn encode_into() {
+        // the double-byte character does not fit with the NULL character
+        let mut buffer = [0xff; 4];
+        let encoding = Encoding::ShiftJis1997;
+        assert_eq!(encoding.encode_into(&mut buffer, "ab亜").unwrap(), 2);
+        assert_eq!(buffer, *b"ab\0\0");
+        assert_eq!(encoding.encode_into(&mut buffer, "a亜").unwrap(), 4);
+        assert_eq!(buffer, *b"a\x88\x9f\0");
+        assert!(encoding.encode_into(&mut buffer, "😀").is_err());
+        assert!(encoding.encode_into(&mut [], "").is_err());
+
+        let mut buffer = [0xff; 6];
+        assert_eq!(
+            Encoding::Utf16Be.encode_into(&mut buffer, "abc").unwrap(),
+            2
+        );
+        assert_eq!(buffer, *b"\0a\0b\0\0");
+    }
+
+    #[test]
+    fn char_reader_utf16() {
+        // the surrogate pair is split across reads
+        let data = b"\x00a\xD8\x3D\xDE\x00\x00\n\x00b";
+        let lines = CharReader::new(Slow(data), Encoding::Utf16Be)
+            .lines()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lines, ["a😀", "b"]);
+    }
+
+    #[test]
+    fn char_reader() {
+        let data = b"\x83\x51\x81\x5B\x83\x80\nab\x88\x9f\r\n\xff\x88";
+        let chars = CharReader::new(Slow(data), Encoding::ShiftJis1997).collect::<Vec<_>>();
+        assert_eq!(chars.len(), 11);
+        assert!(chars[9].is_err() && chars[10].is_err());
+        // the offsets are relative to the start of the data, not the read
+        for (c, position) in chars[9..].iter().zip([(13, 9), (14, 9)]) {
+            match c {
+                Err(Error::Decoding(e)) => assert_eq!(e.offset(), Some(position)),
+                x => panic!("unexpected result: {x:?}"),
+            }
+        }
+        let text = chars[..9]
+            .iter()
+            .map(|c| *c.as_ref().unwrap())
+            .collect::<String>();
+        assert_eq!(text, "ゲーム\nab亜\r\n");
+
+        let lines = CharReader::new(Slow(data), Encoding::ShiftJis1997)
+            .lines()
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].as_ref().unwrap(), "ゲーム");
+        assert_eq!(lines[1].as_ref().unwrap(), "ab亜");
+        assert!(lines[2].is_err());
+
+        let lines = CharReader::new(Slow(data), Encoding::ShiftJis1997)
+            .policy(ErrorPolicy::Skip)
+            .lines()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lines, ["ゲーム", "ab亜"]);
+    }
+}
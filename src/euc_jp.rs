@@ -0,0 +1,375 @@
+//! [EUC-JP][`EucJp`] encoding.
+//!
+//! [EUC-JP][`EucJp`] (Extended Unix Code for Japanese) encodes the same `JIS X
+//! 0208` character set as [Shift JIS 1997][`crate::ShiftJis1997`] and is used
+//! by some development-era assets and debug text. The first 128 characters
+//! are ASCII (`0x5C` is a backslash, not a yen sign). A `JIS X 0208`
+//! character is encoded as its row and cell, each with the eighth bit set,
+//! i.e., two bytes in the range `[0xA1,0xFE]`. Half-width katakana are
+//! prefixed with `0x8E`. Characters of `JIS X 0212`, which are prefixed with
+//! `0x8F`, are not supported.
+//!
+//! The `JIS X 0208` tables of [Shift JIS 1997][`crate::ShiftJis1997`] are used
+//! for decoding and encoding.
+//!
+//! # Examples
+//!
+//! ```
+//! # use picori::{EucJp, Result};
+//! fn main() -> Result<()> {
+//!     let data = EucJp::encode("ゲーム.arc")?;
+//!     assert_eq!(data, b"\xA5\xB2\xA1\xBC\xA5\xE0.arc");
+//!     assert_eq!(EucJp::all(&data)?, "ゲーム.arc");
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{BufReader, Read};
+
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
+use crate::error::DecodingProblem::*;
+use crate::error::EncodingProblem::*;
+use crate::helper::ProblemLocation;
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
+use crate::shift_jis_1997::{decode_double, encode_double};
+use crate::Result;
+
+/// [EUC-JP][`EucJp`] encoding.
+pub struct EucJp {}
+
+/// Convert the row and cell (`[0x21,0x7E]`) of a `JIS X 0208` character to
+/// Shift JIS.
+fn jis_to_sjis(row: u8, cell: u8) -> [u8; 2] {
+    let first = ((row + 1) >> 1) + if row <= 0x5E { 0x70 } else { 0xB0 };
+    let second = if row & 1 == 1 {
+        cell + if cell >= 0x60 { 0x20 } else { 0x1F }
+    } else {
+        cell + 0x7E
+    };
+    [first, second]
+}
+
+/// Convert a Shift JIS character to the row and cell of a `JIS X 0208`
+/// character. The rows above 94 (lead-bytes above `0xEF`) can not be
+/// converted.
+fn sjis_to_jis(first: u8, second: u8) -> Option<[u8; 2]> {
+    let row = match first {
+        0x81..=0x9F => (first - 0x70) * 2,
+        0xE0..=0xEF => (first - 0xB0) * 2,
+        _ => return None,
+    };
+    Some(match second {
+        0x40..=0x7E => [row - 1, second - 0x1F],
+        0x80..=0x9E => [row - 1, second - 0x20],
+        _ => [row, second - 0x7E],
+    })
+}
+
+/// A iterator decoder for the [`EucJp`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:    Tracked<<I as IntoIterator>::IntoIter>,
+    _marker: PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:    Tracked::new(iter.into_iter()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
+    fn next_byte(&mut self) -> Result<u8> {
+        match self.iter.next() {
+            Some(byte) => Ok(*byte.borrow()),
+            None => Err(UnexpectedEndOfData(Location::current()).into()),
+        }
+    }
+
+    fn decode_next(&mut self, byte: u8) -> Result<char> {
+        match byte {
+            // ASCII character
+            0x00..=0x7F => Ok(byte as char),
+            // Half-width katakana
+            0x8E => match self.next_byte()? {
+                next @ 0xA1..=0xDF => Ok(char::from_u32(0xFF61 + (next - 0xA1) as u32).unwrap()),
+                next => Err(InvalidByte(next, Location::current()).into()),
+            },
+            // JIS X 0212 character (not supported)
+            0x8F => {
+                self.next_byte()?;
+                self.next_byte()?;
+                Err(InvalidByte(byte, Location::current()).into())
+            },
+            // JIS X 0208 character
+            0xA1..=0xFE => match self.next_byte()? {
+                next @ 0xA1..=0xFE => {
+                    let [first, second] = jis_to_sjis(byte - 0x80, next - 0x80);
+                    decode_double(first, second)
+                },
+                next => Err(InvalidByte(next, Location::current()).into()),
+            },
+            // Invalid as first byte
+            _ => Err(InvalidByte(byte, Location::current()).into()),
+        }
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.iter.offset();
+        let byte = *self.iter.next()?.borrow();
+        let result = self.decode_next(byte);
+        Some(self.iter.locate(offset, result))
+    }
+}
+
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize { self.iter.offset() }
+}
+
+impl EucJp {
+    /// Encode a single character into one or two bytes. The second byte is
+    /// [`None`] for single-byte characters.
+    pub fn encode_char(c: char) -> Option<(u8, Option<u8>)> {
+        match c {
+            '\u{00}'..='\u{7F}' => Some((c as u8, None)),
+            '\u{FF61}'..='\u{FF9F}' => Some((0x8E, Some((c as u32 - 0xFF61) as u8 + 0xA1))),
+            _ => {
+                let [first, second] = encode_double(c)?;
+                let [row, cell] = sjis_to_jis(first, second)?;
+                Some((row + 0x80, Some(cell + 0x80)))
+            },
+        }
+    }
+
+    /// Encode all characters of `data`. Fails with the first character that
+    /// is not part of [EUC-JP][`EucJp`] and its byte index in `data`. No
+    /// NULL byte is appended.
+    pub fn encode(data: &str) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(data.len());
+        for (index, c) in data.char_indices() {
+            let (first, second) =
+                Self::encode_char(c).ok_or(InvalidCharacter(c, index, Location::current()))?;
+            output.push(first);
+            output.extend(second);
+        }
+        Ok(output)
+    }
+
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL bytes and only
+    /// stop at the end of the iterator or if an decoding error occurs.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode all bytes of `data` like [`EucJp::all`], but ASCII-only data is
+    /// borrowed without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, &[], |data| Self::all(data))
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`EucJp::all`], but invalid
+    /// bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`EucJp::first`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`EucJp::all`], invalid bytes
+    /// are handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`EucJp::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::eucjp`] for decoding as [EUC-JP][`EucJp`]
+/// strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [EUC-JP][`EucJp`].
+    fn eucjp<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+#[cfg(feature = "std")]
+impl ParseStringEncoding for EucJp {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let data = Self::encode(data)?;
+        let destination = buffer
+            .get_mut(..data.len())
+            .ok_or(BufferTooSmall(Location::current()))?;
+        destination.copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        let buffer = BufReader::new(reader);
+        let iter = buffer
+            .bytes()
+            .take_while(|x| x.is_ok())
+            .filter_map(|x| x.ok());
+        Self::first(iter)
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShiftJis1997;
+
+    #[test]
+    fn parse_str() {
+        let data = b"abc\xB0\xA1\x8E\xB1\0def";
+        assert_eq!(EucJp::parse_str(data).unwrap(), "abc亜ｱ".to_string());
+    }
+
+    #[test]
+    fn round_trip() {
+        // every character of rows 1 to 94 of Shift JIS 1997
+        for first in (0x81..=0x9F).chain(0xE0..=0xEF) {
+            for second in (0x40..=0x7E).chain(0x80..=0xFC) {
+                if let Ok(text) = ShiftJis1997::all([first, second]) {
+                    let [row, cell] = sjis_to_jis(first, second).unwrap();
+                    let data = [row + 0x80, cell + 0x80];
+                    assert_eq!(jis_to_sjis(row, cell), [first, second]);
+                    assert_eq!(EucJp::all(data).unwrap(), text);
+                    // the backslash of JIS X 0208 is encoded as ASCII
+                    if !text.is_ascii() {
+                        assert_eq!(EucJp::encode(&text).unwrap(), data);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(EucJp::encode("\\~").unwrap(), b"\\~");
+        assert!(EucJp::encode("¥").is_err());
+        assert!(EucJp::all(b"\x8F\xB0\xA1").is_err());
+        assert!(EucJp::all(b"\xB0").is_err());
+    }
+}
@@ -0,0 +1,494 @@
+//! JSystem event lists (`event_list.dat`).
+//!
+//! The event list describes the cutscenes (events) of The Legend of Zelda:
+//! The Wind Waker (and other games based on the same engine). An
+//! [event][`Event`] lists the [actors][`Actor`] taking part, each actor has a
+//! linked list of [actions][`Action`], and each action has a linked list of
+//! [properties][`Property`] with typed [values][`Value`].
+//!
+//! | Offset | Size | Description                              |
+//! |--------|------|------------------------------------------|
+//! | `0x00` | 8    | Offset and number of events (`0xB0`)     |
+//! | `0x08` | 8    | Offset and number of actors (`0x50`)     |
+//! | `0x10` | 8    | Offset and number of actions (`0x50`)    |
+//! | `0x18` | 8    | Offset and number of properties (`0x40`) |
+//! | `0x20` | 8    | Offset and number of floats              |
+//! | `0x28` | 8    | Offset and number of integers            |
+//! | `0x30` | 8    | Offset and size of the strings           |
+//!
+//! The values of the properties are stored in the float, integer, and string
+//! lists, they are resolved when parsing and the lists are rebuilt by
+//! [`EventList::to_binary`]. The indices (e.g., [`Action::next_action`]) are
+//! written as is.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::event_list::EventList;
+//!
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("event_list.dat")?;
+//!     let list = EventList::from_binary(&mut file)?;
+//!     for event in list.events.iter() {
+//!         println!("{}", event.name);
+//!         for actor in list.actors_of(event) {
+//!             for action in list.actions_of(actor) {
+//!                 for property in list.properties_of(action) {
+//!                     println!(
+//!                         "  {}.{}.{} = {:?}",
+//!                         actor.name, action.name, property.name, property.value
+//!                     );
+//!                 }
+//!             }
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Ascii, Result, ShiftJis1997};
+
+/// Size of the header.
+pub const HEADER_SIZE: usize = 0x40;
+
+/// Maximum number of actors of an event.
+pub const MAX_EVENT_ACTORS: usize = 20;
+
+/// Event (cutscene).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Name of the event.
+    pub name: String,
+
+    /// Index of the event.
+    pub index: u32,
+
+    /// Unknown.
+    pub unknown: u32,
+
+    /// Priority.
+    pub priority: u32,
+
+    /// Indices of the actors (at most [`MAX_EVENT_ACTORS`]).
+    pub actors: Vec<u32>,
+
+    /// Flags that must be set before the event starts, `-1` for none.
+    pub starting_flags: [i32; 2],
+
+    /// Flags set when the event ends, `-1` for none.
+    pub ending_flags: [i32; 3],
+
+    /// Play the jingle (sound) when the event ends.
+    pub play_jingle: bool,
+}
+
+/// Actor (staff) of an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    /// Name of the actor, e.g., `Link`.
+    pub name: String,
+
+    /// Staff identifier.
+    pub staff_identifier: u32,
+
+    /// Index of the actor.
+    pub index: u32,
+
+    /// Flag set by the actor, `-1` for none.
+    pub flag: i32,
+
+    /// Staff type.
+    pub staff_type: u32,
+
+    /// Index of the first action, `-1` for none.
+    pub first_action: i32,
+}
+
+/// Action of an actor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    /// Name of the action, e.g., `WAIT`.
+    pub name: String,
+
+    /// Number of the action to distinguish actions with the same name.
+    pub duplicate_id: u32,
+
+    /// Index of the action.
+    pub index: u32,
+
+    /// Flags that must be set before the action starts, `-1` for none.
+    pub starting_flags: [i32; 3],
+
+    /// Flag set when the action ends, `-1` for none.
+    pub flag: i32,
+
+    /// Index of the first property, `-1` for none.
+    pub first_property: i32,
+
+    /// Index of the next action of the actor, `-1` for none.
+    pub next_action: i32,
+}
+
+/// Value of a [`Property`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Floats (type `0`).
+    Float(Vec<f32>),
+
+    /// Vectors (type `1`).
+    Vector(Vec<[f32; 3]>),
+
+    /// Integers (type `3`).
+    Integer(Vec<i32>),
+
+    /// String (type `4`).
+    String(String),
+}
+
+impl Value {
+    /// Get the type ID of the value.
+    pub fn id(&self) -> u32 {
+        match self {
+            Value::Float(_) => 0,
+            Value::Vector(_) => 1,
+            Value::Integer(_) => 3,
+            Value::String(_) => 4,
+        }
+    }
+}
+
+/// Property of an action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// Name of the property, e.g., `pos`.
+    pub name: String,
+
+    /// Index of the property.
+    pub index: u32,
+
+    /// Value.
+    pub value: Value,
+
+    /// Index of the next property of the action, `-1` for none.
+    pub next_property: i32,
+}
+
+/// Event list.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EventList {
+    /// Events.
+    pub events: Vec<Event>,
+
+    /// Actors (of all events).
+    pub actors: Vec<Actor>,
+
+    /// Actions (of all actors).
+    pub actions: Vec<Action>,
+
+    /// Properties (of all actions).
+    pub properties: Vec<Property>,
+}
+
+impl EventList {
+    /// Parse event list from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let header = input.bu32_array::<14>()?;
+        let table = |index: usize| (base + header[2 * index] as u64, header[2 * index + 1]);
+
+        let (offset, count) = table(0);
+        input.goto(offset)?;
+        let events = (0..count)
+            .map(|_| {
+                let name = input.str_fixed::<0x20, Ascii>()?;
+                let index = input.bu32()?;
+                let unknown = input.bu32()?;
+                let priority = input.bu32()?;
+                let actors = input.bu32_array::<MAX_EVENT_ACTORS>()?;
+                let actor_count = input.bu32()? as usize;
+                let starting_flags = input.bu32_array::<2>()?.map(|x| x as i32);
+                let ending_flags = input.bu32_array::<3>()?.map(|x| x as i32);
+                let play_jingle = input.u8()? != 0;
+                let _padding = input.u8_array::<0x1B>()?;
+                ensure!(
+                    actor_count <= MAX_EVENT_ACTORS,
+                    ParseProblem::InvalidData("event list actor count", Location::current())
+                );
+                Ok(Event {
+                    name,
+                    index,
+                    unknown,
+                    priority,
+                    actors: actors[..actor_count].to_vec(),
+                    starting_flags,
+                    ending_flags,
+                    play_jingle,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (offset, count) = table(1);
+        input.goto(offset)?;
+        let actors = (0..count)
+            .map(|_| {
+                let name = input.str_fixed::<0x20, Ascii>()?;
+                let staff_identifier = input.bu32()?;
+                let index = input.bu32()?;
+                let flag = input.bu32()? as i32;
+                let staff_type = input.bu32()?;
+                let first_action = input.bu32()? as i32;
+                let _padding = input.u8_array::<0x1C>()?;
+                Ok(Actor {
+                    name,
+                    staff_identifier,
+                    index,
+                    flag,
+                    staff_type,
+                    first_action,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (offset, count) = table(2);
+        input.goto(offset)?;
+        let actions = (0..count)
+            .map(|_| {
+                let name = input.str_fixed::<0x20, Ascii>()?;
+                let duplicate_id = input.bu32()?;
+                let index = input.bu32()?;
+                let starting_flags = input.bu32_array::<3>()?.map(|x| x as i32);
+                let flag = input.bu32()? as i32;
+                let first_property = input.bu32()? as i32;
+                let next_action = input.bu32()? as i32;
+                let _padding = input.u8_array::<0x10>()?;
+                Ok(Action {
+                    name,
+                    duplicate_id,
+                    index,
+                    starting_flags,
+                    flag,
+                    first_property,
+                    next_action,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (offset, count) = table(4);
+        input.goto(offset)?;
+        let floats = (0..count)
+            .map(|_| Ok(f32::from_bits(input.bu32()?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let (offset, count) = table(5);
+        input.goto(offset)?;
+        let integers = (0..count)
+            .map(|_| Ok(input.bu32()? as i32))
+            .collect::<Result<Vec<_>>>()?;
+
+        let (offset, size) = table(6);
+        input.goto(offset)?;
+        let strings = input.read_as_vec(size as usize)?;
+
+        let (offset, count) = table(3);
+        input.goto(offset)?;
+        let properties = (0..count)
+            .map(|_| {
+                let name = input.str_fixed::<0x20, Ascii>()?;
+                let index = input.bu32()?;
+                let kind = input.bu32()?;
+                let data_index = input.bu32()? as usize;
+                let data_size = input.bu32()? as usize;
+                let next_property = input.bu32()? as i32;
+                let _padding = input.u8_array::<0x0C>()?;
+                let value = match kind {
+                    0 => Value::Float(Self::slice(&floats, data_index, data_size)?.to_vec()),
+                    1 => Value::Vector(
+                        Self::slice(&floats, data_index, data_size * 3)?
+                            .chunks_exact(3)
+                            .map(|x| [x[0], x[1], x[2]])
+                            .collect(),
+                    ),
+                    3 => Value::Integer(Self::slice(&integers, data_index, data_size)?.to_vec()),
+                    4 => Value::String(ShiftJis1997::first(Self::slice(
+                        &strings, data_index, data_size,
+                    )?)?),
+                    _ => Err(ParseProblem::InvalidData(
+                        "unknown event list property type",
+                        Location::current(),
+                    ))?,
+                };
+                Ok(Property {
+                    name,
+                    index,
+                    value,
+                    next_property,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            events,
+            actors,
+            actions,
+            properties,
+        })
+    }
+
+    /// Write event list to binary stream. The float, integer, and string lists
+    /// are rebuilt from the values of the properties.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let mut floats = Vec::new();
+        let mut integers = Vec::new();
+        let mut strings = Vec::new();
+        let mut references = Vec::with_capacity(self.properties.len());
+        for property in self.properties.iter() {
+            let reference = match &property.value {
+                Value::Float(values) => {
+                    floats.extend_from_slice(values);
+                    (floats.len() - values.len(), values.len())
+                },
+                Value::Vector(values) => {
+                    floats.extend(values.iter().flatten());
+                    (floats.len() - values.len() * 3, values.len())
+                },
+                Value::Integer(values) => {
+                    integers.extend_from_slice(values);
+                    (integers.len() - values.len(), values.len())
+                },
+                Value::String(value) => {
+                    let start = strings.len();
+                    strings.extend(ShiftJis1997::encode(value)?);
+                    strings.push(0);
+                    strings.resize(strings.len().next_multiple_of(4), 0);
+                    (start, strings.len() - start)
+                },
+            };
+            references.push(reference);
+        }
+
+        let counts = [
+            (self.events.len(), 0xB0),
+            (self.actors.len(), 0x50),
+            (self.actions.len(), 0x50),
+            (self.properties.len(), 0x40),
+            (floats.len(), 4),
+            (integers.len(), 4),
+            (strings.len(), 1),
+        ];
+        let mut offset = HEADER_SIZE;
+        for (count, size) in counts.iter() {
+            output.bu32(offset as u32)?;
+            output.bu32(*count as u32)?;
+            offset += count * size;
+        }
+        output.u8_array(&[0; 8])?;
+
+        for event in self.events.iter() {
+            ensure!(
+                event.actors.len() <= MAX_EVENT_ACTORS,
+                BuildProblem::InvalidData("too many event actors", Location::current())
+            );
+            let mut actors = [0; MAX_EVENT_ACTORS];
+            actors[..event.actors.len()].copy_from_slice(&event.actors);
+            output.str::<0x20, Ascii>(&event.name)?;
+            output.bu32(event.index)?;
+            output.bu32(event.unknown)?;
+            output.bu32(event.priority)?;
+            output.bu32_array(&actors)?;
+            output.bu32(event.actors.len() as u32)?;
+            output.bu32_array(&event.starting_flags.map(|x| x as u32))?;
+            output.bu32_array(&event.ending_flags.map(|x| x as u32))?;
+            output.u8(event.play_jingle as u8)?;
+            output.u8_array(&[0; 0x1B])?;
+        }
+
+        for actor in self.actors.iter() {
+            output.str::<0x20, Ascii>(&actor.name)?;
+            output.bu32(actor.staff_identifier)?;
+            output.bu32(actor.index)?;
+            output.bu32(actor.flag as u32)?;
+            output.bu32(actor.staff_type)?;
+            output.bu32(actor.first_action as u32)?;
+            output.u8_array(&[0; 0x1C])?;
+        }
+
+        for action in self.actions.iter() {
+            output.str::<0x20, Ascii>(&action.name)?;
+            output.bu32(action.duplicate_id)?;
+            output.bu32(action.index)?;
+            output.bu32_array(&action.starting_flags.map(|x| x as u32))?;
+            output.bu32(action.flag as u32)?;
+            output.bu32(action.first_property as u32)?;
+            output.bu32(action.next_action as u32)?;
+            output.u8_array(&[0; 0x10])?;
+        }
+
+        for (property, (index, size)) in self.properties.iter().zip(references) {
+            output.str::<0x20, Ascii>(&property.name)?;
+            output.bu32(property.index)?;
+            output.bu32(property.value.id())?;
+            output.bu32(index as u32)?;
+            output.bu32(size as u32)?;
+            output.bu32(property.next_property as u32)?;
+            output.u8_array(&[0; 0x0C])?;
+        }
+
+        for value in floats.iter() {
+            output.bu32(value.to_bits())?;
+        }
+        for value in integers.iter() {
+            output.bu32(*value as u32)?;
+        }
+        output.u8_array(&strings)?;
+        Ok(())
+    }
+
+    /// Get the event with the name `name`.
+    pub fn event(&self, name: &str) -> Option<&Event> {
+        self.events.iter().find(|x| x.name == name)
+    }
+
+    /// Get the actors of the event `event` (indices out of range are skipped).
+    pub fn actors_of<'a>(&'a self, event: &'a Event) -> impl Iterator<Item = &'a Actor> {
+        event
+            .actors
+            .iter()
+            .filter_map(|x| self.actors.get(*x as usize))
+    }
+
+    /// Get the actions of the actor `actor` (following
+    /// [`Action::next_action`]).
+    pub fn actions_of<'a>(&'a self, actor: &Actor) -> impl Iterator<Item = &'a Action> {
+        let first = self.link(&self.actions, actor.first_action);
+        std::iter::successors(first, |x| self.link(&self.actions, x.next_action))
+            .take(self.actions.len())
+    }
+
+    /// Get the properties of the action `action` (following
+    /// [`Property::next_property`]).
+    pub fn properties_of<'a>(&'a self, action: &Action) -> impl Iterator<Item = &'a Property> {
+        let first = self.link(&self.properties, action.first_property);
+        std::iter::successors(first, |x| self.link(&self.properties, x.next_property))
+            .take(self.properties.len())
+    }
+
+    fn link<'a, T>(&self, list: &'a [T], index: i32) -> Option<&'a T> {
+        usize::try_from(index).ok().and_then(|x| list.get(x))
+    }
+
+    fn slice<T>(list: &[T], index: usize, size: usize) -> Result<&[T]> {
+        match list.get(index..index + size) {
+            Some(data) => Ok(data),
+            None => Err(ParseProblem::InvalidRange(
+                "event list property value",
+                Location::current(),
+            ))?,
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! GCI memory card files (`.gci`).
+//!
+//! A GCI file is a single save of a GameCube memory card, the directory entry
+//! of the file on the card followed by its blocks. The directory entry
+//! describes the file (game, name, and size) and points to the banner, icons,
+//! and comments in the save data, see [`Gci::banner`], [`Gci::icons`], and
+//! [`Gci::comments`].
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 6    | Game ID (game code and maker code)            |
+//! | `0x06` | 1    | Unused (`0xFF`)                               |
+//! | `0x07` | 1    | Banner format and icon animation              |
+//! | `0x08` | 32   | File name                                     |
+//! | `0x28` | 4    | Modification time (seconds since 2000)        |
+//! | `0x2C` | 4    | Offset of the banner and icons                |
+//! | `0x30` | 2    | Icon formats (2 bits per icon)                |
+//! | `0x32` | 2    | Icon animation speed (2 bits per icon)        |
+//! | `0x34` | 1    | Permissions                                   |
+//! | `0x35` | 1    | Copy counter                                  |
+//! | `0x36` | 2    | First block (on the card)                     |
+//! | `0x38` | 2    | Number of blocks                              |
+//! | `0x3A` | 2    | Unused (`0xFFFF`)                             |
+//! | `0x3C` | 4    | Offset of the comments                        |
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("save.gci")?;
+//!     let gci = picori::gci::Gci::from_binary(&mut file)?;
+//!     let [title, description] = gci.comments()?;
+//!     println!("{}: {} ({})", gci.entry.game_id, title, description);
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::encoding::Encoding;
+use crate::error::BuildProblem;
+use crate::gcm::GameId;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Writer};
+use crate::texture::{self, Format, TlutFormat};
+use crate::{Bnr, Result};
+
+/// Size of a memory card block.
+pub const BLOCK_SIZE: usize = 0x2000;
+
+/// Size of a directory entry.
+pub const ENTRY_SIZE: usize = 0x40;
+
+/// Size of a comment.
+pub const COMMENT_SIZE: usize = 32;
+
+/// Width of the banner.
+pub const BANNER_WIDTH: usize = 96;
+
+/// Height of the banner.
+pub const BANNER_HEIGHT: usize = 32;
+
+/// Width and height of an icon.
+pub const ICON_SIZE: usize = 32;
+
+/// Number of icons (animation frames).
+pub const ICON_COUNT: usize = 8;
+
+/// Size of a palette (256 RGB5A3 colors).
+const PALETTE_SIZE: usize = 0x200;
+
+/// Format of the banner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BannerFormat {
+    /// No banner.
+    None,
+
+    /// [C8][`Format::C8`] with a palette after the banner.
+    Ci8,
+
+    /// [RGB5A3][`Format::Rgb5a3`].
+    Rgb5a3,
+}
+
+/// Format of an icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    /// No icon.
+    None,
+
+    /// [C8][`Format::C8`] with the palette shared by all icons (after the last
+    /// icon).
+    Ci8Shared,
+
+    /// [RGB5A3][`Format::Rgb5a3`].
+    Rgb5a3,
+
+    /// [C8][`Format::C8`] with a palette after the icon.
+    Ci8,
+}
+
+impl IconFormat {
+    /// Size of the icon data, including its own palette.
+    fn size(&self) -> usize {
+        match self {
+            IconFormat::None => 0,
+            IconFormat::Ci8Shared => ICON_SIZE * ICON_SIZE,
+            IconFormat::Rgb5a3 => ICON_SIZE * ICON_SIZE * 2,
+            IconFormat::Ci8 => ICON_SIZE * ICON_SIZE + PALETTE_SIZE,
+        }
+    }
+}
+
+/// Icon animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Animation {
+    /// The icons are repeated from the first icon.
+    Loop,
+
+    /// The icons are played forward and backward.
+    PingPong,
+}
+
+/// Icon (frame of the icon animation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    /// Image (RGBA8, 32x32).
+    pub image: Vec<u8>,
+
+    /// Duration of the frame in video frames (4, 8, or 12).
+    pub duration: u8,
+}
+
+/// Directory entry of a memory card file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    /// Game ID (game code and maker code).
+    pub game_id: GameId,
+
+    /// Banner format (bits 0-1) and icon animation (bit 2).
+    pub banner_flags: u8,
+
+    /// File name (NULL-padded).
+    pub filename: [u8; 32],
+
+    /// Modification time, seconds since 2000-01-01.
+    pub modified: u32,
+
+    /// Offset of the banner and icons in the save data.
+    pub image_offset: u32,
+
+    /// Icon formats, 2 bits per icon (8 icons).
+    pub icon_formats: u16,
+
+    /// Icon animation speed, 2 bits per icon (8 icons).
+    pub animation_speed: u16,
+
+    /// Permissions, e.g., public, no copy, and no move.
+    pub permissions: u8,
+
+    /// Copy counter.
+    pub copy_counter: u8,
+
+    /// First block of the file on the memory card.
+    pub first_block: u16,
+
+    /// Number of blocks.
+    pub block_count: u16,
+
+    /// Offset of the comments in the save data.
+    pub comment_offset: u32,
+}
+
+impl DirectoryEntry {
+    /// Parse directory entry (`0x40` bytes).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let game_id = GameId::from_binary(input)?;
+        let _unused = input.u8()?;
+        let banner_flags = input.u8()?;
+        let filename = input.u8_array::<32>()?;
+        let modified = input.bu32()?;
+        let image_offset = input.bu32()?;
+        let icon_formats = input.bu16()?;
+        let animation_speed = input.bu16()?;
+        let permissions = input.u8()?;
+        let copy_counter = input.u8()?;
+        let first_block = input.bu16()?;
+        let block_count = input.bu16()?;
+        let _unused = input.bu16()?;
+        let comment_offset = input.bu32()?;
+
+        Ok(Self {
+            game_id,
+            banner_flags,
+            filename,
+            modified,
+            image_offset,
+            icon_formats,
+            animation_speed,
+            permissions,
+            copy_counter,
+            first_block,
+            block_count,
+            comment_offset,
+        })
+    }
+
+    /// Write directory entry (`0x40` bytes).
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        self.game_id.to_binary(output)?;
+        output.u8(0xFF)?;
+        output.u8(self.banner_flags)?;
+        output.u8_array(&self.filename)?;
+        output.bu32(self.modified)?;
+        output.bu32(self.image_offset)?;
+        output.bu16(self.icon_formats)?;
+        output.bu16(self.animation_speed)?;
+        output.u8(self.permissions)?;
+        output.u8(self.copy_counter)?;
+        output.bu16(self.first_block)?;
+        output.bu16(self.block_count)?;
+        output.bu16(0xFFFF)?;
+        output.bu32(self.comment_offset)?;
+        Ok(())
+    }
+
+    /// Get the encoding of the file name and comments, Shift JIS for Japanese
+    /// games and Windows-1252 otherwise (the same as the
+    /// [banner][`Bnr::encoding`]).
+    pub fn encoding(&self) -> Encoding { Bnr::encoding(self.game_id.region()) }
+
+    /// Get the file name.
+    pub fn filename(&self) -> Result<String> { self.encoding().first(&self.filename) }
+
+    /// Get the format of the banner.
+    pub fn banner_format(&self) -> BannerFormat {
+        match self.banner_flags & 0x03 {
+            1 => BannerFormat::Ci8,
+            2 => BannerFormat::Rgb5a3,
+            _ => BannerFormat::None,
+        }
+    }
+
+    /// Get the icon animation.
+    pub fn animation(&self) -> Animation {
+        match self.banner_flags & 0x04 {
+            0 => Animation::Loop,
+            _ => Animation::PingPong,
+        }
+    }
+
+    /// Get the format of the icon `index` (0-7).
+    pub fn icon_format(&self, index: usize) -> IconFormat {
+        match (self.icon_formats >> (2 * index)) & 0x03 {
+            1 => IconFormat::Ci8Shared,
+            2 => IconFormat::Rgb5a3,
+            3 => IconFormat::Ci8,
+            _ => IconFormat::None,
+        }
+    }
+
+    /// Get the animation speed of the icon `index` (0-7), `0` for the end of
+    /// the animation, otherwise the duration of the frame in units of 4 video
+    /// frames.
+    pub fn icon_speed(&self, index: usize) -> u8 {
+        ((self.animation_speed >> (2 * index)) & 0x03) as u8
+    }
+}
+
+/// GCI memory card file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gci {
+    /// Directory entry.
+    pub entry: DirectoryEntry,
+
+    /// Save data ([`DirectoryEntry::block_count`] blocks), including the
+    /// banner, icons, and comments.
+    pub data: Vec<u8>,
+}
+
+impl Gci {
+    /// Parse GCI file from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let entry = DirectoryEntry::from_binary(input)?;
+        let mut data = vec![0; entry.block_count as usize * BLOCK_SIZE];
+        input.read_into(&mut data)?;
+        Ok(Self { entry, data })
+    }
+
+    /// Write GCI file to binary stream.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.data.len() == self.entry.block_count as usize * BLOCK_SIZE,
+            BuildProblem::InvalidData("GCI data size (not the block count)", Location::current())
+        );
+        self.entry.to_binary(output)?;
+        output.u8_array(&self.data)?;
+        Ok(())
+    }
+
+    /// Get the comments, the title (usually the name of the game) and the
+    /// description of the save.
+    pub fn comments(&self) -> Result<[String; 2]> {
+        let offset = self.entry.comment_offset as usize;
+        let comments =
+            self.data
+                .get(offset..offset + 2 * COMMENT_SIZE)
+                .ok_or(ParseProblem::InvalidRange(
+                    "GCI comment offset (outside of save data)",
+                    Location::current(),
+                ))?;
+        let encoding = self.entry.encoding();
+        Ok([
+            encoding.first(&comments[..COMMENT_SIZE])?,
+            encoding.first(&comments[COMMENT_SIZE..])?,
+        ])
+    }
+
+    /// Get `size` bytes of the save data at `offset`.
+    fn image_data(&self, offset: usize, size: usize) -> Result<&[u8]> {
+        Ok(self
+            .data
+            .get(offset..offset + size)
+            .ok_or(ParseProblem::InvalidRange(
+                "GCI image (outside of save data)",
+                Location::current(),
+            ))?)
+    }
+
+    /// Get the size of the banner data, including its palette.
+    fn banner_size(&self) -> usize {
+        match self.entry.banner_format() {
+            BannerFormat::None => 0,
+            BannerFormat::Ci8 => BANNER_WIDTH * BANNER_HEIGHT + PALETTE_SIZE,
+            BannerFormat::Rgb5a3 => BANNER_WIDTH * BANNER_HEIGHT * 2,
+        }
+    }
+
+    /// Decode the banner (RGBA8, 96x32). Returns [`None`] if the save has no
+    /// banner.
+    pub fn banner(&self) -> Result<Option<Vec<u8>>> {
+        let data = self.image_data(self.entry.image_offset as usize, self.banner_size())?;
+        let (pixels, palette) = data.split_at(data.len().min(BANNER_WIDTH * BANNER_HEIGHT));
+        Ok(match self.entry.banner_format() {
+            BannerFormat::None => None,
+            BannerFormat::Ci8 => Some(texture::decode(
+                Format::C8,
+                pixels,
+                BANNER_WIDTH,
+                BANNER_HEIGHT,
+                Some((TlutFormat::Rgb5a3, palette)),
+            )?),
+            BannerFormat::Rgb5a3 => Some(texture::decode(
+                Format::Rgb5a3,
+                data,
+                BANNER_WIDTH,
+                BANNER_HEIGHT,
+                None,
+            )?),
+        })
+    }
+
+    /// Decode the icons (frames of the icon animation, RGBA8, 32x32) until the
+    /// end of the animation. Icons without data are skipped. See
+    /// [`DirectoryEntry::animation`] for the order of the frames.
+    pub fn icons(&self) -> Result<Vec<Icon>> {
+        let start = self.entry.image_offset as usize + self.banner_size();
+        let formats = (0..ICON_COUNT)
+            .map(|i| self.entry.icon_format(i))
+            .collect::<Vec<_>>();
+        let shared = start + formats.iter().map(|x| x.size()).sum::<usize>();
+
+        let mut icons = Vec::new();
+        let mut offset = start;
+        for (index, format) in formats.iter().enumerate() {
+            let speed = self.entry.icon_speed(index);
+            if speed == 0 {
+                break;
+            }
+
+            let data = self.image_data(offset, format.size())?;
+            offset += format.size();
+            let pixels = &data[..data.len().min(ICON_SIZE * ICON_SIZE)];
+            let image = match format {
+                IconFormat::None => continue,
+                IconFormat::Ci8Shared => {
+                    let palette = self.image_data(shared, PALETTE_SIZE)?;
+                    let tlut = Some((TlutFormat::Rgb5a3, palette));
+                    texture::decode(Format::C8, pixels, ICON_SIZE, ICON_SIZE, tlut)?
+                },
+                IconFormat::Ci8 => {
+                    let tlut = Some((TlutFormat::Rgb5a3, &data[ICON_SIZE * ICON_SIZE..]));
+                    texture::decode(Format::C8, pixels, ICON_SIZE, ICON_SIZE, tlut)?
+                },
+                IconFormat::Rgb5a3 => {
+                    texture::decode(Format::Rgb5a3, data, ICON_SIZE, ICON_SIZE, None)?
+                },
+            };
+            icons.push(Icon {
+                image,
+                duration: speed * 4,
+            });
+        }
+        Ok(icons)
+    }
+}
@@ -5,8 +5,15 @@
 use crate::helper::{Parser, Writer};
 use crate::{Ascii, Result};
 
+/// Offset of the apploader from the start of the disc (or partition data).
+pub const APPLOADER_OFFSET: u64 = 0x2440;
+
+/// Size of the apploader header.
+pub const APPLOADER_HEADER_SIZE: u64 = 0x20;
+
 /// [GCM][`crate::gcm`] Apploader (`apploader.img`) object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Apploader {
     /// Date.
     pub date: String,
@@ -23,7 +30,8 @@ pub struct Apploader {
     /// Unknown0 (unknown purpose).
     pub unknown: u32,
 
-    /// Apploader data.
+    /// Apploader data (code followed by the trailer).
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
 }
 
@@ -48,6 +56,15 @@ impl Apploader {
         })
     }
 
+    /// Apploader code, i.e., the data without the trailer.
+    pub fn body(&self) -> &[u8] { &self.data[..(self.size as usize).min(self.data.len())] }
+
+    /// Apploader trailer.
+    pub fn trailer(&self) -> &[u8] { &self.data[(self.size as usize).min(self.data.len())..] }
+
+    /// Total size of the apploader (`apploader.img`), including the header.
+    pub fn total_size(&self) -> u64 { APPLOADER_HEADER_SIZE + self.data.len() as u64 }
+
     pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> { 
         output.str::<0x10, Ascii>(&self.date)?;
         output.bu32(self.entry_point)?;
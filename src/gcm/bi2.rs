@@ -10,11 +10,13 @@
 
 use std::collections::HashMap;
 
+use crate::gcm::Region;
 use crate::helper::{Parser, Writer};
 use crate::Result;
 
 /// [`Bi2`] Options.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Bi2Options {
     /// Debug monitor size  (unknown purpose).
     DebugMonitorSize,
@@ -34,7 +36,8 @@ pub enum Bi2Options {
     /// Track size (unknown purpose).
     TrackSize,
 
-    /// Country code (unknown purpose, this already exists in `boot.bin`).
+    /// Country code (`0`: Japan, `1`: America, `2`: Europe, `4`: Korea). This
+    /// is the region checked by the console, see [`Bi2::region`].
     CountryCode,
 
     /// Initial PAD_SPEC_X that the Pad library uses.
@@ -55,13 +58,13 @@ impl Bi2Options {
     pub fn index(&self) -> usize {
         use Bi2Options::*;
         match self {
-            DebugMonitorSize => 1,
-            SimulatedMemorySize => 2,
-            ArgumentOffset => 3,
-            DebugFlag => 4,
-            TrackLocation => 5,
-            TrackSize => 6,
-            CountryCode => 7,
+            DebugMonitorSize => 0,
+            SimulatedMemorySize => 1,
+            ArgumentOffset => 2,
+            DebugFlag => 3,
+            TrackLocation => 4,
+            TrackSize => 5,
+            CountryCode => 6,
             PadSpec => 8,
             LongFilenameSupport => 9,
             DolLimit => 11,
@@ -74,13 +77,13 @@ impl From<usize> for Bi2Options {
     fn from(index: usize) -> Self {
         use Bi2Options::*;
         match index {
-            1 => DebugMonitorSize,
-            2 => SimulatedMemorySize,
-            3 => ArgumentOffset,
-            4 => DebugFlag,
-            5 => TrackLocation,
-            6 => TrackSize,
-            7 => CountryCode,
+            0 => DebugMonitorSize,
+            1 => SimulatedMemorySize,
+            2 => ArgumentOffset,
+            3 => DebugFlag,
+            4 => TrackLocation,
+            5 => TrackSize,
+            6 => CountryCode,
             8 => PadSpec,
             9 => LongFilenameSupport,
             11 => DolLimit,
@@ -90,7 +93,8 @@ impl From<usize> for Bi2Options {
 }
 
 /// [GCM][`crate::gcm`] Boot information (`bi2.bin`) object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bi2 {
     // TODO: HashMap or Array?
     options: HashMap<Bi2Options, u32>,
@@ -108,6 +112,51 @@ impl Bi2 {
 
     /// Get all options.
     pub fn options(&self) -> &HashMap<Bi2Options, u32> { &self.options }
+
+    fn value(&self, options: Bi2Options) -> u32 { self.get(options).copied().unwrap_or(0) }
+
+    /// Debug monitor size.
+    pub fn debug_monitor_size(&self) -> u32 { self.value(Bi2Options::DebugMonitorSize) }
+
+    /// Simulated memory size.
+    pub fn simulated_memory_size(&self) -> u32 { self.value(Bi2Options::SimulatedMemorySize) }
+
+    /// Argument offset.
+    pub fn argument_offset(&self) -> u32 { self.value(Bi2Options::ArgumentOffset) }
+
+    /// Debug flag.
+    pub fn debug_flag(&self) -> bool { self.value(Bi2Options::DebugFlag) != 0 }
+
+    /// Track location.
+    pub fn track_location(&self) -> u32 { self.value(Bi2Options::TrackLocation) }
+
+    /// Track size.
+    pub fn track_size(&self) -> u32 { self.value(Bi2Options::TrackSize) }
+
+    /// Raw country code, see [`Bi2::region`].
+    pub fn country_code(&self) -> u32 { self.value(Bi2Options::CountryCode) }
+
+    /// Region the console checks against its own region before booting the
+    /// game. This can differ from the [region of the game
+    /// ID][`crate::gcm::GameId::region`].
+    pub fn region(&self) -> Region {
+        match self.country_code() {
+            0 => Region::Japan,
+            1 => Region::America,
+            2 => Region::Europe,
+            4 => Region::Korea,
+            code => Region::Unknown(code as u8),
+        }
+    }
+
+    /// Initial PAD_SPEC_X that the Pad library uses.
+    pub fn pad_spec(&self) -> u32 { self.value(Bi2Options::PadSpec) }
+
+    /// Long filename support.
+    pub fn long_filename_support(&self) -> bool { self.value(Bi2Options::LongFilenameSupport) != 0 }
+
+    /// Dol limit.
+    pub fn dol_limit(&self) -> u32 { self.value(Bi2Options::DolLimit) }
 }
 
 impl Bi2 {
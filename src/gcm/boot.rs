@@ -1,23 +1,51 @@
 //! [GCM][`crate::gcm`] Boot Header (`boot.bin`). This is the first 0x440 bytes
 //! of the GCM image.
 
-use crate::error::ParseProblem;
+use crate::error::{BuildProblem, ParseProblem};
+use crate::gcm::{GameId, Region};
 use crate::helper::{ensure, Parser, ProblemLocation, Writer};
-use crate::{Ascii, Result};
+use crate::{Encoding, Result};
+
+/// GameCube disc magic (located at offset `0x1C`).
+pub(crate) const GAMECUBE_MAGIC: u32 = 0xC2339F3D;
+
+/// Wii disc magic (located at offset `0x18`).
+pub(crate) const WII_MAGIC: u32 = 0x5D1C9EA3;
 
 /// [`Boot`] Console Type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConsoleType {
     /// Nintendo GameCube.
     GameCube,
+
+    /// Nintendo Wii. This is also the console type of the header found at the
+    /// start of the (decrypted) Wii partition data.
+    Wii,
+}
+
+impl ConsoleType {
+    /// Number of bits that offsets and sizes in the [`Boot`] header and the
+    /// [`Fst`][`crate::gcm::Fst`] are shifted by. Wii discs store offsets
+    /// divided by 4 to be able to address more than 4 GiB.
+    pub fn offset_shift(&self) -> u32 {
+        match self {
+            ConsoleType::GameCube => 0,
+            ConsoleType::Wii => 2,
+        }
+    }
 }
 
 /// [GCM][`crate::gcm`] Boot Header (`boot.bin`) object.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Boot {
-    /// Console.
+    /// Console, determined by the disc magic.
     pub console: ConsoleType,
 
+    /// Console code, e.g., `G` for GameCube games and `R` for Wii games.
+    pub console_code: u8,
+
     /// Game code.
     pub game_code: [u8; 2],
 
@@ -39,7 +67,8 @@ pub struct Boot {
     /// Stream buffer size.
     pub streaming_buffer_size: u8,
 
-    /// Game name.
+    /// Game name, encoded with the [encoding][`Encoding::from_region`] of the
+    /// region of the game.
     pub game_name: String,
 
     /// Debug monitor offset (unknown purpose).
@@ -74,7 +103,7 @@ pub struct Boot {
 impl Boot {
     /// Parse GCM Boot.
     pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
-        let console = input.u8()?;
+        let console_code = input.u8()?;
         let game_code = input.u8_array::<2>()?;
         let country_code = input.u8()?;
         let maker_code = input.u8_array::<2>()?;
@@ -82,9 +111,12 @@ impl Boot {
         let version = input.u8()?;
         let audio_streaming = input.u8()?;
         let streaming_buffer_size = input.u8()?;
-        let _reserved0 = input.u8_array::<0x12>()?;
-        let magic = input.bu32()?;
-        let game_name = input.str_fixed::<0x3E0, Ascii>()?;
+        let _reserved0 = input.u8_array::<0xE>()?;
+        let wii_magic = input.bu32()?;
+        let gamecube_magic = input.bu32()?;
+        let game_name = input.u8_array::<0x3E0>()?;
+        let encoding = Encoding::from_region(Region::from_country_code(country_code));
+        let game_name = encoding.first(&game_name)?;
         let debug_monitor_offset = input.bu32()?;
         let debug_monitor_address = input.bu32()?;
         let _reserved1 = input.u8_array::<0x18>()?;
@@ -97,21 +129,20 @@ impl Boot {
         let unknown0 = input.bu32()?;
         let _reserved2 = input.u8_array::<0x4>()?;
 
-        ensure!(
-            magic == 0xC2339F3D,
-            ParseProblem::InvalidHeader("invalid magic", std::panic::Location::current())
-        );
-
-        let console = match console {
-            0x47 => ConsoleType::GameCube,
-            _ => Err(ParseProblem::InvalidHeader(
-                "invalid console type",
+        let console = if wii_magic == WII_MAGIC {
+            ConsoleType::Wii
+        } else if gamecube_magic == GAMECUBE_MAGIC {
+            ConsoleType::GameCube
+        } else {
+            Err(ParseProblem::InvalidMagic(
+                "expected GameCube (0xC2339F3D) or Wii (0x5D1C9EA3) disc magic",
                 std::panic::Location::current(),
-            ))?,
+            ))?
         };
 
         Ok(Self {
             console,
+            console_code,
             game_code,
             country_code,
             maker_code,
@@ -132,12 +163,39 @@ impl Boot {
         })
     }
 
+    /// Get the [`GameId`] (console, game, country, and maker code).
+    pub fn game_id(&self) -> GameId { GameId::from(self) }
+
+    /// Set the console, game, country, and maker code from `id`.
+    pub fn set_game_id(&mut self, id: GameId) {
+        self.console_code = id.console_code;
+        self.game_code = id.game_code;
+        self.country_code = id.country_code;
+        self.maker_code = id.maker_code;
+    }
+
+    /// Offset of the main executable from the start of the disc (or partition
+    /// data), with the [offset shift][`ConsoleType::offset_shift`] applied.
+    pub fn executable_offset(&self) -> u64 {
+        (self.main_executable_offset as u64) << self.console.offset_shift()
+    }
+
+    /// Offset of the [FST][`crate::gcm::Fst`] from the start of the disc (or
+    /// partition data), with the [offset shift][`ConsoleType::offset_shift`]
+    /// applied.
+    pub fn fst_offset(&self) -> u64 { (self.fst_offset as u64) << self.console.offset_shift() }
+
+    /// Size of the [FST][`crate::gcm::Fst`], with the [offset
+    /// shift][`ConsoleType::offset_shift`] applied.
+    pub fn fst_size(&self) -> u64 { (self.fst_size as u64) << self.console.offset_shift() }
+
     pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
-        let console = match self.console {
-            ConsoleType::GameCube => 0x47_u8,
+        let (wii_magic, gamecube_magic) = match self.console {
+            ConsoleType::GameCube => (0, GAMECUBE_MAGIC),
+            ConsoleType::Wii => (WII_MAGIC, 0),
         };
 
-        output.u8(console)?;
+        output.u8(self.console_code)?;
         output.u8_array(&self.game_code)?;
         output.u8(self.country_code)?;
         output.u8_array(&self.maker_code)?;
@@ -145,9 +203,17 @@ impl Boot {
         output.u8(self.version)?;
         output.u8(self.audio_streaming)?;
         output.u8(self.streaming_buffer_size)?;
-        output.u8_array(&[0; 0x12])?;
-        output.bu32(0xC2339F3D)?;
-        output.str::<0x3E0, Ascii>(&self.game_name)?;
+        output.u8_array(&[0; 0xE])?;
+        output.bu32(wii_magic)?;
+        output.bu32(gamecube_magic)?;
+        let mut game_name = [0u8; 0x3E0];
+        let encoding = Encoding::from_region(self.game_id().region());
+        let length = encoding.encode_into(&mut game_name, &self.game_name)?;
+        ensure!(
+            length == self.game_name.len(),
+            BuildProblem::InvalidData("game name (too long)", std::panic::Location::current())
+        );
+        output.u8_array(&game_name)?;
         output.bu32(self.debug_monitor_offset)?;
         output.bu32(self.debug_monitor_address)?;
         output.u8_array(&[0; 0x18])?;
@@ -0,0 +1,571 @@
+//! [GCM][`crate::gcm`] builder. Rebuilds a disc image from its system files
+//! (boot, bi2, apploader, and executable) and a list of files.
+//!
+//! Files are laid out after the [FST][`crate::gcm::Fst`] in the order they are
+//! added. The alignment of each file is determined by its extension (see
+//! [`GcmBuilder::extension_alignment`]) and otherwise by the default alignment
+//! (see [`GcmBuilder::alignment`]). Some games stream files directly from the
+//! disc (movies and audio) and expect them at specific alignments. For these,
+//! the builder can keep the offset from the original disc (see
+//! [`GcmBuilder::preserve_offsets`]). If the original offset is not available
+//! (e.g., it overlaps with the new system area), the original alignment is
+//! kept instead.
+//!
+//! A builder can also be created from a directory tree in the extracted layout
+//! (see [`crate::gcm::extract`]) with [`GcmBuilder::from_root`] or
+//! [`build_from_root`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! # use picori::gcm::GcmBuilder;
+//! fn main() -> Result<()> {
+//!     let mut input = File::open("game.iso")?;
+//!     let gcm = picori::Gcm::from_binary(&mut input)?;
+//!
+//!     let mut builder = GcmBuilder::from_gcm(&gcm);
+//!     builder.add_file("mod/readme.txt", b"hello".to_vec());
+//!     for extension in GcmBuilder::STREAMED_EXTENSIONS {
+//!         builder.preserve_offsets(extension);
+//!     }
+//!
+//!     let mut output = File::create("modded.iso")?;
+//!     builder.build_with_source(&mut input, &mut output)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Cursor;
+use std::ops::Range;
+use std::panic::Location;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::BuildProblem;
+use crate::gcm::apploader::APPLOADER_OFFSET;
+use crate::gcm::extract::{FILES_DIRECTORY, SYSTEM_DIRECTORY};
+use crate::gcm::{fst, Apploader, Bi2, Boot, Executable, Gcm};
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Encoding, Result};
+
+/// Default alignment of files.
+pub const DEFAULT_ALIGNMENT: u32 = 4;
+
+/// Alignment of the executable and the [FST][`crate::gcm::Fst`].
+const SYSTEM_ALIGNMENT: u64 = 0x100;
+
+/// Largest alignment kept from original file offsets.
+const MAX_PRESERVED_ALIGNMENT: u64 = 0x8000;
+
+/// Source of the data of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSource {
+    /// Data in memory.
+    Data(Vec<u8>),
+
+    /// Data in the source disc image (see [`GcmBuilder::build_with_source`]).
+    Disc {
+        /// Offset of the data in the source disc image.
+        offset: u64,
+        /// Size of the data.
+        size:   u32,
+    },
+
+    /// Data in a file on the host file system, read when building.
+    Path {
+        /// Path of the file.
+        path: PathBuf,
+        /// Size of the file.
+        size: u64,
+    },
+}
+
+impl FileSource {
+    /// Size of the data.
+    pub fn size(&self) -> u64 {
+        match self {
+            FileSource::Data(data) => data.len() as u64,
+            FileSource::Disc { size, .. } => *size as u64,
+            FileSource::Path { size, .. } => *size,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct File {
+    path: PathBuf,
+    source: FileSource,
+    original_offset: Option<u64>,
+}
+
+enum Node {
+    File(String, usize),
+    Directory(String, Vec<Node>),
+}
+
+/// [GCM][`crate::gcm`] builder.
+pub struct GcmBuilder {
+    boot: Boot,
+    bi2: Bi2,
+    apploader: Apploader,
+    executable: Executable,
+    files: Vec<File>,
+    alignment: u32,
+    extension_alignment: HashMap<String, u32>,
+    preserve_extensions: HashSet<String>,
+    disc_size: Option<u64>,
+    encoding: Option<Encoding>,
+}
+
+impl GcmBuilder {
+    /// Extensions of files that are commonly streamed from the disc (movies
+    /// and audio streams).
+    pub const STREAMED_EXTENSIONS: [&'static str; 5] = ["thp", "afc", "ast", "adp", "str"];
+
+    /// Create a builder without any files.
+    pub fn new(boot: Boot, bi2: Bi2, apploader: Apploader, executable: Executable) -> Self {
+        Self {
+            boot,
+            bi2,
+            apploader,
+            executable,
+            files: Vec::new(),
+            alignment: DEFAULT_ALIGNMENT,
+            extension_alignment: HashMap::new(),
+            preserve_extensions: HashSet::new(),
+            disc_size: None,
+            encoding: None,
+        }
+    }
+
+    /// Create a builder with the system files and all files of `gcm`. The file
+    /// data is read from the source disc image when building, see
+    /// [`GcmBuilder::build_with_source`].
+    pub fn from_gcm(gcm: &Gcm) -> Self {
+        let mut builder = Self::new(
+            gcm.boot().clone(),
+            gcm.bi2().clone(),
+            gcm.apploader().clone(),
+            gcm.executable().clone(),
+        );
+        for (path, entry) in gcm.fst().files() {
+            if let fst::Entry::File { offset, size, .. } = entry {
+                builder.files.push(File {
+                    path,
+                    source: FileSource::Disc { offset, size },
+                    original_offset: Some(offset),
+                });
+            }
+        }
+        builder
+    }
+
+    /// Create a builder from a directory tree in the extracted layout (see
+    /// [`crate::gcm::extract`]), i.e., the system files are read from `sys/`
+    /// and all files in `files/` are added. `sys/fst.bin` is ignored, the
+    /// [FST][`crate::gcm::Fst`] is regenerated with the entries of each
+    /// directory sorted case-insensitively. File data is read when building.
+    pub fn from_root<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref();
+        let system = root.join(SYSTEM_DIRECTORY);
+        let read = |name: &str| -> Result<Cursor<Vec<u8>>> {
+            Ok(Cursor::new(fs::read(system.join(name))?))
+        };
+
+        let boot = Boot::from_binary(&mut read("boot.bin")?)?;
+        let bi2 = Bi2::from_binary(&mut read("bi2.bin")?)?;
+        let apploader = Apploader::from_binary(&mut read("apploader.img")?)?;
+        let executable = Executable::from_binary(&mut read("main.dol")?)?;
+
+        let mut builder = Self::new(boot, bi2, apploader, executable);
+        builder.add_directory(&root.join(FILES_DIRECTORY), Path::new(""))?;
+        Ok(builder)
+    }
+
+    fn add_directory(&mut self, directory: &Path, path: &Path) -> Result<()> {
+        let mut entries = fs::read_dir(directory)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|x| x.file_name().to_string_lossy().to_lowercase());
+        for entry in entries {
+            let metadata = entry.metadata()?;
+            let name = path.join(entry.file_name());
+            if metadata.is_dir() {
+                self.add_directory(&entry.path(), &name)?;
+            } else {
+                self.add_file_source(name, FileSource::Path {
+                    path: entry.path(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a file (or replace the data of an existing file) at `path`, e.g.,
+    /// `audio/bgm.ast`.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, data: Vec<u8>) -> &mut Self {
+        self.add_file_source(path, FileSource::Data(data))
+    }
+
+    /// Add a file (or replace the data of an existing file) at `path` with
+    /// data from `source`.
+    pub fn add_file_source<P: AsRef<Path>>(&mut self, path: P, source: FileSource) -> &mut Self {
+        let path = normalize(path.as_ref());
+        match self.files.iter_mut().find(|x| x.path == path) {
+            Some(file) => file.source = source,
+            None => self.files.push(File {
+                path,
+                source,
+                original_offset: None,
+            }),
+        }
+        self
+    }
+
+    /// Remove the file at `path`. Returns `true` if the file existed.
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = normalize(path.as_ref());
+        let count = self.files.len();
+        self.files.retain(|x| x.path != path);
+        count != self.files.len()
+    }
+
+    /// Get a mutable reference to the [`Boot`] header. The executable and
+    /// [FST][`crate::gcm::Fst`] offsets are updated when building.
+    pub fn boot_mut(&mut self) -> &mut Boot { &mut self.boot }
+
+    /// Get a mutable reference to the [`Bi2`].
+    pub fn bi2_mut(&mut self) -> &mut Bi2 { &mut self.bi2 }
+
+    /// Set the apploader.
+    pub fn set_apploader(&mut self, apploader: Apploader) -> &mut Self {
+        self.apploader = apploader;
+        self
+    }
+
+    /// Set the main executable.
+    pub fn set_executable(&mut self, executable: Executable) -> &mut Self {
+        self.executable = executable;
+        self
+    }
+
+    /// Set the default alignment of files. Must be a power of two.
+    pub fn alignment(&mut self, alignment: u32) -> &mut Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Set the alignment of files with the extension `extension` (e.g.,
+    /// `thp`). Must be a power of two.
+    pub fn extension_alignment(&mut self, extension: &str, alignment: u32) -> &mut Self {
+        self.extension_alignment.insert(
+            extension.trim_start_matches('.').to_ascii_lowercase(),
+            alignment,
+        );
+        self
+    }
+
+    /// Keep the original offset of files with the extension `extension`
+    /// (e.g., `thp`). Only applies to files from the original disc, see
+    /// [`GcmBuilder::from_gcm`].
+    pub fn preserve_offsets(&mut self, extension: &str) -> &mut Self {
+        self.preserve_extensions
+            .insert(extension.trim_start_matches('.').to_ascii_lowercase());
+        self
+    }
+
+    /// Pad the output image to `size` bytes, e.g.,
+    /// [`GAMECUBE_DISC_SIZE`][`crate::gcm::junk::GAMECUBE_DISC_SIZE`].
+    pub fn disc_size(&mut self, size: u64) -> &mut Self {
+        self.disc_size = Some(size);
+        self
+    }
+
+    /// Set the encoding of the [FST][`crate::gcm::Fst`] names. By default,
+    /// the [encoding][`Encoding::from_region`] usually used for the region of
+    /// the game is used (the same as [`Gcm::from_binary`]).
+    pub fn encoding(&mut self, encoding: Encoding) -> &mut Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    fn extension(path: &Path) -> Option<String> {
+        path.extension()
+            .map(|x| x.to_string_lossy().to_ascii_lowercase())
+    }
+
+    fn file_alignment(&self, file: &File) -> u64 {
+        Self::extension(&file.path)
+            .and_then(|x| self.extension_alignment.get(&x))
+            .copied()
+            .unwrap_or(self.alignment) as u64
+    }
+
+    fn is_preserved(&self, file: &File) -> bool {
+        file.original_offset.is_some()
+            && Self::extension(&file.path).is_some_and(|x| self.preserve_extensions.contains(&x))
+    }
+
+    /// Build the [FST][`crate::gcm::Fst`] tree in the order the files were
+    /// added.
+    fn tree(&self) -> Vec<Node> {
+        let mut root = Vec::new();
+        for (index, file) in self.files.iter().enumerate() {
+            let mut nodes = &mut root;
+            let components = file
+                .path
+                .iter()
+                .map(|x| x.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            let Some((name, directories)) = components.split_last() else {
+                continue;
+            };
+            for directory in directories {
+                let position = nodes
+                    .iter()
+                    .position(|x| matches!(x, Node::Directory(x, _) if x == directory));
+                let position = match position {
+                    Some(position) => position,
+                    None => {
+                        nodes.push(Node::Directory(directory.clone(), Vec::new()));
+                        nodes.len() - 1
+                    },
+                };
+                let Node::Directory(_, children) = &mut nodes[position] else {
+                    unreachable!()
+                };
+                nodes = children;
+            }
+            nodes.push(Node::File(name.clone(), index));
+        }
+        root
+    }
+
+    /// Compute the offset of every file.
+    fn layout(&self, start: u64) -> Result<Vec<u64>> {
+        let mut offsets = vec![0; self.files.len()];
+        let mut used: Vec<Range<u64>> = Vec::new();
+        let overlaps = |used: &[Range<u64>], range: &Range<u64>| {
+            used.iter()
+                .find(|x| x.start < range.end && range.start < x.end && range.start != range.end)
+                .map(|x| x.end)
+        };
+
+        // place files at their original offsets first
+        let mut remaining = Vec::new();
+        for (index, file) in self.files.iter().enumerate() {
+            match file.original_offset {
+                Some(offset) if self.is_preserved(file) => {
+                    let range = offset..offset + file.source.size();
+                    if offset >= start && overlaps(&used, &range).is_none() {
+                        offsets[index] = offset;
+                        used.push(range);
+                    } else {
+                        let alignment =
+                            (1 << offset.trailing_zeros().min(63)).min(MAX_PRESERVED_ALIGNMENT);
+                        remaining.push((index, alignment.max(self.file_alignment(file))));
+                    }
+                },
+                _ => remaining.push((index, self.file_alignment(file))),
+            }
+        }
+
+        let mut position = start;
+        for (index, alignment) in remaining {
+            ensure!(
+                alignment.is_power_of_two(),
+                BuildProblem::InvalidData("alignment must be a power of two", Location::current())
+            );
+            let size = self.files[index].source.size();
+            let mut offset = align(position, alignment);
+            while let Some(end) = overlaps(&used, &(offset..offset + size)) {
+                offset = align(end, alignment);
+            }
+            offsets[index] = offset;
+            used.push(offset..offset + size);
+            position = offset + size;
+        }
+
+        Ok(offsets)
+    }
+
+    /// Build the disc image and write it to `output`. Fails if any file data
+    /// comes from a source disc image, use [`GcmBuilder::build_with_source`]
+    /// instead.
+    pub fn build<W: Writer + Seeker>(&self, output: &mut W) -> Result<()> {
+        self.write::<Cursor<&[u8]>, W>(None, output)
+    }
+
+    /// Build the disc image and write it to `output`. File data from the
+    /// original disc image (see [`FileSource::Disc`]) is read from `source`.
+    pub fn build_with_source<D: Parser + Seeker, W: Writer + Seeker>(
+        &self,
+        source: &mut D,
+        output: &mut W,
+    ) -> Result<()> {
+        self.write(Some(source), output)
+    }
+
+    fn write<D: Parser + Seeker, W: Writer + Seeker>(
+        &self,
+        mut source: Option<&mut D>,
+        output: &mut W,
+    ) -> Result<()> {
+        let shift = self.boot.console.offset_shift();
+        let executable_offset = align(
+            APPLOADER_OFFSET + self.apploader.total_size(),
+            SYSTEM_ALIGNMENT,
+        );
+        let fst_offset = align(
+            executable_offset + self.executable.data().len() as u64,
+            SYSTEM_ALIGNMENT,
+        );
+
+        // fst entries and string table, file offsets are filled in later
+        let mut entries = vec![(1_u32 << 24, 0_u32, 0_u32)];
+        let mut names = Vec::new();
+        let mut file_entries = Vec::new();
+        let encoding = self
+            .encoding
+            .unwrap_or_else(|| Encoding::from_region(self.boot.game_id().region()));
+        flatten(
+            &self.tree(),
+            0,
+            encoding,
+            &mut entries,
+            &mut names,
+            &mut file_entries,
+        )?;
+        entries[0].2 = entries.len() as u32;
+        let fst_size = entries.len() as u64 * 0x0C + names.len() as u64;
+
+        let offsets = self.layout(fst_offset + fst_size)?;
+        for (entry, file) in file_entries {
+            entries[entry].1 = to_u32(offsets[file] >> shift)?;
+            entries[entry].2 = to_u32(self.files[file].source.size())?;
+        }
+
+        let mut boot = self.boot.clone();
+        boot.main_executable_offset = to_u32(executable_offset >> shift)?;
+        boot.fst_offset = to_u32(fst_offset >> shift)?;
+        boot.fst_size = to_u32(fst_size >> shift)?;
+        boot.fst_max_size = boot.fst_size;
+
+        let base = output.position()?;
+        boot.to_binary(output)?;
+        self.bi2.to_binary(output)?;
+        self.apploader.to_binary(output)?;
+        output.goto(base + executable_offset)?;
+        self.executable.to_binary(output)?;
+        output.goto(base + fst_offset)?;
+        for (a, b, c) in entries.iter() {
+            output.bu32(*a)?;
+            output.bu32(*b)?;
+            output.bu32(*c)?;
+        }
+        output.u8_array(&names)?;
+
+        let mut end = fst_offset + fst_size;
+        for (file, offset) in self.files.iter().zip(offsets) {
+            let data = match (&file.source, source.as_mut()) {
+                (FileSource::Data(data), _) => data.clone(),
+                (FileSource::Disc { offset, size }, Some(source)) => {
+                    source.goto(*offset)?;
+                    source.read_as_vec(*size as usize)?
+                },
+                (FileSource::Disc { .. }, None) => Err(BuildProblem::InvalidData(
+                    "file data from source disc without source",
+                    Location::current(),
+                ))?,
+                (FileSource::Path { path, size }, _) => {
+                    let data = fs::read(path)?;
+                    ensure!(
+                        data.len() as u64 == *size,
+                        BuildProblem::InvalidData("file size changed", Location::current())
+                    );
+                    data
+                },
+            };
+            output.goto(base + offset)?;
+            output.u8_array(&data)?;
+            end = end.max(offset + data.len() as u64);
+        }
+
+        if let Some(disc_size) = self.disc_size {
+            ensure!(
+                end <= disc_size,
+                BuildProblem::InvalidData("data exceeds the disc size", Location::current())
+            );
+            if end < disc_size {
+                output.goto(base + disc_size - 1)?;
+                output.u8(0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a disc image from the directory tree `root` in the extracted layout
+/// (see [`crate::gcm::extract`]) and write it to `output`. This is the inverse
+/// of [`Gcm::extract_to`], see [`GcmBuilder::from_root`] for details.
+pub fn build_from_root<P: AsRef<Path>, W: Writer + Seeker>(root: P, output: &mut W) -> Result<()> {
+    GcmBuilder::from_root(root)?.build(output)
+}
+
+fn align(value: u64, alignment: u64) -> u64 { (value + alignment - 1) & !(alignment - 1) }
+
+fn to_u32(value: u64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        BuildProblem::InvalidData("offset or size out of range", Location::current()).into()
+    })
+}
+
+/// Normalize a file path, i.e., remove the root and `.` components.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|x| matches!(x, Component::Normal(_)))
+        .collect()
+}
+
+/// Flatten the tree into [FST][`crate::gcm::Fst`] entries (in depth-first
+/// order) with the names encoded with `encoding`. Returns the file entries as
+/// (entry index, file index) pairs.
+fn flatten(
+    nodes: &[Node],
+    parent: u32,
+    encoding: Encoding,
+    entries: &mut Vec<(u32, u32, u32)>,
+    names: &mut Vec<u8>,
+    files: &mut Vec<(usize, usize)>,
+) -> Result<()> {
+    for node in nodes {
+        let name = match node {
+            Node::File(name, _) | Node::Directory(name, _) => name,
+        };
+        let name = encoding.encode(name)?;
+        let name_offset = names.len() as u32;
+        ensure!(
+            name_offset < 0x1000000,
+            BuildProblem::InvalidData("string table too large", Location::current())
+        );
+        names.extend_from_slice(&name);
+        names.push(0);
+
+        let index = entries.len();
+        match node {
+            Node::File(_, file) => {
+                entries.push((name_offset, 0, 0));
+                files.push((index, *file));
+            },
+            Node::Directory(_, children) => {
+                entries.push(((1 << 24) | name_offset, parent, 0));
+                flatten(children, index as u32, encoding, entries, names, files)?;
+                entries[index].2 = entries.len() as u32;
+            },
+        }
+    }
+    Ok(())
+}
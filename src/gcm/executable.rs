@@ -3,10 +3,10 @@
 
 use crate::error::ParseProblem;
 use crate::helper::{Parser, ProblemLocation, Seeker, Writer};
-use crate::Result;
+use crate::{Dol, Result};
 
 /// [GCM][`crate::gcm`] executable file object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Executable {
     data: Vec<u8>,
 }
@@ -51,4 +51,7 @@ impl Executable {
 
     /// Get the executable data.
     pub fn data(&self) -> &[u8] { &self.data }
+
+    /// Parse the executable data as a [`Dol`].
+    pub fn dol(&self) -> Result<Dol> { Dol::from_binary(&mut std::io::Cursor::new(&self.data)) }
 }
@@ -0,0 +1,180 @@
+//! Extract a [GCM][`crate::gcm`] to a directory tree.
+//!
+//! The extracted layout is the one used by Dolphin and most other tools:
+//!
+//! ```text
+//! root/
+//! ├── sys/
+//! │   ├── boot.bin
+//! │   ├── bi2.bin
+//! │   ├── apploader.img
+//! │   ├── main.dol
+//! │   └── fst.bin
+//! └── files/
+//!     └── ...
+//! ```
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! # use picori::gcm::ExtractOptions;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.iso")?;
+//!     let gcm = picori::Gcm::from_binary(&mut file)?;
+//!     let options = ExtractOptions::default().progress(|path, done, total| {
+//!         println!("{}/{}: {}", done, total, path.display());
+//!     });
+//!     gcm.extract_to(&mut file, "root", options)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::gcm::{fst, Gcm};
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Size of the chunks file data is copied in.
+const CHUNK_SIZE: usize = 0x100000;
+
+/// Name of the directory containing the system files.
+pub const SYSTEM_DIRECTORY: &str = "sys";
+
+/// Name of the directory containing the files.
+pub const FILES_DIRECTORY: &str = "files";
+
+/// Progress callback, called with the path being extracted (relative to the
+/// root), the number of bytes extracted so far, and the total number of bytes.
+type Progress<'p> = Box<dyn FnMut(&Path, u64, u64) + 'p>;
+
+/// Options for [`Gcm::extract_to`].
+pub struct ExtractOptions<'p> {
+    system:   bool,
+    files:    bool,
+    progress: Option<Progress<'p>>,
+}
+
+impl Default for ExtractOptions<'_> {
+    fn default() -> Self {
+        Self {
+            system:   true,
+            files:    true,
+            progress: None,
+        }
+    }
+}
+
+impl<'p> ExtractOptions<'p> {
+    /// Extract the system files to `sys/` (default: `true`).
+    pub fn system(mut self, system: bool) -> Self {
+        self.system = system;
+        self
+    }
+
+    /// Extract the files to `files/` (default: `true`).
+    pub fn files(mut self, files: bool) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Call `progress` after every written chunk with the path being extracted
+    /// (relative to the root), the number of bytes extracted so far, and the
+    /// total number of bytes.
+    pub fn progress<F: FnMut(&Path, u64, u64) + 'p>(mut self, progress: F) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+}
+
+/// Check that the FST path only contains normal components, i.e., it can not
+/// escape the `files/` directory.
+fn is_safe_path(path: &Path) -> bool {
+    path.components().all(|x| matches!(x, Component::Normal(_)))
+}
+
+impl Gcm {
+    /// Extract the disc image `reader` (the image this [`Gcm`] was parsed from)
+    /// to the directory `path` with the `sys/` and `files/` layout.
+    pub fn extract_to<D, P>(&self, reader: &mut D, path: P, options: ExtractOptions) -> Result<()>
+    where
+        D: Parser + Seeker,
+        P: AsRef<Path>,
+    {
+        let root = path.as_ref();
+        let mut options = options;
+
+        // (path relative to root, disc offset, size)
+        let mut entries = Vec::new();
+        let mut directories = vec![PathBuf::new()];
+        if options.system {
+            let boot = self.boot();
+            let system = |name| Path::new(SYSTEM_DIRECTORY).join(name);
+            entries.push((system("boot.bin"), 0, 0x440));
+            entries.push((system("bi2.bin"), 0x440, 0x2000));
+            entries.push((
+                system("apploader.img"),
+                0x2440,
+                self.apploader().total_size(),
+            ));
+            entries.push((
+                system("main.dol"),
+                boot.executable_offset(),
+                self.executable().data().len() as u64,
+            ));
+            entries.push((system("fst.bin"), boot.fst_offset(), boot.fst_size()));
+        }
+        if options.files {
+            for (path, entry) in self.fst().files() {
+                ensure!(
+                    is_safe_path(&path),
+                    ParseProblem::InvalidData("invalid file name", std::panic::Location::current())
+                );
+                match entry {
+                    fst::Entry::File { offset, size, .. } => {
+                        entries.push((Path::new(FILES_DIRECTORY).join(path), offset, size as u64))
+                    },
+                    fst::Entry::Directory { .. } => directories.push(path),
+                    fst::Entry::Root => {},
+                }
+            }
+        }
+
+        let total = entries.iter().map(|(_, _, size)| size).sum::<u64>();
+        let mut done = 0;
+        if options.files {
+            for directory in directories {
+                fs::create_dir_all(root.join(FILES_DIRECTORY).join(directory))?;
+            }
+        }
+        for (path, offset, size) in entries {
+            let target = root.join(&path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut output = fs::File::create(&target)?;
+            reader.goto(offset)?;
+            let mut remaining = size;
+            loop {
+                let length = remaining.min(CHUNK_SIZE as u64) as usize;
+                let data = reader.read_as_vec(length)?;
+                output.write_all(&data)?;
+                remaining -= length as u64;
+                done += length as u64;
+                if let Some(progress) = options.progress.as_mut() {
+                    progress(&path, done, total);
+                }
+                if remaining == 0 {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
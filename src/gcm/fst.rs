@@ -2,14 +2,15 @@
 //! information about the file structure of the GameCube disc, i.e. the file
 //! names and their locations.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::ParseProblem;
 use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
-use crate::{Ascii, Result};
+use crate::{Encoding, Result};
 
 /// Enum varient of a single [`Fst`] entry.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Entry {
     /// Root directory.
     Root,
@@ -19,8 +20,9 @@ pub enum Entry {
         name:   String,
         /// `internal`: Entry index.
         index:  u32,
-        /// File offset from the beginning of the GCM file.
-        offset: u32,
+        /// File offset from the beginning of the GCM file (or Wii partition
+        /// data).
+        offset: u64,
         /// File size.
         size:   u32,
     },
@@ -75,6 +77,7 @@ impl RawEntry {
 }
 
 /// [GCM][`crate::gcm`] File String Table (`fst.bin`) object.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fst {
     entries: Vec<Entry>,
 }
@@ -85,6 +88,29 @@ impl Fst {
     /// To read the full string table, this function needs the size of the
     /// [`Fst`]. This is available in the [`crate::gcm::Boot`] struct.
     pub fn from_binary<D: Parser + Seeker>(reader: &mut D, fst_size: usize) -> Result<Fst> {
+        Self::from_binary_with_shift(reader, fst_size, 0)
+    }
+
+    /// Parse GCM FST where file offsets are stored shifted right by `shift`
+    /// bits. This is the case for the FST inside Wii partitions, see
+    /// [`crate::gcm::ConsoleType::offset_shift`].
+    pub fn from_binary_with_shift<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        shift: u32,
+    ) -> Result<Fst> {
+        Self::from_binary_with_encoding(reader, fst_size, shift, Encoding::Ascii)
+    }
+
+    /// Parse GCM FST where the names in the string table are encoded with
+    /// `encoding` (Japanese games use Shift JIS) and file offsets are stored
+    /// shifted right by `shift` bits.
+    pub fn from_binary_with_encoding<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        shift: u32,
+        encoding: Encoding,
+    ) -> Result<Fst> {
         let base = reader.position()?;
 
         let _ = reader.bu32()?;
@@ -105,10 +131,26 @@ impl Fst {
             .collect::<Result<Vec<_>>>()?;
 
         let entry_size = 0x0C * entry_count;
+        ensure!(
+            entry_size <= fst_size,
+            ParseProblem::InvalidRange(
+                "entry count (larger than fst)",
+                std::panic::Location::current()
+            )
+        );
         let name_table_size = fst_size - entry_size;
         let string_table = reader.read_as_vec(name_table_size)?;
 
         let mut entries = Vec::with_capacity(entry_count);
+        let decode_name = |offset: u32| match string_table.get(offset as usize..) {
+            Some(data) => encoding.first(data),
+            None => Err(ParseProblem::InvalidRange(
+                "name offset (outside of string table)",
+                std::panic::Location::current(),
+            )
+            .into()),
+        };
+
         for (i, entry) in temp_entries.iter().enumerate() {
             if i == 0 {
                 entries.push(Entry::Root);
@@ -117,13 +159,13 @@ impl Fst {
 
             let entry = match entry {
                 RawEntry::File { name, offset, size } => Entry::File {
-                    name:   Ascii::first(&string_table[*name as usize..])?,
+                    name:   decode_name(*name)?,
                     index:  i as u32,
-                    offset: *offset,
+                    offset: (*offset as u64) << shift,
                     size:   *size,
                 },
                 RawEntry::Directory { name, parent, end } => Entry::Directory {
-                    name:   Ascii::first(&string_table[*name as usize..])?,
+                    name:   decode_name(*name)?,
                     parent: *parent,
                     begin:  (i + 1) as u32,
                     end:    *end,
@@ -136,8 +178,65 @@ impl Fst {
         Ok(Fst { entries })
     }
 
+    /// Find the [`Entry`] with the given path, e.g., `audiores/Banks`. The
+    /// comparison is case-insensitive (like on the console) and a leading `/`
+    /// is ignored.
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<Entry> {
+        let path = path.as_ref();
+        let path = path.strip_prefix("/").unwrap_or(path);
+        self.files()
+            .find(|(x, _)| {
+                x.components().count() == path.components().count()
+                    && x.components()
+                        .zip(path.components())
+                        .all(|(a, b)| a.as_os_str().eq_ignore_ascii_case(b.as_os_str()))
+            })
+            .map(|(_, entry)| entry)
+    }
+
+    /// Find all entries (files and directories) whose path matches the glob
+    /// `pattern`, e.g., `**/*.arc`. Supported wildcards are `?` (any
+    /// character), `*` (any number of characters within a path component),
+    /// and `**` (any number of path components). Matching is case-insensitive.
+    pub fn glob(&self, pattern: &str) -> Vec<(PathBuf, Entry)> {
+        let pattern = pattern
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+        self.files()
+            .filter(|(_, entry)| *entry != Entry::Root)
+            .filter(|(path, _)| {
+                let path = path.iter().map(|x| x.to_string_lossy()).collect::<Vec<_>>();
+                let path = path.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+                glob_match_path(&pattern, &path)
+            })
+            .collect()
+    }
+
+    /// Find all files with the extension `extension` (with or without the
+    /// leading `.`, e.g., `.bti` or `bti`). Matching is case-insensitive.
+    pub fn find_by_extension(&self, extension: &str) -> Vec<(PathBuf, Entry)> {
+        let extension = extension.trim_start_matches('.');
+        self.files()
+            .filter(|(path, entry)| {
+                matches!(entry, Entry::File { .. })
+                    && path
+                        .extension()
+                        .is_some_and(|x| x.eq_ignore_ascii_case(extension))
+            })
+            .collect()
+    }
+
+    /// Set the size of the file entry at `index`.
+    pub(crate) fn set_file_size(&mut self, index: u32, new_size: u32) {
+        if let Some(Entry::File { size, .. }) = self.entries.get_mut(index as usize) {
+            *size = new_size;
+        }
+    }
+
     /// Get an iterator over all [`Entry`]s.
-    pub fn files(&self) -> FileIterator {
+    pub fn files(&self) -> FileIterator<'_> {
         FileIterator {
             fst: self,
             index: 0,
@@ -147,6 +246,65 @@ impl Fst {
     }
 }
 
+/// Match path components against glob pattern components. `**` is matched by
+/// backtracking to the last `**` only, so matching takes at most
+/// `pattern.len() * path.len()` steps.
+fn glob_match_path(pattern: &[&str], path: &[&str]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < path.len() {
+        match pattern.get(p) {
+            Some(&"**") => {
+                star = Some((p + 1, t));
+                p += 1;
+            },
+            Some(first) if glob_match(first, path[t]) => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((next, start)) => {
+                    star = Some((next, start + 1));
+                    p = next;
+                    t = start + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|x| *x == "**")
+}
+
+/// Match a single path component against a glob pattern component (by
+/// characters, the same way as [`glob_match_path`] matches components).
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            },
+            Some(c) if *c == '?' || c.eq_ignore_ascii_case(&name[t]) => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((next, start)) => {
+                    star = Some((next, start + 1));
+                    p = next;
+                    t = start + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|x| *x == '*')
+}
+
 /// Iterator over all [`Entry`]s in [`Fst`].
 pub struct FileIterator<'fst> {
     fst: &'fst Fst,
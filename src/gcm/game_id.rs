@@ -0,0 +1,200 @@
+//! [GCM][`crate::gcm`] Game ID. The first 6 bytes of the [`Boot`] header
+//! identify the game, e.g., `GZLE01` for The Legend of Zelda: The Wind Waker
+//! (USA):
+//!
+//! | Offset | Size | Description                        |
+//! |--------|------|------------------------------------|
+//! | `0x00` | 1    | Console code (`G`)                 |
+//! | `0x01` | 2    | Game code (`ZL`)                   |
+//! | `0x03` | 1    | Country code (`E`), see [`Region`] |
+//! | `0x04` | 2    | Maker code (`01`)                  |
+//!
+//! ## Example
+//!
+//! ```
+//! # use picori::Result;
+//! # use picori::gcm::{GameId, Region};
+//! fn main() -> Result<()> {
+//!     let id = "GZLP01".parse::<GameId>()?;
+//!     assert_eq!(id.region(), Region::Europe);
+//!     assert!(id.is_pal());
+//!     Ok(())
+//! }
+//! ```
+
+use std::fmt;
+use std::panic::Location;
+use std::str::FromStr;
+
+use crate::error::ParseProblem;
+use crate::gcm::Boot;
+use crate::helper::{ensure, Parser, ProblemLocation, Writer};
+use crate::{Error, Result};
+
+/// Region of a game, determined by the country code of the [`GameId`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Region {
+    /// Japan (NTSC-J), country code `J`.
+    Japan,
+
+    /// North America (NTSC-U), country code `E` (or `N` for Japanese imports).
+    America,
+
+    /// Europe (PAL), country codes `P`, `D`, `F`, `H`, `I`, `S`, `X`, `Y`,
+    /// `Z`, `L`, `M`, `R`, and `V`.
+    Europe,
+
+    /// Australia (PAL), country code `U`.
+    Australia,
+
+    /// Korea (NTSC), country codes `K`, `Q`, and `T`.
+    Korea,
+
+    /// Taiwan (NTSC), country code `W`.
+    Taiwan,
+
+    /// Region free, country code `A`.
+    World,
+
+    /// Unknown country code.
+    Unknown(u8),
+}
+
+impl Region {
+    /// Get the [`Region`] of the country code `code`.
+    pub fn from_country_code(code: u8) -> Self {
+        match code {
+            b'J' => Region::Japan,
+            b'E' | b'N' => Region::America,
+            b'P' | b'D' | b'F' | b'H' | b'I' | b'S' | b'X' | b'Y' | b'Z' | b'L' | b'M' | b'R'
+            | b'V' => Region::Europe,
+            b'U' => Region::Australia,
+            b'K' | b'Q' | b'T' => Region::Korea,
+            b'W' => Region::Taiwan,
+            b'A' => Region::World,
+            _ => Region::Unknown(code),
+        }
+    }
+
+    /// Returns `true` for PAL regions (50 Hz video by default).
+    pub fn is_pal(&self) -> bool { matches!(self, Region::Europe | Region::Australia) }
+
+    /// Returns `true` for NTSC regions.
+    pub fn is_ntsc(&self) -> bool {
+        matches!(
+            self,
+            Region::Japan | Region::America | Region::Korea | Region::Taiwan
+        )
+    }
+}
+
+/// [GCM][`crate::gcm`] Game ID object.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameId {
+    /// Console code, e.g., `G` for GameCube games and `R` for Wii games.
+    pub console_code: u8,
+
+    /// Game code.
+    pub game_code: [u8; 2],
+
+    /// Country code, see [`Region`].
+    pub country_code: u8,
+
+    /// Maker code, e.g., `01` for Nintendo.
+    pub maker_code: [u8; 2],
+}
+
+impl GameId {
+    /// Create a [`GameId`] from the 6 raw bytes.
+    pub fn from_bytes(bytes: [u8; 6]) -> Self {
+        Self {
+            console_code: bytes[0],
+            game_code:    [bytes[1], bytes[2]],
+            country_code: bytes[3],
+            maker_code:   [bytes[4], bytes[5]],
+        }
+    }
+
+    /// Parse [`GameId`] (6 bytes).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        Ok(Self::from_bytes(input.u8_array::<6>()?))
+    }
+
+    /// Get the 6 raw bytes.
+    pub fn to_bytes(&self) -> [u8; 6] {
+        [
+            self.console_code,
+            self.game_code[0],
+            self.game_code[1],
+            self.country_code,
+            self.maker_code[0],
+            self.maker_code[1],
+        ]
+    }
+
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.u8_array(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Get the [`Region`] of the game.
+    pub fn region(&self) -> Region { Region::from_country_code(self.country_code) }
+
+    /// Returns `true` if the game is for a PAL region.
+    pub fn is_pal(&self) -> bool { self.region().is_pal() }
+
+    /// Returns `true` if the game is for a NTSC region.
+    pub fn is_ntsc(&self) -> bool { self.region().is_ntsc() }
+
+    /// Get the 4 byte game ID without the maker code, e.g., `GZLE`. This is
+    /// also the ID used for Wii titles.
+    pub fn short(&self) -> [u8; 4] {
+        [
+            self.console_code,
+            self.game_code[0],
+            self.game_code[1],
+            self.country_code,
+        ]
+    }
+}
+
+impl From<&Boot> for GameId {
+    fn from(boot: &Boot) -> Self {
+        Self {
+            console_code: boot.console_code,
+            game_code:    boot.game_code,
+            country_code: boot.country_code,
+            maker_code:   boot.maker_code,
+        }
+    }
+}
+
+impl fmt::Display for GameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.to_bytes() {
+            if byte.is_ascii_graphic() {
+                write!(f, "{}", byte as char)?;
+            } else {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameId {
+    type Err = Error;
+
+    /// Parse a [`GameId`] from its 6 character string, e.g., `GZLE01`.
+    fn from_str(value: &str) -> Result<Self> {
+        ensure!(
+            value.len() == 6 && value.is_ascii(),
+            ParseProblem::InvalidData("expected 6 ascii characters", Location::current())
+        );
+        let mut bytes = [0u8; 6];
+        bytes.copy_from_slice(value.as_bytes());
+        Ok(Self::from_bytes(bytes))
+    }
+}
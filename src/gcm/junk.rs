@@ -0,0 +1,219 @@
+//! [GCM][`crate::gcm`] junk data.
+//!
+//! Unused areas of GameCube (and Wii) discs are not filled with zeros but with
+//! pseudo-random "junk" data generated by Nintendo's mastering tools. The junk
+//! is produced by a lagged Fibonacci generator that is reseeded at the start
+//! of every `0x8000` byte sector of the disc, using the first four bytes of
+//! the game id, the disc number, and the sector index. Because of this, junk can
+//! be regenerated for any offset of the disc, which is used to restore
+//! scrubbed (or [NKit][`crate::nkit`]) images back to verifiable full images.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::OpenOptions;
+//! # use picori::Result;
+//! # use picori::gcm::junk;
+//! fn main() -> Result<()> {
+//!     let mut file = OpenOptions::new()
+//!         .read(true)
+//!         .write(true)
+//!         .open("scrubbed.iso")?;
+//!     let gcm = picori::Gcm::from_binary(&mut file)?;
+//!     let mut junk = junk::Junk::from_boot(gcm.boot());
+//!     junk::restore(&gcm, &mut junk, junk::GAMECUBE_DISC_SIZE, &mut file)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::ops::Range;
+
+use crate::gcm::{fst, Boot, Gcm};
+use crate::helper::{Seeker, Writer};
+use crate::Result;
+
+/// Size of the blocks (disc sectors) the generator is reseeded at.
+pub const BLOCK_SIZE: u64 = 0x8000;
+
+/// Size of a full GameCube disc.
+pub const GAMECUBE_DISC_SIZE: u64 = 0x57058000;
+
+const K: usize = 521;
+const J: usize = 32;
+const SEED_SIZE: usize = 17;
+
+/// Junk data generator.
+#[derive(Clone)]
+pub struct Junk {
+    seed:     u32,
+    block:    Option<u64>,
+    offset:   usize,
+    buffer:   [u32; K],
+    position: usize,
+}
+
+impl Junk {
+    /// Create a junk generator for the game id (the first four bytes of the
+    /// disc) and disc number.
+    pub fn new(id: [u8; 4], disc: u8) -> Self {
+        // the seed is a permutation of the id bytes, not the id itself
+        let seed = u32::from_be_bytes([
+            id[2],
+            id[1],
+            id[3].wrapping_add(id[2]),
+            id[0].wrapping_add(id[1]),
+        ]) ^ disc as u32;
+        Self {
+            seed,
+            block: None,
+            offset: 0,
+            buffer: [0; K],
+            position: 0,
+        }
+    }
+
+    /// Create a junk generator for the disc described by the [`Boot`] header.
+    pub fn from_boot(boot: &Boot) -> Self { Self::new(boot.game_id().short(), boot.disc_id) }
+
+    fn reseed(&mut self, block: u64) {
+        let mut sample = self.seed.wrapping_mul(0x260BCD5)
+            ^ (block as u32).wrapping_mul(0x1EF29123);
+        for i in 0..SEED_SIZE {
+            let mut value = 0u32;
+            for _ in 0..32 {
+                sample = sample.wrapping_mul(0x5D588B65).wrapping_add(1);
+                value = (value >> 1) | (sample & 0x80000000);
+            }
+            self.buffer[i] = value;
+        }
+
+        self.buffer[16] ^= (self.buffer[0] >> 9) ^ (self.buffer[16] << 23);
+        for i in SEED_SIZE..K {
+            self.buffer[i] =
+                (self.buffer[i - 17] << 23) ^ (self.buffer[i - 16] >> 9) ^ self.buffer[i - 1];
+        }
+
+        // the output skips bits 16-17 of every word, do the shifting once here
+        // instead of for every byte.
+        for x in self.buffer.iter_mut() {
+            *x = (*x & 0xFF00FFFF) | ((*x >> 2) & 0x00FF0000);
+        }
+
+        for _ in 0..4 {
+            self.forward();
+        }
+
+        self.block = Some(block);
+        self.offset = 0;
+        self.position = 0;
+    }
+
+    fn forward(&mut self) {
+        for i in 0..J {
+            self.buffer[i] ^= self.buffer[i + K - J];
+        }
+        for i in J..K {
+            self.buffer[i] ^= self.buffer[i - J];
+        }
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.offset += count;
+        self.position += count;
+        while self.position >= K * 4 {
+            self.forward();
+            self.position -= K * 4;
+        }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        let byte = self.buffer[self.position / 4].to_be_bytes()[self.position % 4];
+        self.skip(1);
+        byte
+    }
+
+    /// Fill `buffer` with the junk data located at `offset` of the disc.
+    pub fn fill(&mut self, offset: u64, buffer: &mut [u8]) {
+        let mut offset = offset;
+        let mut buffer = buffer;
+        while !buffer.is_empty() {
+            let block = offset / BLOCK_SIZE;
+            let block_offset = (offset % BLOCK_SIZE) as usize;
+            let length = buffer.len().min((BLOCK_SIZE as usize) - block_offset);
+
+            // the generator can only move forward, reseed if needed.
+            if self.block != Some(block) || self.offset > block_offset {
+                self.reseed(block);
+            }
+            self.skip(block_offset - self.offset);
+
+            let (current, rest) = buffer.split_at_mut(length);
+            for byte in current.iter_mut() {
+                *byte = self.next_byte();
+            }
+
+            offset += length as u64;
+            buffer = rest;
+        }
+    }
+}
+
+/// Unused regions of the disc, i.e., the regions between the system area
+/// (boot, bi2, apploader, executable, and FST) and the files, and between the
+/// files themselves. The regions start at the first 4 byte aligned offset
+/// after the data before them, the bytes before that are zero on the original
+/// disc.
+pub fn unused_regions(gcm: &Gcm, disc_size: u64) -> Vec<Range<u64>> {
+    let boot = gcm.boot();
+    let system_end = (boot.fst_offset() + boot.fst_size())
+        .max(boot.executable_offset() + gcm.executable().data().len() as u64);
+
+    let mut used = gcm
+        .fst()
+        .files()
+        .filter_map(|(_, entry)| match entry {
+            fst::Entry::File { offset, size, .. } => Some(offset..offset + size as u64),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    used.push(0..system_end);
+    used.sort_by_key(|x| x.start);
+
+    let mut regions = Vec::new();
+    let mut position = 0;
+    for range in used {
+        let start = (position + 3) & !3;
+        if range.start > start {
+            regions.push(start..range.start);
+        }
+        position = position.max(range.end);
+    }
+
+    let start = (position + 3) & !3;
+    if disc_size > start {
+        regions.push(start..disc_size);
+    }
+    regions
+}
+
+/// Write the junk data for all [unused regions][`unused_regions`] of the disc
+/// to `output`. This is used to restore the junk data of scrubbed images.
+pub fn restore<W: Writer + Seeker>(
+    gcm: &Gcm,
+    junk: &mut Junk,
+    disc_size: u64,
+    output: &mut W,
+) -> Result<()> {
+    let mut buffer = vec![0u8; BLOCK_SIZE as usize];
+    for region in unused_regions(gcm, disc_size) {
+        output.goto(region.start)?;
+        let mut offset = region.start;
+        while offset < region.end {
+            let length = (region.end - offset).min(BLOCK_SIZE - offset % BLOCK_SIZE) as usize;
+            junk.fill(offset, &mut buffer[..length]);
+            output.u8_array(&buffer[..length])?;
+            offset += length as u64;
+        }
+    }
+    Ok(())
+}
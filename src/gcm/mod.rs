@@ -26,8 +26,12 @@
 pub mod apploader;
 pub mod bi2;
 pub mod boot;
+pub mod builder;
 pub mod executable;
+pub mod extract;
 pub mod fst;
+pub mod game_id;
+pub mod junk;
 
 #[doc(inline)]
 pub use apploader::*;
@@ -36,12 +40,24 @@ pub use bi2::*;
 #[doc(inline)]
 pub use boot::*;
 #[doc(inline)]
+pub use builder::{build_from_root, GcmBuilder};
+#[doc(inline)]
 pub use executable::*;
 #[doc(inline)]
+pub use extract::ExtractOptions;
+#[doc(inline)]
 pub use fst::Fst;
+#[doc(inline)]
+pub use game_id::{GameId, Region};
+#[doc(inline)]
+pub use junk::Junk;
+
+use std::io::Cursor;
 
-use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
-use crate::Result;
+use crate::disc::{DiscKind, DiscReader, DiscStream};
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Bnr, Dol, Encoding, Result};
 
 /// `.gcm` file object.
 ///
@@ -60,9 +76,36 @@ pub struct Gcm {
 }
 
 impl Gcm {
-    /// Parse GCM file from binary stream.
+    /// Parse GCM file from binary stream. [FST][`Fst`] names are decoded with
+    /// the [encoding][`Encoding::from_region`] usually used for the region of
+    /// the game.
     pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Gcm> {
+        Self::from_binary_with_encoding(reader, None)
+    }
+
+    /// Parse GCM file from a [`DiscReader`] backend (e.g., a
+    /// [CISO][`crate::ciso`] or [WBFS][`crate::wbfs`] image). To read file
+    /// data afterwards, wrap the backend in a [`DiscStream`] and use
+    /// [`Gcm::from_binary`] instead.
+    pub fn from_disc<R: DiscReader>(reader: R) -> Result<Gcm> {
+        Self::from_binary(&mut DiscStream::new(reader))
+    }
+
+    /// Parse GCM file from binary stream with [FST][`Fst`] names decoded with
+    /// `encoding`. If `encoding` is `None`, the encoding is chosen based on
+    /// the region of the game (see [`Encoding::from_region`]).
+    pub fn from_binary_with_encoding<D: Parser + Seeker>(
+        reader: &mut D,
+        encoding: Option<Encoding>,
+    ) -> Result<Gcm> {
         let position = reader.position()?;
+        ensure!(
+            DiscKind::detect(reader)?.is_some(),
+            ParseProblem::InvalidMagic(
+                "expected GameCube (0xC2339F3D) or Wii (0x5D1C9EA3) disc magic",
+                std::panic::Location::current()
+            )
+        );
 
         let boot = Boot::from_binary(reader)?;
         ensure!(
@@ -82,11 +125,17 @@ impl Gcm {
             ParseProblem::InvalidData("invalid apploader", std::panic::Location::current())
         );
 
-        reader.goto(position + boot.main_executable_offset as u64)?;
+        reader.goto(position + boot.executable_offset())?;
         let executable = Executable::from_binary(reader)?;
 
-        reader.goto(position + boot.fst_offset as u64)?;
-        let fst = Fst::from_binary(reader, boot.fst_size as usize)?;
+        reader.goto(position + boot.fst_offset())?;
+        let encoding = encoding.unwrap_or_else(|| Encoding::from_region(boot.game_id().region()));
+        let fst = Fst::from_binary_with_encoding(
+            reader,
+            boot.fst_size() as usize,
+            boot.console.offset_shift(),
+            encoding,
+        )?;
 
         Ok(Gcm {
             boot,
@@ -100,15 +149,147 @@ impl Gcm {
     /// Get reference to [`Boot`] struct.
     pub fn boot(&self) -> &Boot { &self.boot }
 
+    /// Parse the main executable (`main.dol`) directly from a disc image,
+    /// without parsing the rest of the [`Gcm`]. The offset is read from the
+    /// [`Boot`] header and the size is determined by the [`Dol`] header.
+    pub fn main_dol<D: Parser + Seeker>(reader: &mut D) -> Result<Dol> {
+        let position = reader.position()?;
+        let boot = Boot::from_binary(reader)?;
+        reader.goto(position + boot.executable_offset())?;
+        Dol::from_binary(reader)
+    }
+
     /// Get reference to [`Bi2`] struct.
     pub fn bi2(&self) -> &Bi2 { &self.bi2 }
 
     /// Get reference to [`Apploader`] struct.
     pub fn apploader(&self) -> &Apploader { &self.apploader }
 
+    /// Extract the apploader, i.e., write `apploader.img` to `output`.
+    pub fn extract_apploader<W: Writer>(&self, output: &mut W) -> Result<()> {
+        self.apploader.to_binary(output)
+    }
+
+    /// Replace the apploader of the disc image `disc` (the image this [`Gcm`]
+    /// was parsed from). The new apploader has to fit in the space before the
+    /// main executable, the [FST][`Fst`], and the first file.
+    pub fn replace_apploader<D: Writer + Seeker>(
+        &mut self,
+        disc: &mut D,
+        apploader: Apploader,
+    ) -> Result<()> {
+        let first_file = self
+            .fst
+            .files()
+            .filter_map(|(_, entry)| match entry {
+                fst::Entry::File { offset, .. } => Some(offset),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(u64::MAX);
+        let limit = self
+            .boot
+            .executable_offset()
+            .min(self.boot.fst_offset())
+            .min(first_file);
+        ensure!(
+            APPLOADER_OFFSET + apploader.total_size() <= limit,
+            BuildProblem::InvalidData(
+                "apploader does not fit before the executable",
+                std::panic::Location::current()
+            )
+        );
+
+        disc.goto(APPLOADER_OFFSET)?;
+        apploader.to_binary(disc)?;
+        self.apploader = apploader;
+        Ok(())
+    }
+
     /// Get reference to [`Executable`] struct.
     pub fn executable(&self) -> &Executable { &self.executable }
 
     /// Get reference to [`Fst`] struct.
     pub fn fst(&self) -> &Fst { &self.fst }
+
+    /// Read the data of the file at `path` (see [`Fst::find`]) from the disc
+    /// image `reader`. Returns [`None`] if there is no such file.
+    pub fn file_data<D: Parser + Seeker, P: AsRef<std::path::Path>>(
+        &self,
+        reader: &mut D,
+        path: P,
+    ) -> Result<Option<Vec<u8>>> {
+        match self.fst.find(path) {
+            Some(fst::Entry::File { offset, size, .. }) => {
+                reader.goto(offset)?;
+                Ok(Some(reader.read_as_vec(size as usize)?))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the data of the file at `path` (see [`Fst::find`]) in the disc
+    /// image `disc` (the image this [`Gcm`] was parsed from) without
+    /// rebuilding the image. The new data has to fit in the space between the
+    /// start of the file and the next file (or the end of the original data
+    /// for the last file). Left over bytes of the original data are zeroed and
+    /// the size in the [FST][`Fst`] is updated.
+    pub fn replace_file<D: Writer + Seeker, P: AsRef<std::path::Path>>(
+        &mut self,
+        disc: &mut D,
+        path: P,
+        data: &[u8],
+    ) -> Result<()> {
+        let Some(fst::Entry::File {
+            index,
+            offset,
+            size,
+            ..
+        }) = self.fst.find(path)
+        else {
+            return Err(BuildProblem::InvalidData(
+                "file not found",
+                std::panic::Location::current(),
+            )
+            .into());
+        };
+
+        let next = self
+            .fst
+            .files()
+            .filter_map(|(_, entry)| match entry {
+                fst::Entry::File { offset: other, .. } if other > offset => Some(other),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(offset + size as u64);
+        ensure!(
+            offset + data.len() as u64 <= next.max(offset + size as u64),
+            BuildProblem::InvalidData(
+                "file does not fit in the original slot",
+                std::panic::Location::current()
+            )
+        );
+
+        disc.goto(offset)?;
+        disc.u8_array(data)?;
+        if data.len() < size as usize {
+            disc.u8_array(&vec![0; size as usize - data.len()])?;
+        }
+
+        // update the size in the fst
+        disc.goto(self.boot.fst_offset() + index as u64 * 0x0C + 0x08)?;
+        disc.bu32(data.len() as u32)?;
+        self.fst.set_file_size(index, data.len() as u32);
+        Ok(())
+    }
+
+    /// Read the banner (`opening.bnr`) from the disc image `reader`. Returns
+    /// [`None`] if the disc has no banner. The banner is parsed with
+    /// [`Bnr::from_binary`] and the region of the game.
+    pub fn banner<D: Parser + Seeker>(&self, reader: &mut D) -> Result<Option<Bnr>> {
+        self.file_data(reader, "opening.bnr")?
+            .map(|data| Bnr::from_binary(&mut Cursor::new(data), self.boot.game_id().region()))
+            .transpose()
+    }
 }
@@ -0,0 +1,187 @@
+//! Gecko code lists (`.gct`).
+//!
+//! GCT is the binary code list format of the Gecko code handler (used by,
+//! e.g., Nintendont, Gecko OS, and the Riivolution `codes` option). The file
+//! is a list of 8-byte [lines][`Line`] enclosed by a magic and a terminator:
+//!
+//! | Offset | Size | Description                      |
+//! |--------|------|----------------------------------|
+//! | `0x00` | 8    | Magic (`00D0C0DE 00D0C0DE`)      |
+//! | `0x08` | 8×N  | Lines of the codes               |
+//! | ...    | 8    | Terminator (`F0000000 00000000`) |
+//!
+//! The boundaries (and names) of the codes are not stored, the lines of all
+//! codes are concatenated. Lists are built from [codes][`Code`] with
+//! [`Gct::from_codes`].
+//!
+//! [`Gct::writes`] resolves the constant RAM writes (code types `00` to `07`)
+//! to [`RamWrite`]s, e.g., to apply the codes to an executable or a memory
+//! dump. Other code types depend on the state of the game and can only be
+//! executed by the code handler.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("GZLE01.gct")?;
+//!     let gct = picori::gct::Gct::from_binary(&mut file)?;
+//!     for write in gct.writes()? {
+//!         println!("{:08x}: {:02x?}", write.address, write.data);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::Result;
+
+/// [GCT][`crate::gct`] magic (`00D0C0DE 00D0C0DE`).
+pub const MAGIC: [u32; 2] = [0x00D0C0DE, 0x00D0C0DE];
+
+/// Terminator of a [GCT][`crate::gct`] code list (`F0000000 00000000`).
+pub const TERMINATOR: [u32; 2] = [0xF0000000, 0x00000000];
+
+/// Default base address of the code handler (`ba`).
+pub const BASE_ADDRESS: u32 = 0x80000000;
+
+/// Line of a code (two 32-bit words).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Line {
+    /// First word (code type and address).
+    pub code: u32,
+
+    /// Second word (value).
+    pub value: u32,
+}
+
+impl Line {
+    /// Create a line from the two words.
+    pub fn new(code: u32, value: u32) -> Self { Self { code, value } }
+
+    /// Code type (the first byte without the address bit), e.g., `0x04` for
+    /// 32-bit writes.
+    pub fn kind(&self) -> u8 { (self.code >> 24) as u8 & 0xFE }
+
+    /// Offset of the address (relative to `ba` or `po`), including the
+    /// address bit of the code type.
+    pub fn offset(&self) -> u32 { self.code & 0x01FFFFFF }
+}
+
+/// Named code (e.g., from a text code list), the name is not stored in GCT
+/// files.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Code {
+    /// Name of the code.
+    pub name: String,
+
+    /// Lines.
+    pub lines: Vec<Line>,
+}
+
+/// Constant write to RAM (see [`Gct::writes`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RamWrite {
+    /// Address.
+    pub address: u32,
+
+    /// Data.
+    pub data: Vec<u8>,
+}
+
+/// GCT code list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Gct {
+    /// Lines of all codes (without the magic and the terminator).
+    pub lines: Vec<Line>,
+}
+
+impl Gct {
+    /// Create a code list with the lines of `codes` (in order).
+    pub fn from_codes(codes: &[Code]) -> Self {
+        Self {
+            lines: codes.iter().flat_map(|x| x.lines.iter().copied()).collect(),
+        }
+    }
+
+    /// Parse GCT code list from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.bu32_array::<2>()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: 00D0C0DE 00D0C0DE", Location::current())
+        );
+
+        let end = input.seek(std::io::SeekFrom::End(0))?;
+        input.goto(base + 8)?;
+        let mut lines = Vec::new();
+        let mut terminated = false;
+        while input.position()? + 8 <= end {
+            let [code, value] = input.bu32_array::<2>()?;
+            if [code, value] == TERMINATOR {
+                terminated = true;
+                break;
+            }
+            lines.push(Line { code, value });
+        }
+        ensure!(
+            terminated,
+            ParseProblem::InvalidData("GCT terminator (missing)", Location::current())
+        );
+        Ok(Self { lines })
+    }
+
+    /// Write GCT code list to binary stream.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.bu32_array(&MAGIC)?;
+        for line in self.lines.iter() {
+            output.bu32(line.code)?;
+            output.bu32(line.value)?;
+        }
+        output.bu32_array(&TERMINATOR)?;
+        Ok(())
+    }
+
+    /// Resolve the constant RAM writes of the code list (code types `00` to
+    /// `07` relative to [`BASE_ADDRESS`]). Returns an error if the list
+    /// contains any other code type.
+    pub fn writes(&self) -> Result<Vec<RamWrite>> {
+        let mut writes = Vec::new();
+        let mut lines = self.lines.iter();
+        while let Some(line) = lines.next() {
+            let address = BASE_ADDRESS.wrapping_add(line.offset());
+            let data = match line.kind() {
+                0x00 => vec![line.value as u8; (line.value >> 16) as usize + 1],
+                0x02 => {
+                    let count = (line.value >> 16) as usize + 1;
+                    (line.value as u16).to_be_bytes().repeat(count)
+                },
+                0x04 => line.value.to_be_bytes().to_vec(),
+                0x06 => {
+                    let size = line.value as usize;
+                    let mut data = Vec::with_capacity(size.next_multiple_of(8));
+                    for _ in 0..size.div_ceil(8) {
+                        let line = lines.next().ok_or(ParseProblem::InvalidData(
+                            "GCT string write (truncated)",
+                            Location::current(),
+                        ))?;
+                        data.extend_from_slice(&line.code.to_be_bytes());
+                        data.extend_from_slice(&line.value.to_be_bytes());
+                    }
+                    data.truncate(size);
+                    data
+                },
+                _ => Err(ParseProblem::InvalidData(
+                    "GCT code type (not a constant RAM write)",
+                    Location::current(),
+                ))?,
+            };
+            writes.push(RamWrite { address, data });
+        }
+        Ok(writes)
+    }
+}
@@ -0,0 +1,354 @@
+//! [GCZ][`crate::gcz`] compressed disc images (requires the `gcz` feature).
+//!
+//! [GCZ][`crate::gcz`] is a compressed disc image format introduced by the
+//! Dolphin emulator. The disc image is split into blocks of equal size that are
+//! compressed with zlib individually. Blocks that do not compress well are
+//! stored uncompressed. Every block has an Adler-32 checksum of the stored
+//! data.
+//!
+//! # Parse
+//!
+//! Use [`GczReader`] to read the image block by block, or
+//! [`GczReader::decompress`] to decompress the whole image at once.
+//!
+//! # Build
+//!
+//! Use [`GczWriter`] to compress an image block by block.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut input = File::open("game.gcz")?;
+//!     let mut reader = picori::GczReader::new(&mut input)?;
+//!     let mut output = File::create("game.iso")?;
+//!     reader.decompress(&mut output)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Read, Write};
+use std::panic::Location;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::error::{BuildProblem, DecompressionProblem};
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::Result;
+
+/// [GCZ][`crate::gcz`] magic number.
+static MAGIC: u32 = 0xB10BC001;
+
+/// Size of the [GCZ][`crate::gcz`] header.
+const HEADER_SIZE: u64 = 0x20;
+
+/// Block offset flag for blocks stored uncompressed.
+const UNCOMPRESSED: u64 = 1 << 63;
+
+/// Default block size used when building [GCZ][`crate::gcz`] files.
+pub const DEFAULT_BLOCK_SIZE: usize = 0x8000;
+
+/// Adler-32 checksum.
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1_u32;
+    let mut b = 0_u32;
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// [GCZ][`crate::gcz`] header.
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// Sub type, `0` for GameCube and `1` for Wii images.
+    pub sub_type: u32,
+
+    /// Total size of the compressed data.
+    pub compressed_data_size: u64,
+
+    /// Total size of the decompressed data.
+    pub data_size: u64,
+
+    /// Block size.
+    pub block_size: u32,
+
+    /// Number of blocks.
+    pub block_count: u32,
+}
+
+impl Header {
+    /// Parse [GCZ][`crate::gcz`] header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let magic = input.lu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: 0xB10BC001", Location::current())
+        );
+
+        let sub_type = input.lu32()?;
+        let compressed_data_size = input.lu64()?;
+        let data_size = input.lu64()?;
+        let block_size = input.lu32()?;
+        let block_count = input.lu32()?;
+        ensure!(
+            block_size > 0 && block_size <= 0x8000000,
+            ParseProblem::InvalidRange("0 < block size <= 0x8000000", Location::current())
+        );
+        ensure!(
+            data_size.div_ceil(block_size as u64) == block_count as u64,
+            ParseProblem::InvalidHeader("block count mismatch", Location::current())
+        );
+
+        Ok(Self {
+            sub_type,
+            compressed_data_size,
+            data_size,
+            block_size,
+            block_count,
+        })
+    }
+
+    /// Write [GCZ][`crate::gcz`] header.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.lu32(MAGIC)?;
+        output.lu32(self.sub_type)?;
+        output.lu64(self.compressed_data_size)?;
+        output.lu64(self.data_size)?;
+        output.lu32(self.block_size)?;
+        output.lu32(self.block_count)?;
+        Ok(())
+    }
+}
+
+/// Reader for [GCZ][`crate::gcz`] files.
+pub struct GczReader<'reader, D: Parser + Seeker> {
+    header:      Header,
+    reader:      &'reader mut D,
+    offsets:     Vec<u64>,
+    hashes:      Vec<u32>,
+    data_offset: u64,
+}
+
+impl<'reader, D: Parser + Seeker> GczReader<'reader, D> {
+    /// Create a new [GCZ][`crate::gcz`] reader from a binary stream.
+    pub fn new(reader: &'reader mut D) -> Result<Self> {
+        let header = Header::from_binary(reader)?;
+        let offsets = (0..header.block_count)
+            .map(|_| reader.lu64())
+            .collect::<Result<Vec<_>>>()?;
+        let hashes = (0..header.block_count)
+            .map(|_| reader.lu32())
+            .collect::<Result<Vec<_>>>()?;
+        let data_offset = reader.position()?;
+        Ok(Self {
+            header,
+            reader,
+            offsets,
+            hashes,
+            data_offset,
+        })
+    }
+
+    /// Get the [GCZ][`crate::gcz`] header.
+    pub fn header(&self) -> &Header { &self.header }
+
+    /// Get the block size of the [GCZ][`crate::gcz`] file.
+    pub fn block_size(&self) -> usize { self.header.block_size as usize }
+
+    /// Get the number of blocks.
+    pub fn block_count(&self) -> usize { self.offsets.len() }
+
+    /// Get the total size of the decompressed file.
+    pub fn total_size(&self) -> u64 { self.header.data_size }
+
+    /// Read and decompress the data of block at index `index`. The last block
+    /// is truncated to the total size.
+    pub fn read_block(&mut self, index: usize) -> Result<Vec<u8>> {
+        let offset = self.offsets[index];
+        let start = offset & !UNCOMPRESSED;
+        let end = match self.offsets.get(index + 1) {
+            Some(next) => next & !UNCOMPRESSED,
+            None => self.header.compressed_data_size,
+        };
+        ensure!(
+            start <= end && end - start <= self.header.block_size as u64 + 0x1000,
+            DecompressionProblem::InvalidData("invalid block offset", Location::current())
+        );
+
+        self.reader.goto(self.data_offset + start)?;
+        let stored = self.reader.read_as_vec((end - start) as usize)?;
+        ensure!(
+            adler32(&stored) == self.hashes[index],
+            DecompressionProblem::InvalidData("block checksum mismatch", Location::current())
+        );
+
+        let block_start = index as u64 * self.header.block_size as u64;
+        let size = (self.header.data_size - block_start).min(self.header.block_size as u64);
+        if offset & UNCOMPRESSED != 0 {
+            ensure!(
+                stored.len() as u64 >= size,
+                DecompressionProblem::UnexpectedEndOfData(Location::current())
+            );
+            let mut stored = stored;
+            stored.truncate(size as usize);
+            return Ok(stored);
+        }
+
+        let mut data = Vec::with_capacity(size as usize);
+        ZlibDecoder::new(stored.as_slice()).read_to_end(&mut data)?;
+        ensure!(
+            data.len() as u64 == size,
+            DecompressionProblem::InvalidDecompressedSize(Location::current())
+        );
+        Ok(data)
+    }
+
+    /// Decompress all [GCZ][`crate::gcz`] blocks and write the data to a
+    /// [`std::io::Write`].
+    pub fn decompress<O: Write>(&mut self, writer: &mut O) -> Result<()> {
+        for index in 0..self.block_count() {
+            writer.write_all(&self.read_block(index)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer for [GCZ][`crate::gcz`] files. The total size has to be known up
+/// front, the block table is written when calling [`GczWriter::finish`].
+pub struct GczWriter<'writer, W: Writer + Seeker> {
+    writer:  &'writer mut W,
+    base:    u64,
+    header:  Header,
+    offsets: Vec<u64>,
+    hashes:  Vec<u32>,
+    buffer:  Vec<u8>,
+    written: u64,
+}
+
+impl<'writer, W: Writer + Seeker> GczWriter<'writer, W> {
+    /// Create a new [GCZ][`crate::gcz`] writer for an image of `data_size`
+    /// bytes.
+    pub fn new(
+        writer: &'writer mut W,
+        sub_type: u32,
+        data_size: u64,
+        block_size: usize,
+    ) -> Result<Self> {
+        ensure!(
+            block_size > 0 && block_size <= 0x8000000,
+            BuildProblem::InvalidData("0 < block size <= 0x8000000", Location::current())
+        );
+        let block_count = data_size.div_ceil(block_size as u64);
+        ensure!(
+            block_count <= u32::MAX as u64,
+            BuildProblem::InvalidData("too many blocks", Location::current())
+        );
+
+        let header = Header {
+            sub_type,
+            compressed_data_size: 0,
+            data_size,
+            block_size: block_size as u32,
+            block_count: block_count as u32,
+        };
+
+        // reserve space for the header and block tables
+        let base = writer.position()?;
+        writer.goto(base + HEADER_SIZE + block_count * 12)?;
+        Ok(Self {
+            writer,
+            base,
+            header,
+            offsets: Vec::with_capacity(block_count as usize),
+            hashes: Vec::with_capacity(block_count as usize),
+            buffer: Vec::with_capacity(block_size),
+            written: 0,
+        })
+    }
+
+    fn write_block(&mut self) -> Result<()> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&self.buffer)?;
+        let compressed = encoder.finish()?;
+
+        let (flag, stored) = if compressed.len() < self.buffer.len() {
+            (0, compressed.as_slice())
+        } else {
+            (UNCOMPRESSED, self.buffer.as_slice())
+        };
+
+        self.offsets.push(self.written | flag);
+        self.hashes.push(adler32(stored));
+        self.writer.u8_array(stored)?;
+        self.written += stored.len() as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Write data to the [GCZ][`crate::gcz`] file.
+    pub fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        let block_size = self.header.block_size as usize;
+        let mut data = data;
+        while !data.is_empty() {
+            ensure!(
+                self.offsets.len() < self.header.block_count as usize,
+                BuildProblem::InvalidData("data exceeds the total size", Location::current())
+            );
+            let length = data.len().min(block_size - self.buffer.len());
+            self.buffer.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if self.buffer.len() == block_size {
+                self.write_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the last (partial) block, the header, and the block tables.
+    pub fn finish(mut self) -> Result<()> {
+        if !self.buffer.is_empty() {
+            self.write_block()?;
+        }
+        ensure!(
+            self.offsets.len() == self.header.block_count as usize,
+            BuildProblem::InvalidData("data is less than the total size", Location::current())
+        );
+
+        let end = self.writer.position()?;
+        self.header.compressed_data_size = self.written;
+        self.writer.goto(self.base)?;
+        self.header.to_binary(self.writer)?;
+        for offset in self.offsets.iter() {
+            self.writer.lu64(*offset)?;
+        }
+        for hash in self.hashes.iter() {
+            self.writer.lu32(*hash)?;
+        }
+        self.writer.goto(end)?;
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_checksum() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+}
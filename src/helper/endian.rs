@@ -11,6 +11,7 @@ pub type NativeEndian = BigEndian;
 pub trait EndianAgnostic {
     fn u16_from_bytes(bytes: &[u8]) -> u16;
     fn u32_from_bytes(bytes: &[u8]) -> u32;
+    fn u64_from_bytes(bytes: &[u8]) -> u64;
 
     fn u16_from_native(n: u16) -> u16;
     fn u32_from_native(n: u32) -> u32;
@@ -29,6 +30,12 @@ impl EndianAgnostic for LittleEndian {
         u32::from_le_bytes(bytes.try_into().unwrap())
     }
 
+    #[inline]
+    fn u64_from_bytes(bytes: &[u8]) -> u64 {
+        debug_assert!(bytes.len() == 8);
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
     #[inline]
     fn u16_from_native(n: u16) -> u16 { n.to_le() }
 
@@ -49,6 +56,12 @@ impl EndianAgnostic for BigEndian {
         u32::from_be_bytes(bytes.try_into().unwrap())
     }
 
+    #[inline]
+    fn u64_from_bytes(bytes: &[u8]) -> u64 {
+        debug_assert!(bytes.len() == 8);
+        u64::from_be_bytes(bytes.try_into().unwrap())
+    }
+
     #[inline]
     fn u16_from_native(n: u16) -> u16 { n.to_be() }
 
@@ -71,6 +84,10 @@ mod tests {
             BigEndian::u32_from_bytes(&[0x01, 0x02, 0x03, 0x04]),
             0x01020304
         );
+        assert_eq!(
+            BigEndian::u64_from_bytes(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]),
+            0x0102030405060708
+        );
     }
 
     #[test]
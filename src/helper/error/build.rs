@@ -1,4 +1,10 @@
+use core::panic::Location;
+
 /// Enum for possible build problems that can occur.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
-pub enum BuildProblem {}
+pub enum BuildProblem {
+    /// The input can not be represented in the output format.
+    #[error("invalid data: {0}")]
+    InvalidData(&'static str, &'static Location<'static>),
+}
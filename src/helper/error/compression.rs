@@ -1,4 +1,10 @@
+use core::panic::Location;
+
 /// Enum for possible compression problems that can occur.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
-pub enum CompressionProblem {}
+pub enum CompressionProblem {
+    /// The size of the data does not match the size given up front.
+    #[error("invalid size: {0} at {1}")]
+    InvalidSize(&'static str, &'static Location<'static>),
+}
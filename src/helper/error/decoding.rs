@@ -1,4 +1,5 @@
-use std::panic::Location;
+use alloc::boxed::Box;
+use core::panic::Location;
 
 /// Enum for possible decoding problems that can occur.
 #[derive(thiserror::Error, Debug)]
@@ -16,4 +17,29 @@ pub enum DecodingProblem {
     /// data to fully decode.
     #[error("unexpected EOD: {0}")]
     UnexpectedEndOfData(&'static Location<'static>),
+
+    /// The decoding problem occurred at the given byte offset of the input,
+    /// after the given number of characters were decoded successfully. The
+    /// offset is the first byte of the invalid character.
+    #[error("{2} (at offset {0:#x}, after {1} valid characters)")]
+    AtOffset(usize, usize, Box<DecodingProblem>),
+}
+
+impl DecodingProblem {
+    /// The byte offset of the invalid character and the number of valid
+    /// characters before it, if known.
+    pub fn offset(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::AtOffset(offset, valid, _) => Some((*offset, *valid)),
+            _ => None,
+        }
+    }
+
+    /// The underlying problem, i.e., without the offset.
+    pub fn problem(&self) -> &DecodingProblem {
+        match self {
+            Self::AtOffset(_, _, problem) => problem.problem(),
+            _ => self,
+        }
+    }
 }
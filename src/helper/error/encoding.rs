@@ -1,4 +1,4 @@
-use std::panic::Location;
+use core::panic::Location;
 
 /// Enum for possible encoding problems that can occur.
 #[derive(thiserror::Error, Debug)]
@@ -9,4 +9,9 @@ pub enum EncodingProblem {
 
     #[error("unable to encode code point: {0} at {1}")]
     UnableToEncodeCodePoint(char, &'static Location<'static>),
+
+    /// The character at the given byte index of the string is not part of the
+    /// encoding.
+    #[error("unable to encode character {0:?} at index {1}: {2}")]
+    InvalidCharacter(char, usize, &'static Location<'static>),
 }
@@ -5,7 +5,7 @@ pub mod decompression;
 pub mod encoding;
 pub mod parse;
 
-use std::panic::Location;
+use core::panic::Location;
 
 use super::{
     BuildProblem, CompressionProblem, DecodingProblem, DecompressionProblem, EncodingProblem,
@@ -42,25 +42,29 @@ pub enum Error {
     Decoding(#[from] DecodingProblem),
 
     /// Reading failed.
+    #[cfg(feature = "std")]
     #[error("read failed: {0} bytes ({1}) at {2}")]
     ReadFailed(usize, #[source] std::io::Error, &'static Location<'static>),
 
     /// Seeking failed.
+    #[cfg(feature = "std")]
     #[error("seek failed: {0} at {1}")]
     SeekFailed(#[source] std::io::Error, &'static Location<'static>),
 
     /// Writing failed.
+    #[cfg(feature = "std")]
     #[error("write failed: {0} bytes ({1}) at {2}")]
     WriteFailed(usize, #[source] std::io::Error, &'static Location<'static>),
 
     /// Unknown IO error.
+    #[cfg(feature = "std")]
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 }
 
 /// A specialized [`Result`] type for Picori. This type is broadly used across
 /// internal and public APIs. The Err variant is [`Error`].
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 macro_rules! ensure {
     ($cond:expr, $err:expr) => {
@@ -74,7 +78,7 @@ pub(crate) use ensure;
 
 pub trait ProblemLocation {
     #[track_caller]
-    fn current() -> &'static std::panic::Location<'static> { std::panic::Location::caller() }
+    fn current() -> &'static Location<'static> { Location::caller() }
 }
 
 impl ProblemLocation for Location<'_> {}
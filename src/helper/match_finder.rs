@@ -0,0 +1,147 @@
+/// Shortest back-reference, also the number of bytes hashed.
+const MIN_MATCH: usize = 3;
+
+/// Number of bits of the hash of the first [`MIN_MATCH`] bytes of a match.
+const HASH_BITS: u32 = 15;
+
+/// Hash chains over the positions of the input, used to find back-references.
+pub(crate) struct MatchFinder {
+    max_length: usize,
+    base:       usize,
+    head:       Vec<usize>,
+    prev:       Vec<usize>,
+    inserted:   usize,
+    candidates: Vec<usize>,
+}
+
+impl MatchFinder {
+    /// Creates a match finder for back-references of at most `max_length`
+    /// bytes.
+    pub(crate) fn new(max_length: usize) -> Self { Self::starting_at(max_length, 0) }
+
+    /// Creates a match finder that ignores all positions before `start`.
+    pub(crate) fn starting_at(max_length: usize, start: usize) -> Self {
+        Self {
+            max_length,
+            base: start,
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            prev: Vec::new(),
+            inserted: start,
+            candidates: Vec::new(),
+        }
+    }
+
+    fn hash(data: &[u8]) -> usize {
+        let value = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+        (value.wrapping_mul(0x9E3779B1) >> (32 - HASH_BITS)) as usize
+    }
+
+    /// Insert all positions before `end` into the hash chains.
+    fn insert_until(&mut self, data: &[u8], end: usize) {
+        while self.inserted < end {
+            let position = self.inserted;
+            if position + MIN_MATCH <= data.len() {
+                let hash = Self::hash(&data[position..]);
+                self.prev.push(self.head[hash]);
+                self.head[hash] = position;
+            } else {
+                self.prev.push(usize::MAX);
+            }
+            self.inserted += 1;
+        }
+    }
+
+    /// Forget all positions and insert the positions before `end` again,
+    /// used after the front of the data has been discarded.
+    pub(crate) fn reset(&mut self, data: &[u8], end: usize) {
+        self.head.fill(usize::MAX);
+        self.prev.clear();
+        self.base = 0;
+        self.inserted = 0;
+        self.insert_until(data, end);
+    }
+
+    /// Find the longest back-reference for the data at `position`, at most
+    /// `window` bytes back. If there are multiple, the nearest is returned,
+    /// or the farthest if `farthest` is set. Returns the length and the
+    /// distance.
+    pub(crate) fn find(
+        &mut self,
+        data: &[u8],
+        position: usize,
+        window: usize,
+        farthest: bool,
+    ) -> (usize, usize) {
+        self.insert_until(data, position);
+        let max_length = self.max_length.min(data.len() - position);
+        if max_length < MIN_MATCH {
+            return (0, 0);
+        }
+
+        let target = &data[position..position + max_length];
+        let mut best = (0, 0);
+        // checks a candidate, returns true if no longer match is possible
+        let mut check = |candidate: usize| {
+            // a longer match must also match the byte after the best match
+            if data[candidate + best.0] == target[best.0] {
+                let length = match_length(&data[candidate..], target);
+                if length > best.0 {
+                    best = (length, position - candidate);
+                }
+            }
+            best.0 == max_length
+        };
+
+        let mut candidate = self.head[Self::hash(target)];
+        if farthest {
+            // the chains go from the nearest to the farthest position, so
+            // collect them first and check them in reverse order. The search
+            // still stops at the first match of `max_length`, e.g., in long
+            // runs of the same byte.
+            self.candidates.clear();
+            while candidate != usize::MAX && position - candidate <= window {
+                self.candidates.push(candidate);
+                candidate = self.prev[candidate - self.base];
+            }
+            for &candidate in self.candidates.iter().rev() {
+                if check(candidate) {
+                    break;
+                }
+            }
+        } else {
+            while candidate != usize::MAX && position - candidate <= window {
+                if check(candidate) {
+                    break;
+                }
+                candidate = self.prev[candidate - self.base];
+            }
+        }
+
+        if best.0 < MIN_MATCH {
+            (0, 0)
+        } else {
+            best
+        }
+    }
+}
+
+/// Number of equal bytes at the start of `a` and `target`, at most
+/// `target.len()`.
+fn match_length(a: &[u8], target: &[u8]) -> usize {
+    let mut length = 0;
+    for (a, b) in a.chunks_exact(8).zip(target.chunks_exact(8)) {
+        let a = u64::from_le_bytes(a.try_into().unwrap());
+        let b = u64::from_le_bytes(b.try_into().unwrap());
+        if a != b {
+            return length + ((a ^ b).trailing_zeros() / 8) as usize;
+        }
+        length += 8;
+    }
+
+    length
+        + a[length..]
+            .iter()
+            .zip(&target[length..])
+            .take_while(|(a, b)| a == b)
+            .count()
+}
@@ -1,10 +1,22 @@
+#[cfg(feature = "std")]
 pub mod alignment;
+#[cfg(feature = "std")]
 mod endian;
 mod error;
+#[cfg(feature = "std")]
+mod match_finder;
 
+#[cfg(feature = "std")]
 mod parser;
+#[cfg(feature = "std")]
 mod reader;
+#[cfg(feature = "std")]
 mod seeker;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub(crate) mod serde;
+#[cfg(feature = "std")]
+pub(crate) mod tree;
+#[cfg(feature = "std")]
 mod writer;
 
 pub use error::build::BuildProblem;
@@ -15,9 +27,15 @@ pub use error::encoding::EncodingProblem;
 pub use error::parse::ParseProblem;
 pub(crate) use error::{ensure, ProblemLocation};
 pub use error::{Error, Result};
+#[cfg(feature = "std")]
+pub(crate) use match_finder::MatchFinder;
+#[cfg(feature = "std")]
+pub use parser::Parser;
+#[cfg(feature = "std")]
 pub(crate) use parser::*;
-
-pub use seeker::Seeker;
+#[cfg(feature = "std")]
 pub use reader::Reader;
+#[cfg(feature = "std")]
+pub use seeker::Seeker;
+#[cfg(feature = "std")]
 pub use writer::Writer;
-pub use parser::Parser;
\ No newline at end of file
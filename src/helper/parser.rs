@@ -30,6 +30,13 @@ pub trait Parser: Reader {
         Ok(E::u32_from_bytes(&buffer))
     }
 
+    /// Read a single endian agnostic u64.
+    fn eu64<E: EndianAgnostic>(&mut self, caller: &'static Location) -> Result<u64> {
+        let mut buffer = [0u8; 8];
+        self.read_into_tracked(&mut buffer, caller)?;
+        Ok(E::u64_from_bytes(&buffer))
+    }
+
     /// Read a single u16 in native endian.
     #[track_caller]
     #[inline]
@@ -58,6 +65,13 @@ pub trait Parser: Reader {
         self.eu32::<BigEndian>(Location::caller())
     }
 
+    /// Read a single u64 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bu64(&mut self) -> Result<u64> {
+        self.eu64::<BigEndian>(Location::caller())
+    }
+
     /// Read a single u16 in little endian.
     #[track_caller]
     #[inline]
@@ -72,6 +86,13 @@ pub trait Parser: Reader {
         self.eu32::<LittleEndian>(Location::caller())
     }
 
+    /// Read a single u64 in little endian.
+    #[track_caller]
+    #[inline]
+    fn lu64(&mut self) -> Result<u64> {
+        self.eu64::<LittleEndian>(Location::caller())
+    }
+
     /// Read L bytes and parse them as a string until the first string terminator.
     #[track_caller]
     #[inline]
@@ -155,6 +176,7 @@ pub trait Parser: Reader {
 
 impl Parser for std::fs::File {}
 impl<T: Parser> Parser for std::io::BufReader<T> {}
+impl<T: Parser + ?Sized> Parser for &mut T {}
 impl<T> Parser for std::io::Cursor<T>
 where
     Self: Reader,
@@ -209,6 +231,17 @@ mod tests {
         assert_eq!(cursor.lu32().unwrap(), 0x04030201);
     }
 
+    #[test]
+    fn u64() {
+        let data: &[u8] = &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            0x07, 0x08,
+        ];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bu64().unwrap(), 0x0102030405060708);
+        assert_eq!(cursor.lu64().unwrap(), 0x0807060504030201);
+    }
+
     #[test]
     fn u8_array() {
         let data: &[u8] = &[0x01, 0x02, 0x03, 0x04];
@@ -37,7 +37,7 @@ pub trait Reader: Read {
     /// Read `L` items of type `T` from this reader or `L` * `sizeof(T)` bytes.
     #[track_caller]
     #[inline]
-    fn read_buffer_of<T: Sized, const L: usize>(&mut self) -> Result<[T; L]>
+    fn read_buffer_of<T, const L: usize>(&mut self) -> Result<[T; L]>
     where
         T: Copy + Default,
     {
@@ -47,7 +47,7 @@ pub trait Reader: Read {
     /// Read `L` items of type `T` from this reader or `L` * `sizeof(T)` bytes.
     /// With caller location.
     #[inline]
-    fn read_buffer_of_tracked<T: Sized, const L: usize>(
+    fn read_buffer_of_tracked<T, const L: usize>(
         &mut self,
         caller: &'static std::panic::Location,
     ) -> Result<[T; L]>
@@ -65,6 +65,7 @@ pub trait Reader: Read {
 
 impl Reader for std::fs::File {}
 impl<T: Reader> Reader for std::io::BufReader<T> {}
+impl<T: Reader + ?Sized> Reader for &mut T {}
 impl<T> Reader for std::io::Cursor<T>
 where
     Self: Read,
@@ -38,6 +38,7 @@ pub trait Seeker: Seek {
 
 impl Seeker for std::fs::File {}
 impl<T: Seeker> Seeker for std::io::BufReader<T> {}
+impl<T: Seeker + ?Sized> Seeker for &mut T {}
 impl<T> Seeker for std::io::Cursor<T>
 where
     Self: Seek,
@@ -0,0 +1,29 @@
+//! Helpers for the `serde` feature.
+
+use alloc::vec::Vec;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// (De)serialize byte arrays longer than 32 bytes (which `serde` does not
+/// support), as a sequence of bytes. Use with `#[serde(with = "...")]`.
+pub mod array {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let value = Vec::<u8>::deserialize(deserializer)?;
+        let size = value.len();
+        value
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(size, &"byte array of matching size"))
+    }
+}
@@ -0,0 +1,170 @@
+//! In-memory directory tree shared by the archive builders.
+
+use std::panic::Location;
+use std::path::Path;
+
+use crate::helper::{ensure, BuildProblem, ProblemLocation};
+use crate::Result;
+
+/// Directory of an in-memory archive tree, e.g.,
+/// [`U8Folder`][`crate::u8arc::U8Folder`] or
+/// [`RarcFolder`][`crate::rarc::RarcFolder`]. The archive types only describe
+/// their children, finding, adding, removing, and renaming entries is
+/// implemented once here. Paths are relative to the directory and are
+/// compared case-insensitively.
+pub(crate) trait Folder: Sized {
+    /// File of the tree.
+    type File;
+    /// Child of a directory, either a file or a directory.
+    type Item;
+
+    /// Files and subdirectories (in the order they are stored).
+    fn children_mut(&mut self) -> &mut Vec<Self::Item>;
+
+    /// Name of the file or directory `item`.
+    fn item_name(item: &Self::Item) -> &str;
+
+    /// Set the name of the file or directory `item`.
+    fn set_item_name(item: &mut Self::Item, name: String);
+
+    /// Create an empty directory item named `name`.
+    fn folder_item(name: &str) -> Self::Item;
+
+    /// Get `item` if it is a directory.
+    fn as_folder(item: &mut Self::Item) -> Option<&mut Self>;
+
+    /// Get `item` if it is a file.
+    fn as_file(item: &mut Self::Item) -> Option<&mut Self::File>;
+
+    /// Get the file or directory at `path`.
+    fn find_mut(&mut self, path: &Path) -> Option<&mut Self::Item> {
+        let (parent, name) = self.parent_mut(path)?;
+        parent
+            .children_mut()
+            .iter_mut()
+            .find(|x| Self::item_name(x).eq_ignore_ascii_case(&name))
+    }
+
+    /// Get the directory containing `path` and the last component of `path`.
+    fn parent_mut(&mut self, path: &Path) -> Option<(&mut Self, String)> {
+        let path = path.strip_prefix("/").unwrap_or(path);
+        let mut components = path.iter().map(|x| x.to_string_lossy()).collect::<Vec<_>>();
+        let name = components.pop()?.into_owned();
+        let mut folder = self;
+        for component in components {
+            folder = folder.children_mut().iter_mut().find_map(|x| {
+                if Self::item_name(x).eq_ignore_ascii_case(&component) {
+                    Self::as_folder(x)
+                } else {
+                    None
+                }
+            })?;
+        }
+        Some((folder, name))
+    }
+
+    /// Get the subdirectory `name`, creating it if there is none.
+    fn folder_mut(&mut self, name: &str) -> &mut Self {
+        let children = self.children_mut();
+        let index = children
+            .iter_mut()
+            .position(|x| Self::item_name(x) == name && Self::as_folder(x).is_some());
+        let index = match index {
+            Some(index) => index,
+            None => {
+                children.push(Self::folder_item(name));
+                children.len() - 1
+            },
+        };
+        Self::as_folder(&mut children[index]).unwrap()
+    }
+
+    /// Get the directory at `path`, creating missing directories.
+    fn add_directory(&mut self, path: &Path) -> &mut Self {
+        let mut folder = self;
+        for component in path.iter() {
+            folder = folder.folder_mut(&component.to_string_lossy());
+        }
+        folder
+    }
+
+    /// Add (or replace) the file at `path` with the file created by `file`
+    /// from the name, creating missing directories.
+    fn add_file(
+        &mut self,
+        path: &Path,
+        file: impl FnOnce(String) -> Self::Item,
+    ) -> &mut Self::File {
+        let folder = match path.parent() {
+            Some(parent) => self.add_directory(parent),
+            None => self,
+        };
+
+        let name = path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let children = folder.children_mut();
+        let index = children
+            .iter_mut()
+            .position(|x| Self::item_name(x) == name && Self::as_file(x).is_some());
+        let item = file(name);
+        let index = match index {
+            Some(index) => {
+                children[index] = item;
+                index
+            },
+            None => {
+                children.push(item);
+                children.len() - 1
+            },
+        };
+        Self::as_file(&mut children[index]).unwrap()
+    }
+
+    /// Get the existing file at `path`.
+    fn file_mut(&mut self, path: &Path) -> Result<&mut Self::File> {
+        self.find_mut(path)
+            .and_then(Self::as_file)
+            .ok_or_else(|| BuildProblem::InvalidData("file not found", Location::current()).into())
+    }
+
+    /// Remove the file or directory (including its contents) at `path`.
+    fn remove(&mut self, path: &Path) -> Option<Self::Item> {
+        let (parent, name) = self.parent_mut(path)?;
+        let children = parent.children_mut();
+        let index = children
+            .iter()
+            .position(|x| Self::item_name(x).eq_ignore_ascii_case(&name))?;
+        Some(children.remove(index))
+    }
+
+    /// Rename the file or directory at `path` to `name` (keeping its position
+    /// in the directory).
+    fn rename(&mut self, path: &Path, name: &str) -> Result<()> {
+        ensure!(
+            !name.is_empty() && !name.contains('/') && name != "." && name != "..",
+            BuildProblem::InvalidData("invalid name", Location::current())
+        );
+        let Some((parent, old)) = self.parent_mut(path) else {
+            return Err(BuildProblem::InvalidData("entry not found", Location::current()).into());
+        };
+        let children = parent.children_mut();
+        ensure!(
+            old.eq_ignore_ascii_case(name)
+                || children
+                    .iter()
+                    .all(|x| !Self::item_name(x).eq_ignore_ascii_case(name)),
+            BuildProblem::InvalidData("entry already exists", Location::current())
+        );
+        let item = children
+            .iter_mut()
+            .find(|x| Self::item_name(x).eq_ignore_ascii_case(&old))
+            .ok_or(BuildProblem::InvalidData(
+                "entry not found",
+                Location::current(),
+            ))?;
+        Self::set_item_name(item, name.to_string());
+        Ok(())
+    }
+}
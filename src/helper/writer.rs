@@ -46,6 +46,12 @@ pub trait Writer: Write {
         self.write_buffer_tracked(&value.to_le_bytes(), Location::caller())
     }
 
+    #[track_caller]
+    #[inline]
+    fn lu64(&mut self, value: u64) -> Result<()> {
+        self.write_buffer_tracked(&value.to_le_bytes(), Location::caller())
+    }
+
     #[track_caller]
     #[inline]
     fn bu16(&mut self, value: u16) -> Result<()> {
@@ -58,6 +64,12 @@ pub trait Writer: Write {
         self.write_buffer_tracked(&value.to_be_bytes(), Location::caller())
     }
 
+    #[track_caller]
+    #[inline]
+    fn bu64(&mut self, value: u64) -> Result<()> {
+        self.write_buffer_tracked(&value.to_be_bytes(), Location::caller())
+    }
+
     #[inline]
     fn bu32_array(&mut self, value: &[u32]) -> Result<()> {
         for value in value {
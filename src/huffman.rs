@@ -0,0 +1,172 @@
+//! Huffman decompression (Nintendo compression type `0x20`).
+//!
+//! The data starts with a little-endian header holding the type (`0x24` for
+//! 4-bit or `0x28` for 8-bit symbols) in the lowest byte and the decompressed
+//! size in the upper 24 bits (zero means the size follows as a separate
+//! 32-bit value). It is followed by the tree and the bit stream, stored in
+//! little-endian 32-bit words and read from the most significant bit.
+//!
+//! ## Decompression
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("data.huf")?;
+//!     let header = picori::huffman::Header::from_binary(&mut file)?;
+//!     let decompressed = picori::huffman::decompress(&mut file, &header)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::compression::Decompress;
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Huffman header.
+pub struct Header {
+    /// Type byte (`0x24` or `0x28`).
+    pub id: u8,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+}
+
+impl Header {
+    /// Reads a Huffman header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        let value = input.lu32()?;
+        let decompressed_size = match value >> 8 {
+            0 => input.lu32()?,
+            size => size,
+        };
+        Ok(Header {
+            id: value as u8,
+            decompressed_size,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.id == 0x24 || self.id == 0x28 }
+
+    /// Number of bits of a symbol (4 or 8).
+    pub fn symbol_bits(&self) -> u32 { (self.id & 0xF) as u32 }
+}
+
+/// Check if the given data is Huffman compressed by looking at the type byte
+/// of the header.
+pub fn is_huffman<D: Parser + Seeker>(input: &mut D) -> bool {
+    let mut check = || -> Result<bool> {
+        let base = input.position()?;
+        let header = Header::from_binary(input)?;
+        input.goto(base)?;
+        Ok(header.is_valid())
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Decompresses the data into a new allocated [`Vec`]. `input` must be
+/// positioned directly after the `header`.
+pub fn decompress<D: Parser + Seeker>(input: &mut D, header: &Header) -> Result<Vec<u8>> {
+    let mut output = vec![0; header.decompressed_size as usize];
+    decompress_into(input, header, output.as_mut_slice())?;
+    Ok(output)
+}
+
+/// Decompresses the data into the beginning of the given buffer, which must
+/// be large enough to hold the decompressed data. `input` must be positioned
+/// directly after the `header`. Returns the number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(
+    input: &mut D,
+    header: &Header,
+    destination: &mut [u8],
+) -> Result<usize> {
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid type", Location::current())
+    );
+
+    let written = header.decompressed_size as usize;
+    ensure!(
+        destination.len() >= written,
+        InvalidDecompressedSize(Location::current())
+    );
+    let destination = &mut destination[..written];
+
+    // the tree size byte is part of the tree, the root node follows it
+    let tree_size = (input.u8()? as usize + 1) * 2;
+    let mut tree = vec![0; tree_size];
+    input.read_into(&mut tree[1..])?;
+
+    let symbol_bits = header.symbol_bits();
+    let symbols = destination.len() * 8 / symbol_bits as usize;
+    let mut node = 1;
+    let mut word = 0;
+    let mut word_bits = 0;
+    let mut symbol = 0;
+    while symbol < symbols {
+        if word_bits == 0 {
+            word = input.lu32()?;
+            word_bits = 32;
+        }
+        let bit = (word >> 31) as usize;
+        word <<= 1;
+        word_bits -= 1;
+
+        // each node holds the offset of its pair of children and a flag per
+        // child that tells whether the child is a leaf
+        let value = tree[node];
+        let child = (node & !1) + (value as usize & 0x3F) * 2 + 2 + bit;
+        ensure!(
+            child < tree.len(),
+            InvalidData("Invalid tree node", Location::current())
+        );
+        if value & (0x80 >> bit) == 0 {
+            node = child;
+            continue;
+        }
+
+        let value = tree[child];
+        if symbol_bits == 8 {
+            destination[symbol] = value;
+        } else {
+            let byte = &mut destination[symbol / 2];
+            if symbol % 2 == 0 {
+                *byte = value & 0xF;
+            } else {
+                *byte |= (value & 0xF) << 4;
+            }
+        }
+        symbol += 1;
+        node = 1;
+    }
+
+    Ok(written)
+}
+
+/// Huffman codec, see [`crate::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Huffman;
+
+impl Decompress for Huffman {
+    const NAME: &'static str = "Huffman";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_huffman(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let header = Header::from_binary(input)?;
+        decompress(input, &header)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let header = Header::from_binary(input)?;
+        decompress_into(input, &header, destination)
+    }
+}
@@ -0,0 +1,250 @@
+//! Wii banner headers, IMET (`opening.bnr`) and IMD5 (`banner.bin`,
+//! `icon.bin`, and `sound.bin`).
+//!
+//! The `opening.bnr` of a Wii disc (and the first content of a channel) starts
+//! with an [IMET header][`Imet`], followed by a [U8][`crate::u8arc`] archive
+//! with the files `meta/banner.bin`, `meta/icon.bin`, and `meta/sound.bin`.
+//! The IMET header contains the name of the channel in ten languages and the
+//! MD5 hash of the header itself.
+//!
+//! | Offset  | Size  | Description                                    |
+//! |---------|-------|------------------------------------------------|
+//! | `0x000` | 0x40  | Padding (zero)                                 |
+//! | `0x040` | 4     | Magic (`IMET`)                                 |
+//! | `0x044` | 4     | Size of the hashed data (`0x600`)              |
+//! | `0x048` | 4     | Version (`3`)                                  |
+//! | `0x04C` | 12    | Sizes of `icon.bin`, `banner.bin`, `sound.bin` |
+//! | `0x058` | 4     | Flag                                           |
+//! | `0x05C` | 0x348 | Names (UTF-16, 42 characters per [`Language`]) |
+//! | `0x3A4` | 0x24C | Padding (zero)                                 |
+//! | `0x5F0` | 16    | MD5 hash of the header (with a zero hash)      |
+//!
+//! In the first content of a channel (`00000000.app`), the header is preceded
+//! by another `0x40` bytes (the build information), i.e., the magic is at
+//! `0x80`.
+//!
+//! The files of the archive start with an [IMD5 header][`Imd5`], the MD5 hash
+//! of the (usually [LZ77][`crate::lz`] compressed) file data that follows it.
+//!
+//! The hashes are verified and computed with the `hash` feature, see
+//! [`Imet::is_valid`], [`Imet::to_binary`], [`Imd5::is_valid`], and
+//! [`Imd5::to_binary`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::imet::{Imet, Language};
+//!
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("opening.bnr")?;
+//!     let imet = Imet::from_binary(&mut file)?;
+//!     println!("{}", imet.name(Language::English));
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+#[cfg(feature = "hash")]
+use md5::{Digest, Md5};
+
+#[cfg(feature = "hash")]
+use crate::error::BuildProblem;
+#[cfg(feature = "hash")]
+use crate::helper::Writer;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation};
+use crate::{Encoding, Result};
+
+/// Size of the IMET header, including the leading padding.
+pub const IMET_SIZE: usize = 0x600;
+
+/// Size of the IMD5 header.
+pub const IMD5_SIZE: usize = 0x20;
+
+/// Number of characters (UTF-16 code units) of a name.
+const NAME_LENGTH: usize = 42;
+
+/// Number of languages.
+const LANGUAGES: usize = 10;
+
+/// Language of a name of the [`Imet`] header, in the order they are stored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Language {
+    Japanese,
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+    Dutch,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Korean,
+}
+
+impl Language {
+    /// Get the index of the name for the language.
+    pub fn index(&self) -> usize { *self as usize }
+}
+
+/// IMET header of `opening.bnr`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Imet {
+    /// Sizes of `icon.bin`, `banner.bin`, and `sound.bin`.
+    pub sizes: [u32; 3],
+
+    /// Flag, usually zero.
+    pub flag: u32,
+
+    /// Names of the channel, one per [`Language`]. Names can have two lines
+    /// (separated by `\n`), the name and a subtitle.
+    pub names: [String; LANGUAGES],
+
+    /// MD5 hash of the header.
+    pub hash: [u8; 16],
+}
+
+impl Imet {
+    /// Create an empty header.
+    pub fn new() -> Self { Self::default() }
+
+    /// Parse IMET header (`0x600` bytes, starting with the padding before the
+    /// magic).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let _padding = input.u8_array::<0x40>()?;
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            &magic == b"IMET",
+            ParseProblem::InvalidMagic("expected: IMET", Location::current())
+        );
+        let hash_size = input.bu32()?;
+        ensure!(
+            hash_size as usize == IMET_SIZE,
+            ParseProblem::InvalidHeader("IMET hash size (expected: 0x600)", Location::current())
+        );
+        let _version = input.bu32()?;
+        let sizes = input.bu32_array::<3>()?;
+        let flag = input.bu32()?;
+
+        let mut names: [String; LANGUAGES] = Default::default();
+        for name in names.iter_mut() {
+            *name = Encoding::Utf16Be.first(&input.u8_array::<{ NAME_LENGTH * 2 }>()?)?;
+        }
+        let _padding = input.u8_array::<0x24C>()?;
+        let hash = input.u8_array::<16>()?;
+
+        Ok(Self {
+            sizes,
+            flag,
+            names,
+            hash,
+        })
+    }
+
+    /// Get the name of the channel in the language `language`.
+    pub fn name(&self, language: Language) -> &str { &self.names[language.index()] }
+
+    /// Write the header with the given hash.
+    #[cfg(feature = "hash")]
+    fn write<W: Writer>(&self, output: &mut W, hash: &[u8; 16]) -> Result<()> {
+        output.u8_array(&[0; 0x40])?;
+        output.u8_array(b"IMET")?;
+        output.bu32(IMET_SIZE as u32)?;
+        output.bu32(3)?;
+        output.bu32_array(&self.sizes)?;
+        output.bu32(self.flag)?;
+        for name in self.names.iter() {
+            let mut buffer = [0u8; NAME_LENGTH * 2];
+            let consumed = Encoding::Utf16Be.encode_into(&mut buffer, name)?;
+            ensure!(
+                consumed == name.len(),
+                BuildProblem::InvalidData("IMET name does not fit", Location::current())
+            );
+            output.u8_array(&buffer)?;
+        }
+        output.u8_array(&[0; 0x24C])?;
+        output.u8_array(hash)?;
+        Ok(())
+    }
+
+    /// Compute the MD5 hash of the header.
+    #[cfg(feature = "hash")]
+    pub fn compute_hash(&self) -> Result<[u8; 16]> {
+        let mut data = Vec::with_capacity(IMET_SIZE);
+        self.write(&mut data, &[0; 16])?;
+        Ok(Md5::digest(&data).into())
+    }
+
+    /// Returns `true` if [`Imet::hash`] matches the hash of the header.
+    #[cfg(feature = "hash")]
+    pub fn is_valid(&self) -> bool { self.compute_hash().is_ok_and(|x| x == self.hash) }
+
+    /// Write IMET header (`0x600` bytes) with a newly computed hash.
+    #[cfg(feature = "hash")]
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        self.write(output, &self.compute_hash()?)
+    }
+}
+
+/// IMD5 header of `banner.bin`, `icon.bin`, and `sound.bin`, with the data
+/// that follows it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Imd5 {
+    /// MD5 hash of the data.
+    pub hash: [u8; 16],
+
+    /// Data, usually [LZ77][`crate::lz`] compressed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+}
+
+impl Imd5 {
+    /// Create a file with the data `data` (the hash is computed when written).
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            hash: [0; 16],
+            data,
+        }
+    }
+
+    /// Parse IMD5 header and the data that follows it.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let magic = input.u8_array::<4>()?;
+        ensure!(
+            &magic == b"IMD5",
+            ParseProblem::InvalidMagic("expected: IMD5", Location::current())
+        );
+        let size = input.bu32()?;
+        let _padding = input.u8_array::<8>()?;
+        let hash = input.u8_array::<16>()?;
+        let data = input.read_as_vec(size as usize)?;
+        Ok(Self { hash, data })
+    }
+
+    /// Returns `true` if `data` starts with an IMD5 header.
+    pub fn is_imd5(data: &[u8]) -> bool { data.starts_with(b"IMD5") }
+
+    /// Compute the MD5 hash of the data.
+    #[cfg(feature = "hash")]
+    pub fn compute_hash(&self) -> [u8; 16] { Md5::digest(&self.data).into() }
+
+    /// Returns `true` if [`Imd5::hash`] matches the hash of the data.
+    #[cfg(feature = "hash")]
+    pub fn is_valid(&self) -> bool { self.compute_hash() == self.hash }
+
+    /// Write IMD5 header with a newly computed hash, followed by the data.
+    #[cfg(feature = "hash")]
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.u8_array(b"IMD5")?;
+        output.bu32(self.data.len() as u32)?;
+        output.u8_array(&[0; 8])?;
+        output.u8_array(&self.compute_hash())?;
+        output.u8_array(&self.data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,480 @@
+//! GameCube IPL (BIOS) resources and SRAM.
+//!
+//! The IPL ROM (2 MiB, e.g., `IPL.bin` of Dolphin) starts with the copyright
+//! and revision string, followed by the (scrambled) boot code. Near the end
+//! of the ROM are the two system [fonts][`Font`], compressed with
+//! [Yay0][crate::yay0]:
+//!
+//! | Offset     | Description                                   |
+//! |------------|-----------------------------------------------|
+//! | `0x000000` | Copyright and revision (ASCII)                |
+//! | `0x1AFF00` | Shift JIS font (Japanese consoles)            |
+//! | `0x1FCF00` | Windows-1252 (ANSI) font (all other consoles) |
+//!
+//! The glyphs of a font are stored in sheets of `columns`×`rows` cells, with
+//! 2 bits per pixel mapped to the four intensities of the font header. They
+//! are expanded to [I4][crate::texture::Format::I4] textures (as done by
+//! `OSLoadFont`), see [`Font::sheet`].
+//!
+//! The [SRAM][`Sram`] (64 bytes, e.g., `SRAM.raw` of Dolphin) stores the
+//! settings of the IPL, e.g., the language (which also selects the
+//! [description of BNR2 banners][crate::bnr::Language]) and the sound mode.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("IPL.bin")?;
+//!     let ipl = picori::ipl::Ipl::from_binary(&mut file)?;
+//!     println!("{}", ipl.copyright);
+//!     let font = &ipl.ansi_font;
+//!     let glyph = font.glyph(font.index('A' as u16).unwrap()).unwrap();
+//!     let sheet = font.sheet(glyph.sheet)?;
+//!     println!("A: {:?} in sheet of {} bytes (RGBA8)", glyph, sheet.len());
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::bnr::Language;
+use crate::compression::Decompress;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::texture::{self, Format};
+use crate::yay0::Yay0;
+use crate::{Encoding, Result};
+
+/// Size of the IPL ROM.
+pub const IPL_SIZE: usize = 0x200000;
+
+/// Offset of the Shift JIS font in the IPL ROM.
+pub const FONT_SJIS_OFFSET: u64 = 0x1AFF00;
+
+/// Offset of the Windows-1252 (ANSI) font in the IPL ROM.
+pub const FONT_ANSI_OFFSET: u64 = 0x1FCF00;
+
+/// Size of the font header.
+pub const FONT_HEADER_SIZE: usize = 0x30;
+
+/// Size of the SRAM.
+pub const SRAM_SIZE: usize = 0x40;
+
+/// Encoding of a [`Font`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FontEncoding {
+    /// Windows-1252 (type `0`).
+    Ansi,
+
+    /// Shift JIS (type `2`).
+    ShiftJis,
+}
+
+/// Position of a glyph in the sheets of a [`Font`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Index of the sheet.
+    pub sheet: usize,
+
+    /// X coordinate of the cell in the sheet.
+    pub x: u16,
+
+    /// Y coordinate of the cell in the sheet.
+    pub y: u16,
+
+    /// Width of the glyph.
+    pub width: u8,
+}
+
+/// IPL font (`OSFontHeader`), decompressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    /// Encoding of the character codes.
+    pub encoding: FontEncoding,
+
+    /// First character code.
+    pub first_char: u16,
+
+    /// Last character code.
+    pub last_char: u16,
+
+    /// Character code used for invalid characters.
+    pub invalid_char: u16,
+
+    /// Ascent.
+    pub ascent: u16,
+
+    /// Descent.
+    pub descent: u16,
+
+    /// Maximum width of a glyph.
+    pub width: u16,
+
+    /// Leading (line spacing).
+    pub leading: u16,
+
+    /// Width of a cell.
+    pub cell_width: u16,
+
+    /// Height of a cell.
+    pub cell_height: u16,
+
+    /// Number of cell columns of a sheet.
+    pub columns: u16,
+
+    /// Number of cell rows of a sheet.
+    pub rows: u16,
+
+    /// Width of a sheet.
+    pub sheet_width: u16,
+
+    /// Height of a sheet.
+    pub sheet_height: u16,
+
+    /// I4 intensities of the four 2-bit pixel values (in both nibbles).
+    pub colors: [u8; 4],
+
+    /// Widths of the glyphs.
+    pub widths: Vec<u8>,
+
+    /// Sheets, expanded to I4 textures.
+    pub sheets: Vec<u8>,
+}
+
+impl Font {
+    /// Parse decompressed IPL font from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let encoding = match input.bu16()? {
+            0 => FontEncoding::Ansi,
+            2 => FontEncoding::ShiftJis,
+            _ => Err(ParseProblem::InvalidHeader(
+                "IPL font type",
+                Location::current(),
+            ))?,
+        };
+        let [first_char, last_char, invalid_char] = input.bu16_array::<3>()?;
+        let [ascent, descent, width, leading] = input.bu16_array::<4>()?;
+        let [cell_width, cell_height] = input.bu16_array::<2>()?;
+        let sheet_size = input.bu32()? as usize;
+        let [sheet_format, columns, rows, sheet_width, sheet_height, width_table] =
+            input.bu16_array::<6>()?;
+        let sheet_image = input.bu32()?;
+        let sheet_full_size = input.bu32()? as usize;
+        let colors = input.u8_array::<4>()?;
+        ensure!(
+            Format::from_id(sheet_format as u32) == Some(Format::I4),
+            ParseProblem::InvalidHeader("IPL font sheet format (expected I4)", Location::current())
+        );
+        ensure!(
+            sheet_size == Format::I4.data_size(sheet_width as usize, sheet_height as usize)
+                && sheet_size > 0
+                && sheet_full_size.is_multiple_of(sheet_size),
+            ParseProblem::InvalidHeader("IPL font sheet size", Location::current())
+        );
+        ensure!(
+            sheet_image >= width_table as u32,
+            ParseProblem::InvalidHeader("IPL font width table", Location::current())
+        );
+
+        // the width table ends at the sheets (and at the last character for
+        // Windows-1252 fonts)
+        let mut count = sheet_full_size / sheet_size * columns as usize * rows as usize;
+        count = count.min((sheet_image - width_table as u32) as usize);
+        if encoding == FontEncoding::Ansi {
+            count = count.min(last_char.saturating_sub(first_char) as usize + 1);
+        }
+        input.goto(base + width_table as u64)?;
+        let widths = input.read_as_vec(count)?;
+
+        // four 2-bit pixels per byte, expanded to two I4 bytes
+        input.goto(base + sheet_image as u64)?;
+        let packed = input.read_as_vec(sheet_full_size / 2)?;
+        let color = |x: u8, shift: u8| colors[(x >> shift) as usize & 3];
+        let sheets = packed
+            .iter()
+            .flat_map(|x| {
+                [
+                    color(*x, 6) & 0xF0 | color(*x, 4) & 0x0F,
+                    color(*x, 2) & 0xF0 | color(*x, 0) & 0x0F,
+                ]
+            })
+            .collect();
+
+        Ok(Self {
+            encoding,
+            first_char,
+            last_char,
+            invalid_char,
+            ascent,
+            descent,
+            width,
+            leading,
+            cell_width,
+            cell_height,
+            columns,
+            rows,
+            sheet_width,
+            sheet_height,
+            colors,
+            widths,
+            sheets,
+        })
+    }
+
+    /// Parse Yay0 compressed IPL font from binary stream.
+    pub fn from_yay0<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let data = Yay0.decompress(input)?;
+        Self::from_binary(&mut Cursor::new(data))
+    }
+
+    /// Number of sheets.
+    pub fn sheet_count(&self) -> usize {
+        let size = Format::I4.data_size(self.sheet_width as usize, self.sheet_height as usize);
+        self.sheets.len() / size
+    }
+
+    /// Get the glyph index of the character code `code`, [`None`] for codes
+    /// outside of the font. Only fonts with [`FontEncoding::Ansi`] map the
+    /// codes directly to glyphs, Shift JIS fonts return [`None`].
+    pub fn index(&self, code: u16) -> Option<usize> {
+        match self.encoding {
+            FontEncoding::Ansi if (self.first_char..=self.last_char).contains(&code) => {
+                Some((code - self.first_char) as usize)
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the position and width of the glyph `index`.
+    pub fn glyph(&self, index: usize) -> Option<Glyph> {
+        let width = *self.widths.get(index)?;
+        let cells = self.columns as usize * self.rows as usize;
+        let cell = index % cells;
+        Some(Glyph {
+            sheet: index / cells,
+            x: (cell % self.columns as usize) as u16 * self.cell_width,
+            y: (cell / self.columns as usize) as u16 * self.cell_height,
+            width,
+        })
+    }
+
+    /// Decode the sheet `index` to RGBA8.
+    pub fn sheet(&self, index: usize) -> Result<Vec<u8>> {
+        let (width, height) = (self.sheet_width as usize, self.sheet_height as usize);
+        let size = Format::I4.data_size(width, height);
+        let data =
+            self.sheets
+                .get(index * size..(index + 1) * size)
+                .ok_or(ParseProblem::InvalidRange(
+                    "IPL font sheet index",
+                    Location::current(),
+                ))?;
+        texture::decode(Format::I4, data, width, height, None)
+    }
+}
+
+/// GameCube IPL ROM resources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ipl {
+    /// Copyright and revision, e.g., `(C) 1999-2001 Nintendo. All rights
+    /// reserved. ... PAL  Revision 1.0`.
+    pub copyright: String,
+
+    /// Windows-1252 (ANSI) font.
+    pub ansi_font: Font,
+
+    /// Shift JIS font.
+    pub sjis_font: Font,
+}
+
+impl Ipl {
+    /// Parse the resources of a (decrypted) IPL ROM from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let header = input.u8_array::<0x100>()?;
+        let copyright = Encoding::Ascii.first(&header)?;
+
+        input.goto(base + FONT_SJIS_OFFSET)?;
+        let sjis_font = Font::from_yay0(input)?;
+        input.goto(base + FONT_ANSI_OFFSET)?;
+        let ansi_font = Font::from_yay0(input)?;
+
+        Ok(Self {
+            copyright,
+            ansi_font,
+            sjis_font,
+        })
+    }
+}
+
+/// Sound mode of the [`Sram`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SoundMode {
+    /// Mono.
+    Mono,
+
+    /// Stereo.
+    Stereo,
+}
+
+/// GameCube SRAM (settings of the IPL).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sram {
+    /// Checksum of `0x0C` to `0x14`.
+    pub checksum: u16,
+
+    /// Inverse checksum of `0x0C` to `0x14`.
+    pub checksum_inverse: u16,
+
+    /// External device data (EAD), e.g., the modem or broadband adapter.
+    pub ead: [u32; 2],
+
+    /// Bias of the real time clock (seconds).
+    pub counter_bias: u32,
+
+    /// Horizontal offset of the display.
+    pub display_offset: i8,
+
+    /// Unknown (bit field).
+    pub ntd: u8,
+
+    /// Language (index into [`Language`]).
+    pub language: u8,
+
+    /// Flags, bit 2 is the sound mode (stereo), bit 7 is progressive scan.
+    pub flags: u8,
+
+    /// IDs of the flash chips of the memory cards (slots A and B).
+    pub flash_ids: [[u8; 12]; 2],
+
+    /// ID of the wireless keyboard.
+    pub keyboard_id: u32,
+
+    /// IDs of the WaveBird controllers.
+    pub pad_ids: [u16; 4],
+
+    /// Last DVD error code.
+    pub dvd_error: u8,
+
+    /// Checksums of the flash IDs (slots A and B).
+    pub flash_id_checksums: [u8; 2],
+
+    /// Boot mode (`gbs`).
+    pub boot_mode: u16,
+}
+
+impl Sram {
+    /// Parse SRAM from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let checksum = input.bu16()?;
+        let checksum_inverse = input.bu16()?;
+        let ead = input.bu32_array::<2>()?;
+        let counter_bias = input.bu32()?;
+        let display_offset = input.u8()? as i8;
+        let ntd = input.u8()?;
+        let language = input.u8()?;
+        let flags = input.u8()?;
+        let flash_ids = [input.u8_array::<12>()?, input.u8_array::<12>()?];
+        let keyboard_id = input.bu32()?;
+        let pad_ids = input.bu16_array::<4>()?;
+        let dvd_error = input.u8()?;
+        let _padding = input.u8()?;
+        let flash_id_checksums = input.u8_array::<2>()?;
+        let boot_mode = input.bu16()?;
+        let _padding = input.bu16()?;
+        Ok(Self {
+            checksum,
+            checksum_inverse,
+            ead,
+            counter_bias,
+            display_offset,
+            ntd,
+            language,
+            flags,
+            flash_ids,
+            keyboard_id,
+            pad_ids,
+            dvd_error,
+            flash_id_checksums,
+            boot_mode,
+        })
+    }
+
+    /// Write SRAM to binary stream (with the stored checksums, see
+    /// [`Sram::update_checksum`]).
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.bu16(self.checksum)?;
+        output.bu16(self.checksum_inverse)?;
+        output.bu32_array(&self.ead)?;
+        output.bu32(self.counter_bias)?;
+        output.u8(self.display_offset as u8)?;
+        output.u8(self.ntd)?;
+        output.u8(self.language)?;
+        output.u8(self.flags)?;
+        output.u8_array(&self.flash_ids[0])?;
+        output.u8_array(&self.flash_ids[1])?;
+        output.bu32(self.keyboard_id)?;
+        for id in self.pad_ids.iter() {
+            output.bu16(*id)?;
+        }
+        output.u8(self.dvd_error)?;
+        output.u8(0)?;
+        output.u8_array(&self.flash_id_checksums)?;
+        output.bu16(self.boot_mode)?;
+        output.bu16(0)?;
+        Ok(())
+    }
+
+    /// Calculate the checksum and the inverse checksum (of the counter bias,
+    /// the display offset, the language, and the flags).
+    pub fn calculate_checksum(&self) -> (u16, u16) {
+        let bias = self.counter_bias;
+        let words = [
+            (bias >> 16) as u16,
+            bias as u16,
+            u16::from_be_bytes([self.display_offset as u8, self.ntd]),
+            u16::from_be_bytes([self.language, self.flags]),
+        ];
+        words.iter().fold((0_u16, 0_u16), |(sum, inverse), x| {
+            (sum.wrapping_add(*x), inverse.wrapping_add(!x))
+        })
+    }
+
+    /// Returns `true` if the stored checksums are correct.
+    pub fn is_valid(&self) -> bool {
+        self.calculate_checksum() == (self.checksum, self.checksum_inverse)
+    }
+
+    /// Update the stored checksums, e.g., after changing the language.
+    pub fn update_checksum(&mut self) {
+        (self.checksum, self.checksum_inverse) = self.calculate_checksum();
+    }
+
+    /// Get the language, [`None`] for unknown languages.
+    pub fn language(&self) -> Option<Language> {
+        [
+            Language::English,
+            Language::German,
+            Language::French,
+            Language::Spanish,
+            Language::Italian,
+            Language::Dutch,
+        ]
+        .get(self.language as usize)
+        .copied()
+    }
+
+    /// Get the sound mode.
+    pub fn sound_mode(&self) -> SoundMode {
+        match self.flags & 0x04 != 0 {
+            true => SoundMode::Stereo,
+            false => SoundMode::Mono,
+        }
+    }
+
+    /// Returns `true` if progressive scan is enabled.
+    pub fn is_progressive(&self) -> bool { self.flags & 0x80 != 0 }
+}
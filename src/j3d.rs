@@ -0,0 +1,897 @@
+//! J3D models (`.bmd` and `.bdl`).
+//!
+//! J3D is the model format of the JSystem library. A model file starts with a
+//! header (`J3D2bmd3` or `J3D2bdl4`) followed by chunks:
+//!
+//! | Chunk  | Description                                                 |
+//! |--------|-------------------------------------------------------------|
+//! | `INF1` | [Scene graph][`Node`] (joints, materials, and shapes)       |
+//! | `VTX1` | [Vertex arrays][`VertexArray`] (positions, normals, ...)    |
+//! | `EVP1` | Envelopes (weighted joints)                                 |
+//! | `DRW1` | Draw matrices                                               |
+//! | `JNT1` | Joints                                                      |
+//! | `SHP1` | [Shapes][`Shape`], the primitives (indices of the vertices) |
+//! | `MAT3` | Materials (only the [names][`J3d::material_names`] are read) |
+//! | `MDL3` | Display lists of the materials (`bdl` only)                 |
+//! | `TEX1` | [Textures][`Texture`] (BTI headers and image data)          |
+//!
+//! All chunks are kept as raw [`Chunk`]s, the chunks above are parsed into
+//! [`J3d::hierarchy`], [`J3d::vertices`], [`J3d::shapes`][field@J3d::shapes],
+//! [`J3d::material_names`], and [`J3d::textures`][field@J3d::textures].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("model.bmd")?;
+//!     let model = picori::j3d::J3d::from_binary(&mut file)?;
+//!     println!("{} shapes", model.shapes.len());
+//!     for texture in model.textures.iter() {
+//!         let image = texture.decode()?;
+//!         println!("{}: {}x{}", texture.name, texture.width, texture.height);
+//!         # let _ = image;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::texture::{self, Format, TlutFormat};
+use crate::{Encoding, Result};
+
+/// Magic of a BMD model.
+pub const MAGIC_BMD: [u8; 8] = *b"J3D2bmd3";
+
+/// Magic of a BDL model.
+pub const MAGIC_BDL: [u8; 8] = *b"J3D2bdl4";
+
+/// Size of a BTI texture header.
+pub const BTI_HEADER_SIZE: usize = 0x20;
+
+/// Chunk of a J3D file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Magic, e.g., `INF1`.
+    pub magic: [u8; 4],
+
+    /// Data of the chunk, including the chunk header (offsets in chunks are
+    /// relative to the start of the chunk).
+    pub data: Vec<u8>,
+}
+
+/// Kind of a scene graph [`Node`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Joint (index into `JNT1`).
+    Joint(u16),
+
+    /// Material (index into `MAT3`).
+    Material(u16),
+
+    /// Shape (index into [`J3d::shapes`][field@J3d::shapes]).
+    Shape(u16),
+}
+
+/// Node of the scene graph (`INF1`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Kind of the node.
+    pub kind: NodeKind,
+
+    /// Children of the node.
+    pub children: Vec<Node>,
+}
+
+/// Vertex attribute (GX attribute).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Attribute {
+    /// Position and normal matrix index (`0`).
+    MatrixIndex,
+
+    /// Position (`9`).
+    Position,
+
+    /// Normal (`10`).
+    Normal,
+
+    /// Color 0 or 1 (`11` and `12`).
+    Color(u8),
+
+    /// Texture coordinate 0 to 7 (`13` to `20`).
+    TexCoord(u8),
+}
+
+impl Attribute {
+    /// Get the attribute of the GX attribute `id`.
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(Attribute::MatrixIndex),
+            9 => Some(Attribute::Position),
+            10 => Some(Attribute::Normal),
+            11..=12 => Some(Attribute::Color(id as u8 - 11)),
+            13..=20 => Some(Attribute::TexCoord(id as u8 - 13)),
+            _ => None,
+        }
+    }
+
+    /// Get the GX attribute id.
+    pub fn id(&self) -> u32 {
+        match self {
+            Attribute::MatrixIndex => 0,
+            Attribute::Position => 9,
+            Attribute::Normal => 10,
+            Attribute::Color(i) => 11 + *i as u32,
+            Attribute::TexCoord(i) => 13 + *i as u32,
+        }
+    }
+
+    /// Index of the array of the attribute in the `VTX1` offset table.
+    fn array_index(&self) -> Option<usize> {
+        match self {
+            Attribute::MatrixIndex => None,
+            Attribute::Position => Some(0),
+            Attribute::Normal => Some(1),
+            Attribute::Color(i) => Some(3 + *i as usize),
+            Attribute::TexCoord(i) => Some(5 + *i as usize),
+        }
+    }
+}
+
+/// Values of a [`VertexArray`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum VertexValues {
+    /// Positions, normals, and texture coordinates (with the fraction shift
+    /// applied), [`VertexArray::components`] values per vertex.
+    Float(Vec<f32>),
+
+    /// Colors (RGBA8).
+    Color(Vec<[u8; 4]>),
+}
+
+/// Vertex array (`VTX1`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexArray {
+    /// Attribute of the array.
+    pub attribute: Attribute,
+
+    /// Number of components per vertex, e.g., 3 for XYZ positions.
+    pub components: usize,
+
+    /// Values.
+    pub values: VertexValues,
+}
+
+impl VertexArray {
+    /// Number of vertices of the array.
+    pub fn len(&self) -> usize {
+        match &self.values {
+            VertexValues::Float(values) => values.len() / self.components.max(1),
+            VertexValues::Color(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if the array has no vertices.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Get the components of the vertex `index` (not for colors).
+    pub fn get(&self, index: usize) -> Option<&[f32]> {
+        match &self.values {
+            VertexValues::Float(values) => {
+                values.get(index * self.components..(index + 1) * self.components)
+            },
+            VertexValues::Color(_) => None,
+        }
+    }
+}
+
+/// Kind of a [`Primitive`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PrimitiveKind {
+    /// Quads (`0x80`).
+    Quads,
+
+    /// Triangles (`0x90`).
+    Triangles,
+
+    /// Triangle strip (`0x98`).
+    TriangleStrip,
+
+    /// Triangle fan (`0xA0`).
+    TriangleFan,
+
+    /// Lines (`0xA8`).
+    Lines,
+
+    /// Line strip (`0xB0`).
+    LineStrip,
+
+    /// Points (`0xB8`).
+    Points,
+}
+
+impl PrimitiveKind {
+    /// Get the primitive kind of the GX opcode `opcode`.
+    pub fn from_opcode(opcode: u8) -> Option<Self> {
+        match opcode & 0xF8 {
+            0x80 => Some(PrimitiveKind::Quads),
+            0x90 => Some(PrimitiveKind::Triangles),
+            0x98 => Some(PrimitiveKind::TriangleStrip),
+            0xA0 => Some(PrimitiveKind::TriangleFan),
+            0xA8 => Some(PrimitiveKind::Lines),
+            0xB0 => Some(PrimitiveKind::LineStrip),
+            0xB8 => Some(PrimitiveKind::Points),
+            _ => None,
+        }
+    }
+}
+
+/// Primitive of a [`Packet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Primitive {
+    /// Kind of the primitive.
+    pub kind: PrimitiveKind,
+
+    /// Vertices, the index (or the value for direct attributes) of every
+    /// attribute of [`Shape::attributes`].
+    pub vertices: Vec<Vec<u16>>,
+}
+
+impl Primitive {
+    /// Get the triangles (indices into [`Primitive::vertices`]) of the
+    /// primitive, empty for lines and points.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let count = self.vertices.len();
+        match self.kind {
+            PrimitiveKind::Triangles => (0..count / 3)
+                .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+                .collect(),
+            PrimitiveKind::TriangleStrip => (2..count)
+                .map(|i| match i % 2 {
+                    0 => [i - 2, i - 1, i],
+                    _ => [i - 1, i - 2, i],
+                })
+                .collect(),
+            PrimitiveKind::TriangleFan => (2..count).map(|i| [0, i - 1, i]).collect(),
+            PrimitiveKind::Quads => (0..count / 4)
+                .flat_map(|i| [[4 * i, 4 * i + 1, 4 * i + 2], [4 * i, 4 * i + 2, 4 * i + 3]])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Packet of a [`Shape`], the primitives drawn with one set of matrices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    /// Indices of the draw matrices (`DRW1`), `0xFFFF` to keep the matrix of
+    /// the previous packet.
+    pub matrices: Vec<u16>,
+
+    /// Primitives.
+    pub primitives: Vec<Primitive>,
+}
+
+/// Vertex attribute of a [`Shape`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ShapeAttribute {
+    /// Attribute.
+    pub attribute: Attribute,
+
+    /// Kind of the value, `1` for direct values, `2` for 8-bit indices, and
+    /// `3` for 16-bit indices.
+    pub kind: u32,
+}
+
+/// Shape (`SHP1`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    /// Matrix type (billboard mode).
+    pub matrix_type: u8,
+
+    /// Radius of the bounding sphere.
+    pub bounding_radius: f32,
+
+    /// Minimum of the bounding box.
+    pub min: [f32; 3],
+
+    /// Maximum of the bounding box.
+    pub max: [f32; 3],
+
+    /// Vertex attributes of the primitives.
+    pub attributes: Vec<ShapeAttribute>,
+
+    /// Packets.
+    pub packets: Vec<Packet>,
+}
+
+/// Texture (`TEX1`), a BTI header and its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Texture {
+    /// Name of the texture.
+    pub name: String,
+
+    /// Texture format.
+    pub format: Format,
+
+    /// Width of the texture.
+    pub width: u16,
+
+    /// Height of the texture.
+    pub height: u16,
+
+    /// Wrap mode of the S and T coordinates.
+    pub wrap: [u8; 2],
+
+    /// Palette format.
+    pub palette_format: Option<TlutFormat>,
+
+    /// Minification and magnification filter.
+    pub filter: [u8; 2],
+
+    /// Number of images (the texture and its mipmaps).
+    pub image_count: u8,
+
+    /// Image data (all mipmaps).
+    pub data: Vec<u8>,
+
+    /// Palette data.
+    pub palette: Vec<u8>,
+}
+
+impl Texture {
+    /// Parse BTI header at the start of `data`, the data of the texture at
+    /// the offsets (relative to the header) of the header.
+    pub fn from_binary(data: &[u8], name: String) -> Result<Self> {
+        let mut input = Cursor::new(data);
+        let format = input.u8()?;
+        let format = Format::from_id(format as u32).ok_or(ParseProblem::InvalidHeader(
+            "unknown BTI texture format",
+            Location::current(),
+        ))?;
+        let _alpha = input.u8()?;
+        let width = input.bu16()?;
+        let height = input.bu16()?;
+        let wrap = input.u8_array::<2>()?;
+        let _palette_enabled = input.u8()?;
+        let palette_format = input.u8()?;
+        let palette_count = input.bu16()? as usize;
+        let palette_offset = input.bu32()? as usize;
+        let _unknown = input.u8_array::<4>()?;
+        let filter = input.u8_array::<2>()?;
+        let _lod = input.u8_array::<2>()?;
+        let image_count = input.u8()?;
+        let _unknown = input.u8()?;
+        let _lod_bias = input.bu16()?;
+        let image_offset = input.bu32()? as usize;
+
+        let range = |offset: usize, size: usize| -> Result<Vec<u8>> {
+            Ok(data
+                .get(offset..offset + size)
+                .ok_or(ParseProblem::InvalidRange(
+                    "BTI data (outside of chunk)",
+                    Location::current(),
+                ))?
+                .to_vec())
+        };
+        let (palette_format, palette) = match format.is_indexed() {
+            true => (
+                Some(TlutFormat::from_id(palette_format as u32).ok_or(
+                    ParseProblem::InvalidHeader("unknown BTI palette format", Location::current()),
+                )?),
+                range(palette_offset, palette_count * 2)?,
+            ),
+            false => (None, Vec::new()),
+        };
+        let size = (0..image_count.max(1) as u32)
+            .map(|i| {
+                let width = (width as usize >> i).max(1);
+                let height = (height as usize >> i).max(1);
+                format.data_size(width, height)
+            })
+            .sum();
+        let data = range(image_offset, size)?;
+
+        Ok(Self {
+            name,
+            format,
+            width,
+            height,
+            wrap,
+            palette_format,
+            filter,
+            image_count,
+            data,
+            palette,
+        })
+    }
+
+    /// Decode the texture (the first image, without mipmaps) to RGBA8.
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        texture::decode(
+            self.format,
+            &self.data,
+            self.width as usize,
+            self.height as usize,
+            self.palette_format.map(|x| (x, self.palette.as_slice())),
+        )
+    }
+}
+
+/// J3D model (BMD or BDL).
+#[derive(Debug, Clone, PartialEq)]
+pub struct J3d {
+    /// Magic, [`MAGIC_BMD`] or [`MAGIC_BDL`].
+    pub magic: [u8; 8],
+
+    /// All chunks of the file.
+    pub chunks: Vec<Chunk>,
+
+    /// Scene graph (`INF1`), the root nodes.
+    pub hierarchy: Vec<Node>,
+
+    /// Number of vertices (`INF1`).
+    pub vertex_count: u32,
+
+    /// Vertex arrays (`VTX1`).
+    pub vertices: Vec<VertexArray>,
+
+    /// Shapes (`SHP1`).
+    pub shapes: Vec<Shape>,
+
+    /// Names of the materials (`MAT3`).
+    pub material_names: Vec<String>,
+
+    /// Textures (`TEX1`).
+    pub textures: Vec<Texture>,
+}
+
+impl J3d {
+    /// Parse J3D model from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<8>()?;
+        ensure!(
+            magic == MAGIC_BMD || magic == MAGIC_BDL,
+            ParseProblem::InvalidMagic("expected: J3D2bmd3 or J3D2bdl4", Location::current())
+        );
+        let chunks = chunks(input, base)?;
+
+        let mut model = Self {
+            magic,
+            chunks,
+            hierarchy: Vec::new(),
+            vertex_count: 0,
+            vertices: Vec::new(),
+            shapes: Vec::new(),
+            material_names: Vec::new(),
+            textures: Vec::new(),
+        };
+        if let Some(data) = model.chunk(b"INF1") {
+            (model.hierarchy, model.vertex_count) = Self::information(data)?;
+        }
+        if let Some(data) = model.chunk(b"VTX1") {
+            model.vertices = Self::vertex_arrays(data)?;
+        }
+        if let Some(data) = model.chunk(b"SHP1") {
+            model.shapes = Self::shapes(data)?;
+        }
+        if let Some(data) = model.chunk(b"MAT3") {
+            let mut input = Cursor::new(data);
+            input.goto(0x14)?;
+            let offset = input.bu32()? as usize;
+            model.material_names = names(data, offset)?;
+        }
+        if let Some(data) = model.chunk(b"TEX1") {
+            model.textures = Self::textures(data)?;
+        }
+        Ok(model)
+    }
+
+    /// Get the data of the first chunk with the magic `magic`.
+    pub fn chunk(&self, magic: &[u8; 4]) -> Option<&[u8]> {
+        self.chunks
+            .iter()
+            .find(|x| &x.magic == magic)
+            .map(|x| x.data.as_slice())
+    }
+
+    fn information(data: &[u8]) -> Result<(Vec<Node>, u32)> {
+        let mut input = Cursor::new(data);
+        input.goto(0x0C)?;
+        let _packet_count = input.bu32()?;
+        let vertex_count = input.bu32()?;
+        let offset = input.bu32()?;
+        input.goto(offset as u64)?;
+
+        // a stack of the children of the open nodes
+        let mut stack = vec![Vec::<Node>::new()];
+        loop {
+            let kind = input.bu16()?;
+            let index = input.bu16()?;
+            let kind =
+                match kind {
+                    0x00 => break,
+                    0x01 => {
+                        stack.push(Vec::new());
+                        continue;
+                    },
+                    0x02 => {
+                        ensure!(
+                            stack.len() > 1,
+                            ParseProblem::InvalidData(
+                                "J3D hierarchy (unbalanced)",
+                                Location::current()
+                            )
+                        );
+                        let children = stack.pop().unwrap();
+                        let parent = stack.last_mut().unwrap().last_mut().ok_or(
+                            ParseProblem::InvalidData(
+                                "J3D hierarchy (no parent)",
+                                Location::current(),
+                            ),
+                        )?;
+                        parent.children = children;
+                        continue;
+                    },
+                    0x10 => NodeKind::Joint(index),
+                    0x11 => NodeKind::Material(index),
+                    0x12 => NodeKind::Shape(index),
+                    _ => Err(ParseProblem::InvalidData(
+                        "J3D hierarchy node kind",
+                        Location::current(),
+                    ))?,
+                };
+            stack.last_mut().unwrap().push(Node {
+                kind,
+                children: Vec::new(),
+            });
+        }
+        ensure!(
+            stack.len() == 1,
+            ParseProblem::InvalidData("J3D hierarchy (unbalanced)", Location::current())
+        );
+        Ok((stack.pop().unwrap(), vertex_count))
+    }
+
+    fn vertex_arrays(data: &[u8]) -> Result<Vec<VertexArray>> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let format_offset = input.bu32()?;
+        let offsets = input.bu32_array::<13>()?;
+
+        let mut arrays = Vec::new();
+        input.goto(format_offset as u64)?;
+        loop {
+            let id = input.bu32()?;
+            if id == 0xFF {
+                break;
+            }
+            let count = input.bu32()?;
+            let kind = input.bu32()?;
+            let shift = input.u8()?;
+            let _padding = input.u8_array::<3>()?;
+
+            let attribute = Attribute::from_id(id).ok_or(ParseProblem::InvalidData(
+                "J3D vertex attribute",
+                Location::current(),
+            ))?;
+            let index = attribute.array_index().ok_or(ParseProblem::InvalidData(
+                "J3D vertex attribute (without array)",
+                Location::current(),
+            ))?;
+            let start = offsets[index] as usize;
+            let end = offsets[index + 1..]
+                .iter()
+                .copied()
+                .find(|x| *x != 0)
+                .map_or(data.len(), |x| x as usize);
+            let array =
+                data.get(start..end)
+                    .filter(|_| start != 0)
+                    .ok_or(ParseProblem::InvalidRange(
+                        "J3D vertex array",
+                        Location::current(),
+                    ))?;
+
+            let (components, values) = match attribute {
+                Attribute::Color(_) => (4, VertexValues::Color(colors(array, kind)?)),
+                _ => {
+                    let components = match (attribute, count) {
+                        (Attribute::Position, 0) => 2,
+                        (Attribute::TexCoord(_), 0) => 1,
+                        (Attribute::TexCoord(_), _) => 2,
+                        _ => 3,
+                    };
+                    (components, VertexValues::Float(floats(array, kind, shift)?))
+                },
+            };
+            arrays.push(VertexArray {
+                attribute,
+                components,
+                values,
+            });
+        }
+        Ok(arrays)
+    }
+
+    fn shapes(data: &[u8]) -> Result<Vec<Shape>> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let count = input.bu16()?;
+        let _padding = input.bu16()?;
+        let shape_offset = input.bu32()? as u64;
+        let remap_offset = input.bu32()? as u64;
+        let _names_offset = input.bu32()?;
+        let attribute_offset = input.bu32()? as u64;
+        let matrix_table_offset = input.bu32()? as u64;
+        let primitive_offset = input.bu32()? as u64;
+        let matrix_data_offset = input.bu32()? as u64;
+        let packet_offset = input.bu32()? as u64;
+
+        input.goto(remap_offset)?;
+        let remap = (0..count)
+            .map(|_| input.bu16())
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut shapes = Vec::with_capacity(count as usize);
+        for index in remap {
+            input.goto(shape_offset + index as u64 * 0x28)?;
+            let matrix_type = input.u8()?;
+            let _padding = input.u8()?;
+            let packet_count = input.bu16()?;
+            let attributes = input.bu16()? as u64;
+            let first_matrix = input.bu16()? as u64;
+            let first_packet = input.bu16()? as u64;
+            let _padding = input.bu16()?;
+            let bounding_radius = f32::from_bits(input.bu32()?);
+            let bounds = input.bu32_array::<6>()?.map(f32::from_bits);
+
+            input.goto(attribute_offset + attributes)?;
+            let mut attributes = Vec::new();
+            loop {
+                let id = input.bu32()?;
+                let kind = input.bu32()?;
+                if id == 0xFF {
+                    break;
+                }
+                let attribute = Attribute::from_id(id).ok_or(ParseProblem::InvalidData(
+                    "J3D shape attribute",
+                    Location::current(),
+                ))?;
+                attributes.push(ShapeAttribute { attribute, kind });
+            }
+
+            let mut packets = Vec::with_capacity(packet_count as usize);
+            for packet in 0..packet_count as u64 {
+                input.goto(matrix_data_offset + (first_matrix + packet) * 8)?;
+                let _unknown = input.bu16()?;
+                let matrix_count = input.bu16()?;
+                let matrix_start = input.bu32()? as u64;
+                input.goto(matrix_table_offset + matrix_start * 2)?;
+                let matrices = (0..matrix_count)
+                    .map(|_| input.bu16())
+                    .collect::<Result<Vec<_>>>()?;
+
+                input.goto(packet_offset + (first_packet + packet) * 8)?;
+                let size = input.bu32()? as u64;
+                let offset = input.bu32()? as u64;
+                input.goto(primitive_offset + offset)?;
+                let end = primitive_offset + offset + size;
+                let primitives = primitives(&mut input, end, &attributes)?;
+                packets.push(Packet {
+                    matrices,
+                    primitives,
+                });
+            }
+
+            shapes.push(Shape {
+                matrix_type,
+                bounding_radius,
+                min: [bounds[0], bounds[1], bounds[2]],
+                max: [bounds[3], bounds[4], bounds[5]],
+                attributes,
+                packets,
+            });
+        }
+        Ok(shapes)
+    }
+
+    fn textures(data: &[u8]) -> Result<Vec<Texture>> {
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let count = input.bu16()? as usize;
+        let _padding = input.bu16()?;
+        let header_offset = input.bu32()? as usize;
+        let names_offset = input.bu32()? as usize;
+        let names = names(data, names_offset)?;
+        ensure!(
+            names.len() == count,
+            ParseProblem::InvalidData("J3D texture names", Location::current())
+        );
+
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let offset = header_offset + i * BTI_HEADER_SIZE;
+                let data = data.get(offset..).ok_or(ParseProblem::InvalidRange(
+                    "J3D texture header",
+                    Location::current(),
+                ))?;
+                Texture::from_binary(data, name)
+            })
+            .collect()
+    }
+}
+
+/// Parse the chunks of the J3D file at `base`, after the magic.
+pub(crate) fn chunks<D: Parser + Seeker>(input: &mut D, base: u64) -> Result<Vec<Chunk>> {
+    let _size = input.bu32()?;
+    let chunk_count = input.bu32()?;
+
+    let mut chunks = Vec::new();
+    let mut offset = base + 0x20;
+    for _ in 0..chunk_count {
+        input.goto(offset)?;
+        let magic = input.u8_array::<4>()?;
+        let size = input.bu32()?;
+        ensure!(
+            size >= 8,
+            ParseProblem::InvalidHeader("J3D chunk size", Location::current())
+        );
+        input.goto(offset)?;
+        let data = input.read_as_vec(size as usize)?;
+        chunks.push(Chunk { magic, data });
+        offset += size as u64;
+    }
+    Ok(chunks)
+}
+
+/// Parse the J3D string table at `offset` of `data`.
+pub(crate) fn names(data: &[u8], offset: usize) -> Result<Vec<String>> {
+    let mut input = Cursor::new(data);
+    input.goto(offset as u64)?;
+    let count = input.bu16()?;
+    let _padding = input.bu16()?;
+    (0..count)
+        .map(|_| {
+            let _hash = input.bu16()?;
+            let start = offset + input.bu16()? as usize;
+            let name = data.get(start..).ok_or(ParseProblem::InvalidRange(
+                "J3D string table",
+                Location::current(),
+            ))?;
+            Encoding::ShiftJis1997.first(name)
+        })
+        .collect()
+}
+
+/// Decode the vertex components of the GX component type `kind`.
+fn floats(data: &[u8], kind: u32, shift: u8) -> Result<Vec<f32>> {
+    let scale = 1.0 / (1_u32 << shift) as f32;
+    Ok(match kind {
+        0 => data.iter().map(|x| *x as f32 * scale).collect(),
+        1 => data.iter().map(|x| *x as i8 as f32 * scale).collect(),
+        2 => data
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|x| u16::from_be_bytes(*x) as f32 * scale)
+            .collect(),
+        3 => data
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|x| i16::from_be_bytes(*x) as f32 * scale)
+            .collect(),
+        4 => data
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .map(|x| f32::from_be_bytes(*x))
+            .collect(),
+        _ => Err(ParseProblem::InvalidData(
+            "J3D component type",
+            Location::current(),
+        ))?,
+    })
+}
+
+/// Decode the colors of the GX color type `kind` to RGBA8.
+fn colors(data: &[u8], kind: u32) -> Result<Vec<[u8; 4]>> {
+    let expand =
+        |value: u32, bits: u32| ((value * 255 + (1 << bits) / 2) / ((1 << bits) - 1)) as u8;
+    Ok(match kind {
+        0 => data
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|x| {
+                let x = u16::from_be_bytes(*x) as u32;
+                [
+                    expand(x >> 11, 5),
+                    expand((x >> 5) & 0x3F, 6),
+                    expand(x & 0x1F, 5),
+                    0xFF,
+                ]
+            })
+            .collect(),
+        1 => data
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .map(|x| [x[0], x[1], x[2], 0xFF])
+            .collect(),
+        2 => data
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .map(|x| [x[0], x[1], x[2], 0xFF])
+            .collect(),
+        3 => data
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|x| {
+                let x = u16::from_be_bytes(*x) as u32;
+                [12, 8, 4, 0].map(|shift| expand((x >> shift) & 0xF, 4))
+            })
+            .collect(),
+        4 => data
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .map(|x| {
+                let x = u32::from_be_bytes([0, x[0], x[1], x[2]]);
+                [18, 12, 6, 0].map(|shift| expand((x >> shift) & 0x3F, 6))
+            })
+            .collect(),
+        5 => data.as_chunks::<4>().0.to_vec(),
+        _ => Err(ParseProblem::InvalidData(
+            "J3D color type",
+            Location::current(),
+        ))?,
+    })
+}
+
+/// Parse the primitives until `end` (or a zero opcode).
+fn primitives(
+    input: &mut Cursor<&[u8]>,
+    end: u64,
+    attributes: &[ShapeAttribute],
+) -> Result<Vec<Primitive>> {
+    let mut primitives = Vec::new();
+    while Seeker::position(input)? < end {
+        let opcode = input.u8()?;
+        if opcode == 0 {
+            break;
+        }
+        let kind = PrimitiveKind::from_opcode(opcode).ok_or(ParseProblem::InvalidData(
+            "J3D primitive opcode",
+            Location::current(),
+        ))?;
+        let count = input.bu16()?;
+        let mut vertices = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let vertex = attributes
+                .iter()
+                .map(|x| match x.kind {
+                    1 | 2 => input.u8().map(|x| x as u16),
+                    3 => input.bu16(),
+                    _ => Err(ParseProblem::InvalidData(
+                        "J3D attribute type",
+                        Location::current(),
+                    ))?,
+                })
+                .collect::<Result<Vec<_>>>()?;
+            vertices.push(vertex);
+        }
+        primitives.push(Primitive { kind, vertices });
+    }
+    Ok(primitives)
+}
@@ -0,0 +1,410 @@
+//! J3D animations (`.bck` and `.btk`).
+//!
+//! The animations of [J3D models][crate::j3d] use the same chunked container
+//! as the models:
+//!
+//! * [BCK][`Bck`] (`J3D1bck1`, chunk `ANK1`) - joint animation, the scale,
+//!   rotation, and translation of every joint.
+//! * [BTK][`Btk`] (`J3D1btk1`, chunk `TTK1`) - texture SRT animation, the
+//!   scale, rotation, and translation of the texture matrices of materials.
+//!
+//! Every component (X, Y, and Z of scale, rotation, and translation) is a
+//! [`Track`] of Hermite [keyframes][`Keyframe`]. Rotations are converted to
+//! degrees.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("wait.bck")?;
+//!     let bck = picori::j3d_animation::Bck::from_binary(&mut file)?;
+//!     for (i, joint) in bck.joints.iter().enumerate() {
+//!         let [x, y, z] = joint.translation.each_ref().map(|x| x.value(0.0));
+//!         println!("joint {}: {} {} {}", i, x, y, z);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::j3d::{self, Chunk};
+use crate::Result;
+
+/// Magic of a BCK joint animation.
+pub const MAGIC_BCK: [u8; 8] = *b"J3D1bck1";
+
+/// Magic of a BTK texture SRT animation.
+pub const MAGIC_BTK: [u8; 8] = *b"J3D1btk1";
+
+/// Loop mode of an animation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Play once and stop at the last frame (`0`).
+    Once,
+
+    /// Play once and reset to the first frame (`1`).
+    OnceAndReset,
+
+    /// Repeat (`2`).
+    Repeat,
+
+    /// Play forwards, then backwards once (`3`).
+    MirroredOnce,
+
+    /// Play forwards, then backwards and repeat (`4`).
+    MirroredRepeat,
+}
+
+impl TryFrom<u8> for LoopMode {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(LoopMode::Once),
+            1 => Ok(LoopMode::OnceAndReset),
+            2 => Ok(LoopMode::Repeat),
+            3 => Ok(LoopMode::MirroredOnce),
+            4 => Ok(LoopMode::MirroredRepeat),
+            _ => Err(ParseProblem::InvalidHeader("unknown loop mode", Location::current()).into()),
+        }
+    }
+}
+
+/// Keyframe of a [`Track`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    /// Frame of the keyframe.
+    pub time: f32,
+
+    /// Value at the keyframe.
+    pub value: f32,
+
+    /// Incoming tangent.
+    pub tangent_in: f32,
+
+    /// Outgoing tangent.
+    pub tangent_out: f32,
+}
+
+/// Animation of one component, e.g., the X translation of a joint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track {
+    /// Keyframes, sorted by time. A single keyframe is a constant value.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Get the value at the frame `time` (Hermite interpolation between the
+    /// keyframes), `0.0` for tracks without keyframes.
+    pub fn value(&self, time: f32) -> f32 {
+        let keyframes = &self.keyframes;
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return 0.0;
+        };
+        if time <= first.time {
+            return first.value;
+        } else if time >= last.time {
+            return last.value;
+        }
+
+        let next = keyframes.partition_point(|x| x.time <= time);
+        let (a, b) = (&keyframes[next - 1], &keyframes[next]);
+        let length = b.time - a.time;
+        let t = (time - a.time) / length;
+        let (t2, t3) = (t * t, t * t * t);
+        (2.0 * t3 - 3.0 * t2 + 1.0) * a.value
+            + (t3 - 2.0 * t2 + t) * a.tangent_out * length
+            + (-2.0 * t3 + 3.0 * t2) * b.value
+            + (t3 - t2) * b.tangent_in * length
+    }
+}
+
+/// Scale, rotation (in degrees), and translation tracks of the X, Y, and Z
+/// components.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransformTracks {
+    /// Scale.
+    pub scale: [Track; 3],
+
+    /// Rotation (in degrees).
+    pub rotation: [Track; 3],
+
+    /// Translation.
+    pub translation: [Track; 3],
+}
+
+/// Animation of a texture matrix of a material ([`Btk`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAnimation {
+    /// Name of the material.
+    pub material: String,
+
+    /// Index of the texture matrix of the material.
+    pub texture_matrix: u8,
+
+    /// Center of the scale and rotation.
+    pub center: [f32; 3],
+
+    /// Tracks.
+    pub tracks: TransformTracks,
+}
+
+/// Joint animation (BCK).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bck {
+    /// All chunks of the file.
+    pub chunks: Vec<Chunk>,
+
+    /// Loop mode.
+    pub loop_mode: LoopMode,
+
+    /// Length in frames.
+    pub length: u16,
+
+    /// Tracks of the joints (indexed by the joints of the model).
+    pub joints: Vec<TransformTracks>,
+}
+
+impl Bck {
+    /// Parse BCK joint animation from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let chunks = animation_chunks(input, MAGIC_BCK)?;
+        let data = chunk(&chunks, b"ANK1")?;
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let loop_mode = LoopMode::try_from(input.u8()?)?;
+        let rotation_shift = input.u8()?;
+        let length = input.bu16()?;
+        let count = input.bu16()?;
+        let _counts = input.bu16_array::<3>()?;
+        let offset = input.bu32()? as u64;
+        let tables = Tables::from_binary(&mut input, data, offset, rotation_shift)?;
+
+        let joints = (0..count as u64)
+            .map(|i| tables.tracks(&mut input, i))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            chunks,
+            loop_mode,
+            length,
+            joints,
+        })
+    }
+}
+
+/// Texture SRT animation (BTK).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Btk {
+    /// All chunks of the file.
+    pub chunks: Vec<Chunk>,
+
+    /// Loop mode.
+    pub loop_mode: LoopMode,
+
+    /// Length in frames.
+    pub length: u16,
+
+    /// Animated texture matrices.
+    pub animations: Vec<TextureAnimation>,
+}
+
+impl Btk {
+    /// Parse BTK texture SRT animation from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let chunks = animation_chunks(input, MAGIC_BTK)?;
+        let data = chunk(&chunks, b"TTK1")?;
+        let mut input = Cursor::new(data);
+        input.goto(0x08)?;
+        let loop_mode = LoopMode::try_from(input.u8()?)?;
+        let rotation_shift = input.u8()?;
+        let length = input.bu16()?;
+        let count = input.bu16()? / 3;
+        let _counts = input.bu16_array::<3>()?;
+        let offset = input.bu32()? as u64;
+        let _remap_offset = input.bu32()?;
+        let names_offset = input.bu32()? as usize;
+        let matrix_offset = input.bu32()? as u64;
+        let center_offset = input.bu32()? as u64;
+        input.goto(0x28)?;
+        let tables = Tables::from_binary(&mut input, data, offset, rotation_shift)?;
+
+        let names = j3d::names(data, names_offset)?;
+        ensure!(
+            names.len() >= count as usize,
+            ParseProblem::InvalidData("BTK material names", Location::current())
+        );
+        let animations = names
+            .into_iter()
+            .take(count as usize)
+            .enumerate()
+            .map(|(i, material)| {
+                let i = i as u64;
+                input.goto(matrix_offset + i)?;
+                let texture_matrix = input.u8()?;
+                input.goto(center_offset + i * 12)?;
+                let center = input.bu32_array::<3>()?.map(f32::from_bits);
+                let tracks = tables.tracks(&mut input, i)?;
+                Ok(TextureAnimation {
+                    material,
+                    texture_matrix,
+                    center,
+                    tracks,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            chunks,
+            loop_mode,
+            length,
+            animations,
+        })
+    }
+}
+
+/// Parse the header of an animation file with the magic `magic` and its
+/// chunks.
+fn animation_chunks<D: Parser + Seeker>(input: &mut D, magic: [u8; 8]) -> Result<Vec<Chunk>> {
+    let base = input.position()?;
+    ensure!(
+        input.u8_array::<8>()? == magic,
+        ParseProblem::InvalidMagic("J3D animation magic", Location::current())
+    );
+    j3d::chunks(input, base)
+}
+
+fn chunk<'a>(chunks: &'a [Chunk], magic: &[u8; 4]) -> Result<&'a [u8]> {
+    Ok(chunks
+        .iter()
+        .find(|x| &x.magic == magic)
+        .ok_or(ParseProblem::InvalidData(
+            "J3D animation chunk not found",
+            Location::current(),
+        ))?
+        .data
+        .as_slice())
+}
+
+/// Value tables of an animation chunk, scale and translation as `f32` and
+/// rotation as `i16`.
+struct Tables {
+    offset:         u64,
+    scale:          Vec<f32>,
+    rotation:       Vec<f32>,
+    rotation_scale: f32,
+    translation:    Vec<f32>,
+}
+
+impl Tables {
+    /// Parse the table offsets (scale, rotation, and translation) at the
+    /// current position and read the tables, the animation entries at
+    /// `offset`.
+    fn from_binary(
+        input: &mut Cursor<&[u8]>,
+        data: &[u8],
+        offset: u64,
+        rotation_shift: u8,
+    ) -> Result<Self> {
+        let [scale, rotation, translation] = input.bu32_array::<3>()?.map(|x| x as usize);
+        // the tables have no size, they end at the next table (or the chunk)
+        let end = |start: usize| {
+            [scale, rotation, translation, data.len()]
+                .into_iter()
+                .filter(|x| *x > start)
+                .min()
+                .unwrap_or(start)
+        };
+        let range = |start: usize| {
+            data.get(start..end(start))
+                .ok_or(ParseProblem::InvalidRange(
+                    "J3D animation table",
+                    Location::current(),
+                ))
+        };
+        let floats = |start: usize| -> Result<Vec<f32>> {
+            Ok(range(start)?
+                .as_chunks::<4>()
+                .0
+                .iter()
+                .map(|x| f32::from_be_bytes(*x))
+                .collect())
+        };
+        let rotation = range(rotation)?
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|x| i16::from_be_bytes(*x) as f32)
+            .collect();
+        Ok(Self {
+            offset,
+            scale: floats(scale)?,
+            rotation,
+            rotation_scale: (1 << rotation_shift) as f32 * 180.0 / 32768.0,
+            translation: floats(translation)?,
+        })
+    }
+
+    /// Parse the tracks of the animation entry `index` (`0x36` bytes each).
+    fn tracks(&self, input: &mut Cursor<&[u8]>, index: u64) -> Result<TransformTracks> {
+        input.goto(self.offset + index * 0x36)?;
+        let mut tracks = TransformTracks::default();
+        for axis in 0..3 {
+            tracks.scale[axis] = Self::track(input, &self.scale, 1.0)?;
+            tracks.rotation[axis] = Self::track(input, &self.rotation, self.rotation_scale)?;
+            tracks.translation[axis] = Self::track(input, &self.translation, 1.0)?;
+        }
+        Ok(tracks)
+    }
+
+    /// Parse a track (count, index, and tangent mode) of the values of `table`
+    /// (the values and tangents scaled by `scale`, but not the times).
+    fn track(input: &mut Cursor<&[u8]>, table: &[f32], scale: f32) -> Result<Track> {
+        let count = input.bu16()? as usize;
+        let index = input.bu16()? as usize;
+        let tangent_mode = input.bu16()?;
+        let stride = match (count, tangent_mode) {
+            (0..=1, _) => 1,
+            (_, 0) => 3,
+            (_, 1) => 4,
+            _ => Err(ParseProblem::InvalidData(
+                "J3D animation tangent mode",
+                Location::current(),
+            ))?,
+        };
+        let values = table
+            .get(index..index + count * stride)
+            .ok_or(ParseProblem::InvalidRange(
+                "J3D animation keyframes",
+                Location::current(),
+            ))?;
+        let keyframes = values
+            .chunks_exact(stride)
+            .map(|x| match x {
+                [value] => Keyframe {
+                    time:        0.0,
+                    value:       *value * scale,
+                    tangent_in:  0.0,
+                    tangent_out: 0.0,
+                },
+                [time, value, tangent] => Keyframe {
+                    time:        *time,
+                    value:       *value * scale,
+                    tangent_in:  *tangent * scale,
+                    tangent_out: *tangent * scale,
+                },
+                [time, value, tangent_in, tangent_out, ..] => Keyframe {
+                    time:        *time,
+                    value:       *value * scale,
+                    tangent_in:  *tangent_in * scale,
+                    tangent_out: *tangent_out * scale,
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        Ok(Track { keyframes })
+    }
+}
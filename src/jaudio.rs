@@ -0,0 +1,260 @@
+//! JAudio init archives (`.aaf` and `.baa`).
+//!
+//! The audio data of JSystem games is stored in a single init archive, e.g.,
+//! `JaiInit.aaf` (The Wind Waker) or `Z2Sound.baa` (Twilight Princess). The
+//! archive starts with an index of its [sections][`Section`]: the sound
+//! tables, the sequence collections, the instrument banks (`IBNK`), and the
+//! wave systems (`WSYS`, the index of the wave archives in `Banks/`).
+//!
+//! There are two versions of the index:
+//!
+//! * AAF - a list of numbered chunks, each with one or more sections (offset,
+//!   size, and type). Chunk `2` lists the instrument banks and chunk `3` the
+//!   wave systems.
+//! * BAA - starts with `AA_<` and ends with `>_AA`, the chunks are identified
+//!   by tags, e.g., `bnk ` for instrument banks and `ws  ` for wave systems.
+//!
+//! Sections only store offsets into the archive, use [`Section::data`] to get
+//! the data of a section from the archive.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs;
+//! # use std::io::Cursor;
+//! # use picori::Result;
+//! use picori::jaudio::{AudioArchive, SectionKind};
+//!
+//! fn main() -> Result<()> {
+//!     let data = fs::read("JaiInit.aaf")?;
+//!     let archive = AudioArchive::from_binary(&mut Cursor::new(&data))?;
+//!     for section in archive.sections_of(SectionKind::InstrumentBank) {
+//!         let bank = section.data(&data).unwrap();
+//!         fs::write(format!("bank_{}.bnk", section.id), bank)?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Magic of a BAA archive (`AA_<`).
+pub const MAGIC_BAA: [u8; 4] = *b"AA_<";
+
+/// Format of the index of an [`AudioArchive`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// AAF (numbered chunks).
+    Aaf,
+
+    /// BAA (tagged chunks).
+    Baa,
+}
+
+/// Kind of a [`Section`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Instrument bank (`IBNK`), AAF chunk `2` and BAA `bnk `.
+    InstrumentBank,
+
+    /// Wave system (`WSYS`), AAF chunk `3` and BAA `ws  `.
+    WaveSystem,
+
+    /// Sound table (BAA `bst `).
+    SoundTable,
+
+    /// Names of the sound table (BAA `bstn`).
+    SoundTableNames,
+
+    /// Sequence collection (BAA `bsc `).
+    SequenceCollection,
+
+    /// Sequence (BAA `bms `).
+    Sequence,
+
+    /// Stream file table (BAA `bsft`).
+    StreamFileTable,
+
+    /// Unknown (BAA `bfca`).
+    Bfca,
+
+    /// Other AAF chunk (the chunk number).
+    Other(u32),
+}
+
+/// Section of an [`AudioArchive`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Kind of the section.
+    pub kind: SectionKind,
+
+    /// Number of the bank, wave system, or sequence (or the type of the
+    /// section for AAF), `0` if the section has no number.
+    pub id: u32,
+
+    /// Offset of the data in the archive.
+    pub offset: u32,
+
+    /// Size of the data, `None` if the index does not store the size (the
+    /// stream file table and `bfca`).
+    pub size: Option<u32>,
+}
+
+impl Section {
+    /// Get the data of the section from the data of the archive.
+    pub fn data<'a>(&self, archive: &'a [u8]) -> Option<&'a [u8]> {
+        let start = self.offset as usize;
+        match self.size {
+            Some(size) => archive.get(start..start + size as usize),
+            None => archive.get(start..),
+        }
+    }
+}
+
+/// JAudio init archive (AAF or BAA).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioArchive {
+    /// Format of the index.
+    pub format: ArchiveFormat,
+
+    /// Sections.
+    pub sections: Vec<Section>,
+}
+
+impl AudioArchive {
+    /// Parse the index of AAF or BAA archive from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let magic = input.u8_array::<4>()?;
+        input.goto(base)?;
+        match magic == MAGIC_BAA {
+            true => Self::baa(input, base),
+            false => Self::aaf(input),
+        }
+    }
+
+    /// Get the sections of the kind `kind`.
+    pub fn sections_of(&self, kind: SectionKind) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(move |x| x.kind == kind)
+    }
+
+    /// Get the instrument bank with the number `id`.
+    pub fn bank(&self, id: u32) -> Option<&Section> {
+        self.sections_of(SectionKind::InstrumentBank)
+            .find(|x| x.id == id)
+    }
+
+    /// Get the wave system with the number `id`.
+    pub fn wave_system(&self, id: u32) -> Option<&Section> {
+        self.sections_of(SectionKind::WaveSystem)
+            .find(|x| x.id == id)
+    }
+
+    fn aaf<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let mut sections = Vec::new();
+        loop {
+            let chunk = input.bu32()?;
+            let kind = match chunk {
+                0 => break,
+                2 => SectionKind::InstrumentBank,
+                3 => SectionKind::WaveSystem,
+                1..=7 => SectionKind::Other(chunk),
+                _ => Err(ParseProblem::InvalidData(
+                    "unknown AAF chunk",
+                    Location::current(),
+                ))?,
+            };
+            // banks and wave systems are lists terminated by a zero offset
+            let list = matches!(chunk, 2 | 3);
+            loop {
+                let offset = input.bu32()?;
+                if list && offset == 0 {
+                    break;
+                }
+                let size = input.bu32()?;
+                let id = input.bu32()?;
+                sections.push(Section {
+                    kind,
+                    id,
+                    offset,
+                    size: Some(size),
+                });
+                if !list {
+                    break;
+                }
+            }
+        }
+        Ok(Self {
+            format: ArchiveFormat::Aaf,
+            sections,
+        })
+    }
+
+    fn baa<D: Parser + Seeker>(input: &mut D, base: u64) -> Result<Self> {
+        let _magic = input.u8_array::<4>()?;
+        let mut sections = Vec::new();
+        loop {
+            let tag = input.u8_array::<4>()?;
+            let (kind, id, offset, size) = match &tag {
+                b">_AA" => break,
+                b"bst " | b"bstn" | b"bsc " => {
+                    let start = input.bu32()?;
+                    let end = input.bu32()?;
+                    let kind = match &tag {
+                        b"bst " => SectionKind::SoundTable,
+                        b"bstn" => SectionKind::SoundTableNames,
+                        _ => SectionKind::SequenceCollection,
+                    };
+                    (kind, 0, start, Some(end.saturating_sub(start)))
+                },
+                b"bms " => {
+                    let id = input.bu32()?;
+                    let start = input.bu32()?;
+                    let end = input.bu32()?;
+                    let size = end.saturating_sub(start);
+                    (SectionKind::Sequence, id, start, Some(size))
+                },
+                b"ws  " | b"bnk " => {
+                    let id = input.bu32()?;
+                    let offset = input.bu32()?;
+                    let kind = match &tag {
+                        b"ws  " => {
+                            let _unknown = input.bu32()?;
+                            SectionKind::WaveSystem
+                        },
+                        _ => SectionKind::InstrumentBank,
+                    };
+                    // the size is stored in the header of the section
+                    let position = input.position()?;
+                    input.goto(base + offset as u64 + 4)?;
+                    let size = input.bu32()?;
+                    input.goto(position)?;
+                    (kind, id, offset, Some(size))
+                },
+                b"bsft" => (SectionKind::StreamFileTable, 0, input.bu32()?, None),
+                b"bfca" => (SectionKind::Bfca, 0, input.bu32()?, None),
+                _ => Err(ParseProblem::InvalidData(
+                    "unknown BAA chunk",
+                    Location::current(),
+                ))?,
+            };
+            sections.push(Section {
+                kind,
+                id,
+                offset,
+                size,
+            });
+        }
+        ensure!(
+            !sections.is_empty(),
+            ParseProblem::InvalidData("empty BAA archive", Location::current())
+        );
+        Ok(Self {
+            format: ArchiveFormat::Baa,
+            sections,
+        })
+    }
+}
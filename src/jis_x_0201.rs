@@ -11,26 +11,130 @@
 //!
 //! [JIS X 0201][`JisX0201`] is encoding that [Shift
 //! JIS][`crate::ShiftJis1997`] is based upon.
+//!
+//! [`to_full_width`] and [`to_half_width`] convert between the half-width
+//! katakana and the full-width katakana of `JIS X 0208`, e.g., to compare
+//! strings decoded from different encodings.
 
-use std::borrow::Borrow;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+#[cfg(feature = "std")]
 use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
 
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
 use crate::error::DecodingProblem::*;
-use crate::helper::{ParseStringEncoding, ProblemLocation, Parser};
+#[cfg(feature = "std")]
+use crate::error::EncodingProblem;
+use crate::helper::ProblemLocation;
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
 use crate::Result;
 
 /// [`JisX0201`] encoding.
 pub struct JisX0201 {}
 
+/// Full-width characters of the half-width katakana and punctuation
+/// (`U+FF61` to `U+FF9F`).
+const FULL_WIDTH: [char; 63] = [
+    '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ', 'ッ', 'ー',
+    'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
+    'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
+    'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', '゛', '゜',
+];
+
+/// Half-width voiced sound mark (dakuten).
+const VOICED_MARK: char = '\u{FF9E}';
+
+/// Half-width semi-voiced sound mark (handakuten).
+const SEMI_VOICED_MARK: char = '\u{FF9F}';
+
+/// Katakana that have a voiced form directly following them.
+const VOICED: &str = "カキクケコサシスセソタチツテトハヒフヘホ";
+
+/// Katakana that have a semi-voiced form two code points after them.
+const SEMI_VOICED: &str = "ハヒフヘホ";
+
+/// Combine a full-width katakana with a half-width (semi-)voiced sound mark,
+/// or [`None`] if the katakana has no such form.
+fn combine(base: char, mark: char) -> Option<char> {
+    match (base, mark) {
+        ('ウ', VOICED_MARK) => Some('ヴ'),
+        ('ワ', VOICED_MARK) => Some('ヷ'),
+        ('ヲ', VOICED_MARK) => Some('ヺ'),
+        (_, VOICED_MARK) if VOICED.contains(base) => char::from_u32(base as u32 + 1),
+        (_, SEMI_VOICED_MARK) if SEMI_VOICED.contains(base) => char::from_u32(base as u32 + 2),
+        _ => None,
+    }
+}
+
+/// Convert the half-width katakana and punctuation of `text` to full-width.
+/// A (semi-)voiced sound mark is combined with the preceding katakana if
+/// possible, e.g., `ｶﾞ` becomes `ガ`. All other characters are unchanged.
+pub fn to_full_width(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let Some(full) = FULL_WIDTH.get((c as usize).wrapping_sub(0xFF61)).copied() else {
+            output.push(c);
+            continue;
+        };
+        match chars.peek().and_then(|mark| combine(full, *mark)) {
+            Some(combined) => {
+                chars.next();
+                output.push(combined);
+            },
+            None => output.push(full),
+        }
+    }
+    output
+}
+
+/// Convert the full-width katakana and punctuation of `text` that have a
+/// half-width form to half-width. A voiced katakana is split into the
+/// katakana and the sound mark, e.g., `ガ` becomes `ｶﾞ`. All other characters
+/// are unchanged.
+pub fn to_half_width(text: &str) -> String {
+    let half = |full: char| {
+        let index = FULL_WIDTH.iter().position(|x| *x == full)?;
+        char::from_u32(0xFF61 + index as u32)
+    };
+
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        if let Some(half) = half(c) {
+            output.push(half);
+            continue;
+        }
+
+        // find the katakana and sound mark that combine to `c`
+        let split = ["ウワヲ", VOICED]
+            .iter()
+            .flat_map(|x| x.chars())
+            .flat_map(|base| [(base, VOICED_MARK), (base, SEMI_VOICED_MARK)])
+            .find(|(base, mark)| combine(*base, *mark) == Some(c));
+        match split {
+            Some((base, mark)) => {
+                output.extend(half(base));
+                output.push(mark);
+            },
+            None => output.push(c),
+        }
+    }
+    output
+}
+
 /// A iterator decoder for the [`JisX0201`] encoding.
 pub struct Decoder<'x, I>
 where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter:    <I as IntoIterator>::IntoIter,
+    iter:    Tracked<<I as IntoIterator>::IntoIter>,
     _marker: PhantomData<&'x ()>,
 }
 
@@ -41,11 +145,21 @@ where
 {
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
-            iter:    iter.into_iter(),
+            iter:    Tracked::new(iter.into_iter()),
             _marker: PhantomData,
         }
     }
 
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
     /// Decode a single byte.
     pub fn decode_byte(byte: u8) -> Option<char> {
         match byte {
@@ -72,19 +186,45 @@ where
     type Item = Result<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.iter.offset();
         if let Some(byte) = self.iter.next() {
             let byte = byte.borrow();
-            Some(match Self::decode_byte(*byte) {
+            let result = match Self::decode_byte(*byte) {
                 Some(c) => Ok(c),
                 None => Err(InvalidByte(*byte, Location::current()).into()),
-            })
+            };
+            Some(self.iter.locate(offset, result))
         } else {
             None
         }
     }
 }
 
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize { self.iter.offset() }
+}
+
 impl JisX0201 {
+    /// Encode a single character, or [`None`] if the character is not part of
+    /// [JIS X 0201][`JisX0201`].
+    pub fn encode_char(c: char) -> Option<u8> {
+        match c {
+            // Modified ASCII character
+            '\u{00a5}' => Some(0x5c),
+            '\u{203e}' => Some(0x7e),
+            '\u{5c}' | '\u{7e}' => None,
+            // Unaltered ASCII character
+            '\u{00}'..='\u{7f}' => Some(c as u8),
+            // Single-byte half-width katakana
+            '\u{ff61}'..='\u{ff9f}' => Some((c as u32 - 0xFF61) as u8 + 0xa1),
+            _ => None,
+        }
+    }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -105,6 +245,13 @@ impl JisX0201 {
         Self::iter(iter).collect()
     }
 
+    /// Decode all bytes of `data` like [`JisX0201::all`], but ASCII-only data
+    /// (without `\` and `~`, which decode to `¥` and `‾`) is borrowed
+    /// without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, b"\\~", |data| Self::all(data))
+    }
+
     /// Decode the first string (until a NULL character is reached) from the
     /// given iterator.
     pub fn first<I>(iter: I) -> Result<String>
@@ -119,6 +266,55 @@ impl JisX0201 {
             })
             .collect()
     }
+
+    /// Decode all bytes into a string like [`JisX0201::all`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`JisX0201::first`], but invalid bytes are
+    /// replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`JisX0201::all`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`JisX0201::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
 }
 
 /// Extension trait for iterators of bytes and adds the helper function
@@ -139,6 +335,7 @@ where
 {
 }
 
+#[cfg(feature = "std")]
 impl ParseStringEncoding for JisX0201 {
     fn parse_str<I>(iter: I) -> Result<String>
     where
@@ -148,8 +345,20 @@ impl ParseStringEncoding for JisX0201 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("JisX0201::write_str")
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for (index, c) in data.char_indices() {
+            let byte = Self::encode_char(c).ok_or(EncodingProblem::InvalidCharacter(
+                c,
+                index,
+                Location::current(),
+            ))?;
+            *buffer
+                .get_mut(i)
+                .ok_or(EncodingProblem::BufferTooSmall(Location::current()))? = byte;
+            i += 1;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
@@ -157,10 +366,7 @@ impl ParseStringEncoding for JisX0201 {
         let iter = buffer
             .bytes()
             .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
+            .filter_map(|x| x.ok());
         Self::first(iter)
     }
 }
@@ -178,4 +384,28 @@ mod tests {
         let data = b"abc\0def";
         assert_eq!(JisX0201::parse_str(data).unwrap(), "abc".to_string());
     }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0; 4];
+        assert_eq!(JisX0201::write_str("a¥ｱ", &mut buffer).unwrap(), 3);
+        assert_eq!(buffer, [b'a', 0x5C, 0xB1, 0]);
+        assert!(JisX0201::write_str("\\", &mut buffer).is_err());
+        assert!(JisX0201::write_str("abcde", &mut buffer).is_err());
+    }
+
+    #[test]
+    fn width() {
+        let half = "ｶﾞｷﾞﾊﾟﾋﾞｳﾞｱﾞｰ｡ﾞabc";
+        let full = "ガギパビヴア゛ー。゛abc";
+        assert_eq!(to_full_width(half), full);
+        assert_eq!(to_half_width(full), half);
+        assert_eq!(to_half_width("ッヅボ"), "ｯﾂﾞﾎﾞ");
+
+        // every half-width character survives the round trip
+        let all = (0xFF61..=0xFF9F)
+            .filter_map(char::from_u32)
+            .collect::<String>();
+        assert_eq!(to_half_width(&to_full_width(&all)), all);
+    }
 }
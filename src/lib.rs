@@ -45,47 +45,233 @@
 //! * [REL][crate::rel] - Relocatable module
 //! * [GCM][crate::gcm] - GameCube master disc
 //! * [CISO][crate::ciso] - Compact ISO
+//! * [GCZ][crate::gcz] - Dolphin compressed disc image (`gcz` feature)
+//! * [WBFS][crate::wbfs] - Wii Backup File System disc image
+//! * [Disc][crate::disc] - Disc image hashing and conversion
+//! * [NKit][crate::nkit] - NKit image detection (and junk data regeneration)
+//! * [Wii][crate::wii] - Wii disc (partitions, tickets, and title metadata)
+//! * [WAD][crate::wad] - Wii title package (channels and IOS)
+//! * [IMET][crate::imet] - Wii banner headers (IMET and IMD5)
+//! * [Wii save][crate::wii_save] - Wii save data export (`data.bin`, `crypto` feature)
+//! * [BNR][crate::bnr] - GameCube banner (`opening.bnr`)
+//! * [IPL][crate::ipl] - GameCube IPL fonts and SRAM
+//! * [BMG][crate::bmg] - BMG message file
+//! * [BCSV][crate::bcsv] - JMapInfo table
+//! * [BRFNT][crate::brfnt] - Wii bitmap font
+//! * [BRLAN][crate::brlan] - Wii layout animation
+//! * [BRLYT][crate::brlyt] - Wii layout (menus and channel banners)
+//! * [BLO][crate::blo] - JSystem screen layout
+//! * [J3D][crate::j3d] - JSystem model (BMD and BDL)
+//! * [J3D animation][crate::j3d_animation] - JSystem joint and texture SRT animation (BCK and BTK)
+//! * [DZB][crate::dzb] - Wind Waker collision mesh
+//! * [DZR/DZS][crate::dzx] - Wind Waker stage and room entities
+//! * [JAudio][crate::jaudio] - JAudio init archive index (AAF and BAA)
+//! * [Event list][crate::event_list] - JSystem cutscene event list (`event_list.dat`)
+//! * [GCI][crate::gci] - GameCube memory card file
+//! * [Memory card][crate::memcard] - GameCube memory card image
+//! * [GCT][crate::gct] - Gecko code list
+//! * [RARC][crate::rarc] - JSystem archive
+//! * [U8][crate::u8arc] - Wii archive
+//! * [VFS][crate::vfs] - Directory tree API shared by discs and archives
+//! * [Compression][crate::compression] - Traits shared by all compression formats
 //! * [Yaz0][crate::yaz0] - Yaz0 compression
+//! * [Yay0][crate::yay0] - Yay0 decompression
+//! * [LZ][crate::lz] - LZ10/LZ11 (Nintendo LZ77) compression
+//! * [Huffman][crate::huffman] - Nintendo Huffman decompression
+//! * [ASH0][crate::ash] - ASH0 decompression
+//! * [RLE][crate::rle] - Nintendo run-length encoding
+//! * [Texture][crate::texture] - GameCube and Wii texture formats
+//! * [THP][crate::thp] - THP video
+//! * [DSP][crate::dsp] - DSP-ADPCM audio
+//! * [AST][crate::ast] - AST audio stream
+//! * [AFC][crate::afc] - AFC audio stream
+//! * [BRSTM][crate::brstm] - BRSTM audio stream
 //! * [JIS X 0201][crate::jis_x_0201] - JIS X 0201 encoding
 //! * [Shift JIS 1997][crate::shift_jis_1997] - Shift JIS 1997 encoding
 //! * [Shift JIS 2004][crate::shift_jis_2004] - Shift JIS 2004 encoding
+//! * [EUC-JP][crate::euc_jp] - EUC-JP encoding
+//! * [UTF-16 BE][crate::utf16_be] - UTF-16 big-endian encoding
+//! * [Windows-1252][crate::windows_1252] - Windows-1252 (Latin-1) encoding
+//!
+//! # `no_std`
+//!
+//! The `std` feature is enabled by default. Without it, only the encodings,
+//! the [textures][crate::texture], (and [`Error`]) are available and they only
+//! require `alloc`, e.g., to use them in homebrew. Reading from
+//! [`Read`](std::io::Read) and writing to [`Write`](std::io::Write), e.g.,
+//! [`CharReader`], require `std`.
+//!
+//! # `serde`
+//!
+//! With the `serde` feature, the parsed metadata (e.g., [`Dol`] headers and
+//! sections, [FST][crate::gcm::fst::Fst] entries, [BMG][crate::bmg::Bmg]
+//! messages, and [tickets][crate::wii::Ticket] and
+//! [TMDs][crate::wii::Tmd]) implements `Serialize` and `Deserialize`, e.g., to
+//! dump it as JSON. Raw data buffers, e.g., the section data of a [`Dol`] or
+//! the contents of a [WAD][crate::wad::Wad], are skipped and empty after
+//! deserialization.
 
 #![allow(missing_docs)]
 #![warn(unused_imports)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod afc;
 pub mod ascii;
+#[cfg(feature = "std")]
+pub mod ash;
+#[cfg(feature = "std")]
+pub mod ast;
+#[cfg(feature = "std")]
+pub mod bcsv;
+#[cfg(feature = "std")]
+pub mod blo;
+#[cfg(feature = "std")]
+pub mod bmg;
+#[cfg(feature = "std")]
+pub mod bnr;
+#[cfg(feature = "std")]
+pub mod brfnt;
+#[cfg(feature = "std")]
+pub mod brlan;
+#[cfg(feature = "std")]
+pub mod brlyt;
+#[cfg(feature = "std")]
+pub mod brstm;
+#[cfg(feature = "std")]
 pub mod ciso;
+#[cfg(feature = "std")]
+pub mod compression;
+#[cfg(feature = "std")]
+pub mod disc;
+#[cfg(feature = "std")]
 pub mod dol;
+#[cfg(feature = "std")]
+pub mod dsp;
+#[cfg(feature = "std")]
+pub mod dzb;
+#[cfg(feature = "std")]
+pub mod dzx;
+pub mod encoding;
+pub mod euc_jp;
+#[cfg(feature = "std")]
+pub mod event_list;
+#[cfg(feature = "std")]
+pub mod gci;
+#[cfg(feature = "std")]
 pub mod gcm;
+#[cfg(feature = "std")]
+pub mod gct;
+#[cfg(feature = "gcz")]
+pub mod gcz;
+#[cfg(feature = "std")]
+pub mod huffman;
+#[cfg(feature = "std")]
+pub mod imet;
+#[cfg(feature = "std")]
+pub mod ipl;
+#[cfg(feature = "std")]
+pub mod j3d;
+#[cfg(feature = "std")]
+pub mod j3d_animation;
+#[cfg(feature = "std")]
+pub mod jaudio;
 pub mod jis_x_0201;
+#[cfg(feature = "std")]
+pub mod lz;
+#[cfg(feature = "std")]
+pub mod memcard;
+#[cfg(feature = "std")]
+pub mod nkit;
+#[cfg(feature = "std")]
 pub mod rarc;
+#[cfg(feature = "std")]
 pub mod rel;
+#[cfg(feature = "std")]
+pub mod rle;
 pub mod shift_jis_1997;
 pub mod shift_jis_2004;
+pub mod texture;
+#[cfg(feature = "std")]
+pub mod thp;
+#[cfg(feature = "std")]
+pub mod u8arc;
+pub mod utf16_be;
+#[cfg(feature = "std")]
+pub mod vfs;
+#[cfg(feature = "std")]
+pub mod wad;
+#[cfg(feature = "std")]
+pub mod wbfs;
+#[cfg(feature = "std")]
+pub mod wii;
+#[cfg(feature = "crypto")]
+pub mod wii_save;
+pub mod windows_1252;
+#[cfg(feature = "std")]
+pub mod yay0;
+#[cfg(feature = "std")]
 pub mod yaz0;
 
 #[doc(inline)]
 pub use ascii::{Ascii, IteratorExt as AsciiIteratorExt};
 #[doc(inline)]
+#[cfg(feature = "std")]
+pub use bnr::Bnr;
+#[doc(inline)]
+#[cfg(feature = "std")]
 pub use ciso::CisoReader;
 #[doc(inline)]
+#[cfg(feature = "std")]
 pub use dol::Dol;
 #[doc(inline)]
+pub use encoding::{CharOffsets, Encoding, ErrorPolicy, Lossy, WithPolicy};
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use encoding::{CharReader, EncodeWriter};
+#[doc(inline)]
+pub use euc_jp::{EucJp, IteratorExt as EucJpIteratorExt};
+#[doc(inline)]
+#[cfg(feature = "std")]
 pub use gcm::Gcm;
 #[doc(inline)]
+#[cfg(feature = "gcz")]
+pub use gcz::GczReader;
+#[doc(inline)]
 pub use helper::{Error, Result};
 #[doc(inline)]
 pub use jis_x_0201::{IteratorExt as JisX0201IteratorExt, JisX0201};
 #[doc(inline)]
-pub use rarc::RarcReader;
+#[cfg(feature = "std")]
+pub use rarc::{RarcBuilder, RarcReader};
 #[doc(inline)]
+#[cfg(feature = "std")]
 pub use rel::Rel;
 #[doc(inline)]
 pub use shift_jis_1997::{IteratorExt as ShiftJis1997IteratorExt, ShiftJis1997};
 #[doc(inline)]
 pub use shift_jis_2004::{IteratorExt as ShiftJis2004IteratorExt, ShiftJis2004};
 #[doc(inline)]
-pub use yaz0::Yaz0Reader;
+#[cfg(feature = "std")]
+pub use thp::ThpReader;
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use u8arc::{U8Builder, U8Reader};
+#[doc(inline)]
+pub use utf16_be::{IteratorExt as Utf16BeIteratorExt, Utf16Be};
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use wii::Wii;
+#[doc(inline)]
+pub use windows_1252::{IteratorExt as Windows1252IteratorExt, Windows1252};
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use yay0::Yay0Reader;
+#[doc(inline)]
+#[cfg(feature = "std")]
+pub use yaz0::{Yaz0Reader, Yaz0Writer};
 
 mod helper;
 
@@ -98,7 +284,11 @@ pub mod error {
     };
 }
 
+#[cfg(feature = "std")]
 pub use helper::Seeker;
+#[cfg(feature = "std")]
 pub use helper::Parser;
+#[cfg(feature = "std")]
 pub use helper::Reader;
+#[cfg(feature = "std")]
 pub use helper::Writer;
@@ -0,0 +1,302 @@
+//! LZ10 and LZ11 (Nintendo LZ77) compression and decompression.
+//!
+//! Both variants start with a little-endian header holding the type (`0x10`
+//! or `0x11`) in the lowest byte and the decompressed size in the upper 24
+//! bits. A size of zero means the size follows as a separate 32-bit value.
+//! LZ11 supports longer back-references than LZ10.
+//!
+//! On the Wii, the header is often preceded by the ASCII magic `LZ77` (e.g.,
+//! in channel and save banners). [`Header::from_binary`] skips the magic, so
+//! these streams are decompressed like any other.
+//!
+//! ## Compression
+//!
+//! ```
+//! # use picori::Result;
+//! use picori::lz::{self, Kind};
+//!
+//! fn main() -> Result<()> {
+//!     let data = b"abcabcabcabcabcabc".to_vec();
+//!     let compressed = lz::compress(&data, Kind::Lz11);
+//!     let mut reader = std::io::Cursor::new(compressed);
+//!     let header = lz::Header::from_binary(&mut reader)?;
+//!     assert_eq!(header.kind, Some(Kind::Lz11));
+//!     assert_eq!(lz::decompress(&mut reader, &header)?, data);
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::compression::{Compress, Decompress};
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, MatchFinder, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Size of the sliding window (the largest distance of a back-reference).
+const WINDOW_SIZE: usize = 0x1000;
+
+/// Shortest back-reference.
+const MIN_MATCH: usize = 3;
+
+/// Magic preceding the header of some streams, the four characters "LZ77".
+pub(crate) const LZ77_MAGIC: u32 = 0x4C5A3737;
+
+/// LZ variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// LZ10, back-references of up to 18 bytes.
+    Lz10,
+    /// LZ11, back-references of up to 0x10110 bytes.
+    Lz11,
+}
+
+impl Kind {
+    /// Type byte of the header.
+    pub fn id(self) -> u8 {
+        match self {
+            Kind::Lz10 => 0x10,
+            Kind::Lz11 => 0x11,
+        }
+    }
+
+    /// Variant with the header type byte `id`.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0x10 => Some(Kind::Lz10),
+            0x11 => Some(Kind::Lz11),
+            _ => None,
+        }
+    }
+
+    fn max_match(self) -> usize {
+        match self {
+            Kind::Lz10 => 0x12,
+            Kind::Lz11 => 0x10110,
+        }
+    }
+}
+
+/// LZ header.
+pub struct Header {
+    /// Variant (`None` if the type byte is unknown).
+    pub kind: Option<Kind>,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+    /// Whether the header was preceded by the `LZ77` magic.
+    pub lz77_magic: bool,
+}
+
+impl Header {
+    /// Reads a LZ header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        let mut value = input.lu32()?;
+        let lz77_magic = value.swap_bytes() == LZ77_MAGIC;
+        if lz77_magic {
+            value = input.lu32()?;
+        }
+        let decompressed_size = match value >> 8 {
+            0 => input.lu32()?,
+            size => size,
+        };
+        Ok(Header {
+            kind: Kind::from_id(value as u8),
+            decompressed_size,
+            lz77_magic,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.kind.is_some() }
+
+    /// Writes the header for `size` bytes of `kind` compressed data.
+    fn to_binary(kind: Kind, size: usize) -> Vec<u8> {
+        let id = kind.id() as u32;
+        if size > 0 && size <= 0xFFFFFF {
+            (id | (size as u32) << 8).to_le_bytes().to_vec()
+        } else {
+            let mut header = id.to_le_bytes().to_vec();
+            header.extend_from_slice(&(size as u32).to_le_bytes());
+            header
+        }
+    }
+}
+
+/// Check if the given data is LZ10 or LZ11 compressed by looking at the type
+/// byte of the header.
+pub fn is_lz<D: Parser + Seeker>(input: &mut D) -> bool {
+    let mut check = || -> Result<bool> {
+        let base = input.position()?;
+        let header = Header::from_binary(input)?;
+        input.goto(base)?;
+        Ok(header.is_valid())
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Decompresses the data into a new allocated [`Vec`]. `input` must be
+/// positioned directly after the `header`.
+pub fn decompress<D: Parser + Seeker>(input: &mut D, header: &Header) -> Result<Vec<u8>> {
+    let mut output = vec![0; header.decompressed_size as usize];
+    decompress_into(input, header, output.as_mut_slice())?;
+    Ok(output)
+}
+
+/// Decompresses the data into the beginning of the given buffer, which must
+/// be large enough to hold the decompressed data. `input` must be positioned
+/// directly after the `header`. Returns the number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(
+    input: &mut D,
+    header: &Header,
+    destination: &mut [u8],
+) -> Result<usize> {
+    let kind = header
+        .kind
+        .ok_or(InvalidHeader("Invalid type", Location::current()))?;
+
+    let written = header.decompressed_size as usize;
+    ensure!(
+        destination.len() >= written,
+        InvalidDecompressedSize(Location::current())
+    );
+    let destination = &mut destination[..written];
+
+    let size = destination.len();
+    let mut dest = 0;
+    let mut flags = 0;
+    let mut flag_bits = 0;
+    while dest < size {
+        if flag_bits == 0 {
+            flags = input.u8()?;
+            flag_bits = 8;
+        }
+
+        if flags & 0x80 == 0 {
+            destination[dest] = input.u8()?;
+            dest += 1;
+        } else {
+            let byte0 = input.u8()? as usize;
+            let (length, byte) = match (kind, byte0 >> 4) {
+                (Kind::Lz10, length) => (length + 3, byte0),
+                (Kind::Lz11, 0) => {
+                    let byte1 = input.u8()? as usize;
+                    (((byte0 & 0xF) << 4 | byte1 >> 4) + 0x11, byte1)
+                },
+                (Kind::Lz11, 1) => {
+                    let byte1 = input.u8()? as usize;
+                    let byte2 = input.u8()? as usize;
+                    (
+                        ((byte0 & 0xF) << 12 | byte1 << 4 | byte2 >> 4) + 0x111,
+                        byte2,
+                    )
+                },
+                (Kind::Lz11, length) => (length + 1, byte0),
+            };
+            let offset = (byte & 0xF) << 8 | input.u8()? as usize;
+
+            ensure!(offset < dest, UnexpectedEndOfData(Location::current()));
+            ensure!(
+                dest + length <= size,
+                InvalidDecompressedSize(Location::current())
+            );
+            let base = dest - (offset + 1);
+            for n in 0..length {
+                destination[dest] = destination[base + n];
+                dest += 1;
+            }
+        }
+
+        flags <<= 1;
+        flag_bits -= 1;
+    }
+
+    Ok(written)
+}
+
+/// Compresses `data` (including the header) with the LZ variant `kind` into
+/// a new allocated [`Vec`]. The output is padded to a multiple of four bytes.
+pub fn compress(data: &[u8], kind: Kind) -> Vec<u8> {
+    let mut output = Header::to_binary(kind, data.len());
+    let mut finder = MatchFinder::new(kind.max_match());
+    let mut flags = 0;
+    let mut flag_bits = 8;
+    let mut position = 0;
+    while position < data.len() {
+        if flag_bits == 8 {
+            flags = output.len();
+            output.push(0);
+            flag_bits = 0;
+        }
+
+        let (length, distance) = finder.find(data, position, WINDOW_SIZE, false);
+        if length < MIN_MATCH {
+            output.push(data[position]);
+            position += 1;
+        } else {
+            output[flags] |= 0x80 >> flag_bits;
+            let offset = distance - 1;
+            match kind {
+                Kind::Lz10 => {
+                    output.push(((length - 3) << 4 | offset >> 8) as u8);
+                },
+                Kind::Lz11 if length <= 0x10 => {
+                    output.push(((length - 1) << 4 | offset >> 8) as u8);
+                },
+                Kind::Lz11 if length <= 0x110 => {
+                    let length = length - 0x11;
+                    output.push((length >> 4) as u8);
+                    output.push(((length & 0xF) << 4 | offset >> 8) as u8);
+                },
+                Kind::Lz11 => {
+                    let length = length - 0x111;
+                    output.push((0x10 | length >> 12) as u8);
+                    output.push((length >> 4) as u8);
+                    output.push(((length & 0xF) << 4 | offset >> 8) as u8);
+                },
+            }
+            output.push(offset as u8);
+            position += length;
+        }
+        flag_bits += 1;
+    }
+
+    output.resize(output.len().next_multiple_of(4), 0);
+    output
+}
+
+/// LZ10/LZ11 codec, see [`crate::compression`]. Decompresses both variants
+/// and compresses with `kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lz {
+    /// Variant used to compress.
+    pub kind: Kind,
+}
+
+impl Default for Lz {
+    fn default() -> Self { Self { kind: Kind::Lz10 } }
+}
+
+impl Decompress for Lz {
+    const NAME: &'static str = "LZ";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_lz(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let header = Header::from_binary(input)?;
+        decompress(input, &header)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let header = Header::from_binary(input)?;
+        decompress_into(input, &header, destination)
+    }
+}
+
+impl Compress for Lz {
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> { Ok(compress(data, self.kind)) }
+}
@@ -0,0 +1,522 @@
+//! GameCube memory card images (`.raw`, `.mcd`).
+//!
+//! A memory card image is a dump of all blocks (`0x2000` bytes) of a memory
+//! card. The first five blocks are the system area:
+//!
+//! * Block 0 - [Header], the card size, encoding, and format information.
+//! * Block 1 and 2 - [Directory], the [directory entries][`DirectoryEntry`] of
+//!   all files (two copies).
+//! * Block 3 and 4 - [Block allocation table][`BlockMap`], the next block of
+//!   every block of a file (two copies).
+//!
+//! The directory and the block allocation table are stored twice, the copy
+//! with a valid checksum and the higher update counter is the active one. A
+//! file is a chain of blocks, see [`Memcard::blocks`], and can be extracted as
+//! a [GCI][`crate::gci`] file with [`Memcard::extract`]. A GCI file is added
+//! to the card with [`Memcard::insert`], which allocates its blocks and
+//! updates both copies of the directory and the block allocation table.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("MemoryCardA.USA.raw")?;
+//!     let card = picori::memcard::Memcard::from_binary(&mut file)?;
+//!     for (index, entry) in card.files() {
+//!         let gci = card.extract(index)?;
+//!         println!(
+//!             "{} {:?} ({} bytes)",
+//!             entry.game_id,
+//!             entry.filename(),
+//!             gci.data.len()
+//!         );
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::error::BuildProblem;
+use crate::gci::{DirectoryEntry, Gci, BLOCK_SIZE, ENTRY_SIZE};
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Writer};
+use crate::Result;
+
+/// Number of blocks of the system area (header, directories, and block
+/// allocation tables).
+pub const SYSTEM_BLOCKS: usize = 5;
+
+/// Number of directory entries.
+pub const DIRECTORY_ENTRIES: usize = 127;
+
+/// Number of blocks of a memory card with a size of 1 megabit.
+pub const BLOCKS_PER_MEGABIT: usize = 16;
+
+/// Value of the block allocation table for the last block of a file.
+pub const LAST_BLOCK: u16 = 0xFFFF;
+
+/// Compute the checksum and inverse checksum of `data`, the sum of all 16-bit
+/// words and the sum of all inverted 16-bit words.
+pub fn checksum(data: &[u8]) -> [u16; 2] {
+    let (mut sum, mut inverse) = (0_u16, 0_u16);
+    for word in data.chunks_exact(2) {
+        let word = u16::from_be_bytes([word[0], word[1]]);
+        sum = sum.wrapping_add(word);
+        inverse = inverse.wrapping_add(!word);
+    }
+    [sum, inverse].map(|x| if x == 0xFFFF { 0 } else { x })
+}
+
+/// Memory card header (block 0).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Serial number (derived from the flash ID and format time).
+    pub serial: [u8; 12],
+
+    /// Format time (ticks of the time base).
+    pub format_time: u64,
+
+    /// SRAM counter bias.
+    pub sram_bias: u32,
+
+    /// SRAM language.
+    pub sram_language: u32,
+
+    /// Unknown.
+    pub unknown: u32,
+
+    /// Device ID.
+    pub device_id: u16,
+
+    /// Size of the card in megabits (4, 8, 16, 32, 64, or 128).
+    pub size: u16,
+
+    /// Encoding, `0` for ANSI and `1` for Shift JIS.
+    pub encoding: u16,
+
+    /// Update counter.
+    pub update_counter: u16,
+
+    /// Checksum of the header.
+    pub checksum: [u16; 2],
+}
+
+impl Header {
+    /// Parse memory card header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let serial = input.u8_array::<12>()?;
+        let format_time = input.bu64()?;
+        let sram_bias = input.bu32()?;
+        let sram_language = input.bu32()?;
+        let unknown = input.bu32()?;
+        let device_id = input.bu16()?;
+        let size = input.bu16()?;
+        let encoding = input.bu16()?;
+        let _unused = input.u8_array::<0x1D4>()?;
+        let update_counter = input.bu16()?;
+        let checksum = input.bu16_array::<2>()?;
+
+        Ok(Self {
+            serial,
+            format_time,
+            sram_bias,
+            sram_language,
+            unknown,
+            device_id,
+            size,
+            encoding,
+            update_counter,
+            checksum,
+        })
+    }
+
+    /// Number of blocks of the card.
+    pub fn block_count(&self) -> usize { self.size as usize * BLOCKS_PER_MEGABIT }
+}
+
+/// Directory (block 1 and 2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directory {
+    /// Directory entries, [`None`] for unused entries.
+    pub entries: Vec<Option<DirectoryEntry>>,
+
+    /// Update counter.
+    pub update_counter: u16,
+
+    /// Checksum of the directory.
+    pub checksum: [u16; 2],
+}
+
+impl Directory {
+    /// Parse directory (one block).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let mut entries = Vec::with_capacity(DIRECTORY_ENTRIES);
+        for _ in 0..DIRECTORY_ENTRIES {
+            let raw = input.u8_array::<ENTRY_SIZE>()?;
+            entries.push(match raw[..6].iter().all(|x| *x == 0xFF) {
+                true => None,
+                false => Some(DirectoryEntry::from_binary(&mut Cursor::new(&raw))?),
+            });
+        }
+        let _padding = input.u8_array::<0x3A>()?;
+        let update_counter = input.bu16()?;
+        let checksum = input.bu16_array::<2>()?;
+
+        Ok(Self {
+            entries,
+            update_counter,
+            checksum,
+        })
+    }
+
+    /// Write directory (one block).
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.entries.len() == DIRECTORY_ENTRIES,
+            BuildProblem::InvalidData("memory card directory entries", Location::current())
+        );
+        for entry in self.entries.iter() {
+            match entry {
+                Some(entry) => entry.to_binary(output)?,
+                None => output.u8_array(&[0xFF; ENTRY_SIZE])?,
+            }
+        }
+        output.u8_array(&[0xFF; 0x3A])?;
+        output.bu16(self.update_counter)?;
+        output.bu16(self.checksum[0])?;
+        output.bu16(self.checksum[1])?;
+        Ok(())
+    }
+}
+
+/// Block allocation table (block 3 and 4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMap {
+    /// Checksum of the block allocation table.
+    pub checksum: [u16; 2],
+
+    /// Update counter.
+    pub update_counter: u16,
+
+    /// Number of free blocks.
+    pub free_blocks: u16,
+
+    /// Last allocated block.
+    pub last_allocated: u16,
+
+    /// Next block of each block (starting with the first block after the
+    /// system area), `0` for free blocks and [`LAST_BLOCK`] for the last
+    /// block of a file.
+    pub map: Vec<u16>,
+}
+
+impl BlockMap {
+    /// Parse block allocation table (one block).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let checksum = input.bu16_array::<2>()?;
+        let update_counter = input.bu16()?;
+        let free_blocks = input.bu16()?;
+        let last_allocated = input.bu16()?;
+        let map = (0..(BLOCK_SIZE - 0x0A) / 2)
+            .map(|_| input.bu16())
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            checksum,
+            update_counter,
+            free_blocks,
+            last_allocated,
+            map,
+        })
+    }
+
+    /// Write block allocation table (one block).
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.map.len() == (BLOCK_SIZE - 0x0A) / 2,
+            BuildProblem::InvalidData("memory card block allocation table", Location::current())
+        );
+        output.bu16(self.checksum[0])?;
+        output.bu16(self.checksum[1])?;
+        output.bu16(self.update_counter)?;
+        output.bu16(self.free_blocks)?;
+        output.bu16(self.last_allocated)?;
+        for next in self.map.iter() {
+            output.bu16(*next)?;
+        }
+        Ok(())
+    }
+
+    /// Get the next block of `block`.
+    pub fn next(&self, block: u16) -> Option<u16> {
+        let index = (block as usize).checked_sub(SYSTEM_BLOCKS)?;
+        self.map.get(index).copied()
+    }
+}
+
+/// GameCube memory card image.
+#[derive(Debug, Clone)]
+pub struct Memcard {
+    header:      Header,
+    directories: [Directory; 2],
+    block_maps:  [BlockMap; 2],
+    data:        Vec<u8>,
+}
+
+impl Memcard {
+    /// Parse memory card image from binary stream.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let mut system = vec![0; SYSTEM_BLOCKS * BLOCK_SIZE];
+        input.read_into(&mut system)?;
+        let block = |index: usize| Cursor::new(&system[index * BLOCK_SIZE..][..BLOCK_SIZE]);
+
+        let header = Header::from_binary(&mut block(0))?;
+        ensure!(
+            matches!(header.size, 4 | 8 | 16 | 32 | 64 | 128),
+            ParseProblem::InvalidHeader(
+                "memory card size (4, 8, 16, 32, 64, or 128 megabits)",
+                Location::current()
+            )
+        );
+        let blocks = header.block_count();
+        let directories = [
+            Directory::from_binary(&mut block(1))?,
+            Directory::from_binary(&mut block(2))?,
+        ];
+        let block_maps = [
+            BlockMap::from_binary(&mut block(3))?,
+            BlockMap::from_binary(&mut block(4))?,
+        ];
+
+        let mut data = system;
+        data.resize(blocks * BLOCK_SIZE, 0);
+        input.read_into(&mut data[SYSTEM_BLOCKS * BLOCK_SIZE..])?;
+
+        Ok(Self {
+            header,
+            directories,
+            block_maps,
+            data,
+        })
+    }
+
+    /// Get the header.
+    pub fn header(&self) -> &Header { &self.header }
+
+    /// Get both copies of the directory.
+    pub fn directories(&self) -> &[Directory; 2] { &self.directories }
+
+    /// Get both copies of the block allocation table.
+    pub fn block_maps(&self) -> &[BlockMap; 2] { &self.block_maps }
+
+    /// Check that the checksum of the system block `index` is valid.
+    fn is_valid(&self, index: usize) -> bool {
+        let block = &self.data[index * BLOCK_SIZE..][..BLOCK_SIZE];
+        match index {
+            0 => checksum(&block[..0x1FC]) == self.header.checksum,
+            1 | 2 => checksum(&block[..0x1FFC]) == self.directories[index - 1].checksum,
+            _ => checksum(&block[4..]) == self.block_maps[index - 3].checksum,
+        }
+    }
+
+    /// Get the index of the active copy, the copy with a valid checksum and
+    /// the higher update counter.
+    fn active(&self, first: usize, counters: [u16; 2]) -> usize {
+        match (self.is_valid(first), self.is_valid(first + 1)) {
+            (true, false) => 0,
+            (false, true) => 1,
+            _ if counters[1] > counters[0] => 1,
+            _ => 0,
+        }
+    }
+
+    /// Get the index (0 or 1) of the active directory.
+    pub fn active_directory(&self) -> usize {
+        self.active(1, self.directories.each_ref().map(|x| x.update_counter))
+    }
+
+    /// Get the index (0 or 1) of the active block allocation table.
+    pub fn active_block_map(&self) -> usize {
+        self.active(3, self.block_maps.each_ref().map(|x| x.update_counter))
+    }
+
+    /// Get the active directory.
+    pub fn directory(&self) -> &Directory { &self.directories[self.active_directory()] }
+
+    /// Get the active block allocation table.
+    pub fn block_map(&self) -> &BlockMap { &self.block_maps[self.active_block_map()] }
+
+    /// Returns `true` if the checksums of the header and the active directory
+    /// and block allocation table are valid.
+    pub fn checksums_valid(&self) -> bool {
+        self.is_valid(0)
+            && self.is_valid(1 + self.active_directory())
+            && self.is_valid(3 + self.active_block_map())
+    }
+
+    /// Iterate over the files (index and directory entry) of the active
+    /// directory.
+    pub fn files(&self) -> impl Iterator<Item = (usize, &DirectoryEntry)> {
+        self.directory()
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| entry.as_ref().map(|x| (index, x)))
+    }
+
+    /// Get the blocks of the file `index` (of the active directory) by
+    /// following the chain of the active block allocation table.
+    pub fn blocks(&self, index: usize) -> Result<Vec<u16>> {
+        let entry = self
+            .directory()
+            .entries
+            .get(index)
+            .and_then(Option::as_ref)
+            .ok_or(ParseProblem::InvalidData(
+                "memory card file (unused directory entry)",
+                Location::current(),
+            ))?;
+        let block_map = self.block_map();
+        let count = self.header.block_count();
+
+        let mut blocks = Vec::with_capacity(entry.block_count as usize);
+        let mut block = entry.first_block;
+        loop {
+            ensure!(
+                (SYSTEM_BLOCKS..count).contains(&(block as usize))
+                    && blocks.len() < entry.block_count as usize,
+                ParseProblem::InvalidData("memory card block chain", Location::current())
+            );
+            blocks.push(block);
+            match block_map.next(block) {
+                Some(LAST_BLOCK) => break,
+                Some(next) => block = next,
+                None => Err(ParseProblem::InvalidData(
+                    "memory card block chain",
+                    Location::current(),
+                ))?,
+            }
+        }
+        ensure!(
+            blocks.len() == entry.block_count as usize,
+            ParseProblem::InvalidData("memory card block chain (too short)", Location::current())
+        );
+        Ok(blocks)
+    }
+
+    /// Get the data of the block `block`.
+    pub fn block(&self, block: u16) -> Option<&[u8]> {
+        let offset = block as usize * BLOCK_SIZE;
+        self.data.get(offset..offset + BLOCK_SIZE)
+    }
+
+    /// Extract the file `index` (of the active directory) as a GCI file.
+    pub fn extract(&self, index: usize) -> Result<Gci> {
+        let blocks = self.blocks(index)?;
+        let entry = self.directory().entries[index].clone().unwrap();
+        let mut data = Vec::with_capacity(blocks.len() * BLOCK_SIZE);
+        for block in blocks {
+            data.extend_from_slice(self.block(block).unwrap());
+        }
+        Ok(Gci { entry, data })
+    }
+
+    /// Insert the GCI file `gci` into the card. The blocks of the file are
+    /// allocated after the last allocated block, and both copies of the
+    /// directory and the block allocation table are replaced by the updated
+    /// active copies (with a new update counter and checksum). Returns the
+    /// index of the directory entry of the file.
+    pub fn insert(&mut self, gci: &Gci) -> Result<usize> {
+        let count = gci.entry.block_count as usize;
+        ensure!(
+            count > 0 && gci.data.len() == count * BLOCK_SIZE,
+            BuildProblem::InvalidData("GCI data size (not the block count)", Location::current())
+        );
+        let mut directory = self.directory().clone();
+        let mut block_map = self.block_map().clone();
+        ensure!(
+            !self.files().any(|(_, x)| {
+                x.game_id == gci.entry.game_id && x.filename == gci.entry.filename
+            }),
+            BuildProblem::InvalidData("memory card file (already exists)", Location::current())
+        );
+        let index =
+            directory
+                .entries
+                .iter()
+                .position(Option::is_none)
+                .ok_or(BuildProblem::InvalidData(
+                    "memory card directory (no free entry)",
+                    Location::current(),
+                ))?;
+
+        // free blocks, starting after the last allocated block
+        let total = self
+            .header
+            .block_count()
+            .min(SYSTEM_BLOCKS + block_map.map.len());
+        let start = (block_map.last_allocated as usize + 1).clamp(SYSTEM_BLOCKS, total);
+        let blocks = (start..total)
+            .chain(SYSTEM_BLOCKS..start)
+            .filter(|x| block_map.map[x - SYSTEM_BLOCKS] == 0)
+            .take(count)
+            .collect::<Vec<_>>();
+        ensure!(
+            blocks.len() == count,
+            BuildProblem::InvalidData("memory card (not enough free blocks)", Location::current())
+        );
+
+        for (i, block) in blocks.iter().enumerate() {
+            let next = blocks.get(i + 1).map_or(LAST_BLOCK, |x| *x as u16);
+            block_map.map[block - SYSTEM_BLOCKS] = next;
+            self.data[block * BLOCK_SIZE..][..BLOCK_SIZE]
+                .copy_from_slice(&gci.data[i * BLOCK_SIZE..][..BLOCK_SIZE]);
+        }
+        block_map.free_blocks = block_map.free_blocks.saturating_sub(count as u16);
+        block_map.last_allocated = blocks[count - 1] as u16;
+
+        let mut entry = gci.entry.clone();
+        entry.first_block = blocks[0] as u16;
+        directory.entries[index] = Some(entry);
+
+        self.update(directory, block_map)?;
+        Ok(index)
+    }
+
+    /// Replace both copies of the directory and the block allocation table,
+    /// incrementing the update counters and recomputing the checksums.
+    fn update(&mut self, mut directory: Directory, mut block_map: BlockMap) -> Result<()> {
+        directory.update_counter = directory.update_counter.wrapping_add(1);
+        let mut block = Vec::with_capacity(BLOCK_SIZE);
+        directory.to_binary(&mut block)?;
+        directory.checksum = checksum(&block[..0x1FFC]);
+        block[0x1FFC..0x1FFE].copy_from_slice(&directory.checksum[0].to_be_bytes());
+        block[0x1FFE..].copy_from_slice(&directory.checksum[1].to_be_bytes());
+        for index in 1..3 {
+            self.data[index * BLOCK_SIZE..][..BLOCK_SIZE].copy_from_slice(&block);
+        }
+
+        block_map.update_counter = block_map.update_counter.wrapping_add(1);
+        let mut block = Vec::with_capacity(BLOCK_SIZE);
+        block_map.to_binary(&mut block)?;
+        block_map.checksum = checksum(&block[4..]);
+        block[..2].copy_from_slice(&block_map.checksum[0].to_be_bytes());
+        block[2..4].copy_from_slice(&block_map.checksum[1].to_be_bytes());
+        for index in 3..5 {
+            self.data[index * BLOCK_SIZE..][..BLOCK_SIZE].copy_from_slice(&block);
+        }
+
+        self.directories = [directory.clone(), directory];
+        self.block_maps = [block_map.clone(), block_map];
+        Ok(())
+    }
+
+    /// Write memory card image to binary stream.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.u8_array(&self.data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//! [NKit][`crate::nkit`] image detection.
+//!
+//! [NKit][`crate::nkit`] is a format for storing GameCube and Wii images with
+//! the junk data (see [`crate::gcm::junk`]) and unused partitions removed. The
+//! image is otherwise a normal disc image, but with an additional header at
+//! offset `0x200` (inside the game name area of the
+//! [`Boot`][`crate::gcm::Boot`] header) that contains the information needed to
+//! restore the original image.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.nkit.iso")?;
+//!     if let Some(header) = picori::nkit::Header::detect(&mut file)? {
+//!         println!("original size: {:#x}", header.original_size);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::gcm::junk::Junk;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Offset of the [NKit][`crate::nkit`] header from the start of the image.
+pub const HEADER_OFFSET: u64 = 0x200;
+
+/// [NKit][`crate::nkit`] magic number representing the four characters
+/// "NKIT".
+static MAGIC: u32 = 0x4E4B4954;
+
+/// [NKit][`crate::nkit`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Version string, e.g., ` v01`.
+    pub version: [u8; 4],
+
+    /// CRC32 of the original image.
+    pub original_crc32: u32,
+
+    /// CRC32 of the [NKit][`crate::nkit`] image, forced to a fixed value by
+    /// the [NKit][`crate::nkit`] tools.
+    pub nkit_crc32: u32,
+
+    /// Size of the original image.
+    pub original_size: u32,
+
+    /// Game id used to generate the junk data of the original image. This can
+    /// differ from the game id in the [`Boot`][`crate::gcm::Boot`] header.
+    pub junk_id: [u8; 4],
+
+    /// CRC32 of the removed Wii update partition (zero for GameCube images).
+    pub update_partition_crc32: u32,
+}
+
+impl Header {
+    /// Parse [NKit][`crate::nkit`] header. The input is expected to be
+    /// positioned at [`HEADER_OFFSET`].
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let magic = input.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: NKIT", Location::current())
+        );
+
+        let version = input.u8_array::<4>()?;
+        let original_crc32 = input.bu32()?;
+        let nkit_crc32 = input.bu32()?;
+        let original_size = input.bu32()?;
+        let junk_id = input.u8_array::<4>()?;
+        let update_partition_crc32 = input.bu32()?;
+
+        Ok(Self {
+            version,
+            original_crc32,
+            nkit_crc32,
+            original_size,
+            junk_id,
+            update_partition_crc32,
+        })
+    }
+
+    /// Detect if the image is an [NKit][`crate::nkit`] image. Returns the
+    /// header if it is, otherwise [`None`].
+    pub fn detect<D: Parser + Seeker>(input: &mut D) -> Result<Option<Self>> {
+        let base = input.position()?;
+        input.goto(base + HEADER_OFFSET)?;
+        let magic = input.bu32()?;
+        if magic != MAGIC {
+            return Ok(None);
+        }
+
+        input.goto(base + HEADER_OFFSET)?;
+        Ok(Some(Self::from_binary(input)?))
+    }
+
+    /// Create a [junk generator][`Junk`] for regenerating the junk data of the
+    /// original image.
+    pub fn junk(&self, disc: u8) -> Junk { Junk::new(self.junk_id, disc) }
+}
@@ -1,10 +1,77 @@
-use crate::error::ParseProblem;
-use crate::helper::ProblemLocation;
-use crate::helper::{ensure, Parser, Seeker};
-use crate::{Ascii, Result};
+//! [RARC][`crate::rarc`] (`JKRArchive`) archives.
+//!
+//! [RARC][`crate::rarc`] is the archive format used by the JSystem library
+//! found in most first-party GameCube games. Archives are usually stored
+//! [Yaz0][`crate::yaz0`] compressed with the extension `.arc` or `.szs`.
+//!
+//! An archive consists of a header, a node table (one node per directory), a
+//! directory entry table (the children of every node, including `.` and `..`),
+//! a string table, and the file data. [`RarcReader`] exposes the archive as a
+//! directory tree with an API mirroring the [FST][`crate::gcm::Fst`], see
+//! [`RarcReader::files`] and [`RarcReader::find`]. Archives are written with
+//! [`RarcBuilder`], which can also rebuild an existing archive (see
+//! [`RarcBuilder::from_rarc`]). [`RarcReader::extract_to`] extracts an archive
+//! to a directory (optionally with a [metadata sidecar][`METADATA_FILE`]) and
+//! [`pack_dir`] packs such a directory back into an archive.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let file = File::open("archive.arc")?;
+//!     let mut rarc = picori::RarcReader::new(file)?;
+//!     for (path, entry) in rarc.files() {
+//!         println!("{}: {:?}", path.display(), entry);
+//!     }
+//!     let _ = rarc.read("map/map.bmd")?;
+//!     Ok(())
+//! }
+//! ```
+
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::fs;
 use std::panic::Location;
+use std::path::{Path, PathBuf};
+
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::tree::Folder;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Ascii, Result};
+
+/// [RARC][`crate::rarc`] magic number representing the four characters
+/// "RARC".
+pub(crate) const MAGIC: u32 = 0x52415243;
+
+/// Directory entry flag: the entry is a file.
+pub const FLAG_FILE: u8 = 0x01;
+
+/// Directory entry flag: the entry is a directory.
+pub const FLAG_DIRECTORY: u8 = 0x02;
+
+/// Directory entry flag: the file data is compressed.
+pub const FLAG_COMPRESSED: u8 = 0x04;
+
+/// Directory entry flag: the file is preloaded to main memory (MRAM).
+pub const FLAG_PRELOAD_TO_MRAM: u8 = 0x10;
+
+/// Directory entry flag: the file is preloaded to auxiliary memory (ARAM).
+pub const FLAG_PRELOAD_TO_ARAM: u8 = 0x20;
+
+/// Directory entry flag: the file is loaded from the disc on demand.
+pub const FLAG_LOAD_FROM_DVD: u8 = 0x40;
+
+/// Directory entry flag: the file data is [Yaz0][`crate::yaz0`] compressed
+/// (otherwise Yay0 if [`FLAG_COMPRESSED`] is set).
+pub const FLAG_YAZ0: u8 = 0x80;
+
+/// Hash of a file or directory name as stored in the archive.
+pub fn name_hash(name: &str) -> u16 {
+    name.bytes()
+        .fold(0_u16, |hash, x| hash.wrapping_mul(3).wrapping_add(x as u16))
+}
 
 #[derive(Debug, Clone)]
 pub struct NamedHash {
@@ -19,9 +86,7 @@ impl Display for NamedHash {
 }
 
 impl std::hash::Hash for NamedHash {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.hash.hash(state);
-    }
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.hash.hash(state); }
 }
 
 impl PartialEq for NamedHash {
@@ -36,19 +101,75 @@ impl PartialEq for NamedHash {
 
 impl Eq for NamedHash {}
 
+/// [RARC][`crate::rarc`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Size of the file data preloaded to main memory (MRAM).
+    pub mram_size: u32,
+
+    /// Size of the file data preloaded to auxiliary memory (ARAM).
+    pub aram_size: u32,
+
+    /// Size of the file data loaded from the disc on demand.
+    pub dvd_size: u32,
+
+    /// Number of file IDs.
+    pub file_count: u16,
+
+    /// File IDs match the directory entry indices.
+    pub sync_file_ids: bool,
+}
+
+/// Enum variant of a single [RARC][`crate::rarc`] entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    /// Root directory.
+    Root {
+        /// Name of the root directory.
+        name: String,
+    },
+    /// File.
+    File {
+        /// Relative filename.
+        name:   String,
+        /// File ID.
+        id:     u16,
+        /// Flags, e.g., [`FLAG_PRELOAD_TO_MRAM`].
+        flags:  u8,
+        /// File offset from the beginning of the [RARC][`crate::rarc`] file.
+        offset: u64,
+        /// File size.
+        size:   u32,
+    },
+    /// Directory.
+    Directory {
+        /// Relative directory name.
+        name: String,
+        /// `internal`: Node index.
+        node: u32,
+    },
+}
+
 #[derive(Debug, Clone)]
 enum RarcDirectory {
     File {
         /// Name of the file.
-        name: NamedHash,
-        /// Offset of the file in the RARC file. This offset is relative to the start of the RARC file.
+        name:   NamedHash,
+        /// File ID.
+        id:     u16,
+        /// Flags.
+        flags:  u8,
+        /// Offset of the file in the RARC file. This offset is relative to the
+        /// start of the RARC file.
         offset: u64,
         /// Size of the file.
-        size: u32,
+        size:   u32,
     },
     Folder {
         /// Name of the folder.
         name: NamedHash,
+        /// Index of the node of the folder.
+        node: u32,
     },
     CurrentFolder,
     ParentFolder,
@@ -56,17 +177,21 @@ enum RarcDirectory {
 
 #[derive(Debug, Clone)]
 struct RarcNode {
+    /// Identifier of the node, e.g., `ROOT`.
+    pub identifier: [u8; 4],
+    /// Name of the node.
+    pub name:       NamedHash,
     /// Index of first directory.
-    pub index: u32,
+    pub index:      u32,
     /// Number of directories.
-    pub count: u32,
+    pub count:      u32,
 }
 
 pub struct RarcReader<Reader> {
-    reader: Reader,
+    reader:      Reader,
+    header:      Header,
     directories: Vec<RarcDirectory>,
-    nodes: HashMap<NamedHash, RarcNode>,
-    root_node: NamedHash,
+    nodes:       Vec<RarcNode>,
 }
 
 impl<Reader: Parser + Seeker> RarcReader<Reader> {
@@ -74,31 +199,32 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
     pub fn new(mut reader: Reader) -> Result<Self> {
         let base = reader.position()?;
 
-        let magic = reader.u32()?;
+        let magic = reader.bu32()?;
         let _file_length = reader.bu32()?;
         let header_length = reader.bu32()?;
         let file_offset = reader.bu32()?;
         let _file_length = reader.bu32()?;
-        let _ = reader.bu32()?;
-        let _ = reader.bu32()?;
-        let _ = reader.bu32()?;
+        let mram_size = reader.bu32()?;
+        let aram_size = reader.bu32()?;
+        let dvd_size = reader.bu32()?;
         let node_count = reader.bu32()?;
         let node_offset = reader.bu32()?;
         let directory_count = reader.bu32()?;
         let directory_offset = reader.bu32()?;
         let string_table_length = reader.bu32()?;
         let string_table_offset = reader.bu32()?;
-        let _file_count = reader.bu16()?;
-        let _ = reader.bu16()?;
+        let file_count = reader.bu16()?;
+        let sync_file_ids = reader.u8()?;
+        let _ = reader.u8()?;
         let _ = reader.bu32()?;
 
         ensure!(
-            magic == 0x43524152,
+            magic == MAGIC,
             ParseProblem::InvalidMagic("invalid RARC magic", Location::current())
         );
 
         ensure!(
-            node_count < 0x10000,
+            node_count > 0 && node_count < 0x10000,
             ParseProblem::InvalidHeader("invalid node count", Location::current())
         );
 
@@ -107,53 +233,60 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
             ParseProblem::InvalidHeader("invalid directory count", Location::current())
         );
 
+        ensure!(
+            string_table_length < 0x1000000,
+            ParseProblem::InvalidHeader("invalid string table length", Location::current())
+        );
+
         let base = base + header_length as u64;
+        reader.goto(base + string_table_offset as u64)?;
+        let string_table = reader.read_as_vec(string_table_length as usize)?;
+        let name = |offset: u32, hash: u16| -> Result<NamedHash> {
+            ensure!(
+                offset < string_table_length,
+                ParseProblem::InvalidData("invalid string table offset", Location::current())
+            );
+            Ok(NamedHash {
+                name: Ascii::first(&string_table[offset as usize..])?,
+                hash,
+            })
+        };
+
         let directory_base = base + directory_offset as u64;
         let data_base = base + file_offset as u64;
         let mut directories = Vec::with_capacity(directory_count as usize);
         for i in 0..directory_count {
             reader.goto(directory_base + 20 * i as u64)?;
-            let index = reader.bu16()?;
+            let id = reader.bu16()?;
             let name_hash = reader.bu16()?;
-            let _ = reader.bu16()?; // 0x200 for folders, 0x1100 for files
+            let flags = reader.u8()?;
+            let _ = reader.u8()?;
             let name_offset = reader.bu16()?;
             let data_offset = reader.bu32()?;
             let data_length = reader.bu32()?;
             let _ = reader.bu32()?;
 
-            let name = {
-                let offset = string_table_offset as u64;
-                let offset = offset + name_offset as u64;
-                ensure!(
-                    (name_offset as u32) < string_table_length,
-                    ParseProblem::InvalidData(
-                        "invalid string table offset",
-                        std::panic::Location::current()
-                    )
-                );
-                reader.goto(base + offset)?;
-                reader.str::<Ascii>()
-            }?;
-
-            if index == 0xFFFF {
-                if name == "." {
+            let name = name(name_offset as u32, name_hash)?;
+            if flags & FLAG_DIRECTORY != 0 {
+                if name.name == "." {
                     directories.push(RarcDirectory::CurrentFolder);
-                } else if name == ".." {
+                } else if name.name == ".." {
                     directories.push(RarcDirectory::ParentFolder);
                 } else {
+                    ensure!(
+                        data_offset < node_count,
+                        ParseProblem::InvalidData("node index out of bounds", Location::current())
+                    );
                     directories.push(RarcDirectory::Folder {
-                        name: NamedHash {
-                            name,
-                            hash: name_hash,
-                        },
+                        name,
+                        node: data_offset,
                     });
                 }
             } else {
                 directories.push(RarcDirectory::File {
-                    name: NamedHash {
-                        name,
-                        hash: name_hash,
-                    },
+                    name,
+                    id,
+                    flags,
                     offset: data_base + data_offset as u64,
                     size: data_length,
                 });
@@ -161,24 +294,15 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
         }
 
         let node_base = base + node_offset as u64;
-        let mut root_node: Option<NamedHash> = None;
-        let mut nodes = HashMap::with_capacity(node_count as usize);
+        let mut nodes = Vec::with_capacity(node_count as usize);
         for i in 0..node_count {
             reader.goto(node_base + 16 * i as u64)?;
-            let _identifier = reader.bu32()?;
+            let identifier = reader.u8_array::<4>()?;
             let name_offset = reader.bu32()?;
             let name_hash = reader.bu16()?;
             let count = reader.bu16()? as u32;
             let index = reader.bu32()?;
 
-            ensure!(
-                index < directory_count,
-                ParseProblem::InvalidData(
-                    "first directory index out of bounds",
-                    std::panic::Location::current()
-                )
-            );
-
             let last_index = index.checked_add(count);
             ensure!(
                 last_index.is_some() && last_index.unwrap() <= directory_count,
@@ -188,47 +312,32 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
                 )
             );
 
-            let name = {
-                let offset = string_table_offset as u64;
-                let offset = offset + name_offset as u64;
-                ensure!(
-                    (name_offset as u32) < string_table_length,
-                    ParseProblem::InvalidData(
-                        "invalid string table offset",
-                        std::panic::Location::current()
-                    )
-                );
-                reader.goto(base + offset)?;
-                reader.str::<Ascii>()
-            }?;
-
-            // FIXME: this assumes that the root node is the first node in the list
-            if root_node.is_none() {
-                root_node = Some(NamedHash {
-                    name: name.clone(),
-                    hash: name_hash,
-                });
-            }
-
-            let name = NamedHash {
+            let name = name(name_offset, name_hash)?;
+            nodes.push(RarcNode {
+                identifier,
                 name,
-                hash: name_hash,
-            };
-            nodes.insert(name.clone(), RarcNode { index, count });
+                index,
+                count,
+            });
         }
 
-        if let Some(root_node) = root_node {
-            Ok(Self {
-                reader,
-                directories,
-                nodes,
-                root_node,
-            })
-        } else {
-            Err(ParseProblem::InvalidData("no root node", std::panic::Location::current()).into())
-        }
+        Ok(Self {
+            reader,
+            header: Header {
+                mram_size,
+                aram_size,
+                dvd_size,
+                file_count,
+                sync_file_ids: sync_file_ids != 0,
+            },
+            directories,
+            nodes,
+        })
     }
 
+    /// Get the [RARC][`crate::rarc`] header.
+    pub fn header(&self) -> &Header { &self.header }
+
     /// Get the data for a file.
     pub fn file_data(&mut self, offset: u64, size: u32) -> Result<Vec<u8>> {
         self.reader.goto(offset)?;
@@ -239,10 +348,46 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
 
     /// Get a iterator over the nodes in the RARC file.
     pub fn nodes(&self) -> Nodes<'_, Reader> {
-        let root_node = self.root_node.clone();
         Nodes {
-            parent: self,
-            stack: vec![NodeState::Begin(root_node)],
+            parent:  self,
+            stack:   vec![NodeState::Begin(0)],
+            visited: vec![false; self.nodes.len()],
+        }
+    }
+
+    /// Get an iterator over all [`Entry`]s (in depth-first order). The paths
+    /// are relative to the root directory.
+    pub fn files(&self) -> Files<'_, Reader> {
+        Files {
+            parent:  self,
+            stack:   vec![],
+            path:    PathBuf::new(),
+            visited: vec![false; self.nodes.len()],
+            root:    true,
+        }
+    }
+
+    /// Find the [`Entry`] with the given path, e.g., `map/map.bmd`. The
+    /// comparison is case-insensitive and a leading `/` is ignored.
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<Entry> {
+        let path = path.as_ref();
+        let path = path.strip_prefix("/").unwrap_or(path);
+        self.files()
+            .find(|(x, _)| {
+                x.components().count() == path.components().count()
+                    && x.components()
+                        .zip(path.components())
+                        .all(|(a, b)| a.as_os_str().eq_ignore_ascii_case(b.as_os_str()))
+            })
+            .map(|(_, entry)| entry)
+    }
+
+    /// Read the data of the file at `path` (see [`RarcReader::find`]). Returns
+    /// [`None`] if there is no such file.
+    pub fn read<P: AsRef<Path>>(&mut self, path: P) -> Result<Option<Vec<u8>>> {
+        match self.find(path) {
+            Some(Entry::File { offset, size, .. }) => Ok(Some(self.file_data(offset, size)?)),
+            _ => Ok(None),
         }
     }
 }
@@ -255,9 +400,9 @@ pub enum Node {
     DirectoryEnd { name: NamedHash },
     /// A file in the current directory.
     File {
-        name: NamedHash,
+        name:   NamedHash,
         offset: u64,
-        size: u32,
+        size:   u32,
     },
     /// The current directory. This is equivalent to ".".
     CurrentDirectory,
@@ -266,56 +411,966 @@ pub enum Node {
 }
 
 enum NodeState {
-    Begin(NamedHash),
-    End(NamedHash),
-    File(NamedHash, u32),
+    Begin(u32),
+    End(u32),
+    File(u32, u32),
 }
 
 /// An iterator over the nodes in an RARC file.
 pub struct Nodes<'parent, Reader> {
-    parent: &'parent RarcReader<Reader>,
-    stack: Vec<NodeState>,
+    parent:  &'parent RarcReader<Reader>,
+    stack:   Vec<NodeState>,
+    visited: Vec<bool>,
 }
 
 impl<'parent, T: Parser + Seeker> Iterator for Nodes<'parent, T> {
     type Item = Node;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(state) = self.stack.pop() else {
-            return None;
-        };
+        let state = self.stack.pop()?;
+        let nodes = &self.parent.nodes;
 
         match state {
-            NodeState::Begin(name) => {
-                self.stack.push(NodeState::File(name.clone(), 0));
-                Some(Node::DirectoryBegin { name })
+            NodeState::Begin(node) => {
+                self.visited[node as usize] = true;
+                self.stack.push(NodeState::File(node, 0));
+                Some(Node::DirectoryBegin {
+                    name: nodes[node as usize].name.clone(),
+                })
             },
-            NodeState::End(name) => Some(Node::DirectoryEnd { name }),
-            NodeState::File(name, index) => {
-                if let Some(node) = self.parent.nodes.get(&name) {
-                    if index + 1 >= node.count {
-                        self.stack.push(NodeState::End(name.clone()));
-                    } else {
-                        self.stack.push(NodeState::File(name.clone(), index + 1));
+            NodeState::End(node) => Some(Node::DirectoryEnd {
+                name: nodes[node as usize].name.clone(),
+            }),
+            NodeState::File(node, index) => {
+                let entry = &nodes[node as usize];
+                if index >= entry.count {
+                    return Some(Node::DirectoryEnd {
+                        name: entry.name.clone(),
+                    });
+                }
+                if index + 1 >= entry.count {
+                    self.stack.push(NodeState::End(node));
+                } else {
+                    self.stack.push(NodeState::File(node, index + 1));
+                }
+
+                let directory = &self.parent.directories[(entry.index + index) as usize];
+                match directory {
+                    RarcDirectory::CurrentFolder => Some(Node::CurrentDirectory),
+                    RarcDirectory::ParentFolder => Some(Node::ParentDirectory),
+                    RarcDirectory::Folder { node, .. } => {
+                        // skip folders that would loop back to a visited node
+                        if !self.visited[*node as usize] {
+                            self.stack.push(NodeState::Begin(*node));
+                        }
+                        self.next()
+                    },
+                    RarcDirectory::File {
+                        name, offset, size, ..
+                    } => Some(Node::File {
+                        name:   name.clone(),
+                        offset: *offset,
+                        size:   *size,
+                    }),
+                }
+            },
+        }
+    }
+}
+
+/// Iterator over all [`Entry`]s in a [`RarcReader`].
+pub struct Files<'parent, Reader> {
+    parent:  &'parent RarcReader<Reader>,
+    /// Stack of (node index, next child index).
+    stack:   Vec<(u32, u32)>,
+    path:    PathBuf,
+    visited: Vec<bool>,
+    root:    bool,
+}
+
+impl<'parent, T: Parser + Seeker> Iterator for Files<'parent, T> {
+    type Item = (PathBuf, Entry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.root {
+            self.root = false;
+            self.visited[0] = true;
+            self.stack.push((0, 0));
+            return Some((PathBuf::new(), Entry::Root {
+                name: self.parent.nodes[0].name.name.clone(),
+            }));
+        }
+
+        loop {
+            let (node, index) = self.stack.last_mut()?;
+            let entry = &self.parent.nodes[*node as usize];
+            if *index >= entry.count {
+                self.stack.pop();
+                self.path.pop();
+                continue;
+            }
+
+            let directory = &self.parent.directories[(entry.index + *index) as usize];
+            *index += 1;
+            match directory {
+                RarcDirectory::CurrentFolder | RarcDirectory::ParentFolder => continue,
+                RarcDirectory::Folder { name, node } => {
+                    if self.visited[*node as usize] {
+                        continue;
+                    }
+                    self.visited[*node as usize] = true;
+                    self.stack.push((*node, 0));
+                    self.path.push(&name.name);
+                    return Some((self.path.clone(), Entry::Directory {
+                        name: name.name.clone(),
+                        node: *node,
+                    }));
+                },
+                RarcDirectory::File {
+                    name,
+                    id,
+                    flags,
+                    offset,
+                    size,
+                } => {
+                    return Some((self.path.join(&name.name), Entry::File {
+                        name:   name.name.clone(),
+                        id:     *id,
+                        flags:  *flags,
+                        offset: *offset,
+                        size:   *size,
+                    }));
+                },
+            }
+        }
+    }
+}
+
+/// Order of the nodes (directories) in an archive built by [`RarcBuilder`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NodeOrder {
+    /// Nodes are ordered depth-first, i.e., every directory is directly
+    /// followed by its subdirectories.
+    #[default]
+    DepthFirst,
+
+    /// Nodes are ordered breadth-first, i.e., all directories of one level are
+    /// followed by the directories of the next level.
+    BreadthFirst,
+}
+
+/// File in a [`RarcBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RarcFile {
+    /// Name of the file.
+    pub name:  String,
+    /// File ID. If [`None`] (or file IDs are synchronized), the ID is assigned
+    /// when building.
+    pub id:    Option<u16>,
+    /// Flags, e.g., [`FLAG_PRELOAD_TO_MRAM`]. [`FLAG_FILE`] is always set.
+    pub flags: u8,
+    /// File data (stored as is, i.e., already compressed if
+    /// [`FLAG_COMPRESSED`] is set).
+    pub data:  Vec<u8>,
+}
+
+/// Directory in a [`RarcBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RarcFolder {
+    /// Name of the directory.
+    pub name:       String,
+    /// Node identifier. If [`None`], the identifier is `ROOT` for the root
+    /// directory and the first four characters of the name (in upper case)
+    /// otherwise.
+    pub identifier: Option<[u8; 4]>,
+    /// Files and subdirectories (in the order they are stored).
+    pub children:   Vec<RarcItem>,
+}
+
+/// Child of a [`RarcFolder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RarcItem {
+    /// File.
+    File(RarcFile),
+    /// Directory.
+    Folder(RarcFolder),
+}
+
+impl RarcItem {
+    /// Name of the file or directory.
+    pub fn name(&self) -> &str {
+        match self {
+            RarcItem::File(file) => &file.name,
+            RarcItem::Folder(folder) => &folder.name,
+        }
+    }
+}
+
+impl RarcFolder {
+    /// Create an empty directory.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name:       name.into(),
+            identifier: None,
+            children:   Vec::new(),
+        }
+    }
+
+    fn identifier(&self, root: bool) -> [u8; 4] {
+        if let Some(identifier) = self.identifier {
+            identifier
+        } else if root {
+            *b"ROOT"
+        } else {
+            let mut identifier = *b"    ";
+            for (x, c) in identifier.iter_mut().zip(self.name.bytes()) {
+                *x = c.to_ascii_uppercase();
+            }
+            identifier
+        }
+    }
+
+    /// Get the file or directory at `path` (relative to this directory). The
+    /// comparison is case-insensitive.
+    pub fn get_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut RarcItem> {
+        self.find_mut(path.as_ref())
+    }
+}
+
+impl Folder for RarcFolder {
+    type File = RarcFile;
+    type Item = RarcItem;
+
+    fn children_mut(&mut self) -> &mut Vec<RarcItem> { &mut self.children }
+
+    fn item_name(item: &RarcItem) -> &str { item.name() }
+
+    fn set_item_name(item: &mut RarcItem, name: String) {
+        match item {
+            RarcItem::File(file) => file.name = name,
+            RarcItem::Folder(folder) => folder.name = name,
+        }
+    }
+
+    fn folder_item(name: &str) -> RarcItem { RarcItem::Folder(RarcFolder::new(name)) }
+
+    fn as_folder(item: &mut RarcItem) -> Option<&mut RarcFolder> {
+        match item {
+            RarcItem::Folder(folder) => Some(folder),
+            RarcItem::File(_) => None,
+        }
+    }
+
+    fn as_file(item: &mut RarcItem) -> Option<&mut RarcFile> {
+        match item {
+            RarcItem::File(file) => Some(file),
+            RarcItem::Folder(_) => None,
+        }
+    }
+}
+
+/// [RARC][`crate::rarc`] archive builder.
+///
+/// File data is laid out in three sections: files preloaded to MRAM, files
+/// preloaded to ARAM, and files loaded from the disc. Each file is aligned to
+/// 32 bytes. Within a node, the directory entries are stored in the order of
+/// [`RarcFolder::children`] followed by `.` and `..`.
+#[derive(Debug, Clone)]
+pub struct RarcBuilder {
+    root:          RarcFolder,
+    sync_file_ids: bool,
+    node_order:    NodeOrder,
+}
+
+/// Alignment of the sections and the file data.
+const ALIGNMENT: usize = 0x20;
+
+/// Size of the header (including the info block).
+const HEADER_SIZE: usize = 0x40;
+
+/// Flattened node used when building.
+struct BuildNode<'a> {
+    folder: &'a RarcFolder,
+    parent: Option<usize>,
+}
+
+impl RarcBuilder {
+    /// Create a builder for an empty archive with the root directory `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            root:          RarcFolder::new(name),
+            sync_file_ids: true,
+            node_order:    NodeOrder::default(),
+        }
+    }
+
+    /// Create a builder containing all files and directories of `reader`. The
+    /// node order, node identifiers, file IDs, and flags are kept, i.e.,
+    /// building the unmodified archive results in the original layout (as long
+    /// as it follows the layout described in [`RarcBuilder`]).
+    pub fn from_rarc<R: Parser + Seeker>(reader: &mut RarcReader<R>) -> Result<Self> {
+        fn folder<R: Parser + Seeker>(
+            reader: &mut RarcReader<R>,
+            node: u32,
+            visited: &mut Vec<u32>,
+        ) -> Result<RarcFolder> {
+            visited.push(node);
+            let entry = reader.nodes[node as usize].clone();
+            let mut result = RarcFolder {
+                name:       entry.name.name.clone(),
+                identifier: Some(entry.identifier),
+                children:   Vec::new(),
+            };
+            for i in entry.index..entry.index + entry.count {
+                match reader.directories[i as usize].clone() {
+                    RarcDirectory::File {
+                        name,
+                        id,
+                        flags,
+                        offset,
+                        size,
+                    } => {
+                        let data = reader.file_data(offset, size)?;
+                        result.children.push(RarcItem::File(RarcFile {
+                            name: name.name,
+                            id: Some(id),
+                            flags,
+                            data,
+                        }));
+                    },
+                    RarcDirectory::Folder { node, .. } if !visited.contains(&node) => {
+                        let child = folder(reader, node, visited)?;
+                        result.children.push(RarcItem::Folder(child));
+                    },
+                    _ => {},
+                }
+            }
+            Ok(result)
+        }
+
+        let mut visited = Vec::new();
+        let root = folder(reader, 0, &mut visited)?;
+        let mut builder = Self {
+            root,
+            sync_file_ids: reader.header.sync_file_ids,
+            node_order: NodeOrder::DepthFirst,
+        };
+
+        // `visited` contains the original node indices in depth-first order, use
+        // the breadth-first order if it matches the original archive instead.
+        if visited.iter().copied().eq(0..visited.len() as u32) {
+            return Ok(builder);
+        }
+        let depth_first = builder
+            .flatten()
+            .iter()
+            .map(|x| x.folder as *const RarcFolder)
+            .collect::<Vec<_>>();
+        let original = depth_first
+            .into_iter()
+            .zip(visited)
+            .collect::<HashMap<_, _>>();
+        builder.node_order = NodeOrder::BreadthFirst;
+        let breadth_first = builder
+            .flatten()
+            .iter()
+            .map(|x| original[&(x.folder as *const RarcFolder)])
+            .collect::<Vec<_>>();
+        if !breadth_first
+            .iter()
+            .copied()
+            .eq(0..breadth_first.len() as u32)
+        {
+            builder.node_order = NodeOrder::DepthFirst;
+        }
+
+        Ok(builder)
+    }
+
+    /// Get the root directory.
+    pub fn root(&self) -> &RarcFolder { &self.root }
+
+    /// Get a mutable reference to the root directory.
+    pub fn root_mut(&mut self) -> &mut RarcFolder { &mut self.root }
+
+    /// Use the directory entry indices as file IDs (default: `true`).
+    pub fn sync_file_ids(&mut self, sync_file_ids: bool) -> &mut Self {
+        self.sync_file_ids = sync_file_ids;
+        self
+    }
+
+    /// Set the order of the nodes (default: [`NodeOrder::DepthFirst`]).
+    pub fn node_order(&mut self, node_order: NodeOrder) -> &mut Self {
+        self.node_order = node_order;
+        self
+    }
+
+    /// Get the file or directory at `path` (see [`RarcFolder::get_mut`]).
+    pub fn get_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut RarcItem> {
+        self.root.get_mut(path)
+    }
+
+    /// Add the directory at `path` (relative to the root directory), creating
+    /// missing parent directories. Returns the existing directory if there is
+    /// one.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, path: P) -> &mut RarcFolder {
+        self.root.add_directory(path.as_ref())
+    }
+
+    /// Replace the data of the existing file at `path`, keeping its ID and
+    /// flags.
+    pub fn replace_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        data: Vec<u8>,
+    ) -> Result<&mut RarcFile> {
+        let file = self.root.file_mut(path.as_ref())?;
+        file.data = data;
+        Ok(file)
+    }
+
+    /// Remove the file or directory (including its contents) at `path`.
+    /// Returns the removed entry.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<RarcItem> {
+        self.root.remove(path.as_ref())
+    }
+
+    /// Rename the file or directory at `path` to `name` (keeping its position
+    /// in the directory).
+    pub fn rename<P: AsRef<Path>>(&mut self, path: P, name: &str) -> Result<()> {
+        self.root.rename(path.as_ref(), name)
+    }
+
+    /// Add (or replace) the file at `path` (relative to the root directory),
+    /// creating missing directories. The file is preloaded to MRAM unless the
+    /// flags are changed with the returned reference.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, data: Vec<u8>) -> &mut RarcFile {
+        self.root.add_file(path.as_ref(), |name| {
+            RarcItem::File(RarcFile {
+                name,
+                id: None,
+                flags: FLAG_FILE | FLAG_PRELOAD_TO_MRAM,
+                data,
+            })
+        })
+    }
+
+    /// Flatten the directory tree into nodes (in node order).
+    fn flatten(&self) -> Vec<BuildNode<'_>> {
+        let mut nodes = vec![BuildNode {
+            folder: &self.root,
+            parent: None,
+        }];
+        match self.node_order {
+            NodeOrder::DepthFirst => {
+                fn visit<'a>(nodes: &mut Vec<BuildNode<'a>>, index: usize) {
+                    let folder = nodes[index].folder;
+                    for child in folder.children.iter() {
+                        if let RarcItem::Folder(child) = child {
+                            nodes.push(BuildNode {
+                                folder: child,
+                                parent: Some(index),
+                            });
+                            visit(nodes, nodes.len() - 1);
+                        }
                     }
-                    let directory = &self.parent.directories[(node.index + index) as usize];
-                    match directory {
-                        RarcDirectory::CurrentFolder => Some(Node::CurrentDirectory),
-                        RarcDirectory::ParentFolder => Some(Node::ParentDirectory),
-                        RarcDirectory::Folder { name } => {
-                            self.stack.push(NodeState::Begin(name.clone()));
-                            self.next()
-                        },
-                        RarcDirectory::File { name, offset, size } => Some(Node::File {
-                            name: name.clone(),
-                            offset: *offset,
-                            size: *size,
-                        }),
+                }
+                visit(&mut nodes, 0);
+            },
+            NodeOrder::BreadthFirst => {
+                let mut index = 0;
+                while index < nodes.len() {
+                    let folder = nodes[index].folder;
+                    for child in folder.children.iter() {
+                        if let RarcItem::Folder(child) = child {
+                            nodes.push(BuildNode {
+                                folder: child,
+                                parent: Some(index),
+                            });
+                        }
                     }
-                } else {
-                    None
+                    index += 1;
                 }
             },
         }
+        nodes
     }
+
+    /// Build the archive and write it to `output`.
+    pub fn build<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let nodes = self.flatten();
+
+        // string table, "." and ".." are always first
+        let mut strings = Vec::new();
+        let mut string_offsets = HashMap::new();
+        let mut string = |name: &str| -> Result<u32> {
+            ensure!(
+                name.is_ascii() && !name.contains('\0'),
+                BuildProblem::InvalidData("invalid name", Location::current())
+            );
+            Ok(*string_offsets.entry(name.to_string()).or_insert_with(|| {
+                let offset = strings.len() as u32;
+                strings.extend_from_slice(name.as_bytes());
+                strings.push(0);
+                offset
+            }))
+        };
+        string(".")?;
+        string("..")?;
+
+        // first directory entry index of every node
+        let mut first_index = Vec::with_capacity(nodes.len());
+        let mut count = 0_usize;
+        for node in nodes.iter() {
+            first_index.push(count);
+            count += node.folder.children.len() + 2;
+        }
+        ensure!(
+            count < 0xFFFF,
+            BuildProblem::InvalidData("too many directory entries", Location::current())
+        );
+
+        // node index of every child directory (nodes of a parent are in child
+        // order in both node orders)
+        let mut child_nodes = vec![Vec::new(); nodes.len()];
+        for (i, node) in nodes.iter().enumerate().skip(1) {
+            child_nodes[node.parent.unwrap()].push(i as u32);
+        }
+
+        // file data sections: MRAM, ARAM, and DVD
+        let mut sections = [Vec::new(), Vec::new(), Vec::new()];
+        let section = |flags: u8| {
+            if flags & FLAG_PRELOAD_TO_MRAM != 0 {
+                0
+            } else if flags & FLAG_PRELOAD_TO_ARAM != 0 {
+                1
+            } else {
+                2
+            }
+        };
+        for node in nodes.iter() {
+            for child in node.folder.children.iter() {
+                if let RarcItem::File(file) = child {
+                    sections[section(file.flags)].push(file);
+                }
+            }
+        }
+        let mut data_offsets = HashMap::new();
+        let mut data_size = 0_usize;
+        let mut section_sizes = [0_usize; 3];
+        for (i, files) in sections.iter().enumerate() {
+            let start = data_size;
+            for file in files.iter() {
+                data_offsets.insert(*file as *const RarcFile, data_size);
+                data_size = align(data_size + file.data.len());
+            }
+            section_sizes[i] = data_size - start;
+        }
+        ensure!(
+            data_size <= u32::MAX as usize,
+            BuildProblem::InvalidData("file data too large", Location::current())
+        );
+
+        // node table
+        let mut node_table = Vec::new();
+        let mut node_names = Vec::with_capacity(nodes.len());
+        for (i, node) in nodes.iter().enumerate() {
+            let name = &node.folder.name;
+            node_names.push(string(name)?);
+            node_table.extend_from_slice(&node.folder.identifier(i == 0));
+            node_table.extend_from_slice(&node_names[i].to_be_bytes());
+            node_table.extend_from_slice(&name_hash(name).to_be_bytes());
+            node_table.extend_from_slice(&((node.folder.children.len() + 2) as u16).to_be_bytes());
+            node_table.extend_from_slice(&(first_index[i] as u32).to_be_bytes());
+        }
+
+        // directory entry table
+        let mut directory_table = Vec::new();
+        let mut entry = |id: u16, name: u32, hash: u16, flags: u8, data: u32, size: u32| {
+            directory_table.extend_from_slice(&id.to_be_bytes());
+            directory_table.extend_from_slice(&hash.to_be_bytes());
+            directory_table.extend_from_slice(&[flags, 0]);
+            directory_table.extend_from_slice(&(name as u16).to_be_bytes());
+            directory_table.extend_from_slice(&data.to_be_bytes());
+            directory_table.extend_from_slice(&size.to_be_bytes());
+            directory_table.extend_from_slice(&[0; 4]);
+        };
+        let mut file_count = 0_u16;
+        for (i, node) in nodes.iter().enumerate() {
+            let mut subdirectories = child_nodes[i].iter();
+            for (j, child) in node.folder.children.iter().enumerate() {
+                match child {
+                    RarcItem::File(file) => {
+                        let id = match (self.sync_file_ids, file.id) {
+                            (false, Some(id)) => id,
+                            (false, None) => file_count,
+                            (true, _) => (first_index[i] + j) as u16,
+                        };
+                        file_count += 1;
+                        entry(
+                            id,
+                            string(&file.name)?,
+                            name_hash(&file.name),
+                            file.flags | FLAG_FILE,
+                            data_offsets[&(file as *const RarcFile)] as u32,
+                            file.data.len() as u32,
+                        );
+                    },
+                    RarcItem::Folder(folder) => {
+                        let node = *subdirectories.next().unwrap();
+                        entry(
+                            0xFFFF,
+                            string(&folder.name)?,
+                            name_hash(&folder.name),
+                            FLAG_DIRECTORY,
+                            node,
+                            0x10,
+                        );
+                    },
+                }
+            }
+            entry(0xFFFF, 0, name_hash("."), FLAG_DIRECTORY, i as u32, 0x10);
+            let parent = node.parent.map(|x| x as u32).unwrap_or(u32::MAX);
+            entry(0xFFFF, 2, name_hash(".."), FLAG_DIRECTORY, parent, 0x10);
+        }
+        ensure!(
+            strings.len() <= 0x10000,
+            BuildProblem::InvalidData("string table too large", Location::current())
+        );
+        if self.sync_file_ids {
+            file_count = count as u16;
+        }
+
+        let node_offset = HEADER_SIZE;
+        let directory_offset = align(node_offset + node_table.len());
+        let string_offset = align(directory_offset + directory_table.len());
+        let data_offset = align(string_offset + strings.len());
+        let total_size = data_offset + data_size;
+        ensure!(
+            total_size <= u32::MAX as usize,
+            BuildProblem::InvalidData("archive too large", Location::current())
+        );
+
+        // header (offsets in the info block are relative to the info block)
+        let info = 0x20;
+        output.bu32(MAGIC)?;
+        output.bu32(total_size as u32)?;
+        output.bu32(info as u32)?;
+        output.bu32((data_offset - info) as u32)?;
+        output.bu32(data_size as u32)?;
+        output.bu32(section_sizes[0] as u32)?;
+        output.bu32(section_sizes[1] as u32)?;
+        output.bu32(section_sizes[2] as u32)?;
+        output.bu32(nodes.len() as u32)?;
+        output.bu32((node_offset - info) as u32)?;
+        output.bu32(count as u32)?;
+        output.bu32((directory_offset - info) as u32)?;
+        output.bu32((data_offset - string_offset) as u32)?;
+        output.bu32((string_offset - info) as u32)?;
+        output.bu16(file_count)?;
+        output.u8(self.sync_file_ids as u8)?;
+        output.u8_array(&[0; 5])?;
+
+        let pad = |output: &mut W, size: usize| output.u8_array(&vec![0; align(size) - size]);
+        output.u8_array(&node_table)?;
+        pad(output, node_table.len())?;
+        output.u8_array(&directory_table)?;
+        pad(output, directory_table.len())?;
+        output.u8_array(&strings)?;
+        pad(output, strings.len())?;
+        for file in sections.iter().flatten() {
+            output.u8_array(&file.data)?;
+            pad(output, file.data.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn align(value: usize) -> usize { (value + ALIGNMENT - 1) & !(ALIGNMENT - 1) }
+
+/// Name of the metadata sidecar written by [`RarcReader::extract_to`]. The
+/// sidecar stores everything that is not part of the directory tree (the root
+/// name, node order and identifiers, file IDs, flags, and the order of the
+/// entries) so the archive can be rebuilt identically.
+pub const METADATA_FILE: &str = ".rarc";
+
+/// Check that `name` is a single normal path component, i.e., it can not
+/// escape the directory it is extracted to.
+fn is_safe_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(std::path::Component::Normal(x)) if x == name)
+        && components.next().is_none()
+}
+
+impl<Reader: Parser + Seeker> RarcReader<Reader> {
+    /// Extract all files and directories to the directory `path`. If
+    /// `metadata` is `true`, the sidecar [`METADATA_FILE`] is written to
+    /// `path` as well.
+    pub fn extract_to<P: AsRef<Path>>(&mut self, path: P, metadata: bool) -> Result<()> {
+        let builder = RarcBuilder::from_rarc(self)?;
+        let root = path.as_ref();
+        fs::create_dir_all(root)?;
+
+        let mut lines = vec![
+            format!(
+                "root {} {}",
+                hex_identifier(&builder.root.identifier(true)),
+                builder.root.name
+            ),
+            match builder.node_order {
+                NodeOrder::DepthFirst => "order depth-first".to_string(),
+                NodeOrder::BreadthFirst => "order breadth-first".to_string(),
+            },
+            format!("sync-file-ids {}", builder.sync_file_ids),
+        ];
+        extract_folder(&builder.root, root, Path::new(""), &mut lines)?;
+
+        if metadata {
+            let mut text = lines.join("\n");
+            text.push('\n');
+            fs::write(root.join(METADATA_FILE), text)?;
+        }
+        Ok(())
+    }
+}
+
+fn hex_identifier(identifier: &[u8; 4]) -> String {
+    identifier.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+fn extract_folder(
+    folder: &RarcFolder,
+    root: &Path,
+    path: &Path,
+    lines: &mut Vec<String>,
+) -> Result<()> {
+    for child in folder.children.iter() {
+        let name = match child {
+            RarcItem::File(file) => &file.name,
+            RarcItem::Folder(folder) => &folder.name,
+        };
+        ensure!(
+            is_safe_name(name),
+            ParseProblem::InvalidData("invalid file name", Location::current())
+        );
+
+        let path = path.join(name);
+        let display = path.to_string_lossy().replace('\\', "/");
+        match child {
+            RarcItem::File(file) => {
+                fs::write(root.join(&path), &file.data)?;
+                let id = file
+                    .id
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                lines.push(format!("file {} {:#04x} {}", id, file.flags, display));
+            },
+            RarcItem::Folder(child) => {
+                fs::create_dir_all(root.join(&path))?;
+                let identifier = hex_identifier(&child.identifier(false));
+                lines.push(format!("dir {} {}", identifier, display));
+                extract_folder(child, root, &path, lines)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Options for [`pack_dir`] and [`RarcBuilder::from_dir`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    metadata: bool,
+    flags:    u8,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self {
+            metadata: true,
+            flags:    FLAG_FILE | FLAG_PRELOAD_TO_MRAM,
+        }
+    }
+}
+
+impl PackOptions {
+    /// Use the sidecar [`METADATA_FILE`] if it exists (default: `true`).
+    pub fn metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Flags of files not listed in the sidecar (default:
+    /// [`FLAG_FILE`] | [`FLAG_PRELOAD_TO_MRAM`]).
+    pub fn flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// Parsed metadata sidecar (see [`METADATA_FILE`]). Paths are relative to the
+/// root directory and use `/` as separator.
+#[derive(Default)]
+struct Sidecar {
+    root:          Option<([u8; 4], String)>,
+    node_order:    Option<NodeOrder>,
+    sync_file_ids: Option<bool>,
+    directories:   HashMap<String, [u8; 4]>,
+    files:         HashMap<String, (Option<u16>, u8)>,
+    order:         HashMap<String, usize>,
+}
+
+impl Sidecar {
+    fn parse(text: &str) -> Result<Self> {
+        let invalid = || ParseProblem::InvalidData("invalid metadata", Location::current());
+        let identifier = |hex: &str| -> Result<[u8; 4]> {
+            let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+            ensure!(hex.len() == 8, invalid());
+            Ok(value.to_be_bytes())
+        };
+
+        let mut sidecar = Sidecar::default();
+        for line in text
+            .lines()
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+        {
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+            match key {
+                "root" => {
+                    let (hex, name) = value.split_once(' ').ok_or_else(invalid)?;
+                    sidecar.root = Some((identifier(hex)?, name.to_string()));
+                },
+                "order" => {
+                    sidecar.node_order = Some(match value {
+                        "depth-first" => NodeOrder::DepthFirst,
+                        "breadth-first" => NodeOrder::BreadthFirst,
+                        _ => return Err(invalid().into()),
+                    })
+                },
+                "sync-file-ids" => {
+                    sidecar.sync_file_ids = Some(value.parse().map_err(|_| invalid())?);
+                },
+                "dir" => {
+                    let (hex, path) = value.split_once(' ').ok_or_else(invalid)?;
+                    sidecar
+                        .directories
+                        .insert(path.to_string(), identifier(hex)?);
+                    sidecar.order.insert(path.to_string(), sidecar.order.len());
+                },
+                "file" => {
+                    let (id, value) = value.split_once(' ').ok_or_else(invalid)?;
+                    let (flags, path) = value.split_once(' ').ok_or_else(invalid)?;
+                    let id = match id {
+                        "-" => None,
+                        id => Some(id.parse().map_err(|_| invalid())?),
+                    };
+                    let flags = u8::from_str_radix(flags.trim_start_matches("0x"), 16)
+                        .map_err(|_| invalid())?;
+                    sidecar.files.insert(path.to_string(), (id, flags));
+                    sidecar.order.insert(path.to_string(), sidecar.order.len());
+                },
+                _ => return Err(invalid().into()),
+            }
+        }
+        Ok(sidecar)
+    }
+}
+
+impl RarcBuilder {
+    /// Create a builder containing all files and directories in the directory
+    /// `path`, e.g., a directory written by [`RarcReader::extract_to`]. If the
+    /// sidecar [`METADATA_FILE`] exists (and is enabled in `options`), the
+    /// root name, node order and identifiers, file IDs, flags, and the order
+    /// of the entries are restored from it. Otherwise, the root directory is
+    /// named after `path` and the entries are sorted by name.
+    pub fn from_dir<P: AsRef<Path>>(path: P, options: PackOptions) -> Result<Self> {
+        let root = path.as_ref();
+        let sidecar_path = root.join(METADATA_FILE);
+        let sidecar = if options.metadata && sidecar_path.is_file() {
+            Sidecar::parse(&fs::read_to_string(sidecar_path)?)?
+        } else {
+            Sidecar::default()
+        };
+
+        let (identifier, name) = match &sidecar.root {
+            Some((identifier, name)) => (Some(*identifier), name.clone()),
+            None => (
+                None,
+                root.file_name()
+                    .map(|x| x.to_string_lossy().to_lowercase())
+                    .unwrap_or_else(|| "archive".to_string()),
+            ),
+        };
+        let mut folder = RarcFolder {
+            name,
+            identifier,
+            children: Vec::new(),
+        };
+        load_folder(&mut folder, root, "", &sidecar, &options)?;
+
+        Ok(Self {
+            root:          folder,
+            sync_file_ids: sidecar.sync_file_ids.unwrap_or(true),
+            node_order:    sidecar.node_order.unwrap_or_default(),
+        })
+    }
+}
+
+fn load_folder(
+    folder: &mut RarcFolder,
+    directory: &Path,
+    path: &str,
+    sidecar: &Sidecar,
+    options: &PackOptions,
+) -> Result<()> {
+    let join = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", path, name)
+        }
+    };
+
+    let mut entries = fs::read_dir(directory)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.retain(|x| !path.is_empty() || x.file_name() != METADATA_FILE);
+    entries.sort_by_cached_key(|x| {
+        let name = x.file_name().to_string_lossy().into_owned();
+        let order = sidecar.order.get(&join(&name)).copied();
+        (order.unwrap_or(usize::MAX), name.to_lowercase())
+    });
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child = join(&name);
+        if entry.metadata()?.is_dir() {
+            let mut child_folder = RarcFolder {
+                name,
+                identifier: sidecar.directories.get(&child).copied(),
+                children: Vec::new(),
+            };
+            load_folder(&mut child_folder, &entry.path(), &child, sidecar, options)?;
+            folder.children.push(RarcItem::Folder(child_folder));
+        } else {
+            let (id, flags) = sidecar
+                .files
+                .get(&child)
+                .copied()
+                .unwrap_or((None, options.flags));
+            folder.children.push(RarcItem::File(RarcFile {
+                name,
+                id,
+                flags,
+                data: fs::read(entry.path())?,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Pack the directory `path` into a [RARC][`crate::rarc`] archive (see
+/// [`RarcBuilder::from_dir`]).
+pub fn pack_dir<P: AsRef<Path>>(path: P, options: PackOptions) -> Result<Vec<u8>> {
+    let mut output = std::io::Cursor::new(Vec::new());
+    RarcBuilder::from_dir(path, options)?.build(&mut output)?;
+    Ok(output.into_inner())
 }
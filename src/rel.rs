@@ -26,6 +26,7 @@ use crate::Result;
 
 /// `.rel` file object.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rel {
     /// The module number. Must be unique per `.rel` file.
     pub module: u32,
@@ -63,6 +64,7 @@ pub struct Rel {
 
 /// Relocatable module section.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Section {
     /// Offset in the `.rel` file.
     pub offset:     u32,
@@ -73,11 +75,13 @@ pub struct Section {
     /// Unknown flag.
     pub unknown:    bool,
     /// Section data.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data:       Vec<u8>,
 }
 
 /// Import kind.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImportKind {
     /// No-op import.
     None,
@@ -111,6 +115,7 @@ pub enum ImportKind {
 
 /// Import.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Import {
     /// Kind of the import.
     pub kind:    ImportKind,
@@ -124,6 +129,7 @@ pub struct Import {
 
 /// Import table.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImportTable {
     /// Import table for module.
     pub module:  u32,
@@ -134,6 +140,7 @@ pub struct ImportTable {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A symbol reference.
 pub struct Symbol {
     /// Section where the symbol is located.
@@ -144,6 +151,7 @@ pub struct Symbol {
 
 /// Reference to section and offset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SectionOffset {
     /// Section.
     pub section: u32,
@@ -153,6 +161,7 @@ pub struct SectionOffset {
 
 /// Relocation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Relocation {
     /// Relocation kind. This is the same as the import kind. But `Dolphin*` are
     /// not used for relocations.
@@ -252,7 +261,7 @@ impl Rel {
     }
 
     /// Relocation iterator.
-    pub fn relocations(&self) -> RelocationIterator {
+    pub fn relocations(&self) -> RelocationIterator<'_> {
         RelocationIterator {
             rel:     self,
             table:   0,
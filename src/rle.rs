@@ -0,0 +1,205 @@
+//! Run-length encoding (Nintendo compression type `0x30`).
+//!
+//! The data starts with a little-endian header holding the type (`0x30`) in
+//! the lowest byte and the decompressed size in the upper 24 bits (zero means
+//! the size follows as a separate 32-bit value). Each block starts with a
+//! flag byte: if the highest bit is set, the next byte is repeated
+//! `(flag & 0x7F) + 3` times, otherwise `(flag & 0x7F) + 1` bytes are copied.
+//!
+//! ## Example
+//!
+//! ```
+//! # use picori::Result;
+//! use picori::rle;
+//!
+//! fn main() -> Result<()> {
+//!     let data = b"aaaaaaaaaaaabcdef".to_vec();
+//!     let compressed = rle::compress(&data);
+//!     let mut reader = std::io::Cursor::new(compressed);
+//!     let header = rle::Header::from_binary(&mut reader)?;
+//!     assert_eq!(rle::decompress(&mut reader, &header)?, data);
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::compression::{Compress, Decompress};
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// RLE type byte of the header.
+pub(crate) const ID: u8 = 0x30;
+
+/// Shortest run.
+const MIN_RUN: usize = 3;
+
+/// Longest run.
+const MAX_RUN: usize = 0x7F + MIN_RUN;
+
+/// Longest sequence of copied bytes.
+const MAX_COPY: usize = 0x80;
+
+/// RLE header.
+pub struct Header {
+    /// Type byte (`0x30`).
+    pub id: u8,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+}
+
+impl Header {
+    /// Reads a RLE header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        let value = input.lu32()?;
+        let decompressed_size = match value >> 8 {
+            0 => input.lu32()?,
+            size => size,
+        };
+        Ok(Header {
+            id: value as u8,
+            decompressed_size,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.id == ID }
+}
+
+/// Check if the given data is RLE compressed by looking at the type byte of
+/// the header.
+pub fn is_rle<D: Parser + Seeker>(input: &mut D) -> bool {
+    let mut check = || -> Result<bool> {
+        let base = input.position()?;
+        let header = Header::from_binary(input)?;
+        input.goto(base)?;
+        Ok(header.is_valid())
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Decompresses the data into a new allocated [`Vec`]. `input` must be
+/// positioned directly after the `header`.
+pub fn decompress<D: Parser + Seeker>(input: &mut D, header: &Header) -> Result<Vec<u8>> {
+    let mut output = vec![0; header.decompressed_size as usize];
+    decompress_into(input, header, output.as_mut_slice())?;
+    Ok(output)
+}
+
+/// Decompresses the data into the beginning of the given buffer, which must
+/// be large enough to hold the decompressed data. `input` must be positioned
+/// directly after the `header`. Returns the number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(
+    input: &mut D,
+    header: &Header,
+    destination: &mut [u8],
+) -> Result<usize> {
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid type", Location::current())
+    );
+
+    let written = header.decompressed_size as usize;
+    ensure!(
+        destination.len() >= written,
+        InvalidDecompressedSize(Location::current())
+    );
+    let destination = &mut destination[..written];
+
+    let size = destination.len();
+    let mut dest = 0;
+    while dest < size {
+        let flag = input.u8()?;
+        let run = flag & 0x80 != 0;
+        let length = (flag & 0x7F) as usize + if run { MIN_RUN } else { 1 };
+        ensure!(
+            dest + length <= size,
+            InvalidDecompressedSize(Location::current())
+        );
+
+        let block = &mut destination[dest..dest + length];
+        if run {
+            block.fill(input.u8()?);
+        } else {
+            input.read_into(block)?;
+        }
+        dest += length;
+    }
+
+    Ok(written)
+}
+
+/// Compresses `data` (including the header) into a new allocated [`Vec`].
+/// The output is padded to a multiple of four bytes.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() + data.len() / MAX_COPY + 8);
+    if data.is_empty() || data.len() > 0xFFFFFF {
+        output.extend_from_slice(&(ID as u32).to_le_bytes());
+        output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    } else {
+        output.extend_from_slice(&(ID as u32 | (data.len() as u32) << 8).to_le_bytes());
+    }
+
+    // bytes not part of a run are collected and copied in blocks
+    let mut copy = 0;
+    let mut position = 0;
+    while position < data.len() {
+        let byte = data[position];
+        let run = data[position..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|x| **x == byte)
+            .count();
+        if run >= MIN_RUN {
+            flush(&mut output, &data[copy..position]);
+            output.push(0x80 | (run - MIN_RUN) as u8);
+            output.push(byte);
+            position += run;
+            copy = position;
+        } else {
+            position += 1;
+        }
+    }
+    flush(&mut output, &data[copy..]);
+
+    output.resize(output.len().next_multiple_of(4), 0);
+    output
+}
+
+/// Emit blocks that copy `data`.
+fn flush(output: &mut Vec<u8>, data: &[u8]) {
+    for chunk in data.chunks(MAX_COPY) {
+        output.push((chunk.len() - 1) as u8);
+        output.extend_from_slice(chunk);
+    }
+}
+
+/// RLE codec, see [`crate::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rle;
+
+impl Decompress for Rle {
+    const NAME: &'static str = "RLE";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_rle(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let header = Header::from_binary(input)?;
+        decompress(input, &header)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let header = Header::from_binary(input)?;
+        decompress_into(input, &header, destination)
+    }
+}
+
+impl Compress for Rle {
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> { Ok(compress(data)) }
+}
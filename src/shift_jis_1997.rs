@@ -36,7 +36,64 @@
 //! `JIS X 0213`.
 //!
 //! # Examples
-//! TODO: Add examples
+//!
+//! ```
+//! # use picori::{Result, ShiftJis1997};
+//! fn main() -> Result<()> {
+//!     let data = ShiftJis1997::encode("ゲーム.arc")?;
+//!     assert_eq!(data, b"\x83\x51\x81\x5B\x83\x80.arc");
+//!     assert_eq!(ShiftJis1997::all(&data)?, "ゲーム.arc");
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Gaiji
+//!
+//! Games often use vendor-specific codes, e.g., the user-defined area starting
+//! at `0xF040`, for custom glyphs (外字, gaiji) like button icons. Those codes
+//! are not part of [Shift JIS][`ShiftJis1997`] and fail to decode, unless a
+//! mapping to characters (e.g., from the Unicode private use area) is supplied
+//! with [`Decoder::gaiji`] and [`Encoder::gaiji`].
+//!
+//! ```
+//! # use picori::{Result, ShiftJis1997, ShiftJis1997IteratorExt};
+//! fn main() -> Result<()> {
+//!     let gaiji = [(0xF040, '\u{E000}')];
+//!     let data = b"\xF0\x40 OK";
+//!     assert!(ShiftJis1997::all(data).is_err());
+//!     let text = data.sjis1997().gaiji(&gaiji).collect::<Result<String>>()?;
+//!     assert_eq!(text, "\u{E000} OK");
+//!     let encoded = ShiftJis1997::encoder(text.chars()).gaiji(&gaiji);
+//!     assert_eq!(encoded.collect::<Result<Vec<_>>>()?, data);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Extensions
+//!
+//! Windows (CP932) extends [Shift JIS][`ShiftJis1997`] with the NEC special
+//! characters (row 13, e.g., `①` and `Ⅰ`), the NEC-selected IBM extensions
+//! (rows 89 to 92) and the IBM extensions (rows 115 to 119). Tools of the era
+//! disagreed on whether to use them and the extensions are rejected unless
+//! enabled with [`Extensions`]. The NEC-selected IBM extensions and the IBM
+//! extensions contain the same characters (and some NEC special characters are
+//! also IBM extensions), [`Extensions::prefer_ibm`] chooses which code is used
+//! when encoding those characters.
+//!
+//! ```
+//! # use picori::{Result, ShiftJis1997, ShiftJis1997IteratorExt};
+//! # use picori::shift_jis_1997::Extensions;
+//! fn main() -> Result<()> {
+//!     let extensions = Extensions::new().nec(true);
+//!     let data = b"\x87\x40";
+//!     assert!(ShiftJis1997::all(data).is_err());
+//!     let text = data.sjis1997().extensions(extensions);
+//!     assert_eq!(text.collect::<Result<String>>()?, "①");
+//!     let encoded = ShiftJis1997::encoder("①".chars()).extensions(extensions);
+//!     assert_eq!(encoded.collect::<Result<Vec<_>>>()?, data);
+//!     Ok(())
+//! }
+//! ```
 //!
 //! # References
 //! Finding references that were still available was incredible difficult. Both
@@ -50,18 +107,28 @@
 //! - [Shift JIS Kanji Table](http://www.rikai.com/library/kanjitables/kanji_codes.sjis.shtml)
 //! - [JIS X 0213 Code Mapping Tables](http://x0213.org/codetable/index.en.html)
 
-use std::borrow::Borrow;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::panic::Location;
+#[cfg(feature = "std")]
 use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
 
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
 use crate::error::DecodingProblem::*;
-use crate::helper::{ensure, ParseStringEncoding, ProblemLocation, Parser};
+use crate::error::EncodingProblem::*;
+use crate::helper::{ensure, ProblemLocation};
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
 use crate::jis_x_0201::Decoder as JisX0201Decoder;
-use crate::Result;
+use crate::{Encoding, JisX0201, Result};
 
 mod internal {
     include!(concat!(env!("OUT_DIR"), "/shift_jis_1997.rs"));
+    include!(concat!(env!("OUT_DIR"), "/shift_jis_extensions.rs"));
 }
 
 /// [`ShiftJis1997`] encoding.
@@ -72,29 +139,238 @@ enum Next {
     One(char),
 }
 
+/// Decode a double-byte JIS X 0208 character, `byte` is the lead-byte.
+pub(crate) fn decode_double(byte: u8, next: u8) -> Result<char> {
+    let (first, last, offset) = internal::SJIS_1997_UTF8_T[byte as usize];
+    ensure!(
+        next >= first && next <= last,
+        InvalidByte(next, Location::current())
+    );
+    let relative = (next - first) as usize;
+    let index = offset + relative;
+    let value = internal::SJIS_1997_UTF8_S[index];
+    ensure!(value != 0, InvalidByte(next, Location::current()));
+    ensure!(
+        (value & 0x8000_0000) == 0,
+        InvalidByte(next, Location::current())
+    );
+    Ok(unsafe { char::from_u32_unchecked(value) })
+}
+
+/// Encode a character as a double-byte JIS X 0208 character, or [`None`] if
+/// the character is not part of JIS X 0208.
+pub(crate) fn encode_double(c: char) -> Option<[u8; 2]> {
+    let index = internal::SJIS_1997_ENCODE
+        .binary_search_by_key(&(c as u32), |x| x.0)
+        .ok()?;
+    Some((internal::SJIS_1997_ENCODE[index].1 as u16).to_be_bytes())
+}
+
+/// Vendor extensions of [Shift JIS][`ShiftJis1997`], see
+/// [extensions][`crate::shift_jis_1997#extensions`]. All extensions are
+/// disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Extensions {
+    nec:        bool,
+    nec_ibm:    bool,
+    ibm:        bool,
+    prefer_ibm: bool,
+}
+
+impl Extensions {
+    /// Creates the default extensions, i.e., all extensions are disabled.
+    pub fn new() -> Self { Self::default() }
+
+    /// Enables all extensions, like Windows (CP932).
+    pub fn cp932() -> Self { Self::new().nec(true).nec_ibm(true).ibm(true) }
+
+    /// NEC special characters (row 13, `0x8740` to `0x879C`).
+    pub fn nec(mut self, enable: bool) -> Self {
+        self.nec = enable;
+        self
+    }
+
+    /// NEC-selected IBM extensions (rows 89 to 92, `0xED40` to `0xEEFC`).
+    pub fn nec_ibm(mut self, enable: bool) -> Self {
+        self.nec_ibm = enable;
+        self
+    }
+
+    /// IBM extensions (rows 115 to 119, `0xFA40` to `0xFC4B`).
+    pub fn ibm(mut self, enable: bool) -> Self {
+        self.ibm = enable;
+        self
+    }
+
+    /// Encode characters that are part of both the NEC and the IBM extensions
+    /// with the IBM code, instead of the NEC code.
+    pub fn prefer_ibm(mut self, prefer: bool) -> Self {
+        self.prefer_ibm = prefer;
+        self
+    }
+
+    /// The tables of the extensions and whether they are enabled, in the order
+    /// of preference.
+    fn tables(&self) -> [(bool, &'static [(u32, u32)]); 3] {
+        let nec = (self.nec, &internal::SJIS_NEC[..]);
+        let nec_ibm = (self.nec_ibm, &internal::SJIS_NEC_IBM[..]);
+        let ibm = (self.ibm, &internal::SJIS_IBM[..]);
+        if self.prefer_ibm {
+            [ibm, nec, nec_ibm]
+        } else {
+            [nec, nec_ibm, ibm]
+        }
+    }
+
+    fn decode(&self, code: u16) -> Option<char> {
+        self.tables()
+            .into_iter()
+            .filter(|x| x.0)
+            .find_map(|(_, table)| {
+                let index = table.binary_search_by_key(&(code as u32), |x| x.0).ok()?;
+                char::from_u32(table[index].1)
+            })
+    }
+
+    fn encode(&self, c: char) -> Option<[u8; 2]> {
+        self.tables()
+            .into_iter()
+            .filter(|x| x.0)
+            .find_map(|(_, table)| {
+                let (code, _) = table.iter().find(|x| x.1 == c as u32)?;
+                Some((*code as u16).to_be_bytes())
+            })
+    }
+}
+
+/// A iterator encoder for the [`ShiftJis1997`] encoding, yields the encoded
+/// bytes of the characters.
+pub struct Encoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<char> + Sized,
+{
+    iter:       <I as IntoIterator>::IntoIter,
+    pending:    Option<u8>,
+    gaiji:      &'x [(u16, char)],
+    extensions: Extensions,
+}
+
+impl<'x, I> Encoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<char> + Sized,
+{
+    fn new(iter: I) -> Self {
+        Encoder {
+            iter:       iter.into_iter(),
+            pending:    None,
+            gaiji:      &[],
+            extensions: Extensions::default(),
+        }
+    }
+
+    /// Encode the characters of the enabled vendor `extensions`, see
+    /// [extensions][`crate::shift_jis_1997#extensions`].
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Encode the characters of `table` as the double-byte code they are
+    /// paired with, see [gaiji][`crate::shift_jis_1997#gaiji`]. The table takes
+    /// precedence over [Shift JIS][`ShiftJis1997`].
+    pub fn gaiji(mut self, table: &'x [(u16, char)]) -> Self {
+        self.gaiji = table;
+        self
+    }
+}
+
+impl<I> Iterator for Encoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<char> + Sized,
+{
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(byte) = self.pending.take() {
+            return Some(Ok(byte));
+        }
+
+        let c = *self.iter.next()?.borrow();
+        if let Some((code, _)) = self.gaiji.iter().find(|x| x.1 == c) {
+            let [first, second] = code.to_be_bytes();
+            self.pending = Some(second);
+            return Some(Ok(first));
+        }
+
+        let (first, second) = match ShiftJis1997::encode_char(c) {
+            Ok(bytes) => bytes,
+            Err(e) => match self.extensions.encode(c) {
+                Some([first, second]) => (first, Some(second)),
+                None => return Some(Err(e)),
+            },
+        };
+        self.pending = second;
+        Some(Ok(first))
+    }
+}
+
 /// A iterator decoder for the [`ShiftJis1997`] encoding.
 pub struct Decoder<'x, I>
 where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter:    <I as IntoIterator>::IntoIter,
-    _marker: PhantomData<&'x ()>,
+    iter:       Tracked<<I as IntoIterator>::IntoIter>,
+    gaiji:      &'x [(u16, char)],
+    extensions: Extensions,
 }
 
-impl<I> Decoder<'_, I>
+impl<'x, I> Decoder<'x, I>
 where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    fn new<'x>(iter: I) -> Decoder<'x, I> {
+    fn new(iter: I) -> Self {
         Decoder {
-            iter:    iter.into_iter(),
-            _marker: PhantomData,
+            iter:       Tracked::new(iter.into_iter()),
+            gaiji:      &[],
+            extensions: Extensions::default(),
         }
     }
 
-    fn decode_next(iter: &mut <I as IntoIterator>::IntoIter) -> Result<Next> {
+    /// Decode the characters of the enabled vendor `extensions`, see
+    /// [extensions][`crate::shift_jis_1997#extensions`].
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Decode the double-byte codes of `table` as the character they are
+    /// paired with, see [gaiji][`crate::shift_jis_1997#gaiji`]. The table takes
+    /// precedence over [Shift JIS][`ShiftJis1997`].
+    pub fn gaiji(mut self, table: &'x [(u16, char)]) -> Self {
+        self.gaiji = table;
+        self
+    }
+
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
+    fn decode_next(
+        iter: &mut Tracked<<I as IntoIterator>::IntoIter>,
+        gaiji: &[(u16, char)],
+        extensions: &Extensions,
+    ) -> Result<Next> {
         let byte = iter.next();
         if let Some(byte) = byte {
             let byte = *byte.borrow();
@@ -109,20 +385,14 @@ where
                         .next()
                         .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
                     let next = *next.borrow();
-                    let (first, last, offset) = internal::SJIS_1997_UTF8_T[byte as usize];
-                    ensure!(
-                        next >= first && next <= last,
-                        InvalidByte(next, Location::current())
-                    );
-                    let relative = (next - first) as usize;
-                    let index = offset + relative;
-                    let value = internal::SJIS_1997_UTF8_S[index];
-                    ensure!(value != 0, InvalidByte(next, Location::current()));
-                    ensure!(
-                        (value & 0x8000_0000) == 0,
-                        InvalidByte(next, Location::current())
-                    );
-                    Ok(Next::One(unsafe { char::from_u32_unchecked(value) }))
+                    let code = u16::from_be_bytes([byte, next]);
+                    if let Some((_, c)) = gaiji.iter().find(|x| x.0 == code) {
+                        return Ok(Next::One(*c));
+                    }
+                    if let Some(c) = extensions.decode(code) {
+                        return Ok(Next::One(c));
+                    }
+                    Ok(Next::One(decode_double(byte, next)?))
                 },
                 // Invalid as first byte
                 _ => Err(InvalidByte(byte, Location::current()).into()),
@@ -141,15 +411,58 @@ where
     type Item = Result<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match Self::decode_next(&mut self.iter) {
+        let offset = self.iter.offset();
+        match Self::decode_next(&mut self.iter, self.gaiji, &self.extensions) {
             Ok(Next::EndOfInput) => None,
-            Ok(Next::One(c)) => Some(Ok(c)),
-            Err(e) => Some(Err(e)),
+            Ok(Next::One(c)) => Some(self.iter.locate(offset, Ok(c))),
+            Err(e) => Some(self.iter.locate(offset, Err(e))),
         }
     }
 }
 
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize { self.iter.offset() }
+}
+
 impl ShiftJis1997 {
+    /// Encode a single character into one or two bytes. The second byte is
+    /// [`None`] for single-byte characters.
+    pub fn encode_char(c: char) -> Result<(u8, Option<u8>)> {
+        if let Some(byte) = JisX0201::encode_char(c) {
+            return Ok((byte, None));
+        }
+
+        let [first, second] =
+            encode_double(c).ok_or(UnableToEncodeCodePoint(c, Location::current()))?;
+        Ok((first, Some(second)))
+    }
+
+    /// Check that every character of `data` can be encoded and decodes back
+    /// to the same character, e.g., to validate a translated script before
+    /// inserting it. Fails with the byte index and character of every
+    /// character that can not be encoded.
+    pub fn can_encode(data: &str) -> core::result::Result<(), Vec<(usize, char)>> {
+        Encoding::ShiftJis1997.can_encode(data)
+    }
+
+    /// Create an iterator that encodes the given iterator of characters into
+    /// bytes.
+    pub fn encoder<'iter, I>(iter: I) -> Encoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<char> + Sized,
+    {
+        Encoder::new(iter)
+    }
+
+    /// Encode all characters of `data`. Fails if a character is not part of
+    /// [Shift JIS 1997][`ShiftJis1997`]. No NULL byte is appended.
+    pub fn encode(data: &str) -> Result<Vec<u8>> { Self::encoder(data.chars()).collect() }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -170,6 +483,13 @@ impl ShiftJis1997 {
         Self::iter(iter).collect()
     }
 
+    /// Decode all bytes of `data` like [`ShiftJis1997::all`], but ASCII-only
+    /// data (without `\` and `~`, which decode to `¥` and `‾`) is borrowed
+    /// without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, b"\\~", |data| Self::all(data))
+    }
+
     /// Decode the first string (until a NULL character is reached) from the
     /// given iterator.
     pub fn first<I>(iter: I) -> Result<String>
@@ -184,6 +504,55 @@ impl ShiftJis1997 {
             })
             .collect()
     }
+
+    /// Decode all bytes into a string like [`ShiftJis1997::all`], but invalid
+    /// bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`ShiftJis1997::first`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`ShiftJis1997::all`], invalid bytes
+    /// are handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`ShiftJis1997::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
 }
 
 /// Extension trait for iterators of bytes and adds the helper function
@@ -205,6 +574,7 @@ where
 {
 }
 
+#[cfg(feature = "std")]
 impl ParseStringEncoding for ShiftJis1997 {
     fn parse_str<I>(iter: I) -> Result<String>
     where
@@ -214,8 +584,15 @@ impl ParseStringEncoding for ShiftJis1997 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("ShiftJis1997::write_str")
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for byte in Self::encoder(data.chars()) {
+            let byte = byte?;
+            ensure!(i < buffer.len(), BufferTooSmall(Location::current()));
+            buffer[i] = byte;
+            i += 1;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
@@ -223,10 +600,7 @@ impl ParseStringEncoding for ShiftJis1997 {
         let iter = buffer
             .bytes()
             .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
+            .filter_map(|x| x.ok());
         Self::first(iter)
     }
 }
@@ -244,4 +618,106 @@ mod tests {
         let data = b"abc\x88\x9f\0def";
         assert_eq!(ShiftJis1997::parse_str(data).unwrap(), "abc亜".to_string());
     }
+
+    #[test]
+    fn encode() {
+        let text = "abc亜¥\\ｱ〜";
+        let data = ShiftJis1997::encode(text).unwrap();
+        assert_eq!(data, b"abc\x88\x9f\x5c\x81\x5f\xb1\x81\x60");
+        assert_eq!(ShiftJis1997::all(&data).unwrap(), text);
+        assert!(ShiftJis1997::encode("😀").is_err());
+
+        let mut buffer = [0; 4];
+        assert_eq!(ShiftJis1997::write_str("a亜", &mut buffer).unwrap(), 3);
+        assert_eq!(&buffer[..3], b"a\x88\x9f");
+        assert!(ShiftJis1997::write_str("亜亜亜", &mut buffer).is_err());
+    }
+
+    #[test]
+    fn lossy() {
+        let data = b"a\x88\x9f\xffb\x88";
+        assert!(ShiftJis1997::all(data).is_err());
+        assert_eq!(ShiftJis1997::all_lossy(data), "a亜\u{fffd}b\u{fffd}");
+        assert_eq!(ShiftJis1997::first_lossy(b"\xffa\0b"), "\u{fffd}a");
+        assert_eq!(data.sjis1997().lossy().count(), 5);
+    }
+
+    #[test]
+    fn can_encode() {
+        assert!(ShiftJis1997::can_encode("ゲーム¥\\ｱ").is_ok());
+        assert_eq!(ShiftJis1997::can_encode("a😀b①c").unwrap_err(), [
+            (1, '😀'),
+            (6, '①')
+        ]);
+    }
+
+    #[test]
+    fn extensions() {
+        let encode = |text: &str, extensions| {
+            ShiftJis1997::encoder(text.chars())
+                .extensions(extensions)
+                .collect::<Result<Vec<_>>>()
+        };
+        let decode = |data: &[u8], extensions| {
+            data.sjis1997()
+                .extensions(extensions)
+                .collect::<Result<String>>()
+        };
+
+        // Ⅰ is both a NEC special character and an IBM extension
+        let cp932 = Extensions::cp932();
+        assert_eq!(encode("Ⅰ纊", cp932).unwrap(), b"\x87\x54\xED\x40");
+        let ibm = cp932.prefer_ibm(true);
+        assert_eq!(encode("Ⅰ纊", ibm).unwrap(), b"\xFA\x4A\xFA\x5C");
+        assert_eq!(decode(b"\x87\x54\xFA\x4A", cp932).unwrap(), "ⅠⅠ");
+
+        // only the enabled extensions are used
+        let nec = Extensions::new().nec(true);
+        assert!(encode("纊", nec).is_err());
+        assert!(decode(b"\xFA\x4A", nec).is_err());
+        assert_eq!(
+            encode("Ⅰ", Extensions::new().ibm(true)).unwrap(),
+            b"\xFA\x4A"
+        );
+        assert!(ShiftJis1997::encode("Ⅰ").is_err());
+    }
+
+    #[test]
+    fn offset() {
+        // invalid second byte of the character at offset 4
+        let data = b"a\x88\x9fb\x88\x20c";
+        match ShiftJis1997::all(data) {
+            Err(crate::Error::Decoding(e)) => {
+                assert_eq!(e.offset(), Some((4, 3)));
+                assert!(matches!(e.problem(), InvalidByte(0x20, _)));
+            },
+            x => panic!("unexpected result: {x:?}"),
+        }
+    }
+
+    #[test]
+    fn error_policy() {
+        let data = b"a\x88\x9f\xffb\0\xff";
+        let decode = |policy| ShiftJis1997::all_with(data, policy);
+        assert!(decode(ErrorPolicy::Strict).is_err());
+        assert_eq!(decode(ErrorPolicy::Replace('?')).unwrap(), "a亜?b\0?");
+        assert_eq!(decode(ErrorPolicy::Skip).unwrap(), "a亜b\0");
+        assert_eq!(decode(ErrorPolicy::StopAtError).unwrap(), "a亜");
+        assert_eq!(
+            ShiftJis1997::first_with(data, ErrorPolicy::Skip).unwrap(),
+            "a亜b"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        // every valid two-byte character encodes to the same bytes
+        for first in (0x81..=0x9F).chain(0xE0..=0xFC) {
+            for second in 0x40..=0xFC {
+                if let Ok(text) = ShiftJis1997::all([first, second]) {
+                    assert_eq!(ShiftJis1997::encode(&text).unwrap(), [first, second]);
+                }
+            }
+        }
+    }
 }
@@ -28,13 +28,22 @@
 //! - [JIS X 0213 Code Mapping Tables](http://x0213.org/codetable/index.en.html)
 //! - [Shift JIS Kanji Table](http://www.rikai.com/library/kanjitables/kanji_codes.sjis.shtml)
 
-use std::borrow::Borrow;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::panic::Location;
+#[cfg(feature = "std")]
 use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
 
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
 use crate::error::DecodingProblem::*;
-use crate::helper::{ensure, ParseStringEncoding, ProblemLocation, Parser};
+#[cfg(feature = "std")]
+use crate::error::EncodingProblem;
+use crate::helper::{ensure, ProblemLocation};
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
 use crate::jis_x_0201::Decoder as JisX0201Decoder;
 use crate::Result;
 
@@ -57,25 +66,47 @@ where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter:     <I as IntoIterator>::IntoIter,
+    iter:     Tracked<<I as IntoIterator>::IntoIter>,
     buffered: Option<char>,
-    _marker:  PhantomData<&'x ()>,
+    gaiji:    &'x [(u16, char)],
 }
 
-impl<I> Decoder<'_, I>
+impl<'x, I> Decoder<'x, I>
 where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    fn new<'x>(iter: I) -> Decoder<'x, I> {
+    fn new(iter: I) -> Self {
         Decoder {
-            iter:     iter.into_iter(),
+            iter:     Tracked::new(iter.into_iter()),
             buffered: None,
-            _marker:  PhantomData,
+            gaiji:    &[],
         }
     }
 
-    fn decode_next(iter: &mut <I as IntoIterator>::IntoIter) -> Result<Next> {
+    /// Decode the double-byte codes of `table` as the character they are
+    /// paired with, see [gaiji][`crate::shift_jis_1997#gaiji`]. The table takes
+    /// precedence over [Shift JIS][`ShiftJis2004`], i.e., also over the `JIS X
+    /// 0213` characters of the user-defined area.
+    pub fn gaiji(mut self, table: &'x [(u16, char)]) -> Self {
+        self.gaiji = table;
+        self
+    }
+
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
+    fn decode_next(
+        iter: &mut Tracked<<I as IntoIterator>::IntoIter>,
+        gaiji: &[(u16, char)],
+    ) -> Result<Next> {
         let byte = iter.next();
         if let Some(byte) = byte {
             let byte = *byte.borrow();
@@ -90,6 +121,10 @@ where
                         .next()
                         .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
                     let next = *next.borrow();
+                    let code = u16::from_be_bytes([byte, next]);
+                    if let Some((_, c)) = gaiji.iter().find(|x| x.0 == code) {
+                        return Ok(Next::One(*c));
+                    }
                     let (first, last, offset) = internal::SJIS_2004_UTF8_T[byte as usize];
                     ensure!(
                         next >= first && next <= last,
@@ -127,23 +162,35 @@ where
     type Item = Result<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.char_offset();
         if let Some(value) = self.buffered {
             self.buffered = None;
-            Some(Ok(value))
+            Some(self.iter.locate(offset, Ok(value)))
         } else {
-            match Self::decode_next(&mut self.iter) {
+            match Self::decode_next(&mut self.iter, self.gaiji) {
                 Ok(Next::EndOfInput) => None,
-                Ok(Next::One(c)) => Some(Ok(c)),
+                Ok(Next::One(c)) => Some(self.iter.locate(offset, Ok(c))),
                 Ok(Next::Two(first, second)) => {
                     self.buffered = Some(second);
-                    Some(Ok(first))
+                    Some(self.iter.locate(offset, Ok(first)))
                 },
-                Err(e) => Some(Err(e)),
+                Err(e) => Some(self.iter.locate(offset, Err(e))),
             }
         }
     }
 }
 
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize {
+        // both characters of a pair start at the same offset
+        self.iter.offset() - 2 * self.buffered.is_some() as usize
+    }
+}
+
 impl ShiftJis2004 {
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
@@ -165,6 +212,13 @@ impl ShiftJis2004 {
         Self::iter(iter).collect()
     }
 
+    /// Decode all bytes of `data` like [`ShiftJis2004::all`], but ASCII-only
+    /// data (without `\` and `~`, which decode to `¥` and `‾`) is borrowed
+    /// without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, b"\\~", |data| Self::all(data))
+    }
+
     /// Decode the first string (until a NULL character is reached) from the
     /// given iterator.
     pub fn first<I>(iter: I) -> Result<String>
@@ -179,6 +233,55 @@ impl ShiftJis2004 {
             })
             .collect()
     }
+
+    /// Decode all bytes into a string like [`ShiftJis2004::all`], but invalid
+    /// bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`ShiftJis2004::first`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`ShiftJis2004::all`], invalid bytes
+    /// are handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`ShiftJis2004::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
 }
 
 /// Extension trait for iterators of bytes and adds the helper function
@@ -200,6 +303,7 @@ where
 {
 }
 
+#[cfg(feature = "std")]
 impl ParseStringEncoding for ShiftJis2004 {
     fn parse_str<I>(iter: I) -> Result<String>
     where
@@ -209,8 +313,20 @@ impl ParseStringEncoding for ShiftJis2004 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("ShiftJis2004::write_str")
+    /// Characters are encoded with the [Shift JIS 1997][`crate::ShiftJis1997`]
+    /// encoder, characters only added by `JIS X 0213` can not be encoded.
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for byte in crate::ShiftJis1997::encoder(data.chars()) {
+            let byte = byte?;
+            ensure!(
+                i < buffer.len(),
+                EncodingProblem::BufferTooSmall(Location::current())
+            );
+            buffer[i] = byte;
+            i += 1;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
@@ -218,10 +334,7 @@ impl ParseStringEncoding for ShiftJis2004 {
         let iter = buffer
             .bytes()
             .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
+            .filter_map(|x| x.ok());
         Self::first(iter)
     }
 }
@@ -239,4 +352,38 @@ mod tests {
         let data = b"abc\x88\x9f\0def";
         assert_eq!(ShiftJis2004::parse_str(data).unwrap(), "abc亜".to_string());
     }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0; 6];
+        assert_eq!(ShiftJis2004::write_str("abc亜", &mut buffer).unwrap(), 5);
+        assert_eq!(ShiftJis2004::parse_str(buffer).unwrap(), "abc亜");
+        assert!(ShiftJis2004::write_str("abcde亜", &mut buffer).is_err());
+        assert!(ShiftJis2004::write_str("😀", &mut buffer).is_err());
+    }
+
+    #[test]
+    fn char_offsets() {
+        // both characters of the pair point to the same bytes
+        let data = b"a\x82\xf5b";
+        let chars = data
+            .sjis2004()
+            .char_offsets()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chars, [(0, 'a'), (1, 'か'), (1, '\u{309a}'), (3, 'b')]);
+    }
+
+    #[test]
+    fn gaiji() {
+        // the table takes precedence over the JIS X 0213 characters
+        let gaiji = [(0xF040, '\u{E000}'), (0xFCF5, '\u{E001}')];
+        let data = b"\xF0\x40\xFC\xF5a";
+        assert_ne!(
+            ShiftJis2004::all(data).ok().as_deref(),
+            Some("\u{E000}\u{E001}a")
+        );
+        let text = data.sjis2004().gaiji(&gaiji).collect::<Result<String>>();
+        assert_eq!(text.unwrap(), "\u{E000}\u{E001}a");
+    }
 }
@@ -0,0 +1,783 @@
+//! GameCube and Wii textures.
+//!
+//! Textures are stored in blocks (tiles) of 32 bytes, e.g., 8×8 pixels for 4
+//! bits per pixel formats and 4×4 pixels for 16 bits per pixel formats. The
+//! blocks are stored row by row and images that are not a multiple of the block
+//! size are padded. This module decodes the texture data independent of the
+//! container (e.g., [BNR][`crate::bnr`]), see [`decode`].
+//!
+//! | Format              | Block | Description                                |
+//! |---------------------|-------|--------------------------------------------|
+//! | [`Format::I4`]      | 8×8   | 4-bit intensity                            |
+//! | [`Format::I8`]      | 8×4   | 8-bit intensity                            |
+//! | [`Format::Ia4`]     | 8×4   | 4-bit intensity and 4-bit alpha            |
+//! | [`Format::Ia8`]     | 4×4   | 8-bit intensity and 8-bit alpha            |
+//! | [`Format::Rgb565`]  | 4×4   | 16-bit color                               |
+//! | [`Format::Rgb5a3`]  | 4×4   | 16-bit color (RGB555 or RGB4A3)            |
+//! | [`Format::Rgba8`]   | 4×4   | 32-bit color (two 32 byte halves)          |
+//! | [`Format::C4`]      | 8×8   | 4-bit palette index                        |
+//! | [`Format::C8`]      | 8×4   | 8-bit palette index                        |
+//! | [`Format::C14x2`]   | 4×4   | 14-bit palette index                       |
+//! | [`Format::Cmpr`]    | 8×8   | S3TC (DXT1) compressed, four 4×4 blocks    |
+//!
+//! The palette formats ([`Format::C4`], [`Format::C8`], and
+//! [`Format::C14x2`]) require a palette (TLUT) of [`TlutFormat`] colors.
+//!
+//! RGBA8 images are encoded with [`encode`] (or [`encode_with`] to choose the
+//! [`Quality`] of [`Format::Cmpr`]), and with [`encode_indexed`] for the
+//! palette formats, which also creates the palette.
+//!
+//! ## Example
+//!
+//! ```
+//! # use picori::Result;
+//! use picori::texture::{self, Format};
+//!
+//! fn main() -> Result<()> {
+//!     // a single 8×4 block of I8 pixels
+//!     let data = [0x80; 32];
+//!     let image = texture::decode(Format::I8, &data, 8, 4, None)?;
+//!     assert_eq!(image[..4], [0x80, 0x80, 0x80, 0x80]);
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::panic::Location;
+
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::{ensure, ProblemLocation};
+use crate::Result;
+
+/// Texture format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// 4-bit intensity (alpha is the intensity).
+    I4,
+
+    /// 8-bit intensity (alpha is the intensity).
+    I8,
+
+    /// 4-bit intensity and 4-bit alpha.
+    Ia4,
+
+    /// 8-bit intensity and 8-bit alpha.
+    Ia8,
+
+    /// 16-bit color with 5-bit red, 6-bit green, and 5-bit blue.
+    Rgb565,
+
+    /// 16-bit color, either opaque RGB555 (top bit set) or RGB4A3.
+    Rgb5a3,
+
+    /// 32-bit color.
+    Rgba8,
+
+    /// 4-bit palette index.
+    C4,
+
+    /// 8-bit palette index.
+    C8,
+
+    /// 14-bit palette index.
+    C14x2,
+
+    /// S3TC (DXT1) compressed color with 1-bit alpha.
+    Cmpr,
+}
+
+impl Format {
+    /// Get the format of the GX format id `id`, e.g., from the header of a
+    /// texture.
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0x0 => Some(Format::I4),
+            0x1 => Some(Format::I8),
+            0x2 => Some(Format::Ia4),
+            0x3 => Some(Format::Ia8),
+            0x4 => Some(Format::Rgb565),
+            0x5 => Some(Format::Rgb5a3),
+            0x6 => Some(Format::Rgba8),
+            0x8 => Some(Format::C4),
+            0x9 => Some(Format::C8),
+            0xA => Some(Format::C14x2),
+            0xE => Some(Format::Cmpr),
+            _ => None,
+        }
+    }
+
+    /// Get the GX format id.
+    pub fn id(&self) -> u32 {
+        match self {
+            Format::I4 => 0x0,
+            Format::I8 => 0x1,
+            Format::Ia4 => 0x2,
+            Format::Ia8 => 0x3,
+            Format::Rgb565 => 0x4,
+            Format::Rgb5a3 => 0x5,
+            Format::Rgba8 => 0x6,
+            Format::C4 => 0x8,
+            Format::C8 => 0x9,
+            Format::C14x2 => 0xA,
+            Format::Cmpr => 0xE,
+        }
+    }
+
+    /// Number of bits per pixel.
+    pub fn bits_per_pixel(&self) -> usize {
+        match self {
+            Format::I4 | Format::C4 | Format::Cmpr => 4,
+            Format::I8 | Format::Ia4 | Format::C8 => 8,
+            Format::Ia8 | Format::Rgb565 | Format::Rgb5a3 | Format::C14x2 => 16,
+            Format::Rgba8 => 32,
+        }
+    }
+
+    /// Width and height of a block in pixels.
+    pub fn block_size(&self) -> (usize, usize) {
+        match self.bits_per_pixel() {
+            4 => (8, 8),
+            8 => (8, 4),
+            _ => (4, 4),
+        }
+    }
+
+    /// Size of a block in bytes, 64 for [`Format::Rgba8`] and 32 otherwise.
+    pub fn block_bytes(&self) -> usize {
+        match self {
+            Format::Rgba8 => 64,
+            _ => 32,
+        }
+    }
+
+    /// Returns `true` if the format is palette indices.
+    pub fn is_indexed(&self) -> bool { matches!(self, Format::C4 | Format::C8 | Format::C14x2) }
+
+    /// Size of the data of a `width`×`height` texture, including the padding
+    /// of the blocks.
+    pub fn data_size(&self, width: usize, height: usize) -> usize {
+        let (block_width, block_height) = self.block_size();
+        width.div_ceil(block_width) * height.div_ceil(block_height) * self.block_bytes()
+    }
+}
+
+/// Palette (TLUT) color format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TlutFormat {
+    /// 8-bit intensity and 8-bit alpha.
+    Ia8,
+
+    /// 16-bit color with 5-bit red, 6-bit green, and 5-bit blue.
+    Rgb565,
+
+    /// 16-bit color, either opaque RGB555 (top bit set) or RGB4A3.
+    Rgb5a3,
+}
+
+impl TlutFormat {
+    /// Get the format of the GX TLUT format id `id`.
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(TlutFormat::Ia8),
+            1 => Some(TlutFormat::Rgb565),
+            2 => Some(TlutFormat::Rgb5a3),
+            _ => None,
+        }
+    }
+
+    /// Get the GX TLUT format id.
+    pub fn id(&self) -> u32 {
+        match self {
+            TlutFormat::Ia8 => 0,
+            TlutFormat::Rgb565 => 1,
+            TlutFormat::Rgb5a3 => 2,
+        }
+    }
+
+    /// Maximum number of palette colors of the palette `format`.
+    fn capacity(format: Format) -> usize {
+        match format {
+            Format::C4 => 16,
+            Format::C8 => 256,
+            _ => 16384,
+        }
+    }
+
+    fn encode(&self, color: [u8; 4]) -> u16 {
+        match self {
+            TlutFormat::Ia8 => to_ia8(color),
+            TlutFormat::Rgb565 => to_rgb565(color),
+            TlutFormat::Rgb5a3 => to_rgb5a3(color),
+        }
+    }
+
+    fn decode_color(&self, value: u16) -> [u8; 4] {
+        match self {
+            TlutFormat::Ia8 => ia8(value),
+            TlutFormat::Rgb565 => rgb565(value),
+            TlutFormat::Rgb5a3 => rgb5a3(value),
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Vec<[u8; 4]> {
+        data.chunks_exact(2)
+            .map(|x| u16::from_be_bytes([x[0], x[1]]))
+            .map(|x| self.decode_color(x))
+            .collect()
+    }
+}
+
+fn expand<const BITS: u32>(value: u16) -> u8 {
+    let value = value & ((1 << BITS) - 1);
+    ((value << (8 - BITS)) | (value >> (2 * BITS - 8))) as u8
+}
+
+fn ia8(value: u16) -> [u8; 4] {
+    let [alpha, intensity] = value.to_be_bytes();
+    [intensity, intensity, intensity, alpha]
+}
+
+fn rgb565(value: u16) -> [u8; 4] {
+    [
+        expand::<5>(value >> 11),
+        expand::<6>(value >> 5),
+        expand::<5>(value),
+        0xFF,
+    ]
+}
+
+fn rgb5a3(value: u16) -> [u8; 4] {
+    if value & 0x8000 != 0 {
+        [
+            expand::<5>(value >> 10),
+            expand::<5>(value >> 5),
+            expand::<5>(value),
+            0xFF,
+        ]
+    } else {
+        let alpha = (value >> 12) & 0x07;
+        [
+            ((value >> 8) & 0x0F) as u8 * 0x11,
+            ((value >> 4) & 0x0F) as u8 * 0x11,
+            (value & 0x0F) as u8 * 0x11,
+            ((alpha << 5) | (alpha << 2) | (alpha >> 1)) as u8,
+        ]
+    }
+}
+
+/// Colors of a S3TC block with the endpoints `color0` and `color1`. If
+/// `color0` is not greater than `color1`, the last color is transparent.
+fn cmpr_colors(color0: u16, color1: u16) -> [[u8; 4]; 4] {
+    let (c0, c1) = (rgb565(color0), rgb565(color1));
+    let mix = |a: u16, b: u16, d: u16| {
+        let mut color = [0xFF; 4];
+        for i in 0..3 {
+            color[i] = ((c0[i] as u16 * a + c1[i] as u16 * b) / d) as u8;
+        }
+        color
+    };
+    if color0 > color1 {
+        [c0, c1, mix(2, 1, 3), mix(1, 2, 3)]
+    } else {
+        [c0, c1, mix(1, 1, 2), [0, 0, 0, 0]]
+    }
+}
+
+/// Decode a 4×4 S3TC block (8 bytes) to `pixels`, row by row.
+fn cmpr(block: &[u8], pixels: &mut [[u8; 4]]) {
+    let color0 = u16::from_be_bytes([block[0], block[1]]);
+    let color1 = u16::from_be_bytes([block[2], block[3]]);
+    let colors = cmpr_colors(color0, color1);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let index = (block[4 + i / 4] >> (6 - (i % 4) * 2)) & 0x03;
+        *pixel = colors[index as usize];
+    }
+}
+
+/// Decode the `block` of `format` to `pixels` (row by row).
+fn decode_block(
+    format: Format,
+    block: &[u8],
+    palette: &[[u8; 4]],
+    pixels: &mut [[u8; 4]; 64],
+) -> Result<()> {
+    let nibble = |i: usize| ((block[i / 2] >> (4 - (i % 2) * 4)) & 0x0F) as u16;
+    let word = |i: usize| u16::from_be_bytes([block[i * 2], block[i * 2 + 1]]);
+    let lookup = |index: u16| {
+        palette
+            .get(index as usize)
+            .copied()
+            .ok_or(ParseProblem::InvalidRange(
+                "palette index (outside of TLUT)",
+                Location::current(),
+            ))
+    };
+
+    match format {
+        Format::I4 => {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = [nibble(i) as u8 * 0x11; 4];
+            }
+        },
+        Format::I8 => {
+            for (i, pixel) in pixels.iter_mut().take(32).enumerate() {
+                *pixel = [block[i]; 4];
+            }
+        },
+        Format::Ia4 => {
+            for (i, pixel) in pixels.iter_mut().take(32).enumerate() {
+                let intensity = (block[i] & 0x0F) * 0x11;
+                *pixel = [intensity, intensity, intensity, (block[i] >> 4) * 0x11];
+            }
+        },
+        Format::Ia8 => {
+            for (i, pixel) in pixels.iter_mut().take(16).enumerate() {
+                *pixel = ia8(word(i));
+            }
+        },
+        Format::Rgb565 => {
+            for (i, pixel) in pixels.iter_mut().take(16).enumerate() {
+                *pixel = rgb565(word(i));
+            }
+        },
+        Format::Rgb5a3 => {
+            for (i, pixel) in pixels.iter_mut().take(16).enumerate() {
+                *pixel = rgb5a3(word(i));
+            }
+        },
+        Format::Rgba8 => {
+            for (i, pixel) in pixels.iter_mut().take(16).enumerate() {
+                let (ar, gb) = (&block[i * 2..], &block[32 + i * 2..]);
+                *pixel = [ar[1], gb[0], gb[1], ar[0]];
+            }
+        },
+        Format::C4 => {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = lookup(nibble(i))?;
+            }
+        },
+        Format::C8 => {
+            for (i, pixel) in pixels.iter_mut().take(32).enumerate() {
+                *pixel = lookup(block[i] as u16)?;
+            }
+        },
+        Format::C14x2 => {
+            for (i, pixel) in pixels.iter_mut().take(16).enumerate() {
+                *pixel = lookup(word(i) & 0x3FFF)?;
+            }
+        },
+        Format::Cmpr => {
+            // four 4×4 blocks (top left, top right, bottom left, bottom right)
+            let mut sub = [[0; 4]; 16];
+            for (j, data) in block.chunks_exact(8).enumerate() {
+                cmpr(data, &mut sub);
+                for (i, pixel) in sub.iter().enumerate() {
+                    let x = (j % 2) * 4 + i % 4;
+                    let y = (j / 2) * 4 + i / 4;
+                    pixels[y * 8 + x] = *pixel;
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Decode the texture `data` of `format` and `width`×`height` pixels to RGBA8
+/// (row by row). The palette formats require `tlut`, i.e., the format and the
+/// data of the palette (big-endian 16-bit colors).
+pub fn decode(
+    format: Format,
+    data: &[u8],
+    width: usize,
+    height: usize,
+    tlut: Option<(TlutFormat, &[u8])>,
+) -> Result<Vec<u8>> {
+    ensure!(
+        data.len() >= format.data_size(width, height),
+        ParseProblem::InvalidRange("texture data (too small)", Location::current())
+    );
+    let palette = match (format.is_indexed(), tlut) {
+        (false, _) => Vec::new(),
+        (true, Some((tlut_format, tlut))) => tlut_format.decode(tlut),
+        (true, None) => Err(ParseProblem::InvalidData(
+            "palette texture without TLUT",
+            Location::current(),
+        ))?,
+    };
+
+    let (block_width, block_height) = format.block_size();
+    let blocks_x = width.div_ceil(block_width);
+    let blocks = blocks_x * height.div_ceil(block_height);
+    let mut image = vec![0; width * height * 4];
+    let mut pixels = [[0; 4]; 64];
+    for (i, block) in data
+        .chunks_exact(format.block_bytes())
+        .take(blocks)
+        .enumerate()
+    {
+        decode_block(format, block, &palette, &mut pixels)?;
+        for (j, pixel) in pixels[..block_width * block_height].iter().enumerate() {
+            let x = (i % blocks_x) * block_width + j % block_width;
+            let y = (i / blocks_x) * block_height + j / block_width;
+            if x < width && y < height {
+                let offset = (y * width + x) * 4;
+                image[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+/// Quality of the [`Format::Cmpr`] block compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quality {
+    /// Use the darkest and brightest color of each block as the endpoints.
+    Fast,
+    /// Try every pair of colors of each block as the endpoints and use the pair
+    /// with the smallest error.
+    #[default]
+    High,
+}
+
+/// Options for [`encode_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncodeOptions {
+    quality: Quality,
+}
+
+impl EncodeOptions {
+    /// Create options with the default [`Quality`].
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the [`Quality`] of the [`Format::Cmpr`] block compression.
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+}
+
+fn luma([r, g, b, _]: [u8; 4]) -> u8 {
+    ((r as u32 * 77 + g as u32 * 150 + b as u32 * 29 + 128) >> 8) as u8
+}
+
+fn to_ia8(color: [u8; 4]) -> u16 { u16::from_be_bytes([color[3], luma(color)]) }
+
+fn to_rgb565([r, g, b, _]: [u8; 4]) -> u16 {
+    ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3)
+}
+
+fn to_rgb5a3([r, g, b, a]: [u8; 4]) -> u16 {
+    let [r, g, b, a] = [r as u16, g as u16, b as u16, a as u16];
+    if a >= 0xE0 {
+        0x8000 | ((r >> 3) << 10) | ((g >> 3) << 5) | (b >> 3)
+    } else {
+        ((a >> 5) << 12) | ((r >> 4) << 8) | ((g >> 4) << 4) | (b >> 4)
+    }
+}
+
+fn distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (*a as i32 - b as i32).pow(2) as u32)
+        .sum()
+}
+
+/// Split `pixels` (row by row) of a `width`×`height` image into the blocks of
+/// `format`, padded with the default value.
+fn tiles<T: Copy + Default>(
+    pixels: &[T],
+    width: usize,
+    height: usize,
+    format: Format,
+) -> impl Iterator<Item = [T; 64]> + '_ {
+    let (block_width, block_height) = format.block_size();
+    let blocks_x = width.div_ceil(block_width);
+    let blocks = blocks_x * height.div_ceil(block_height);
+    (0..blocks).map(move |i| {
+        let mut block = [T::default(); 64];
+        for (j, pixel) in block[..block_width * block_height].iter_mut().enumerate() {
+            let x = (i % blocks_x) * block_width + j % block_width;
+            let y = (i / blocks_x) * block_height + j / block_width;
+            if x < width && y < height {
+                *pixel = pixels[y * width + x];
+            }
+        }
+        block
+    })
+}
+
+/// Encode a 4×4 S3TC block of `pixels` (row by row). Pixels with an alpha
+/// below 0x80 are transparent.
+fn to_cmpr(pixels: &[[u8; 4]], quality: Quality, output: &mut Vec<u8>) {
+    let opaque = pixels
+        .iter()
+        .filter(|x| x[3] >= 0x80)
+        .copied()
+        .collect::<Vec<_>>();
+    let transparent = opaque.len() < pixels.len();
+
+    let mut candidates = Vec::new();
+    if let (Some(min), Some(max)) = (
+        opaque.iter().min_by_key(|x| luma(**x)),
+        opaque.iter().max_by_key(|x| luma(**x)),
+    ) {
+        candidates.push((to_rgb565(*max), to_rgb565(*min)));
+    }
+    if quality == Quality::High {
+        for (i, a) in opaque.iter().enumerate() {
+            for b in opaque[i + 1..].iter() {
+                candidates.push((to_rgb565(*a), to_rgb565(*b)));
+            }
+        }
+    }
+
+    // four colors if `color0 > color1`, otherwise three colors and transparent
+    let order = |(a, b): (u16, u16)| match transparent {
+        false => (a.max(b), a.min(b)),
+        true => (a.min(b), a.max(b)),
+    };
+    // the last color is transparent if `color0 <= color1`
+    let count = |color0: u16, color1: u16| if color0 > color1 { 4 } else { 3 };
+    let error = |(color0, color1): (u16, u16)| -> u32 {
+        let colors = cmpr_colors(color0, color1);
+        opaque
+            .iter()
+            .map(|x| {
+                colors[..count(color0, color1)]
+                    .iter()
+                    .map(|c| distance(*x, *c))
+                    .min()
+                    .unwrap_or(0)
+            })
+            .sum()
+    };
+    let (color0, color1) = candidates
+        .into_iter()
+        .map(order)
+        .min_by_key(|x| error(*x))
+        .unwrap_or((0, 0));
+
+    let colors = cmpr_colors(color0, color1);
+    output.extend_from_slice(&color0.to_be_bytes());
+    output.extend_from_slice(&color1.to_be_bytes());
+    for row in pixels.chunks_exact(4) {
+        let mut indices = 0;
+        for pixel in row {
+            let index = match pixel[3] >= 0x80 {
+                false => 3,
+                true => (0..count(color0, color1))
+                    .min_by_key(|i| distance(*pixel, colors[*i]))
+                    .unwrap_or(0),
+            };
+            indices = (indices << 2) | index as u8;
+        }
+        output.push(indices);
+    }
+}
+
+/// Encode the `block` of `format` (row by row) to `output`.
+fn encode_block(format: Format, block: &[[u8; 4]; 64], quality: Quality, output: &mut Vec<u8>) {
+    match format {
+        Format::I4 => {
+            for pair in block.chunks_exact(2) {
+                output.push((luma(pair[0]) & 0xF0) | (luma(pair[1]) >> 4));
+            }
+        },
+        Format::I8 => output.extend(block[..32].iter().map(|x| luma(*x))),
+        Format::Ia4 => output.extend(block[..32].iter().map(|x| (x[3] & 0xF0) | (luma(*x) >> 4))),
+        Format::Ia8 => {
+            output.extend(block[..16].iter().flat_map(|x| to_ia8(*x).to_be_bytes()));
+        },
+        Format::Rgb565 => {
+            output.extend(block[..16].iter().flat_map(|x| to_rgb565(*x).to_be_bytes()));
+        },
+        Format::Rgb5a3 => {
+            output.extend(block[..16].iter().flat_map(|x| to_rgb5a3(*x).to_be_bytes()));
+        },
+        Format::Rgba8 => {
+            output.extend(block[..16].iter().flat_map(|x| [x[3], x[0]]));
+            output.extend(block[..16].iter().flat_map(|x| [x[1], x[2]]));
+        },
+        Format::Cmpr => {
+            // four 4×4 blocks (top left, top right, bottom left, bottom right)
+            for j in 0..4 {
+                let mut sub = [[0; 4]; 16];
+                for (i, pixel) in sub.iter_mut().enumerate() {
+                    let x = (j % 2) * 4 + i % 4;
+                    let y = (j / 2) * 4 + i / 4;
+                    *pixel = block[y * 8 + x];
+                }
+                to_cmpr(&sub, quality, output);
+            }
+        },
+        Format::C4 | Format::C8 | Format::C14x2 => unreachable!(),
+    }
+}
+
+/// Check that `image` is RGBA8 with `width`×`height` pixels.
+fn check_image(image: &[u8], width: usize, height: usize) -> Result<Vec<[u8; 4]>> {
+    ensure!(
+        image.len() == width * height * 4,
+        BuildProblem::InvalidData("RGBA8 image size does not match", Location::current())
+    );
+    Ok(image
+        .chunks_exact(4)
+        .map(|x| [x[0], x[1], x[2], x[3]])
+        .collect())
+}
+
+/// Encode the RGBA8 `image` (row by row) of `width`×`height` pixels to
+/// `format`, see [`encode_with`].
+pub fn encode(format: Format, image: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+    encode_with(format, image, width, height, &EncodeOptions::default())
+}
+
+/// Encode the RGBA8 `image` (row by row) of `width`×`height` pixels to
+/// `format` with `options`. Intensity formats use the luma of the color. The
+/// palette formats are not supported, use [`encode_indexed`] instead.
+pub fn encode_with(
+    format: Format,
+    image: &[u8],
+    width: usize,
+    height: usize,
+    options: &EncodeOptions,
+) -> Result<Vec<u8>> {
+    ensure!(
+        !format.is_indexed(),
+        BuildProblem::InvalidData("palette format (use encode_indexed)", Location::current())
+    );
+    let pixels = check_image(image, width, height)?;
+
+    let mut data = Vec::with_capacity(format.data_size(width, height));
+    for block in tiles(&pixels, width, height, format) {
+        encode_block(format, &block, options.quality, &mut data);
+    }
+    Ok(data)
+}
+
+/// Reduce the `colors` (and their number of pixels) to at most `count` colors
+/// with median cut.
+fn median_cut(colors: Vec<([u8; 4], usize)>, count: usize) -> Vec<[u8; 4]> {
+    let range = |colors: &[([u8; 4], usize)]| {
+        (0..4)
+            .map(|c| {
+                let (min, max) = colors.iter().fold((0xFF, 0), |(min, max), (x, _)| {
+                    (x[c].min(min), x[c].max(max))
+                });
+                (max.saturating_sub(min), c)
+            })
+            .max()
+            .unwrap_or((0, 0))
+    };
+
+    let mut boxes = vec![colors];
+    while boxes.len() < count {
+        let Some((index, (_, channel))) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.len() > 1)
+            .map(|(i, x)| (i, range(x)))
+            .max_by_key(|(_, x)| x.0)
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_by_key(|(x, _)| x[channel]);
+        let total = colors.iter().map(|x| x.1).sum::<usize>();
+        let mut sum = 0;
+        let median = colors
+            .iter()
+            .position(|(_, n)| {
+                sum += n;
+                sum * 2 >= total
+            })
+            .unwrap_or(0)
+            .clamp(0, colors.len() - 2);
+        let upper = colors.split_off(median + 1);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|colors| {
+            let total = colors.iter().map(|x| x.1).sum::<usize>().max(1);
+            let mut color = [0; 4];
+            for (c, value) in color.iter_mut().enumerate() {
+                let sum = colors.iter().map(|(x, n)| x[c] as usize * n).sum::<usize>();
+                *value = ((sum + total / 2) / total) as u8;
+            }
+            color
+        })
+        .collect()
+}
+
+/// Encode the RGBA8 `image` (row by row) of `width`×`height` pixels to the
+/// palette `format` with a palette of `tlut` colors. Returns the texture data
+/// and the palette (big-endian 16-bit colors). If the image has more colors
+/// than the format can index, the colors are reduced with median cut.
+pub fn encode_indexed(
+    format: Format,
+    tlut: TlutFormat,
+    image: &[u8],
+    width: usize,
+    height: usize,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    ensure!(
+        format.is_indexed(),
+        BuildProblem::InvalidData("not a palette format (use encode)", Location::current())
+    );
+    let pixels = check_image(image, width, height)?;
+
+    // count the colors after conversion to the palette format
+    let values = pixels.iter().map(|x| tlut.encode(*x)).collect::<Vec<_>>();
+    let mut colors = BTreeMap::new();
+    for value in values.iter() {
+        *colors.entry(*value).or_insert(0) += 1;
+    }
+
+    let capacity = TlutFormat::capacity(format);
+    let palette = if colors.len() <= capacity {
+        colors.keys().copied().collect::<Vec<_>>()
+    } else {
+        let colors = colors
+            .iter()
+            .map(|(x, n)| (tlut.decode_color(*x), *n))
+            .collect();
+        median_cut(colors, capacity)
+            .into_iter()
+            .map(|x| tlut.encode(x))
+            .collect()
+    };
+    let decoded = palette
+        .iter()
+        .map(|x| tlut.decode_color(*x))
+        .collect::<Vec<_>>();
+    let indices = colors
+        .keys()
+        .map(|value| {
+            let color = tlut.decode_color(*value);
+            let index = (0..decoded.len())
+                .min_by_key(|i| distance(color, decoded[*i]))
+                .unwrap_or(0);
+            (*value, index as u16)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let indices = values.iter().map(|x| indices[x]).collect::<Vec<_>>();
+
+    let mut data = Vec::with_capacity(format.data_size(width, height));
+    for block in tiles(&indices, width, height, format) {
+        match format {
+            Format::C4 => data.extend(block.chunks_exact(2).map(|x| ((x[0] << 4) | x[1]) as u8)),
+            Format::C8 => data.extend(block[..32].iter().map(|x| *x as u8)),
+            _ => data.extend(block[..16].iter().flat_map(|x| x.to_be_bytes())),
+        }
+    }
+    let tlut = palette.iter().flat_map(|x| x.to_be_bytes()).collect();
+    Ok((data, tlut))
+}
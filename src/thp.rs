@@ -0,0 +1,441 @@
+//! THP videos (`.thp`).
+//!
+//! THP is the video format of the GameCube and Wii SDK. A video consists of a
+//! header, a component table (the video and the optional audio component),
+//! and the frames. Every frame starts with the size of the next and the
+//! previous frame and the size of each component, followed by the data of the
+//! components, i.e., a JPEG image and a block of THP ADPCM audio.
+//!
+//! | Offset | Size | Description                                  |
+//! |--------|------|----------------------------------------------|
+//! | `0x00` | 4    | Magic (`THP\0`)                              |
+//! | `0x04` | 4    | Version (`0x00010000` or `0x00011000`)       |
+//! | `0x08` | 4    | Maximum size of a frame                      |
+//! | `0x0C` | 4    | Maximum number of audio samples of a frame   |
+//! | `0x10` | 4    | Frames per second (`f32`)                    |
+//! | `0x14` | 4    | Number of frames                             |
+//! | `0x18` | 4    | Size of the first frame                      |
+//! | `0x1C` | 4    | Size of all frames                           |
+//! | `0x20` | 4    | Offset of the component table                |
+//! | `0x24` | 4    | Offset of the frame offset table (or `0`)    |
+//! | `0x28` | 4    | Offset of the first frame                    |
+//! | `0x2C` | 4    | Offset of the last frame                     |
+//!
+//! [`ThpReader`] parses the header and the components, and
+//! [`ThpReader::frames`] iterates over the frames.
+//!
+//! # Audio
+//!
+//! The audio block of a frame starts with the size of the data of a channel,
+//! the number of samples, the ADPCM coefficients (8 pairs per channel), and
+//! the initial history of each channel, followed by the data of each channel.
+//! [`decode_audio`] decodes a block to PCM16, [`Frame::decode_audio`] the
+//! block of a frame, and [`ThpReader::decode_audio`] the whole audio track.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let file = File::open("movie.thp")?;
+//!     let mut thp = picori::ThpReader::new(file)?;
+//!     println!(
+//!         "{} frames at {} fps",
+//!         thp.header().frame_count,
+//!         thp.header().fps
+//!     );
+//!     for frame in thp.frames() {
+//!         let frame = frame?;
+//!         std::fs::write(format!("{}.jpg", frame.index), frame.video().unwrap())?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Cursor, Read};
+use std::panic::Location;
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Magic of THP videos (`THP\0`).
+pub const MAGIC: u32 = 0x54485000;
+
+/// THP header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    /// Version, `0x00010000` (1.0) or `0x00011000` (1.1).
+    pub version: u32,
+
+    /// Maximum size of a frame.
+    pub max_buffer_size: u32,
+
+    /// Maximum number of audio samples of a frame.
+    pub max_audio_samples: u32,
+
+    /// Frames per second.
+    pub fps: f32,
+
+    /// Number of frames.
+    pub frame_count: u32,
+
+    /// Size of the first frame.
+    pub first_frame_size: u32,
+
+    /// Size of all frames.
+    pub data_size: u32,
+
+    /// Offset of the component table.
+    pub component_offset: u32,
+
+    /// Offset of the frame offset table, `0` if there is none.
+    pub offsets_offset: u32,
+
+    /// Offset of the first frame.
+    pub first_frame_offset: u32,
+
+    /// Offset of the last frame.
+    pub last_frame_offset: u32,
+}
+
+impl Header {
+    /// Parse THP header.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let magic = input.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected THP", Location::current())
+        );
+
+        let version = input.bu32()?;
+        ensure!(
+            version == 0x00010000 || version == 0x00011000,
+            ParseProblem::UnsupportedVersion(version as usize, Location::current())
+        );
+
+        Ok(Self {
+            version,
+            max_buffer_size: input.bu32()?,
+            max_audio_samples: input.bu32()?,
+            fps: f32::from_bits(input.bu32()?),
+            frame_count: input.bu32()?,
+            first_frame_size: input.bu32()?,
+            data_size: input.bu32()?,
+            component_offset: input.bu32()?,
+            offsets_offset: input.bu32()?,
+            first_frame_offset: input.bu32()?,
+            last_frame_offset: input.bu32()?,
+        })
+    }
+
+    /// Returns `true` for version 1.1, which has additional component
+    /// information.
+    pub fn is_version_1_1(&self) -> bool { self.version == 0x00011000 }
+}
+
+/// Video component information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoInfo {
+    /// Width of the frames.
+    pub width: u32,
+
+    /// Height of the frames.
+    pub height: u32,
+
+    /// Video format (version 1.1), `0` for progressive, `1` and `2` for
+    /// interlaced (odd and even field first).
+    pub format: u32,
+}
+
+/// Audio component information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioInfo {
+    /// Number of channels (1 or 2).
+    pub channels: u32,
+
+    /// Sample rate.
+    pub frequency: u32,
+
+    /// Total number of samples.
+    pub samples: u32,
+
+    /// Number of audio tracks (version 1.1), `1` otherwise.
+    pub tracks: u32,
+}
+
+/// Component of a THP video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    /// JPEG video.
+    Video(VideoInfo),
+
+    /// THP ADPCM audio.
+    Audio(AudioInfo),
+}
+
+/// Frame of a THP video.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Index of the frame.
+    pub index: usize,
+
+    /// Data of each component, in the order of [`ThpReader::components`].
+    pub components: Vec<(Component, Vec<u8>)>,
+}
+
+impl Frame {
+    /// JPEG image of the frame.
+    pub fn video(&self) -> Option<&[u8]> {
+        self.components
+            .iter()
+            .find_map(|(component, data)| match component {
+                Component::Video(_) => Some(data.as_slice()),
+                _ => None,
+            })
+    }
+
+    /// Audio block of the frame.
+    pub fn audio(&self) -> Option<&[u8]> {
+        self.components
+            .iter()
+            .find_map(|(component, data)| match component {
+                Component::Audio(_) => Some(data.as_slice()),
+                _ => None,
+            })
+    }
+
+    /// Decode the audio block of the frame to PCM16 samples (interleaved), see
+    /// [`decode_audio`]. Returns [`None`] if the video has no audio.
+    pub fn decode_audio(&self) -> Result<Option<Vec<i16>>> {
+        self.components
+            .iter()
+            .find_map(|(component, data)| match component {
+                Component::Audio(audio) => Some(decode_audio(data, audio.channels as usize)),
+                _ => None,
+            })
+            .transpose()
+    }
+}
+
+/// THP video reader.
+pub struct ThpReader<D: Parser + Seeker> {
+    reader:     D,
+    base:       u64,
+    header:     Header,
+    components: Vec<Component>,
+}
+
+impl<D: Parser + Seeker> ThpReader<D> {
+    /// Creates a new THP reader.
+    pub fn new(mut reader: D) -> Result<Self> {
+        let base = reader.position()?;
+        let header = Header::from_binary(&mut reader)?;
+
+        reader.goto(base + header.component_offset as u64)?;
+        let count = reader.bu32()? as usize;
+        let types = reader.u8_array::<16>()?;
+        ensure!(
+            count <= types.len(),
+            ParseProblem::InvalidRange("component count", Location::current())
+        );
+
+        let mut components = Vec::with_capacity(count);
+        for kind in types.iter().take(count) {
+            let component = match kind {
+                0 => Component::Video(VideoInfo {
+                    width:  reader.bu32()?,
+                    height: reader.bu32()?,
+                    format: match header.is_version_1_1() {
+                        true => reader.bu32()?,
+                        false => 0,
+                    },
+                }),
+                1 => Component::Audio(AudioInfo {
+                    channels:  reader.bu32()?,
+                    frequency: reader.bu32()?,
+                    samples:   reader.bu32()?,
+                    tracks:    match header.is_version_1_1() {
+                        true => reader.bu32()?,
+                        false => 1,
+                    },
+                }),
+                _ => Err(ParseProblem::InvalidData(
+                    "unknown component type",
+                    Location::current(),
+                ))?,
+            };
+            components.push(component);
+        }
+
+        Ok(Self {
+            reader,
+            base,
+            header,
+            components,
+        })
+    }
+
+    /// Get reference to the [`Header`].
+    pub fn header(&self) -> &Header { &self.header }
+
+    /// Get the components of the video.
+    pub fn components(&self) -> &[Component] { &self.components }
+
+    /// Get the video component information.
+    pub fn video(&self) -> Option<&VideoInfo> {
+        self.components.iter().find_map(|x| match x {
+            Component::Video(video) => Some(video),
+            _ => None,
+        })
+    }
+
+    /// Get the audio component information.
+    pub fn audio(&self) -> Option<&AudioInfo> {
+        self.components.iter().find_map(|x| match x {
+            Component::Audio(audio) => Some(audio),
+            _ => None,
+        })
+    }
+
+    /// Decode the audio of all frames to PCM16 samples (interleaved), e.g., to
+    /// write a WAV file with the [channels and sample rate][`AudioInfo`] of the
+    /// audio component. Returns an empty vector if the video has no audio.
+    pub fn decode_audio(&mut self) -> Result<Vec<i16>> {
+        let mut samples = Vec::new();
+        for frame in self.frames() {
+            if let Some(audio) = frame?.decode_audio()? {
+                samples.extend(audio);
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Iterate over the frames, from the first to the last frame.
+    pub fn frames(&mut self) -> Frames<'_, D> {
+        Frames {
+            offset: self.header.first_frame_offset as u64,
+            size:   self.header.first_frame_size as u64,
+            index:  0,
+            thp:    self,
+        }
+    }
+
+    /// Read the frame at `offset` (from the start of the video) of `size`
+    /// bytes. Returns the frame and the size of the next frame.
+    fn read_frame(&mut self, index: usize, offset: u64, size: u64) -> Result<(Frame, u64)> {
+        self.reader.goto(self.base + offset)?;
+        let next_size = self.reader.bu32()? as u64;
+        let _previous_size = self.reader.bu32()?;
+        let sizes = (0..self.components.len())
+            .map(|_| self.reader.bu32())
+            .collect::<Result<Vec<_>>>()?;
+
+        let header_size = 8 + 4 * sizes.len() as u64;
+        let data_size = sizes.iter().map(|x| *x as u64).sum::<u64>();
+        ensure!(
+            header_size + data_size <= size,
+            ParseProblem::InvalidRange("frame component sizes", Location::current())
+        );
+
+        let mut components = Vec::with_capacity(sizes.len());
+        for (component, size) in self.components.iter().zip(sizes) {
+            // the sizes are not trusted, the data is read without allocating it
+            // first
+            let mut data = Vec::new();
+            self.reader
+                .by_ref()
+                .take(size as u64)
+                .read_to_end(&mut data)?;
+            ensure!(
+                data.len() == size as usize,
+                ParseProblem::InvalidRange("frame component (truncated)", Location::current())
+            );
+            components.push((*component, data));
+        }
+
+        Ok((Frame { index, components }, next_size))
+    }
+}
+
+/// Iterator over the [frames][`Frame`] of a [`ThpReader`].
+pub struct Frames<'x, D: Parser + Seeker> {
+    thp:    &'x mut ThpReader<D>,
+    offset: u64,
+    size:   u64,
+    index:  usize,
+}
+
+impl<D: Parser + Seeker> Iterator for Frames<'_, D> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.thp.header.frame_count as usize {
+            return None;
+        }
+
+        match self.thp.read_frame(self.index, self.offset, self.size) {
+            Ok((frame, next_size)) => {
+                self.offset += self.size;
+                self.size = next_size;
+                self.index += 1;
+                Some(Ok(frame))
+            },
+            Err(error) => {
+                // stop after the first error
+                self.index = self.thp.header.frame_count as usize;
+                Some(Err(error))
+            },
+        }
+    }
+}
+
+/// Decode the THP ADPCM audio `block` of a frame with `channels` channels (1
+/// or 2) to PCM16 samples (interleaved). If the video has multiple audio
+/// tracks, the first track is decoded.
+pub fn decode_audio(block: &[u8], channels: usize) -> Result<Vec<i16>> {
+    ensure!(
+        channels == 1 || channels == 2,
+        ParseProblem::InvalidRange("audio channels (expected 1 or 2)", Location::current())
+    );
+
+    let mut input = Cursor::new(block);
+    let channel_size = input.bu32()? as usize;
+    let samples = input.bu32()? as usize;
+    let coefficients = [input.bu16_array::<16>()?, input.bu16_array::<16>()?];
+    let history = input.bu16_array::<4>()?;
+    let data = &block[0x50..];
+    ensure!(
+        channel_size * channels <= data.len(),
+        ParseProblem::InvalidRange("audio channel size", Location::current())
+    );
+    ensure!(
+        samples <= channel_size / 8 * 14,
+        ParseProblem::InvalidRange("audio sample count", Location::current())
+    );
+
+    // every 8 bytes are a header (coefficient index and scale) and 14 samples
+    let mut output = vec![0; samples * channels];
+    for channel in 0..channels {
+        let coefficients = coefficients[channel].map(|x| x as i16 as i32);
+        let mut hist1 = history[channel * 2] as i16 as i32;
+        let mut hist2 = history[channel * 2 + 1] as i16 as i32;
+        let data = &data[channel * channel_size..(channel + 1) * channel_size];
+        for i in 0..samples {
+            let frame = &data[i / 14 * 8..];
+            let index = ((frame[0] >> 4) & 0x07) as usize;
+            let scale = frame[0] & 0x0F;
+            let byte = frame[1 + (i % 14) / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+            let nibble = (((nibble << 4) as i8) >> 4) as i32;
+
+            let prediction = hist1 * coefficients[index * 2] + hist2 * coefficients[index * 2 + 1];
+            let sample = ((prediction >> 11) + (nibble << scale)).clamp(-0x8000, 0x7FFF);
+            hist2 = hist1;
+            hist1 = sample;
+            output[i * channels + channel] = sample as i16;
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,802 @@
+//! [U8][`crate::u8arc`] archives.
+//!
+//! [U8][`crate::u8arc`] is the archive format used by the Wii system software
+//! and many Wii games, e.g., for banners (`opening.bnr`), channel assets, and
+//! game data. Archives usually have the extension `.arc` or `.app` and are
+//! sometimes [Yaz0][`crate::yaz0`] compressed.
+//!
+//! An archive consists of a header, a node table (files and directories), a
+//! string table, and the file data. Directories are stored depth-first, i.e.,
+//! the children of a directory directly follow the directory node.
+//! [`U8Reader`] exposes the archive with the same directory tree API as
+//! [`RarcReader`][`crate::RarcReader`], see [`U8Reader::files`] and
+//! [`U8Reader::find`]. Archives are written with [`U8Builder`] and extracted
+//! to a directory with [`U8Reader::extract_to`] (and packed again with
+//! [`pack_dir`]).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let file = File::open("opening.bnr")?;
+//!     let mut u8arc = picori::U8Reader::new(file)?;
+//!     for (path, entry) in u8arc.files() {
+//!         println!("{}: {:?}", path.display(), entry);
+//!     }
+//!     let _ = u8arc.read("meta/banner.bin")?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::tree::Folder;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Ascii, Result};
+
+/// [U8][`crate::u8arc`] magic number.
+pub(crate) const MAGIC: u32 = 0x55AA382D;
+
+/// Size of a node in the node table.
+pub(crate) const NODE_SIZE: u32 = 12;
+
+/// Node type of files.
+pub(crate) const NODE_FILE: u8 = 0;
+
+/// Node type of directories.
+pub(crate) const NODE_DIRECTORY: u8 = 1;
+
+/// [U8][`crate::u8arc`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Offset of the root node (relative to the start of the archive).
+    pub root_offset: u32,
+
+    /// Size of the node table and string table.
+    pub header_size: u32,
+
+    /// Offset of the file data (relative to the start of the archive).
+    pub data_offset: u32,
+}
+
+/// Enum variant of a single [U8][`crate::u8arc`] entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    /// Root directory.
+    Root,
+    /// File.
+    File {
+        /// Relative filename.
+        name:   String,
+        /// File offset from the beginning of the [U8][`crate::u8arc`] file.
+        offset: u64,
+        /// File size.
+        size:   u32,
+    },
+    /// Directory.
+    Directory {
+        /// Relative directory name.
+        name:   String,
+        /// `internal`: Index of the parent directory node.
+        parent: u32,
+        /// `internal`: Index of the node following the last node in this
+        /// directory.
+        next:   u32,
+    },
+}
+
+struct U8Node {
+    name:  String,
+    entry: Entry,
+}
+
+pub struct U8Reader<Reader> {
+    reader: Reader,
+    base:   u64,
+    header: Header,
+    nodes:  Vec<U8Node>,
+}
+
+impl<Reader: Parser + Seeker> U8Reader<Reader> {
+    /// Creates a new U8 reader.
+    pub fn new(mut reader: Reader) -> Result<Self> {
+        let base = reader.position()?;
+
+        let magic = reader.bu32()?;
+        let root_offset = reader.bu32()?;
+        let header_size = reader.bu32()?;
+        let data_offset = reader.bu32()?;
+
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("invalid U8 magic", Location::current())
+        );
+
+        ensure!(
+            header_size < 0x1000000,
+            ParseProblem::InvalidHeader("invalid header size", Location::current())
+        );
+
+        reader.goto(base + root_offset as u64)?;
+        let table = reader.read_as_vec(header_size as usize)?;
+        ensure!(
+            table.len() >= NODE_SIZE as usize && table[0] == NODE_DIRECTORY,
+            ParseProblem::InvalidHeader("invalid root node", Location::current())
+        );
+
+        let node_count = u32::from_be_bytes(table[8..12].try_into().unwrap());
+        let string_table = node_count
+            .checked_mul(NODE_SIZE)
+            .and_then(|x| table.get(x as usize..));
+        ensure!(
+            node_count > 0 && string_table.is_some(),
+            ParseProblem::InvalidHeader("invalid node count", Location::current())
+        );
+        let string_table = string_table.unwrap();
+
+        let mut nodes = Vec::with_capacity(node_count as usize);
+        // stack of the `next` index of the directories containing the current node
+        let mut directories = vec![node_count];
+        for (i, data) in table
+            .chunks_exact(NODE_SIZE as usize)
+            .take(node_count as usize)
+            .enumerate()
+        {
+            let i = i as u32;
+            let kind = data[0];
+            let name_offset = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+            let offset = u32::from_be_bytes(data[4..8].try_into().unwrap());
+            let size = u32::from_be_bytes(data[8..12].try_into().unwrap());
+
+            while directories.len() > 1 && i >= *directories.last().unwrap() {
+                directories.pop();
+            }
+
+            if i == 0 {
+                nodes.push(U8Node {
+                    name:  String::new(),
+                    entry: Entry::Root,
+                });
+                continue;
+            }
+
+            ensure!(
+                (name_offset as usize) < string_table.len(),
+                ParseProblem::InvalidData("invalid string table offset", Location::current())
+            );
+            let name = Ascii::first(&string_table[name_offset as usize..])?;
+
+            let entry = match kind {
+                NODE_FILE => Entry::File {
+                    name: name.clone(),
+                    offset: base + offset as u64,
+                    size,
+                },
+                NODE_DIRECTORY => {
+                    ensure!(
+                        offset < i && size > i && size <= *directories.last().unwrap(),
+                        ParseProblem::InvalidData("invalid directory node", Location::current())
+                    );
+                    directories.push(size);
+                    Entry::Directory {
+                        name:   name.clone(),
+                        parent: offset,
+                        next:   size,
+                    }
+                },
+                _ => {
+                    return Err(
+                        ParseProblem::InvalidData("invalid node type", Location::current()).into(),
+                    )
+                },
+            };
+            nodes.push(U8Node { name, entry });
+        }
+
+        Ok(Self {
+            reader,
+            base,
+            header: Header {
+                root_offset,
+                header_size,
+                data_offset,
+            },
+            nodes,
+        })
+    }
+
+    /// Get the [U8][`crate::u8arc`] header.
+    pub fn header(&self) -> &Header { &self.header }
+
+    /// Get the data for a file.
+    pub fn file_data(&mut self, offset: u64, size: u32) -> Result<Vec<u8>> {
+        self.reader.goto(offset)?;
+        // the size is not trusted, the data is read without allocating it first
+        let mut result = Vec::new();
+        self.reader
+            .by_ref()
+            .take(size as u64)
+            .read_to_end(&mut result)?;
+        ensure!(
+            result.len() == size as usize,
+            ParseProblem::InvalidRange("file data (truncated)", Location::current())
+        );
+        Ok(result)
+    }
+
+    /// Get an iterator over all [`Entry`]s (in depth-first order). The paths
+    /// are relative to the root directory.
+    pub fn files(&self) -> Files<'_, Reader> {
+        Files {
+            parent: self,
+            index:  0,
+            stack:  vec![],
+            path:   PathBuf::new(),
+        }
+    }
+
+    /// Find the [`Entry`] with the given path, e.g., `meta/banner.bin`. The
+    /// comparison is case-insensitive and a leading `/` is ignored.
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<Entry> {
+        let path = path.as_ref();
+        let path = path.strip_prefix("/").unwrap_or(path);
+        self.files()
+            .find(|(x, _)| {
+                x.components().count() == path.components().count()
+                    && x.components()
+                        .zip(path.components())
+                        .all(|(a, b)| a.as_os_str().eq_ignore_ascii_case(b.as_os_str()))
+            })
+            .map(|(_, entry)| entry)
+    }
+
+    /// Read the data of the file at `path` (see [`U8Reader::find`]). Returns
+    /// [`None`] if there is no such file.
+    pub fn read<P: AsRef<Path>>(&mut self, path: P) -> Result<Option<Vec<u8>>> {
+        match self.find(path) {
+            Some(Entry::File { offset, size, .. }) => Ok(Some(self.file_data(offset, size)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Iterator over all [`Entry`]s in a [`U8Reader`].
+pub struct Files<'parent, Reader> {
+    parent: &'parent U8Reader<Reader>,
+    index:  usize,
+    /// Stack of the `next` index of the directories in `path`.
+    stack:  Vec<u32>,
+    path:   PathBuf,
+}
+
+impl<'parent, T: Parser + Seeker> Iterator for Files<'parent, T> {
+    type Item = (PathBuf, Entry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.parent.nodes.get(self.index)?;
+        while self.stack.last().is_some_and(|x| self.index as u32 >= *x) {
+            self.stack.pop();
+            self.path.pop();
+        }
+        self.index += 1;
+
+        match &node.entry {
+            Entry::Root => Some((PathBuf::new(), Entry::Root)),
+            Entry::File { .. } => Some((self.path.join(&node.name), node.entry.clone())),
+            Entry::Directory { next, .. } => {
+                self.stack.push(*next);
+                self.path.push(&node.name);
+                Some((self.path.clone(), node.entry.clone()))
+            },
+        }
+    }
+}
+
+/// Default alignment of the file data in archives built by [`U8Builder`].
+pub const DEFAULT_ALIGNMENT: u32 = 0x20;
+
+/// File in a [`U8Builder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct U8File {
+    /// Name of the file.
+    pub name: String,
+    /// File data.
+    pub data: Vec<u8>,
+}
+
+/// Directory in a [`U8Builder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct U8Folder {
+    /// Name of the directory.
+    pub name:     String,
+    /// Files and subdirectories (in the order they are stored).
+    pub children: Vec<U8Item>,
+}
+
+/// Child of a [`U8Folder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum U8Item {
+    /// File.
+    File(U8File),
+    /// Directory.
+    Folder(U8Folder),
+}
+
+impl U8Item {
+    /// Name of the file or directory.
+    pub fn name(&self) -> &str {
+        match self {
+            U8Item::File(file) => &file.name,
+            U8Item::Folder(folder) => &folder.name,
+        }
+    }
+}
+
+impl U8Folder {
+    /// Create an empty directory.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name:     name.into(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Get the file or directory at `path` (relative to this directory). The
+    /// comparison is case-insensitive.
+    pub fn get_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut U8Item> {
+        self.find_mut(path.as_ref())
+    }
+}
+
+impl Folder for U8Folder {
+    type File = U8File;
+    type Item = U8Item;
+
+    fn children_mut(&mut self) -> &mut Vec<U8Item> { &mut self.children }
+
+    fn item_name(item: &U8Item) -> &str { item.name() }
+
+    fn set_item_name(item: &mut U8Item, name: String) {
+        match item {
+            U8Item::File(file) => file.name = name,
+            U8Item::Folder(folder) => folder.name = name,
+        }
+    }
+
+    fn folder_item(name: &str) -> U8Item { U8Item::Folder(U8Folder::new(name)) }
+
+    fn as_folder(item: &mut U8Item) -> Option<&mut U8Folder> {
+        match item {
+            U8Item::Folder(folder) => Some(folder),
+            U8Item::File(_) => None,
+        }
+    }
+
+    fn as_file(item: &mut U8Item) -> Option<&mut U8File> {
+        match item {
+            U8Item::File(file) => Some(file),
+            U8Item::Folder(_) => None,
+        }
+    }
+}
+
+/// [U8][`crate::u8arc`] archive builder.
+///
+/// The node table directly follows the header and the file data starts at the
+/// first aligned offset after the string table. Each file is aligned to the
+/// data alignment (see [`U8Builder::alignment`]).
+#[derive(Debug, Clone)]
+pub struct U8Builder {
+    root:      U8Folder,
+    alignment: u32,
+}
+
+impl Default for U8Builder {
+    fn default() -> Self { Self::new() }
+}
+
+impl U8Builder {
+    /// Create a builder for an empty archive.
+    pub fn new() -> Self {
+        Self {
+            root:      U8Folder::new(""),
+            alignment: DEFAULT_ALIGNMENT,
+        }
+    }
+
+    /// Create a builder containing all files and directories of `reader`.
+    pub fn from_u8<R: Parser + Seeker>(reader: &mut U8Reader<R>) -> Result<Self> {
+        let mut builder = Self::new();
+        // stack of (`next` index, directory)
+        let mut stack: Vec<(u32, U8Folder)> = Vec::new();
+        let entries = reader.files().map(|(_, entry)| entry).collect::<Vec<_>>();
+        for (i, entry) in entries.into_iter().enumerate().skip(1) {
+            while stack.last().is_some_and(|x| i as u32 >= x.0) {
+                let (_, folder) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(parent) => parent.1.children.push(U8Item::Folder(folder)),
+                    None => builder.root.children.push(U8Item::Folder(folder)),
+                }
+            }
+
+            let children = match stack.last_mut() {
+                Some(parent) => &mut parent.1.children,
+                None => &mut builder.root.children,
+            };
+            match entry {
+                Entry::File { name, offset, size } => {
+                    let data = reader.file_data(offset, size)?;
+                    children.push(U8Item::File(U8File { name, data }));
+                },
+                Entry::Directory { name, next, .. } => stack.push((next, U8Folder::new(name))),
+                Entry::Root => {},
+            }
+        }
+        while let Some((_, folder)) = stack.pop() {
+            match stack.last_mut() {
+                Some(parent) => parent.1.children.push(U8Item::Folder(folder)),
+                None => builder.root.children.push(U8Item::Folder(folder)),
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Get the root directory.
+    pub fn root(&self) -> &U8Folder { &self.root }
+
+    /// Get a mutable reference to the root directory.
+    pub fn root_mut(&mut self) -> &mut U8Folder { &mut self.root }
+
+    /// Set the alignment of the file data (default: [`DEFAULT_ALIGNMENT`]).
+    /// The alignment must be a power of two.
+    pub fn alignment(&mut self, alignment: u32) -> &mut Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Get the file or directory at `path` (see [`U8Folder::get_mut`]).
+    pub fn get_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut U8Item> {
+        self.root.get_mut(path)
+    }
+
+    /// Add the directory at `path` (relative to the root directory), creating
+    /// missing parent directories. Returns the existing directory if there is
+    /// one.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, path: P) -> &mut U8Folder {
+        self.root.add_directory(path.as_ref())
+    }
+
+    /// Replace the data of the existing file at `path`.
+    pub fn replace_file<P: AsRef<Path>>(&mut self, path: P, data: Vec<u8>) -> Result<&mut U8File> {
+        let file = self.root.file_mut(path.as_ref())?;
+        file.data = data;
+        Ok(file)
+    }
+
+    /// Remove the file or directory (including its contents) at `path`.
+    /// Returns the removed entry.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<U8Item> {
+        self.root.remove(path.as_ref())
+    }
+
+    /// Rename the file or directory at `path` to `name` (keeping its position
+    /// in the directory).
+    pub fn rename<P: AsRef<Path>>(&mut self, path: P, name: &str) -> Result<()> {
+        self.root.rename(path.as_ref(), name)
+    }
+
+    /// Add (or replace) the file at `path` (relative to the root directory),
+    /// creating missing directories.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, data: Vec<u8>) -> &mut U8File {
+        self.root
+            .add_file(path.as_ref(), |name| U8Item::File(U8File { name, data }))
+    }
+
+    /// Build the archive and write it to `output`.
+    pub fn build<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.alignment.is_power_of_two(),
+            BuildProblem::InvalidData("alignment must be a power of two", Location::current())
+        );
+
+        // flatten the tree depth-first, directories store the index of their
+        // parent and of the node following their last node
+        enum Flat<'a> {
+            File(&'a U8File),
+            Folder(&'a U8Folder, u32, u32),
+        }
+        fn visit<'a>(nodes: &mut Vec<Flat<'a>>, folder: &'a U8Folder, parent: u32) {
+            let index = nodes.len();
+            nodes.push(Flat::Folder(folder, parent, 0));
+            for child in folder.children.iter() {
+                match child {
+                    U8Item::File(file) => nodes.push(Flat::File(file)),
+                    U8Item::Folder(child) => visit(nodes, child, index as u32),
+                }
+            }
+            let end = nodes.len() as u32;
+            if let Flat::Folder(_, _, next) = &mut nodes[index] {
+                *next = end;
+            }
+        }
+        let mut nodes = Vec::new();
+        visit(&mut nodes, &self.root, 0);
+
+        let mut strings = vec![0_u8];
+        let mut name_offsets = vec![0_u32];
+        for node in nodes.iter().skip(1) {
+            let name = match node {
+                Flat::File(file) => &file.name,
+                Flat::Folder(folder, ..) => &folder.name,
+            };
+            ensure!(
+                name.is_ascii() && !name.contains('\0'),
+                BuildProblem::InvalidData("invalid name", Location::current())
+            );
+            name_offsets.push(strings.len() as u32);
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+        }
+        ensure!(
+            strings.len() < 0x1000000,
+            BuildProblem::InvalidData("string table too large", Location::current())
+        );
+
+        let alignment = self.alignment as u64;
+        let align = |x: u64| (x + alignment - 1) & !(alignment - 1);
+        let root_offset = 0x20_u64;
+        let header_size = nodes.len() as u64 * NODE_SIZE as u64 + strings.len() as u64;
+        let data_offset = align(root_offset + header_size);
+        let mut offset = data_offset;
+        let mut file_offsets = Vec::with_capacity(nodes.len());
+        for node in nodes.iter() {
+            file_offsets.push(offset);
+            if let Flat::File(file) = node {
+                offset = align(offset + file.data.len() as u64);
+            }
+        }
+        ensure!(
+            offset <= u32::MAX as u64,
+            BuildProblem::InvalidData("archive too large", Location::current())
+        );
+
+        output.bu32(MAGIC)?;
+        output.bu32(root_offset as u32)?;
+        output.bu32(header_size as u32)?;
+        output.bu32(data_offset as u32)?;
+        output.u8_array(&[0; 16])?;
+        for (i, node) in nodes.iter().enumerate() {
+            let (kind, offset, size) = match node {
+                Flat::File(file) => (NODE_FILE, file_offsets[i] as u32, file.data.len() as u32),
+                Flat::Folder(_, parent, next) => (NODE_DIRECTORY, *parent, *next),
+            };
+            output.bu32((kind as u32) << 24 | name_offsets[i])?;
+            output.bu32(offset)?;
+            output.bu32(size)?;
+        }
+        output.u8_array(&strings)?;
+        let mut position = root_offset + header_size;
+        for (i, node) in nodes.iter().enumerate() {
+            if let Flat::File(file) = node {
+                output.u8_array(&vec![0; (file_offsets[i] - position) as usize])?;
+                output.u8_array(&file.data)?;
+                position = file_offsets[i] + file.data.len() as u64;
+            }
+        }
+        output.u8_array(&vec![0; (offset - position) as usize])?;
+
+        Ok(())
+    }
+}
+
+/// Name of the metadata sidecar written by [`U8Reader::extract_to`]. The
+/// sidecar stores the order of the entries and the data alignment so the
+/// archive can be rebuilt identically.
+pub const METADATA_FILE: &str = ".u8";
+
+/// Largest data alignment detected by [`U8Reader::extract_to`].
+const MAX_ALIGNMENT: u64 = 0x8000;
+
+/// Check that `name` is a single normal path component, i.e., it can not
+/// escape the directory it is extracted to.
+fn is_safe_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(std::path::Component::Normal(x)) if x == name)
+        && components.next().is_none()
+}
+
+impl<Reader: Parser + Seeker> U8Reader<Reader> {
+    /// Extract all files and directories to the directory `path`. If
+    /// `metadata` is `true`, the sidecar [`METADATA_FILE`] is written to
+    /// `path` as well.
+    pub fn extract_to<P: AsRef<Path>>(&mut self, path: P, metadata: bool) -> Result<()> {
+        let root = path.as_ref();
+        fs::create_dir_all(root)?;
+
+        // the data alignment is the largest power of two all file offsets are
+        // aligned to
+        let mut alignment = None;
+        let mut lines = Vec::new();
+        let entries = self.files().collect::<Vec<_>>();
+        for (path, entry) in entries {
+            let name = match &entry {
+                Entry::Root => continue,
+                Entry::File { name, .. } | Entry::Directory { name, .. } => name,
+            };
+            ensure!(
+                is_safe_name(name),
+                ParseProblem::InvalidData("invalid file name", Location::current())
+            );
+
+            let display = path.to_string_lossy().replace('\\', "/");
+            match entry {
+                Entry::File { offset, size, .. } => {
+                    let data = self.file_data(offset, size)?;
+                    fs::write(root.join(&path), data)?;
+                    let aligned = 1_u64 << (offset - self.base).trailing_zeros().min(15);
+                    alignment = Some(alignment.unwrap_or(MAX_ALIGNMENT).min(aligned));
+                    lines.push(format!("file {}", display));
+                },
+                _ => {
+                    fs::create_dir_all(root.join(&path))?;
+                    lines.push(format!("dir {}", display));
+                },
+            }
+        }
+
+        if metadata {
+            let alignment = alignment.unwrap_or(DEFAULT_ALIGNMENT as u64);
+            let mut text = format!("alignment {:#x}\n", alignment);
+            for line in lines {
+                text.push_str(&line);
+                text.push('\n');
+            }
+            fs::write(root.join(METADATA_FILE), text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for [`pack_dir`] and [`U8Builder::from_dir`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    metadata:  bool,
+    alignment: Option<u32>,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self {
+            metadata:  true,
+            alignment: None,
+        }
+    }
+}
+
+impl PackOptions {
+    /// Use the sidecar [`METADATA_FILE`] if it exists (default: `true`).
+    pub fn metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Data alignment, overrides the alignment from the sidecar (default: the
+    /// alignment from the sidecar or [`DEFAULT_ALIGNMENT`]).
+    pub fn alignment(mut self, alignment: u32) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+}
+
+/// Parsed metadata sidecar (see [`METADATA_FILE`]). Paths are relative to the
+/// root directory and use `/` as separator.
+#[derive(Default)]
+struct Sidecar {
+    alignment: Option<u32>,
+    order:     HashMap<String, usize>,
+}
+
+impl Sidecar {
+    fn parse(text: &str) -> Result<Self> {
+        let invalid = || ParseProblem::InvalidData("invalid metadata", Location::current());
+        let mut sidecar = Sidecar::default();
+        for line in text
+            .lines()
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+        {
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+            match key {
+                "alignment" => {
+                    let alignment = u32::from_str_radix(value.trim_start_matches("0x"), 16)
+                        .map_err(|_| invalid())?;
+                    sidecar.alignment = Some(alignment);
+                },
+                "dir" | "file" => {
+                    sidecar.order.insert(value.to_string(), sidecar.order.len());
+                },
+                _ => return Err(invalid().into()),
+            }
+        }
+        Ok(sidecar)
+    }
+}
+
+impl U8Builder {
+    /// Create a builder containing all files and directories in the directory
+    /// `path`, e.g., a directory written by [`U8Reader::extract_to`]. If the
+    /// sidecar [`METADATA_FILE`] exists (and is enabled in `options`), the
+    /// data alignment and the order of the entries are restored from it.
+    /// Otherwise, the entries are sorted by name.
+    pub fn from_dir<P: AsRef<Path>>(path: P, options: PackOptions) -> Result<Self> {
+        let root = path.as_ref();
+        let sidecar_path = root.join(METADATA_FILE);
+        let sidecar = if options.metadata && sidecar_path.is_file() {
+            Sidecar::parse(&fs::read_to_string(sidecar_path)?)?
+        } else {
+            Sidecar::default()
+        };
+
+        let mut builder = Self::new();
+        builder.alignment = options
+            .alignment
+            .or(sidecar.alignment)
+            .unwrap_or(DEFAULT_ALIGNMENT);
+        load_folder(&mut builder.root, root, "", &sidecar)?;
+        Ok(builder)
+    }
+}
+
+fn load_folder(
+    folder: &mut U8Folder,
+    directory: &Path,
+    path: &str,
+    sidecar: &Sidecar,
+) -> Result<()> {
+    let join = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", path, name)
+        }
+    };
+
+    let mut entries = fs::read_dir(directory)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.retain(|x| !path.is_empty() || x.file_name() != METADATA_FILE);
+    entries.sort_by_cached_key(|x| {
+        let name = x.file_name().to_string_lossy().into_owned();
+        let order = sidecar.order.get(&join(&name)).copied();
+        (order.unwrap_or(usize::MAX), name.to_lowercase())
+    });
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.metadata()?.is_dir() {
+            let path = join(&name);
+            let mut child = U8Folder::new(name);
+            load_folder(&mut child, &entry.path(), &path, sidecar)?;
+            folder.children.push(U8Item::Folder(child));
+        } else {
+            folder.children.push(U8Item::File(U8File {
+                name,
+                data: fs::read(entry.path())?,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Pack the directory `path` into a [U8][`crate::u8arc`] archive (see
+/// [`U8Builder::from_dir`]).
+pub fn pack_dir<P: AsRef<Path>>(path: P, options: PackOptions) -> Result<Vec<u8>> {
+    let mut output = std::io::Cursor::new(Vec::new());
+    U8Builder::from_dir(path, options)?.build(&mut output)?;
+    Ok(output.into_inner())
+}
@@ -0,0 +1,324 @@
+//! [UTF-16 BE][`Utf16Be`] encoding.
+//!
+//! [UTF-16 BE][`Utf16Be`] encodes characters as one or two big-endian 16-bit
+//! units and is used by, e.g., the comments of Wii banners and save files.
+//! Characters above `U+FFFF` are encoded as a surrogate pair, a high surrogate
+//! (`[0xD800,0xDBFF]`) followed by a low surrogate (`[0xDC00,0xDFFF]`). A
+//! surrogate that is not part of a pair is invalid and will cause an
+//! [`InvalidCodePoint`][`crate::error::DecodingProblem::InvalidCodePoint`] to
+//! be returned.
+//!
+//! # Examples
+//!
+//! ```
+//! # use picori::{Result, Utf16Be};
+//! fn main() -> Result<()> {
+//!     let data = Utf16Be::encode("Wii 🎮");
+//!     assert_eq!(data, b"\x00W\x00i\x00i\x00 \xD8\x3C\xDF\xAE");
+//!     assert_eq!(Utf16Be::all(&data)?, "Wii 🎮");
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{BufReader, Read};
+
+use crate::encoding::{CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy};
+use crate::error::DecodingProblem::*;
+#[cfg(feature = "std")]
+use crate::error::EncodingProblem::*;
+use crate::helper::ProblemLocation;
+#[cfg(feature = "std")]
+use crate::helper::{ensure, ParseStringEncoding, Parser};
+use crate::Result;
+
+/// [UTF-16 BE][`Utf16Be`] encoding.
+pub struct Utf16Be {}
+
+/// A iterator decoder for the [`Utf16Be`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:     Tracked<<I as IntoIterator>::IntoIter>,
+    buffered: Option<u16>,
+    _marker:  PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:     Tracked::new(iter.into_iter()),
+            buffered: None,
+            _marker:  PhantomData,
+        }
+    }
+
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
+    /// Read the next 16-bit unit, or [`None`] at the end of the input.
+    fn next_unit(&mut self) -> Option<Result<u16>> {
+        if let Some(unit) = self.buffered.take() {
+            return Some(Ok(unit));
+        }
+
+        let high = *self.iter.next()?.borrow();
+        Some(match self.iter.next() {
+            Some(low) => Ok(u16::from_be_bytes([high, *low.borrow()])),
+            None => Err(UnexpectedEndOfData(Location::current()).into()),
+        })
+    }
+
+    fn decode_next(&mut self) -> Option<Result<char>> {
+        let unit = match self.next_unit()? {
+            Ok(unit) => unit,
+            Err(e) => return Some(Err(e)),
+        };
+
+        Some(match unit {
+            // High surrogate, must be followed by a low surrogate
+            0xD800..=0xDBFF => match self.next_unit() {
+                Some(Ok(low @ 0xDC00..=0xDFFF)) => {
+                    let value = 0x10000 + (((unit as u32) & 0x3FF) << 10 | (low as u32) & 0x3FF);
+                    Ok(char::from_u32(value).unwrap())
+                },
+                Some(Ok(next)) => {
+                    // the next unit is not part of the invalid character
+                    self.buffered = Some(next);
+                    Err(InvalidCodePoint(unit as usize, Location::current()).into())
+                },
+                Some(Err(e)) => Err(e),
+                None => Err(UnexpectedEndOfData(Location::current()).into()),
+            },
+            // Low surrogate without a high surrogate
+            0xDC00..=0xDFFF => Err(InvalidCodePoint(unit as usize, Location::current()).into()),
+            _ => Ok(char::from_u32(unit as u32).unwrap()),
+        })
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.char_offset();
+        let result = self.decode_next()?;
+        Some(self.iter.locate(offset, result))
+    }
+}
+
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize {
+        // a buffered unit has already been read
+        self.iter.offset() - 2 * self.buffered.is_some() as usize
+    }
+}
+
+impl Utf16Be {
+    /// Encode all characters of `data`. Every character can be encoded, so
+    /// this never fails. No NULL character is appended.
+    pub fn encode(data: &str) -> Vec<u8> {
+        data.encode_utf16().flat_map(|x| x.to_be_bytes()).collect()
+    }
+
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL characters
+    /// and only stop at the end of the iterator or if an decoding error
+    /// occurs.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`Utf16Be::all`], but invalid
+    /// bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`Utf16Be::first`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`Utf16Be::all`], invalid bytes
+    /// are handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`Utf16Be::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::utf16be`] for decoding as [UTF-16 BE][`Utf16Be`] strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [UTF-16 BE][`Utf16Be`].
+    fn utf16be<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+#[cfg(feature = "std")]
+impl ParseStringEncoding for Utf16Be {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let data = Self::encode(data);
+        ensure!(
+            data.len() <= buffer.len(),
+            BufferTooSmall(Location::current())
+        );
+        buffer[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        let buffer = BufReader::new(reader);
+        let iter = buffer
+            .bytes()
+            .take_while(|x| x.is_ok())
+            .filter_map(|x| x.ok());
+        Self::first(iter)
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str() {
+        let data = b"\x00a\x00b\x30\x42\x00\x00\x00c";
+        assert_eq!(Utf16Be::parse_str(data).unwrap(), "abあ".to_string());
+    }
+
+    #[test]
+    fn surrogates() {
+        let data = Utf16Be::encode("a😀");
+        assert_eq!(data, b"\x00a\xD8\x3D\xDE\x00");
+        assert_eq!(Utf16Be::all(&data).unwrap(), "a😀");
+
+        // lone high surrogate, the following character is kept
+        assert_eq!(Utf16Be::all_lossy(b"\xD8\x3D\x00a"), "\u{fffd}a");
+        // lone low surrogate
+        assert_eq!(Utf16Be::all_lossy(b"\xDE\x00\x00a"), "\u{fffd}a");
+        // high surrogate or half of a unit at the end
+        assert!(Utf16Be::all(b"\x00a\xD8\x3D").is_err());
+        assert!(Utf16Be::all(b"\x00a\x00").is_err());
+        assert_eq!(data.utf16be().count(), 2);
+    }
+
+    #[test]
+    fn char_offsets() {
+        let data = b"\x00a\xD8\x3D\xDE\x00\xDE\x00\x00b";
+        let chars = data.utf16be().char_offsets().collect::<Vec<_>>();
+        assert_eq!(chars.len(), 4);
+        assert_eq!(chars[0].as_ref().unwrap(), &(0, 'a'));
+        assert_eq!(chars[1].as_ref().unwrap(), &(2, '😀'));
+        assert!(chars[2].is_err());
+        assert_eq!(chars[3].as_ref().unwrap(), &(8, 'b'));
+    }
+}
@@ -0,0 +1,456 @@
+//! Virtual file systems over discs and archives.
+//!
+//! The [`Vfs`] trait is implemented by the containers that store a directory
+//! tree ([GCM][`crate::gcm`] discs via [`GcmFs`], [RARC][`crate::rarc`]
+//! archives, and [U8][`crate::u8arc`] archives). Tools written against [`Vfs`]
+//! work on discs and archives interchangeably. Archives stored inside another
+//! container can be opened with [`Vfs::open_archive`]. Archives are often
+//! [Yaz0][`crate::yaz0`] compressed, [`OpenOptions::auto_decompress`]
+//! decompresses them transparently. [`walk_nested`] descends into all nested
+//! archives and yields virtual paths (e.g.,
+//! `res/Object/Always.arc!/bmg/zel_00.bmg`) that can be read with
+//! [`read_nested`].
+//!
+//! Paths are relative to the root directory, a leading `/` is ignored, and the
+//! comparison is case-insensitive (like on the console).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::path::Path;
+//! # use picori::Result;
+//! # use picori::vfs::{GcmFs, OpenOptions, Vfs};
+//! fn main() -> Result<()> {
+//!     let mut disc = GcmFs::new(File::open("game.iso")?)?;
+//!     let options = OpenOptions::new().auto_decompress(true);
+//!     let path = Path::new("res/Object/Link.arc");
+//!     if let Some(mut archive) = disc.open_archive_with(path, &options)? {
+//!         for entry in archive.walk() {
+//!             println!("{} ({} bytes)", entry.path.display(), entry.metadata.size);
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::compression::Decompressor;
+use crate::gcm::{fst, Gcm};
+use crate::helper::{Parser, Seeker};
+use crate::yay0::Yay0;
+use crate::yaz0::Yaz0;
+use crate::{rarc, u8arc, yay0, yaz0, RarcReader, Result, U8Reader};
+
+/// Type of a [`Vfs`] entry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FileType {
+    /// File.
+    File,
+    /// Directory.
+    Directory,
+}
+
+/// Metadata of a [`Vfs`] entry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Metadata {
+    /// Type of the entry.
+    pub file_type: FileType,
+    /// Size of the file (`0` for directories).
+    pub size:      u64,
+}
+
+impl Metadata {
+    /// Metadata of a file with the size `size`.
+    pub fn file(size: u64) -> Self {
+        Self {
+            file_type: FileType::File,
+            size,
+        }
+    }
+
+    /// Metadata of a directory.
+    pub fn directory() -> Self {
+        Self {
+            file_type: FileType::Directory,
+            size:      0,
+        }
+    }
+
+    /// The entry is a file.
+    pub fn is_file(&self) -> bool { self.file_type == FileType::File }
+
+    /// The entry is a directory.
+    pub fn is_dir(&self) -> bool { self.file_type == FileType::Directory }
+}
+
+/// Entry of a [`Vfs`] directory tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DirEntry {
+    /// Path relative to the root directory.
+    pub path:     PathBuf,
+    /// Metadata of the entry.
+    pub metadata: Metadata,
+}
+
+impl DirEntry {
+    /// Name of the entry (the last component of the path).
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Virtual file system, i.e., a read-only directory tree.
+pub trait Vfs {
+    /// Get all entries (files and directories, excluding the root directory)
+    /// in depth-first order.
+    fn walk(&self) -> Vec<DirEntry>;
+
+    /// Read the data of the file at `path`. Returns [`None`] if there is no
+    /// such file.
+    fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>>;
+
+    /// Read the first `size` bytes (or less if the file is smaller) of the
+    /// file at `path`, e.g., to check its magic. Returns [`None`] if there is
+    /// no such file.
+    fn read_prefix(&mut self, path: &Path, size: usize) -> Result<Option<Vec<u8>>> {
+        Ok(self.read(path)?.map(|mut data| {
+            data.truncate(size);
+            data
+        }))
+    }
+
+    /// Get the metadata of the entry at `path`. Returns [`None`] if there is
+    /// no such entry.
+    fn stat(&self, path: &Path) -> Option<Metadata> {
+        let path = relative(path);
+        if path.components().next().is_none() {
+            return Some(Metadata::directory());
+        }
+        self.walk()
+            .into_iter()
+            .find(|x| path_eq(&x.path, path))
+            .map(|x| x.metadata)
+    }
+
+    /// Get the entries of the directory at `path`. Returns [`None`] if there
+    /// is no such directory.
+    fn list(&self, path: &Path) -> Option<Vec<DirEntry>> {
+        let path = relative(path);
+        if !self.stat(path)?.is_dir() {
+            return None;
+        }
+        Some(
+            self.walk()
+                .into_iter()
+                .filter(|x| x.path.parent().is_some_and(|x| path_eq(x, path)))
+                .collect(),
+        )
+    }
+
+    /// Open the file at `path` as a stream (e.g., to parse it). Returns
+    /// [`None`] if there is no such file.
+    fn open(&mut self, path: &Path) -> Result<Option<Cursor<Vec<u8>>>> {
+        self.open_with(path, &OpenOptions::default())
+    }
+
+    /// Open the archive ([RARC][`crate::rarc`] or [U8][`crate::u8arc`]) at
+    /// `path` as a nested [`Vfs`]. Returns [`None`] if there is no such file
+    /// or the file is not an archive.
+    fn open_archive(&mut self, path: &Path) -> Result<Option<Box<dyn Vfs>>> {
+        self.open_archive_with(path, &OpenOptions::default())
+    }
+
+    /// Read the data of the file at `path` with `options`, see
+    /// [`Vfs::read`].
+    fn read_with(&mut self, path: &Path, options: &OpenOptions) -> Result<Option<Vec<u8>>> {
+        match self.read(path)? {
+            Some(data) => Ok(Some(options.decode(data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Open the file at `path` with `options`, see [`Vfs::open`].
+    fn open_with(&mut self, path: &Path, options: &OpenOptions) -> Result<Option<Cursor<Vec<u8>>>> {
+        Ok(self.read_with(path, options)?.map(Cursor::new))
+    }
+
+    /// Open the archive at `path` with `options`, see [`Vfs::open_archive`].
+    fn open_archive_with(
+        &mut self,
+        path: &Path,
+        options: &OpenOptions,
+    ) -> Result<Option<Box<dyn Vfs>>> {
+        match self.read_with(path, options)? {
+            Some(data) => open_archive(data),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Options for opening files and archives, see [`Vfs::open_with`] and
+/// [`open_archive_with`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct OpenOptions {
+    auto_decompress: bool,
+}
+
+impl OpenOptions {
+    /// Create the default options.
+    pub fn new() -> Self { Self::default() }
+
+    /// Decompress [Yaz0][`crate::yaz0`] and [Yay0][`crate::yay0`] compressed
+    /// files (detected by magic) before they are returned or parsed (default:
+    /// `false`).
+    pub fn auto_decompress(mut self, auto_decompress: bool) -> Self {
+        self.auto_decompress = auto_decompress;
+        self
+    }
+
+    /// Apply the options to the file data `data`.
+    pub fn decode(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        if self.auto_decompress {
+            let codecs: [&dyn Decompressor; 2] = [&Yaz0::default(), &Yay0];
+            if let Some(codec) = codecs.iter().find(|x| x.detect(&data)) {
+                return codec.decompress_bytes(&data);
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Open the archive `data` with `options`, see [`open_archive`].
+pub fn open_archive_with(data: Vec<u8>, options: &OpenOptions) -> Result<Option<Box<dyn Vfs>>> {
+    open_archive(options.decode(data)?)
+}
+
+/// Open the archive ([RARC][`crate::rarc`] or [U8][`crate::u8arc`]) `data` as
+/// a [`Vfs`] (detected by magic). Returns [`None`] if `data` is not an archive.
+pub fn open_archive(data: Vec<u8>) -> Result<Option<Box<dyn Vfs>>> {
+    let magic = data
+        .get(..4)
+        .map(|x| u32::from_be_bytes(x.try_into().unwrap()));
+    Ok(match magic {
+        Some(rarc::MAGIC) => Some(Box::new(RarcReader::new(Cursor::new(data))?)),
+        Some(u8arc::MAGIC) => Some(Box::new(U8Reader::new(Cursor::new(data))?)),
+        _ => None,
+    })
+}
+
+/// Separator between the path of an archive and a path inside the archive in
+/// the virtual paths of [`walk_nested`], e.g.,
+/// `res/Object/Always.arc!/bmg/zel_00.bmg`.
+pub const NESTED_SEPARATOR: &str = "!/";
+
+/// Entry yielded by [`walk_nested`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NestedEntry {
+    /// Virtual path, archives are separated by [`NESTED_SEPARATOR`].
+    pub path:     String,
+    /// Metadata of the entry.
+    pub metadata: Metadata,
+    /// Number of archives the entry is nested in (`0` for entries of the
+    /// outermost container).
+    pub depth:    usize,
+}
+
+/// Check if the file starting with `prefix` is an archive that can be opened
+/// with `options`.
+fn is_archive(prefix: &[u8], options: &OpenOptions) -> bool {
+    match prefix
+        .get(..4)
+        .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+    {
+        Some(rarc::MAGIC) | Some(u8arc::MAGIC) => true,
+        Some(yaz0::MAGIC) | Some(yaz0::MAGIC_YAZ1) | Some(yay0::MAGIC) => options.auto_decompress,
+        _ => false,
+    }
+}
+
+/// Get all entries of `vfs` and (recursively) of all archives stored in it,
+/// e.g., a disc containing [Yaz0][`crate::yaz0`] compressed
+/// [RARC][`crate::rarc`] archives (with [`OpenOptions::auto_decompress`]). The
+/// entries of an archive directly follow the archive itself.
+pub fn walk_nested<V: Vfs + ?Sized>(
+    vfs: &mut V,
+    options: &OpenOptions,
+) -> Result<Vec<NestedEntry>> {
+    let mut entries = Vec::new();
+    walk_nested_into(vfs, "", 0, options, &mut entries)?;
+    Ok(entries)
+}
+
+fn walk_nested_into<V: Vfs + ?Sized>(
+    vfs: &mut V,
+    prefix: &str,
+    depth: usize,
+    options: &OpenOptions,
+    entries: &mut Vec<NestedEntry>,
+) -> Result<()> {
+    for entry in vfs.walk() {
+        let path = format!(
+            "{}{}",
+            prefix,
+            entry.path.to_string_lossy().replace('\\', "/")
+        );
+        entries.push(NestedEntry {
+            path: path.clone(),
+            metadata: entry.metadata,
+            depth,
+        });
+
+        if !entry.metadata.is_file() {
+            continue;
+        }
+        let header = vfs.read_prefix(&entry.path, 4)?.unwrap_or_default();
+        if !is_archive(&header, options) {
+            continue;
+        }
+        if let Some(mut archive) = vfs.open_archive_with(&entry.path, options)? {
+            let prefix = format!("{}{}", path, NESTED_SEPARATOR);
+            walk_nested_into(archive.as_mut(), &prefix, depth + 1, options, entries)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read the file at the virtual path `path` (see [`walk_nested`]) from `vfs`.
+/// Returns [`None`] if there is no such file.
+pub fn read_nested<V: Vfs + ?Sized>(
+    vfs: &mut V,
+    path: &str,
+    options: &OpenOptions,
+) -> Result<Option<Vec<u8>>> {
+    match path.split_once(NESTED_SEPARATOR) {
+        None => vfs.read_with(Path::new(path), options),
+        Some((archive, path)) => match vfs.open_archive_with(Path::new(archive), options)? {
+            Some(mut archive) => read_nested(archive.as_mut(), path, options),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Strip the leading `/` from `path`.
+fn relative(path: &Path) -> &Path { path.strip_prefix("/").unwrap_or(path) }
+
+/// Case-insensitive comparison of two paths.
+fn path_eq(a: &Path, b: &Path) -> bool {
+    a.components().count() == b.components().count()
+        && a.components()
+            .zip(b.components())
+            .all(|(a, b)| a.as_os_str().eq_ignore_ascii_case(b.as_os_str()))
+}
+
+/// [GCM][`crate::gcm`] disc image as a [`Vfs`] (the files of the
+/// [FST][`crate::gcm::Fst`]).
+pub struct GcmFs<R> {
+    gcm:    Gcm,
+    reader: R,
+}
+
+impl<R: Parser + Seeker> GcmFs<R> {
+    /// Parse the disc image `reader`.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let gcm = Gcm::from_binary(&mut reader)?;
+        Ok(Self { gcm, reader })
+    }
+
+    /// Create a [`Vfs`] for the already parsed `gcm` and its disc image
+    /// `reader`.
+    pub fn from_gcm(gcm: Gcm, reader: R) -> Self { Self { gcm, reader } }
+
+    /// Get a reference to the [`Gcm`].
+    pub fn gcm(&self) -> &Gcm { &self.gcm }
+
+    /// Consume the file system and return the [`Gcm`] and the disc image.
+    pub fn into_inner(self) -> (Gcm, R) { (self.gcm, self.reader) }
+}
+
+impl<R: Parser + Seeker> Vfs for GcmFs<R> {
+    fn walk(&self) -> Vec<DirEntry> {
+        self.gcm
+            .fst()
+            .files()
+            .filter_map(|(path, entry)| {
+                let metadata = match entry {
+                    fst::Entry::Root => return None,
+                    fst::Entry::File { size, .. } => Metadata::file(size as u64),
+                    fst::Entry::Directory { .. } => Metadata::directory(),
+                };
+                Some(DirEntry { path, metadata })
+            })
+            .collect()
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>> {
+        self.gcm.file_data(&mut self.reader, path)
+    }
+
+    fn read_prefix(&mut self, path: &Path, size: usize) -> Result<Option<Vec<u8>>> {
+        match self.gcm.fst().find(path) {
+            Some(fst::Entry::File {
+                offset,
+                size: file_size,
+                ..
+            }) => {
+                self.reader.goto(offset)?;
+                let size = size.min(file_size as usize);
+                Ok(Some(self.reader.read_as_vec(size)?))
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<R: Parser + Seeker> Vfs for RarcReader<R> {
+    fn walk(&self) -> Vec<DirEntry> {
+        self.files()
+            .filter_map(|(path, entry)| {
+                let metadata = match entry {
+                    rarc::Entry::Root { .. } => return None,
+                    rarc::Entry::File { size, .. } => Metadata::file(size as u64),
+                    rarc::Entry::Directory { .. } => Metadata::directory(),
+                };
+                Some(DirEntry { path, metadata })
+            })
+            .collect()
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>> { RarcReader::read(self, path) }
+}
+
+impl<R: Parser + Seeker> Vfs for U8Reader<R> {
+    fn walk(&self) -> Vec<DirEntry> {
+        self.files()
+            .filter_map(|(path, entry)| {
+                let metadata = match entry {
+                    u8arc::Entry::Root => return None,
+                    u8arc::Entry::File { size, .. } => Metadata::file(size as u64),
+                    u8arc::Entry::Directory { .. } => Metadata::directory(),
+                };
+                Some(DirEntry { path, metadata })
+            })
+            .collect()
+    }
+
+    fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>> { U8Reader::read(self, path) }
+}
+
+impl<V: Vfs + ?Sized> Vfs for Box<V> {
+    fn walk(&self) -> Vec<DirEntry> { (**self).walk() }
+
+    fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>> { (**self).read(path) }
+
+    fn read_prefix(&mut self, path: &Path, size: usize) -> Result<Option<Vec<u8>>> {
+        (**self).read_prefix(path, size)
+    }
+
+    fn stat(&self, path: &Path) -> Option<Metadata> { (**self).stat(path) }
+
+    fn list(&self, path: &Path) -> Option<Vec<DirEntry>> { (**self).list(path) }
+}
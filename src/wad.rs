@@ -0,0 +1,288 @@
+//! Wii WAD files (`.wad`).
+//!
+//! A WAD file is the installable package of a Wii title, e.g., a channel or
+//! an IOS. It consists of a header followed by the certificate chain, the
+//! [ticket][`Ticket`], the [title metadata][`Tmd`], the encrypted contents,
+//! and an optional footer. Every section starts at an offset aligned to
+//! `0x40` bytes, and so does every content within the data section.
+//!
+//! | Offset | Size | Description                                   |
+//! |--------|------|-----------------------------------------------|
+//! | `0x00` | 4    | Header size (`0x20`)                          |
+//! | `0x04` | 2    | WAD type (`Is` or `ib`)                       |
+//! | `0x06` | 2    | WAD version                                   |
+//! | `0x08` | 4    | Certificate chain size                        |
+//! | `0x0C` | 4    | Reserved                                      |
+//! | `0x10` | 4    | Ticket size                                   |
+//! | `0x14` | 4    | Title metadata size                           |
+//! | `0x18` | 4    | Data size (all contents)                      |
+//! | `0x1C` | 4    | Footer size                                   |
+//!
+//! The contents are stored in the order of the content records of the title
+//! metadata, encrypted with the title key (the size of a content is padded to
+//! the AES block size). With the `crypto` feature enabled, the contents can be
+//! decrypted with [`Wad::decrypt_content`], given the title key (see
+//! [`Ticket::title_key`][`crate::wii::Ticket::title_key`]).
+//!
+//! Some contents are wrapped in an [IMD5][`crate::imet::Imd5`] header and/or
+//! [LZ77][`crate::lz`] compressed (e.g., the `banner.bin` of a channel, or
+//! the U8 archives of the System Menu). [`ContentOptions`] unwraps them
+//! transparently, see [`Wad::decrypt_content_with`] and
+//! [`Wad::decrypted_contents`]. The options work on any content data, e.g.,
+//! the `.app` files of a NAND dump, see [`ContentOptions::decode`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("channel.wad")?;
+//!     let wad = picori::wad::Wad::from_binary(&mut file)?;
+//!     println!("{:016x} v{}", wad.tmd.title_id, wad.tmd.title_version);
+//!     for (content, data) in wad.tmd.contents.iter().zip(wad.contents.iter()) {
+//!         println!("{:08x}.app: {} bytes", content.id, data.len());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::panic::Location;
+
+#[cfg(feature = "crypto")]
+use sha1::{Digest, Sha1};
+
+use crate::compression::Decompressor;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::imet::Imd5;
+#[cfg(feature = "crypto")]
+use crate::wii::Content;
+use crate::wii::{Certificate, Ticket, Tmd};
+use crate::Result;
+
+/// Size of the WAD header.
+pub const HEADER_SIZE: u32 = 0x20;
+
+/// Alignment of the sections and the contents.
+pub const ALIGNMENT: u64 = 0x40;
+
+/// WAD type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WadKind {
+    /// Installable title (`Is`), e.g., a channel or an IOS.
+    Installable,
+
+    /// boot2 (`ib`).
+    Boot2,
+}
+
+impl WadKind {
+    /// Get the raw value of the WAD type.
+    pub fn value(&self) -> u16 {
+        match self {
+            WadKind::Installable => 0x4973,
+            WadKind::Boot2 => 0x6962,
+        }
+    }
+}
+
+impl TryFrom<u16> for WadKind {
+    type Error = crate::Error;
+
+    fn try_from(value: u16) -> Result<Self> {
+        match value {
+            0x4973 => Ok(WadKind::Installable),
+            0x6962 => Ok(WadKind::Boot2),
+            _ => Err(ParseProblem::InvalidHeader("unknown WAD type", Location::current()).into()),
+        }
+    }
+}
+
+/// Options for unwrapping contents, see [`Wad::decrypt_content_with`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ContentOptions {
+    unwrap_imd5: bool,
+    unwrap_lz77: bool,
+}
+
+impl Default for ContentOptions {
+    /// Unwrap both IMD5 headers and LZ77 compression.
+    fn default() -> Self {
+        Self {
+            unwrap_imd5: true,
+            unwrap_lz77: true,
+        }
+    }
+}
+
+impl ContentOptions {
+    /// Create the default options.
+    pub fn new() -> Self { Self::default() }
+
+    /// Strip [IMD5][`crate::imet::Imd5`] headers (default: `true`).
+    pub fn unwrap_imd5(mut self, unwrap_imd5: bool) -> Self {
+        self.unwrap_imd5 = unwrap_imd5;
+        self
+    }
+
+    /// Decompress [LZ][`crate::lz`] streams that start with the `LZ77` magic
+    /// (default: `true`). Streams without the magic are returned as is, the
+    /// bare LZ header is too short to be detected reliably.
+    pub fn unwrap_lz77(mut self, unwrap_lz77: bool) -> Self {
+        self.unwrap_lz77 = unwrap_lz77;
+        self
+    }
+
+    /// Apply the options to the (decrypted) content data `data`. Nested
+    /// wrappers (e.g., LZ77 inside IMD5) are unwrapped until the data is
+    /// neither.
+    pub fn decode(&self, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        loop {
+            if self.unwrap_imd5 && Imd5::is_imd5(&data) {
+                data = Imd5::from_binary(&mut Cursor::new(data))?.data;
+            } else if self.unwrap_lz77 && data.starts_with(b"LZ77") {
+                data = crate::lz::Lz::default().decompress_bytes(&data)?;
+            } else {
+                return Ok(data);
+            }
+        }
+    }
+}
+
+/// WAD file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wad {
+    /// WAD type.
+    pub kind: WadKind,
+
+    /// WAD version.
+    pub version: u16,
+
+    /// Certificate chain.
+    pub certificates: Vec<Certificate>,
+
+    /// Ticket.
+    pub ticket: Ticket,
+
+    /// Title metadata.
+    pub tmd: Tmd,
+
+    /// Contents (encrypted), in the order of [`Tmd::contents`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub contents: Vec<Vec<u8>>,
+
+    /// Footer, usually the build information of the title.
+    pub footer: Vec<u8>,
+}
+
+impl Wad {
+    /// Parse WAD file from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
+        let base = input.position()?;
+        let header_size = input.bu32()?;
+        ensure!(
+            header_size == HEADER_SIZE,
+            ParseProblem::InvalidHeader("WAD header size (expected: 0x20)", Location::current())
+        );
+        let kind = WadKind::try_from(input.bu16()?)?;
+        let version = input.bu16()?;
+        let cert_chain_size = input.bu32()?;
+        let _reserved = input.bu32()?;
+        let ticket_size = input.bu32()?;
+        let tmd_size = input.bu32()?;
+        let data_size = input.bu32()?;
+        let footer_size = input.bu32()?;
+
+        // read the section at `offset` and return the (aligned) offset of the
+        // next section
+        let mut offset = (HEADER_SIZE as u64).next_multiple_of(ALIGNMENT);
+        let mut section = |input: &mut D, size: u32| -> Result<Vec<u8>> {
+            input.goto(base + offset)?;
+            offset = (offset + size as u64).next_multiple_of(ALIGNMENT);
+            input.read_as_vec(size as usize)
+        };
+
+        let certificates = Certificate::chain_from_binary(&section(input, cert_chain_size)?)?;
+        let ticket = Ticket::from_binary(&mut Cursor::new(section(input, ticket_size)?))?;
+        let tmd = Tmd::from_binary(&mut Cursor::new(section(input, tmd_size)?))?;
+        let data = section(input, data_size)?;
+        let footer = section(input, footer_size)?;
+
+        let mut contents = Vec::with_capacity(tmd.contents.len());
+        let mut start = 0;
+        for content in tmd.contents.iter() {
+            let size = content.size.next_multiple_of(16);
+            let end = start + size;
+            ensure!(
+                end <= data.len() as u64,
+                ParseProblem::InvalidRange("WAD content (outside of data)", Location::current())
+            );
+            contents.push(data[start as usize..end as usize].to_vec());
+            start = end.next_multiple_of(ALIGNMENT);
+        }
+
+        Ok(Self {
+            kind,
+            version,
+            certificates,
+            ticket,
+            tmd,
+            contents,
+            footer,
+        })
+    }
+
+    /// Get the (encrypted) data of the content with the given content index.
+    pub fn content(&self, index: u16) -> Option<&[u8]> {
+        let position = self.tmd.contents.iter().position(|x| x.index == index)?;
+        self.contents.get(position).map(Vec::as_slice)
+    }
+
+    /// Decrypt the content with the given content index with `title_key`, and
+    /// verify the SHA-1 hash of the decrypted content.
+    #[cfg(feature = "crypto")]
+    pub fn decrypt_content(&self, index: u16, title_key: &[u8; 16]) -> Result<Vec<u8>> {
+        let content = self.tmd.content(index).ok_or(ParseProblem::InvalidData(
+            "WAD content index",
+            Location::current(),
+        ))?;
+        let mut data = self.content(index).unwrap().to_vec();
+        crate::wii::reader::decrypt(title_key, &content.iv(), &mut data);
+        data.truncate(content.size as usize);
+        let hash: [u8; 20] = Sha1::digest(&data).into();
+        ensure!(
+            hash == content.hash,
+            ParseProblem::InvalidData("WAD content hash mismatch", Location::current())
+        );
+        Ok(data)
+    }
+
+    /// Decrypt the content with the given content index with `title_key`
+    /// (see [`Wad::decrypt_content`]), and unwrap it with `options`.
+    #[cfg(feature = "crypto")]
+    pub fn decrypt_content_with(
+        &self,
+        index: u16,
+        title_key: &[u8; 16],
+        options: &ContentOptions,
+    ) -> Result<Vec<u8>> {
+        options.decode(self.decrypt_content(index, title_key)?)
+    }
+
+    /// Iterate over the content records and the decrypted contents, unwrapped
+    /// with `options`, in the order of [`Tmd::contents`].
+    #[cfg(feature = "crypto")]
+    pub fn decrypted_contents<'a>(
+        &'a self,
+        title_key: &'a [u8; 16],
+        options: &'a ContentOptions,
+    ) -> impl Iterator<Item = Result<(&'a Content, Vec<u8>)>> + 'a {
+        self.tmd.contents.iter().map(move |content| {
+            let data = self.decrypt_content_with(content.index, title_key, options)?;
+            Ok((content, data))
+        })
+    }
+}
@@ -0,0 +1,261 @@
+//! Wii Backup File System (WBFS) disc images.
+//!
+//! A [WBFS][`crate::wbfs`] file stores a single Wii disc image as a sequence of
+//! WBFS sectors (usually 2 MiB). Sectors that are not used are omitted and the
+//! disc info has a table that maps each sector of the disc to a sector of the
+//! file (`0` for omitted sectors).
+//!
+//! | Offset | Size | Description                                      |
+//! |--------|------|--------------------------------------------------|
+//! | `0x00` | 4    | Magic (`WBFS`)                                   |
+//! | `0x04` | 4    | Number of HD sectors                             |
+//! | `0x08` | 1    | HD sector size (shift)                           |
+//! | `0x09` | 1    | WBFS sector size (shift)                         |
+//! | `0x0C` | ...  | Disc table (one byte per disc slot)              |
+//! | HD sector 1 | `0x100` | Disc header (copy)                      |
+//! | ...    | ...  | Sector table (`u16` per WBFS sector of the disc) |
+//!
+//! Only the first disc slot is read, and images split into multiple files
+//! (`.wbf1`, ...) are not supported. The size of the image is not stored, it
+//! is the end of the last used sector, see [`WbfsReader::total_size`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut input = File::open("game.wbfs")?;
+//!     let mut reader = picori::wbfs::WbfsReader::new(&mut input)?;
+//!     let mut output = File::create("game.iso")?;
+//!     reader.decompress(&mut output)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Write;
+use std::panic::Location;
+
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::Result;
+
+/// [WBFS][`crate::wbfs`] magic number representing the four characters "WBFS".
+static MAGIC: u32 = 0x57424653;
+
+/// Size of a Wii disc sector.
+const WII_SECTOR_SIZE: u64 = 0x8000;
+
+/// Number of Wii disc sectors of a dual-layer disc.
+const WII_SECTOR_COUNT: u64 = 143432 * 2;
+
+/// Size of the disc header copy in the disc info.
+const DISC_HEADER_SIZE: u64 = 0x100;
+
+/// HD sector size (shift) used when building [WBFS][`crate::wbfs`] files.
+const HD_SECTOR_SHIFT: u8 = 9;
+
+/// Default WBFS sector size used when building [WBFS][`crate::wbfs`] files.
+pub const DEFAULT_SECTOR_SIZE: usize = 0x200000;
+
+/// Number of WBFS sectors of a disc.
+fn sectors_per_disc(sector_size: u64) -> u64 { WII_SECTOR_COUNT * WII_SECTOR_SIZE / sector_size }
+
+/// Reader for [WBFS][`crate::wbfs`] files.
+pub struct WbfsReader<'reader, D: Parser + Seeker> {
+    reader:      &'reader mut D,
+    base:        u64,
+    sector_size: u64,
+    sectors:     Vec<u16>,
+}
+
+impl<'reader, D: Parser + Seeker> WbfsReader<'reader, D> {
+    /// Create a new [WBFS][`crate::wbfs`] reader from a binary stream.
+    pub fn new(reader: &'reader mut D) -> Result<Self> {
+        let base = reader.position()?;
+        let magic = reader.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected WBFS", Location::current())
+        );
+        let _hd_sector_count = reader.bu32()?;
+        let hd_sector_shift = reader.u8()?;
+        let sector_shift = reader.u8()?;
+        ensure!(
+            (9..=12).contains(&hd_sector_shift) && (15..=27).contains(&sector_shift),
+            ParseProblem::InvalidHeader("WBFS sector sizes", Location::current())
+        );
+        let _padding = reader.u8_array::<2>()?;
+        ensure!(
+            reader.u8()? != 0,
+            ParseProblem::InvalidData("WBFS has no disc in the first slot", Location::current())
+        );
+
+        let hd_sector_size = 1_u64 << hd_sector_shift;
+        let sector_size = 1_u64 << sector_shift;
+        reader.goto(base + hd_sector_size + DISC_HEADER_SIZE)?;
+        let mut sectors = (0..sectors_per_disc(sector_size))
+            .map(|_| reader.bu16())
+            .collect::<Result<Vec<_>>>()?;
+        let used = sectors.iter().rposition(|x| *x != 0).map_or(0, |x| x + 1);
+        sectors.truncate(used);
+        Ok(Self {
+            reader,
+            base,
+            sector_size,
+            sectors,
+        })
+    }
+
+    /// Get the WBFS sector size (the block size).
+    pub fn block_size(&self) -> usize { self.sector_size as usize }
+
+    /// Get the total size of the disc image, the end of the last used sector.
+    pub fn total_size(&self) -> u64 { self.sectors.len() as u64 * self.sector_size }
+
+    /// Read data of block (WBFS sector) at index `index`. If the sector is
+    /// omitted, a zeroed buffer will be returned.
+    pub fn read_block(&mut self, index: usize) -> Result<Vec<u8>> {
+        let sector = *self.sectors.get(index).ok_or(ParseProblem::InvalidRange(
+            "WBFS sector (outside of disc)",
+            Location::current(),
+        ))?;
+        let mut buffer = vec![0; self.sector_size as usize];
+        if sector != 0 {
+            self.reader
+                .goto(self.base + sector as u64 * self.sector_size)?;
+            self.reader.read_into(&mut buffer)?;
+        }
+        Ok(buffer)
+    }
+
+    /// Decompress all sectors and write the disc image to a
+    /// [`std::io::Write`].
+    pub fn decompress<O: Write>(&mut self, writer: &mut O) -> Result<()> {
+        for index in 0..self.sectors.len() {
+            writer.write_all(&self.read_block(index)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer for [WBFS][`crate::wbfs`] files. Data is written sector by sector,
+/// sectors that only contain zero bytes are omitted. The header and the disc
+/// info are written when calling [`WbfsWriter::finish`].
+pub struct WbfsWriter<'writer, W: Writer + Seeker> {
+    writer:      &'writer mut W,
+    base:        u64,
+    sector_size: usize,
+    header:      Vec<u8>,
+    sectors:     Vec<u16>,
+    next:        u16,
+    buffer:      Vec<u8>,
+}
+
+impl<'writer, W: Writer + Seeker> WbfsWriter<'writer, W> {
+    /// Create a new [WBFS][`crate::wbfs`] writer with the given WBFS sector
+    /// size. The sector size must be a power of two (up to `0x8000000`) and
+    /// large enough for the header and the disc info, i.e., at least
+    /// `0x40000`.
+    pub fn new(writer: &'writer mut W, sector_size: usize) -> Result<Self> {
+        ensure!(
+            sector_size.is_power_of_two() && sector_size <= 0x8000000,
+            BuildProblem::InvalidData(
+                "WBFS sector size (power of two, up to 0x8000000)",
+                Location::current()
+            )
+        );
+        let info_size =
+            (1_u64 << HD_SECTOR_SHIFT) + DISC_HEADER_SIZE + sectors_per_disc(sector_size as u64) * 2;
+        ensure!(
+            info_size <= sector_size as u64,
+            BuildProblem::InvalidData(
+                "WBFS sector size (too small for the disc info)",
+                Location::current()
+            )
+        );
+
+        // the first sector is reserved for the header and the disc info
+        let base = writer.position()?;
+        writer.u8_array(&vec![0; sector_size])?;
+        Ok(Self {
+            writer,
+            base,
+            sector_size,
+            header: Vec::new(),
+            sectors: Vec::new(),
+            next: 1,
+            buffer: Vec::with_capacity(sector_size),
+        })
+    }
+
+    fn write_sector(&mut self) -> Result<()> {
+        ensure!(
+            (self.sectors.len() as u64) < sectors_per_disc(self.sector_size as u64),
+            BuildProblem::InvalidData("WBFS image (larger than a disc)", Location::current())
+        );
+
+        if self.buffer.iter().all(|x| *x == 0) {
+            self.sectors.push(0);
+        } else {
+            ensure!(
+                self.next < u16::MAX,
+                BuildProblem::InvalidData("too many WBFS sectors", Location::current())
+            );
+            self.buffer.resize(self.sector_size, 0);
+            self.writer.u8_array(&self.buffer)?;
+            self.sectors.push(self.next);
+            self.next += 1;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Write data to the [WBFS][`crate::wbfs`] file.
+    pub fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        if self.header.len() < DISC_HEADER_SIZE as usize {
+            let length = data.len().min(DISC_HEADER_SIZE as usize - self.header.len());
+            self.header.extend_from_slice(&data[..length]);
+        }
+
+        let mut data = data;
+        while !data.is_empty() {
+            let length = data.len().min(self.sector_size - self.buffer.len());
+            self.buffer.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if self.buffer.len() == self.sector_size {
+                self.write_sector()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the last (partial) sector, the header, and the disc info.
+    pub fn finish(mut self) -> Result<()> {
+        if !self.buffer.is_empty() {
+            self.write_sector()?;
+        }
+
+        let end = self.writer.position()?;
+        let hd_sector_size = 1_u64 << HD_SECTOR_SHIFT;
+        let mut header = self.header;
+        header.resize(DISC_HEADER_SIZE as usize, 0);
+        let mut sectors = self.sectors;
+        sectors.resize(sectors_per_disc(self.sector_size as u64) as usize, 0);
+
+        self.writer.goto(self.base)?;
+        self.writer.bu32(MAGIC)?;
+        self.writer
+            .bu32((end - self.base).div_ceil(hd_sector_size) as u32)?;
+        self.writer.u8(HD_SECTOR_SHIFT)?;
+        self.writer.u8(self.sector_size.trailing_zeros() as u8)?;
+        self.writer.u8_array(&[0, 0, 1])?;
+        self.writer.goto(self.base + hd_sector_size)?;
+        self.writer.u8_array(&header)?;
+        for sector in sectors {
+            self.writer.bu16(sector)?;
+        }
+        self.writer.goto(end)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+//! [Wii][`crate::wii`] certificate. Certificates are stored in chains (e.g.,
+//! in partitions and WADs) and contain the public keys used to verify the
+//! signatures of tickets, title metadata, and other certificates.
+
+use std::io::Cursor;
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{Parser, ProblemLocation};
+use crate::wii::Signature;
+use crate::{Ascii, Result};
+
+/// [`Certificate`] public key kind.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyKind {
+    /// RSA-4096 (`0`).
+    Rsa4096,
+
+    /// RSA-2048 (`1`).
+    Rsa2048,
+
+    /// Elliptic curve (ECC B-233) (`2`).
+    Ecc,
+}
+
+impl KeyKind {
+    /// Get the raw value of the key kind.
+    pub fn value(&self) -> u32 {
+        match self {
+            KeyKind::Rsa4096 => 0,
+            KeyKind::Rsa2048 => 1,
+            KeyKind::Ecc => 2,
+        }
+    }
+
+    /// Size of the public key (RSA modulus) in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            KeyKind::Rsa4096 => 0x200,
+            KeyKind::Rsa2048 => 0x100,
+            KeyKind::Ecc => 0x3C,
+        }
+    }
+}
+
+impl TryFrom<u32> for KeyKind {
+    type Error = crate::Error;
+
+    fn try_from(value: u32) -> Result<Self> {
+        match value {
+            0 => Ok(KeyKind::Rsa4096),
+            1 => Ok(KeyKind::Rsa2048),
+            2 => Ok(KeyKind::Ecc),
+            _ => Err(ParseProblem::InvalidHeader("unknown key kind", Location::current()).into()),
+        }
+    }
+}
+
+/// [Wii][`crate::wii`] certificate object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Certificate {
+    /// Signature of the certificate.
+    pub signature: Signature,
+
+    /// Signature issuer, e.g., `Root-CA00000001`.
+    pub issuer: String,
+
+    /// Public key kind.
+    pub key_kind: KeyKind,
+
+    /// Name of the certificate, e.g., `XS00000003`. The issuer of a signature
+    /// is the issuer of the certificate followed by `-` and its name.
+    pub name: String,
+
+    /// Key ID.
+    pub key_id: u32,
+
+    /// Public key (RSA modulus or ECC public key).
+    pub public_key: Vec<u8>,
+
+    /// RSA public exponent, [`None`] for ECC keys.
+    pub exponent: Option<u32>,
+}
+
+impl Certificate {
+    /// Parse [Wii][`crate::wii`] certificate.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let signature = Signature::from_binary(input)?;
+        let issuer = input.str_fixed::<0x40, Ascii>()?;
+        let key_kind = KeyKind::try_from(input.bu32()?)?;
+        let name = input.str_fixed::<0x40, Ascii>()?;
+        let key_id = input.bu32()?;
+        let public_key = input.read_as_vec(key_kind.size())?;
+        let exponent = match key_kind {
+            KeyKind::Ecc => {
+                let _padding = input.u8_array::<0x3C>()?;
+                None
+            },
+            _ => {
+                let exponent = input.bu32()?;
+                let _padding = input.u8_array::<0x34>()?;
+                Some(exponent)
+            },
+        };
+
+        Ok(Self {
+            signature,
+            issuer,
+            key_kind,
+            name,
+            key_id,
+            public_key,
+            exponent,
+        })
+    }
+
+    /// Parse a certificate chain, the certificates stored back to back.
+    pub fn chain_from_binary(data: &[u8]) -> Result<Vec<Self>> {
+        let mut input = Cursor::new(data);
+        let mut certificates = Vec::new();
+        while (input.position() as usize) < data.len() {
+            certificates.push(Self::from_binary(&mut input)?);
+        }
+        Ok(certificates)
+    }
+
+    /// Get the full name of the certificate (the issuer and the name), as
+    /// used for the issuer of signatures made with its key.
+    pub fn full_name(&self) -> String { format!("{}-{}", self.issuer, self.name) }
+}
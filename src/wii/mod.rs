@@ -0,0 +1,141 @@
+//! Parse Wii disc images.
+//!
+//! A Wii disc shares the disc header layout with the [GCM][`crate::gcm`]
+//! format, but all game data is stored in encrypted partitions. The partition
+//! table (located at `0x40000`) lists up to four tables of partitions. Each
+//! partition has a header with a [`Ticket`], a [`Tmd`], and the location of the
+//! encrypted partition data.
+//!
+//! With the `crypto` feature enabled, the partition data can be decrypted with
+//! [`PartitionReader`][`crate::wii::reader::PartitionReader`].
+//!
+//! # Parse
+//!
+//! Parse from binary stream by calling [`Wii::from_binary`]. On error a
+//! [Error][`crate::Error`] is return. Otherwise, the parsing succeeded and you
+//! get back a [`Wii`] struct.
+//!
+//! ## Example
+//!
+//! This is an example of how to list the partitions of a Wii disc image.
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("game.iso")?;
+//!     let wii = picori::Wii::from_binary(&mut file)?;
+//!     for partition in wii.partitions() {
+//!         println!("{:?} at {:#x}", partition.kind, partition.offset);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub mod certificate;
+pub mod partition;
+#[cfg(feature = "crypto")]
+pub mod reader;
+pub mod signature;
+pub mod ticket;
+pub mod tmd;
+#[cfg(feature = "crypto")]
+pub mod verify;
+
+#[doc(inline)]
+pub use certificate::*;
+#[doc(inline)]
+pub use partition::*;
+#[cfg(feature = "crypto")]
+#[doc(inline)]
+pub use reader::PartitionReader;
+#[doc(inline)]
+pub use signature::*;
+#[doc(inline)]
+pub use ticket::*;
+#[doc(inline)]
+pub use tmd::*;
+#[cfg(feature = "crypto")]
+#[doc(inline)]
+pub use verify::HashFailure;
+
+pub(crate) use crate::gcm::boot::WII_MAGIC as MAGIC;
+use crate::disc::{DiscReader, DiscStream};
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Offset of the partition table information.
+const PARTITION_INFO_OFFSET: u64 = 0x40000;
+
+/// Wii disc image object.
+///
+/// Only the partition table and the partition headers are parsed. The
+/// partition data itself is encrypted and is not read.
+pub struct Wii {
+    partitions: Vec<Partition>,
+}
+
+impl Wii {
+    /// Parse Wii disc from a [`DiscReader`] backend (e.g., a
+    /// [WBFS][`crate::wbfs`] image), see [`DiscStream`].
+    pub fn from_disc<R: DiscReader>(reader: R) -> Result<Wii> {
+        Self::from_binary(&mut DiscStream::new(reader))
+    }
+
+    /// Parse Wii disc from binary stream.
+    pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Wii> {
+        let base = reader.position()?;
+
+        reader.goto(base + 0x18)?;
+        let magic = reader.bu32()?;
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("expected: 0x5D1C9EA3", std::panic::Location::current())
+        );
+
+        reader.goto(base + PARTITION_INFO_OFFSET)?;
+        let tables = (0..4)
+            .map(|_| -> Result<(u32, u64)> {
+                let count = reader.bu32()?;
+                let offset = (reader.bu32()? as u64) << 2;
+                Ok((count, offset))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut entries = Vec::new();
+        for (table, (count, offset)) in tables.into_iter().enumerate() {
+            ensure!(
+                count <= 0x40,
+                ParseProblem::InvalidRange(
+                    "partition count limit (max 64)",
+                    std::panic::Location::current()
+                )
+            );
+
+            reader.goto(base + offset)?;
+            for _ in 0..count {
+                let offset = (reader.bu32()? as u64) << 2;
+                let kind = PartitionKind::from(reader.bu32()?);
+                entries.push((table, offset, kind));
+            }
+        }
+
+        let partitions = entries
+            .into_iter()
+            .map(|(table, offset, kind)| Partition::from_binary(reader, kind, table, base + offset))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Wii { partitions })
+    }
+
+    /// Get all partitions.
+    pub fn partitions(&self) -> &[Partition] { &self.partitions }
+
+    /// Get the first partition of the given kind.
+    pub fn partition(&self, kind: PartitionKind) -> Option<&Partition> {
+        self.partitions.iter().find(|x| x.kind == kind)
+    }
+
+    /// Get the game data partition.
+    pub fn data_partition(&self) -> Option<&Partition> { self.partition(PartitionKind::Data) }
+}
@@ -0,0 +1,135 @@
+//! [Wii][`crate::wii`] partition. Wii discs are split into partitions, each
+//! partition starts with a header containing the [`Ticket`], the [`Tmd`], the
+//! certificate chain, and the location of the H3 hash table and the
+//! (encrypted) partition data.
+
+use crate::helper::{Parser, Seeker};
+use crate::wii::{Ticket, Tmd};
+use crate::Result;
+
+/// [`Partition`] kind.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PartitionKind {
+    /// Game data partition.
+    Data,
+
+    /// System update partition.
+    Update,
+
+    /// Channel installer partition.
+    Channel,
+
+    /// Other partition, the value is usually the lower 32-bits of a title ID.
+    Other(u32),
+}
+
+impl From<u32> for PartitionKind {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => PartitionKind::Data,
+            1 => PartitionKind::Update,
+            2 => PartitionKind::Channel,
+            _ => PartitionKind::Other(value),
+        }
+    }
+}
+
+impl PartitionKind {
+    /// Get the raw value of the partition kind.
+    pub fn value(&self) -> u32 {
+        match self {
+            PartitionKind::Data => 0,
+            PartitionKind::Update => 1,
+            PartitionKind::Channel => 2,
+            PartitionKind::Other(value) => *value,
+        }
+    }
+}
+
+/// [Wii][`crate::wii`] partition object.
+///
+/// All offsets are relative to the start of the partition, with the exception
+/// of [`Partition::offset`] that is relative to the start of the disc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Partition {
+    /// Partition kind.
+    pub kind: PartitionKind,
+
+    /// Index of the partition table (0-3) that this partition was listed in.
+    pub table: usize,
+
+    /// Partition offset from the start of the disc.
+    pub offset: u64,
+
+    /// Partition ticket.
+    pub ticket: Ticket,
+
+    /// Partition title metadata.
+    pub tmd: Tmd,
+
+    /// Title metadata size.
+    pub tmd_size: u32,
+
+    /// Title metadata offset.
+    pub tmd_offset: u64,
+
+    /// Certificate chain size.
+    pub cert_chain_size: u32,
+
+    /// Certificate chain offset.
+    pub cert_chain_offset: u64,
+
+    /// H3 hash table offset.
+    pub h3_offset: u64,
+
+    /// Encrypted partition data offset.
+    pub data_offset: u64,
+
+    /// Encrypted partition data size.
+    pub data_size: u64,
+}
+
+impl Partition {
+    /// Parse [Wii][`crate::wii`] partition header located at `offset` (from
+    /// the start of the disc).
+    pub fn from_binary<D: Parser + Seeker>(
+        input: &mut D,
+        kind: PartitionKind,
+        table: usize,
+        offset: u64,
+    ) -> Result<Self> {
+        input.goto(offset)?;
+        let ticket = Ticket::from_binary(input)?;
+        let tmd_size = input.bu32()?;
+        let tmd_offset = (input.bu32()? as u64) << 2;
+        let cert_chain_size = input.bu32()?;
+        let cert_chain_offset = (input.bu32()? as u64) << 2;
+        let h3_offset = (input.bu32()? as u64) << 2;
+        let data_offset = (input.bu32()? as u64) << 2;
+        let data_size = (input.bu32()? as u64) << 2;
+
+        input.goto(offset + tmd_offset)?;
+        let tmd = Tmd::from_binary(input)?;
+
+        Ok(Self {
+            kind,
+            table,
+            offset,
+            ticket,
+            tmd,
+            tmd_size,
+            tmd_offset,
+            cert_chain_size,
+            cert_chain_offset,
+            h3_offset,
+            data_offset,
+            data_size,
+        })
+    }
+
+    /// Get the required IOS version for this partition. See
+    /// [`Tmd::ios_version`].
+    pub fn ios_version(&self) -> Option<u32> { self.tmd.ios_version() }
+}
@@ -0,0 +1,205 @@
+//! [Wii][`crate::wii`] partition data decryption (requires the `crypto`
+//! feature).
+//!
+//! The partition data is split into clusters of `0x8000` bytes. Each cluster
+//! starts with `0x400` bytes of hashes followed by `0x7C00` bytes of user data,
+//! both encrypted with AES-128-CBC using the title key. The hashes are
+//! encrypted with a zero IV and the user data with bytes `0x3D0..0x3E0` of the
+//! encrypted hash block as IV.
+//!
+//! [`PartitionReader`] decrypts the partition data on demand and exposes only
+//! the user data as a contiguous, seekable stream. The stream starts with a
+//! disc header and can be parsed with [`Gcm::from_binary`][`crate::Gcm`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let common_key = [0u8; 16]; // the common key is not provided by picori
+//!     let mut file = File::open("game.iso")?;
+//!     let wii = picori::Wii::from_binary(&mut file)?;
+//!     let partition = wii.data_partition().unwrap();
+//!     let mut reader = picori::wii::PartitionReader::new(&mut file, partition, &common_key)?;
+//!     let gcm = picori::Gcm::from_binary(&mut reader)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Read, Seek, SeekFrom};
+
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::{BlockDecryptMut, KeyIvInit};
+
+use crate::disc::DiscReader;
+use crate::helper::{Parser, Reader, Seeker};
+use crate::wii::{Partition, Ticket};
+use crate::Result;
+
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+/// Size of an encrypted cluster.
+pub const CLUSTER_SIZE: usize = 0x8000;
+
+/// Size of the hash block at the start of each cluster.
+pub const CLUSTER_HASH_SIZE: usize = 0x400;
+
+/// Size of the user data in each cluster.
+pub const CLUSTER_DATA_SIZE: usize = CLUSTER_SIZE - CLUSTER_HASH_SIZE;
+
+/// Decrypt `data` in-place with AES-128-CBC.
+pub(crate) fn decrypt(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+    Aes128CbcDec::new(key.into(), iv.into())
+        .decrypt_padded_mut::<NoPadding>(data)
+        .expect("data must be a multiple of the block size");
+}
+
+impl Ticket {
+    /// Decrypt the title key with the given common key. The common key must
+    /// match [`Ticket::common_key_index`] and is not provided by this library.
+    pub fn title_key(&self, common_key: &[u8; 16]) -> [u8; 16] {
+        let mut title_key = self.encrypted_title_key;
+        decrypt(common_key, &self.title_key_iv(), &mut title_key);
+        title_key
+    }
+}
+
+/// Decrypted cluster.
+pub struct Cluster {
+    /// Decrypted hash block.
+    pub hashes: Box<[u8; CLUSTER_HASH_SIZE]>,
+
+    /// Decrypted user data.
+    pub data: Box<[u8; CLUSTER_DATA_SIZE]>,
+}
+
+/// Reader for decrypted [Wii][`crate::wii`] partition data.
+pub struct PartitionReader<D: Parser + Seeker> {
+    reader: D,
+    title_key: [u8; 16],
+    pub(crate) h3_offset: u64,
+    pub(crate) h3_hash: Option<[u8; 20]>,
+    data_offset: u64,
+    cluster_count: u64,
+    position: u64,
+    cached: Option<(u64, Cluster)>,
+}
+
+impl<D: Parser + Seeker> PartitionReader<D> {
+    /// Create a new partition reader. `common_key` is used to decrypt the
+    /// title key found in the partition [`Ticket`].
+    pub fn new(reader: D, partition: &Partition, common_key: &[u8; 16]) -> Result<Self> {
+        let title_key = partition.ticket.title_key(common_key);
+        Ok(Self::with_title_key(reader, partition, title_key))
+    }
+
+    /// Create a new partition reader with an already decrypted title key.
+    pub fn with_title_key(reader: D, partition: &Partition, title_key: [u8; 16]) -> Self {
+        Self {
+            reader,
+            title_key,
+            h3_offset: partition.offset + partition.h3_offset,
+            h3_hash: partition.tmd.contents.first().map(|x| x.hash),
+            data_offset: partition.offset + partition.data_offset,
+            cluster_count: partition.data_size / CLUSTER_SIZE as u64,
+            position: 0,
+            cached: None,
+        }
+    }
+
+    /// Number of clusters in the partition.
+    pub fn cluster_count(&self) -> u64 { self.cluster_count }
+
+    /// Size of the decrypted user data.
+    pub fn data_size(&self) -> u64 { self.cluster_count * CLUSTER_DATA_SIZE as u64 }
+
+    /// Get a mutable reference to the underlying reader.
+    pub(crate) fn inner(&mut self) -> &mut D { &mut self.reader }
+
+    /// Read the raw (encrypted) cluster at index `index`.
+    pub fn read_raw_cluster(&mut self, index: u64) -> Result<Vec<u8>> {
+        self.reader
+            .goto(self.data_offset + index * CLUSTER_SIZE as u64)?;
+        self.reader.read_as_vec(CLUSTER_SIZE)
+    }
+
+    /// Read and decrypt the cluster at index `index`.
+    pub fn read_cluster(&mut self, index: u64) -> Result<Cluster> {
+        let mut raw = self.read_raw_cluster(index)?;
+        let (hashes, data) = raw.split_at_mut(CLUSTER_HASH_SIZE);
+
+        let mut iv = [0u8; 16];
+        iv.copy_from_slice(&hashes[0x3D0..0x3E0]);
+        decrypt(&self.title_key, &iv, data);
+        decrypt(&self.title_key, &[0u8; 16], hashes);
+
+        let mut cluster = Cluster {
+            hashes: Box::new([0u8; CLUSTER_HASH_SIZE]),
+            data:   Box::new([0u8; CLUSTER_DATA_SIZE]),
+        };
+        cluster.hashes.copy_from_slice(hashes);
+        cluster.data.copy_from_slice(data);
+        Ok(cluster)
+    }
+
+    fn cluster_data(&mut self, index: u64) -> Result<&[u8; CLUSTER_DATA_SIZE]> {
+        if self.cached.as_ref().map(|x| x.0) != Some(index) {
+            let cluster = self.read_cluster(index)?;
+            self.cached = Some((index, cluster));
+        }
+
+        Ok(&self.cached.as_ref().unwrap().1.data)
+    }
+}
+
+impl<D: Parser + Seeker> Read for PartitionReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.data_size() || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let index = self.position / CLUSTER_DATA_SIZE as u64;
+        let offset = (self.position % CLUSTER_DATA_SIZE as u64) as usize;
+        let data = self.cluster_data(index).map_err(std::io::Error::other)?;
+        let size = buf.len().min(CLUSTER_DATA_SIZE - offset);
+        buf[..size].copy_from_slice(&data[offset..offset + size]);
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl<D: Parser + Seeker> Seek for PartitionReader<D> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+            SeekFrom::End(n) => self.data_size().checked_add_signed(n),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<D: Parser + Seeker> DiscReader for PartitionReader<D> {
+    fn block_size(&self) -> u64 { CLUSTER_DATA_SIZE as u64 }
+
+    fn size(&self) -> u64 { self.data_size() }
+
+    fn read_block(&mut self, index: u64) -> Result<Vec<u8>> {
+        Ok(self.cluster_data(index)?.to_vec())
+    }
+}
+
+impl<D: Parser + Seeker> Reader for PartitionReader<D> {}
+impl<D: Parser + Seeker> Seeker for PartitionReader<D> {}
+impl<D: Parser + Seeker> Parser for PartitionReader<D> {}
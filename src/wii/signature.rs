@@ -0,0 +1,103 @@
+//! [Wii][`crate::wii`] signature block. Tickets, title metadata, and
+//! certificates all begin with a signature block. The type of the signature
+//! determines the size of the signature data and the padding that follows it.
+
+use std::panic::Location;
+
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::{ensure, Parser, ProblemLocation, Writer};
+use crate::Result;
+
+/// [`Signature`] kind.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SignatureKind {
+    /// RSA-4096 with SHA-1 (`0x00010000`).
+    Rsa4096,
+
+    /// RSA-2048 with SHA-1 (`0x00010001`).
+    Rsa2048,
+
+    /// Elliptic curve (ECC B-233) with SHA-1 (`0x00010002`).
+    Ecc,
+}
+
+impl SignatureKind {
+    /// Get the raw value of the signature kind.
+    pub fn value(&self) -> u32 {
+        match self {
+            SignatureKind::Rsa4096 => 0x00010000,
+            SignatureKind::Rsa2048 => 0x00010001,
+            SignatureKind::Ecc => 0x00010002,
+        }
+    }
+
+    /// Size of the signature data in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            SignatureKind::Rsa4096 => 0x200,
+            SignatureKind::Rsa2048 => 0x100,
+            SignatureKind::Ecc => 0x3C,
+        }
+    }
+
+    /// Size of the padding that follows the signature data in bytes.
+    pub fn padding(&self) -> usize {
+        match self {
+            SignatureKind::Rsa4096 | SignatureKind::Rsa2048 => 0x3C,
+            SignatureKind::Ecc => 0x40,
+        }
+    }
+}
+
+impl TryFrom<u32> for SignatureKind {
+    type Error = crate::Error;
+
+    fn try_from(value: u32) -> Result<Self> {
+        match value {
+            0x00010000 => Ok(SignatureKind::Rsa4096),
+            0x00010001 => Ok(SignatureKind::Rsa2048),
+            0x00010002 => Ok(SignatureKind::Ecc),
+            _ => Err(
+                ParseProblem::InvalidHeader("unknown signature kind", Location::current()).into(),
+            ),
+        }
+    }
+}
+
+/// [Wii][`crate::wii`] signature block object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signature {
+    /// Signature kind.
+    pub kind: SignatureKind,
+
+    /// Signature data (without padding).
+    pub data: Vec<u8>,
+}
+
+impl Signature {
+    /// Parse signature block, including the padding that follows it.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let kind = SignatureKind::try_from(input.bu32()?)?;
+        let data = input.read_as_vec(kind.size())?;
+        let _padding = input.read_as_vec(kind.padding())?;
+        Ok(Self { kind, data })
+    }
+
+    /// Write signature block, including the (zero) padding that follows it.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.data.len() == self.kind.size(),
+            BuildProblem::InvalidData("signature size", Location::current())
+        );
+        output.bu32(self.kind.value())?;
+        output.u8_array(&self.data)?;
+        output.u8_array(&vec![0; self.kind.padding()])?;
+        Ok(())
+    }
+
+    /// Size of the signature block in bytes, including the signature kind and
+    /// the padding.
+    pub fn block_size(&self) -> usize { 4 + self.kind.size() + self.kind.padding() }
+}
@@ -0,0 +1,127 @@
+//! [Wii][`crate::wii`] Ticket (`ticket.bin`). The ticket grants the console
+//! the right to use a title and contains the encrypted title key that is used
+//! to decrypt the title contents. The ticket is 0x2A4 bytes.
+
+use crate::helper::Parser;
+use crate::wii::Signature;
+use crate::{Ascii, Result};
+
+/// [`Ticket`] time limit entry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeLimit {
+    /// Whether the time limit is enabled.
+    pub enabled: bool,
+
+    /// Time limit in seconds.
+    pub seconds: u32,
+}
+
+/// [Wii][`crate::wii`] Ticket object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ticket {
+    /// Signature of the ticket.
+    pub signature: Signature,
+
+    /// Signature issuer, e.g., `Root-CA00000001-XS00000003`.
+    pub issuer: String,
+
+    /// ECDH data used to generate a one-time key for console-specific tickets.
+    #[cfg_attr(feature = "serde", serde(with = "crate::helper::serde::array"))]
+    pub ecdh_data: [u8; 0x3C],
+
+    /// Ticket format version.
+    pub version: u8,
+
+    /// Title key encrypted with the common key.
+    pub encrypted_title_key: [u8; 16],
+
+    /// Ticket ID.
+    pub ticket_id: u64,
+
+    /// Console ID, or zero if the ticket is not console-specific.
+    pub console_id: u32,
+
+    /// Title ID.
+    pub title_id: u64,
+
+    /// Title version.
+    pub title_version: u16,
+
+    /// Permitted titles mask.
+    pub permitted_titles_mask: u32,
+
+    /// Permit mask.
+    pub permit_mask: u32,
+
+    /// Whether the title can be exported with the PRNG key.
+    pub title_export_allowed: bool,
+
+    /// Index of the common key used to encrypt the title key.
+    pub common_key_index: u8,
+
+    /// Content access permissions, one bit for each content index.
+    #[cfg_attr(feature = "serde", serde(with = "crate::helper::serde::array"))]
+    pub content_access_permissions: [u8; 0x40],
+
+    /// Time limits.
+    pub time_limits: [TimeLimit; 8],
+}
+
+impl Ticket {
+    /// Parse [Wii][`crate::wii`] Ticket.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let signature = Signature::from_binary(input)?;
+        let issuer = input.str_fixed::<0x40, Ascii>()?;
+        let ecdh_data = input.u8_array::<0x3C>()?;
+        let version = input.u8()?;
+        let _reserved0 = input.u8_array::<2>()?;
+        let encrypted_title_key = input.u8_array::<16>()?;
+        let _unknown0 = input.u8()?;
+        let ticket_id = input.bu64()?;
+        let console_id = input.bu32()?;
+        let title_id = input.bu64()?;
+        let _unknown1 = input.bu16()?;
+        let title_version = input.bu16()?;
+        let permitted_titles_mask = input.bu32()?;
+        let permit_mask = input.bu32()?;
+        let title_export_allowed = input.u8()? != 0;
+        let common_key_index = input.u8()?;
+        let _unknown2 = input.u8_array::<0x30>()?;
+        let content_access_permissions = input.u8_array::<0x40>()?;
+        let _padding = input.bu16()?;
+
+        let mut time_limits = [TimeLimit::default(); 8];
+        for time_limit in time_limits.iter_mut() {
+            time_limit.enabled = input.bu32()? != 0;
+            time_limit.seconds = input.bu32()?;
+        }
+
+        Ok(Self {
+            signature,
+            issuer,
+            ecdh_data,
+            version,
+            encrypted_title_key,
+            ticket_id,
+            console_id,
+            title_id,
+            title_version,
+            permitted_titles_mask,
+            permit_mask,
+            title_export_allowed,
+            common_key_index,
+            content_access_permissions,
+            time_limits,
+        })
+    }
+
+    /// Get the initialization vector used to decrypt the title key. This is
+    /// the title ID followed by 8 zero bytes.
+    pub fn title_key_iv(&self) -> [u8; 16] {
+        let mut iv = [0u8; 16];
+        iv[..8].copy_from_slice(&self.title_id.to_be_bytes());
+        iv
+    }
+}
@@ -0,0 +1,229 @@
+//! [Wii][`crate::wii`] Title Metadata (`tmd.bin`). The title metadata
+//! describes a title, which IOS version it requires, and the contents that it
+//! consists of (with their sizes and SHA-1 hashes).
+//!
+//! The title metadata is found in partition headers, in WADs, and on the NAND
+//! (`/title/<title ID>/content/title.tmd`). Files downloaded from the update
+//! servers have the certificate chain appended, see [`Tmd::size`].
+
+use std::panic::Location;
+
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::{ensure, Parser, ProblemLocation, Writer};
+use crate::wii::Signature;
+use crate::{Ascii, Result};
+
+/// [`Tmd`] content record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Content {
+    /// Content ID. This is also the name of the content file (`%08x.app`).
+    pub id: u32,
+
+    /// Content index.
+    pub index: u16,
+
+    /// Content type, e.g., `0x0001` for normal contents and `0x8001` for
+    /// shared contents.
+    pub kind: u16,
+
+    /// Size of the (decrypted) content in bytes.
+    pub size: u64,
+
+    /// SHA-1 hash of the (decrypted) content.
+    pub hash: [u8; 20],
+}
+
+impl Content {
+    /// Whether the content is shared between titles.
+    pub fn is_shared(&self) -> bool { self.kind & 0x8000 != 0 }
+
+    /// Get the name of the content file, e.g., `0000000a.app`.
+    pub fn filename(&self) -> String { format!("{:08x}.app", self.id) }
+
+    /// Get the initialization vector used to decrypt the content (in WADs and
+    /// on the NAND). This is the content index followed by 14 zero bytes.
+    pub fn iv(&self) -> [u8; 16] {
+        let mut iv = [0u8; 16];
+        iv[..2].copy_from_slice(&self.index.to_be_bytes());
+        iv
+    }
+}
+
+/// [Wii][`crate::wii`] Title Metadata object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tmd {
+    /// Signature of the title metadata.
+    pub signature: Signature,
+
+    /// Signature issuer, e.g., `Root-CA00000001-CP00000004`.
+    pub issuer: String,
+
+    /// Title metadata format version.
+    pub version: u8,
+
+    /// Certificate authority CRL version.
+    pub ca_crl_version: u8,
+
+    /// Signer CRL version.
+    pub signer_crl_version: u8,
+
+    /// Whether the title is a vWii title.
+    pub is_vwii: bool,
+
+    /// Title ID of the required system title (IOS).
+    pub system_version: u64,
+
+    /// Title ID.
+    pub title_id: u64,
+
+    /// Title type.
+    pub title_type: u32,
+
+    /// Group ID (maker code).
+    pub group_id: u16,
+
+    /// Region.
+    pub region: u16,
+
+    /// Age ratings.
+    pub ratings: [u8; 16],
+
+    /// IPC mask.
+    pub ipc_mask: [u8; 12],
+
+    /// Access rights.
+    pub access_rights: u32,
+
+    /// Title version.
+    pub title_version: u16,
+
+    /// Index of the boot content.
+    pub boot_index: u16,
+
+    /// Content records.
+    pub contents: Vec<Content>,
+}
+
+impl Tmd {
+    /// Parse [Wii][`crate::wii`] Title Metadata.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let signature = Signature::from_binary(input)?;
+        let issuer = input.str_fixed::<0x40, Ascii>()?;
+        let version = input.u8()?;
+        let ca_crl_version = input.u8()?;
+        let signer_crl_version = input.u8()?;
+        let is_vwii = input.u8()? != 0;
+        let system_version = input.bu64()?;
+        let title_id = input.bu64()?;
+        let title_type = input.bu32()?;
+        let group_id = input.bu16()?;
+        let _zero = input.bu16()?;
+        let region = input.bu16()?;
+        let ratings = input.u8_array::<16>()?;
+        let _reserved0 = input.u8_array::<12>()?;
+        let ipc_mask = input.u8_array::<12>()?;
+        let _reserved1 = input.u8_array::<18>()?;
+        let access_rights = input.bu32()?;
+        let title_version = input.bu16()?;
+        let content_count = input.bu16()?;
+        let boot_index = input.bu16()?;
+        let _minor_version = input.bu16()?;
+
+        ensure!(
+            content_count <= 512,
+            ParseProblem::InvalidRange("content count limit (max 512)", Location::current())
+        );
+
+        let contents = (0..content_count)
+            .map(|_| -> Result<Content> {
+                Ok(Content {
+                    id:    input.bu32()?,
+                    index: input.bu16()?,
+                    kind:  input.bu16()?,
+                    size:  input.bu64()?,
+                    hash:  input.u8_array::<20>()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            signature,
+            issuer,
+            version,
+            ca_crl_version,
+            signer_crl_version,
+            is_vwii,
+            system_version,
+            title_id,
+            title_type,
+            group_id,
+            region,
+            ratings,
+            ipc_mask,
+            access_rights,
+            title_version,
+            boot_index,
+            contents,
+        })
+    }
+
+    /// Get the required IOS version, e.g., `Some(36)` for IOS36. Returns
+    /// [`None`] if the required system title is not an IOS (this is the case
+    /// for IOS titles themselves and the boot2/system menu).
+    pub fn ios_version(&self) -> Option<u32> {
+        if self.system_version >> 32 == 0x00000001 {
+            Some(self.system_version as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Get the content record with the given index.
+    pub fn content(&self, index: u16) -> Option<&Content> {
+        self.contents.iter().find(|x| x.index == index)
+    }
+
+    /// Size of the title metadata in bytes, the header and the content
+    /// records.
+    pub fn size(&self) -> usize { self.signature.block_size() + 0xA4 + 0x24 * self.contents.len() }
+
+    /// Write [Wii][`crate::wii`] Title Metadata. Reserved fields are written
+    /// as zeros.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.contents.len() <= 512,
+            BuildProblem::InvalidData("content count limit (max 512)", Location::current())
+        );
+        self.signature.to_binary(output)?;
+        output.str::<0x40, Ascii>(&self.issuer)?;
+        output.u8(self.version)?;
+        output.u8(self.ca_crl_version)?;
+        output.u8(self.signer_crl_version)?;
+        output.u8(self.is_vwii as u8)?;
+        output.bu64(self.system_version)?;
+        output.bu64(self.title_id)?;
+        output.bu32(self.title_type)?;
+        output.bu16(self.group_id)?;
+        output.bu16(0)?;
+        output.bu16(self.region)?;
+        output.u8_array(&self.ratings)?;
+        output.u8_array(&[0; 12])?;
+        output.u8_array(&self.ipc_mask)?;
+        output.u8_array(&[0; 18])?;
+        output.bu32(self.access_rights)?;
+        output.bu16(self.title_version)?;
+        output.bu16(self.contents.len() as u16)?;
+        output.bu16(self.boot_index)?;
+        output.bu16(0)?;
+        for content in self.contents.iter() {
+            output.bu32(content.id)?;
+            output.bu16(content.index)?;
+            output.bu16(content.kind)?;
+            output.bu64(content.size)?;
+            output.u8_array(&content.hash)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! [Wii][`crate::wii`] partition hash verification (requires the `crypto`
+//! feature).
+//!
+//! The partition data is protected by a tree of SHA-1 hashes:
+//!
+//! * `H0` - 31 hashes per cluster, one for each `0x400` byte subblock of user
+//!   data.
+//! * `H1` - 8 hashes per subgroup (8 clusters), one for each `H0` table.
+//! * `H2` - 8 hashes per group (8 subgroups), one for each `H1` table.
+//! * `H3` - one hash per group, one for each `H2` table. The `H3` table is
+//!   stored (unencrypted) in the partition header area.
+//! * `H4` - the hash of the `H3` table, stored in the first content record of
+//!   the partition [TMD][`crate::wii::Tmd`].
+//!
+//! The `H0`, `H1`, and `H2` tables are stored in the hash block of each
+//! cluster.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let common_key = [0u8; 16]; // the common key is not provided by picori
+//!     let mut file = File::open("game.iso")?;
+//!     let wii = picori::Wii::from_binary(&mut file)?;
+//!     let partition = wii.data_partition().unwrap();
+//!     let mut reader = picori::wii::PartitionReader::new(&mut file, partition, &common_key)?;
+//!     for failure in reader.verify()? {
+//!         println!("{:?}", failure);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use sha1::{Digest, Sha1};
+
+use crate::helper::{Parser, Seeker};
+use crate::wii::reader::{Cluster, PartitionReader, CLUSTER_DATA_SIZE};
+use crate::Result;
+
+/// Size of a subblock of user data covered by a single `H0` hash.
+pub const SUBBLOCK_SIZE: usize = 0x400;
+
+/// Number of subblocks in a cluster.
+pub const SUBBLOCKS_PER_CLUSTER: usize = CLUSTER_DATA_SIZE / SUBBLOCK_SIZE;
+
+/// Size of the `H3` table.
+pub const H3_TABLE_SIZE: usize = 0x18000;
+
+const H0_OFFSET: usize = 0x000;
+const H0_SIZE: usize = 20 * SUBBLOCKS_PER_CLUSTER;
+const H1_OFFSET: usize = 0x280;
+const H1_SIZE: usize = 20 * 8;
+const H2_OFFSET: usize = 0x340;
+const H2_SIZE: usize = 20 * 8;
+
+/// Hash verification failure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HashFailure {
+    /// The `H0` hash of a subblock did not match, i.e., the user data of the
+    /// subblock is corrupt.
+    H0 {
+        /// Cluster index.
+        cluster:  u64,
+        /// Subblock index within the cluster (0-30).
+        subblock: usize,
+    },
+
+    /// The `H1` hash of the `H0` table of a cluster did not match.
+    H1 {
+        /// Cluster index.
+        cluster: u64,
+    },
+
+    /// The `H2` hash of the `H1` table of a cluster did not match.
+    H2 {
+        /// Cluster index.
+        cluster: u64,
+    },
+
+    /// The `H3` hash of the `H2` table of a cluster did not match.
+    H3 {
+        /// Cluster index.
+        cluster: u64,
+    },
+
+    /// The hash of the `H3` table did not match the hash in the
+    /// [TMD][`crate::wii::Tmd`].
+    H4,
+}
+
+impl HashFailure {
+    /// Offset of the failed subblock in the decrypted user data. Returns
+    /// [`None`] for failures that do not belong to a single subblock.
+    pub fn data_offset(&self) -> Option<u64> {
+        match self {
+            HashFailure::H0 { cluster, subblock } => {
+                Some(cluster * CLUSTER_DATA_SIZE as u64 + (subblock * SUBBLOCK_SIZE) as u64)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] { Sha1::digest(data).into() }
+
+/// Verify the hashes of a single decrypted cluster. `h3_table` is used to
+/// verify the `H2` table of the cluster.
+pub fn verify_cluster(index: u64, cluster: &Cluster, h3_table: &[u8]) -> Vec<HashFailure> {
+    let mut failures = Vec::new();
+    let hashes = &cluster.hashes[..];
+
+    for (subblock, data) in cluster.data.chunks(SUBBLOCK_SIZE).enumerate() {
+        let expected = &hashes[H0_OFFSET + subblock * 20..][..20];
+        if sha1(data) != expected {
+            failures.push(HashFailure::H0 {
+                cluster: index,
+                subblock,
+            });
+        }
+    }
+
+    let h1_index = (index % 8) as usize;
+    let expected = &hashes[H1_OFFSET + h1_index * 20..][..20];
+    if sha1(&hashes[H0_OFFSET..H0_OFFSET + H0_SIZE]) != expected {
+        failures.push(HashFailure::H1 { cluster: index });
+    }
+
+    let h2_index = ((index / 8) % 8) as usize;
+    let expected = &hashes[H2_OFFSET + h2_index * 20..][..20];
+    if sha1(&hashes[H1_OFFSET..H1_OFFSET + H1_SIZE]) != expected {
+        failures.push(HashFailure::H2 { cluster: index });
+    }
+
+    let h3_index = (index / 64) as usize;
+    let expected = h3_table.get(h3_index * 20..h3_index * 20 + 20);
+    if Some(&sha1(&hashes[H2_OFFSET..H2_OFFSET + H2_SIZE])[..]) != expected {
+        failures.push(HashFailure::H3 { cluster: index });
+    }
+
+    failures
+}
+
+impl<D: Parser + Seeker> PartitionReader<D> {
+    /// Read the `H3` table of the partition.
+    pub fn h3_table(&mut self) -> Result<Vec<u8>> {
+        let offset = self.h3_offset;
+        let reader = self.inner();
+        reader.goto(offset)?;
+        reader.read_as_vec(H3_TABLE_SIZE)
+    }
+
+    /// Verify all hashes of the partition and return every failure found. An
+    /// empty result means that the partition data is intact.
+    pub fn verify(&mut self) -> Result<Vec<HashFailure>> {
+        let h3_table = self.h3_table()?;
+
+        let mut failures = Vec::new();
+        if self.h3_hash != Some(sha1(&h3_table)) {
+            failures.push(HashFailure::H4);
+        }
+
+        for index in 0..self.cluster_count() {
+            let cluster = self.read_cluster(index)?;
+            failures.extend(verify_cluster(index, &cluster, &h3_table));
+        }
+
+        Ok(failures)
+    }
+}
@@ -0,0 +1,264 @@
+//! Wii save data exports (`data.bin`, requires the `crypto` feature).
+//!
+//! Saves copied to an SD card by the Wii system menu are stored in
+//! `private/wii/title/<game code>/data.bin`. The file consists of:
+//!
+//! * Header (`0xF0C0` bytes, encrypted with the SD key) - the title ID, the
+//!   permissions, an MD5 hash, and the banner (`banner.bin`).
+//! * [Bk header][`BkHeader`] (`0x80` bytes) - the console (NG ID and MAC
+//!   address) and the number and size of the files.
+//! * Files - a `0x80` byte header followed by the data (aligned to `0x40`
+//!   bytes), encrypted with the SD key and the IV of the file header.
+//! * Certificates and signatures of the console (not parsed).
+//!
+//! The SD key and IV are not provided by this library and must be supplied by
+//! the caller, see [`SaveData::from_binary`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let (sd_key, sd_iv) = ([0u8; 16], [0u8; 16]); // not provided by picori
+//!     let mut file = File::open("data.bin")?;
+//!     let save = picori::wii_save::SaveData::from_binary(&mut file, &sd_key, &sd_iv)?;
+//!     println!("{:016x}", save.title_id);
+//!     for file in save.files.iter() {
+//!         println!("{} ({} bytes)", file.name, file.data.len());
+//!     }
+//!     save.extract_to("save")?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::fs;
+use std::io::Cursor;
+use std::panic::Location;
+use std::path::{Component, Path};
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation};
+use crate::wii::reader::decrypt;
+use crate::{Ascii, Result};
+
+/// Size of the (encrypted) header.
+pub const HEADER_SIZE: usize = 0xF0C0;
+
+/// Size of the [Bk header][`BkHeader`].
+pub const BK_HEADER_SIZE: usize = 0x80;
+
+/// Size of a file header.
+pub const FILE_HEADER_SIZE: usize = 0x80;
+
+/// Magic of a file header.
+const FILE_MAGIC: u32 = 0x03ADF17E;
+
+/// Kind of a [`SaveFile`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SaveFileKind {
+    /// File (`1`).
+    File,
+
+    /// Directory (`2`).
+    Directory,
+}
+
+/// Bk header, the information about the console and the files of the save.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BkHeader {
+    /// Version of the header (`1`).
+    pub version: u16,
+
+    /// NG ID of the console.
+    pub ng_id: u32,
+
+    /// Number of files.
+    pub file_count: u32,
+
+    /// Size of the files (headers and data).
+    pub files_size: u32,
+
+    /// Total size of the files and the certificates.
+    pub total_size: u32,
+
+    /// Game code (lower 32 bits of the title ID).
+    pub game_code: [u8; 4],
+
+    /// MAC address of the console.
+    pub mac_address: [u8; 6],
+}
+
+impl BkHeader {
+    /// Parse Bk header (`0x80` bytes).
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
+        let size = input.bu32()?;
+        let magic = input.u8_array::<2>()?;
+        ensure!(
+            size == 0x70 && &magic == b"Bk",
+            ParseProblem::InvalidMagic("expected: Bk", Location::current())
+        );
+        let version = input.bu16()?;
+        let ng_id = input.bu32()?;
+        let file_count = input.bu32()?;
+        let files_size = input.bu32()?;
+        let _unknown = input.u8_array::<8>()?;
+        let total_size = input.bu32()?;
+        let _unknown = input.u8_array::<0x44>()?;
+        let game_code = input.u8_array::<4>()?;
+        let mac_address = input.u8_array::<6>()?;
+        let _padding = input.u8_array::<0x12>()?;
+
+        Ok(Self {
+            version,
+            ng_id,
+            file_count,
+            files_size,
+            total_size,
+            game_code,
+            mac_address,
+        })
+    }
+}
+
+/// File (or directory) of a save.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveFile {
+    /// Path of the file, relative to the data directory of the title, e.g.,
+    /// `zeldaTp.dat`.
+    pub name: String,
+
+    /// Kind of the file.
+    pub kind: SaveFileKind,
+
+    /// Permissions (owner, group, and other).
+    pub permissions: u8,
+
+    /// Attributes.
+    pub attributes: u8,
+
+    /// Decrypted data, empty for directories.
+    pub data: Vec<u8>,
+}
+
+/// Wii save data export (`data.bin`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveData {
+    /// Title ID.
+    pub title_id: u64,
+
+    /// Permissions of the banner.
+    pub permissions: u8,
+
+    /// MD5 hash of the header.
+    pub hash: [u8; 16],
+
+    /// Banner (`banner.bin`, starting with `WIBN`).
+    pub banner: Vec<u8>,
+
+    /// Bk header.
+    pub bk_header: BkHeader,
+
+    /// Files and directories.
+    pub files: Vec<SaveFile>,
+}
+
+impl SaveData {
+    /// Parse and decrypt save data from binary stream with the SD key and IV.
+    pub fn from_binary<D: Parser>(
+        input: &mut D,
+        sd_key: &[u8; 16],
+        sd_iv: &[u8; 16],
+    ) -> Result<Self> {
+        let mut header = input.read_as_vec(HEADER_SIZE)?;
+        decrypt(sd_key, sd_iv, &mut header);
+        let mut reader = Cursor::new(&header);
+        let title_id = reader.bu64()?;
+        let banner_size = reader.bu32()? as usize;
+        let permissions = reader.u8()?;
+        let _unknown = reader.u8()?;
+        let hash = reader.u8_array::<16>()?;
+        ensure!(
+            0x20 + banner_size <= HEADER_SIZE,
+            ParseProblem::InvalidHeader("save banner size", Location::current())
+        );
+        let banner = header[0x20..0x20 + banner_size].to_vec();
+
+        let bk_header = BkHeader::from_binary(input)?;
+        let mut files = Vec::with_capacity(bk_header.file_count as usize);
+        for _ in 0..bk_header.file_count {
+            let magic = input.bu32()?;
+            ensure!(
+                magic == FILE_MAGIC,
+                ParseProblem::InvalidMagic("expected: 0x03ADF17E", Location::current())
+            );
+            let size = input.bu32()? as usize;
+            let permissions = input.u8()?;
+            let attributes = input.u8()?;
+            let kind = match input.u8()? {
+                1 => SaveFileKind::File,
+                2 => SaveFileKind::Directory,
+                _ => Err(ParseProblem::InvalidData(
+                    "save file kind",
+                    Location::current(),
+                ))?,
+            };
+            let name = input.str_fixed::<0x45, Ascii>()?;
+            let iv = input.u8_array::<16>()?;
+            let _unknown = input.u8_array::<0x20>()?;
+
+            let data = match kind {
+                SaveFileKind::File => {
+                    let mut data = input.read_as_vec(size.next_multiple_of(0x40))?;
+                    decrypt(sd_key, &iv, &mut data);
+                    data.truncate(size);
+                    data
+                },
+                SaveFileKind::Directory => Vec::new(),
+            };
+            files.push(SaveFile {
+                name,
+                kind,
+                permissions,
+                attributes,
+                data,
+            });
+        }
+
+        Ok(Self {
+            title_id,
+            permissions,
+            hash,
+            banner,
+            bk_header,
+            files,
+        })
+    }
+
+    /// Extract the files and directories of the save to the directory `path`.
+    /// Returns an error if a file name is not a relative path.
+    pub fn extract_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let root = path.as_ref();
+        fs::create_dir_all(root)?;
+        for file in self.files.iter() {
+            let name = Path::new(&file.name);
+            ensure!(
+                name.components().all(|x| matches!(x, Component::Normal(_))),
+                ParseProblem::InvalidData(
+                    "save file name (not a relative path)",
+                    Location::current()
+                )
+            );
+            let target = root.join(name);
+            match file.kind {
+                SaveFileKind::Directory => fs::create_dir_all(target)?,
+                SaveFileKind::File => {
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(target, &file.data)?
+                },
+            }
+        }
+        Ok(())
+    }
+}
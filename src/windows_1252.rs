@@ -0,0 +1,301 @@
+//! [Windows-1252][`Windows1252`] encoding.
+//!
+//! [Windows-1252][`Windows1252`] (CP-1252) is an extension of Latin-1 (ISO
+//! 8859-1) used for the text of European games. The range `[0x80,0x9F]`,
+//! which holds control characters in Latin-1, is used for additional
+//! characters, e.g., `€` and `Œ`. The five unused bytes in this range are
+//! decoded as the Latin-1 control characters (like web browsers do), so every
+//! byte can be decoded and Latin-1 text is decoded correctly.
+//!
+//! # Examples
+//!
+//! ```
+//! # use picori::{Result, Windows1252};
+//! fn main() -> Result<()> {
+//!     let data = Windows1252::encode("Café 5€")?;
+//!     assert_eq!(data, b"Caf\xE9 5\x80");
+//!     assert_eq!(Windows1252::all(&data)?, "Café 5€");
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{BufReader, Read};
+
+use crate::encoding::{
+    decode_cow, CharOffset, CharOffsets, ErrorPolicy, Lossy, Tracked, WithPolicy,
+};
+use crate::error::EncodingProblem::*;
+use crate::helper::ProblemLocation;
+#[cfg(feature = "std")]
+use crate::helper::{ParseStringEncoding, Parser};
+use crate::Result;
+
+/// Characters of the bytes `[0x80,0x9F]`.
+const HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// [Windows-1252][`Windows1252`] encoding.
+pub struct Windows1252 {}
+
+/// A iterator decoder for the [`Windows1252`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:    Tracked<<I as IntoIterator>::IntoIter>,
+    _marker: PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:    Tracked::new(iter.into_iter()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replace invalid bytes with U+FFFD (REPLACEMENT CHARACTER) instead of
+    /// returning an error. [Windows-1252][`Windows1252`] has no invalid bytes,
+    /// this exists for symmetry with the other decoders.
+    pub fn lossy(self) -> Lossy<Self> { Lossy::new(self) }
+
+    /// Handle invalid bytes according to `policy`.
+    pub fn policy(self, policy: ErrorPolicy) -> WithPolicy<Self> { WithPolicy::new(self, policy) }
+
+    /// Yield the byte offset of each character together with the character.
+    pub fn char_offsets(self) -> CharOffsets<Self> { CharOffsets::new(self) }
+
+    /// Decode a single byte.
+    pub fn decode_byte(byte: u8) -> char {
+        match byte {
+            0x80..=0x9F => HIGH[byte as usize - 0x80],
+            _ => byte as char,
+        }
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = *self.iter.next()?.borrow();
+        Some(Ok(Self::decode_byte(byte)))
+    }
+}
+
+impl<I> CharOffset for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn char_offset(&self) -> usize { self.iter.offset() }
+}
+
+impl Windows1252 {
+    /// Encode a single character, or [`None`] if the character is not part of
+    /// [Windows-1252][`Windows1252`].
+    pub fn encode_char(c: char) -> Option<u8> {
+        match c {
+            '\u{00}'..='\u{7F}' | '\u{A0}'..='\u{FF}' => Some(c as u8),
+            _ => HIGH.iter().position(|x| *x == c).map(|x| x as u8 + 0x80),
+        }
+    }
+
+    /// Encode all characters of `data`. Fails with the first character that
+    /// is not part of [Windows-1252][`Windows1252`] and its byte index in
+    /// `data`. No NULL byte is appended.
+    pub fn encode(data: &str) -> Result<Vec<u8>> {
+        data.char_indices()
+            .map(|(index, c)| {
+                Self::encode_char(c)
+                    .ok_or_else(|| InvalidCharacter(c, index, Location::current()).into())
+            })
+            .collect()
+    }
+
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL bytes and only
+    /// stop at the end of the iterator.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode all bytes of `data` like [`Windows1252::all`], but ASCII-only
+    /// data is borrowed without allocating.
+    pub fn decode_cow(data: &[u8]) -> Result<Cow<'_, str>> {
+        decode_cow(data, &[], |data| Self::all(data))
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`Windows1252::all`], but invalid
+    /// bytes are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).lossy().collect()
+    }
+
+    /// Decode the first string like [`Windows1252::first`], but invalid bytes
+    /// are replaced with U+FFFD (REPLACEMENT CHARACTER).
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .lossy()
+            .take_while(|c| *c != '\0')
+            .collect()
+    }
+
+    /// Decode all bytes into a string like [`Windows1252::all`], invalid bytes
+    /// are handled according to `policy`.
+    pub fn all_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).policy(policy).collect()
+    }
+
+    /// Decode the first string like [`Windows1252::first`], invalid bytes are
+    /// handled according to `policy`.
+    pub fn first_with<I>(iter: I, policy: ErrorPolicy) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .policy(policy)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::cp1252`] for decoding as [Windows-1252][`Windows1252`]
+/// strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [Windows-1252][`Windows1252`].
+    fn cp1252<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+#[cfg(feature = "std")]
+impl ParseStringEncoding for Windows1252 {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for (index, c) in data.char_indices() {
+            let byte =
+                Self::encode_char(c).ok_or(InvalidCharacter(c, index, Location::current()))?;
+            *buffer
+                .get_mut(i)
+                .ok_or(BufferTooSmall(Location::current()))? = byte;
+            i += 1;
+        }
+        Ok(i)
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        let buffer = BufReader::new(reader);
+        let iter = buffer
+            .bytes()
+            .take_while(|x| x.is_ok())
+            .filter_map(|x| x.ok());
+        Self::first(iter)
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str() {
+        let data = b"Gr\xFC\xDFe \x80\0def";
+        assert_eq!(Windows1252::parse_str(data).unwrap(), "Grüße €".to_string());
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let text = Windows1252::all(&data).unwrap();
+        assert_eq!(text.chars().count(), 256);
+        assert_eq!(Windows1252::encode(&text).unwrap(), data);
+        assert!(Windows1252::encode("ゲーム").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Yay0 decompression.
+//!
+//! Yay0 (also known as SZP) is the predecessor of [Yaz0][`crate::yaz0`]. It
+//! uses the same back-references, but stores the code bits, the
+//! back-references, and the literal bytes in three separate streams.
+//!
+//! ## Decompression
+//!
+//! Decompress a Yay0 compressed file:
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("data.yay0")?;
+//!     let mut reader = picori::Yay0Reader::new(file)?;
+//!     // use `reader` to read the decompressed data like any other file
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Alternatively, you can use the [`decompress`] or [`decompress_into`]
+//! function to decompress:
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("data.yay0")?;
+//!     let header = picori::yay0::Header::from_binary(&mut file)?;
+//!     let decompressed = picori::yay0::decompress(&mut file, &header)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Cursor, Read, Seek};
+use std::panic::Location;
+
+use crate::compression::Decompress;
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::{Reader, Result};
+
+/// Yay0 magic number representing the four characters "Yay0".
+pub(crate) const MAGIC: u32 = 0x59617930;
+
+/// Yay0 header.
+pub struct Header {
+    /// Yay0 magic (0x59617930).
+    pub magic: u32,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+    /// Offset of the back-references (relative to the header).
+    pub link_offset: u32,
+    /// Offset of the literal bytes (relative to the header).
+    pub chunk_offset: u32,
+}
+
+impl Header {
+    /// Reads a Yay0 header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        Ok(Header {
+            magic: input.bu32()?,
+            decompressed_size: input.bu32()?,
+            link_offset: input.bu32()?,
+            chunk_offset: input.bu32()?,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool {
+        self.magic == MAGIC && self.link_offset >= 0x10 && self.chunk_offset >= 0x10
+    }
+}
+
+/// Decompresses a Yay0 compressed file.
+///
+/// If the data does not start with a Yay0 header, reads and seeks are passed
+/// through to the underlying reader.
+pub struct Yay0Reader<D: Parser + Seeker> {
+    reader:       D,
+    decompressed: Option<Cursor<Vec<u8>>>,
+}
+
+impl<D: Parser + Seeker> Yay0Reader<D> {
+    /// Creates a new Yay0 reader.
+    pub fn new(mut reader: D) -> Result<Yay0Reader<D>> {
+        let base = reader.position()?;
+        match Header::from_binary(&mut reader) {
+            Ok(header) if header.is_valid() => {
+                let data = decompress(&mut reader, &header)?;
+                Ok(Yay0Reader {
+                    reader,
+                    decompressed: Some(Cursor::new(data)),
+                })
+            },
+            _ => {
+                reader.goto(base)?;
+                Ok(Yay0Reader {
+                    reader,
+                    decompressed: None,
+                })
+            },
+        }
+    }
+
+    /// Decompressed size of the data.
+    pub fn decompressed_size(&self) -> usize {
+        self.decompressed.as_ref().map_or(0, |x| x.get_ref().len())
+    }
+}
+
+impl<D: Parser + Seeker + Read> Read for Yay0Reader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.decompressed.as_mut() {
+            Some(decompressed) => decompressed.read(buf),
+            None => self.reader.read(buf),
+        }
+    }
+}
+
+impl<D: Parser + Seeker + Seek> Seek for Yay0Reader<D> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self.decompressed.as_mut() {
+            Some(decompressed) => {
+                let size = decompressed.get_ref().len() as u64;
+                let position = decompressed.seek(pos)?.min(size);
+                decompressed.set_position(position);
+                Ok(position)
+            },
+            None => self.reader.seek(pos),
+        }
+    }
+}
+
+impl<D: Parser + Seeker> Reader for Yay0Reader<D> {}
+impl<D: Parser + Seeker> Seeker for Yay0Reader<D> {}
+impl<D: Parser + Seeker> Parser for Yay0Reader<D> {}
+
+/// Check if the given data is compressed with Yay0 by looking for the Yay0
+/// magic.
+pub fn is_yay0<D: Parser + Seeker>(input: &mut D) -> bool {
+    let mut check = || -> Result<bool> {
+        let base = input.position()?;
+        let header = Header::from_binary(input)?;
+        let is_compressed = header.is_valid();
+        input.goto(base)?;
+        Ok(is_compressed)
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Decompresses the data into a new allocated [`Vec`]. `input` must be
+/// positioned directly after the `header`.
+pub fn decompress<D: Parser + Seeker>(input: &mut D, header: &Header) -> Result<Vec<u8>> {
+    let mut output = vec![0; header.decompressed_size as usize];
+    decompress_into(input, header, output.as_mut_slice())?;
+    Ok(output)
+}
+
+/// Number of bytes read at once from each of the three streams.
+const STREAM_BUFFER_SIZE: usize = 0x1000;
+
+/// One of the three streams of Yay0 compressed data, read in blocks.
+struct Stream {
+    offset: u64,
+    buffer: Vec<u8>,
+    index:  usize,
+}
+
+impl Stream {
+    fn new(offset: u64) -> Self {
+        Self {
+            offset,
+            buffer: Vec::new(),
+            index: 0,
+        }
+    }
+
+    fn next<D: Parser + Seeker, const L: usize>(&mut self, input: &mut D) -> Result<[u8; L]> {
+        let mut bytes = [0; L];
+        for byte in bytes.iter_mut() {
+            if self.index == self.buffer.len() {
+                input.goto(self.offset)?;
+                self.buffer.resize(STREAM_BUFFER_SIZE, 0);
+                let size = input.read(&mut self.buffer)?;
+                ensure!(size > 0, UnexpectedEndOfData(Location::current()));
+                self.buffer.truncate(size);
+                self.offset += size as u64;
+                self.index = 0;
+            }
+            *byte = self.buffer[self.index];
+            self.index += 1;
+        }
+        Ok(bytes)
+    }
+}
+
+/// Decompresses the data into the beginning of the given buffer, which must
+/// be large enough to hold the decompressed data. `input` must be positioned
+/// directly after the `header`. Returns the number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(
+    input: &mut D,
+    header: &Header,
+    destination: &mut [u8],
+) -> Result<usize> {
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid magic", Location::current())
+    );
+
+    let written = header.decompressed_size as usize;
+    ensure!(
+        destination.len() >= written,
+        InvalidDecompressedSize(Location::current())
+    );
+    let destination = &mut destination[..written];
+
+    // offsets are relative to the start of the header
+    let base = input.position()? - 0x10;
+    let mut codes = Stream::new(base + 0x10);
+    let mut links = Stream::new(base + header.link_offset as u64);
+    let mut chunks = Stream::new(base + header.chunk_offset as u64);
+
+    let size = destination.len();
+    let mut dest = 0;
+    let mut code = 0;
+    let mut code_bits = 0;
+    while dest < size {
+        if code_bits == 0 {
+            code = u32::from_be_bytes(codes.next(input)?);
+            code_bits = 32;
+        }
+
+        if code & 0x8000_0000 != 0 {
+            let [byte] = chunks.next(input)?;
+            destination[dest] = byte;
+            dest += 1;
+        } else {
+            let link = u16::from_be_bytes(links.next(input)?) as usize;
+            let offset = link & 0xFFF;
+            let length = match link >> 12 {
+                0 => u8::from_be_bytes(chunks.next(input)?) as usize + 0x12,
+                length => length + 2,
+            };
+
+            ensure!(offset < dest, UnexpectedEndOfData(Location::current()));
+            ensure!(
+                dest + length <= size,
+                InvalidDecompressedSize(Location::current())
+            );
+            let base = dest - (offset + 1);
+            for n in 0..length {
+                destination[dest] = destination[base + n];
+                dest += 1;
+            }
+        }
+
+        code <<= 1;
+        code_bits -= 1;
+    }
+
+    Ok(written)
+}
+
+/// Yay0 codec, see [`crate::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Yay0;
+
+impl Decompress for Yay0 {
+    const NAME: &'static str = "Yay0";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_yay0(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let header = Header::from_binary(input)?;
+        decompress(input, &header)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let header = Header::from_binary(input)?;
+        decompress_into(input, &header, destination)
+    }
+}
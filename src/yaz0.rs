@@ -2,7 +2,41 @@
 //!
 //! ## Compression
 //!
-//! Compression is not yet supported.
+//! Compress data with the [`compress`] function:
+//!
+//! ```
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let data = b"abcabcabcabcabcabc".to_vec();
+//!     let compressed = picori::yaz0::compress(&data);
+//!     let mut reader = std::io::Cursor::new(compressed);
+//!     let size = picori::yaz0::Header::decompressed_size(&mut reader)?;
+//!     assert_eq!(picori::yaz0::decompress(&mut reader, size)?, data);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`compress_with`] takes [`CompressOptions`] to pick a different
+//! [`Strategy`] or a smaller window. Use [`Strategy::Nintendo`] to create
+//! files identical to the ones found on discs:
+//!
+//! ```
+//! use picori::yaz0::{CompressOptions, Strategy};
+//!
+//! let options = CompressOptions::new().strategy(Strategy::Nintendo);
+//! let compressed = picori::yaz0::compress_with(b"abcabcabcabcabcabc", &options);
+//! ```
+//!
+//! Back-references are found with hash chains over the last
+//! [`WINDOW_SIZE`] bytes. Run `cargo bench --bench yaz0` to measure the
+//! throughput of each strategy.
+//!
+//! With the `parallel` feature, `compress_parallel` compresses large inputs
+//! in chunks using all cores.
+//!
+//! Files with the `Yaz1` magic are encoded exactly like Yaz0 files and are
+//! accepted by all functions of this module; [`Header::variant`] tells them
+//! apart. Use [`CompressOptions::variant`] to create them.
 //!
 //! ## Decompression
 //!
@@ -37,21 +71,61 @@
 //! [Yaz0](http://www.amnoid.de/gc/yaz0.txt) - Implementation of the Yaz0 decompression is based
 //! on the specification and format description by Amnoid.
 
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 use std::panic::Location;
 
+use crate::compression::{Compress, Decompress};
+use crate::error::CompressionProblem::*;
 use crate::error::DecompressionProblem::*;
-use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::helper::{ensure, MatchFinder, Parser, ProblemLocation, Seeker};
 use crate::{Reader, Result};
 
+/// Yaz0 magic number representing the four characters "Yaz0".
+pub(crate) const MAGIC: u32 = 0x59617A30;
+
+/// Yaz1 magic number representing the four characters "Yaz1".
+pub(crate) const MAGIC_YAZ1: u32 = 0x59617A31;
+
+/// Magic of a Yaz0 file. Some titles use `Yaz1` for data that is encoded
+/// exactly like Yaz0 data, the magic is only a flag for the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    /// `Yaz0` magic (0x59617A30).
+    #[default]
+    Yaz0,
+    /// `Yaz1` magic (0x59617A31).
+    Yaz1,
+}
+
+impl Variant {
+    /// Returns the variant with the given magic.
+    pub fn from_magic(magic: u32) -> Option<Variant> {
+        match magic {
+            MAGIC => Some(Variant::Yaz0),
+            MAGIC_YAZ1 => Some(Variant::Yaz1),
+            _ => None,
+        }
+    }
+
+    /// Returns the magic of the variant.
+    pub fn magic(self) -> u32 {
+        match self {
+            Variant::Yaz0 => MAGIC,
+            Variant::Yaz1 => MAGIC_YAZ1,
+        }
+    }
+}
+
 /// Yaz0 header.
 pub struct Header {
-    /// Yaz0 magic (0x59617A30).
+    /// Yaz0 (0x59617A30) or Yaz1 (0x59617A31) magic, see [`Variant`].
     pub magic: u32,
     /// Size of decompressed data.
     pub decompressed_size: u32,
-    _reserved0: u32,
-    _reserved1: u32,
+    /// Alignment of the decompressed data, set by newer tools (`0` if
+    /// unspecified).
+    pub alignment: u32,
+    _reserved: u32,
 }
 
 impl Header {
@@ -60,15 +134,16 @@ impl Header {
         Ok(Header {
             magic: input.bu32()?,
             decompressed_size: input.bu32()?,
-            _reserved0: input.bu32()?,
-            _reserved1: input.bu32()?,
+            alignment: input.bu32()?,
+            _reserved: input.bu32()?,
         })
     }
 
-    /// Checks if the header is valid.
-    pub fn is_valid(&self) -> bool {
-        self.magic == 0x59617A30
-    }
+    /// Checks if the header is valid, i.e., has the magic of a [`Variant`].
+    pub fn is_valid(&self) -> bool { self.variant().is_some() }
+
+    /// Returns the variant of the magic, or [`None`] if the magic is invalid.
+    pub fn variant(&self) -> Option<Variant> { Variant::from_magic(self.magic) }
 
     pub fn decompressed_size(input: &mut impl Parser) -> Result<usize> {
         let header = Header::from_binary(input)?;
@@ -80,12 +155,47 @@ impl Header {
     }
 }
 
+/// Number of decompressed bytes between two checkpoints of [`Yaz0Reader`].
+const CHECKPOINT_INTERVAL: usize = 0x100000;
+
+/// Number of compressed bytes [`Yaz0Reader`] reads at once.
+const INPUT_BUFFER_SIZE: usize = 0x1000;
+
+/// State of the decompression at some decompressed position. Restoring a
+/// state continues the decompression from that position.
+#[derive(Clone)]
+struct Decoder {
+    /// Position of the next compressed byte.
+    input:     u64,
+    /// Position of the next decompressed byte.
+    output:    usize,
+    code:      u8,
+    code_bits: u32,
+    /// Remaining bytes and distance of the current back-reference.
+    copy:      usize,
+    distance:  usize,
+    /// The last [`WINDOW_SIZE`] decompressed bytes.
+    window:    Box<[u8]>,
+}
+
 /// Decompresses a Yaz0 compressed file.
+///
+/// The data is decompressed as it is read, only keeping the sliding window
+/// and a checkpoint for every MiB of decompressed data in memory. Seeking is
+/// cheap; the next read continues from the nearest checkpoint before the new
+/// position (or the current position if that is closer).
+///
+/// If the data does not start with a Yaz0 header, reads and seeks are passed
+/// through to the underlying reader.
 pub struct Yaz0Reader<D: Parser + Seeker> {
-    reader: D,
-    decompressed: Vec<u8>,
-    position: usize,
+    reader:      D,
+    size:        usize,
+    position:    usize,
     transparent: bool,
+    decoder:     Decoder,
+    checkpoints: Vec<Decoder>,
+    input:       Vec<u8>,
+    input_start: u64,
 }
 
 impl<D: Parser + Seeker> Yaz0Reader<D> {
@@ -93,46 +203,148 @@ impl<D: Parser + Seeker> Yaz0Reader<D> {
     pub fn new(mut reader: D) -> Result<Yaz0Reader<D>> {
         let base = reader.position()?;
         let header = Header::from_binary(&mut reader);
-        if header.as_ref().map(|x| x.is_valid()).unwrap_or(false) {
-            let header = header.unwrap();
-            let data = decompress(&mut reader, header.decompressed_size as usize)?;
-            Ok(Yaz0Reader {
-                reader,
-                decompressed: data,
-                position: 0,
-                transparent: false,
-            })
-        } else {
-            reader.goto(base)?;
-            Ok(Yaz0Reader {
-                reader,
-                decompressed: Vec::new(),
-                position: 0,
-                transparent: true,
-            })
-        }
+        let (size, transparent) = match header {
+            Ok(header) if header.is_valid() => (header.decompressed_size as usize, false),
+            _ => {
+                reader.goto(base)?;
+                (0, true)
+            },
+        };
+
+        let input = reader.position()?;
+        let decoder = Decoder {
+            input,
+            output: 0,
+            code: 0,
+            code_bits: 0,
+            copy: 0,
+            distance: 0,
+            window: vec![0; WINDOW_SIZE].into_boxed_slice(),
+        };
+        Ok(Yaz0Reader {
+            reader,
+            size,
+            position: 0,
+            transparent,
+            checkpoints: vec![decoder.clone()],
+            decoder,
+            input: Vec::new(),
+            input_start: input,
+        })
     }
 
     /// Decompressed size of the data.
-    pub fn decompressed_size(&self) -> usize {
-        self.decompressed.len()
+    pub fn decompressed_size(&self) -> usize { self.size }
+
+    /// Next compressed byte.
+    fn next(&mut self) -> Result<u8> {
+        let mut index = (self.decoder.input - self.input_start) as usize;
+        if index >= self.input.len() {
+            self.input_start += self.input.len() as u64;
+            self.input.resize(INPUT_BUFFER_SIZE, 0);
+            let size = self.reader.read(&mut self.input)?;
+            self.input.truncate(size);
+            ensure!(size > 0, UnexpectedEndOfData(Location::current()));
+            index = 0;
+        }
+
+        self.decoder.input += 1;
+        Ok(self.input[index])
+    }
+
+    /// Continue the decompression from `decoder`.
+    fn restore(&mut self, decoder: Decoder) -> Result<()> {
+        self.reader.goto(decoder.input)?;
+        self.input.clear();
+        self.input_start = decoder.input;
+        self.decoder = decoder;
+        Ok(())
+    }
+
+    /// Decompress the next `size` bytes into `output` (or discard them if
+    /// `output` is `None`).
+    fn decode(&mut self, mut output: Option<&mut [u8]>, size: usize) -> Result<()> {
+        for n in 0..size {
+            if self.decoder.copy == 0 {
+                if self.decoder.code_bits == 0 {
+                    self.decoder.code = self.next()?;
+                    self.decoder.code_bits = 8;
+                }
+
+                let literal = self.decoder.code & 0x80 != 0;
+                self.decoder.code <<= 1;
+                self.decoder.code_bits -= 1;
+                if literal {
+                    // a literal byte is a back-reference of one byte to itself
+                    let byte = self.next()?;
+                    self.decoder.copy = 1;
+                    self.decoder.distance = 0;
+                    self.decoder.window[self.decoder.output % WINDOW_SIZE] = byte;
+                } else {
+                    let byte0 = self.next()?;
+                    let byte1 = self.next()?;
+                    let offset = ((byte0 & 0xf) as usize) << 8 | byte1 as usize;
+                    let length = match byte0 >> 4 {
+                        0 => self.next()? as usize + 0x12,
+                        length => length as usize + 2,
+                    };
+                    ensure!(
+                        offset < self.decoder.output,
+                        UnexpectedEndOfData(Location::current())
+                    );
+                    self.decoder.copy = length;
+                    self.decoder.distance = offset + 1;
+                }
+            }
+
+            let decoder = &mut self.decoder;
+            let byte = decoder.window[(decoder.output - decoder.distance) % WINDOW_SIZE];
+            decoder.window[decoder.output % WINDOW_SIZE] = byte;
+            decoder.output += 1;
+            decoder.copy -= 1;
+            if let Some(output) = output.as_mut() {
+                output[n] = byte;
+            }
+
+            if decoder.output.is_multiple_of(CHECKPOINT_INTERVAL)
+                && decoder.output / CHECKPOINT_INTERVAL == self.checkpoints.len()
+            {
+                self.checkpoints.push(self.decoder.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decompress the data up to the current position.
+    fn sync(&mut self) -> Result<()> {
+        let checkpoint = self.position / CHECKPOINT_INTERVAL;
+        let checkpoint = checkpoint.min(self.checkpoints.len() - 1);
+        let output = self.checkpoints[checkpoint].output;
+        if self.position < self.decoder.output || output > self.decoder.output {
+            self.restore(self.checkpoints[checkpoint].clone())?;
+        }
+
+        self.decode(None, self.position - self.decoder.output)
     }
 }
 
 impl<D: Parser + Seeker + Read> Read for Yaz0Reader<D> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if self.transparent {
-            self.reader.read(buf)
-        } else {
-            let size = buf.len();
-            let mut n = 0;
-            while n < size && self.position < self.decompressed.len() {
-                buf[n] = self.decompressed[self.position];
-                n += 1;
-                self.position += 1;
-            }
-            Ok(n)
+            return self.reader.read(buf);
         }
+
+        let size = buf.len().min(self.size.saturating_sub(self.position));
+        let mut decompress = || -> Result<()> {
+            if self.position != self.decoder.output {
+                self.sync()?;
+            }
+            self.decode(Some(buf), size)
+        };
+        decompress().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.position += size;
+        Ok(size)
     }
 }
 
@@ -155,7 +367,7 @@ impl<D: Parser + Seeker + Seek> Seek for Yaz0Reader<D> {
                     }
                 },
                 std::io::SeekFrom::End(n) => {
-                    let absolute = self.decompressed.len() as i64 + n;
+                    let absolute = self.size as i64 + n;
                     if absolute < 0 {
                         Err(std::io::Error::new(
                             std::io::ErrorKind::InvalidInput,
@@ -167,12 +379,7 @@ impl<D: Parser + Seeker + Seek> Seek for Yaz0Reader<D> {
                 },
             }?;
 
-            if position > self.decompressed.len() {
-                self.position = self.decompressed.len();
-            } else {
-                self.position = position;
-            }
-
+            self.position = position.min(self.size);
             Ok(self.position as u64)
         }
     }
@@ -182,8 +389,23 @@ impl<D: Parser + Seeker> Reader for Yaz0Reader<D> {}
 impl<D: Parser + Seeker> Seeker for Yaz0Reader<D> {}
 impl<D: Parser + Seeker> Parser for Yaz0Reader<D> {}
 
+/// Reads the Yaz0 header at the current position of `input` without
+/// decompressing anything, e.g., to show the decompressed size or to allocate
+/// a buffer for it. The position of `input` is not changed.
+pub fn header<D: Parser + Seeker>(input: &mut D) -> Result<Header> {
+    let base = input.position()?;
+    let header = Header::from_binary(input);
+    input.goto(base)?;
+    let header = header?;
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid magic", Location::current())
+    );
+    Ok(header)
+}
+
 /// Check if the given data is compressed with Yaz0 by looking for the Yaz0
-/// magic.
+/// or Yaz1 magic.
 pub fn is_yaz0<D: Parser + Seeker>(input: &mut D) -> bool {
     let mut check = || -> Result<bool> {
         let base = input.position()?;
@@ -204,11 +426,11 @@ pub fn decompress<D: Parser + Seeker>(input: &mut D, decompressed_size: usize) -
     Ok(output)
 }
 
-/// Decompresses the data into the given buffer. The buffer must be large
-/// enough to hold the decompressed data.
-pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]) -> Result<()> {
-    let decompressed_size = destination.len();
-    let size = decompressed_size as usize;
+/// Decompresses the data into the given buffer. Exactly `destination.len()`
+/// bytes are decompressed, see [`Header::decompressed_size`]. Returns the
+/// number of bytes written.
+pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]) -> Result<usize> {
+    let size = destination.len();
     let mut dest = 0;
     let mut code = 0;
     let mut code_bits = 0;
@@ -235,6 +457,10 @@ pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]
             };
 
             ensure!(offset < dest, UnexpectedEndOfData(Location::current()));
+            ensure!(
+                dest + length <= size,
+                InvalidDecompressedSize(Location::current())
+            );
             let base = dest - (offset + 1);
             for n in 0..length {
                 destination[dest] = destination[base + n];
@@ -246,5 +472,497 @@ pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]
         code_bits -= 1;
     }
 
-    Ok(())
+    Ok(size)
+}
+
+/// Size of the sliding window (the largest distance of a back-reference).
+pub const WINDOW_SIZE: usize = 0x1000;
+
+/// Shortest back-reference.
+const MIN_MATCH: usize = 3;
+
+/// Longest back-reference.
+const MAX_MATCH: usize = 0x111;
+
+/// Strategy used to choose between literal bytes and back-references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Always take the longest back-reference at the current position.
+    #[default]
+    Greedy,
+    /// Emit a literal byte instead of a back-reference if the next position
+    /// has a longer back-reference.
+    Lazy,
+    /// Choose the sequence of tokens that results in the smallest output.
+    Optimal,
+    /// Reproduce the token choices of Nintendo's compressor. The farthest of
+    /// the longest back-references is used, and a back-reference is deferred
+    /// by one byte if that makes it at least two bytes longer. Compressing
+    /// the decompressed contents of an original file gives the original
+    /// file byte for byte.
+    Nintendo,
+}
+
+/// Options for [`compress_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressOptions {
+    strategy: Strategy,
+    window:   usize,
+    variant:  Variant,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::default(),
+            window:   WINDOW_SIZE,
+            variant:  Variant::default(),
+        }
+    }
+}
+
+impl CompressOptions {
+    /// Creates the default options, i.e., [`Strategy::Greedy`] with the
+    /// largest window.
+    pub fn new() -> Self { Self::default() }
+
+    /// Strategy used to choose the tokens.
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Largest distance searched for back-references, clamped to
+    /// `1..=`[`WINDOW_SIZE`]. A smaller window is faster but compresses
+    /// worse. [`Strategy::Nintendo`] only reproduces the original output
+    /// with the full window.
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.clamp(1, WINDOW_SIZE);
+        self
+    }
+
+    /// Magic written to the header, [`Variant::Yaz0`] by default.
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+}
+
+/// Encoder of the Yaz0 token stream, i.e., groups of eight tokens (literal
+/// bytes or back-references) preceded by a code byte.
+struct Encoder {
+    output:    Vec<u8>,
+    code:      usize,
+    code_bits: u32,
+}
+
+impl Encoder {
+    fn new(output: Vec<u8>) -> Self {
+        Self {
+            output,
+            code: 0,
+            code_bits: 8,
+        }
+    }
+
+    /// Size in bits of a back-reference of `length` bytes.
+    fn cost(length: usize) -> usize {
+        if length < MIN_MATCH {
+            9
+        } else if length < 0x12 {
+            17
+        } else {
+            25
+        }
+    }
+
+    fn next_token(&mut self) {
+        if self.code_bits == 8 {
+            self.code = self.output.len();
+            self.output.push(0);
+            self.code_bits = 0;
+        }
+        self.code_bits += 1;
+    }
+
+    fn literal(&mut self, byte: u8) {
+        self.next_token();
+        self.output[self.code] |= 0x80 >> (self.code_bits - 1);
+        self.output.push(byte);
+    }
+
+    fn back_reference(&mut self, length: usize, distance: usize) {
+        self.next_token();
+        let offset = distance - 1;
+        if length < 0x12 {
+            self.output.push(((length - 2) << 4 | offset >> 8) as u8);
+            self.output.push(offset as u8);
+        } else {
+            self.output.push((offset >> 8) as u8);
+            self.output.push(offset as u8);
+            self.output.push((length - 0x12) as u8);
+        }
+    }
+
+    /// Emit a back-reference, or a literal byte if `length` is too short.
+    fn token(&mut self, data: &[u8], position: usize, length: usize, distance: usize) -> usize {
+        if length >= MIN_MATCH {
+            self.back_reference(length, distance);
+            length
+        } else {
+            self.literal(data[position]);
+            1
+        }
+    }
+
+    /// Take the bytes of all complete groups of tokens out of the output.
+    fn take_complete(&mut self) -> Vec<u8> {
+        if self.code_bits == 8 {
+            std::mem::take(&mut self.output)
+        } else {
+            let rest = self.output.split_off(self.code);
+            self.code = 0;
+            std::mem::replace(&mut self.output, rest)
+        }
+    }
+
+    /// Emit the tokens for `data[start..]` with `options`. Back-references may
+    /// refer to the [`WINDOW_SIZE`] bytes before `start`.
+    fn encode(&mut self, data: &[u8], start: usize, options: &CompressOptions) {
+        let mut finder = MatchFinder::starting_at(MAX_MATCH, start.saturating_sub(WINDOW_SIZE));
+        if options.strategy == Strategy::Optimal {
+            self.optimal(&mut finder, data, start, data.len(), options.window);
+        } else {
+            let mut position = start;
+            while position < data.len() {
+                position += self.step(&mut finder, data, position, options);
+            }
+        }
+    }
+
+    /// Emit all tokens of the token stream of `other`.
+    #[cfg(feature = "parallel")]
+    fn append(&mut self, other: &Encoder) {
+        let input = other.output.as_slice();
+        let mut index = 0;
+        while index < input.len() {
+            let code = input[index];
+            index += 1;
+            for bit in 0..8 {
+                if index >= input.len() {
+                    break;
+                }
+                if code & (0x80 >> bit) != 0 {
+                    self.literal(input[index]);
+                    index += 1;
+                } else {
+                    let size = if input[index] >> 4 == 0 { 3 } else { 2 };
+                    self.next_token();
+                    self.output.extend_from_slice(&input[index..index + size]);
+                    index += size;
+                }
+            }
+        }
+    }
+
+    /// Emit the tokens for the data at `position` with `options` (all
+    /// strategies but [`Strategy::Optimal`]). Returns the number of bytes
+    /// encoded, at most `MAX_MATCH + 1`. Unless `data` ends within that
+    /// range, `MAX_MATCH + 1` bytes must follow `position`.
+    fn step(
+        &mut self,
+        finder: &mut MatchFinder,
+        data: &[u8],
+        position: usize,
+        options: &CompressOptions,
+    ) -> usize {
+        let window = options.window;
+        let farthest = options.strategy == Strategy::Nintendo;
+        let (length, distance) = finder.find(data, position, window, farthest);
+        if length >= MIN_MATCH && position + 1 < data.len() {
+            let next = finder.find(data, position + 1, window, farthest);
+            match options.strategy {
+                Strategy::Lazy if next.0 > length => {
+                    self.literal(data[position]);
+                    return 1;
+                },
+                Strategy::Nintendo if next.0 >= length + 2 => {
+                    self.literal(data[position]);
+                    self.back_reference(next.0, next.1);
+                    return 1 + next.0;
+                },
+                _ => {},
+            }
+        }
+        self.token(data, position, length, distance)
+    }
+
+    /// Emit the tokens for `data[start..end]` that result in the smallest
+    /// output. Back-references may refer to data before `start` but do not
+    /// extend past `end`.
+    fn optimal(
+        &mut self,
+        finder: &mut MatchFinder,
+        data: &[u8],
+        start: usize,
+        end: usize,
+        window: usize,
+    ) {
+        let matches = (start..end)
+            .map(|position| {
+                let (length, distance) = finder.find(data, position, window, false);
+                (length.min(end - position), distance)
+            })
+            .collect::<Vec<_>>();
+
+        // cost[i] is the smallest size in bits of the tokens for the data
+        // from start + i to end and step[i] the length of the first of them.
+        let size = end - start;
+        let mut cost = vec![0; size + 1];
+        let mut step = vec![1; size];
+        for i in (0..size).rev() {
+            cost[i] = Self::cost(1) + cost[i + 1];
+            for length in MIN_MATCH..=matches[i].0 {
+                let total = Self::cost(length) + cost[i + length];
+                if total < cost[i] {
+                    cost[i] = total;
+                    step[i] = length;
+                }
+            }
+        }
+
+        let mut i = 0;
+        while i < size {
+            i += self.token(data, start + i, step[i], matches[i].1);
+        }
+    }
+}
+
+/// Returns the Yaz0 header for `size` bytes of decompressed data.
+fn header_bytes(size: u32, variant: Variant) -> [u8; 0x10] {
+    let mut header = [0; 0x10];
+    header[0..4].copy_from_slice(&variant.magic().to_be_bytes());
+    header[4..8].copy_from_slice(&size.to_be_bytes());
+    header
+}
+
+/// Compresses `data` (including the Yaz0 header) into a new allocated
+/// [`Vec`].
+pub fn compress(data: &[u8]) -> Vec<u8> { compress_with(data, &CompressOptions::default()) }
+
+/// Compresses `data` (including the Yaz0 header) into a new allocated
+/// [`Vec`] with the given options.
+pub fn compress_with(data: &[u8], options: &CompressOptions) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() / 2 + 0x10);
+    output.extend_from_slice(&header_bytes(data.len() as u32, options.variant));
+    let mut encoder = Encoder::new(output);
+    encoder.encode(data, 0, options);
+    encoder.output
+}
+
+/// Number of bytes compressed by each task of [`compress_parallel`].
+#[cfg(feature = "parallel")]
+const CHUNK_SIZE: usize = 0x40000;
+
+/// Compresses `data` (including the Yaz0 header) with the given options,
+/// using all cores (`parallel` feature).
+///
+/// `data` is split into chunks of 256 KiB that are compressed independently
+/// and stitched into a single stream. Back-references may refer to the data
+/// of the previous chunk but do not cross the end of a chunk, so the output
+/// is slightly larger than the output of [`compress_with`] and
+/// [`Strategy::Nintendo`] does not reproduce the original files.
+#[cfg(feature = "parallel")]
+pub fn compress_parallel(data: &[u8], options: &CompressOptions) -> Vec<u8> {
+    use rayon::prelude::*;
+
+    let chunks = (0..data.len())
+        .step_by(CHUNK_SIZE)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|start| {
+            let end = data.len().min(start + CHUNK_SIZE);
+            let mut encoder = Encoder::new(Vec::new());
+            encoder.encode(&data[..end], start, options);
+            encoder
+        })
+        .collect::<Vec<_>>();
+
+    let size = chunks.iter().map(|x| x.output.len()).sum::<usize>();
+    let mut output = Vec::with_capacity(size + 0x10);
+    output.extend_from_slice(&header_bytes(data.len() as u32, options.variant));
+    let mut encoder = Encoder::new(output);
+    for chunk in chunks {
+        encoder.append(&chunk);
+    }
+    encoder.output
+}
+
+/// Number of bytes [`Yaz0Writer`] collects before encoding them with
+/// [`Strategy::Optimal`].
+const BLOCK_SIZE: usize = 0x10000;
+
+/// Compresses data with Yaz0 as it is written.
+///
+/// The Yaz0 header holds the decompressed size, so it must be known up front.
+/// Only the sliding window and a small amount of pending data are kept in
+/// memory. The output is identical to [`compress_with`], except for
+/// [`Strategy::Optimal`], which only chooses the best tokens within blocks
+/// of 64 KiB.
+///
+/// ```
+/// use std::io::Write;
+///
+/// use picori::yaz0::Yaz0Writer;
+///
+/// # fn main() -> picori::Result<()> {
+/// let mut writer = Yaz0Writer::new(Vec::new(), 12)?;
+/// writer.write_all(b"abcabc")?;
+/// writer.write_all(b"abcabc")?;
+/// let compressed = writer.finish()?;
+/// assert_eq!(compressed, picori::yaz0::compress(b"abcabcabcabc"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct Yaz0Writer<W: Write> {
+    writer:   W,
+    options:  CompressOptions,
+    size:     usize,
+    received: usize,
+    buffer:   Vec<u8>,
+    position: usize,
+    finder:   MatchFinder,
+    encoder:  Encoder,
+}
+
+impl<W: Write> Yaz0Writer<W> {
+    /// Creates a new Yaz0 writer for `size` bytes of decompressed data and
+    /// writes the header.
+    pub fn new(writer: W, size: u32) -> Result<Self> {
+        Self::with_options(writer, size, &CompressOptions::default())
+    }
+
+    /// Creates a new Yaz0 writer with the given options.
+    pub fn with_options(mut writer: W, size: u32, options: &CompressOptions) -> Result<Self> {
+        writer.write_all(&header_bytes(size, options.variant))?;
+        Ok(Self {
+            writer,
+            options: *options,
+            size: size as usize,
+            received: 0,
+            buffer: Vec::new(),
+            position: 0,
+            finder: MatchFinder::new(MAX_MATCH),
+            encoder: Encoder::new(Vec::new()),
+        })
+    }
+
+    /// Encode the pending data, keeping enough of it to choose the tokens
+    /// like [`compress_with`] unless `last` is set.
+    fn encode(&mut self, last: bool) -> std::io::Result<()> {
+        let data = self.buffer.as_slice();
+        if self.options.strategy == Strategy::Optimal {
+            let end = if last {
+                data.len()
+            } else {
+                data.len().saturating_sub(MAX_MATCH)
+            };
+            if last || end >= self.position + BLOCK_SIZE {
+                let window = self.options.window;
+                self.encoder
+                    .optimal(&mut self.finder, data, self.position, end, window);
+                self.position = end;
+            }
+        } else {
+            while self.position < data.len() && (last || data.len() - self.position > MAX_MATCH + 1)
+            {
+                let size = self
+                    .encoder
+                    .step(&mut self.finder, data, self.position, &self.options);
+                self.position += size;
+            }
+        }
+
+        // discard the data that has left the window
+        if self.position > WINDOW_SIZE + BLOCK_SIZE {
+            let discard = self.position - WINDOW_SIZE;
+            self.buffer.drain(..discard);
+            self.position -= discard;
+            self.finder.reset(&self.buffer, self.position);
+        }
+
+        let complete = self.encoder.take_complete();
+        self.writer.write_all(&complete)
+    }
+
+    /// Compresses the remaining data and returns the underlying writer.
+    /// Fails if the number of bytes written differs from the size given to
+    /// [`Yaz0Writer::new`].
+    pub fn finish(mut self) -> Result<W> {
+        ensure!(
+            self.received == self.size,
+            InvalidSize("fewer bytes written than expected", Location::current())
+        );
+        self.encode(true)?;
+        let rest = std::mem::take(&mut self.encoder.output);
+        self.writer.write_all(&rest)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for Yaz0Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.size - self.received {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "more bytes written than expected",
+            ));
+        }
+
+        self.buffer.extend_from_slice(buf);
+        self.received += buf.len();
+        self.encode(false)?;
+        Ok(buf.len())
+    }
+
+    /// Writes all complete groups of tokens to the underlying writer. The
+    /// pending data is not encoded until more data is written or the writer
+    /// is finished.
+    fn flush(&mut self) -> std::io::Result<()> { self.writer.flush() }
+}
+
+/// Yaz0 codec, see [`crate::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Yaz0 {
+    /// Options used to compress.
+    pub options: CompressOptions,
+}
+
+impl Decompress for Yaz0 {
+    const NAME: &'static str = "Yaz0";
+
+    fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool { is_yaz0(input) }
+
+    fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+        let size = Header::decompressed_size(input)?;
+        decompress(input, size)
+    }
+
+    fn decompress_into<D: Parser + Seeker>(
+        &self,
+        input: &mut D,
+        destination: &mut [u8],
+    ) -> Result<usize> {
+        let size = Header::decompressed_size(input)?;
+        ensure!(
+            destination.len() >= size,
+            InvalidDecompressedSize(Location::current())
+        );
+        decompress_into(input, &mut destination[..size])
+    }
+}
+
+impl Compress for Yaz0 {
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> { Ok(compress_with(data, &self.options)) }
 }
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod afc {
+    use std::io::Cursor;
+
+    use picori::afc::{self, Afc};
+
+    fn stream(samples: u32, looping: bool, data: &[u8]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        stream.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        stream.extend_from_slice(&samples.to_be_bytes());
+        stream.extend_from_slice(&32000_u16.to_be_bytes());
+        stream.extend_from_slice(&[0, 4, 0, 0x10, 0, 0x1E]);
+        stream.extend_from_slice(&(looping as u32).to_be_bytes());
+        stream.extend_from_slice(&1_u32.to_be_bytes());
+        stream.resize(0x20, 0);
+        stream.extend_from_slice(data);
+        stream
+    }
+
+    #[test]
+    fn decode_frame() {
+        // scale 4, no prediction
+        let mut output = Vec::new();
+        let frame = [0x20, 0x1F, 0x70, 0, 0, 0, 0, 0, 0x08];
+        let history = afc::decode_frame(&frame, [100, 50], &mut output);
+        assert_eq!(output[..4], [4, -4, 28, 0]);
+        assert_eq!(output[15], -32);
+        assert_eq!(history, [-32, 0]);
+
+        // scale 2, previous sample (no rounding)
+        output.clear();
+        let frame = [0x11, 0x11, 0, 0, 0, 0, 0, 0, 0];
+        let history = afc::decode_frame(&frame, [-1, 0], &mut output);
+        assert_eq!(output[..3], [1, 3, 3]);
+        assert_eq!(history, [3, 3]);
+    }
+
+    #[test]
+    fn from_binary() {
+        let data = stream(17, true, &[
+            0x00, 0x10, 0, 0, 0, 0, 0, 0, 0, // left, 1 then silence
+            0x01, 0x00, 0, 0, 0, 0, 0, 0, 0, // right, silence
+            0x01, 0x10, 0, 0, 0, 0, 0, 0, 0, // left, previous sample + 1
+            0x01, 0xF0, 0, 0, 0, 0, 0, 0, 0, // right, previous sample - 1
+        ]);
+        let afc = Afc::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(afc.header.size, 36);
+        assert_eq!(afc.header.sample_count, 17);
+        assert_eq!(afc.header.sample_rate, 32000);
+        assert!(afc.header.looping);
+        assert_eq!(afc.header.loop_start, 1);
+        assert_eq!(afc.data.len(), 36);
+
+        let samples = afc.decode().unwrap();
+        assert_eq!(samples.len(), 34);
+        assert_eq!(samples[..4], [1, 0, 0, 0]);
+        assert_eq!(samples[32..], [1, -1]);
+
+        // more samples than the data
+        let data = stream(33, false, &data[0x20..]);
+        let afc = Afc::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(!afc.header.looping);
+        assert!(afc.decode().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        // not whole frames
+        let data = stream(16, false, &[0; 9]);
+        assert!(Afc::from_binary(&mut Cursor::new(&data)).is_err());
+
+        // truncated data
+        let data = stream(16, false, &[0; 18]);
+        assert!(Afc::from_binary(&mut Cursor::new(&data[..0x28])).is_err());
+    }
+}
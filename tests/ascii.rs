@@ -9,8 +9,7 @@ mod ascii {
 
         let ok = (0..=0x7f)
             .zip(result.unwrap().chars())
-            .map(|(a, b)| a as u8 as char == b)
-            .all(|x| x);
+            .all(|(a, b)| a as u8 as char == b);
         assert!(ok);
     }
 
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod ash {
+    use std::collections::BTreeSet;
+    use std::io::Cursor;
+
+    use picori::ash::{self, is_ash};
+
+    /// Bit stream of big-endian 32-bit words.
+    #[derive(Default)]
+    struct BitWriter {
+        bits: Vec<u32>,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u32, count: u32) {
+            for i in (0..count).rev() {
+                self.bits.push((value >> i) & 1);
+            }
+        }
+
+        fn finish(self) -> Vec<u8> {
+            self.bits
+                .chunks(32)
+                .flat_map(|x| {
+                    let word = x
+                        .iter()
+                        .enumerate()
+                        .fold(0_u32, |word, (i, bit)| word | bit << (31 - i));
+                    word.to_be_bytes()
+                })
+                .collect()
+        }
+    }
+
+    /// Write a balanced tree over `values` and return the codes (value, bits,
+    /// number of bits).
+    fn tree(stream: &mut BitWriter, values: &[u32], width: u32) -> Vec<(u32, u32, u32)> {
+        if values.len() == 1 {
+            stream.write(0, 1);
+            stream.write(values[0], width);
+            return vec![(values[0], 0, 0)];
+        }
+
+        stream.write(1, 1);
+        let (left, right) = values.split_at(values.len() / 2);
+        let mut codes = tree(stream, left, width)
+            .into_iter()
+            .map(|(v, c, n)| (v, c, n + 1))
+            .collect::<Vec<_>>();
+        codes.extend(
+            tree(stream, right, width)
+                .into_iter()
+                .map(|(v, c, n)| (v, 1 << n | c, n + 1)),
+        );
+        codes
+    }
+
+    /// ASH0 compress `tokens` (symbol and distance, literal bytes are symbols
+    /// below 0x100).
+    fn compress(tokens: &[(u32, u32)], size: u32) -> Vec<u8> {
+        let symbols = tokens.iter().map(|x| x.0).collect::<BTreeSet<_>>();
+        let distances = tokens
+            .iter()
+            .filter(|x| x.0 >= 0x100)
+            .map(|x| x.1)
+            .chain([0])
+            .collect::<BTreeSet<_>>();
+
+        let mut stream1 = BitWriter::default();
+        let mut stream2 = BitWriter::default();
+        let symbols = tree(&mut stream1, &symbols.into_iter().collect::<Vec<_>>(), 9);
+        let distances = tree(&mut stream2, &distances.into_iter().collect::<Vec<_>>(), 11);
+        let code = |codes: &[(u32, u32, u32)], value| *codes.iter().find(|x| x.0 == value).unwrap();
+        for (symbol, distance) in tokens {
+            let (_, bits, count) = code(&symbols, *symbol);
+            stream1.write(bits, count);
+            if *symbol >= 0x100 {
+                let (_, bits, count) = code(&distances, *distance);
+                stream2.write(bits, count);
+            }
+        }
+
+        let stream1 = stream1.finish();
+        let mut output = b"ASH0".to_vec();
+        output.extend_from_slice(&size.to_be_bytes());
+        output.extend_from_slice(&(0xC + stream1.len() as u32).to_be_bytes());
+        output.extend(stream1);
+        output.extend(stream2.finish());
+        output
+    }
+
+    #[test]
+    fn decompress() {
+        // "abc", back-reference (length 6, distance 3), "X"
+        let tokens = [
+            (b'a' as u32, 0),
+            (b'b' as u32, 0),
+            (b'c' as u32, 0),
+            (0x103, 2),
+            (b'X' as u32, 0),
+        ];
+        let data = compress(&tokens, 10);
+        let mut cursor = Cursor::new(&data);
+        assert!(is_ash(&mut cursor));
+        let header = ash::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.decompressed_size, 10);
+        assert_eq!(
+            ash::decompress(&mut cursor, &header).unwrap(),
+            b"abcabcabcX"
+        );
+
+        // the distance points before the start of the data
+        let data = compress(&[(b'a' as u32, 0), (0x100, 5)], 4);
+        let mut cursor = Cursor::new(&data);
+        let header = ash::Header::from_binary(&mut cursor).unwrap();
+        assert!(ash::decompress(&mut cursor, &header).is_err());
+    }
+
+    #[test]
+    fn literals() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output");
+        let tokens = data.iter().map(|x| (*x as u32, 0)).collect::<Vec<_>>();
+        let compressed = compress(&tokens, data.len() as u32);
+        let mut cursor = Cursor::new(&compressed);
+        let header = ash::Header::from_binary(&mut cursor).unwrap();
+        assert!(ash::decompress(&mut cursor, &header).unwrap() == data);
+    }
+
+    #[test]
+    fn invalid() {
+        let data = b"ASH1\x00\x00\x00\x01\x00\x00\x00\x0C";
+        let mut cursor = Cursor::new(data);
+        assert!(!is_ash(&mut cursor));
+        let header = ash::Header::from_binary(&mut cursor).unwrap();
+        assert!(ash::decompress(&mut cursor, &header).is_err());
+
+        // truncated tree
+        let data = b"ASH0\x00\x00\x00\x01\x00\x00\x00\x10\xFF\xFF\xFF\xFF";
+        let mut cursor = Cursor::new(data);
+        let header = ash::Header::from_binary(&mut cursor).unwrap();
+        assert!(ash::decompress(&mut cursor, &header).is_err());
+    }
+}
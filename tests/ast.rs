@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod ast {
+    use std::io::Cursor;
+
+    use picori::ast::{Ast, Format};
+
+    fn stream(format: u16, channels: u16, samples: u32, blocks: &[&[&[u8]]]) -> Vec<u8> {
+        let size = blocks
+            .iter()
+            .map(|x| 0x20 + x.iter().map(|x| x.len()).sum::<usize>())
+            .sum::<usize>();
+        let mut data = Vec::new();
+        data.extend_from_slice(b"STRM");
+        data.extend_from_slice(&(size as u32).to_be_bytes());
+        for half in [format, 16, channels, 0xFFFF] {
+            data.extend_from_slice(&half.to_be_bytes());
+        }
+        for word in [32000, samples, 1, samples, blocks[0][0].len() as u32] {
+            data.extend_from_slice(&word.to_be_bytes());
+        }
+        data.resize(0x40, 0);
+        for block in blocks {
+            data.extend_from_slice(b"BLCK");
+            data.extend_from_slice(&(block[0].len() as u32).to_be_bytes());
+            data.extend_from_slice(&[0; 0x18]);
+            block.iter().for_each(|x| data.extend_from_slice(x));
+        }
+        data
+    }
+
+    #[test]
+    fn pcm16() {
+        let data = stream(1, 2, 3, &[
+            &[&[0x00, 0x01, 0x00, 0x02], &[0xFF, 0xFF, 0xFF, 0xFE]],
+            &[&[0x00, 0x03, 0x00, 0x00], &[0xFF, 0xFD, 0x00, 0x00]],
+        ]);
+        let ast = Ast::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(ast.header.format, Format::Pcm16);
+        assert_eq!(ast.header.channels, 2);
+        assert_eq!(ast.header.sample_rate, 32000);
+        assert!(ast.header.looping);
+        assert_eq!(ast.header.loop_start, 1);
+        assert_eq!(ast.header.loop_end, 3);
+        assert_eq!(ast.header.block_size, 4);
+        assert_eq!(ast.blocks.len(), 2);
+        assert_eq!(ast.decode().unwrap(), [1, -1, 2, -2, 3, -3]);
+    }
+
+    #[test]
+    fn adpcm() {
+        let block: &[u8] = &[
+            0x20, 0x1F, 0, 0, 0, 0, 0, 0, 0, // scale 4, no prediction
+            0x11, 0x11, 0, 0, 0, 0, 0, 0, 0, // scale 2, previous sample
+        ];
+        let data = stream(0, 1, 20, &[&[block]]);
+        let ast = Ast::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(ast.header.format, Format::Adpcm);
+        let samples = ast.decode().unwrap();
+        assert_eq!(samples.len(), 20);
+        assert_eq!(samples[..3], [4, -4, 0]);
+        assert_eq!(samples[16..], [2, 4, 4, 4]);
+
+        // more samples than the blocks
+        let data = stream(0, 1, 33, &[&[block]]);
+        let ast = Ast::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(ast.decode().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let data = stream(1, 1, 2, &[&[&[0, 1, 0, 2]]]);
+        assert!(Ast::from_binary(&mut Cursor::new(&data[..0x48])).is_err());
+
+        let mut invalid = data.clone();
+        invalid[0x40] = b'X';
+        assert!(Ast::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        let mut invalid = data.clone();
+        invalid[0x09] = 2;
+        assert!(Ast::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // block larger than the size of all blocks
+        let mut invalid = data;
+        invalid[0x07] = 0x10;
+        assert!(Ast::from_binary(&mut Cursor::new(&invalid)).is_err());
+    }
+}
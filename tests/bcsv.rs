@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod bcsv {
+    use std::io::Cursor;
+
+    use picori::bcsv::{self, Bcsv, FieldType, Value};
+    use picori::encoding::Encoding;
+
+    /// Fields (name, mask, offset, shift, type) and entries (16 bytes).
+    fn table(fields: &[(&str, u32, u16, u8, u8)], entries: &[[u8; 16]], strings: &[u8]) -> Vec<u8> {
+        let mut table = Vec::new();
+        let offset = 0x10 + fields.len() as u32 * 12;
+        for word in [entries.len() as u32, fields.len() as u32, offset, 16] {
+            table.extend_from_slice(&word.to_be_bytes());
+        }
+        for (name, mask, offset, shift, kind) in fields {
+            table.extend_from_slice(&bcsv::hash(name).to_be_bytes());
+            table.extend_from_slice(&mask.to_be_bytes());
+            table.extend_from_slice(&offset.to_be_bytes());
+            table.extend_from_slice(&[*shift, *kind]);
+        }
+        entries.iter().for_each(|x| table.extend_from_slice(x));
+        table.extend_from_slice(strings);
+        table
+    }
+
+    const FIELDS: &[(&str, u32, u16, u8, u8)] = &[
+        ("Id", 0xFFFFFFFF, 0, 0, 0),
+        ("Group", 0x0000FF00, 0, 8, 3),
+        ("Count", 0xFFFF, 4, 0, 4),
+        ("Flag", 0xF0, 6, 4, 5),
+        ("Scale", 0xFFFFFFFF, 8, 0, 2),
+        ("Name", 0xFFFFFFFF, 12, 0, 6),
+    ];
+
+    #[test]
+    fn hash() {
+        assert_eq!(bcsv::hash(""), 0);
+        assert_eq!(bcsv::hash("ab"), 97 * 31 + 98);
+        assert_eq!(bcsv::legacy_hash("ab"), (97 << 8) + 98);
+        assert_eq!(bcsv::legacy_hash("abcd"), 0x61626364 % 33554393);
+    }
+
+    #[test]
+    fn from_binary() {
+        let entries = [
+            [
+                0x00, 0x00, 0x12, 0x34, 0xFF, 0xFE, 0xA0, 0x00, 0x3F, 0x80, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00,
+            ],
+            [
+                0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x02, 0x10, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x06,
+            ],
+        ];
+        let data = table(FIELDS, &entries, b"Mario\0Luigi\0");
+        let table = Bcsv::from_binary(&mut Cursor::new(&data), Encoding::ShiftJis1997).unwrap();
+        assert_eq!(table.fields.len(), 6);
+        assert_eq!(table.fields[1].kind, FieldType::Int2);
+        assert_eq!(table.fields[1].mask, 0xFF00);
+        assert_eq!(table.entry_size, 16);
+        assert_eq!(table.entries.len(), 2);
+
+        let row = table.row(0).unwrap();
+        assert_eq!(row.get_by_name("Id"), Some(&Value::Int(0x1234)));
+        assert_eq!(row.get_by_name("Group"), Some(&Value::Int(0x12)));
+        assert_eq!(row.get_by_name("Count"), Some(&Value::Short(-2)));
+        assert_eq!(row.get_by_name("Flag"), Some(&Value::Char(0x0A)));
+        assert_eq!(row.get_by_name("Scale").unwrap().as_float(), Some(1.0));
+        assert_eq!(row.get_by_name("Name").unwrap().as_str(), Some("Mario"));
+        assert_eq!(row.get_by_name("Missing"), None);
+
+        let rows = table.rows().collect::<Vec<_>>();
+        assert_eq!(rows[1].values()[0].as_int(), Some(-1));
+        assert_eq!(rows[1].get_by_name("Name").unwrap().as_str(), Some("Luigi"));
+        assert_eq!(table.get(1, bcsv::hash("Scale")), Some(&Value::Float(2.0)));
+        assert_eq!(table.field(bcsv::hash("Count")), Some(2));
+        assert!(table.row(2).is_none());
+    }
+
+    #[test]
+    fn to_binary() {
+        let entries = [
+            [
+                0x00, 0x00, 0x12, 0x34, 0xFF, 0xFE, 0xA0, 0x00, 0x3F, 0x80, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00,
+            ],
+            [
+                0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x02, 0x10, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x06,
+            ],
+        ];
+        let data = table(FIELDS, &entries, b"Mario\0Luigi\0");
+        let mut bcsv = Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).unwrap();
+        let mut output = Vec::new();
+        bcsv.to_binary(&mut output, Encoding::Ascii).unwrap();
+        assert_eq!(output, data);
+
+        // bits of the shared integer, deduplicated strings
+        bcsv.entries[0][0] = Value::Int(0x5678);
+        bcsv.entries[0][1] = Value::Int(0x1FF);
+        bcsv.entries[1][5] = Value::String("Mario".to_string());
+        let mut output = Vec::new();
+        bcsv.to_binary(&mut output, Encoding::Ascii).unwrap();
+        let written = Bcsv::from_binary(&mut Cursor::new(&output), Encoding::Ascii).unwrap();
+        assert_eq!(written.get(0, bcsv::hash("Id")), Some(&Value::Int(0xFF78)));
+        assert_eq!(written.get(0, bcsv::hash("Group")), Some(&Value::Int(0xFF)));
+        assert_eq!(
+            written.get(1, bcsv::hash("Name")),
+            Some(&Value::String("Mario".to_string()))
+        );
+        assert!(output.ends_with(b"\0\0\0\0Mario\0"));
+
+        bcsv.entries[1][2] = Value::Int(1);
+        assert!(bcsv.to_binary(&mut Vec::new(), Encoding::Ascii).is_err());
+    }
+
+    #[test]
+    fn add_field() {
+        let mut bcsv = Bcsv::new();
+        assert_eq!(bcsv.add_field("Id", FieldType::Int), 0);
+        bcsv.entries.push(vec![Value::Int(7)]);
+        assert_eq!(bcsv.add_field("Flag", FieldType::Char), 1);
+        assert_eq!(bcsv.add_field("Name", FieldType::EmbeddedString), 2);
+        assert_eq!(bcsv.add_field("Count", FieldType::Short), 3);
+        let offsets = bcsv.fields.iter().map(|x| x.offset).collect::<Vec<_>>();
+        assert_eq!(offsets, [0, 4, 8, 40]);
+        assert_eq!(bcsv.fields[1].mask, 0xFF);
+        assert_eq!(bcsv.stride(), 44);
+        assert_eq!(bcsv.entry_size, 44);
+        assert_eq!(bcsv.entries[0][3], Value::Short(0));
+
+        bcsv.entries[0][2] = Value::String("Kinopio".to_string());
+        let mut output = Vec::new();
+        bcsv.to_binary(&mut output, Encoding::ShiftJis1997).unwrap();
+        assert_eq!(output.len(), 0x10 + 4 * 12 + 44);
+        let written = Bcsv::from_binary(&mut Cursor::new(&output), Encoding::ShiftJis1997).unwrap();
+        assert_eq!(written, bcsv);
+
+        // embedded strings are at most 31 bytes
+        bcsv.entries[0][2] = Value::String("K".repeat(32));
+        assert!(bcsv.to_binary(&mut Vec::new(), Encoding::Ascii).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let entry = [0; 16];
+        let data = table(FIELDS, &[entry], b"\0");
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_ok());
+
+        // unknown type
+        let data = table(&[("Id", 0xFFFFFFFF, 0, 0, 7)], &[entry], b"");
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+
+        // field outside of the entry
+        let data = table(&[("Id", 0xFFFFFFFF, 14, 0, 0)], &[entry], b"");
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+
+        // string outside of the string table
+        let mut entry = entry;
+        entry[15] = 0x10;
+        let data = table(FIELDS, &[entry], b"\0");
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+
+        // truncated entries
+        let data = table(FIELDS, &[entry, entry], b"");
+        assert!(
+            Bcsv::from_binary(&mut Cursor::new(&data[..data.len() - 1]), Encoding::Ascii).is_err()
+        );
+
+        // shift larger than the value
+        let data = table(&[("Id", 0xFFFFFFFF, 0, 40, 0)], &[entry], b"");
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+
+        // entry count and size larger than the input
+        let mut data = table(FIELDS, &[], b"");
+        data[0..4].copy_from_slice(&0xFFFFFFFF_u32.to_be_bytes());
+        data[12..16].copy_from_slice(&0xFFFFFFFF_u32.to_be_bytes());
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+        let data = [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0x10, 0, 0, 0, 0];
+        assert!(Bcsv::from_binary(&mut Cursor::new(&data), Encoding::Ascii).is_err());
+    }
+}
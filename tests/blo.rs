@@ -0,0 +1,159 @@
+mod common;
+
+#[cfg(test)]
+mod blo {
+    use std::io::Cursor;
+
+    use picori::blo::{Blo, PaneKind};
+
+    use crate::common::{section, u16s};
+
+    fn resource(kind: u8, name: &str) -> Vec<u8> {
+        let mut data = vec![kind, name.len() as u8];
+        data.extend_from_slice(name.as_bytes());
+        data
+    }
+
+    /// Pane header, `count` is the number of parameters (6 to 10).
+    fn pane(count: u8, tag: &[u8; 4], position: [u16; 2]) -> Vec<u8> {
+        let mut data = vec![count, 1, 0, 0];
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&u16s(&[position[0], position[1], 640, 480]));
+        if count > 6 {
+            data.extend_from_slice(&u16s(&[90]));
+            data.extend_from_slice(&[4, 0x80, 0][..count as usize - 7]);
+        }
+        data.resize(data.len().next_multiple_of(4), 0);
+        data
+    }
+
+    fn screen() -> Vec<u8> {
+        let mut picture = pane(10, b"pic0", [10, 20]);
+        picture.push(3);
+        picture.extend_from_slice(&resource(2, "timg/icon.bti"));
+        picture.extend_from_slice(&resource(0, ""));
+        picture.push(0x0F);
+
+        let mut window = pane(6, b"win0", [0, 0]);
+        window.push(9);
+        window.extend_from_slice(&u16s(&[8, 8, 100, 50]));
+        for _ in 0..4 {
+            window.extend_from_slice(&resource(2, "timg/corner.bti"));
+        }
+        window.extend_from_slice(&resource(0, ""));
+
+        let mut text = pane(6, b"txt0", [5, 5]);
+        text.push(9);
+        text.extend_from_slice(&resource(2, "font/rock.bfn"));
+        text.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0x80, 0x80, 0x80, 0xFF]);
+        text.extend_from_slice(&[1, 2]);
+        text.extend_from_slice(&u16s(&[0xFFFE, 4, 24, 20, 5]));
+        text.extend_from_slice(b"Start");
+
+        let sections = [
+            section(b"INF1", &[2, 0x80, 1, 0xE0, 0, 0, 0, 0xFF], 4),
+            section(b"PAN1", &pane(6, b"root", [0, 0]), 4),
+            section(b"BGN1", &[], 4),
+            section(b"PIC1", &picture, 4),
+            section(b"PAN1", &pane(7, b"grp0", [1, 2]), 4),
+            section(b"BGN1", &[], 4),
+            section(b"WIN1", &window, 4),
+            section(b"TBX1", &text, 4),
+            section(b"END1", &[], 4),
+            section(b"END1", &[], 4),
+            section(b"EXT1", &[], 4),
+        ];
+        let mut data = b"SCRNblo1".to_vec();
+        let size = 0x20 + sections.iter().map(|x| x.len()).sum::<usize>();
+        data.extend_from_slice(&(size as u32).to_be_bytes());
+        data.extend_from_slice(&(sections.len() as u32).to_be_bytes());
+        data.resize(0x20, 0);
+        sections.iter().for_each(|x| data.extend_from_slice(x));
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let blo = Blo::from_binary(&mut Cursor::new(screen())).unwrap();
+        assert_eq!((blo.screen.width, blo.screen.height), (640, 480));
+        assert_eq!(blo.screen.color, [0, 0, 0, 0xFF]);
+        assert_eq!(blo.panes.len(), 1);
+
+        let root = &blo.panes[0];
+        assert_eq!(root.kind, PaneKind::Null);
+        assert!(root.visible);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(
+            (root.rotation, root.alpha, root.inherit_alpha),
+            (0, 0xFF, true)
+        );
+
+        let picture = &root.children[0];
+        assert_eq!(picture.tag, *b"pic0");
+        assert_eq!(picture.position, [10, 20]);
+        assert_eq!((picture.rotation, picture.anchor), (90, 4));
+        assert_eq!((picture.alpha, picture.inherit_alpha), (0x80, false));
+        match &picture.kind {
+            PaneKind::Picture(x) => {
+                assert_eq!(x.texture.name, "timg/icon.bti");
+                assert!(x.palette.is_none());
+                assert_eq!(x.binding, 0x0F);
+            },
+            x => panic!("unexpected pane: {x:?}"),
+        }
+
+        let group = blo.pane(b"grp0").unwrap();
+        assert_eq!(group.children.len(), 2);
+        match &group.children[0].kind {
+            PaneKind::Window(x) => {
+                assert_eq!(x.content, [8, 8, 100, 50]);
+                assert_eq!(x.textures[3].name, "timg/corner.bti");
+            },
+            x => panic!("unexpected pane: {x:?}"),
+        }
+        match &blo.pane(b"txt0").unwrap().kind {
+            PaneKind::TextBox(x) => {
+                assert_eq!(x.font.name, "font/rock.bfn");
+                assert_eq!(x.colors[1], [0x80, 0x80, 0x80, 0xFF]);
+                assert_eq!(x.binding, [1, 2]);
+                assert_eq!((x.char_spacing, x.line_spacing), (-2, 4));
+                assert_eq!(x.font_size, [24, 20]);
+                assert_eq!(x.text, "Start");
+            },
+            x => panic!("unexpected pane: {x:?}"),
+        }
+        assert!(blo.pane(b"none").is_none());
+    }
+
+    #[test]
+    fn resources() {
+        let blo = Blo::from_binary(&mut Cursor::new(screen())).unwrap();
+        let names = blo.resources().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec![
+            "timg/icon.bti",
+            "timg/corner.bti",
+            "timg/corner.bti",
+            "timg/corner.bti",
+            "timg/corner.bti",
+            "font/rock.bfn"
+        ]);
+    }
+
+    #[test]
+    fn invalid() {
+        // invalid magic
+        let mut data = screen();
+        data[0] = b'X';
+        assert!(Blo::from_binary(&mut Cursor::new(data)).is_err());
+
+        // unbalanced hierarchy (missing END1)
+        let mut data = screen();
+        let count = u32::from_be_bytes(data[0x0C..0x10].try_into().unwrap());
+        data[0x0C..0x10].copy_from_slice(&(count - 3).to_be_bytes());
+        assert!(Blo::from_binary(&mut Cursor::new(data)).is_err());
+
+        // truncated
+        let data = screen();
+        assert!(Blo::from_binary(&mut Cursor::new(&data[..0x60])).is_err());
+    }
+}
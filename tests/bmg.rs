@@ -0,0 +1,224 @@
+mod common;
+
+#[cfg(test)]
+mod bmg {
+    use std::io::Cursor;
+
+    use picori::bmg::{Bmg, Escape, Message, Token};
+    use picori::encoding::Encoding;
+
+    use crate::common::section;
+
+    fn file(encoding: u8, entries: &[(u32, u16)], text: &[u8]) -> Vec<u8> {
+        let mut info = Vec::new();
+        info.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+        info.extend_from_slice(&8_u16.to_be_bytes());
+        info.extend_from_slice(&[0x00, 0x07, 0x01, 0x00]);
+        for (offset, attribute) in entries {
+            info.extend_from_slice(&offset.to_be_bytes());
+            info.extend_from_slice(&attribute.to_be_bytes());
+            info.extend_from_slice(&[0, 0]);
+        }
+        let info = section(b"INF1", &info, 0x20);
+        let data = section(b"DAT1", text, 0x20);
+
+        let mut bmg = b"MESGbmg1".to_vec();
+        let size = 0x20 + info.len() + data.len();
+        bmg.extend_from_slice(&(size as u32).to_be_bytes());
+        bmg.extend_from_slice(&2_u32.to_be_bytes());
+        bmg.push(encoding);
+        bmg.resize(0x20, 0);
+        bmg.extend_from_slice(&info);
+        bmg.extend_from_slice(&data);
+        bmg
+    }
+
+    /// Append the section `magic` to the BMG `file`.
+    fn append(file: &mut Vec<u8>, magic: &[u8], body: &[u8]) {
+        file.extend_from_slice(&section(magic, body, 0x20));
+        let size = file.len() as u32;
+        let count = u32::from_be_bytes(file[0x0C..0x10].try_into().unwrap()) + 1;
+        file[0x08..0x0C].copy_from_slice(&size.to_be_bytes());
+        file[0x0C..0x10].copy_from_slice(&count.to_be_bytes());
+    }
+
+    const TEXT: &[u8] =
+        b"\0Hello \x1A\x06\xFF\x00\x00\x00World\x1A\x06\x01\x00\x02\x05!\0\x1A\x05\x02\x00\x01\0";
+
+    #[test]
+    fn from_binary() {
+        let data = file(1, &[(0, 0), (1, 0x1234), (26, 0)], TEXT);
+        let bmg = Bmg::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::Windows1252);
+        assert_eq!(bmg.group, 7);
+        assert_eq!(bmg.default_color, 1);
+        assert_eq!(bmg.messages.len(), 3);
+
+        assert!(bmg.messages[0].tokens.is_empty());
+        assert_eq!(bmg.messages[0].attributes, [0, 0, 0, 0]);
+
+        let message = &bmg.messages[1];
+        assert_eq!(message.attributes, [0x12, 0x34, 0, 0]);
+        assert_eq!(message.tokens, [
+            Token::Text("Hello ".to_string()),
+            Token::Escape(Escape {
+                group:     0xFF,
+                tag:       0,
+                arguments: vec![0],
+            }),
+            Token::Text("World".to_string()),
+            Token::Escape(Escape {
+                group:     1,
+                tag:       2,
+                arguments: vec![5],
+            }),
+            Token::Text("!".to_string()),
+        ]);
+        assert_eq!(message.text(), "Hello World!");
+
+        assert_eq!(bmg.messages[2].tokens, [Token::Escape(Escape {
+            group:     2,
+            tag:       1,
+            arguments: vec![],
+        })]);
+    }
+
+    #[test]
+    fn to_binary() {
+        let mut data = file(1, &[(0, 0), (1, 0x1234), (26, 0)], TEXT);
+        let ids = [
+            0, 3, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 6, 0, 1, 0, 0,
+        ];
+        append(&mut data, b"MID1", &ids);
+        append(&mut data, b"FLW1", &[1, 2, 3, 4]);
+
+        let mut bmg = Bmg::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bmg.encoding_id, 1);
+        assert_eq!(bmg.attribute_size, 4);
+        assert_eq!(bmg.id_format, Some([0x10, 0, 0, 0, 0, 0]));
+        assert_eq!(bmg.messages[1].id, Some(6));
+        assert_eq!(bmg.find(0x10000).unwrap().text(), "");
+        assert!(bmg.find(7).is_none());
+        assert_eq!(bmg.sections.len(), 1);
+        assert_eq!(bmg.sections[0].magic, *b"FLW1");
+        assert_eq!(bmg.sections[0].data[..5], [1, 2, 3, 4, 0]);
+
+        // unchanged
+        let mut output = Vec::new();
+        bmg.to_binary(&mut output).unwrap();
+        assert_eq!(output, data);
+
+        // modified
+        bmg.messages[1].tokens[2] = Token::Text("Picori".to_string());
+        bmg.messages.push(Message {
+            id:         Some(8),
+            attributes: vec![0; 4],
+            tokens:     vec![Token::Text("New".to_string())],
+        });
+        let mut output = Vec::new();
+        bmg.to_binary(&mut output).unwrap();
+        let written = Bmg::from_binary(&mut Cursor::new(&output)).unwrap();
+        assert_eq!(written, bmg);
+        assert_eq!(written.find(6).unwrap().text(), "Hello Picori!");
+
+        // every message needs an ID and the same attribute size
+        bmg.messages[3].id = None;
+        assert!(bmg.to_binary(&mut Vec::new()).is_err());
+        bmg.messages[3].id = Some(8);
+        bmg.messages[3].attributes.push(0);
+        assert!(bmg.to_binary(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn utf16_be() {
+        let text = [
+            0x00, 0x00, // empty message
+            0x30, 0x42, 0x00, 0x1A, 0x08, 0x01, 0x00, 0x03, 0x00, 0x1A, 0x00, 0x00,
+        ];
+        let data = file(2, &[(0, 0), (2, 0)], &text);
+        let bmg = Bmg::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::Utf16Be);
+        assert_eq!(bmg.messages[1].tokens, [
+            Token::Text("あ".to_string()),
+            Token::Escape(Escape {
+                group:     1,
+                tag:       3,
+                arguments: vec![0x00, 0x1A],
+            }),
+        ]);
+
+        let mut output = Vec::new();
+        bmg.to_binary(&mut output).unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn shift_jis() {
+        let text = b"\x82\xA0\x1A\x05\x00\x00\x01\0";
+        let data = file(3, &[(0, 0)], text);
+        let bmg = Bmg::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::ShiftJis1997);
+        assert_eq!(bmg.messages[0].text(), "あ");
+        assert_eq!(bmg.messages[0].tokens.len(), 2);
+
+        // override the encoding of the header
+        let data = file(0, &[(0, 0)], b"\x82\xA0\0");
+        let bmg = Bmg::from_binary_with_encoding(&mut Cursor::new(&data), Encoding::ShiftJis1997)
+            .unwrap();
+        assert_eq!(bmg.messages[0].text(), "あ");
+    }
+
+    #[test]
+    fn invalid() {
+        let data = file(1, &[(0, 0)], b"text\0");
+        assert!(Bmg::from_binary(&mut Cursor::new(&data)).is_ok());
+
+        let mut invalid = data.clone();
+        invalid[0] = b'X';
+        assert!(Bmg::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // unsupported encoding (UTF-8)
+        let mut invalid = data.clone();
+        invalid[0x10] = 4;
+        assert!(Bmg::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // message outside of DAT1
+        let invalid = file(1, &[(0x100, 0)], b"text\0");
+        assert!(Bmg::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // escape sequence outside of DAT1 or too small
+        assert!(Bmg::from_binary(&mut Cursor::new(file(1, &[(0, 0)], b"\x1A\x40"))).is_err());
+        assert!(Bmg::from_binary(&mut Cursor::new(file(1, &[(0, 0)], b"\x1A\x02\0"))).is_err());
+
+        // section size past the end of the file, not allocated up front
+        let mut invalid = data.clone();
+        invalid[0x24..0x28].copy_from_slice(&0xFFFF_FFF0_u32.to_be_bytes());
+        assert!(Bmg::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // missing DAT1
+        let mut invalid = data;
+        invalid[0x0F] = 1;
+        assert!(Bmg::from_binary(&mut Cursor::new(&invalid)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut data = file(1, &[(0, 0), (1, 0x1234), (26, 0)], TEXT);
+        append(&mut data, b"FLW1", &[1, 2, 3, 4]);
+        let bmg = Bmg::from_binary(&mut Cursor::new(&data)).unwrap();
+        let json = serde_json::to_string(&bmg).unwrap();
+        assert!(json.contains(r#""encoding":"Windows1252""#));
+
+        // section data is not serialized
+        let copy: Bmg = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.messages, bmg.messages);
+        assert_eq!(copy.sections[0].magic, *b"FLW1");
+        assert!(copy.sections[0].data.is_empty());
+        let copy = Bmg {
+            sections: bmg.sections.clone(),
+            ..copy
+        };
+        assert_eq!(copy, bmg);
+    }
+}
@@ -0,0 +1,140 @@
+mod common;
+
+#[cfg(test)]
+mod bnr {
+    use std::io::Cursor;
+
+    use picori::bnr::{self, Language, Version};
+    use picori::gcm::Region;
+    use picori::Bnr;
+
+    use crate::common::put;
+
+    fn banner(magic: &[u8; 4], descriptions: &[[&[u8]; 5]]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x1820 + descriptions.len() * 0x140];
+        put(&mut data, 0x00, magic);
+        put(&mut data, 0x20, &0xFC00_u16.to_be_bytes()); // red (RGB555)
+        put(&mut data, 0x22, &0x0F00_u16.to_be_bytes()); // transparent red (RGB4A3)
+        put(&mut data, 0x28, &0x83E0_u16.to_be_bytes()); // green (RGB555)
+        put(&mut data, 0x40, &0x801F_u16.to_be_bytes()); // blue, second tile
+        for (i, fields) in descriptions.iter().enumerate() {
+            let offset = 0x1820 + i * 0x140;
+            for (field, at) in fields.iter().zip([0x00, 0x20, 0x40, 0x80, 0xC0]) {
+                put(&mut data, offset + at, field);
+            }
+        }
+        data
+    }
+
+    fn pixel(bnr: &Bnr, x: usize, y: usize) -> &[u8] {
+        let offset = (y * bnr::WIDTH + x) * 4;
+        &bnr.image[offset..offset + 4]
+    }
+
+    #[test]
+    fn bnr1() {
+        let data = banner(b"BNR1", &[[
+            b"\x83\x51\x81\x5B\x83\x80",
+            b"Maker",
+            b"Full Name",
+            b"Full Maker",
+            b"Line 1\nLine 2",
+        ]]);
+        assert_eq!(data.len(), bnr::BNR1_SIZE);
+
+        let bnr = Bnr::from_binary(&mut Cursor::new(&data), Region::Japan).unwrap();
+        assert_eq!(bnr.version, Version::Bnr1);
+        assert_eq!(bnr.image.len(), bnr::WIDTH * bnr::HEIGHT * 4);
+        assert_eq!(pixel(&bnr, 0, 0), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(&bnr, 1, 0), [0xFF, 0x00, 0x00, 0x00]);
+        assert_eq!(pixel(&bnr, 0, 1), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(pixel(&bnr, 4, 0), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(pixel(&bnr, 95, 31), [0x00, 0x00, 0x00, 0x00]);
+
+        let description = bnr.description().unwrap();
+        assert_eq!(description.name, "ゲーム");
+        assert_eq!(description.maker, "Maker");
+        assert_eq!(description.full_name, "Full Name");
+        assert_eq!(description.full_maker, "Full Maker");
+        assert_eq!(description.description, "Line 1\nLine 2");
+        assert!(bnr.localized(Language::English).is_none());
+    }
+
+    #[test]
+    fn bnr2() {
+        let names: [&[u8]; 6] = [b"Game", b"Spiel", b"Jeu \xE9", b"Juego", b"Gioco", b"Spel"];
+        let descriptions = names.map(|x| [x, b"", b"", b"", b""]);
+        let data = banner(b"BNR2", &descriptions);
+        assert_eq!(data.len(), bnr::BNR2_SIZE);
+
+        let bnr = Bnr::from_binary(&mut Cursor::new(&data), Region::Europe).unwrap();
+        assert_eq!(bnr.version, Version::Bnr2);
+        assert_eq!(bnr.descriptions.len(), 6);
+        assert_eq!(bnr.description().unwrap().name, "Game");
+        assert_eq!(bnr.localized(Language::French).unwrap().name, "Jeu é");
+        assert_eq!(bnr.localized(Language::Dutch).unwrap().name, "Spel");
+    }
+
+    #[test]
+    fn write() {
+        let data = banner(b"BNR1", &[[
+            b"\x83\x51\x81\x5B\x83\x80",
+            b"Maker",
+            b"Full Name",
+            b"Full Maker",
+            b"Line 1\nLine 2",
+        ]]);
+        let bnr = Bnr::from_binary(&mut Cursor::new(&data), Region::Japan).unwrap();
+        let mut output = Cursor::new(Vec::new());
+        bnr.to_binary(&mut output, Region::Japan).unwrap();
+        assert!(output.into_inner() == data);
+
+        let mut bnr = Bnr::new(Version::Bnr2);
+        bnr.image[..8].copy_from_slice(&[0x10, 0x20, 0x30, 0x40, 0xFF, 0xFF, 0xFF, 0xF0]);
+        bnr.localized_mut(Language::German).unwrap().name = "Über".to_string();
+        let mut output = Cursor::new(Vec::new());
+        bnr.to_binary(&mut output, Region::Europe).unwrap();
+        let data = output.into_inner();
+        assert_eq!(data.len(), bnr::BNR2_SIZE);
+        assert_eq!(&data[0x20..0x24], [0x21, 0x23, 0xFF, 0xFF]);
+        assert_eq!(&data[0x1820 + 0x140..0x1825 + 0x140], b"\xDCber\0");
+
+        let parsed = Bnr::from_binary(&mut Cursor::new(&data), Region::Europe).unwrap();
+        assert_eq!(parsed.localized(Language::German).unwrap().name, "Über");
+        assert_eq!(&parsed.image[..8], [
+            0x11, 0x22, 0x33, 0x49, 0xFF, 0xFF, 0xFF, 0xFF
+        ]);
+
+        // text that is too long, can not be encoded, or a mismatched version
+        let mut bnr = Bnr::new(Version::Bnr1);
+        bnr.descriptions[0].name = "a".repeat(0x20);
+        assert!(bnr
+            .to_binary(&mut Cursor::new(Vec::new()), Region::America)
+            .is_err());
+        bnr.descriptions[0].name = "ゲーム".to_string();
+        assert!(bnr
+            .to_binary(&mut Cursor::new(Vec::new()), Region::America)
+            .is_err());
+        assert!(bnr
+            .to_binary(&mut Cursor::new(Vec::new()), Region::Japan)
+            .is_ok());
+        bnr.version = Version::Bnr2;
+        assert!(bnr
+            .to_binary(&mut Cursor::new(Vec::new()), Region::Japan)
+            .is_err());
+
+        // no descriptions
+        bnr.descriptions.clear();
+        assert!(bnr.description().is_none());
+        assert!(bnr.localized(Language::English).is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let data = banner(b"BNR3", &[[b"", b"", b"", b"", b""]]);
+        assert!(Bnr::from_binary(&mut Cursor::new(&data), Region::America).is_err());
+
+        let data = banner(b"BNR2", &[[b"", b"", b"", b"", b""]]);
+        assert!(Bnr::from_binary(&mut Cursor::new(&data), Region::Europe).is_err());
+    }
+}
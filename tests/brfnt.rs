@@ -0,0 +1,155 @@
+mod common;
+
+#[cfg(test)]
+mod brfnt {
+    use std::io::Cursor;
+
+    use picori::brfnt::{Brfnt, CharWidth, FontEncoding, Mapping};
+    use picori::texture::{self, Format};
+
+    use crate::common::section;
+
+    /// Sheet (16x12, I8) with cells of 4x4 pixels (2x2 per sheet), the cell
+    /// `i` filled with `0x10 * (first + i + 1)`.
+    fn sheet(first: u8) -> Vec<u8> {
+        let mut image = vec![0; 16 * 12 * 4];
+        for cell in 0..4 {
+            let value = 0x10 * (first + cell as u8 + 1);
+            for y in 0..4 {
+                for x in 0..4 {
+                    let px = (cell % 2) * 5 + 1 + x;
+                    let py = (cell / 2) * 5 + 1 + y;
+                    image[(py * 16 + px) * 4..][..4].fill(value);
+                }
+            }
+        }
+        texture::encode(Format::I8, &image, 16, 12).unwrap()
+    }
+
+    fn font() -> Vec<u8> {
+        let mut info = vec![0, 10];
+        info.extend_from_slice(&1_u16.to_be_bytes());
+        info.extend_from_slice(&[0, 4, 5, 1]);
+        info.extend_from_slice(&[0; 12]);
+        info.extend_from_slice(&[8, 4, 6, 0]);
+
+        let mut widths = Vec::new();
+        widths.extend_from_slice(&0_u16.to_be_bytes());
+        widths.extend_from_slice(&2_u16.to_be_bytes());
+        widths.extend_from_slice(&0_u32.to_be_bytes());
+        widths.extend_from_slice(&[0, 4, 5, 1, 3, 4, 0xFF, 4, 3]);
+
+        // 'A'-'C' direct (glyphs 0-2), 'a'-'c' table (glyphs 3, none, 5),
+        // scan for '!' (glyph 6)
+        let mut direct = Vec::new();
+        for value in [0x41_u16, 0x43, 0, 0, 0, 0, 0] {
+            direct.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut table = Vec::new();
+        for value in [0x61_u16, 0x63, 1, 0, 0, 0, 3, 0xFFFF, 5] {
+            table.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut scan = Vec::new();
+        for value in [0x21_u16, 0x21, 2, 0, 0, 0, 1, 0x21, 6] {
+            scan.extend_from_slice(&value.to_be_bytes());
+        }
+
+        let sheets = [sheet(0), sheet(4)];
+        let sections = 0x10 + 0x20 + 0x20;
+        let rest = [
+            section(b"CWDH", &widths, 4),
+            section(b"CMAP", &direct, 4),
+            section(b"CMAP", &table, 4),
+            section(b"CMAP", &scan, 4),
+        ];
+        let sheet_offset =
+            (sections + rest.iter().map(|x| x.len()).sum::<usize>()).next_multiple_of(0x20) as u32;
+
+        let mut glyphs = vec![4, 4, 3, 4];
+        glyphs.extend_from_slice(&(sheets[0].len() as u32).to_be_bytes());
+        for value in [2_u16, 1, 2, 2, 16, 12] {
+            glyphs.extend_from_slice(&value.to_be_bytes());
+        }
+        glyphs.extend_from_slice(&sheet_offset.to_be_bytes());
+
+        let mut data = b"RFNT".to_vec();
+        data.extend_from_slice(&[0xFE, 0xFF, 0x01, 0x04]);
+        data.extend_from_slice(&0_u32.to_be_bytes());
+        data.extend_from_slice(&0x10_u16.to_be_bytes());
+        data.extend_from_slice(&6_u16.to_be_bytes());
+        data.extend_from_slice(&section(b"FINF", &info, 4));
+        data.extend_from_slice(&section(b"TGLP", &glyphs, 4));
+        rest.iter().for_each(|x| data.extend_from_slice(x));
+        data.resize(sheet_offset as usize, 0);
+        sheets.iter().for_each(|x| data.extend_from_slice(x));
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let font = Brfnt::from_binary(&mut Cursor::new(font())).unwrap();
+        assert_eq!(font.version, 0x0104);
+        assert_eq!(font.info.line_feed, 10);
+        assert_eq!(font.info.alternate_index, 1);
+        assert_eq!(font.info.encoding, FontEncoding::Utf16);
+        assert_eq!(
+            (font.info.height, font.info.width, font.info.ascent),
+            (8, 4, 6)
+        );
+        assert_eq!(font.sheets.format, Format::I8);
+        assert_eq!(font.sheets.sheets.len(), 2);
+        assert_eq!(font.widths.len(), 1);
+        assert_eq!(font.maps.len(), 3);
+        assert_eq!(font.maps[0].mapping, Mapping::Direct(0));
+        assert_eq!(font.maps[2].mapping, Mapping::Scan(vec![(0x21, 6)]));
+    }
+
+    #[test]
+    fn glyphs() {
+        let font = Brfnt::from_binary(&mut Cursor::new(font())).unwrap();
+        assert_eq!(font.glyph_index(b'A' as u16), Some(0));
+        assert_eq!(font.glyph_index(b'C' as u16), Some(2));
+        assert_eq!(font.glyph_index(b'a' as u16), Some(3));
+        assert_eq!(font.glyph_index(b'b' as u16), None);
+        assert_eq!(font.glyph_index(b'!' as u16), Some(6));
+        assert_eq!(font.glyph_index(b'D' as u16), None);
+        assert_eq!(font.characters().count(), 6);
+
+        let width = CharWidth {
+            left:        -1,
+            glyph_width: 4,
+            char_width:  3,
+        };
+        assert_eq!(font.char_width(2), width);
+        assert_eq!(font.char_width(6), font.info.default_width);
+
+        let glyph = font.glyph(b'B' as u16).unwrap().unwrap();
+        assert_eq!(glyph.index, 1);
+        assert_eq!(glyph.image.len(), 4 * 4 * 4);
+        assert!(glyph.image.iter().all(|x| *x == 0x20));
+        assert_eq!(glyph.width.char_width, 4);
+
+        // second sheet
+        let glyph = font.glyph(b'!' as u16).unwrap().unwrap();
+        assert!(glyph.image.iter().all(|x| *x == 0x70));
+        assert!(font.glyph(b'b' as u16).unwrap().is_none());
+        assert!(font.glyph_by_index(8).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = font();
+        data[0] = b'X';
+        assert!(Brfnt::from_binary(&mut Cursor::new(data)).is_err());
+
+        // little-endian
+        let mut data = font();
+        data[4..6].copy_from_slice(&[0xFF, 0xFE]);
+        assert!(Brfnt::from_binary(&mut Cursor::new(data)).is_err());
+
+        // compressed sheets
+        let mut data = font();
+        data[0x30 + 0x12] = 0x80;
+        assert!(Brfnt::from_binary(&mut Cursor::new(data)).is_err());
+    }
+}
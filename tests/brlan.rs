@@ -0,0 +1,115 @@
+mod common;
+
+#[cfg(test)]
+mod brlan {
+    use std::io::Cursor;
+
+    use picori::brlan::{AnimationKind, Brlan, Keys};
+
+    use crate::common::{f32s, name, put, u16s, u32s};
+
+    /// Element at `offset` with a single animation of `kind` (and a single
+    /// target) with the keys `keys`.
+    fn element(
+        data: &mut Vec<u8>,
+        offset: usize,
+        name: &str,
+        is_material: bool,
+        kind: &[u8; 4],
+        key_type: u8,
+        keys: &[u8],
+    ) {
+        put(data, offset, &self::name::<20>(name));
+        put(data, offset + 0x14, &[1, is_material as u8, 0, 0]);
+        put(data, offset + 0x18, &u32s(&[0x1C]));
+        put(data, offset + 0x1C, kind);
+        put(data, offset + 0x20, &[1, 0, 0, 0]);
+        put(data, offset + 0x24, &u32s(&[0x0C]));
+        let count = keys.len() / if key_type == 1 { 8 } else { 12 };
+        put(data, offset + 0x28, &[0, 5, key_type, 0]);
+        put(data, offset + 0x2C, &u16s(&[count as u16, 0]));
+        put(data, offset + 0x30, &u32s(&[0x0C]));
+        put(data, offset + 0x34, keys);
+    }
+
+    fn animation() -> Vec<u8> {
+        let mut pai1 = vec![0; 8];
+        put(&mut pai1, 0x08, &u16s(&[60]));
+        put(&mut pai1, 0x0A, &[1, 0]);
+        put(&mut pai1, 0x0C, &u16s(&[1, 2]));
+        put(&mut pai1, 0x10, &u32s(&[0x20, 4]));
+        put(&mut pai1, 0x18, b"t.tpl\0");
+        put(&mut pai1, 0x20, &u32s(&[0x28, 0x74]));
+
+        let hermite = f32s(&[0.0, 0.0, 6.0, 60.0, 360.0, 6.0]);
+        element(&mut pai1, 0x28, "pic", false, b"RLPA", 2, &hermite);
+        let mut step = f32s(&[0.0]);
+        step.extend_from_slice(&[0, 0, 0, 0]);
+        step.extend_from_slice(&f32s(&[30.0]));
+        step.extend_from_slice(&[0, 1, 0, 0]);
+        element(&mut pai1, 0x74, "mat0", true, b"RLTP", 1, &step);
+
+        let size = pai1.len().next_multiple_of(4) as u32;
+        pai1.resize(size as usize, 0);
+        put(&mut pai1, 0, b"pai1");
+        put(&mut pai1, 4, &u32s(&[size]));
+
+        // pat1 is skipped
+        let mut pat1 = b"pat1".to_vec();
+        pat1.extend_from_slice(&u32s(&[0x10, 0, 0]));
+
+        let mut data = b"RLAN".to_vec();
+        data.extend_from_slice(&[0xFE, 0xFF, 0x00, 0x08]);
+        data.extend_from_slice(&u32s(&[0x10 + 0x10 + size]));
+        data.extend_from_slice(&u16s(&[0x10, 2]));
+        data.extend_from_slice(&pat1);
+        data.extend_from_slice(&pai1);
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let animation = Brlan::from_binary(&mut Cursor::new(animation())).unwrap();
+        assert_eq!(animation.version, 8);
+        assert_eq!(animation.frame_count, 60);
+        assert!(animation.looping);
+        assert_eq!(animation.textures, vec!["t.tpl"]);
+        assert_eq!(animation.elements.len(), 2);
+
+        let pane = animation.element("pic").unwrap();
+        assert!(!pane.is_material);
+        assert_eq!(pane.animations.len(), 1);
+        assert_eq!(pane.animations[0].kind, AnimationKind::PaneSrt);
+        let target = &pane.animations[0].targets[0];
+        assert_eq!(target.target, 5);
+        assert_eq!(target.keys.len(), 2);
+        assert!((target.keys.value(30.0) - 180.0).abs() < 1e-3);
+        assert_eq!(target.keys.value(90.0), 360.0);
+
+        let material = animation.element("mat0").unwrap();
+        assert!(material.is_material);
+        assert_eq!(material.animations[0].kind, AnimationKind::TexturePattern);
+        let keys = &material.animations[0].targets[0].keys;
+        assert_eq!(keys, &Keys::Step(vec![(0.0, 0), (30.0, 1)]));
+        assert_eq!(keys.value(29.0), 0.0);
+        assert_eq!(keys.value(30.0), 1.0);
+        assert!(animation.element("missing").is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = animation();
+        data[0] = b'X';
+        assert!(Brlan::from_binary(&mut Cursor::new(data)).is_err());
+
+        // unknown animation kind
+        let mut data = animation();
+        data[0x20 + 0x28 + 0x1C] = b'X';
+        assert!(Brlan::from_binary(&mut Cursor::new(data)).is_err());
+
+        // without pai1
+        let mut data = animation();
+        data[0x0F] = 1;
+        assert!(Brlan::from_binary(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+mod common;
+
+#[cfg(test)]
+mod brlyt {
+    use std::io::Cursor;
+
+    use picori::brlyt::{Brlyt, PaneKind};
+
+    use crate::common::{f32s, name, section};
+
+    fn names(names: &[&str]) -> Vec<u8> {
+        let mut data = (names.len() as u16).to_be_bytes().to_vec();
+        data.extend_from_slice(&[0, 0]);
+        let mut strings = Vec::new();
+        for name in names {
+            let offset = 8 * names.len() + strings.len();
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&[0; 4]);
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+        }
+        data.extend_from_slice(&strings);
+        data
+    }
+
+    fn material() -> Vec<u8> {
+        let mut data = vec![0, 1, 0, 0];
+        data.extend_from_slice(&0x10_u32.to_be_bytes());
+        data.extend_from_slice(&name::<20>("mat0"));
+        for value in [0xFF_i16, 0, 0, 0xFF, 0, 0, 0, 0, -1, -1, -1, -1] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&[0x11; 16]);
+        data.extend_from_slice(&0x111_u32.to_be_bytes());
+        data.extend_from_slice(&[0, 1, 0, 1]);
+        data.extend_from_slice(&f32s(&[0.5, 0.0, 90.0, 1.0, 2.0]));
+        data.extend_from_slice(&[1, 4, 30, 0]);
+        data.extend_from_slice(&[0xAA; 4]);
+        data
+    }
+
+    fn pane(name: &str, flags: u8) -> Vec<u8> {
+        let mut data = vec![flags, 4, 0xFF, 0];
+        data.extend_from_slice(&self::name::<16>(name));
+        data.extend_from_slice(b"userdata");
+        data.extend_from_slice(&f32s(&[
+            10.0, -20.0, 0.0, 0.0, 0.0, 45.0, 1.0, 1.0, 608.0, 456.0,
+        ]));
+        data
+    }
+
+    fn layout() -> Vec<u8> {
+        let mut lyt = vec![1, 0, 0, 0];
+        lyt.extend_from_slice(&f32s(&[608.0, 456.0]));
+
+        let mut picture = pane("pic", 1);
+        picture.extend_from_slice(&[0xFF; 16]);
+        picture.extend_from_slice(&[0, 0, 1, 0]);
+        picture.extend_from_slice(&f32s(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0]));
+
+        let mut text = pane("text", 0);
+        text.extend_from_slice(&[0, 8, 0, 4, 0, 0, 0, 0, 1, 2, 0, 0]);
+        text.extend_from_slice(&0x74_u32.to_be_bytes());
+        text.extend_from_slice(&[0xFF; 8]);
+        text.extend_from_slice(&f32s(&[16.0, 16.0, 1.0, 2.0]));
+        text.extend_from_slice(&[0, b'H', 0, b'i', 0, 0]);
+
+        let mut group = name::<16>("g").to_vec();
+        group.extend_from_slice(&[0, 2, 0, 0]);
+        group.extend_from_slice(&name::<16>("pic"));
+        group.extend_from_slice(&name::<16>("text"));
+        let mut root_group = name::<16>("RootGroup").to_vec();
+        root_group.extend_from_slice(&[0; 4]);
+
+        let sections = [
+            section(b"lyt1", &lyt, 4),
+            section(b"txl1", &names(&["a.tpl", "b.tpl"]), 4),
+            section(b"fnl1", &names(&["font.brfnt"]), 4),
+            section(b"mat1", &material(), 4),
+            section(b"pan1", &pane("RootPane", 1), 4),
+            section(b"pas1", &[], 4),
+            section(b"pic1", &picture, 4),
+            section(b"txt1", &text, 4),
+            section(b"pae1", &[], 4),
+            section(b"grp1", &root_group, 4),
+            section(b"grs1", &[], 4),
+            section(b"grp1", &group, 4),
+            section(b"gre1", &[], 4),
+        ];
+        let mut data = b"RLYT".to_vec();
+        data.extend_from_slice(&[0xFE, 0xFF, 0x00, 0x0A]);
+        let size = 0x10 + sections.iter().map(|x| x.len()).sum::<usize>();
+        data.extend_from_slice(&(size as u32).to_be_bytes());
+        data.extend_from_slice(&0x10_u16.to_be_bytes());
+        data.extend_from_slice(&(sections.len() as u16).to_be_bytes());
+        sections.iter().for_each(|x| data.extend_from_slice(x));
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let layout = Brlyt::from_binary(&mut Cursor::new(layout())).unwrap();
+        assert_eq!(layout.version, 0x0A);
+        assert!(layout.layout.centered);
+        assert_eq!((layout.layout.width, layout.layout.height), (608.0, 456.0));
+        assert_eq!(layout.textures, vec!["a.tpl", "b.tpl"]);
+        assert_eq!(layout.fonts, vec!["font.brfnt"]);
+
+        assert_eq!(layout.materials.len(), 1);
+        let material = &layout.materials[0];
+        assert_eq!(material.name, "mat0");
+        assert_eq!(material.colors[0], [0xFF, 0, 0, 0xFF]);
+        assert_eq!(material.colors[2], [-1; 4]);
+        assert_eq!(material.texture_maps[0].texture, 1);
+        assert_eq!(material.texture_srts[0].rotation, 90.0);
+        assert_eq!(material.texture_srts[0].scale, [1.0, 2.0]);
+        assert_eq!(material.texture_coord_gens, vec![[1, 4, 30, 0]]);
+        assert_eq!(material.data, vec![0xAA; 4]);
+        assert_eq!(layout.texture_name(0, 0), Some("b.tpl"));
+        assert_eq!(layout.texture_name(0, 1), None);
+    }
+
+    #[test]
+    fn panes() {
+        let layout = Brlyt::from_binary(&mut Cursor::new(layout())).unwrap();
+        assert_eq!(layout.panes.len(), 1);
+        let root = &layout.panes[0];
+        assert_eq!(root.name, "RootPane");
+        assert_eq!(root.kind, PaneKind::Null);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.user_data, *b"userdata");
+        assert_eq!(root.translation, [10.0, -20.0, 0.0]);
+        assert_eq!(root.rotation[2], 45.0);
+        assert_eq!((root.width, root.height), (608.0, 456.0));
+
+        let picture = layout.pane("pic").unwrap();
+        assert!(picture.is_visible());
+        match &picture.kind {
+            PaneKind::Picture(x) => {
+                assert_eq!(x.material, 0);
+                assert_eq!(x.texture_coords, vec![[
+                    [0.0, 0.0],
+                    [1.0, 0.0],
+                    [0.0, 1.0],
+                    [1.0, 1.0]
+                ]]);
+            },
+            x => panic!("unexpected pane: {x:?}"),
+        }
+
+        let text = layout.pane("text").unwrap();
+        assert!(!text.is_visible());
+        match &text.kind {
+            PaneKind::Text(x) => {
+                assert_eq!(x.text, "Hi");
+                assert_eq!((x.position, x.alignment), (1, 2));
+                assert_eq!(x.font_size, [16.0, 16.0]);
+                assert_eq!(x.line_spacing, 2.0);
+            },
+            x => panic!("unexpected pane: {x:?}"),
+        }
+        assert!(layout.pane("missing").is_none());
+
+        assert_eq!(layout.groups.len(), 1);
+        assert_eq!(layout.groups[0].name, "RootGroup");
+        assert_eq!(layout.groups[0].children[0].name, "g");
+        assert_eq!(layout.groups[0].children[0].panes, vec!["pic", "text"]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = layout();
+        data[0] = b'X';
+        assert!(Brlyt::from_binary(&mut Cursor::new(data)).is_err());
+
+        // little-endian
+        let mut data = layout();
+        data[4..6].copy_from_slice(&[0xFF, 0xFE]);
+        assert!(Brlyt::from_binary(&mut Cursor::new(data)).is_err());
+
+        // unbalanced groups (without the last section, gre1)
+        let mut data = layout();
+        data[0x0F] -= 1;
+        assert!(Brlyt::from_binary(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod brstm {
+    use std::io::Cursor;
+
+    use picori::brstm::{Brstm, Codec};
+
+    fn align(data: &mut Vec<u8>) { data.resize(data.len().next_multiple_of(0x20), 0); }
+
+    fn chunk(magic: &[u8], mut body: Vec<u8>) -> Vec<u8> {
+        let mut data = magic.to_vec();
+        data.extend_from_slice(&[0; 4]);
+        data.append(&mut body);
+        align(&mut data);
+        let size = data.len() as u32;
+        data[4..8].copy_from_slice(&size.to_be_bytes());
+        data
+    }
+
+    fn stream(
+        codec: u8,
+        block_samples: u32,
+        final_samples: u32,
+        history: &[[i16; 2]],
+        blocks: &[&[&[u8]]],
+        seek: &[[i16; 2]],
+    ) -> Vec<u8> {
+        let channels = history.len();
+        let last = blocks.last().unwrap()[0].len() as u32;
+        let samples = block_samples * (blocks.len() as u32 - 1) + final_samples;
+
+        // HEAD: references, stream information, (empty) track information,
+        // and channel information
+        let mut head = Vec::new();
+        for offset in [0x18_u32, 0x4C, 0x50] {
+            head.extend_from_slice(&0x01000000_u32.to_be_bytes());
+            head.extend_from_slice(&offset.to_be_bytes());
+        }
+        head.extend_from_slice(&[codec, 1, channels as u8, 0]);
+        head.extend_from_slice(&[0x7D, 0x00, 0, 0]);
+        for word in [
+            1,
+            samples,
+            0,
+            blocks.len() as u32,
+            blocks[0][0].len() as u32,
+            block_samples,
+            last,
+            final_samples,
+            last,
+            block_samples,
+            4,
+        ] {
+            head.extend_from_slice(&word.to_be_bytes());
+        }
+        head.extend_from_slice(&[0; 4]);
+        head.extend_from_slice(&[channels as u8, 0, 0, 0]);
+        let table = head.len();
+        for channel in 0..channels {
+            let offset = (table + 8 * channels + 0x38 * channel) as u32;
+            head.extend_from_slice(&0x01000000_u32.to_be_bytes());
+            head.extend_from_slice(&offset.to_be_bytes());
+        }
+        for history in history {
+            let offset = head.len() as u32 + 8;
+            head.extend_from_slice(&0x01000000_u32.to_be_bytes());
+            head.extend_from_slice(&offset.to_be_bytes());
+            let mut coefficients = [0_i16; 16];
+            coefficients[2] = 2048;
+            for half in coefficients
+                .into_iter()
+                .chain([0, 0x10, history[0], history[1], 0x10, 0, 0, 0])
+            {
+                head.extend_from_slice(&half.to_be_bytes());
+            }
+        }
+        let head = chunk(b"HEAD", head);
+
+        let adpc = chunk(
+            b"ADPC",
+            seek.iter()
+                .flat_map(|x| x.iter().flat_map(|x| x.to_be_bytes()))
+                .collect(),
+        );
+
+        let mut body = 0x18_u32.to_be_bytes().to_vec();
+        body.resize(0x18, 0);
+        for block in blocks {
+            block.iter().for_each(|x| body.extend_from_slice(x));
+        }
+        let data = chunk(b"DATA", body);
+
+        let head_offset = 0x40_u32;
+        let adpc_offset = head_offset + head.len() as u32;
+        let data_offset = adpc_offset + adpc.len() as u32;
+        let mut stream = b"RSTM".to_vec();
+        stream.extend_from_slice(&[0xFE, 0xFF, 0x01, 0x00]);
+        let size = data_offset + data.len() as u32;
+        stream.extend_from_slice(&size.to_be_bytes());
+        stream.extend_from_slice(&[0x00, 0x40, 0x00, 0x02]);
+        for (offset, chunk) in [
+            (head_offset, &head),
+            (adpc_offset, &adpc),
+            (data_offset, &data),
+        ] {
+            stream.extend_from_slice(&offset.to_be_bytes());
+            stream.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+        }
+        stream.resize(0x40, 0);
+        stream.extend_from_slice(&head);
+        stream.extend_from_slice(&adpc);
+        stream.extend_from_slice(&data);
+
+        // absolute offset of the samples in the stream information
+        let offset = head_offset as usize + 8 + 0x18 + 0x10;
+        stream[offset..offset + 4].copy_from_slice(&(data_offset + 0x20).to_be_bytes());
+        stream
+    }
+
+    #[test]
+    fn adpcm() {
+        // channel 0 adds 1 to the previous sample twice per frame, channel 1
+        // repeats the previous sample
+        let add: &[u8] = &[0x10, 0x11, 0, 0, 0, 0, 0, 0];
+        let repeat: &[u8] = &[0x10, 0, 0, 0, 0, 0, 0, 0];
+        let data = stream(
+            2,
+            14,
+            4,
+            &[[0, 0], [50, 0]],
+            &[&[add, repeat], &[add, repeat]],
+            &[[0, 0], [50, 0], [2, 2], [50, 50]],
+        );
+        let brstm = Brstm::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(brstm.info.codec, Codec::Adpcm);
+        assert!(brstm.info.looping);
+        assert_eq!(brstm.info.channels, 2);
+        assert_eq!(brstm.info.sample_rate, 32000);
+        assert_eq!(brstm.info.loop_start, 1);
+        assert_eq!(brstm.info.sample_count, 18);
+        assert_eq!(brstm.info.block_count, 2);
+        assert_eq!(brstm.channels.len(), 2);
+        assert_eq!(brstm.channels[1].history, [50, 0]);
+        assert_eq!(brstm.channels[0].coefficients[2], 2048);
+        assert_eq!(brstm.seek_table[0], [[0, 0], [50, 0]]);
+        assert_eq!(brstm.seek_table[1], [[2, 2], [50, 50]]);
+        assert_eq!(brstm.block(1, 1), Some((repeat, 4)));
+        assert_eq!(brstm.block(2, 0), None);
+
+        let samples = brstm.decode().unwrap();
+        assert_eq!(samples.len(), 36);
+        assert_eq!(samples[..6], [1, 50, 2, 50, 2, 50]);
+        assert_eq!(samples[28..], [3, 50, 4, 50, 4, 50, 4, 50]);
+
+        // seeking starts at the block with the history of the seek table
+        assert_eq!(brstm.decode_from(15).unwrap(), samples[30..]);
+        assert_eq!(brstm.decode_from(3).unwrap(), samples[6..]);
+        assert!(brstm.decode_from(18).unwrap().is_empty());
+        assert!(brstm.decode_from(19).is_err());
+    }
+
+    #[test]
+    fn pcm16() {
+        let data = stream(
+            1,
+            2,
+            1,
+            &[[0, 0]],
+            &[&[&[0x00, 0x01, 0xFF, 0xFE]], &[&[0x00, 0x03, 0x00, 0x00]]],
+            &[],
+        );
+        let brstm = Brstm::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(brstm.info.codec, Codec::Pcm16);
+        assert!(brstm.seek_table.is_empty());
+        assert_eq!(brstm.decode().unwrap(), [1, -2, 3]);
+        assert_eq!(brstm.decode_from(2).unwrap(), [3]);
+    }
+
+    #[test]
+    fn invalid() {
+        let data = stream(1, 2, 2, &[[0, 0]], &[&[&[0, 1, 0, 2]]], &[]);
+        assert!(Brstm::from_binary(&mut Cursor::new(&data)).is_ok());
+        assert!(Brstm::from_binary(&mut Cursor::new(&data[..data.len() - 0x1E])).is_err());
+
+        let mut invalid = data.clone();
+        invalid[0] = b'X';
+        assert!(Brstm::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // HEAD magic
+        let mut invalid = data.clone();
+        invalid[0x40] = b'X';
+        assert!(Brstm::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // codec
+        let mut invalid = data.clone();
+        invalid[0x40 + 8 + 0x18] = 3;
+        assert!(Brstm::from_binary(&mut Cursor::new(&invalid)).is_err());
+
+        // block count and size larger than the input
+        for (offset, value) in [(0x14, 0x10000_u32), (0x14, u32::MAX), (0x28, u32::MAX)] {
+            let mut invalid = data.clone();
+            let offset = 0x40 + 8 + 0x18 + offset;
+            invalid[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+            assert!(Brstm::from_binary(&mut Cursor::new(&invalid)).is_err());
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by the tests to build binary test data.
+
+#![allow(dead_code)]
+
+/// Write `bytes` to `data` at `offset`, growing `data` if needed.
+pub fn put(data: &mut Vec<u8>, offset: usize, bytes: &[u8]) {
+    if data.len() < offset + bytes.len() {
+        data.resize(offset + bytes.len(), 0);
+    }
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Big-endian bytes of `values`.
+pub fn u16s(values: &[u16]) -> Vec<u8> { values.iter().flat_map(|x| x.to_be_bytes()).collect() }
+
+/// Big-endian bytes of `values`.
+pub fn u32s(values: &[u32]) -> Vec<u8> { values.iter().flat_map(|x| x.to_be_bytes()).collect() }
+
+/// Big-endian bytes of `values`.
+pub fn f32s(values: &[f32]) -> Vec<u8> { values.iter().flat_map(|x| x.to_be_bytes()).collect() }
+
+/// Section with `magic`, its size, and `data`, padded to a multiple of
+/// `align`.
+pub fn section(magic: &[u8], data: &[u8], align: usize) -> Vec<u8> {
+    let size = (8 + data.len()).next_multiple_of(align);
+    let mut section = magic.to_vec();
+    section.extend_from_slice(&(size as u32).to_be_bytes());
+    section.extend_from_slice(data);
+    section.resize(size, 0);
+    section
+}
+
+/// Zero-padded fixed size name.
+pub fn name<const L: usize>(name: &str) -> [u8; L] {
+    let mut buffer = [0; L];
+    buffer[..name.len()].copy_from_slice(name.as_bytes());
+    buffer
+}
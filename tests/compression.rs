@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod compression {
+    use std::io::Cursor;
+
+    use picori::compression::{self, Compress, Decompress, Decompressor, Format};
+    use picori::huffman::Huffman;
+    use picori::lz::{Kind, Lz};
+    use picori::rle::Rle;
+    use picori::yay0::Yay0;
+    use picori::yaz0::{self, CompressOptions, Variant, Yaz0};
+    use picori::{Parser, Result, Seeker};
+
+    /// Codec that stores the data after a "RAW!" magic.
+    struct Raw;
+
+    impl Decompress for Raw {
+        const NAME: &'static str = "Raw";
+
+        fn is_compressed<D: Parser + Seeker>(&self, input: &mut D) -> bool {
+            let base = input.position().unwrap();
+            let magic = input.u8_array::<4>();
+            input.goto(base).unwrap();
+            matches!(magic, Ok(x) if &x == b"RAW!")
+        }
+
+        fn decompress<D: Parser + Seeker>(&self, input: &mut D) -> Result<Vec<u8>> {
+            input.u8_array::<4>()?;
+            let mut data = Vec::new();
+            input.read_to_end(&mut data)?;
+            Ok(data)
+        }
+    }
+
+    impl Compress for Raw {
+        fn compress(&self, data: &[u8]) -> Result<Vec<u8>> { Ok([b"RAW!", data].concat()) }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output");
+        let codecs: [(&dyn Compress, &dyn Decompressor); 5] = [
+            (&Yaz0::default(), &Yaz0::default()),
+            (&Lz::default(), &Lz::default()),
+            (&Lz { kind: Kind::Lz11 }, &Lz::default()),
+            (&Rle, &Rle),
+            (&Raw, &Raw),
+        ];
+        let decompressors: [&dyn Decompressor; 6] = [
+            &Yaz0::default(),
+            &Yay0,
+            &Lz::default(),
+            &Huffman,
+            &Rle,
+            &Raw,
+        ];
+
+        for (compressor, decompressor) in codecs {
+            let compressed = compressor.compress(data).unwrap();
+            assert!(decompressor.detect(&compressed));
+            assert!(decompressor.decompress_bytes(&compressed).unwrap() == data);
+
+            let detected = decompressors
+                .iter()
+                .filter(|x| x.detect(&compressed))
+                .map(|x| x.name())
+                .collect::<Vec<_>>();
+            assert_eq!(detected, [decompressor.name()]);
+        }
+    }
+
+    #[test]
+    fn generic() {
+        let compressed = Rle.compress(b"aaaaaaaa").unwrap();
+        let mut cursor = Cursor::new(&compressed);
+        assert!(Rle.is_compressed(&mut cursor));
+        assert_eq!(cursor.position(), 0);
+        assert_eq!(Rle.decompress(&mut cursor).unwrap(), b"aaaaaaaa");
+        assert!(!Yaz0::default().is_compressed(&mut Cursor::new(&compressed)));
+    }
+
+    #[test]
+    fn decompress_into() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output");
+        let codecs: [(&dyn Compress, &dyn Decompressor); 4] = [
+            (&Yaz0::default(), &Yaz0::default()),
+            (&Lz { kind: Kind::Lz11 }, &Lz::default()),
+            (&Rle, &Rle),
+            (&Raw, &Raw),
+        ];
+
+        // one buffer reused for all files
+        let mut buffer = vec![0; data.len() + 0x100];
+        for (compressor, decompressor) in codecs {
+            buffer.fill(0xAA);
+            let compressed = compressor.compress(data).unwrap();
+            let written = decompressor
+                .decompress_bytes_into(&compressed, &mut buffer)
+                .unwrap();
+            assert_eq!(written, data.len());
+            assert!(&buffer[..written] == data);
+            assert!(buffer[written..].iter().all(|&x| x == 0xAA));
+
+            let mut small = vec![0; data.len() - 1];
+            assert!(decompressor
+                .decompress_bytes_into(&compressed, &mut small)
+                .is_err());
+        }
+
+        let compressed = Rle.compress(b"aaaaaaaa").unwrap();
+        let mut buffer = [0; 16];
+        assert_eq!(
+            Rle.decompress_into(&mut Cursor::new(&compressed), &mut buffer)
+                .unwrap(),
+            8
+        );
+        assert_eq!(&buffer[..8], b"aaaaaaaa");
+    }
+
+    #[test]
+    fn decompress_auto() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output").as_slice();
+        let lz = Lz { kind: Kind::Lz11 }.compress(data).unwrap();
+        let cases = [
+            (Yaz0::default().compress(data).unwrap(), Format::Yaz0),
+            (
+                yaz0::compress_with(data, &CompressOptions::new().variant(Variant::Yaz1)),
+                Format::Yaz1,
+            ),
+            (
+                Lz::default().compress(data).unwrap(),
+                Format::Lz(Kind::Lz10),
+            ),
+            (lz.clone(), Format::Lz(Kind::Lz11)),
+            ([b"LZ77".as_slice(), &lz].concat(), Format::Lz77(Kind::Lz11)),
+            (Rle.compress(data).unwrap(), Format::Rle),
+        ];
+        for (compressed, format) in cases {
+            let mut cursor = Cursor::new(&compressed);
+            let (found, decompressed) = compression::decompress_auto(&mut cursor).unwrap().unwrap();
+            assert_eq!(found, format);
+            assert!(decompressed == data);
+        }
+
+        // not compressed, or only a matching type byte
+        for data in [b"abcdefgh".as_slice(), b"\x10\xFF\xFF\xFF\x00", b"ab"] {
+            let mut cursor = Cursor::new(data);
+            assert!(compression::decompress_auto(&mut cursor).unwrap().is_none());
+            assert_eq!(cursor.position(), 0);
+        }
+
+        // decompressed size too large for the input, rejected before allocating
+        for data in [
+            b"\x11\x00\x00\x00\xFF\xFF\xFF\xFF\x00\x00".as_slice(),
+            b"\x28\xFF\xFF\xFF\x00\x00\x00\x00",
+            b"\x30\x00\x10\x00\xFF\x61",
+        ] {
+            let mut cursor = Cursor::new(data);
+            assert!(compression::decompress_auto(&mut cursor).unwrap().is_none());
+            assert_eq!(cursor.position(), 0);
+        }
+
+        // long runs are still detected
+        let compressed = Rle.compress(&[b'a'; 1000]).unwrap();
+        let mut cursor = Cursor::new(&compressed);
+        let (found, decompressed) = compression::decompress_auto(&mut cursor).unwrap().unwrap();
+        assert_eq!(found, Format::Rle);
+        assert_eq!(decompressed, [b'a'; 1000]);
+
+        // errors of formats detected by magic are returned
+        let mut cursor = Cursor::new(b"Yaz0\x00\x00\x00\x10\x00\x00\x00\x00\x00\x00\x00\x00");
+        assert!(compression::decompress_auto(&mut cursor).is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn compress_many() {
+        let files: [&[u8]; 3] = [b"aaaaaaaa", b"", b"abcabcabcabc"];
+        let compressed = compression::compress_many(&Rle, &files).unwrap();
+        for (file, compressed) in files.iter().zip(&compressed) {
+            assert_eq!(Rle.decompress_bytes(compressed).unwrap(), *file);
+        }
+
+        let codec: &(dyn Compress + Sync) = &Yaz0::default();
+        assert_eq!(compression::compress_many(codec, &files).unwrap().len(), 3);
+    }
+}
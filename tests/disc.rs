@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod disc {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    #[cfg(feature = "hash")]
+    use picori::disc::hash::{self, Hasher};
+    use picori::disc::{self, DiscKind, DiscReader, Format};
+    use picori::gcm::ConsoleType;
+    use picori::wbfs::{WbfsReader, WbfsWriter};
+
+    fn data(size: usize) -> Vec<u8> { (0..size).map(|i| (i * 7 + i / 251) as u8).collect() }
+
+    fn image() -> Vec<u8> {
+        let mut image = data(0x600000);
+        image[0x200000..0x400000].fill(0);
+        image
+    }
+
+    fn convert(input: &[u8], format: Format) -> Vec<u8> {
+        let mut output = Cursor::new(Vec::new());
+        disc::convert(&mut Cursor::new(input), &mut output, format).unwrap();
+        output.into_inner()
+    }
+
+    #[test]
+    fn detect() {
+        let image = image();
+        let ciso = convert(&image, Format::Ciso);
+        assert_eq!(
+            Format::detect(&mut Cursor::new(&image)).unwrap(),
+            Format::Iso
+        );
+        assert_eq!(
+            Format::detect(&mut Cursor::new(&ciso)).unwrap(),
+            Format::Ciso
+        );
+        assert_eq!(Format::detect(&mut Cursor::new([])).unwrap(), Format::Iso);
+    }
+
+    #[test]
+    fn detect_kind() {
+        let mut header = vec![0u8; 0x20];
+        assert_eq!(DiscKind::from_header(&header), None);
+        assert_eq!(DiscKind::from_header(&[]), None);
+
+        header[0x1C..0x20].copy_from_slice(&0xC2339F3D_u32.to_be_bytes());
+        let mut input = Cursor::new(&header);
+        assert_eq!(
+            DiscKind::detect(&mut input).unwrap(),
+            Some(DiscKind::GameCube)
+        );
+        assert_eq!(input.position(), 0);
+
+        header[0x18..0x1C].copy_from_slice(&0x5D1C9EA3_u32.to_be_bytes());
+        header[0x1C..0x20].fill(0);
+        let kind = DiscKind::detect(&mut Cursor::new(&header)).unwrap();
+        assert_eq!(kind, Some(DiscKind::Wii));
+        assert_eq!(kind.unwrap().console(), ConsoleType::Wii);
+
+        assert!(picori::Gcm::from_binary(&mut Cursor::new(image())).is_err());
+    }
+
+    #[test]
+    fn open_stream() {
+        let image = image();
+        let ciso = convert(&image, Format::Ciso);
+        for input in [&image, &ciso] {
+            let mut input = Cursor::new(input);
+            let mut stream = disc::open(&mut input).unwrap();
+            assert_eq!(stream.get_ref().size(), image.len() as u64);
+
+            let mut buffer = [0u8; 0x20];
+            stream.seek(SeekFrom::Start(0x1FFFF0)).unwrap();
+            stream.read_exact(&mut buffer).unwrap();
+            assert_eq!(buffer, image[0x1FFFF0..0x200010]);
+            stream.seek(SeekFrom::End(-8)).unwrap();
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, image[0x5FFFF8..]);
+        }
+
+        let mut input = Cursor::new(&image[..0x8010]);
+        let mut reader = disc::IsoReader::new(&mut input).unwrap();
+        assert_eq!(reader.block_size(), 0x8000);
+        assert_eq!(reader.read_block(1).unwrap(), image[0x8000..0x8010]);
+    }
+
+    #[test]
+    fn convert_ciso() {
+        let image = image();
+        let ciso = convert(&image, Format::Ciso);
+        // the zero block is omitted
+        assert_eq!(ciso.len(), 0x8000 + 0x400000);
+        assert_eq!(convert(&ciso, Format::Iso), image);
+    }
+
+    #[test]
+    fn convert_wbfs() {
+        let image = image();
+        let wbfs = convert(&image, Format::Wbfs);
+        assert_eq!(
+            Format::detect(&mut Cursor::new(&wbfs)).unwrap(),
+            Format::Wbfs
+        );
+        // the header sector and the two used sectors, the zero sector is omitted
+        assert_eq!(wbfs.len(), 0x200000 + 0x400000);
+        assert_eq!(convert(&wbfs, Format::Iso), image);
+        assert_eq!(convert(&convert(&wbfs, Format::Ciso), Format::Iso), image);
+
+        let mut input = Cursor::new(&wbfs);
+        let mut stream = disc::open(&mut input).unwrap();
+        let mut buffer = [0u8; 0x20];
+        stream.seek(SeekFrom::Start(0x3FFFF0)).unwrap();
+        stream.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, image[0x3FFFF0..0x400010]);
+
+        let mut invalid = wbfs.clone();
+        invalid[0x09] = 40;
+        assert!(WbfsReader::new(&mut Cursor::new(&invalid)).is_err());
+        invalid[0x09] = 21;
+        invalid[0x0C] = 0;
+        assert!(WbfsReader::new(&mut Cursor::new(&invalid)).is_err());
+        assert!(WbfsWriter::new(&mut Cursor::new(Vec::new()), 0x1000).is_err());
+        assert!(WbfsWriter::new(&mut Cursor::new(Vec::new()), 0x20000).is_err());
+    }
+
+    /// Backend that returns blocks shorter than its block size.
+    struct ShortReader;
+
+    impl DiscReader for ShortReader {
+        fn block_size(&self) -> u64 { 0x10 }
+
+        fn size(&self) -> u64 { 0x40 }
+
+        fn read_block(&mut self, _index: u64) -> picori::Result<Vec<u8>> { Ok(vec![0xAA; 8]) }
+    }
+
+    #[test]
+    fn short_block() {
+        let mut stream = disc::DiscStream::new(ShortReader);
+        let mut buffer = [0u8; 0x10];
+        let error = stream.read_exact(&mut buffer).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(stream.stream_position().unwrap(), 8);
+
+        let mut buffer = Vec::new();
+        assert!(stream.read_to_end(&mut buffer).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "gcz")]
+    fn convert_gcz() {
+        let image = image();
+        let gcz = convert(&image, Format::Gcz);
+        assert_eq!(Format::detect(&mut Cursor::new(&gcz)).unwrap(), Format::Gcz);
+        assert!(gcz.len() < image.len());
+        assert_eq!(convert(&gcz, Format::Iso), image);
+
+        let ciso = convert(&gcz, Format::Ciso);
+        assert_eq!(convert(&ciso, Format::Iso), image);
+
+        // the sub type of the header is 1 for Wii images
+        let header = picori::gcz::Header::from_binary(&mut Cursor::new(&gcz)).unwrap();
+        assert_eq!(header.sub_type, 0);
+        let mut wii = image.clone();
+        wii[0x18..0x1C].copy_from_slice(&0x5D1C9EA3_u32.to_be_bytes());
+        let gcz = convert(&wii, Format::Gcz);
+        let header = picori::gcz::Header::from_binary(&mut Cursor::new(&gcz)).unwrap();
+        assert_eq!(header.sub_type, 1);
+        assert_eq!(convert(&gcz, Format::Iso), wii);
+    }
+
+    #[test]
+    #[cfg(feature = "gcz")]
+    fn gcz_checksum_mismatch() {
+        let mut gcz = convert(&image(), Format::Gcz);
+        let last = gcz.len() - 1;
+        gcz[last] ^= 0xFF;
+        let mut output = Cursor::new(Vec::new());
+        assert!(disc::convert(&mut Cursor::new(gcz), &mut output, Format::Iso).is_err());
+    }
+
+    #[test]
+    fn convert_progress() {
+        let image = image();
+        let mut calls = Vec::new();
+        let mut output = Cursor::new(Vec::new());
+        disc::convert_with_progress(
+            &mut Cursor::new(&image),
+            &mut output,
+            Format::Iso,
+            |done, total| calls.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(calls.first(), Some(&(0, 0x600000)));
+        assert_eq!(calls.last(), Some(&(0x600000, 0x600000)));
+        assert!(calls.windows(2).all(|x| x[0].0 < x[1].0));
+        assert_eq!(output.into_inner(), image);
+    }
+
+    #[test]
+    #[cfg(feature = "hash")]
+    fn hash_empty() {
+        let digests = picori::disc::hash(&mut Cursor::new([])).unwrap();
+        assert_eq!(digests.size, 0);
+        assert_eq!(digests.crc32, 0);
+        assert_eq!(digests.md5, [
+            0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04, 0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8,
+            0x42, 0x7e
+        ]);
+        assert_eq!(digests.sha1, [
+            0xda, 0x39, 0xa3, 0xee, 0x5e, 0x6b, 0x4b, 0x0d, 0x32, 0x55, 0xbf, 0xef, 0x95, 0x60,
+            0x18, 0x90, 0xaf, 0xd8, 0x07, 0x09
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "hash")]
+    fn hash_abc() {
+        let digests = picori::disc::hash(&mut Cursor::new(b"abc")).unwrap();
+        assert_eq!(digests.size, 3);
+        assert_eq!(digests.crc32, 0x352441C2);
+        assert_eq!(digests.md5, [
+            0x90, 0x01, 0x50, 0x98, 0x3c, 0xd2, 0x4f, 0xb0, 0xd6, 0x96, 0x3f, 0x7d, 0x28, 0xe1,
+            0x7f, 0x72
+        ]);
+        assert_eq!(digests.sha1, [
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+            0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "hash")]
+    fn hash_parallel() {
+        let data = data(hash::CHUNK_SIZE * 2 + 0x1234);
+        let digests = picori::disc::hash(&mut Cursor::new(&data)).unwrap();
+        let parallel = picori::disc::hash_parallel(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(digests, parallel);
+        assert_eq!(digests.size, data.len() as u64);
+
+        let mut hasher = Hasher::new();
+        for chunk in data.chunks(0x10001) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize(), digests);
+    }
+}
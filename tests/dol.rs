@@ -144,4 +144,30 @@ mod dol {
         assert_eq!(init2.size, 0x24E8);
         assert_eq!(init2.aligned_size, 0x2500);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut file = include_bytes!("../assets/tests/dol/test1.dol");
+        let dol = Dol::from_binary(&mut Cursor::new(&mut file)).unwrap();
+        let json = serde_json::to_string(&dol).unwrap();
+        assert!(json.contains(r#""name":".init""#));
+
+        // section data is not serialized
+        let copy: Dol = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.header.entry_point, dol.header.entry_point);
+        assert_eq!(copy.sections.len(), dol.sections.len());
+        let init = copy.section_by_name(".init").unwrap();
+        assert_eq!(init.address, 0x8000_3100);
+        assert_eq!(init.size, 0x24E8);
+        assert!(init.data.is_empty());
+        assert_eq!(
+            copy.rom_copy_info.unwrap().entries.len(),
+            dol.rom_copy_info.unwrap().entries.len()
+        );
+
+        // only guessed section names are valid
+        let json = json.replacen(r#""name":".init""#, r#""name":".unknown""#, 1);
+        assert!(serde_json::from_str::<Dol>(&json).is_err());
+    }
 }
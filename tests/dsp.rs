@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod dsp {
+    use std::io::Cursor;
+
+    use picori::dsp::{self, Dsp};
+
+    /// Coefficients with index 0 (silence) and index 1 (previous sample).
+    fn coefficients() -> [i16; 16] {
+        let mut coefficients = [0; 16];
+        coefficients[2] = 2048;
+        coefficients
+    }
+
+    fn sound(samples: u32, data: &[u8]) -> Vec<u8> {
+        let mut sound = Vec::new();
+        let nibbles = 2 + (samples / 14) * 16 + samples % 14;
+        for word in [samples, nibbles, 32000] {
+            sound.extend_from_slice(&word.to_be_bytes());
+        }
+        sound.extend_from_slice(&[0, 1, 0, 0]);
+        for word in [2_u32, 16 + 2 + 5, 2] {
+            sound.extend_from_slice(&word.to_be_bytes());
+        }
+        coefficients()
+            .iter()
+            .for_each(|x| sound.extend_from_slice(&x.to_be_bytes()));
+        for half in [0_i16, 0x11, 10, 0, 0x11, 12, 10] {
+            sound.extend_from_slice(&half.to_be_bytes());
+        }
+        sound.resize(0x60, 0);
+        sound.extend_from_slice(data);
+        sound
+    }
+
+    #[test]
+    fn decode_adpcm() {
+        // scale 4 (1 << 2) with coefficient index 1
+        let data = [0x12, 0x1F, 0x7F, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let samples = dsp::decode_adpcm(&data, &coefficients(), [10, 0], 4).unwrap();
+        assert_eq!(samples, [14, 10, 38, 34]);
+
+        // coefficient index 0, the samples are the scaled nibbles
+        let data = [0x0C, 0x17, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x20];
+        let samples = dsp::decode_adpcm(&data, &coefficients(), [0, 0], 16).unwrap();
+        assert_eq!(samples[..4], [0x1000, 0x7000, -0x8000, 0]);
+        assert_eq!(samples[14..], [16, 0]);
+
+        // continue with the returned history
+        let mut output = Vec::new();
+        let data = [0x10, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let history =
+            dsp::decode_adpcm_into(&data, &coefficients(), [100, 0], 2, &mut output).unwrap();
+        assert_eq!(history, [102, 101]);
+        assert_eq!(output, [101, 102]);
+
+        // largest scale, coefficients, and history, the sum does not fit an i32
+        let data = [0x0F, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let mut extreme = [0; 16];
+        extreme[..2].fill(0x7FFF);
+        let samples = dsp::decode_adpcm(&data, &extreme, [0x7FFF, 0x7FFF], 2).unwrap();
+        assert_eq!(samples, [0x7FFF, 0x7FFF]);
+        let data = [0x0F, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        extreme[..2].fill(-0x8000);
+        let samples = dsp::decode_adpcm(&data, &extreme, [0x7FFF, 0x7FFF], 2).unwrap();
+        assert_eq!(samples, [-0x8000, -0x8000]);
+
+        assert!(dsp::decode_adpcm(&data, &coefficients(), [0, 0], 15).is_err());
+        assert!(dsp::decode_adpcm(&data[..1], &coefficients(), [0, 0], 1).is_err());
+    }
+
+    #[test]
+    fn from_binary() {
+        let data = sound(16, &[
+            0x11, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        let dsp = Dsp::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(dsp.header.sample_count, 16);
+        assert_eq!(dsp.header.nibble_count, 20);
+        assert_eq!(dsp.header.sample_rate, 32000);
+        assert!(dsp.header.looping);
+        assert_eq!(dsp.header.loop_start(), 0);
+        assert_eq!(dsp.header.loop_end(), 19);
+        assert_eq!(dsp.header.coefficients, coefficients());
+        assert_eq!(dsp.header.predictor_scale, 0x11);
+        assert_eq!(dsp.header.history, [10, 0]);
+        assert_eq!(dsp.header.loop_predictor_scale, 0x11);
+        assert_eq!(dsp.header.loop_history, [12, 10]);
+        assert_eq!(dsp.data.len(), 10);
+
+        let samples = dsp.decode().unwrap();
+        assert_eq!(samples.len(), 16);
+        assert_eq!(samples[..3], [12, 10, 10]);
+
+        // truncated data
+        assert!(Dsp::from_binary(&mut Cursor::new(&data[..0x64])).is_err());
+
+        // sample and nibble count larger than the data
+        let mut invalid = data.clone();
+        invalid[0..8].fill(0xFF);
+        assert!(Dsp::from_binary(&mut Cursor::new(&invalid)).is_err());
+        let mut dsp = Dsp::from_binary(&mut Cursor::new(&data)).unwrap();
+        dsp.header.sample_count = u32::MAX;
+        assert!(dsp.decode().is_err());
+
+        // not ADPCM
+        let mut data = data;
+        data[0x0F] = 1;
+        assert!(Dsp::from_binary(&mut Cursor::new(&data)).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+#[cfg(test)]
+mod dzb {
+    use std::io::Cursor;
+
+    use picori::dzb::Dzb;
+
+    use crate::common::{f32s, u16s, u32s};
+
+    /// Square (two triangles) with a single group and two properties.
+    fn mesh() -> Vec<u8> {
+        let vertices = f32s(&[
+            0.0, 0.0, 0.0, 100.0, 0.0, 0.0, 100.0, 0.0, 100.0, 0.0, 0.0, 100.0,
+        ]);
+        let triangles = u16s(&[0, 1, 2, 0, 0, 0, 2, 3, 1, 0]);
+        let blocks = u16s(&[0]);
+        let mut octree = vec![1, 0];
+        octree.extend_from_slice(&u16s(&[0xFFFF, 0, 0, 0, 0, 0, 0, 0, 0]));
+        let mut group = u32s(&[0]);
+        group.extend_from_slice(&f32s(&[1.0, 1.0, 1.0]));
+        group.extend_from_slice(&u16s(&[0, 0x4000, 0, 0xFFFF]));
+        group.extend_from_slice(&f32s(&[0.0, 10.0, 0.0]));
+        group.extend_from_slice(&u16s(&[0xFFFF, 0xFFFF, 0xFFFF, 2, 0, 0]));
+        group.extend_from_slice(&u32s(&[0x100]));
+        let properties = u32s(&[0x00000001, 0, 0, 0, 0x00000002, 0x00100000, 0, 0]);
+        let name = b"Room2\0".to_vec();
+
+        let blocks_data = [vertices, triangles, blocks, octree, group, properties, name];
+        let mut header = Vec::new();
+        let mut offset = 0x34;
+        let counts = [4, 2, 1, 1, 1, 2];
+        for (count, data) in counts.iter().zip(blocks_data.iter()) {
+            header.extend_from_slice(&u32s(&[*count, offset]));
+            offset += data.len() as u32;
+        }
+        header.extend_from_slice(&[0; 4]);
+
+        let mut data = header;
+        blocks_data.iter().for_each(|x| data.extend_from_slice(x));
+        // name offset of the group
+        let group = 0x34 + 48 + 20 + 2 + 20;
+        data[group..group + 4].copy_from_slice(&u32s(&[offset]));
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let dzb = Dzb::from_binary(&mut Cursor::new(mesh())).unwrap();
+        assert_eq!(dzb.vertices.len(), 4);
+        assert_eq!(dzb.vertices[2], [100.0, 0.0, 100.0]);
+        assert_eq!(dzb.triangles.len(), 2);
+        assert_eq!(dzb.triangles[1].vertices, [0, 2, 3]);
+        assert_eq!(dzb.triangles[1].property, 1);
+        assert_eq!(dzb.blocks, vec![0]);
+        assert!(dzb.octree[0].is_leaf());
+        assert_eq!(dzb.octree[0].parent, 0xFFFF);
+
+        let group = &dzb.groups[0];
+        assert_eq!(group.name, "Room2");
+        assert_eq!(group.rotation, [0, 0x4000, 0]);
+        assert_eq!(group.translation, [0.0, 10.0, 0.0]);
+        assert_eq!(group.room, 2);
+        assert_eq!(group.flags, 0x100);
+        assert_eq!(dzb.properties[1].words[1], 0x00100000);
+    }
+
+    #[test]
+    fn triangles() {
+        let dzb = Dzb::from_binary(&mut Cursor::new(mesh())).unwrap();
+        let triangles = dzb.triangles().collect::<Vec<_>>();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[0].positions, [
+            [0.0, 0.0, 0.0],
+            [100.0, 0.0, 0.0],
+            [100.0, 0.0, 100.0]
+        ]);
+        assert_eq!(triangles[0].property.words[0], 1);
+        assert_eq!(triangles[1].index, 1);
+        assert_eq!(triangles[1].property.words[0], 2);
+        assert_eq!(triangles[1].group.unwrap().name, "Room2");
+        assert_eq!(dzb.block(0), Some(0..2));
+        assert_eq!(dzb.block(1), None);
+    }
+
+    #[test]
+    fn invalid() {
+        // vertex index out of range
+        let mut data = mesh();
+        data[0x34 + 48 + 1] = 4;
+        assert!(Dzb::from_binary(&mut Cursor::new(data)).is_err());
+
+        // property index out of range
+        let mut data = mesh();
+        data[0x34 + 48 + 7] = 2;
+        assert!(Dzb::from_binary(&mut Cursor::new(data)).is_err());
+
+        // truncated
+        let data = mesh();
+        assert!(Dzb::from_binary(&mut Cursor::new(&data[..0x40])).is_err());
+    }
+}
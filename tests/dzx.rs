@@ -0,0 +1,120 @@
+mod common;
+
+#[cfg(test)]
+mod dzx {
+    use std::io::Cursor;
+
+    use picori::dzx::{Dzx, Entities};
+
+    use crate::common::{f32s, name};
+
+    fn actor(name: &str, params: u32, x: f32) -> Vec<u8> {
+        let mut data = self::name::<8>(name).to_vec();
+        data.extend_from_slice(&params.to_be_bytes());
+        data.extend_from_slice(&f32s(&[x, 0.0, -x]));
+        data.extend_from_slice(&[0, 0, 0x80, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        data
+    }
+
+    fn stage() -> Vec<u8> {
+        let mut scaled = actor("Ylsic", 0x10, 5.0);
+        scaled.extend_from_slice(&[10, 20, 10, 0xFF]);
+        let mut treasure = name::<8>("takara").to_vec();
+        treasure.extend_from_slice(&0xFF000000_u32.to_be_bytes());
+        treasure.extend_from_slice(&f32s(&[1.0, 2.0, 3.0]));
+        treasure.extend_from_slice(&[0, 3, 0x40, 0, 0x01, 0x0A, 0xFF, 0xFF]);
+        let mut exit = name::<8>("sea").to_vec();
+        exit.extend_from_slice(&[0, 44, 2, 0xFF]);
+
+        let chunks: [(&[u8; 4], u32, Vec<u8>); 7] = [
+            (
+                b"ACTR",
+                2,
+                [actor("Bk", 1, 0.0), actor("kamome", 2, 100.0)].concat(),
+            ),
+            (b"ACT1", 1, actor("Mo2", 3, 50.0)),
+            (b"SCOB", 1, scaled),
+            (b"TRES", 1, treasure),
+            (b"SCLS", 1, exit),
+            (b"RPPN", 2, vec![0xAB; 0x20]),
+            (b"FILI", 0, Vec::new()),
+        ];
+        let mut data = (chunks.len() as u32).to_be_bytes().to_vec();
+        let mut offset = 4 + 0x0C * chunks.len();
+        for (magic, count, entries) in chunks.iter() {
+            data.extend_from_slice(*magic);
+            data.extend_from_slice(&count.to_be_bytes());
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            offset += entries.len();
+        }
+        chunks.iter().for_each(|x| data.extend_from_slice(&x.2));
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let dzx = Dzx::from_binary(&mut Cursor::new(stage())).unwrap();
+        assert_eq!(dzx.chunks.len(), 7);
+
+        match &dzx.chunks[0].entities {
+            Entities::Actors(actors) => {
+                assert_eq!(actors.len(), 2);
+                assert_eq!(actors[1].name, "kamome");
+                assert_eq!(actors[1].params, 2);
+                assert_eq!(actors[1].position, [100.0, 0.0, -100.0]);
+                assert_eq!(actors[1].rotation, [0, 0x8000, 0xFFFF]);
+                assert_eq!(actors[1].enemy_number, 0xFFFF);
+            },
+            x => panic!("unexpected entities: {x:?}"),
+        }
+        assert_eq!(dzx.chunks[0].layer(), None);
+        assert_eq!(dzx.chunks[1].layer(), Some(1));
+
+        match &dzx.chunks[2].entities {
+            Entities::ScaledActors(actors) => {
+                assert_eq!(actors[0].actor.name, "Ylsic");
+                assert_eq!(actors[0].scale, [10, 20, 10]);
+            },
+            x => panic!("unexpected entities: {x:?}"),
+        }
+        match &dzx.chunks[3].entities {
+            Entities::Treasures(treasures) => {
+                assert_eq!(treasures[0].name, "takara");
+                assert_eq!(treasures[0].room, 3);
+                assert_eq!(treasures[0].rotation_y, 0x4000);
+                assert_eq!((treasures[0].item, treasures[0].flag), (0x01, 0x0A));
+            },
+            x => panic!("unexpected entities: {x:?}"),
+        }
+        match &dzx.chunks[4].entities {
+            Entities::Exits(exits) => {
+                assert_eq!(exits[0].stage, "sea");
+                assert_eq!((exits[0].spawn, exits[0].room, exits[0].fade), (0, 44, 2));
+            },
+            x => panic!("unexpected entities: {x:?}"),
+        }
+        assert_eq!(dzx.chunks[5].entities, Entities::Raw(vec![0xAB; 0x20]));
+        assert_eq!(dzx.chunks[6].entities, Entities::Raw(Vec::new()));
+    }
+
+    #[test]
+    fn actors() {
+        let dzx = Dzx::from_binary(&mut Cursor::new(stage())).unwrap();
+        let names = dzx.actors().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Bk", "kamome", "Mo2", "Ylsic"]);
+        assert_eq!(dzx.find_chunks(b"ACTR").count(), 1);
+        assert_eq!(dzx.find_chunks(b"STAG").count(), 0);
+    }
+
+    #[test]
+    fn invalid() {
+        // chunk outside of the file
+        let mut data = stage();
+        data[0x0C] = 0x10;
+        assert!(Dzx::from_binary(&mut Cursor::new(data)).is_err());
+
+        // truncated
+        let data = stage();
+        assert!(Dzx::from_binary(&mut Cursor::new(&data[..0x80])).is_err());
+    }
+}
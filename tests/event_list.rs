@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod event_list {
+    use std::io::Cursor;
+
+    use picori::event_list::{Action, Actor, Event, EventList, Property, Value};
+
+    fn list() -> EventList {
+        EventList {
+            events:     vec![Event {
+                name:           "DEFAULT_GETITEM".to_string(),
+                index:          0,
+                unknown:        0,
+                priority:       1,
+                actors:         vec![0, 1],
+                starting_flags: [-1, -1],
+                ending_flags:   [12, -1, -1],
+                play_jingle:    true,
+            }],
+            actors:     vec![
+                Actor {
+                    name: "Link".to_string(),
+                    staff_identifier: 0,
+                    index: 0,
+                    flag: -1,
+                    staff_type: 0,
+                    first_action: 0,
+                },
+                Actor {
+                    name: "CAMERA".to_string(),
+                    staff_identifier: 1,
+                    index: 1,
+                    flag: -1,
+                    staff_type: 2,
+                    first_action: 2,
+                },
+            ],
+            actions:    vec![
+                Action {
+                    name:           "WAIT".to_string(),
+                    duplicate_id:   0,
+                    index:          0,
+                    starting_flags: [-1, -1, -1],
+                    flag:           10,
+                    first_property: 0,
+                    next_action:    1,
+                },
+                Action {
+                    name:           "TALK".to_string(),
+                    duplicate_id:   0,
+                    index:          1,
+                    starting_flags: [10, -1, -1],
+                    flag:           12,
+                    first_property: 2,
+                    next_action:    -1,
+                },
+                Action {
+                    name:           "FIXEDFRM".to_string(),
+                    duplicate_id:   0,
+                    index:          2,
+                    starting_flags: [-1, -1, -1],
+                    flag:           -1,
+                    first_property: -1,
+                    next_action:    -1,
+                },
+            ],
+            properties: vec![
+                Property {
+                    name:          "pos".to_string(),
+                    index:         0,
+                    value:         Value::Vector(vec![[1.0, 2.0, 3.0]]),
+                    next_property: 1,
+                },
+                Property {
+                    name:          "Timer".to_string(),
+                    index:         1,
+                    value:         Value::Integer(vec![30]),
+                    next_property: -1,
+                },
+                Property {
+                    name:          "msg".to_string(),
+                    index:         2,
+                    value:         Value::String("ちょっと".to_string()),
+                    next_property: 3,
+                },
+                Property {
+                    name:          "speed".to_string(),
+                    index:         3,
+                    value:         Value::Float(vec![0.5, 1.5]),
+                    next_property: -1,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let list = list();
+        let mut data = Vec::new();
+        list.to_binary(&mut data).unwrap();
+        assert_eq!(&data[..8], &[0, 0, 0, 0x40, 0, 0, 0, 1]);
+        assert_eq!(
+            data.len(),
+            0x40 + 0xB0 + 2 * 0x50 + 3 * 0x50 + 4 * 0x40 + 5 * 4 + 4 + 12
+        );
+
+        let parsed = EventList::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(parsed, list);
+    }
+
+    #[test]
+    fn links() {
+        let list = list();
+        let event = list.event("DEFAULT_GETITEM").unwrap();
+        let actors = list.actors_of(event).collect::<Vec<_>>();
+        assert_eq!(actors.len(), 2);
+        assert_eq!(actors[1].name, "CAMERA");
+
+        let actions = list.actions_of(actors[0]).map(|x| x.name.as_str());
+        assert_eq!(actions.collect::<Vec<_>>(), vec!["WAIT", "TALK"]);
+        let talk = &list.actions[1];
+        let properties = list.properties_of(talk).map(|x| x.name.as_str());
+        assert_eq!(properties.collect::<Vec<_>>(), vec!["msg", "speed"]);
+        assert_eq!(list.properties_of(&list.actions[2]).count(), 0);
+        assert!(list.event("OPENING").is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = Vec::new();
+        list().to_binary(&mut data).unwrap();
+
+        // unknown property type
+        let property = 0x40 + 0xB0 + 5 * 0x50;
+        let mut invalid = data.clone();
+        invalid[property + 0x27] = 2;
+        assert!(EventList::from_binary(&mut Cursor::new(invalid)).is_err());
+
+        // property value out of range
+        let mut invalid = data.clone();
+        invalid[property + 0x2F] = 2;
+        assert!(EventList::from_binary(&mut Cursor::new(invalid)).is_err());
+
+        // too many actors
+        let mut invalid = data.clone();
+        invalid[0x40 + 0x7F] = 21;
+        assert!(EventList::from_binary(&mut Cursor::new(invalid)).is_err());
+
+        // truncated
+        assert!(EventList::from_binary(&mut Cursor::new(&data[..0x100])).is_err());
+    }
+}
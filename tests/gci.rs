@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod gci {
+    use std::io::Cursor;
+
+    use picori::gci::{Animation, BannerFormat, Gci, IconFormat, BLOCK_SIZE};
+    use picori::Encoding;
+
+    fn file(game_id: &[u8; 6], blocks: u16, comment_offset: u32) -> Vec<u8> {
+        let mut data = game_id.to_vec();
+        data.extend_from_slice(&[0xFF, 0x02]);
+        let mut filename = [0; 32];
+        filename[..9].copy_from_slice(b"gczelda2\0");
+        data.extend_from_slice(&filename);
+        data.extend_from_slice(&0x12345678_u32.to_be_bytes());
+        data.extend_from_slice(&0x40_u32.to_be_bytes());
+        data.extend_from_slice(&[0x00, 0x05, 0x00, 0x0A, 0x04, 0x01]);
+        data.extend_from_slice(&5_u16.to_be_bytes());
+        data.extend_from_slice(&blocks.to_be_bytes());
+        data.extend_from_slice(&[0xFF, 0xFF]);
+        data.extend_from_slice(&comment_offset.to_be_bytes());
+
+        let mut save = vec![0; blocks as usize * BLOCK_SIZE];
+        let offset = comment_offset as usize;
+        save[offset..offset + 12].copy_from_slice(b"Zelda: TWW\0\0");
+        save[offset + 32..offset + 38].copy_from_slice(b"File 1");
+        save[BLOCK_SIZE - 1] = 0xAB;
+        data.extend_from_slice(&save);
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let data = file(b"GZLE01", 2, 0x10);
+        let gci = Gci::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(gci.entry.game_id.to_string(), "GZLE01");
+        assert_eq!(gci.entry.banner_flags, 0x02);
+        assert_eq!(gci.entry.filename().unwrap(), "gczelda2");
+        assert_eq!(gci.entry.modified, 0x12345678);
+        assert_eq!(gci.entry.image_offset, 0x40);
+        assert_eq!(gci.entry.icon_formats, 0x0005);
+        assert_eq!(gci.entry.animation_speed, 0x000A);
+        assert_eq!(gci.entry.permissions, 0x04);
+        assert_eq!(gci.entry.copy_counter, 0x01);
+        assert_eq!(gci.entry.first_block, 5);
+        assert_eq!(gci.entry.block_count, 2);
+        assert_eq!(gci.entry.comment_offset, 0x10);
+        assert_eq!(gci.data.len(), 2 * BLOCK_SIZE);
+        assert_eq!(gci.data[BLOCK_SIZE - 1], 0xAB);
+        assert_eq!(gci.comments().unwrap(), ["Zelda: TWW", "File 1"]);
+
+        let mut output = Vec::new();
+        gci.to_binary(&mut output).unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn windows_1252_comments() {
+        let mut data = file(b"GPXE01", 1, 0x10);
+        data[0x50..0x5C].copy_from_slice(b"Pok\xE9mon XD\0\0");
+        let gci = Gci::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(gci.entry.encoding(), Encoding::Windows1252);
+        assert_eq!(gci.comments().unwrap(), ["Pokémon XD", "File 1"]);
+
+        let japan = file(b"GZLJ01", 1, 0x10);
+        let gci = Gci::from_binary(&mut Cursor::new(&japan)).unwrap();
+        assert_eq!(gci.entry.encoding(), Encoding::ShiftJis1997);
+    }
+
+    #[test]
+    fn banner_and_icons() {
+        let mut gci = Gci::from_binary(&mut Cursor::new(file(b"GZLE01", 2, 0x10))).unwrap();
+        let fill = |data: &mut [u8], value: &[u8]| {
+            data.chunks_exact_mut(value.len())
+                .for_each(|x| x.copy_from_slice(value))
+        };
+
+        // RGB5A3 banner (white), icons: CI8 with the shared palette (blue),
+        // RGB5A3 (green), CI8 with its own palette (red), and an icon after
+        // the end of the animation
+        let mut offset = 0x40;
+        fill(&mut gci.data[offset..offset + 96 * 32 * 2], &[0xFF, 0xFF]);
+        offset += 96 * 32 * 2;
+        fill(&mut gci.data[offset..offset + 32 * 32], &[1]);
+        offset += 32 * 32;
+        fill(&mut gci.data[offset..offset + 32 * 32 * 2], &[0x83, 0xE0]);
+        offset += 32 * 32 * 2;
+        fill(&mut gci.data[offset..offset + 32 * 32], &[1]);
+        gci.data[offset + 32 * 32 + 2..offset + 32 * 32 + 4].copy_from_slice(&[0xFC, 0x00]);
+        offset += 32 * 32 + 0x200 + 32 * 32 * 2;
+        gci.data[offset + 2..offset + 4].copy_from_slice(&[0x80, 0x1F]);
+
+        gci.entry.banner_flags = 0x06;
+        gci.entry.icon_formats = 0b10_11_10_01;
+        gci.entry.animation_speed = 0b00_11_10_01;
+        assert_eq!(gci.entry.banner_format(), BannerFormat::Rgb5a3);
+        assert_eq!(gci.entry.animation(), Animation::PingPong);
+        assert_eq!(gci.entry.icon_format(0), IconFormat::Ci8Shared);
+        assert_eq!(gci.entry.icon_format(2), IconFormat::Ci8);
+        assert_eq!(gci.entry.icon_format(4), IconFormat::None);
+        assert_eq!(gci.entry.icon_speed(1), 2);
+
+        let banner = gci.banner().unwrap().unwrap();
+        assert_eq!(banner.len(), 96 * 32 * 4);
+        assert_eq!(banner[..4], [0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let icons = gci.icons().unwrap();
+        assert_eq!(icons.len(), 3);
+        assert_eq!(icons[0].image[..4], [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(icons[0].duration, 4);
+        assert_eq!(icons[1].image[32 * 32 * 4 - 4..], [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(icons[1].duration, 8);
+        assert_eq!(icons[2].image[..4], [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(icons[2].duration, 12);
+
+        // no banner, icons outside of the save data
+        gci.entry.banner_flags = 0;
+        assert!(gci.banner().unwrap().is_none());
+        gci.entry.image_offset = 2 * BLOCK_SIZE as u32 - 0x100;
+        assert!(gci.icons().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        // truncated save data
+        let data = file(b"GZLE01", 2, 0x10);
+        assert!(Gci::from_binary(&mut Cursor::new(&data[..data.len() - 1])).is_err());
+
+        // comments outside of the save data
+        let mut gci = Gci::from_binary(&mut Cursor::new(&data)).unwrap();
+        gci.entry.comment_offset = 2 * BLOCK_SIZE as u32 - 0x20;
+        assert!(gci.comments().is_err());
+
+        // data does not match the number of blocks
+        gci.entry.block_count = 1;
+        assert!(gci.to_binary(&mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod apploader {
+    use std::io::Cursor;
+
+    use picori::gcm::Apploader;
+    use picori::Gcm;
+
+    use super::super::disc;
+
+    #[test]
+    fn extract() {
+        let data = disc();
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        let apploader = gcm.apploader();
+        assert_eq!(apploader.date, "2002/01/01");
+        assert_eq!(apploader.entry_point, 0x81200000);
+        assert_eq!(apploader.body(), &[0xAA; 0x20]);
+        assert_eq!(apploader.trailer(), &[0xBB; 0x10]);
+        assert_eq!(apploader.total_size(), 0x50);
+
+        let mut output = Vec::new();
+        gcm.extract_apploader(&mut output).unwrap();
+        assert_eq!(output, data[0x2440..0x2490]);
+    }
+
+    #[test]
+    fn replace() {
+        let mut disc = Cursor::new(disc());
+        let mut gcm = Gcm::from_binary(&mut disc).unwrap();
+        let apploader = Apploader {
+            date:         "2022/12/24".to_string(),
+            entry_point:  0x81200100,
+            size:         0x40,
+            trailer_size: 0,
+            unknown:      0,
+            data:         vec![0xDD; 0x40],
+        };
+        gcm.replace_apploader(&mut disc, apploader.clone()).unwrap();
+        assert_eq!(gcm.apploader(), &apploader);
+
+        disc.set_position(0);
+        let gcm = Gcm::from_binary(&mut disc).unwrap();
+        assert_eq!(gcm.apploader(), &apploader);
+        assert_eq!(gcm.executable().data().len(), 0x120);
+    }
+
+    #[test]
+    fn replace_too_large() {
+        let mut disc = Cursor::new(disc());
+        let mut gcm = Gcm::from_binary(&mut disc).unwrap();
+        let apploader = Apploader {
+            size: 0x400,
+            data: vec![0xDD; 0x400],
+            ..Default::default()
+        };
+        assert!(gcm.replace_apploader(&mut disc, apploader).is_err());
+        assert_eq!(gcm.apploader().body(), &[0xAA; 0x20]);
+    }
+}
@@ -1,4 +1,42 @@
 #[cfg(test)]
 mod bi2 {
-   
+    use std::io::Cursor;
+
+    use picori::gcm::{Bi2, Bi2Options, Region};
+
+    #[test]
+    fn ok() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x00..0x04].copy_from_slice(&0x00000000_u32.to_be_bytes());
+        data[0x04..0x08].copy_from_slice(&0x01800000_u32.to_be_bytes());
+        data[0x0C..0x10].copy_from_slice(&0x00000003_u32.to_be_bytes());
+        data[0x18..0x1C].copy_from_slice(&0x00000002_u32.to_be_bytes());
+        data[0x24..0x28].copy_from_slice(&0x00000001_u32.to_be_bytes());
+
+        let bi2 = Bi2::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bi2.debug_monitor_size(), 0);
+        assert_eq!(bi2.simulated_memory_size(), 0x01800000);
+        assert_eq!(bi2.argument_offset(), 0);
+        assert!(bi2.debug_flag());
+        assert_eq!(bi2.country_code(), 2);
+        assert_eq!(bi2.region(), Region::Europe);
+        assert!(bi2.long_filename_support());
+        assert_eq!(bi2.get(Bi2Options::CountryCode), Some(&2));
+
+        let mut output = Vec::new();
+        bi2.to_binary(&mut Cursor::new(&mut output)).unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn region() {
+        let mut bi2 = Bi2::default();
+        assert_eq!(bi2.region(), Region::Japan);
+        bi2.set(Bi2Options::CountryCode, 1);
+        assert_eq!(bi2.region(), Region::America);
+        bi2.set(Bi2Options::CountryCode, 4);
+        assert_eq!(bi2.region(), Region::Korea);
+        bi2.set(Bi2Options::CountryCode, 9);
+        assert_eq!(bi2.region(), Region::Unknown(9));
+    }
 }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod boot {
-    use picori::gcm::{self, Boot};
+    use picori::gcm::{self, Boot, GameId, Region};
 
     #[test]
     fn ok() {
@@ -110,4 +110,42 @@ mod boot {
 
         assert_eq!(output, data);
     }
+
+    #[test]
+    fn game_id() {
+        let id = "GZLE01".parse::<GameId>().unwrap();
+        assert_eq!(id.console_code, b'G');
+        assert_eq!(id.game_code, *b"ZL");
+        assert_eq!(id.country_code, b'E');
+        assert_eq!(id.maker_code, *b"01");
+        assert_eq!(id.region(), Region::America);
+        assert!(id.is_ntsc());
+        assert!(!id.is_pal());
+        assert_eq!(id.short(), *b"GZLE");
+        assert_eq!(id.to_string(), "GZLE01");
+        assert_eq!(GameId::from_bytes(id.to_bytes()), id);
+
+        assert!("GZLP01".parse::<GameId>().unwrap().is_pal());
+        assert_eq!(
+            "GZLJ01".parse::<GameId>().unwrap().region(),
+            Region::Japan
+        );
+        assert_eq!(
+            GameId::from_bytes(*b"GZL\x0001").to_string(),
+            "GZL\\x0001"
+        );
+        assert_eq!(Region::from_country_code(b'U'), Region::Australia);
+        assert_eq!(Region::from_country_code(0), Region::Unknown(0));
+        assert!("GZLE0".parse::<GameId>().is_err());
+        assert!("GZLE01X".parse::<GameId>().is_err());
+
+        let mut data = vec![0u8; 0x440];
+        data[..6].copy_from_slice(b"GZLE01");
+        data[0x1C..0x20].copy_from_slice(&0xC2339F3D_u32.to_be_bytes());
+        let mut boot = Boot::from_binary(&mut std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(boot.game_id(), id);
+        boot.set_game_id("RSBP01".parse().unwrap());
+        assert_eq!(boot.game_id().to_string(), "RSBP01");
+        assert_eq!(boot.country_code, b'P');
+    }
 }
@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod builder {
+    use std::io::Cursor;
+
+    use picori::gcm::{fst, GcmBuilder};
+    use picori::{Encoding, Gcm};
+
+    use super::super::disc;
+
+    fn rebuild(builder: &GcmBuilder) -> (Gcm, Cursor<Vec<u8>>) {
+        let mut output = Cursor::new(Vec::new());
+        builder
+            .build_with_source(&mut Cursor::new(disc()), &mut output)
+            .unwrap();
+        output.set_position(0);
+        let gcm = Gcm::from_binary(&mut output).unwrap();
+        (gcm, output)
+    }
+
+    fn offset(gcm: &Gcm, path: &str) -> u64 {
+        match gcm.fst().find(path) {
+            Some(fst::Entry::File { offset, .. }) => offset,
+            _ => panic!("missing file: {}", path),
+        }
+    }
+
+    #[test]
+    fn rebuild_disc() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.add_file("new/c.txt", b"new file".to_vec());
+        let (gcm, mut output) = rebuild(&builder);
+
+        assert_eq!(gcm.boot().game_name, "Test Disc");
+        assert_eq!(gcm.apploader(), original.apploader());
+        assert_eq!(gcm.executable().data(), original.executable().data());
+        let files = gcm
+            .fst()
+            .files()
+            .map(|(path, _)| path.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![
+            "",
+            "a.bin",
+            "dir",
+            "dir/b.arc",
+            "new",
+            "new/c.txt"
+        ]);
+        assert_eq!(
+            gcm.file_data(&mut output, "a.bin").unwrap(),
+            Some(b"a.bin file data\0".to_vec())
+        );
+        assert_eq!(
+            gcm.file_data(&mut output, "dir/b.arc").unwrap(),
+            Some(vec![0xCC; 0x20])
+        );
+        assert_eq!(
+            gcm.file_data(&mut output, "new/c.txt").unwrap(),
+            Some(b"new file".to_vec())
+        );
+
+        // files are packed after the fst
+        assert!(offset(&gcm, "a.bin") < 0x8000);
+        assert_eq!(offset(&gcm, "dir/b.arc") % 4, 0);
+    }
+
+    #[test]
+    fn shift_jis_names() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.boot_mut().country_code = b'J';
+        builder.boot_mut().game_name = "ゼルダの伝説".to_string();
+        builder.add_file("ムービー/オープニング.thp", b"movie".to_vec());
+        let (gcm, mut output) = rebuild(&builder);
+
+        assert_eq!(gcm.boot().game_name, "ゼルダの伝説");
+        assert_eq!(
+            gcm.file_data(&mut output, "ムービー/オープニング.thp").unwrap(),
+            Some(b"movie".to_vec())
+        );
+
+        // the rebuilt disc rebuilds to the same image
+        let rebuilt = output.into_inner();
+        let mut again = Cursor::new(Vec::new());
+        GcmBuilder::from_gcm(&gcm)
+            .build_with_source(&mut Cursor::new(&rebuilt), &mut again)
+            .unwrap();
+        assert_eq!(again.into_inner(), rebuilt);
+
+        // names that the encoding of the region can not encode
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.add_file("オープニング.thp", b"movie".to_vec());
+        let mut output = Cursor::new(Vec::new());
+        assert!(builder
+            .build_with_source(&mut Cursor::new(disc()), &mut output)
+            .is_err());
+        builder.encoding(Encoding::ShiftJis1997);
+        builder
+            .build_with_source(&mut Cursor::new(disc()), &mut output)
+            .unwrap();
+        output.set_position(0);
+        let gcm = Gcm::from_binary_with_encoding(&mut output, Some(Encoding::ShiftJis1997)).unwrap();
+        assert!(gcm.fst().find("オープニング.thp").is_some());
+    }
+
+    #[test]
+    fn extension_alignment() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.extension_alignment(".ARC", 0x8000);
+        let (gcm, mut output) = rebuild(&builder);
+        assert_eq!(offset(&gcm, "dir/b.arc"), 0x8000);
+        assert_eq!(
+            gcm.file_data(&mut output, "dir/b.arc").unwrap(),
+            Some(vec![0xCC; 0x20])
+        );
+
+        builder.extension_alignment("arc", 0x3000);
+        assert!(builder
+            .build_with_source(&mut Cursor::new(disc()), &mut Cursor::new(Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn preserve_offsets() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.preserve_offsets("arc");
+        builder.add_file("c.bin", vec![0x11; 0x8000]);
+        let (gcm, mut output) = rebuild(&builder);
+        assert_eq!(offset(&gcm, "dir/b.arc"), 0x9000);
+        assert_eq!(
+            gcm.file_data(&mut output, "dir/b.arc").unwrap(),
+            Some(vec![0xCC; 0x20])
+        );
+
+        // c.bin does not fit before dir/b.arc and is placed after it
+        assert_eq!(offset(&gcm, "c.bin"), 0x9020);
+        assert_eq!(
+            gcm.file_data(&mut output, "c.bin").unwrap(),
+            Some(vec![0x11; 0x8000])
+        );
+    }
+
+    #[test]
+    fn build_without_source() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        assert!(builder.build(&mut Cursor::new(Vec::new())).is_err());
+
+        assert!(builder.remove_file("/a.bin"));
+        assert!(builder.remove_file("dir/b.arc"));
+        assert!(!builder.remove_file("dir/b.arc"));
+        builder
+            .add_file("a.bin", b"data".to_vec())
+            .disc_size(0x10000);
+        let mut output = Cursor::new(Vec::new());
+        builder.build(&mut output).unwrap();
+        assert_eq!(output.get_ref().len(), 0x10000);
+        output.set_position(0);
+        let gcm = Gcm::from_binary(&mut output).unwrap();
+        assert_eq!(
+            gcm.file_data(&mut output, "a.bin").unwrap(),
+            Some(b"data".to_vec())
+        );
+    }
+}
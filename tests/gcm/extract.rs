@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod extract {
+    use std::fs;
+    use std::io::Cursor;
+
+    use picori::gcm::{self, ExtractOptions};
+    use picori::Gcm;
+
+    use super::super::disc;
+
+    #[test]
+    fn extract_to() {
+        let root = std::env::temp_dir().join(format!("picori-extract-{}", std::process::id()));
+        let data = disc();
+        let mut reader = Cursor::new(&data);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+
+        let mut calls = Vec::new();
+        let options = ExtractOptions::default().progress(|path, done, total| {
+            calls.push((path.to_path_buf(), done, total));
+        });
+        gcm.extract_to(&mut reader, &root, options).unwrap();
+
+        assert_eq!(fs::read(root.join("sys/boot.bin")).unwrap(), data[..0x440]);
+        assert_eq!(
+            fs::read(root.join("sys/bi2.bin")).unwrap(),
+            data[0x440..0x2440]
+        );
+        assert_eq!(
+            fs::read(root.join("sys/apploader.img")).unwrap(),
+            data[0x2440..0x2490]
+        );
+        assert_eq!(
+            fs::read(root.join("sys/main.dol")).unwrap(),
+            gcm.executable().data()
+        );
+        assert_eq!(
+            fs::read(root.join("sys/fst.bin")).unwrap(),
+            data[0x3000..0x3040]
+        );
+        assert_eq!(
+            fs::read(root.join("files/a.bin")).unwrap(),
+            b"a.bin file data\0"
+        );
+        assert_eq!(
+            fs::read(root.join("files/dir/b.arc")).unwrap(),
+            [0xCC; 0x20]
+        );
+
+        let total = 0x440 + 0x2000 + 0x50 + gcm.executable().data().len() as u64 + 0x40 + 0x30;
+        assert_eq!(calls.len(), 7);
+        assert_eq!(calls.last().unwrap().1, total);
+        assert!(calls.iter().all(|(_, _, x)| *x == total));
+        assert_eq!(calls[6].0, std::path::Path::new("files/dir/b.arc"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn extract_files_only() {
+        let root =
+            std::env::temp_dir().join(format!("picori-extract-files-{}", std::process::id()));
+        let data = disc();
+        let mut reader = Cursor::new(&data);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        let options = ExtractOptions::default().system(false);
+        gcm.extract_to(&mut reader, &root, options).unwrap();
+
+        assert!(!root.join("sys").exists());
+        assert!(root.join("files/a.bin").exists());
+        assert!(root.join("files/dir/b.arc").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn round_trip() {
+        let root = std::env::temp_dir().join(format!("picori-round-trip-{}", std::process::id()));
+        let data = disc();
+        let mut reader = Cursor::new(&data);
+        let original = Gcm::from_binary(&mut reader).unwrap();
+        original
+            .extract_to(&mut reader, &root, ExtractOptions::default())
+            .unwrap();
+        fs::write(root.join("files/dir/C.txt"), b"added").unwrap();
+
+        let mut output = Cursor::new(Vec::new());
+        gcm::build_from_root(&root, &mut output).unwrap();
+        output.set_position(0);
+        let gcm = Gcm::from_binary(&mut output).unwrap();
+
+        assert_eq!(gcm.boot().game_id(), original.boot().game_id());
+        assert_eq!(gcm.apploader(), original.apploader());
+        assert_eq!(gcm.executable().data(), original.executable().data());
+        let files = gcm
+            .fst()
+            .files()
+            .map(|(path, _)| path.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["", "a.bin", "dir", "dir/b.arc", "dir/C.txt"]);
+        assert_eq!(
+            gcm.file_data(&mut output, "a.bin").unwrap(),
+            Some(b"a.bin file data\0".to_vec())
+        );
+        assert_eq!(
+            gcm.file_data(&mut output, "dir/b.arc").unwrap(),
+            Some(vec![0xCC; 0x20])
+        );
+        assert_eq!(
+            gcm.file_data(&mut output, "dir/c.txt").unwrap(),
+            Some(b"added".to_vec())
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,8 +1,272 @@
+pub mod apploader;
+pub mod bi2;
 pub mod boot;
+pub mod builder;
+pub mod extract;
+pub mod vfs;
+
+pub use crate::common::put;
+
+/// Small GameCube disc image with an apploader, a main executable, and a FST
+/// with the files `a.bin` (at 0x8000) and `dir/b.arc` (at 0x9000).
+pub fn disc() -> Vec<u8> {
+    let mut data = vec![0u8; 0x10000];
+    put(&mut data, 0x000, b"GZLE01");
+    put(&mut data, 0x01C, &0xC2339F3D_u32.to_be_bytes());
+    put(&mut data, 0x020, b"Test Disc");
+    put(&mut data, 0x420, &0x2800_u32.to_be_bytes());
+    put(&mut data, 0x424, &0x3000_u32.to_be_bytes());
+    put(&mut data, 0x428, &0x40_u32.to_be_bytes());
+    put(&mut data, 0x42C, &0x40_u32.to_be_bytes());
+
+    // apploader.img
+    put(&mut data, 0x2440, b"2002/01/01");
+    put(&mut data, 0x2450, &0x81200000_u32.to_be_bytes());
+    put(&mut data, 0x2454, &0x20_u32.to_be_bytes());
+    put(&mut data, 0x2458, &0x10_u32.to_be_bytes());
+    put(&mut data, 0x2460, &[0xAA; 0x20]);
+    put(&mut data, 0x2480, &[0xBB; 0x10]);
+
+    // main.dol with a single text section
+    put(&mut data, 0x2800, &0x100_u32.to_be_bytes());
+    put(&mut data, 0x2848, &0x80003100_u32.to_be_bytes());
+    put(&mut data, 0x2890, &0x20_u32.to_be_bytes());
+    put(&mut data, 0x28E0, &0x80003100_u32.to_be_bytes());
+    put(&mut data, 0x2900, &[0x60; 0x20]);
+
+    // fst.bin
+    put(&mut data, 0x3000, &0x01000000_u32.to_be_bytes());
+    put(&mut data, 0x3008, &4_u32.to_be_bytes());
+    put(&mut data, 0x300C, &0x00000000_u32.to_be_bytes());
+    put(&mut data, 0x3010, &0x8000_u32.to_be_bytes());
+    put(&mut data, 0x3014, &0x10_u32.to_be_bytes());
+    put(&mut data, 0x3018, &0x01000006_u32.to_be_bytes());
+    put(&mut data, 0x301C, &0_u32.to_be_bytes());
+    put(&mut data, 0x3020, &4_u32.to_be_bytes());
+    put(&mut data, 0x3024, &0x0000000A_u32.to_be_bytes());
+    put(&mut data, 0x3028, &0x9000_u32.to_be_bytes());
+    put(&mut data, 0x302C, &0x20_u32.to_be_bytes());
+    put(&mut data, 0x3030, b"a.bin\0dir\0b.arc\0");
+
+    put(&mut data, 0x8000, b"a.bin file data\0");
+    put(&mut data, 0x9000, &[0xCC; 0x20]);
+    data
+}
 
 #[cfg(test)]
 mod gcm {
+    use std::io::Cursor;
+
+    use picori::bnr::{self, Version};
+    use picori::gcm::{fst, GcmBuilder};
+    use picori::wbfs::{WbfsReader, WbfsWriter};
+    use picori::{Encoding, Gcm};
+
+    use super::{disc, put};
+
+    #[test]
+    fn ok() {
+        let gcm = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let files = gcm
+            .fst()
+            .files()
+            .map(|(path, _)| path.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["", "a.bin", "dir", "dir/b.arc"]);
+    }
+
+    #[test]
+    fn from_disc() {
+        let mut wbfs = Cursor::new(Vec::new());
+        let mut writer = WbfsWriter::new(&mut wbfs, 0x40000).unwrap();
+        writer.write_data(&disc()).unwrap();
+        writer.finish().unwrap();
+
+        wbfs.set_position(0);
+        let reader = WbfsReader::new(&mut wbfs).unwrap();
+        let gcm = Gcm::from_disc(reader).unwrap();
+        assert_eq!(gcm.boot().game_name, "Test Disc");
+        assert!(gcm.fst().find("dir/b.arc").is_some());
+    }
+
+    #[test]
+    fn shift_jis_names() {
+        // rename a.bin to "あbin"
+        let mut data = disc();
+        put(&mut data, 0x3030, b"\x82\xA0bin");
+        assert!(Gcm::from_binary(&mut Cursor::new(&data)).is_err());
+
+        let gcm =
+            Gcm::from_binary_with_encoding(&mut Cursor::new(&data), Some(Encoding::ShiftJis1997))
+                .unwrap();
+        assert!(gcm.fst().find("あbin").is_some());
+
+        // japanese games default to shift jis
+        put(&mut data, 0x003, b"J");
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(gcm.fst().find("あbin").is_some());
+        assert!(gcm.fst().find("dir/b.arc").is_some());
+    }
+
+    #[test]
+    fn main_dol() {
+        let data = disc();
+        let dol = Gcm::main_dol(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(dol.entry_point(), 0x80003100);
+        let section = dol.section_by_address(0x80003100).unwrap();
+        assert_eq!(section.data, [0x60; 0x20]);
+
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        let other = gcm.executable().dol().unwrap();
+        assert_eq!(other.entry_point(), dol.entry_point());
+        assert_eq!(other.sections.len(), dol.sections.len());
+    }
+
+    #[test]
+    fn find() {
+        let gcm = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let fst = gcm.fst();
+        assert!(matches!(
+            fst.find("a.bin"),
+            Some(fst::Entry::File { offset: 0x8000, .. })
+        ));
+        assert!(matches!(
+            fst.find("/DIR/B.ARC"),
+            Some(fst::Entry::File { offset: 0x9000, .. })
+        ));
+        assert!(matches!(
+            fst.find("dir"),
+            Some(fst::Entry::Directory { .. })
+        ));
+        assert_eq!(fst.find("b.arc"), None);
+        assert_eq!(fst.find("dir/b.arc/c"), None);
+    }
+
+    #[test]
+    fn banner() {
+        let mut data = disc();
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(gcm.banner(&mut Cursor::new(&data)).unwrap().is_none());
+        assert_eq!(
+            gcm.file_data(&mut Cursor::new(&data), "a.bin").unwrap(),
+            Some(b"a.bin file data\0".to_vec())
+        );
+
+        // rename a.bin to opening.bnr, the data is not a banner
+        put(&mut data, 0x428, &0x46_u32.to_be_bytes());
+        put(&mut data, 0x3018, &0x0100000C_u32.to_be_bytes());
+        put(&mut data, 0x3024, &0x00000010_u32.to_be_bytes());
+        put(&mut data, 0x3030, b"opening.bnr\0dir\0b.arc\0");
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(gcm.banner(&mut Cursor::new(&data)).is_err());
+
+        // banner at 0xA000 (the game is American, the text is Windows-1252)
+        put(&mut data, 0x3010, &0xA000_u32.to_be_bytes());
+        put(&mut data, 0x3014, &(bnr::BNR1_SIZE as u32).to_be_bytes());
+        put(&mut data, 0xA000, b"BNR1");
+        put(&mut data, 0xA000 + 0x1820, b"Caf\xE9");
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        let banner = gcm.banner(&mut Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(banner.version, Version::Bnr1);
+        assert_eq!(banner.description().unwrap().name, "Café");
+    }
+
+    #[test]
+    fn glob() {
+        let gcm = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let fst = gcm.fst();
+        let paths = |entries: Vec<(std::path::PathBuf, fst::Entry)>| {
+            entries
+                .into_iter()
+                .map(|(path, _)| path.to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        // the example of the documentation
+        assert_eq!(paths(fst.glob("**/*.arc")), vec!["dir/b.arc"]);
+        assert_eq!(paths(fst.glob("/dir/*")), vec!["dir/b.arc"]);
+        assert_eq!(paths(fst.glob("*")), vec!["a.bin", "dir"]);
+        assert_eq!(paths(fst.glob("**")), vec!["a.bin", "dir", "dir/b.arc"]);
+        assert_eq!(paths(fst.glob("?.BIN")), vec!["a.bin"]);
+        assert_eq!(paths(fst.glob("dir/**/b.arc")), vec!["dir/b.arc"]);
+        assert!(fst.glob("*.arc").is_empty());
+
+        assert_eq!(paths(fst.find_by_extension(".arc")), vec!["dir/b.arc"]);
+        assert_eq!(paths(fst.find_by_extension("BIN")), vec!["a.bin"]);
+        assert!(fst.find_by_extension("bti").is_empty());
+    }
+
+    #[test]
+    fn glob_characters() {
+        let original = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let mut builder = GcmBuilder::from_gcm(&original);
+        builder.boot_mut().country_code = b'J';
+        builder.add_file("ムービー/オープニング.thp", b"movie".to_vec());
+        let name = "a".repeat(64);
+        builder.add_file(&name, b"name".to_vec());
+        let mut output = Cursor::new(Vec::new());
+        builder
+            .build_with_source(&mut Cursor::new(disc()), &mut output)
+            .unwrap();
+        output.set_position(0);
+        let gcm = Gcm::from_binary(&mut output).unwrap();
+        let fst = gcm.fst();
+
+        // `?` matches a single (multibyte) character
+        assert_eq!(fst.glob("*/??????.thp").len(), 1);
+        assert_eq!(fst.glob("????/*.thp").len(), 1);
+        assert!(fst.glob("???/*.thp").is_empty());
+
+        // patterns that backtrack a lot still finish quickly
+        let pattern = "*a".repeat(32) + "b";
+        assert!(fst.glob(&pattern).is_empty());
+        assert_eq!(fst.glob(&("*a".repeat(32) + "*")).len(), 1);
+        let pattern = "**/".repeat(32) + "b";
+        assert!(fst.glob(&pattern).is_empty());
+    }
+
+    #[test]
+    fn replace_file() {
+        let mut disc = Cursor::new(disc());
+        let mut gcm = Gcm::from_binary(&mut disc).unwrap();
+
+        // smaller than the original
+        gcm.replace_file(&mut disc, "a.bin", b"small").unwrap();
+        assert_eq!(
+            disc.get_ref()[0x8000..0x8010],
+            *b"small\0\0\0\0\0\0\0\0\0\0\0"
+        );
+
+        // larger than the original, but fits before dir/b.arc
+        let data = vec![0x11; 0x1000];
+        gcm.replace_file(&mut disc, "a.bin", &data).unwrap();
+        assert_eq!(
+            gcm.file_data(&mut disc, "a.bin").unwrap().as_deref(),
+            Some(&data[..])
+        );
+
+        disc.set_position(0);
+        let gcm = Gcm::from_binary(&mut disc).unwrap();
+        assert!(matches!(
+            gcm.fst().find("a.bin"),
+            Some(fst::Entry::File { size: 0x1000, .. })
+        ));
+        assert_eq!(
+            gcm.file_data(&mut disc, "dir/b.arc").unwrap(),
+            Some(vec![0xCC; 0x20])
+        );
+    }
 
     #[test]
-    fn ok() {}
+    fn replace_file_too_large() {
+        let mut disc = Cursor::new(disc());
+        let mut gcm = Gcm::from_binary(&mut disc).unwrap();
+        assert!(gcm
+            .replace_file(&mut disc, "a.bin", &[0x11; 0x1001])
+            .is_err());
+        assert!(gcm
+            .replace_file(&mut disc, "dir/b.arc", &[0x11; 0x21])
+            .is_err());
+        assert!(gcm.replace_file(&mut disc, "missing", &[]).is_err());
+        assert_eq!(disc.get_ref()[..], super::disc()[..]);
+    }
 }
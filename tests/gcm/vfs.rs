@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod vfs {
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+
+    use picori::vfs::{self, GcmFs, Metadata, OpenOptions, Vfs};
+    use picori::{RarcBuilder, U8Builder};
+
+    use crate::gcm::{disc, put};
+
+    /// Yaz0 "compress" `data` using only literal bytes.
+    fn yaz0(data: &[u8]) -> Vec<u8> {
+        let mut output = b"Yaz0".to_vec();
+        output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        output.extend_from_slice(&[0; 8]);
+        for chunk in data.chunks(8) {
+            output.push(0xFF);
+            output.extend_from_slice(chunk);
+        }
+        output
+    }
+
+    /// Disc with a U8 archive (`x.txt` and `y/z.txt`) as `dir/b.arc`.
+    fn nested(compressed: bool) -> Vec<u8> {
+        let mut builder = U8Builder::new();
+        builder.add_file("x.txt", b"x".to_vec());
+        builder.add_file("y/z.txt", b"zz".to_vec());
+        let mut archive = Cursor::new(Vec::new());
+        builder.build(&mut archive).unwrap();
+        let mut archive = archive.into_inner();
+        if compressed {
+            archive = yaz0(&archive);
+        }
+
+        let mut data = disc();
+        put(&mut data, 0x302C, &(archive.len() as u32).to_be_bytes());
+        put(&mut data, 0x9000, &archive);
+        data
+    }
+
+    #[test]
+    fn gcm() {
+        let mut fs = GcmFs::new(Cursor::new(disc())).unwrap();
+        let paths = fs
+            .walk()
+            .into_iter()
+            .map(|x| x.path.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["a.bin", "dir", "dir/b.arc"]);
+
+        assert_eq!(fs.stat(Path::new("/")), Some(Metadata::directory()));
+        assert_eq!(fs.stat(Path::new("/DIR")), Some(Metadata::directory()));
+        assert_eq!(fs.stat(Path::new("a.bin")), Some(Metadata::file(0x10)));
+        assert_eq!(fs.stat(Path::new("c.bin")), None);
+
+        let root = fs.list(Path::new("")).unwrap();
+        assert_eq!(root.iter().map(|x| x.name()).collect::<Vec<_>>(), [
+            "a.bin", "dir"
+        ]);
+        assert!(fs.list(Path::new("a.bin")).is_none());
+
+        let mut data = Vec::new();
+        let mut file = fs.open(Path::new("a.bin")).unwrap().unwrap();
+        file.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"a.bin file data\0");
+        assert!(fs.open(Path::new("dir")).unwrap().is_none());
+        assert!(fs.open_archive(Path::new("a.bin")).unwrap().is_none());
+    }
+
+    #[test]
+    fn nested_archive() {
+        let mut fs = GcmFs::new(Cursor::new(nested(false))).unwrap();
+        let mut archive = fs.open_archive(Path::new("dir/b.arc")).unwrap().unwrap();
+        let paths = archive
+            .walk()
+            .into_iter()
+            .map(|x| x.path.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["x.txt", "y", "y/z.txt"]);
+        assert_eq!(archive.stat(Path::new("y/z.txt")), Some(Metadata::file(2)));
+        assert_eq!(
+            archive.read(Path::new("y/z.txt")).unwrap(),
+            Some(b"zz".to_vec())
+        );
+    }
+
+    #[test]
+    fn auto_decompress() {
+        let mut fs = GcmFs::new(Cursor::new(nested(true))).unwrap();
+        let path = Path::new("dir/b.arc");
+        assert!(fs.open_archive(path).unwrap().is_none());
+
+        let options = OpenOptions::new().auto_decompress(true);
+        let data = fs.read_with(path, &options).unwrap().unwrap();
+        assert_eq!(&data[..4], b"\x55\xAA\x38\x2D");
+        let mut archive = fs.open_archive_with(path, &options).unwrap().unwrap();
+        assert_eq!(
+            archive.read(Path::new("x.txt")).unwrap(),
+            Some(b"x".to_vec())
+        );
+
+        let compressed = fs.read(path).unwrap().unwrap();
+        assert!(vfs::open_archive(compressed.clone()).unwrap().is_none());
+        assert!(vfs::open_archive_with(compressed, &options)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn walk_nested() {
+        // dir/b.arc (Yaz0) -> inner.arc (RARC) -> r.bin
+        let mut rarc = RarcBuilder::new("inner");
+        rarc.add_file("r.bin", b"rarc".to_vec());
+        let mut inner = Cursor::new(Vec::new());
+        rarc.build(&mut inner).unwrap();
+        let mut u8arc = U8Builder::new();
+        u8arc.add_file("inner.arc", inner.into_inner());
+        u8arc.add_file("x.txt", b"x".to_vec());
+        let mut archive = Cursor::new(Vec::new());
+        u8arc.build(&mut archive).unwrap();
+        let archive = yaz0(&archive.into_inner());
+
+        let mut data = disc();
+        put(&mut data, 0x302C, &(archive.len() as u32).to_be_bytes());
+        put(&mut data, 0x9000, &archive);
+        let mut fs = GcmFs::new(Cursor::new(data)).unwrap();
+
+        let entries = vfs::walk_nested(&mut fs, &OpenOptions::new()).unwrap();
+        let paths = entries.iter().map(|x| x.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["a.bin", "dir", "dir/b.arc"]);
+
+        let options = OpenOptions::new().auto_decompress(true);
+        let entries = vfs::walk_nested(&mut fs, &options).unwrap();
+        let paths = entries
+            .iter()
+            .map(|x| (x.path.as_str(), x.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [
+            ("a.bin", 0),
+            ("dir", 0),
+            ("dir/b.arc", 0),
+            ("dir/b.arc!/inner.arc", 1),
+            ("dir/b.arc!/inner.arc!/r.bin", 2),
+            ("dir/b.arc!/x.txt", 1),
+        ]);
+
+        let path = "dir/b.arc!/inner.arc!/r.bin";
+        assert_eq!(
+            vfs::read_nested(&mut fs, path, &options).unwrap(),
+            Some(b"rarc".to_vec())
+        );
+        assert_eq!(
+            vfs::read_nested(&mut fs, "dir/b.arc!/y.txt", &options).unwrap(),
+            None
+        );
+    }
+}
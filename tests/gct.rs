@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod gct {
+    use std::io::Cursor;
+
+    use picori::gct::{Code, Gct, Line, RamWrite};
+
+    fn codes() -> Vec<Code> {
+        vec![
+            Code {
+                name:  "Infinite Health".to_string(),
+                lines: vec![Line::new(0x043CA594, 0x00500050)],
+            },
+            Code {
+                name:  "Moon Jump".to_string(),
+                lines: vec![
+                    Line::new(0x003CA600, 0x000200FF),
+                    Line::new(0x023CA610, 0x00011234),
+                    Line::new(0x053CA620, 0x0000000A),
+                    Line::new(0x063CA630, 0x0000000A),
+                    Line::new(0x48656C6C, 0x6F2C2057),
+                    Line::new(0x6F000000, 0x00000000),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        let gct = Gct::from_codes(&codes());
+        assert_eq!(gct.lines.len(), 7);
+        let mut data = Vec::new();
+        gct.to_binary(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + 7 * 8 + 8);
+        assert_eq!(&data[..8], &[
+            0x00, 0xD0, 0xC0, 0xDE, 0x00, 0xD0, 0xC0, 0xDE
+        ]);
+        assert_eq!(&data[data.len() - 8..], &[0xF0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let parsed = Gct::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(parsed, gct);
+        assert_eq!(parsed.lines[0].kind(), 0x04);
+        assert_eq!(parsed.lines[3].kind(), 0x04);
+        assert_eq!(parsed.lines[3].offset(), 0x013CA620);
+    }
+
+    #[test]
+    fn writes() {
+        let writes = Gct::from_codes(&codes()).writes().unwrap();
+        assert_eq!(writes, vec![
+            RamWrite {
+                address: 0x803CA594,
+                data:    vec![0x00, 0x50, 0x00, 0x50],
+            },
+            RamWrite {
+                address: 0x803CA600,
+                data:    vec![0xFF; 3],
+            },
+            RamWrite {
+                address: 0x803CA610,
+                data:    vec![0x12, 0x34, 0x12, 0x34],
+            },
+            RamWrite {
+                address: 0x813CA620,
+                data:    vec![0, 0, 0, 0x0A],
+            },
+            RamWrite {
+                address: 0x803CA630,
+                data:    b"Hello, Wo\0".to_vec(),
+            },
+        ]);
+
+        // conditional codes depend on the state of the game
+        let gct = Gct {
+            lines: vec![Line::new(0x203CA594, 0x00500050), Line::new(0xE2000001, 0)],
+        };
+        assert!(gct.writes().is_err());
+
+        // truncated string write
+        let gct = Gct {
+            lines: vec![Line::new(0x063CA630, 0x00000010), Line::new(0, 0)],
+        };
+        assert!(gct.writes().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = Vec::new();
+        Gct::from_codes(&codes()).to_binary(&mut data).unwrap();
+
+        // invalid magic
+        let mut invalid = data.clone();
+        invalid[3] = 0xDF;
+        assert!(Gct::from_binary(&mut Cursor::new(invalid)).is_err());
+
+        // missing terminator
+        let truncated = &data[..data.len() - 8];
+        assert!(Gct::from_binary(&mut Cursor::new(truncated)).is_err());
+    }
+}
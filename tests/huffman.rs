@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod huffman {
+    use std::io::Cursor;
+
+    use picori::huffman::{self, is_huffman};
+
+    /// Huffman compress `data` with 4-bit symbols and a balanced tree, i.e.,
+    /// the code of each symbol is its value.
+    fn compress4(data: &[u8]) -> Vec<u8> {
+        let mut output = vec![0x24];
+        output.extend_from_slice(&(data.len() as u32).to_le_bytes()[..3]);
+
+        // node p (level order, starting at 1) has its children at 2p and 2p+1
+        let mut tree = vec![15];
+        for p in 1..16_usize {
+            let offset = (2 * p - (p & !1) - 2) / 2;
+            let leaves = if p >= 8 { 0xC0 } else { 0 };
+            tree.push((leaves | offset) as u8);
+        }
+        tree.extend(0..16);
+        output.extend(tree);
+
+        let symbols = data.iter().flat_map(|x| [x & 0xF, x >> 4]);
+        let bits = symbols.flat_map(|x| (0..4).rev().map(move |i| (x >> i) & 1));
+        let bits = bits.collect::<Vec<_>>();
+        for chunk in bits.chunks(32) {
+            let word = chunk
+                .iter()
+                .enumerate()
+                .fold(0_u32, |word, (i, bit)| word | (*bit as u32) << (31 - i));
+            output.extend_from_slice(&word.to_le_bytes());
+        }
+        output
+    }
+
+    #[test]
+    fn decompress8() {
+        let data: &[u8] = &[
+            0x28, 0x05, 0x00, 0x00, // type = 0x28, size = 5
+            0x03, // tree size = (3 + 1) * 2
+            0x80, b'a', // root: leaf 'a' (0) and node (1)
+            0xC0, b'b', b'c', // node: leaf 'b' (10) and leaf 'c' (11)
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x5A, // bits = 0 10 11 0 10
+        ];
+
+        let mut cursor = Cursor::new(data);
+        assert!(is_huffman(&mut cursor));
+        let header = huffman::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.symbol_bits(), 8);
+        assert_eq!(huffman::decompress(&mut cursor, &header).unwrap(), b"abcab");
+    }
+
+    #[test]
+    fn decompress4() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output");
+        let compressed = compress4(data);
+        let mut cursor = Cursor::new(&compressed);
+        assert!(is_huffman(&mut cursor));
+        let header = huffman::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.symbol_bits(), 4);
+        assert!(huffman::decompress(&mut cursor, &header).unwrap() == data);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut cursor = Cursor::new(&[0x10, 0x01, 0x00, 0x00]);
+        assert!(!is_huffman(&mut cursor));
+        let header = huffman::Header::from_binary(&mut cursor).unwrap();
+        assert!(huffman::decompress(&mut cursor, &header).is_err());
+
+        // the root points past the end of the tree
+        let data: &[u8] = &[
+            0x28, 0x01, 0x00, 0x00, 0x01, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let mut cursor = Cursor::new(data);
+        let header = huffman::Header::from_binary(&mut cursor).unwrap();
+        assert!(huffman::decompress(&mut cursor, &header).is_err());
+
+        // truncated bit stream
+        let data = compress4(b"abcdef");
+        let mut cursor = Cursor::new(&data[..data.len() - 4]);
+        let header = huffman::Header::from_binary(&mut cursor).unwrap();
+        assert!(huffman::decompress(&mut cursor, &header).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod imet {
+    use std::io::Cursor;
+
+    use picori::imet::{Imd5, Imet, Language};
+
+    fn utf16(text: &str) -> Vec<u8> { text.encode_utf16().flat_map(|x| x.to_be_bytes()).collect() }
+
+    fn header() -> Vec<u8> {
+        let mut data = vec![0u8; 0x600];
+        data[0x40..0x44].copy_from_slice(b"IMET");
+        data[0x44..0x48].copy_from_slice(&0x600_u32.to_be_bytes());
+        data[0x48..0x4C].copy_from_slice(&3_u32.to_be_bytes());
+        for (i, size) in [0x1000_u32, 0x2000, 0x3000].iter().enumerate() {
+            data[0x4C + 4 * i..0x50 + 4 * i].copy_from_slice(&size.to_be_bytes());
+        }
+        let names = ["ゼルダ", "Zelda\nTwilight Princess", "Zelda"];
+        for (i, name) in names.iter().enumerate() {
+            let name = utf16(name);
+            data[0x5C + 0x54 * i..][..name.len()].copy_from_slice(&name);
+        }
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let imet = Imet::from_binary(&mut Cursor::new(header())).unwrap();
+        assert_eq!(imet.sizes, [0x1000, 0x2000, 0x3000]);
+        assert_eq!(imet.flag, 0);
+        assert_eq!(imet.name(Language::Japanese), "ゼルダ");
+        assert_eq!(imet.name(Language::English), "Zelda\nTwilight Princess");
+        assert_eq!(imet.name(Language::German), "Zelda");
+        assert_eq!(imet.name(Language::Korean), "");
+
+        let mut data = header();
+        data[0x40] = b'X';
+        assert!(Imet::from_binary(&mut Cursor::new(data)).is_err());
+        let mut data = header();
+        data[0x46] = 0x07;
+        assert!(Imet::from_binary(&mut Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn imd5() {
+        let mut data = b"IMD5".to_vec();
+        data.extend_from_slice(&4_u32.to_be_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0xAA; 16]);
+        data.extend_from_slice(b"LZ77data");
+        assert!(Imd5::is_imd5(&data));
+        let imd5 = Imd5::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(imd5.hash, [0xAA; 16]);
+        assert_eq!(imd5.data, b"LZ77");
+
+        assert!(!Imd5::is_imd5(b"LZ77"));
+        data[..4].copy_from_slice(b"IMD4");
+        assert!(Imd5::from_binary(&mut Cursor::new(&data)).is_err());
+    }
+
+    #[cfg(feature = "hash")]
+    mod hash {
+        use std::io::Cursor;
+
+        use md5::{Digest, Md5};
+        use picori::imet::{Imd5, Imet, Language};
+
+        use super::header;
+
+        #[test]
+        fn imet() {
+            let mut data = header();
+            let hash: [u8; 16] = Md5::digest(&data).into();
+            data[0x5F0..].copy_from_slice(&hash);
+            let mut imet = Imet::from_binary(&mut Cursor::new(&data)).unwrap();
+            assert_eq!(imet.hash, hash);
+            assert!(imet.is_valid());
+
+            let mut output = Vec::new();
+            imet.to_binary(&mut output).unwrap();
+            assert_eq!(output, data);
+
+            imet.names[Language::French.index()] = "Zelda".to_string();
+            assert!(!imet.is_valid());
+            let mut output = Vec::new();
+            imet.to_binary(&mut output).unwrap();
+            assert!(Imet::from_binary(&mut Cursor::new(output))
+                .unwrap()
+                .is_valid());
+
+            // names are at most 42 characters
+            imet.names[0] = "Z".repeat(43);
+            assert!(imet.to_binary(&mut Vec::new()).is_err());
+        }
+
+        #[test]
+        fn imd5() {
+            let imd5 = Imd5::new(b"LZ77data".to_vec());
+            assert!(!imd5.is_valid());
+            let mut output = Vec::new();
+            imd5.to_binary(&mut output).unwrap();
+            assert_eq!(output.len(), 0x20 + 8);
+
+            let written = Imd5::from_binary(&mut Cursor::new(&output)).unwrap();
+            assert!(written.is_valid());
+            assert_eq!(written.hash, <[u8; 16]>::from(Md5::digest(b"LZ77data")));
+            assert_eq!(written.data, imd5.data);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+mod common;
+
+#[cfg(test)]
+mod ipl {
+    use std::io::Cursor;
+
+    use picori::bnr::Language;
+    use picori::ipl::{
+        Font, FontEncoding, Ipl, SoundMode, Sram, FONT_ANSI_OFFSET, FONT_SJIS_OFFSET, IPL_SIZE,
+    };
+
+    use crate::common::{u16s, u32s};
+
+    /// Yay0 stream of literal bytes only.
+    fn yay0(data: &[u8]) -> Vec<u8> {
+        let masks = data.len().div_ceil(32);
+        let offset = 0x10 + 4 * masks as u32;
+        let mut output = b"Yay0".to_vec();
+        output.extend_from_slice(&u32s(&[data.len() as u32, offset, offset]));
+        output.extend_from_slice(&vec![0xFF; 4 * masks]);
+        output.extend_from_slice(data);
+        output
+    }
+
+    /// Font with three glyphs (`' '`, `'!'`, and `'"'`) in two sheets of two
+    /// 8×8 cells.
+    fn font_data(kind: u16) -> Vec<u8> {
+        let mut data = u16s(&[kind, 0x20, 0x22, 0x20, 7, 1, 8, 9, 8, 8]);
+        data.extend_from_slice(&u32s(&[64]));
+        data.extend_from_slice(&u16s(&[0, 2, 1, 16, 8, 0x30]));
+        data.extend_from_slice(&u32s(&[0x34, 128]));
+        data.extend_from_slice(&[0x00, 0x55, 0xAA, 0xFF]);
+        data.extend_from_slice(&[5, 6, 7, 0]);
+        let mut sheets = vec![0; 64];
+        sheets[0] = 0b00_01_10_11;
+        sheets[32] = 0xFF;
+        data.extend_from_slice(&sheets);
+        data
+    }
+
+    #[test]
+    fn font_from_binary() {
+        let font = Font::from_binary(&mut Cursor::new(font_data(0))).unwrap();
+        assert_eq!(font.encoding, FontEncoding::Ansi);
+        assert_eq!((font.first_char, font.last_char), (0x20, 0x22));
+        assert_eq!((font.ascent, font.descent, font.leading), (7, 1, 9));
+        assert_eq!(font.widths, vec![5, 6, 7]);
+        assert_eq!(font.sheets.len(), 128);
+        assert_eq!(&font.sheets[..2], &[0x05, 0xAF]);
+        assert_eq!(font.sheet_count(), 2);
+
+        assert_eq!(font.index('!' as u16), Some(1));
+        assert_eq!(font.index('A' as u16), None);
+        let glyph = font.glyph(2).unwrap();
+        assert_eq!((glyph.sheet, glyph.x, glyph.y, glyph.width), (1, 0, 0, 7));
+        assert_eq!(font.glyph(1).unwrap().x, 8);
+        assert!(font.glyph(3).is_none());
+
+        let sheet = font.sheet(0).unwrap();
+        assert_eq!(sheet.len(), 16 * 8 * 4);
+        assert_eq!(&sheet[..16], &[
+            0x00, 0x00, 0x00, 0x00, 0x55, 0x55, 0x55, 0x55, 0xAA, 0xAA, 0xAA, 0xAA, 0xFF, 0xFF,
+            0xFF, 0xFF
+        ]);
+        assert_eq!(&font.sheet(1).unwrap()[..4], &[0xFF; 4]);
+        assert!(font.sheet(2).is_err());
+
+        let sjis = Font::from_binary(&mut Cursor::new(font_data(2))).unwrap();
+        assert_eq!(sjis.encoding, FontEncoding::ShiftJis);
+        assert_eq!(sjis.widths.len(), 4);
+        assert_eq!(sjis.index(0x20), None);
+    }
+
+    #[test]
+    fn ipl_from_binary() {
+        let mut data = vec![0; IPL_SIZE];
+        let copyright = b"(C) 1999-2001 Nintendo.  All rights reserved.";
+        data[..copyright.len()].copy_from_slice(copyright);
+        let sjis = yay0(&font_data(2));
+        let ansi = yay0(&font_data(0));
+        let offset = FONT_SJIS_OFFSET as usize;
+        data[offset..offset + sjis.len()].copy_from_slice(&sjis);
+        let offset = FONT_ANSI_OFFSET as usize;
+        data[offset..offset + ansi.len()].copy_from_slice(&ansi);
+
+        let ipl = Ipl::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(ipl.copyright.as_bytes(), copyright);
+        assert_eq!(ipl.ansi_font.encoding, FontEncoding::Ansi);
+        assert_eq!(ipl.sjis_font.encoding, FontEncoding::ShiftJis);
+        assert_eq!(ipl.ansi_font.widths, vec![5, 6, 7]);
+    }
+
+    /// Default SRAM of Dolphin.
+    fn sram_data() -> Vec<u8> {
+        let mut data = vec![0xFF, 0x6B, 0x00, 0x91, 0, 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0x40, 0x00, 0x00, 0x00, 0x2C]);
+        data.resize(0x40, 0);
+        data
+    }
+
+    #[test]
+    fn sram_round_trip() {
+        let mut sram = Sram::from_binary(&mut Cursor::new(sram_data())).unwrap();
+        assert!(sram.is_valid());
+        assert_eq!(sram.counter_bias, 0xFFFFFF40);
+        assert_eq!(sram.language(), Some(Language::English));
+        assert_eq!(sram.sound_mode(), SoundMode::Stereo);
+        assert!(!sram.is_progressive());
+
+        let mut data = Vec::new();
+        sram.to_binary(&mut data).unwrap();
+        assert_eq!(data, sram_data());
+
+        sram.language = 2;
+        assert!(!sram.is_valid());
+        sram.update_checksum();
+        assert!(sram.is_valid());
+        assert_eq!(sram.language(), Some(Language::French));
+        assert_eq!((sram.checksum, sram.checksum_inverse), (0x016B, 0xFE91));
+    }
+
+    #[test]
+    fn invalid() {
+        // unknown font type
+        assert!(Font::from_binary(&mut Cursor::new(font_data(1))).is_err());
+
+        // unsupported sheet format
+        let mut data = font_data(0);
+        data[0x19] = 2;
+        assert!(Font::from_binary(&mut Cursor::new(data)).is_err());
+
+        // truncated sheets
+        let data = font_data(0);
+        assert!(Font::from_binary(&mut Cursor::new(&data[..0x50])).is_err());
+
+        // truncated SRAM
+        assert!(Sram::from_binary(&mut Cursor::new(&sram_data()[..0x20])).is_err());
+    }
+}
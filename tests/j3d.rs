@@ -0,0 +1,224 @@
+mod common;
+
+#[cfg(test)]
+mod j3d {
+    use std::io::Cursor;
+
+    use picori::j3d::{Attribute, J3d, NodeKind, PrimitiveKind, VertexValues};
+    use picori::texture::{self, Format};
+
+    use crate::common::{put, u16s, u32s};
+
+    fn chunk(magic: &[u8; 4], mut data: Vec<u8>) -> Vec<u8> {
+        data.resize(data.len().next_multiple_of(0x20), 0);
+        put(&mut data, 0, magic);
+        let size = data.len() as u32;
+        put(&mut data, 4, &size.to_be_bytes());
+        data
+    }
+
+    fn string_table(names: &[&str]) -> Vec<u8> {
+        let mut table = u16s(&[names.len() as u16, 0xFFFF]);
+        let mut strings = Vec::new();
+        for name in names {
+            let offset = 4 + 4 * names.len() + strings.len();
+            table.extend_from_slice(&u16s(&[0, offset as u16]));
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+        }
+        table.extend_from_slice(&strings);
+        table
+    }
+
+    fn information() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &u32s(&[0, 1, 4, 0x18]));
+        // joint 0 { material 0 { shape 0 } }
+        let hierarchy = [0x10, 0, 1, 0, 0x11, 0, 1, 0, 0x12, 0, 2, 0, 2, 0, 0, 0];
+        put(&mut data, 0x18, &u16s(&hierarchy));
+        chunk(b"INF1", data)
+    }
+
+    fn vertices() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        let mut offsets = [0; 13];
+        (offsets[0], offsets[3], offsets[5]) = (0x80, 0xA0, 0xC0);
+        put(&mut data, 0x08, &u32s(&[0x40]));
+        put(&mut data, 0x0C, &u32s(&offsets));
+        // position (xyz, s16, shift 8), color 0 (rgba8), texture 0 (st, f32)
+        put(&mut data, 0x40, &u32s(&[9, 1, 3, 0x08000000]));
+        put(&mut data, 0x50, &u32s(&[11, 1, 5, 0]));
+        put(&mut data, 0x60, &u32s(&[13, 1, 4, 0]));
+        put(&mut data, 0x70, &u32s(&[0xFF, 0, 0, 0]));
+        let positions = [0x100, 0, 0xFF00, 0x80, 0x200, 0, 0, 0, 0x100];
+        put(&mut data, 0x80, &u16s(&positions));
+        put(&mut data, 0xA0, &[0xFF, 0, 0, 0xFF, 0, 0xFF, 0, 0x80]);
+        let coords = [0.0_f32, 1.0, 0.5, 0.25];
+        put(&mut data, 0xC0, &u32s(&coords.map(f32::to_bits)));
+        chunk(b"VTX1", data)
+    }
+
+    fn shapes() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &u16s(&[1, 0xFFFF]));
+        let offsets = [0x40, 0x68, 0, 0x70, 0x98, 0xC0, 0xA0, 0xA8];
+        put(&mut data, 0x0C, &u32s(&offsets));
+
+        put(&mut data, 0x40, &[3, 0xFF]);
+        put(&mut data, 0x42, &u16s(&[1, 0, 0, 0, 0xFFFF]));
+        let bounds = [2.0_f32, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+        put(&mut data, 0x4C, &u32s(&bounds.map(f32::to_bits)));
+        put(&mut data, 0x68, &u16s(&[0]));
+        // matrix index (direct), position (u16), color (u8), texture (u16)
+        let attributes = [0, 1, 9, 3, 11, 2, 13, 3, 0xFF, 0];
+        put(&mut data, 0x70, &u32s(&attributes));
+        put(&mut data, 0x98, &u16s(&[7, 0xFFFF]));
+        put(&mut data, 0xA0, &u16s(&[0xFFFF, 2, 0, 0]));
+        put(&mut data, 0xA8, &u32s(&[0x20, 0]));
+
+        // triangle strip with 4 vertices
+        let mut primitive = vec![0x98, 0, 4];
+        for i in 0..4_u8 {
+            primitive.extend_from_slice(&[i * 3, 0, i % 3, i % 2, 0, i]);
+        }
+        put(&mut data, 0xC0, &primitive);
+        chunk(b"SHP1", data)
+    }
+
+    fn materials() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &u16s(&[2, 0xFFFF]));
+        put(&mut data, 0x14, &u32s(&[0x20]));
+        put(&mut data, 0x20, &string_table(&["body", "eyes"]));
+        chunk(b"MAT3", data)
+    }
+
+    fn image() -> Vec<u8> {
+        let rgba = (0..8 * 4)
+            .flat_map(|x| [x as u8 * 8; 4])
+            .collect::<Vec<_>>();
+        texture::encode(Format::I8, &rgba, 8, 4).unwrap()
+    }
+
+    fn textures() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &u16s(&[1, 0xFFFF]));
+        put(&mut data, 0x0C, &u32s(&[0x20, 0x60]));
+        put(&mut data, 0x20, &[1, 0]);
+        put(&mut data, 0x22, &u16s(&[8, 4]));
+        put(&mut data, 0x26, &[1, 1]);
+        put(&mut data, 0x30, &[1, 1, 0, 0, 1, 0]);
+        put(&mut data, 0x3C, &u32s(&[0x20]));
+        put(&mut data, 0x40, &image());
+        put(&mut data, 0x60, &string_table(&["body_tex"]));
+        chunk(b"TEX1", data)
+    }
+
+    fn model(magic: &[u8; 8]) -> Vec<u8> {
+        let chunks = [information(), vertices(), shapes(), materials(), textures()];
+        let mut data = magic.to_vec();
+        let size = 0x20 + chunks.iter().map(|x| x.len()).sum::<usize>();
+        data.extend_from_slice(&u32s(&[size as u32, chunks.len() as u32]));
+        data.extend_from_slice(b"SVR3");
+        data.resize(0x20, 0xFF);
+        chunks.iter().for_each(|x| data.extend_from_slice(x));
+        data
+    }
+
+    #[test]
+    fn hierarchy() {
+        let model = J3d::from_binary(&mut Cursor::new(model(b"J3D2bmd3"))).unwrap();
+        assert_eq!(model.chunks.len(), 5);
+        assert_eq!(model.vertex_count, 4);
+        assert_eq!(model.hierarchy.len(), 1);
+        let joint = &model.hierarchy[0];
+        assert_eq!(joint.kind, NodeKind::Joint(0));
+        assert_eq!(joint.children[0].kind, NodeKind::Material(0));
+        assert_eq!(joint.children[0].children[0].kind, NodeKind::Shape(0));
+        assert!(joint.children[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn vertices_arrays() {
+        let model = J3d::from_binary(&mut Cursor::new(model(b"J3D2bdl4"))).unwrap();
+        assert_eq!(model.vertices.len(), 3);
+
+        let positions = &model.vertices[0];
+        assert_eq!(positions.attribute, Attribute::Position);
+        // including the padding of the array
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions.get(0), Some(&[1.0, 0.0, -1.0][..]));
+        assert_eq!(positions.get(1), Some(&[0.5, 2.0, 0.0][..]));
+        assert_eq!(positions.get(5), None);
+
+        let colors = &model.vertices[1];
+        assert_eq!(colors.attribute, Attribute::Color(0));
+        match &colors.values {
+            VertexValues::Color(x) => assert_eq!(x[..2], [[0xFF, 0, 0, 0xFF], [0, 0xFF, 0, 0x80]]),
+            x => panic!("unexpected values: {x:?}"),
+        }
+
+        let coords = &model.vertices[2];
+        assert_eq!(coords.attribute, Attribute::TexCoord(0));
+        assert_eq!(coords.components, 2);
+        assert_eq!(coords.get(1), Some(&[0.5, 0.25][..]));
+    }
+
+    #[test]
+    fn shapes_primitives() {
+        let model = J3d::from_binary(&mut Cursor::new(model(b"J3D2bmd3"))).unwrap();
+        assert_eq!(model.shapes.len(), 1);
+        let shape = &model.shapes[0];
+        assert_eq!(shape.matrix_type, 3);
+        assert_eq!(shape.bounding_radius, 2.0);
+        assert_eq!((shape.min, shape.max), ([-1.0; 3], [1.0; 3]));
+        assert_eq!(shape.attributes.len(), 4);
+        assert_eq!(shape.attributes[1].attribute, Attribute::Position);
+        assert_eq!(shape.attributes[1].kind, 3);
+
+        assert_eq!(shape.packets.len(), 1);
+        let packet = &shape.packets[0];
+        assert_eq!(packet.matrices, vec![7, 0xFFFF]);
+        assert_eq!(packet.primitives.len(), 1);
+        let primitive = &packet.primitives[0];
+        assert_eq!(primitive.kind, PrimitiveKind::TriangleStrip);
+        assert_eq!(primitive.vertices.len(), 4);
+        assert_eq!(primitive.vertices[3], vec![9, 0, 1, 3]);
+        assert_eq!(primitive.triangles(), vec![[0, 1, 2], [2, 1, 3]]);
+    }
+
+    #[test]
+    fn materials_textures() {
+        let model = J3d::from_binary(&mut Cursor::new(model(b"J3D2bmd3"))).unwrap();
+        assert_eq!(model.material_names, vec!["body", "eyes"]);
+        assert!(model.chunk(b"MAT3").is_some());
+        assert!(model.chunk(b"JNT1").is_none());
+
+        assert_eq!(model.textures.len(), 1);
+        let texture = &model.textures[0];
+        assert_eq!(texture.name, "body_tex");
+        assert_eq!(texture.format, Format::I8);
+        assert_eq!((texture.width, texture.height), (8, 4));
+        assert_eq!(texture.wrap, [1, 1]);
+        assert_eq!(texture.palette_format, None);
+        assert_eq!(texture.data, image());
+        let rgba = texture.decode().unwrap();
+        assert_eq!(rgba[4 * 9..4 * 10], [72; 4]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = model(b"J3D2bmd3");
+        data[4] = b'x';
+        assert!(J3d::from_binary(&mut Cursor::new(data)).is_err());
+
+        // unbalanced hierarchy
+        let mut data = model(b"J3D2bmd3");
+        data[0x20 + 0x18 + 0x19] = 0;
+        assert!(J3d::from_binary(&mut Cursor::new(data)).is_err());
+
+        // truncated
+        let data = model(b"J3D2bmd3");
+        assert!(J3d::from_binary(&mut Cursor::new(&data[..0x100])).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+#[cfg(test)]
+mod j3d_animation {
+    use std::io::Cursor;
+
+    use picori::j3d_animation::{Bck, Btk, Keyframe, LoopMode, Track};
+
+    use crate::common::{f32s, put, u16s, u32s};
+
+    fn file(magic: &[u8; 8], chunk_magic: &[u8; 4], mut chunk: Vec<u8>) -> Vec<u8> {
+        chunk.resize(chunk.len().next_multiple_of(0x20), 0);
+        let size = chunk.len() as u32;
+        put(&mut chunk, 0, chunk_magic);
+        put(&mut chunk, 4, &size.to_be_bytes());
+
+        let mut data = magic.to_vec();
+        data.extend_from_slice(&u32s(&[0x20 + size, 1]));
+        data.extend_from_slice(b"SVR1");
+        data.resize(0x20, 0xFF);
+        data.extend_from_slice(&chunk);
+        data
+    }
+
+    fn bck() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &[2, 1]);
+        put(&mut data, 0x0A, &u16s(&[30, 1, 1, 7, 9]));
+        put(&mut data, 0x14, &u32s(&[0x40, 0x80, 0xA0, 0xC0]));
+        // x: constant, y: animated rotation and translation, z: constant
+        let constant = [1, 0, 0, 1, 0, 0, 1, 0, 0];
+        put(&mut data, 0x40, &u16s(&constant));
+        put(&mut data, 0x52, &u16s(&[1, 0, 0, 2, 1, 0, 2, 1, 1]));
+        put(&mut data, 0x64, &u16s(&constant));
+        put(&mut data, 0x80, &f32s(&[1.0]));
+        put(&mut data, 0xA0, &u16s(&[0x4000, 0, 0, 0, 10, 0x2000, 0]));
+        let translation = [5.0, 0.0, 0.0, 0.5, 0.5, 20.0, 10.0, 0.5, 0.5];
+        put(&mut data, 0xC0, &f32s(&translation));
+        file(b"J3D1bck1", b"ANK1", data)
+    }
+
+    fn btk() -> Vec<u8> {
+        let mut data = vec![0; 8];
+        put(&mut data, 0x08, &[0, 0]);
+        put(&mut data, 0x0A, &u16s(&[10, 3, 1, 1, 6]));
+        let offsets = [0x60, 0x98, 0xA0, 0xC0, 0xC4, 0xE0, 0xE4, 0xE8];
+        put(&mut data, 0x14, &u32s(&offsets));
+        // x: animated translation
+        put(&mut data, 0x60, &u16s(&[1, 0, 0, 1, 0, 0, 2, 0, 0]));
+        put(&mut data, 0x72, &u16s(&[1, 0, 0, 1, 0, 0, 1, 0, 0]));
+        put(&mut data, 0x84, &u16s(&[1, 0, 0, 1, 0, 0, 1, 0, 0]));
+        put(&mut data, 0x98, &u16s(&[0]));
+        put(&mut data, 0xA0, &u16s(&[1, 0xFFFF, 0, 8]));
+        put(&mut data, 0xA8, b"water\0");
+        put(&mut data, 0xC0, &[1]);
+        put(&mut data, 0xC4, &f32s(&[0.5, 0.5, 0.0]));
+        put(&mut data, 0xE0, &f32s(&[1.0]));
+        put(&mut data, 0xE4, &u16s(&[0]));
+        put(&mut data, 0xE8, &f32s(&[0.0, 0.0, 0.1, 10.0, 1.0, 0.1]));
+        file(b"J3D1btk1", b"TTK1", data)
+    }
+
+    #[test]
+    fn track() {
+        let keyframe = |time, value, tangent| Keyframe {
+            time,
+            value,
+            tangent_in: tangent,
+            tangent_out: tangent,
+        };
+        assert_eq!(Track::default().value(1.0), 0.0);
+        let track = Track {
+            keyframes: vec![keyframe(0.0, 3.0, 0.0)],
+        };
+        assert_eq!(track.value(5.0), 3.0);
+        let track = Track {
+            keyframes: vec![keyframe(0.0, 0.0, 0.0), keyframe(10.0, 10.0, 0.0)],
+        };
+        assert_eq!(track.value(-1.0), 0.0);
+        assert_eq!(track.value(5.0), 5.0);
+        assert!(track.value(2.0) < 2.0);
+        assert_eq!(track.value(20.0), 10.0);
+    }
+
+    #[test]
+    fn bck_from_binary() {
+        let bck = Bck::from_binary(&mut Cursor::new(bck())).unwrap();
+        assert_eq!(bck.loop_mode, LoopMode::Repeat);
+        assert_eq!(bck.length, 30);
+        assert_eq!(bck.joints.len(), 1);
+
+        let joint = &bck.joints[0];
+        assert_eq!(joint.scale.each_ref().map(|x| x.value(0.0)), [1.0; 3]);
+        assert_eq!(joint.rotation[0].keyframes.len(), 1);
+        assert_eq!(joint.rotation[0].value(0.0), 180.0);
+        assert_eq!(joint.rotation[1].keyframes[1].time, 10.0);
+        assert_eq!(joint.rotation[1].value(10.0), 90.0);
+        assert_eq!(joint.translation[0].value(7.0), 5.0);
+        let keyframe = joint.translation[1].keyframes[1];
+        assert_eq!((keyframe.time, keyframe.value), (20.0, 10.0));
+        assert_eq!(joint.translation[1].value(10.0), 5.0);
+    }
+
+    #[test]
+    fn btk_from_binary() {
+        let btk = Btk::from_binary(&mut Cursor::new(btk())).unwrap();
+        assert_eq!(btk.loop_mode, LoopMode::Once);
+        assert_eq!(btk.length, 10);
+        assert_eq!(btk.animations.len(), 1);
+
+        let animation = &btk.animations[0];
+        assert_eq!(animation.material, "water");
+        assert_eq!(animation.texture_matrix, 1);
+        assert_eq!(animation.center, [0.5, 0.5, 0.0]);
+        let tracks = &animation.tracks;
+        assert_eq!(tracks.scale[0].value(0.0), 1.0);
+        assert_eq!(tracks.translation[0].keyframes.len(), 2);
+        assert!((tracks.translation[0].value(5.0) - 0.5).abs() < 1e-6);
+        assert_eq!(tracks.translation[1].value(5.0), 0.0);
+    }
+
+    #[test]
+    fn invalid() {
+        // wrong kind of animation
+        assert!(Bck::from_binary(&mut Cursor::new(btk())).is_err());
+        assert!(Btk::from_binary(&mut Cursor::new(bck())).is_err());
+
+        // unknown loop mode
+        let mut data = bck();
+        data[0x28] = 9;
+        assert!(Bck::from_binary(&mut Cursor::new(data)).is_err());
+
+        // keyframes outside of the table
+        let mut data = bck();
+        data[0x20 + 0x58] = 0x10;
+        assert!(Bck::from_binary(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+#[cfg(test)]
+mod jaudio {
+    use std::io::Cursor;
+
+    use picori::jaudio::{ArchiveFormat, AudioArchive, SectionKind};
+
+    use crate::common::u32s;
+
+    /// AAF with a sound table, two banks, and one wave system.
+    fn aaf() -> Vec<u8> {
+        let mut data = u32s(&[1, 0x50, 0x10, 0]);
+        data.extend_from_slice(&u32s(&[2, 0x60, 0x08, 3, 0x68, 0x08, 7, 0]));
+        data.extend_from_slice(&u32s(&[3, 0x70, 0x10, 0, 0]));
+        data.extend_from_slice(&u32s(&[0]));
+        data.resize(0x50, 0);
+        data.extend_from_slice(&[0xAA; 0x10]);
+        data.extend_from_slice(b"IBNK");
+        data.extend_from_slice(&u32s(&[0x08]));
+        data.extend_from_slice(b"IBNK");
+        data.extend_from_slice(&u32s(&[0x08]));
+        data.extend_from_slice(b"WSYS");
+        data.extend_from_slice(&u32s(&[0x10, 0, 0]));
+        data
+    }
+
+    /// BAA with a sound table, a bank, a wave system, and a sequence.
+    fn baa() -> Vec<u8> {
+        let mut data = b"AA_<".to_vec();
+        data.extend_from_slice(b"bst ");
+        data.extend_from_slice(&u32s(&[0x60, 0x70]));
+        data.extend_from_slice(b"bnk ");
+        data.extend_from_slice(&u32s(&[4, 0x70]));
+        data.extend_from_slice(b"ws  ");
+        data.extend_from_slice(&u32s(&[1, 0x80, 0]));
+        data.extend_from_slice(b"bms ");
+        data.extend_from_slice(&u32s(&[9, 0x90, 0x98]));
+        data.extend_from_slice(b"bsft");
+        data.extend_from_slice(&u32s(&[0x98]));
+        data.extend_from_slice(b">_AA");
+        data.resize(0x60, 0);
+        data.extend_from_slice(&[0xBB; 0x10]);
+        data.extend_from_slice(b"IBNK");
+        data.extend_from_slice(&u32s(&[0x10, 4, 0]));
+        data.extend_from_slice(b"WSYS");
+        data.extend_from_slice(&u32s(&[0x10, 1, 0]));
+        data.extend_from_slice(&[0xCC; 0x08]);
+        data.extend_from_slice(b"bsft");
+        data
+    }
+
+    #[test]
+    fn from_binary_aaf() {
+        let data = aaf();
+        let archive = AudioArchive::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(archive.format, ArchiveFormat::Aaf);
+        assert_eq!(archive.sections.len(), 4);
+        assert_eq!(archive.sections[0].kind, SectionKind::Other(1));
+        assert_eq!(archive.sections[0].data(&data), Some(&[0xAA; 0x10][..]));
+
+        let banks = archive
+            .sections_of(SectionKind::InstrumentBank)
+            .collect::<Vec<_>>();
+        assert_eq!(banks.len(), 2);
+        assert_eq!(banks[1].offset, 0x68);
+        assert_eq!(&banks[1].data(&data).unwrap()[..4], b"IBNK");
+        assert_eq!(archive.bank(7), Some(banks[1]));
+        assert_eq!(archive.bank(8), None);
+
+        let wsys = archive.wave_system(0).unwrap();
+        assert_eq!(wsys.size, Some(0x10));
+        assert_eq!(&wsys.data(&data).unwrap()[..4], b"WSYS");
+    }
+
+    #[test]
+    fn from_binary_baa() {
+        let data = baa();
+        let archive = AudioArchive::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(archive.format, ArchiveFormat::Baa);
+        assert_eq!(archive.sections.len(), 5);
+        assert_eq!(archive.sections[0].kind, SectionKind::SoundTable);
+        assert_eq!(archive.sections[0].data(&data), Some(&[0xBB; 0x10][..]));
+
+        let bank = archive.bank(4).unwrap();
+        assert_eq!((bank.offset, bank.size), (0x70, Some(0x10)));
+        let wsys = archive.wave_system(1).unwrap();
+        assert_eq!((wsys.offset, wsys.size), (0x80, Some(0x10)));
+
+        let sequence = archive.sections[3];
+        assert_eq!(sequence.kind, SectionKind::Sequence);
+        assert_eq!(sequence.id, 9);
+        assert_eq!(sequence.data(&data), Some(&[0xCC; 0x08][..]));
+
+        let table = archive.sections[4];
+        assert_eq!(table.kind, SectionKind::StreamFileTable);
+        assert_eq!(table.data(&data), Some(&b"bsft"[..]));
+    }
+
+    #[test]
+    fn invalid() {
+        // unknown AAF chunk
+        let mut data = aaf();
+        data[3] = 0x20;
+        assert!(AudioArchive::from_binary(&mut Cursor::new(data)).is_err());
+
+        // unknown BAA chunk
+        let mut data = baa();
+        data[4..8].copy_from_slice(b"xyz ");
+        assert!(AudioArchive::from_binary(&mut Cursor::new(data)).is_err());
+
+        // missing end of BAA index
+        let data = baa();
+        assert!(AudioArchive::from_binary(&mut Cursor::new(&data[..0x20])).is_err());
+
+        // section outside of the archive
+        let data = aaf();
+        let archive = AudioArchive::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(archive.sections[0].data(&data[..0x58]), None);
+    }
+}
@@ -14,8 +14,7 @@ mod jis_x_0201 {
             .filter(|x| *x != 0x5c)
             .filter(|x| *x != 0x7e)
             .zip(result)
-            .map(|(a, b)| (a as u8) as char == b)
-            .all(|x| x);
+            .all(|(a, b)| (a as u8) as char == b);
 
         assert!(ok);
         assert!([0x5c].iter().jisx0201().next().unwrap().unwrap() == '\u{00a5}');
@@ -1 +1,4 @@
+#![allow(clippy::module_inception)]
+
+mod common;
 mod gcm;
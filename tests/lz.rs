@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod lz {
+    use std::io::Cursor;
+
+    use picori::lz::{self, is_lz, Kind};
+
+    fn round_trip(data: &[u8], kind: Kind) -> Vec<u8> {
+        let compressed = lz::compress(data, kind);
+        assert_eq!(compressed.len() % 4, 0);
+        let mut cursor = Cursor::new(&compressed);
+        assert!(is_lz(&mut cursor));
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.kind, Some(kind));
+        assert_eq!(header.decompressed_size as usize, data.len());
+        assert!(lz::decompress(&mut cursor, &header).unwrap() == data);
+        compressed
+    }
+
+    #[test]
+    fn decompress() {
+        let data: &[u8] = &[
+            0x10, 0x0A, 0x00, 0x00, // type = 0x10, size = 10
+            0x10, // flags = 0001
+            b'a', b'b', b'c', // literals
+            0x30, 0x02, // back-reference (length 6, distance 3)
+            b'X', // literal
+        ];
+
+        let mut cursor = Cursor::new(data);
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.kind, Some(Kind::Lz10));
+        assert_eq!(lz::decompress(&mut cursor, &header).unwrap(), b"abcabcabcX");
+
+        let mut cursor = Cursor::new(&data[..data.len() - 1]);
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert!(lz::decompress(&mut cursor, &header).is_err());
+    }
+
+    #[test]
+    fn lz11_back_references() {
+        let data: &[u8] = &[
+            0x11, 0x27, 0x01, 0x00, // type = 0x11, size = 0x127
+            0x70, // flags = 0111
+            b'a', // literal
+            0x30, 0x00, // back-reference (length 4, distance 1)
+            0x00, 0x00, 0x00, // back-reference (length 0x11, distance 1)
+            0x10, 0x00, 0x00, 0x00, // back-reference (length 0x111, distance 1)
+        ];
+
+        let mut cursor = Cursor::new(data);
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.kind, Some(Kind::Lz11));
+        let output = lz::decompress(&mut cursor, &header).unwrap();
+        assert_eq!(output, [b'a'; 0x127]);
+    }
+
+    #[test]
+    fn compress() {
+        for kind in [Kind::Lz10, Kind::Lz11] {
+            assert_eq!(round_trip(&[], kind), [kind.id(), 0, 0, 0, 0, 0, 0, 0]);
+            round_trip(b"a", kind);
+            round_trip(include_bytes!("../assets/tests/yaz0/test.output"), kind);
+            round_trip(include_bytes!("../assets/tests/yaz0/test1.output"), kind);
+        }
+
+        // LZ11 uses the longest back-references for runs
+        let lz10 = round_trip(&[0xAB; 0x20000], Kind::Lz10);
+        let lz11 = round_trip(&[0xAB; 0x20000], Kind::Lz11);
+        assert!(lz11.len() < 0x20);
+        assert!(lz11.len() < lz10.len());
+    }
+
+    #[test]
+    fn invalid_type() {
+        let data: &[u8] = &[0x30, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let mut cursor = Cursor::new(data);
+        assert!(!is_lz(&mut cursor));
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert!(!header.is_valid());
+        assert!(lz::decompress(&mut cursor, &header).is_err());
+    }
+
+    #[test]
+    fn lz77_magic() {
+        let mut data = b"LZ77".to_vec();
+        data.extend_from_slice(&lz::compress(b"banner banner banner", Kind::Lz10));
+        let mut cursor = Cursor::new(&data);
+        assert!(is_lz(&mut cursor));
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert!(header.lz77_magic);
+        assert_eq!(header.kind, Some(Kind::Lz10));
+        assert_eq!(
+            lz::decompress(&mut cursor, &header).unwrap(),
+            b"banner banner banner"
+        );
+
+        let data = lz::compress(b"banner", Kind::Lz10);
+        let header = lz::Header::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(!header.lz77_magic);
+    }
+
+    #[test]
+    fn extended_size() {
+        let data: &[u8] = &[
+            0x10, 0x00, 0x00, 0x00, // type = 0x10, size in the next word
+            0x03, 0x00, 0x00, 0x00, // size = 3
+            0x00, b'a', b'b', b'c', // literals
+        ];
+        let mut cursor = Cursor::new(data);
+        let header = lz::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(lz::decompress(&mut cursor, &header).unwrap(), b"abc");
+    }
+}
@@ -0,0 +1,215 @@
+#[cfg(test)]
+mod memcard {
+    use std::io::Cursor;
+
+    use picori::gci::BLOCK_SIZE;
+    use picori::memcard::{self, Memcard};
+
+    fn set_checksum(block: &mut [u8], range: std::ops::Range<usize>, offset: usize) {
+        let [sum, inverse] = memcard::checksum(&block[range]);
+        block[offset..offset + 2].copy_from_slice(&sum.to_be_bytes());
+        block[offset + 2..offset + 4].copy_from_slice(&inverse.to_be_bytes());
+    }
+
+    /// Card (4 megabits, 64 blocks) with the files (game ID and blocks) in the
+    /// second copy of the directory and block allocation table.
+    fn card(files: &[(&[u8; 6], &[u16])]) -> Vec<u8> {
+        let mut card = vec![0; 64 * BLOCK_SIZE];
+        let header = &mut card[..BLOCK_SIZE];
+        header[0x22..0x24].copy_from_slice(&4_u16.to_be_bytes());
+        header[0x26..0x1FC].fill(0xFF);
+        set_checksum(header, 0..0x1FC, 0x1FC);
+
+        for copy in 0..2 {
+            let directory = &mut card[(1 + copy) * BLOCK_SIZE..][..BLOCK_SIZE];
+            directory.fill(0xFF);
+            if copy == 1 {
+                for (index, (game_id, blocks)) in files.iter().enumerate() {
+                    let entry = &mut directory[index * 0x40..][..0x40];
+                    entry[..6].copy_from_slice(*game_id);
+                    entry[0x08..0x28].fill(0);
+                    entry[0x08..0x0C].copy_from_slice(b"save");
+                    entry[0x36..0x38].copy_from_slice(&blocks[0].to_be_bytes());
+                    entry[0x38..0x3A].copy_from_slice(&(blocks.len() as u16).to_be_bytes());
+                    entry[0x3C..0x40].copy_from_slice(&0_u32.to_be_bytes());
+                }
+            }
+            directory[0x1FFA..0x1FFC].copy_from_slice(&(copy as u16 + 1).to_be_bytes());
+            set_checksum(directory, 0..0x1FFC, 0x1FFC);
+
+            let map = &mut card[(3 + copy) * BLOCK_SIZE..][..BLOCK_SIZE];
+            let mut used = 0_u16;
+            if copy == 1 {
+                for (_, blocks) in files.iter() {
+                    for (i, block) in blocks.iter().enumerate() {
+                        let next = blocks.get(i + 1).copied().unwrap_or(0xFFFF);
+                        let offset = 0x0A + (*block as usize - 5) * 2;
+                        map[offset..offset + 2].copy_from_slice(&next.to_be_bytes());
+                        used += 1;
+                    }
+                }
+            }
+            map[0x04..0x06].copy_from_slice(&(copy as u16 + 1).to_be_bytes());
+            map[0x06..0x08].copy_from_slice(&(59 - used).to_be_bytes());
+            map[0x08..0x0A].copy_from_slice(&4_u16.to_be_bytes());
+            set_checksum(map, 4..BLOCK_SIZE, 0);
+        }
+
+        for (index, (_, blocks)) in files.iter().enumerate() {
+            for (i, block) in blocks.iter().enumerate() {
+                card[*block as usize * BLOCK_SIZE] = index as u8;
+                card[*block as usize * BLOCK_SIZE + 1] = i as u8;
+            }
+        }
+        card
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(memcard::checksum(&[0x00, 0x01, 0x00, 0x02]), [3, 0xFFFB]);
+        assert_eq!(memcard::checksum(&[0xFF, 0xFF]), [0, 0]);
+        assert_eq!(memcard::checksum(&[]), [0, 0]);
+    }
+
+    #[test]
+    fn from_binary() {
+        let data = card(&[(b"GZLE01", &[5, 7, 6]), (b"GALE01", &[8])]);
+        let card = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(card.header().size, 4);
+        assert_eq!(card.header().block_count(), 64);
+        assert!(card.checksums_valid());
+        assert_eq!(card.active_directory(), 1);
+        assert_eq!(card.active_block_map(), 1);
+        assert_eq!(card.block_map().free_blocks, 55);
+        assert!(card.directories()[0].entries.iter().all(|x| x.is_none()));
+
+        let files = card.files().collect::<Vec<_>>();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].1.game_id.to_string(), "GZLE01");
+        assert_eq!(files[0].1.filename().unwrap(), "save");
+        assert_eq!(files[1].0, 1);
+
+        assert_eq!(card.blocks(0).unwrap(), [5, 7, 6]);
+        let gci = card.extract(0).unwrap();
+        assert_eq!(gci.entry.block_count, 3);
+        assert_eq!(gci.data.len(), 3 * BLOCK_SIZE);
+        assert_eq!(gci.data[..2], [0, 0]);
+        assert_eq!(gci.data[BLOCK_SIZE..BLOCK_SIZE + 2], [0, 1]);
+        assert_eq!(gci.data[2 * BLOCK_SIZE..2 * BLOCK_SIZE + 2], [0, 2]);
+        assert_eq!(card.extract(1).unwrap().data[..2], [1, 0]);
+        assert!(card.extract(2).is_err());
+        assert!(card.extract(200).is_err());
+    }
+
+    #[test]
+    fn active_copy() {
+        // invalid checksum of the second directory, the first is active
+        let mut data = card(&[(b"GZLE01", &[5])]);
+        data[2 * BLOCK_SIZE + 0x100] ^= 1;
+        let card = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(card.active_directory(), 0);
+        assert_eq!(card.active_block_map(), 1);
+        assert_eq!(card.files().count(), 0);
+        assert!(card.checksums_valid());
+    }
+
+    #[test]
+    fn insert() {
+        let data = card(&[(b"GZLE01", &[5, 7, 6]), (b"GALE01", &[8])]);
+        let mut memcard = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        let mut gci = memcard.extract(0).unwrap();
+        gci.entry.filename[..5].copy_from_slice(b"other");
+        gci.data.truncate(2 * BLOCK_SIZE);
+        gci.entry.block_count = 2;
+        assert_eq!(memcard.insert(&gci).unwrap(), 2);
+
+        let mut output = Vec::new();
+        memcard.to_binary(&mut output).unwrap();
+        assert_eq!(output.len(), data.len());
+        let written = Memcard::from_binary(&mut Cursor::new(&output)).unwrap();
+        assert!(written.checksums_valid());
+        assert_eq!(written.directories()[0], written.directories()[1]);
+        assert_eq!(written.block_maps()[0], written.block_maps()[1]);
+        assert_eq!(written.directory().update_counter, 3);
+        assert_eq!(written.block_map().free_blocks, 53);
+        assert_eq!(written.block_map().last_allocated, 10);
+        assert_eq!(written.blocks(2).unwrap(), [9, 10]);
+        assert_eq!(written.extract(0).unwrap(), memcard.extract(0).unwrap());
+        let extracted = written.extract(2).unwrap();
+        assert_eq!(extracted.entry.first_block, 9);
+        assert_eq!(extracted.data, gci.data);
+
+        // already on the card
+        assert!(memcard.insert(&gci).is_err());
+
+        // data does not match the number of blocks
+        gci.entry.filename[0] = b'O';
+        gci.entry.block_count = 3;
+        assert!(memcard.insert(&gci).is_err());
+
+        // not enough free blocks
+        gci.entry.block_count = 54;
+        gci.data.resize(54 * BLOCK_SIZE, 0);
+        assert!(memcard.insert(&gci).is_err());
+        gci.entry.block_count = 53;
+        gci.data.truncate(53 * BLOCK_SIZE);
+        assert_eq!(memcard.insert(&gci).unwrap(), 3);
+        assert_eq!(memcard.block_map().free_blocks, 0);
+        assert_eq!(memcard.blocks(3).unwrap()[..2], [11, 12]);
+        assert_eq!(memcard.blocks(3).unwrap()[51..], [62, 63]);
+    }
+
+    #[test]
+    fn insert_full() {
+        // every block is used and the last allocated block is out of range
+        let blocks = (5..64).collect::<Vec<u16>>();
+        let mut data = card(&[(b"GZLE01", &blocks)]);
+        data[4 * BLOCK_SIZE + 0x08..][..2].copy_from_slice(&0xFFFF_u16.to_be_bytes());
+        set_checksum(&mut data[4 * BLOCK_SIZE..][..BLOCK_SIZE], 4..BLOCK_SIZE, 0);
+        let mut memcard = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(memcard.block_map().last_allocated, 0xFFFF);
+
+        let mut gci = memcard.extract(0).unwrap();
+        gci.entry.filename[0] = b'S';
+        gci.entry.block_count = 1;
+        gci.data.truncate(BLOCK_SIZE);
+        assert!(memcard.insert(&gci).is_err());
+
+        // the search wraps around to the start of the card
+        let mut data = card(&[(b"GZLE01", &[5])]);
+        data[4 * BLOCK_SIZE + 0x08..][..2].copy_from_slice(&0xFFFF_u16.to_be_bytes());
+        set_checksum(&mut data[4 * BLOCK_SIZE..][..BLOCK_SIZE], 4..BLOCK_SIZE, 0);
+        let mut memcard = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(memcard.insert(&gci).unwrap(), 1);
+        assert_eq!(memcard.blocks(1).unwrap(), [6]);
+    }
+
+    #[test]
+    fn invalid() {
+        // block chain with a loop (5 -> 6 -> 5)
+        let mut data = card(&[(b"GZLE01", &[5, 6])]);
+        data[4 * BLOCK_SIZE + 0x0C..][..2].copy_from_slice(&5_u16.to_be_bytes());
+        set_checksum(&mut data[4 * BLOCK_SIZE..][..BLOCK_SIZE], 4..BLOCK_SIZE, 0);
+        let memcard = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(memcard.active_block_map(), 1);
+        assert!(memcard.blocks(0).is_err());
+
+        // block in the system area
+        let mut data = card(&[(b"GZLE01", &[5, 6])]);
+        data[4 * BLOCK_SIZE + 0x0A..][..2].copy_from_slice(&2_u16.to_be_bytes());
+        set_checksum(&mut data[4 * BLOCK_SIZE..][..BLOCK_SIZE], 4..BLOCK_SIZE, 0);
+        let memcard = Memcard::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(memcard.blocks(0).is_err());
+
+        // card size the block allocation table cannot address
+        for size in [0_u16, 2, 12, 256, 0xFFFF] {
+            let mut data = card(&[]);
+            data[0x22..0x24].copy_from_slice(&size.to_be_bytes());
+            assert!(Memcard::from_binary(&mut Cursor::new(&data)).is_err());
+        }
+
+        // truncated image
+        let data = card(&[]);
+        assert!(Memcard::from_binary(&mut Cursor::new(&data[..data.len() - 1])).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+#[cfg(test)]
+mod nkit {
+    use std::io::Cursor;
+
+    use picori::gcm::junk::{self, Junk};
+    use picori::nkit::Header;
+    use picori::Gcm;
+
+    use crate::common::put;
+
+    fn disc() -> Vec<u8> {
+        let mut data = vec![0u8; 0x10000];
+        put(&mut data, 0x000, b"GZLE01");
+        put(&mut data, 0x01C, &0xC2339F3D_u32.to_be_bytes());
+        put(&mut data, 0x020, b"Junk Test");
+        put(&mut data, 0x420, &0x2800_u32.to_be_bytes());
+        put(&mut data, 0x424, &0x3000_u32.to_be_bytes());
+        put(&mut data, 0x428, &0x1E_u32.to_be_bytes());
+        put(&mut data, 0x42C, &0x1E_u32.to_be_bytes());
+        put(&mut data, 0x2440, b"2002/01/01");
+
+        // main.dol with a single text section
+        put(&mut data, 0x2800, &0x100_u32.to_be_bytes());
+        put(&mut data, 0x2848, &0x80003100_u32.to_be_bytes());
+        put(&mut data, 0x2890, &0x20_u32.to_be_bytes());
+
+        // fst.bin with a single file
+        put(&mut data, 0x3000, &0x01000000_u32.to_be_bytes());
+        put(&mut data, 0x3008, &2_u32.to_be_bytes());
+        put(&mut data, 0x300C, &0x00000000_u32.to_be_bytes());
+        put(&mut data, 0x3010, &0x8000_u32.to_be_bytes());
+        put(&mut data, 0x3014, &0x11_u32.to_be_bytes());
+        put(&mut data, 0x3018, b"a.bin\0");
+        data
+    }
+
+    #[test]
+    fn junk_is_deterministic() {
+        let mut a = vec![0u8; 0x1000];
+        let mut b = vec![0u8; 0x1000];
+        Junk::new(*b"GZLE", 0).fill(0x8000, &mut a);
+        Junk::new(*b"GZLE", 0).fill(0x8000, &mut b);
+        assert_eq!(a, b);
+        assert!(a.iter().any(|x| *x != 0));
+
+        Junk::new(*b"GZLP", 0).fill(0x8000, &mut b);
+        assert_ne!(a, b);
+        Junk::new(*b"GZLE", 1).fill(0x8000, &mut b);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn junk_known_answer() {
+        // computed with a port of nod's lagged Fibonacci generator, the first
+        // vector crosses a sector boundary (the generator is reseeded)
+        let mut buffer = [0u8; 16];
+        Junk::new(*b"GZLE", 0).fill(0x7FF8, &mut buffer);
+        assert_eq!(buffer, [
+            0x10, 0x0D, 0xB4, 0x12, 0x54, 0x01, 0x50, 0xA5, 0x7D, 0x43, 0xED, 0x94, 0xD2, 0x82,
+            0xE2, 0xCC
+        ]);
+
+        Junk::new(*b"GALE", 1).fill(0x12345678, &mut buffer);
+        assert_eq!(buffer, [
+            0xB3, 0xF9, 0x28, 0xFE, 0x7B, 0xC9, 0x3E, 0x60, 0x20, 0x25, 0x9E, 0x02, 0xC3, 0xF2,
+            0x8B, 0x54
+        ]);
+    }
+
+    #[test]
+    fn junk_random_access() {
+        // spans a block boundary and the end of the generator buffer
+        let offset = junk::BLOCK_SIZE - 0x1000;
+        let mut full = vec![0u8; 0x3000];
+        Junk::new(*b"GZLE", 0).fill(offset, &mut full);
+
+        let mut junk = Junk::new(*b"GZLE", 0);
+        let mut parts = vec![0u8; 0x3000];
+        for (i, chunk) in parts.chunks_mut(0x7FF).enumerate().rev() {
+            junk.fill(offset + i as u64 * 0x7FF, chunk);
+        }
+        assert_eq!(full, parts);
+
+        // every block is generated from a new seed
+        let mut next = vec![0u8; 0x1000];
+        Junk::new(*b"GZLE", 0).fill(junk::BLOCK_SIZE * 2, &mut next);
+        assert_ne!(full[0x1000..0x2000], next[..]);
+    }
+
+    #[test]
+    fn unused_regions() {
+        let gcm = Gcm::from_binary(&mut Cursor::new(disc())).unwrap();
+        let regions = junk::unused_regions(&gcm, 0x10000);
+        assert_eq!(regions, vec![0x3020..0x8000, 0x8014..0x10000]);
+    }
+
+    #[test]
+    fn restore() {
+        let data = disc();
+        let gcm = Gcm::from_binary(&mut Cursor::new(&data)).unwrap();
+        let mut junk = Junk::from_boot(gcm.boot());
+        let mut output = Cursor::new(data.clone());
+        junk::restore(&gcm, &mut junk, 0x10000, &mut output).unwrap();
+        let output = output.into_inner();
+
+        assert_eq!(output[..0x3020], data[..0x3020]);
+        assert_eq!(output[0x8000..0x8014], data[0x8000..0x8014]);
+
+        let mut expected = vec![0u8; 0x8000 - 0x3020];
+        Junk::new(*b"GZLE", 0).fill(0x3020, &mut expected);
+        assert_eq!(output[0x3020..0x8000], expected[..]);
+    }
+
+    #[test]
+    fn detect() {
+        let mut data = disc();
+        assert_eq!(Header::detect(&mut Cursor::new(&data)).unwrap(), None);
+
+        put(&mut data, 0x200, b"NKIT v01");
+        put(&mut data, 0x208, &0x12345678_u32.to_be_bytes());
+        put(&mut data, 0x20C, &0xAABBCCDD_u32.to_be_bytes());
+        put(&mut data, 0x210, &0x57058000_u32.to_be_bytes());
+        put(&mut data, 0x214, b"GZLJ");
+        let header = Header::detect(&mut Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(&header.version, b" v01");
+        assert_eq!(header.original_crc32, 0x12345678);
+        assert_eq!(header.nkit_crc32, 0xAABBCCDD);
+        assert_eq!(header.original_size, 0x57058000);
+        assert_eq!(header.junk_id, *b"GZLJ");
+        assert_eq!(header.update_partition_crc32, 0);
+
+        let mut a = vec![0u8; 0x100];
+        let mut b = vec![0u8; 0x100];
+        header.junk(0).fill(0x10000, &mut a);
+        Junk::new(*b"GZLJ", 0).fill(0x10000, &mut b);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let mut data = disc();
+        put(&mut data, 0x200, b"NKIX");
+        let mut input = Cursor::new(&data[0x200..]);
+        assert!(Header::from_binary(&mut input).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+mod common;
+
+#[cfg(test)]
+mod rarc {
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use picori::rarc::{self, name_hash, Entry, NodeOrder, RarcBuilder, RarcReader};
+
+    use crate::common::put;
+
+    fn directory(
+        id: u16,
+        name: &str,
+        flags: u8,
+        name_offset: u16,
+        data: u32,
+        size: u32,
+    ) -> Vec<u8> {
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&id.to_be_bytes());
+        entry.extend_from_slice(&name_hash(name).to_be_bytes());
+        entry.extend_from_slice(&[flags, 0]);
+        entry.extend_from_slice(&name_offset.to_be_bytes());
+        entry.extend_from_slice(&data.to_be_bytes());
+        entry.extend_from_slice(&size.to_be_bytes());
+        entry.extend_from_slice(&0_u32.to_be_bytes());
+        entry
+    }
+
+    fn node(identifier: &[u8; 4], name: &str, name_offset: u32, count: u16, index: u32) -> Vec<u8> {
+        let mut node = identifier.to_vec();
+        node.extend_from_slice(&name_offset.to_be_bytes());
+        node.extend_from_slice(&name_hash(name).to_be_bytes());
+        node.extend_from_slice(&count.to_be_bytes());
+        node.extend_from_slice(&index.to_be_bytes());
+        node
+    }
+
+    /// archive/
+    /// ├── a.txt ("hello")
+    /// └── sub/
+    ///     └── b.bin ("abc")
+    fn archive() -> Vec<u8> {
+        let mut data = vec![0; 0x160];
+        let header = [
+            0x52415243, 0x160, 0x20, 0x100, 0x40, 0x40, 0, 0, // header
+            2, 0x20, 7, 0x40, 0x20, 0xCC, // info
+        ];
+        for (i, x) in header.iter().enumerate() {
+            put(&mut data, i * 4, &u32::to_be_bytes(*x));
+        }
+        put(&mut data, 0x38, &[0, 7, 1, 0]);
+
+        put(&mut data, 0x40, &node(b"ROOT", "archive", 5, 4, 0));
+        put(&mut data, 0x50, &node(b"SUB ", "sub", 19, 3, 4));
+
+        let directories = [
+            directory(0, "a.txt", 0x11, 13, 0, 5),
+            directory(0xFFFF, "sub", 0x02, 19, 1, 0x10),
+            directory(0xFFFF, ".", 0x02, 0, 0, 0x10),
+            directory(0xFFFF, "..", 0x02, 2, 0xFFFFFFFF, 0x10),
+            directory(4, "b.bin", 0x21, 23, 0x20, 3),
+            directory(0xFFFF, ".", 0x02, 0, 1, 0x10),
+            directory(0xFFFF, "..", 0x02, 2, 0, 0x10),
+        ];
+        for (i, x) in directories.iter().enumerate() {
+            put(&mut data, 0x60 + i * 20, x);
+        }
+
+        put(&mut data, 0xEC, b".\0..\0archive\0a.txt\0sub\0b.bin\0");
+        put(&mut data, 0x120, b"hello");
+        put(&mut data, 0x140, b"abc");
+        data
+    }
+
+    #[test]
+    fn header() {
+        let rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        let header = rarc.header();
+        assert_eq!(header.mram_size, 0x40);
+        assert_eq!(header.aram_size, 0);
+        assert_eq!(header.file_count, 7);
+        assert!(header.sync_file_ids);
+    }
+
+    #[test]
+    fn files() {
+        let rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        let files = rarc.files().collect::<Vec<_>>();
+        assert_eq!(files.len(), 4);
+        assert_eq!(
+            files[0],
+            (PathBuf::new(), Entry::Root {
+                name: "archive".to_string(),
+            })
+        );
+        assert_eq!(
+            files[1],
+            (PathBuf::from("a.txt"), Entry::File {
+                name:   "a.txt".to_string(),
+                id:     0,
+                flags:  rarc::FLAG_FILE | rarc::FLAG_PRELOAD_TO_MRAM,
+                offset: 0x120,
+                size:   5,
+            })
+        );
+        assert_eq!(
+            files[2],
+            (PathBuf::from("sub"), Entry::Directory {
+                name: "sub".to_string(),
+                node: 1,
+            })
+        );
+        assert_eq!(files[3].0, PathBuf::from("sub/b.bin"));
+    }
+
+    #[test]
+    fn find() {
+        let mut rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        assert!(matches!(
+            rarc.find("/SUB/b.bin"),
+            Some(Entry::File { id: 4, .. })
+        ));
+        assert!(rarc.find("sub/c.bin").is_none());
+        assert_eq!(rarc.read("a.txt").unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(rarc.read("sub/b.bin").unwrap(), Some(b"abc".to_vec()));
+        assert_eq!(rarc.read("sub").unwrap(), None);
+    }
+
+    #[test]
+    fn nodes() {
+        let rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        let names = rarc
+            .nodes()
+            .filter_map(|x| match x {
+                rarc::Node::DirectoryBegin { name } => Some(format!("+{}", name)),
+                rarc::Node::DirectoryEnd { name } => Some(format!("-{}", name)),
+                rarc::Node::File { name, .. } => Some(name.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, [
+            "+archive", "a.txt", "+sub", "b.bin", "-sub", "-archive"
+        ]);
+    }
+
+    #[test]
+    fn invalid_node_index() {
+        let mut data = archive();
+        data[0x60 + 20 + 8..0x60 + 20 + 12].copy_from_slice(&5_u32.to_be_bytes());
+        assert!(RarcReader::new(Cursor::new(data)).is_err());
+    }
+
+    fn build(builder: &RarcBuilder) -> Vec<u8> {
+        let mut output = Cursor::new(Vec::new());
+        builder.build(&mut output).unwrap();
+        output.into_inner()
+    }
+
+    #[test]
+    fn name_hashes() {
+        assert_eq!(name_hash("."), 0x2E);
+        assert_eq!(name_hash(".."), 0xB8);
+    }
+
+    #[test]
+    fn builder() {
+        let mut builder = RarcBuilder::new("archive");
+        builder.add_file("a.txt", b"hello".to_vec());
+        builder.add_file("sub/b.bin", b"abc".to_vec()).flags =
+            rarc::FLAG_FILE | rarc::FLAG_PRELOAD_TO_ARAM;
+        let data = build(&builder);
+
+        let mut rarc = RarcReader::new(Cursor::new(data)).unwrap();
+        let header = rarc.header().clone();
+        assert_eq!(header.mram_size, 0x20);
+        assert_eq!(header.aram_size, 0x20);
+        assert_eq!(header.dvd_size, 0);
+        assert_eq!(header.file_count, 7);
+        assert!(header.sync_file_ids);
+        assert!(matches!(
+            rarc.find("a.txt"),
+            Some(Entry::File { id: 0, .. })
+        ));
+        assert!(matches!(
+            rarc.find("sub/b.bin"),
+            Some(Entry::File {
+                id: 4,
+                flags: 0x21,
+                ..
+            })
+        ));
+        assert_eq!(rarc.read("a.txt").unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(rarc.read("sub/b.bin").unwrap(), Some(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn rebuild() {
+        let mut builder = RarcBuilder::new("archive");
+        builder.add_file("x/y/c.bin", b"c".to_vec());
+        builder.add_file("z/d.bin", b"d".to_vec()).flags =
+            rarc::FLAG_FILE | rarc::FLAG_LOAD_FROM_DVD;
+        builder.add_file("e.bin", b"e".to_vec());
+        for order in [NodeOrder::DepthFirst, NodeOrder::BreadthFirst] {
+            builder.node_order(order);
+            let data = build(&builder);
+            let mut rarc = RarcReader::new(Cursor::new(data.clone())).unwrap();
+            let rebuilt = RarcBuilder::from_rarc(&mut rarc).unwrap();
+            assert_eq!(build(&rebuilt), data);
+        }
+    }
+
+    #[test]
+    fn unsynchronized_file_ids() {
+        let mut builder = RarcBuilder::new("archive");
+        builder.sync_file_ids(false);
+        builder.add_file("a.txt", b"hello".to_vec());
+        builder.add_file("sub/b.bin", b"abc".to_vec()).id = Some(9);
+        let mut rarc = RarcReader::new(Cursor::new(build(&builder))).unwrap();
+        assert_eq!(rarc.header().file_count, 2);
+        assert!(!rarc.header().sync_file_ids);
+        assert!(matches!(
+            rarc.find("sub/b.bin"),
+            Some(Entry::File { id: 9, .. })
+        ));
+
+        let rebuilt = RarcBuilder::from_rarc(&mut rarc).unwrap();
+        assert_eq!(build(&rebuilt), build(&builder));
+    }
+
+    #[test]
+    fn extract_to() {
+        let root = std::env::temp_dir().join(format!("picori-rarc-{}", std::process::id()));
+        let mut rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        rarc.extract_to(&root, true).unwrap();
+        assert_eq!(fs::read(root.join("a.txt")).unwrap(), b"hello");
+        assert_eq!(fs::read(root.join("sub/b.bin")).unwrap(), b"abc");
+        assert_eq!(
+            fs::read_to_string(root.join(rarc::METADATA_FILE)).unwrap(),
+            "root 524f4f54 archive\norder depth-first\nsync-file-ids true\nfile 0 0x11 a.txt\ndir \
+             53554220 sub\nfile 4 0x21 sub/b.bin\n"
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        rarc.extract_to(&root, false).unwrap();
+        assert!(!root.join(rarc::METADATA_FILE).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn pack_dir() {
+        let root = std::env::temp_dir().join(format!("picori-rarc-pack-{}", std::process::id()));
+        let mut builder = RarcBuilder::new("archive");
+        builder.node_order(NodeOrder::BreadthFirst);
+        builder.sync_file_ids(false);
+        builder.add_file("x/y/c.bin", b"c".to_vec()).id = Some(7);
+        builder.add_file("z/d.bin", b"d".to_vec()).flags =
+            rarc::FLAG_FILE | rarc::FLAG_LOAD_FROM_DVD;
+        builder.add_file("B.bin", b"b".to_vec());
+        let data = build(&builder);
+
+        let mut rarc = RarcReader::new(Cursor::new(data.clone())).unwrap();
+        rarc.extract_to(&root, true).unwrap();
+        let packed = rarc::pack_dir(&root, rarc::PackOptions::default()).unwrap();
+        assert_eq!(packed, data);
+
+        // without metadata the entries are sorted and use the default flags
+        let options = rarc::PackOptions::default().metadata(false);
+        let rebuilt = RarcBuilder::from_dir(&root, options).unwrap();
+        let names = rebuilt
+            .root()
+            .children
+            .iter()
+            .map(|x| match x {
+                rarc::RarcItem::File(file) => {
+                    assert_eq!(file.flags, rarc::FLAG_FILE | rarc::FLAG_PRELOAD_TO_MRAM);
+                    file.name.clone()
+                },
+                rarc::RarcItem::Folder(folder) => folder.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["B.bin", "x", "z"]);
+        let mut rarc = RarcReader::new(Cursor::new(build(&rebuilt))).unwrap();
+        assert_eq!(rarc.read("x/y/c.bin").unwrap(), Some(b"c".to_vec()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn edit() {
+        let mut rarc = RarcReader::new(Cursor::new(archive())).unwrap();
+        let mut builder = RarcBuilder::from_rarc(&mut rarc).unwrap();
+        builder.replace_file("SUB/b.bin", b"xyz".to_vec()).unwrap();
+        assert!(builder.replace_file("sub", Vec::new()).is_err());
+        builder.rename("a.txt", "c.txt").unwrap();
+        assert!(builder.rename("c.txt", "sub").is_err());
+        assert!(builder.rename("d.txt", "e.txt").is_err());
+        builder.add_directory("new/empty");
+        builder.add_file("new/d.txt", b"d".to_vec());
+        assert!(matches!(
+            builder.remove("new/empty"),
+            Some(rarc::RarcItem::Folder(_))
+        ));
+        assert!(builder.remove("new/empty").is_none());
+        assert!(matches!(
+            builder.get_mut("sub/b.bin"),
+            Some(rarc::RarcItem::File(rarc::RarcFile { id: Some(4), .. }))
+        ));
+
+        let mut rarc = RarcReader::new(Cursor::new(build(&builder))).unwrap();
+        let paths = rarc
+            .files()
+            .map(|x| x.0.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["", "c.txt", "sub", "sub/b.bin", "new", "new/d.txt"]);
+        assert_eq!(rarc.read("c.txt").unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(rarc.read("sub/b.bin").unwrap(), Some(b"xyz".to_vec()));
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod rle {
+    use std::io::Cursor;
+
+    use picori::rle::{self, is_rle};
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = rle::compress(data);
+        assert_eq!(compressed.len() % 4, 0);
+        let mut cursor = Cursor::new(&compressed);
+        assert!(is_rle(&mut cursor));
+        let header = rle::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.decompressed_size as usize, data.len());
+        assert!(rle::decompress(&mut cursor, &header).unwrap() == data);
+        compressed
+    }
+
+    #[test]
+    fn decompress() {
+        let data: &[u8] = &[
+            0x30, 0x08, 0x00, 0x00, // type = 0x30, size = 8
+            0x82, b'a', // run of 5 'a'
+            0x02, b'b', b'c', b'd', // copy of 3 bytes
+        ];
+
+        let mut cursor = Cursor::new(data);
+        let header = rle::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(rle::decompress(&mut cursor, &header).unwrap(), b"aaaaabcd");
+
+        let mut cursor = Cursor::new(&data[..data.len() - 1]);
+        let header = rle::Header::from_binary(&mut cursor).unwrap();
+        assert!(rle::decompress(&mut cursor, &header).is_err());
+    }
+
+    #[test]
+    fn compress() {
+        assert_eq!(round_trip(&[]), [0x30, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(round_trip(b"aaaaabcd"), [
+            0x30, 0x08, 0x00, 0x00, 0x82, b'a', 0x02, b'b', b'c', b'd', 0x00, 0x00
+        ]);
+        round_trip(include_bytes!("../assets/tests/yaz0/test.output"));
+
+        // runs and copies longer than a single block
+        let compressed = round_trip(&[0; 0x1000]);
+        assert_eq!(compressed.len(), 4 + (0x1000 / 0x82 + 1) * 2);
+        let noise = (0..0x1000_u32)
+            .map(|i| (i.wrapping_mul(0x9E3779B1) >> 13) as u8)
+            .collect::<Vec<_>>();
+        round_trip(&noise);
+    }
+
+    #[test]
+    fn invalid_type() {
+        let mut cursor = Cursor::new(&[0x10, 0x01, 0x00, 0x00, 0x00, 0x61]);
+        assert!(!is_rle(&mut cursor));
+        let header = rle::Header::from_binary(&mut cursor).unwrap();
+        assert!(rle::decompress(&mut cursor, &header).is_err());
+    }
+}
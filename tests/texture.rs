@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod texture {
+    use picori::texture::{self, EncodeOptions, Format, Quality, TlutFormat};
+
+    fn pixel(image: &[u8], width: usize, x: usize, y: usize) -> &[u8] {
+        let offset = (y * width + x) * 4;
+        &image[offset..offset + 4]
+    }
+
+    #[test]
+    fn format() {
+        for id in 0..0x10 {
+            if let Some(format) = Format::from_id(id) {
+                assert_eq!(format.id(), id);
+            }
+        }
+        assert_eq!(Format::from_id(0xE), Some(Format::Cmpr));
+        assert_eq!(Format::from_id(0x7), None);
+        assert_eq!(Format::Cmpr.data_size(10, 10), 4 * 32);
+        assert_eq!(Format::Rgba8.data_size(4, 4), 64);
+        assert_eq!(Format::I8.data_size(96, 32), 96 * 32);
+        assert_eq!(TlutFormat::from_id(2), Some(TlutFormat::Rgb5a3));
+    }
+
+    #[test]
+    fn intensity() {
+        let image = texture::decode(Format::I4, &[0x0F; 32], 8, 8, None).unwrap();
+        assert_eq!(pixel(&image, 8, 0, 0), [0x00; 4]);
+        assert_eq!(pixel(&image, 8, 1, 0), [0xFF; 4]);
+
+        let data = (0..32).collect::<Vec<u8>>();
+        let image = texture::decode(Format::I8, &data, 8, 4, None).unwrap();
+        assert_eq!(pixel(&image, 8, 7, 3), [31; 4]);
+
+        let image = texture::decode(Format::Ia4, &[0xF8; 32], 8, 4, None).unwrap();
+        assert_eq!(pixel(&image, 8, 0, 0), [0x88, 0x88, 0x88, 0xFF]);
+
+        let image = texture::decode(Format::Ia8, &[0x80, 0x40].repeat(16), 4, 4, None).unwrap();
+        assert_eq!(pixel(&image, 4, 3, 3), [0x40, 0x40, 0x40, 0x80]);
+    }
+
+    #[test]
+    fn color() {
+        let mut data = [0xF8, 0x00].repeat(16);
+        data[2..4].copy_from_slice(&[0x07, 0xE0]);
+        let image = texture::decode(Format::Rgb565, &data, 4, 4, None).unwrap();
+        assert_eq!(pixel(&image, 4, 0, 0), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 4, 1, 0), [0x00, 0xFF, 0x00, 0xFF]);
+
+        let mut data = [0x80, 0x1F].repeat(16);
+        data[2..4].copy_from_slice(&[0x21, 0x23]);
+        let image = texture::decode(Format::Rgb5a3, &data, 4, 4, None).unwrap();
+        assert_eq!(pixel(&image, 4, 0, 0), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(pixel(&image, 4, 1, 0), [0x11, 0x22, 0x33, 0x49]);
+
+        // alpha and red, then green and blue
+        let mut data = [0; 64];
+        data[30..32].copy_from_slice(&[0x11, 0x22]);
+        data[62..64].copy_from_slice(&[0x33, 0x44]);
+        let image = texture::decode(Format::Rgba8, &data, 4, 4, None).unwrap();
+        assert_eq!(pixel(&image, 4, 3, 3), [0x22, 0x33, 0x44, 0x11]);
+        assert_eq!(pixel(&image, 4, 0, 0), [0x00; 4]);
+    }
+
+    #[test]
+    fn palette() {
+        let tlut: &[u8] = &[0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F];
+        let image = texture::decode(
+            Format::C4,
+            &[0x12; 32],
+            8,
+            8,
+            Some((TlutFormat::Rgb565, tlut)),
+        )
+        .unwrap();
+        assert_eq!(pixel(&image, 8, 0, 0), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 8, 1, 0), [0x00, 0x00, 0xFF, 0xFF]);
+
+        let tlut: &[u8] = &[0x80, 0x40];
+        let image =
+            texture::decode(Format::C8, &[0; 32], 8, 4, Some((TlutFormat::Ia8, tlut))).unwrap();
+        assert_eq!(pixel(&image, 8, 7, 3), [0x40, 0x40, 0x40, 0x80]);
+
+        let image = texture::decode(
+            Format::C14x2,
+            &[0xC0, 0x00].repeat(16),
+            4,
+            4,
+            Some((TlutFormat::Rgb5a3, &[0xFF, 0xFF])),
+        )
+        .unwrap();
+        assert_eq!(pixel(&image, 4, 0, 0), [0xFF; 4]);
+
+        // missing TLUT or index outside of the TLUT
+        assert!(texture::decode(Format::C8, &[0; 32], 8, 4, None).is_err());
+        assert!(
+            texture::decode(Format::C8, &[1; 32], 8, 4, Some((TlutFormat::Ia8, tlut))).is_err()
+        );
+    }
+
+    #[test]
+    fn cmpr() {
+        let mut data = [0u8; 32];
+        // red and blue with interpolated colors
+        data[0..8].copy_from_slice(&[0xF8, 0x00, 0x00, 0x1F, 0b00_01_10_11, 0, 0, 0]);
+        // blue and red with the average and transparent
+        data[8..16].copy_from_slice(&[0x00, 0x1F, 0xF8, 0x00, 0b00_01_10_11, 0, 0, 0]);
+        let image = texture::decode(Format::Cmpr, &data, 8, 8, None).unwrap();
+        assert_eq!(pixel(&image, 8, 0, 0), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 8, 1, 0), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(pixel(&image, 8, 2, 0), [0xAA, 0x00, 0x55, 0xFF]);
+        assert_eq!(pixel(&image, 8, 3, 0), [0x55, 0x00, 0xAA, 0xFF]);
+        assert_eq!(pixel(&image, 8, 6, 0), [0x7F, 0x00, 0x7F, 0xFF]);
+        assert_eq!(pixel(&image, 8, 7, 0), [0x00; 4]);
+        assert_eq!(pixel(&image, 8, 0, 1), [0xFF, 0x00, 0x00, 0xFF]);
+        // bottom left block
+        assert_eq!(pixel(&image, 8, 0, 4), [0x00, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn padding() {
+        let data = (0..64).collect::<Vec<u8>>();
+        let image = texture::decode(Format::I8, &data, 10, 2, None).unwrap();
+        assert_eq!(image.len(), 10 * 2 * 4);
+        assert_eq!(pixel(&image, 10, 7, 1), [15; 4]);
+        assert_eq!(pixel(&image, 10, 8, 0), [32; 4]);
+        assert_eq!(pixel(&image, 10, 9, 1), [41; 4]);
+
+        assert!(texture::decode(Format::I8, &data, 20, 2, None).is_err());
+    }
+
+    /// 12×6 image with a gradient and a transparent corner.
+    fn image() -> Vec<u8> {
+        let mut image = Vec::new();
+        for y in 0..6 {
+            for x in 0..12 {
+                let alpha = if x < 2 && y < 2 { 0x00 } else { 0xFF };
+                image.extend_from_slice(&[x as u8 * 0x15, y as u8 * 0x33, 0x80, alpha]);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn encode() {
+        let image = image();
+        let formats = [
+            Format::I4,
+            Format::I8,
+            Format::Ia4,
+            Format::Ia8,
+            Format::Rgb565,
+            Format::Rgb5a3,
+            Format::Rgba8,
+            Format::Cmpr,
+        ];
+        for format in formats {
+            let data = texture::encode(format, &image, 12, 6).unwrap();
+            assert_eq!(data.len(), format.data_size(12, 6));
+            let decoded = texture::decode(format, &data, 12, 6, None).unwrap();
+            // encoding the decoded image gives the same data
+            assert_eq!(
+                texture::encode(format, &decoded, 12, 6).unwrap(),
+                data,
+                "{format:?}"
+            );
+        }
+
+        let data = texture::encode(Format::Rgba8, &image, 12, 6).unwrap();
+        assert_eq!(
+            texture::decode(Format::Rgba8, &data, 12, 6, None).unwrap(),
+            image
+        );
+
+        let data = texture::encode(Format::Rgb5a3, &image, 12, 6).unwrap();
+        let decoded = texture::decode(Format::Rgb5a3, &data, 12, 6, None).unwrap();
+        assert_eq!(pixel(&decoded, 12, 0, 0), [0x00, 0x00, 0x88, 0x00]);
+        assert_eq!(pixel(&decoded, 12, 11, 5), [0xE7, 0xFF, 0x84, 0xFF]);
+
+        assert!(texture::encode(Format::I8, &image, 12, 5).is_err());
+        assert!(texture::encode(Format::C8, &image, 12, 6).is_err());
+    }
+
+    #[test]
+    fn encode_cmpr() {
+        let image = image();
+        let options = EncodeOptions::new().quality(Quality::Fast);
+        let fast = texture::encode_with(Format::Cmpr, &image, 12, 6, &options).unwrap();
+        let high = texture::encode(Format::Cmpr, &image, 12, 6).unwrap();
+
+        let error = |data: &[u8]| {
+            let decoded = texture::decode(Format::Cmpr, data, 12, 6, None).unwrap();
+            let error = decoded
+                .iter()
+                .zip(&image)
+                .map(|(a, b)| (*a as i32 - *b as i32).pow(2));
+            error.sum::<i32>()
+        };
+        assert!(error(&high) <= error(&fast));
+
+        // transparent pixels stay transparent, opaque pixels stay opaque
+        for data in [fast, high] {
+            let decoded = texture::decode(Format::Cmpr, &data, 12, 6, None).unwrap();
+            assert!(decoded
+                .chunks(4)
+                .zip(image.chunks(4))
+                .all(|(a, b)| a[3] == b[3]));
+        }
+    }
+
+    #[test]
+    fn encode_indexed() {
+        let image = image();
+        for (format, tlut_format) in [
+            (Format::C8, TlutFormat::Rgb5a3),
+            (Format::C14x2, TlutFormat::Rgb5a3),
+            (Format::C8, TlutFormat::Ia8),
+        ] {
+            let (data, tlut) = texture::encode_indexed(format, tlut_format, &image, 12, 6).unwrap();
+            assert!(tlut.len() <= 2 * 256);
+            let decoded =
+                texture::decode(format, &data, 12, 6, Some((tlut_format, &tlut))).unwrap();
+            // few enough colors to be exact
+            let expected = texture::decode(
+                Format::Rgb5a3,
+                &texture::encode(Format::Rgb5a3, &image, 12, 6).unwrap(),
+                12,
+                6,
+                None,
+            )
+            .unwrap();
+            if tlut_format == TlutFormat::Rgb5a3 {
+                assert_eq!(decoded, expected);
+            }
+        }
+
+        // more colors than the palette, the colors are reduced
+        let (data, tlut) =
+            texture::encode_indexed(Format::C4, TlutFormat::Rgb565, &image, 12, 6).unwrap();
+        assert_eq!(data.len(), Format::C4.data_size(12, 6));
+        assert_eq!(tlut.len(), 2 * 16);
+        let decoded =
+            texture::decode(Format::C4, &data, 12, 6, Some((TlutFormat::Rgb565, &tlut))).unwrap();
+        assert!(decoded
+            .chunks(4)
+            .zip(image.chunks(4))
+            .all(|(a, b)| a[0].abs_diff(b[0]) < 0x30));
+
+        assert!(texture::encode_indexed(Format::I8, TlutFormat::Ia8, &image, 12, 6).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod thp {
+    use std::io::Cursor;
+
+    use picori::thp::{self, AudioInfo, Component, VideoInfo};
+    use picori::ThpReader;
+
+    /// THP 1.1 video with a video and a (stereo) audio component and two
+    /// frames with the audio blocks `audio`.
+    fn video_with(audio: [&[u8]; 2]) -> Vec<u8> {
+        let frames: [(&[u8], &[u8]); 2] =
+            [(b"\xFF\xD8jpeg0\xFF\xD9", audio[0]), (b"jpeg1", audio[1])];
+        let frame_size =
+            |(video, audio): (&[u8], &[u8])| (16 + video.len() + audio.len() + 31) & !31;
+
+        let mut data = Vec::new();
+        let words = |data: &mut Vec<u8>, words: &[u32]| {
+            words
+                .iter()
+                .for_each(|x| data.extend_from_slice(&x.to_be_bytes()))
+        };
+        data.extend_from_slice(b"THP\0");
+        words(&mut data, &[0x00011000, 0x40, 0x100]);
+        words(&mut data, &[
+            29.97_f32.to_bits(),
+            2,
+            frame_size(frames[0]) as u32,
+            0,
+        ]);
+        words(&mut data, &[
+            0x30,
+            0,
+            0x60,
+            0x60 + frame_size(frames[0]) as u32,
+        ]);
+
+        // components
+        words(&mut data, &[2]);
+        data.extend_from_slice(&[0, 1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        data.extend_from_slice(&[0xFF; 8]);
+        words(&mut data, &[640, 480, 0, 2, 32000, 0x100, 1]);
+        data.resize(0x60, 0);
+
+        for (i, frame) in frames.iter().enumerate() {
+            let start = data.len();
+            let next = frames.get(i + 1).map_or(0, |x| frame_size(*x));
+            let previous = if i == 0 { 0 } else { frame_size(frames[i - 1]) };
+            words(&mut data, &[next as u32, previous as u32]);
+            words(&mut data, &[frame.0.len() as u32, frame.1.len() as u32]);
+            data.extend_from_slice(frame.0);
+            data.extend_from_slice(frame.1);
+            data.resize(start + frame_size(*frame), 0);
+        }
+        data
+    }
+
+    fn video() -> Vec<u8> { video_with([b"audio0", b"a1"]) }
+
+    /// Stereo audio block with `samples` samples. The left channel predicts
+    /// from the previous sample (coefficient index 1), starting at `history`.
+    fn audio_block(samples: u32, history: i16, left: &[u8; 8], right: &[u8; 8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&8_u32.to_be_bytes());
+        data.extend_from_slice(&samples.to_be_bytes());
+        let mut coefficients = [0_i16; 32];
+        coefficients[2] = 2048;
+        coefficients
+            .iter()
+            .for_each(|x| data.extend_from_slice(&x.to_be_bytes()));
+        [history, 0, 0, 0]
+            .iter()
+            .for_each(|x| data.extend_from_slice(&x.to_be_bytes()));
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+        data
+    }
+
+    #[test]
+    fn audio() {
+        let first = audio_block(4, 100, &[0x12, 0x1F, 0x70, 0, 0, 0, 0, 0], &[
+            0x00, 0x87, 0, 0, 0, 0, 0, 0,
+        ]);
+        let expected = [104, -8, 100, 7, 128, 0, 128, 0];
+        assert_eq!(thp::decode_audio(&first, 2).unwrap(), expected);
+        // large samples are clamped
+        let second = audio_block(2, 0x7FF0, &[0x14, 0x77, 0, 0, 0, 0, 0, 0], &[
+            0x0F, 0x80, 0, 0, 0, 0, 0, 0,
+        ]);
+        assert_eq!(thp::decode_audio(&second, 2).unwrap(), [
+            0x7FFF, -0x8000, 0x7FFF, 0
+        ]);
+
+        let mut thp = ThpReader::new(Cursor::new(video_with([&first, &second]))).unwrap();
+        let frame = thp.frames().next().unwrap().unwrap();
+        assert_eq!(frame.decode_audio().unwrap().unwrap(), expected);
+        let samples = thp.decode_audio().unwrap();
+        assert_eq!(samples.len(), 2 * (4 + 2));
+        assert_eq!(samples[..8], expected);
+
+        assert!(thp::decode_audio(&first, 3).is_err());
+        assert!(thp::decode_audio(&first[..0x50 + 12], 2).is_err());
+        assert!(thp::decode_audio(&audio_block(15, 0, &[0; 8], &[0; 8]), 2).is_err());
+    }
+
+    #[test]
+    fn frames() {
+        let mut thp = ThpReader::new(Cursor::new(video())).unwrap();
+        assert_eq!(thp.header().frame_count, 2);
+        assert_eq!(thp.header().fps, 29.97);
+        assert_eq!(
+            thp.video(),
+            Some(&VideoInfo {
+                width:  640,
+                height: 480,
+                format: 0,
+            })
+        );
+        assert_eq!(
+            thp.audio(),
+            Some(&AudioInfo {
+                channels:  2,
+                frequency: 32000,
+                samples:   0x100,
+                tracks:    1,
+            })
+        );
+        assert!(matches!(thp.components(), [
+            Component::Video(_),
+            Component::Audio(_)
+        ]));
+
+        let frames = thp.frames().collect::<picori::Result<Vec<_>>>().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].index, 0);
+        assert_eq!(frames[0].video(), Some(&b"\xFF\xD8jpeg0\xFF\xD9"[..]));
+        assert_eq!(frames[0].audio(), Some(&b"audio0"[..]));
+        assert_eq!(frames[1].video(), Some(&b"jpeg1"[..]));
+        assert_eq!(frames[1].audio(), Some(&b"a1"[..]));
+
+        // the frames can be iterated again
+        assert_eq!(thp.frames().count(), 2);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = video();
+        data[3] = b'X';
+        assert!(ThpReader::new(Cursor::new(&data)).is_err());
+
+        let mut data = video();
+        data[6] = 0x20;
+        assert!(ThpReader::new(Cursor::new(&data)).is_err());
+
+        // component sizes larger than the frame
+        let mut data = video();
+        data[0x68..0x6C].copy_from_slice(&0x1000_u32.to_be_bytes());
+        let mut thp = ThpReader::new(Cursor::new(&data)).unwrap();
+        let mut frames = thp.frames();
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+
+        // frame and component sizes past the end of the file, the component is
+        // not allocated up front
+        let mut data = video();
+        data[0x18..0x1C].copy_from_slice(&0xFFFF_FFE0_u32.to_be_bytes());
+        data[0x68..0x6C].copy_from_slice(&0xFFFF_0000_u32.to_be_bytes());
+        let mut thp = ThpReader::new(Cursor::new(&data)).unwrap();
+        assert!(thp.frames().next().unwrap().is_err());
+
+        // truncated video
+        let data = video();
+        let mut thp = ThpReader::new(Cursor::new(&data[..0x70])).unwrap();
+        assert!(thp.frames().next().unwrap().is_err());
+    }
+}
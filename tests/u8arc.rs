@@ -0,0 +1,207 @@
+mod common;
+
+#[cfg(test)]
+mod u8arc {
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use picori::u8arc::{self, Entry, U8Builder, U8Reader};
+
+    use crate::common::put;
+
+    fn node(kind: u8, name: u32, offset: u32, size: u32) -> Vec<u8> {
+        let mut node = name.to_be_bytes().to_vec();
+        node[0] = kind;
+        node.extend_from_slice(&offset.to_be_bytes());
+        node.extend_from_slice(&size.to_be_bytes());
+        node
+    }
+
+    /// ├── a.txt ("hello")
+    /// ├── sub/
+    /// │   └── b.bin ("abc")
+    /// └── c.bin ("c")
+    fn archive() -> Vec<u8> {
+        let mut data = vec![0; 0xE0];
+        for (i, x) in [0x55AA382D_u32, 0x20, 0x53, 0x80].iter().enumerate() {
+            put(&mut data, i * 4, &x.to_be_bytes());
+        }
+
+        let nodes = [
+            node(1, 0, 0, 5),
+            node(0, 1, 0x80, 5),
+            node(1, 7, 0, 4),
+            node(0, 11, 0xA0, 3),
+            node(0, 17, 0xC0, 1),
+        ];
+        for (i, x) in nodes.iter().enumerate() {
+            put(&mut data, 0x20 + i * 12, x);
+        }
+
+        put(&mut data, 0x5C, b"\0a.txt\0sub\0b.bin\0c.bin\0");
+        put(&mut data, 0x80, b"hello");
+        put(&mut data, 0xA0, b"abc");
+        put(&mut data, 0xC0, b"c");
+        data
+    }
+
+    #[test]
+    fn files() {
+        let u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        let header = u8arc.header();
+        assert_eq!(header.root_offset, 0x20);
+        assert_eq!(header.data_offset, 0x80);
+
+        let files = u8arc.files().collect::<Vec<_>>();
+        let paths = files.iter().map(|x| x.0.clone()).collect::<Vec<_>>();
+        assert_eq!(paths, [
+            PathBuf::new(),
+            PathBuf::from("a.txt"),
+            PathBuf::from("sub"),
+            PathBuf::from("sub/b.bin"),
+            PathBuf::from("c.bin"),
+        ]);
+        assert_eq!(files[0].1, Entry::Root);
+        assert_eq!(files[1].1, Entry::File {
+            name:   "a.txt".to_string(),
+            offset: 0x80,
+            size:   5,
+        });
+        assert_eq!(files[2].1, Entry::Directory {
+            name:   "sub".to_string(),
+            parent: 0,
+            next:   4,
+        });
+    }
+
+    #[test]
+    fn find() {
+        let mut u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        assert!(matches!(
+            u8arc.find("/SUB/B.bin"),
+            Some(Entry::File { size: 3, .. })
+        ));
+        assert!(u8arc.find("sub/c.bin").is_none());
+        assert_eq!(u8arc.read("a.txt").unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(u8arc.read("sub/b.bin").unwrap(), Some(b"abc".to_vec()));
+        assert_eq!(u8arc.read("c.bin").unwrap(), Some(b"c".to_vec()));
+        assert_eq!(u8arc.read("sub").unwrap(), None);
+
+        // size past the end of the archive, not allocated up front
+        assert!(u8arc.file_data(0xC0, u32::MAX).is_err());
+        assert!(u8arc.file_data(0xC0, 0x21).is_err());
+        assert_eq!(u8arc.file_data(0xC0, 0x20).unwrap().len(), 0x20);
+    }
+
+    #[test]
+    fn invalid_directory() {
+        let mut data = archive();
+        // sub/ extends past the end of the root directory
+        data[0x20 + 2 * 12 + 11] = 6;
+        assert!(U8Reader::new(Cursor::new(data)).is_err());
+
+        let mut data = archive();
+        data[0] = 0;
+        assert!(U8Reader::new(Cursor::new(data)).is_err());
+    }
+
+    fn build(builder: &U8Builder) -> Vec<u8> {
+        let mut output = Cursor::new(Vec::new());
+        builder.build(&mut output).unwrap();
+        output.into_inner()
+    }
+
+    #[test]
+    fn rebuild() {
+        let mut u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        let builder = U8Builder::from_u8(&mut u8arc).unwrap();
+        assert_eq!(build(&builder), archive());
+    }
+
+    #[test]
+    fn builder() {
+        let mut builder = U8Builder::new();
+        builder.add_file("a.txt", b"hello".to_vec());
+        builder.add_file("sub/b.bin", b"abc".to_vec());
+        builder.add_file("c.bin", b"c".to_vec());
+        assert_eq!(build(&builder), archive());
+
+        builder.alignment(0x40);
+        builder.add_file("sub/b.bin", b"xyz".to_vec());
+        let data = build(&builder);
+        assert_eq!(data.len(), 0x140);
+        let mut u8arc = U8Reader::new(Cursor::new(data)).unwrap();
+        assert_eq!(u8arc.header().data_offset, 0x80);
+        assert!(matches!(
+            u8arc.find("sub/b.bin"),
+            Some(Entry::File { offset: 0xC0, .. })
+        ));
+        assert_eq!(u8arc.read("sub/b.bin").unwrap(), Some(b"xyz".to_vec()));
+        assert_eq!(u8arc.read("c.bin").unwrap(), Some(b"c".to_vec()));
+
+        builder.alignment(3);
+        assert!(builder.build(&mut Cursor::new(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn extract_to() {
+        let root = std::env::temp_dir().join(format!("picori-u8arc-{}", std::process::id()));
+        let mut u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        u8arc.extract_to(&root, true).unwrap();
+        assert_eq!(fs::read(root.join("a.txt")).unwrap(), b"hello");
+        assert_eq!(fs::read(root.join("sub/b.bin")).unwrap(), b"abc");
+        assert_eq!(fs::read(root.join("c.bin")).unwrap(), b"c");
+        assert_eq!(
+            fs::read_to_string(root.join(u8arc::METADATA_FILE)).unwrap(),
+            "alignment 0x20\nfile a.txt\ndir sub\nfile sub/b.bin\nfile c.bin\n"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn pack_dir() {
+        let root = std::env::temp_dir().join(format!("picori-u8arc-pack-{}", std::process::id()));
+        let mut u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        u8arc.extract_to(&root, true).unwrap();
+        let options = u8arc::PackOptions::default();
+        assert_eq!(u8arc::pack_dir(&root, options).unwrap(), archive());
+
+        // without metadata the entries are sorted by name
+        let options = u8arc::PackOptions::default()
+            .metadata(false)
+            .alignment(0x40);
+        let packed = u8arc::pack_dir(&root, options).unwrap();
+        let u8arc = U8Reader::new(Cursor::new(packed)).unwrap();
+        assert_eq!(u8arc.header().data_offset, 0x80);
+        let paths = u8arc
+            .files()
+            .map(|x| x.0.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["", "a.txt", "c.bin", "sub", "sub/b.bin"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn edit() {
+        let mut u8arc = U8Reader::new(Cursor::new(archive())).unwrap();
+        let mut builder = U8Builder::from_u8(&mut u8arc).unwrap();
+        builder.replace_file("sub/B.bin", b"xyz".to_vec()).unwrap();
+        assert!(builder.replace_file("sub", Vec::new()).is_err());
+        builder.rename("sub", "dir").unwrap();
+        assert!(builder.rename("dir", "c.bin").is_err());
+        assert!(matches!(
+            builder.remove("a.txt"),
+            Some(u8arc::U8Item::File(_))
+        ));
+        builder.add_directory("dir/empty");
+
+        let mut u8arc = U8Reader::new(Cursor::new(build(&builder))).unwrap();
+        let paths = u8arc
+            .files()
+            .map(|x| x.0.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["", "dir", "dir/b.bin", "dir/empty", "c.bin"]);
+        assert_eq!(u8arc.read("dir/b.bin").unwrap(), Some(b"xyz".to_vec()));
+    }
+}
@@ -0,0 +1,279 @@
+mod common;
+
+#[cfg(test)]
+mod wad {
+    use std::io::Cursor;
+
+    use picori::lz::{self, Kind};
+    use picori::wad::{ContentOptions, Wad, WadKind};
+    use picori::wii::KeyKind;
+
+    use crate::common::put;
+
+    fn certificate(issuer: &[u8], kind: u32, name: &[u8]) -> Vec<u8> {
+        let key_size = match kind {
+            0 => 0x200 + 0x38,
+            1 => 0x100 + 0x38,
+            _ => 0x78,
+        };
+        let mut data = vec![0u8; 0x240 + 0x88 + key_size];
+        put(&mut data, 0x000, &0x00010000_u32.to_be_bytes());
+        put(&mut data, 0x240, issuer);
+        put(&mut data, 0x280, &kind.to_be_bytes());
+        put(&mut data, 0x284, name);
+        put(&mut data, 0x2C4, &0x12345678_u32.to_be_bytes());
+        put(&mut data, 0x2C8, &[0xAB; 4]);
+        if kind != 2 {
+            let exponent = 0x2C8 + key_size - 0x38;
+            put(&mut data, exponent, &0x00010001_u32.to_be_bytes());
+        }
+        data
+    }
+
+    fn ticket(title_id: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 0x2A4];
+        put(&mut data, 0x000, &0x00010001_u32.to_be_bytes());
+        put(&mut data, 0x140, b"Root-CA00000001-XS00000003");
+        put(&mut data, 0x1DC, &title_id.to_be_bytes());
+        data
+    }
+
+    fn tmd(title_id: u64, sizes: &[u64]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x1E4 + 0x24 * sizes.len()];
+        put(&mut data, 0x000, &0x00010001_u32.to_be_bytes());
+        put(&mut data, 0x140, b"Root-CA00000001-CP00000004");
+        put(&mut data, 0x184, &0x00000001_00000038_u64.to_be_bytes());
+        put(&mut data, 0x18C, &title_id.to_be_bytes());
+        put(&mut data, 0x1DC, &0x0003_u16.to_be_bytes());
+        put(&mut data, 0x1DE, &(sizes.len() as u16).to_be_bytes());
+        for (i, size) in sizes.iter().enumerate() {
+            let base = 0x1E4 + 0x24 * i;
+            put(&mut data, base, &(i as u32).to_be_bytes());
+            put(&mut data, base + 4, &(i as u16).to_be_bytes());
+            put(&mut data, base + 6, &0x0001_u16.to_be_bytes());
+            put(&mut data, base + 8, &size.to_be_bytes());
+        }
+        data
+    }
+
+    fn pad(data: &mut Vec<u8>) { data.resize(data.len().next_multiple_of(0x40), 0); }
+
+    fn file(sizes: &[u64]) -> Vec<u8> {
+        let mut certificates = certificate(b"Root", 0, b"CA00000001");
+        certificates.extend(certificate(b"Root-CA00000001", 1, b"XS00000003"));
+        certificates.extend(certificate(b"Root-CA00000001", 2, b"MS00000002"));
+        let ticket = ticket(0x00010001_48414241);
+        let tmd = tmd(0x00010001_48414241, sizes);
+        let mut contents = Vec::new();
+        for (i, size) in sizes.iter().enumerate() {
+            contents.resize(
+                contents.len() + size.next_multiple_of(16) as usize,
+                i as u8 + 1,
+            );
+            if i + 1 < sizes.len() {
+                pad(&mut contents);
+            }
+        }
+        let footer = b"build info".to_vec();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x20_u32.to_be_bytes());
+        data.extend_from_slice(b"Is");
+        data.extend_from_slice(&0_u16.to_be_bytes());
+        data.extend_from_slice(&(certificates.len() as u32).to_be_bytes());
+        data.extend_from_slice(&0_u32.to_be_bytes());
+        data.extend_from_slice(&(ticket.len() as u32).to_be_bytes());
+        data.extend_from_slice(&(tmd.len() as u32).to_be_bytes());
+        data.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+        data.extend_from_slice(&(footer.len() as u32).to_be_bytes());
+        for section in [certificates, ticket, tmd, contents, footer] {
+            pad(&mut data);
+            data.extend_from_slice(&section);
+        }
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let wad = Wad::from_binary(&mut Cursor::new(file(&[0x25, 0x40, 0x01]))).unwrap();
+        assert_eq!(wad.kind, WadKind::Installable);
+        assert_eq!(wad.version, 0);
+
+        assert_eq!(wad.certificates.len(), 3);
+        let certificate = &wad.certificates[1];
+        assert_eq!(certificate.issuer, "Root-CA00000001");
+        assert_eq!(certificate.name, "XS00000003");
+        assert_eq!(certificate.full_name(), wad.ticket.issuer);
+        assert_eq!(certificate.key_kind, KeyKind::Rsa2048);
+        assert_eq!(certificate.key_id, 0x12345678);
+        assert_eq!(certificate.public_key.len(), 0x100);
+        assert_eq!(certificate.public_key[..5], [0xAB, 0xAB, 0xAB, 0xAB, 0]);
+        assert_eq!(certificate.exponent, Some(0x10001));
+        assert_eq!(wad.certificates[0].key_kind, KeyKind::Rsa4096);
+        assert_eq!(wad.certificates[2].key_kind, KeyKind::Ecc);
+        assert_eq!(wad.certificates[2].exponent, None);
+
+        assert_eq!(wad.ticket.title_id, 0x00010001_48414241);
+        assert_eq!(wad.tmd.title_id, 0x00010001_48414241);
+        assert_eq!(wad.tmd.ios_version(), Some(56));
+        assert_eq!(wad.tmd.contents.len(), 3);
+
+        let sizes = wad.contents.iter().map(|x| x.len()).collect::<Vec<_>>();
+        assert_eq!(sizes, [0x30, 0x40, 0x10]);
+        assert!(wad.contents[0].iter().all(|x| *x == 1));
+        assert!(wad.contents[1].iter().all(|x| *x == 2));
+        assert_eq!(wad.content(2), Some(&[3; 0x10][..]));
+        assert_eq!(wad.content(3), None);
+        assert_eq!(wad.footer, b"build info");
+    }
+
+    /// IMD5 header (without a valid hash) followed by `data`.
+    fn imd5(data: &[u8]) -> Vec<u8> {
+        let mut output = b"IMD5".to_vec();
+        output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        output.resize(0x20, 0);
+        output.extend_from_slice(data);
+        output
+    }
+
+    fn lz77(data: &[u8]) -> Vec<u8> {
+        let mut output = b"LZ77".to_vec();
+        output.extend_from_slice(&lz::compress(data, Kind::Lz10));
+        output
+    }
+
+    #[test]
+    fn decode() {
+        let archive = b"\x55\xAA\x38\x2D archive archive archive".to_vec();
+        let options = ContentOptions::new();
+        assert_eq!(options.decode(archive.clone()).unwrap(), archive);
+        assert_eq!(options.decode(lz77(&archive)).unwrap(), archive);
+        assert_eq!(options.decode(imd5(&archive)).unwrap(), archive);
+        assert_eq!(options.decode(imd5(&lz77(&archive))).unwrap(), archive);
+
+        // bare LZ10 streams are not detected
+        let compressed = lz::compress(&archive, Kind::Lz10);
+        assert_eq!(options.decode(compressed.clone()).unwrap(), compressed);
+
+        let options = ContentOptions::new().unwrap_lz77(false);
+        assert_eq!(
+            options.decode(imd5(&lz77(&archive))).unwrap(),
+            lz77(&archive)
+        );
+        let options = ContentOptions::new().unwrap_imd5(false);
+        assert_eq!(
+            options.decode(imd5(&lz77(&archive))).unwrap(),
+            imd5(&lz77(&archive))
+        );
+
+        // truncated IMD5 data
+        let data = imd5(&archive);
+        let options = ContentOptions::new();
+        assert!(options.decode(data[..data.len() - 1].to_vec()).is_err());
+    }
+
+    fn find(data: &[u8], value: &[u8]) -> usize {
+        data.windows(value.len()).position(|x| x == value).unwrap()
+    }
+
+    #[test]
+    fn invalid() {
+        // unknown WAD type (backup WAD)
+        let mut data = file(&[0x10]);
+        data[4..6].copy_from_slice(b"Bk");
+        assert!(Wad::from_binary(&mut Cursor::new(data)).is_err());
+
+        // invalid header size
+        let mut data = file(&[0x10]);
+        data[3] = 0x40;
+        assert!(Wad::from_binary(&mut Cursor::new(data)).is_err());
+
+        // content larger than the data section
+        let mut data = file(&[0x10]);
+        let tmd = find(&data, b"Root-CA00000001-CP00000004") - 0x140;
+        data[tmd + 0x1E4 + 15] = 0x20;
+        assert!(Wad::from_binary(&mut Cursor::new(data)).is_err());
+    }
+
+    #[cfg(feature = "crypto")]
+    mod crypto {
+        use std::io::Cursor;
+
+        use aes::cipher::block_padding::NoPadding;
+        use aes::cipher::{BlockEncryptMut, KeyIvInit};
+        use picori::wad::{ContentOptions, Wad};
+        use sha1::{Digest, Sha1};
+
+        use super::{file, find, imd5, lz77, put};
+
+        const COMMON_KEY: [u8; 16] = [0x11; 16];
+        const TITLE_KEY: [u8; 16] = [0x22; 16];
+
+        fn encrypt(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+            let len = data.len();
+            cbc::Encryptor::<aes::Aes128>::new(key.into(), iv.into())
+                .encrypt_padded_mut::<NoPadding>(data, len)
+                .unwrap();
+        }
+
+        /// WAD with a single content of 0x25 bytes (encrypted and hashed).
+        fn encrypted() -> Vec<u8> { encrypted_with(&(0..0x25).collect::<Vec<u8>>()) }
+
+        /// WAD with the single content `content` (encrypted and hashed).
+        fn encrypted_with(content: &[u8]) -> Vec<u8> {
+            let mut data = file(&[content.len() as u64]);
+            let ticket = find(&data, b"Root-CA00000001-XS00000003") - 0x140;
+            let mut title_key = TITLE_KEY;
+            let mut iv = [0; 16];
+            iv[..8].copy_from_slice(&0x00010001_48414241_u64.to_be_bytes());
+            encrypt(&COMMON_KEY, &iv, &mut title_key);
+            put(&mut data, ticket + 0x1BF, &title_key);
+
+            let size = content.len();
+            let mut content = content.to_vec();
+            content.resize(size.next_multiple_of(16), 0);
+            let tmd = find(&data, b"Root-CA00000001-CP00000004") - 0x140;
+            put(&mut data, tmd + 0x1E4 + 16, &Sha1::digest(&content[..size]));
+            encrypt(&TITLE_KEY, &[0; 16], &mut content);
+            let offset = data.len() - 10 - content.len().next_multiple_of(0x40);
+            put(&mut data, offset, &content);
+            data
+        }
+
+        #[test]
+        fn decrypt_content() {
+            let wad = Wad::from_binary(&mut Cursor::new(encrypted())).unwrap();
+            let title_key = wad.ticket.title_key(&COMMON_KEY);
+            assert_eq!(title_key, TITLE_KEY);
+            let content = wad.decrypt_content(0, &title_key).unwrap();
+            assert_eq!(content, (0..0x25).collect::<Vec<u8>>());
+            assert!(wad.decrypt_content(1, &title_key).is_err());
+            assert!(wad.decrypt_content(0, &[0; 16]).is_err());
+        }
+
+        #[test]
+        fn decrypted_contents() {
+            let banner = b"IMET banner of the channel".to_vec();
+            let data = encrypted_with(&imd5(&lz77(&banner)));
+            let wad = Wad::from_binary(&mut Cursor::new(data)).unwrap();
+            let options = ContentOptions::new();
+            let content = wad.decrypt_content_with(0, &TITLE_KEY, &options).unwrap();
+            assert_eq!(content, banner);
+
+            let contents = wad
+                .decrypted_contents(&TITLE_KEY, &options)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(contents.len(), 1);
+            assert_eq!(contents[0].0.id, 0);
+            assert_eq!(contents[0].1, banner);
+
+            let options = ContentOptions::new().unwrap_imd5(false);
+            let content = wad.decrypt_content_with(0, &TITLE_KEY, &options).unwrap();
+            assert_eq!(content, imd5(&lz77(&banner)));
+            assert!(wad
+                .decrypted_contents(&[0; 16], &options)
+                .all(|x| x.is_err()));
+        }
+    }
+}
@@ -0,0 +1,385 @@
+mod common;
+
+#[cfg(test)]
+mod wii {
+    use std::io::Cursor;
+
+    #[cfg(feature = "serde")]
+    use picori::wii::{Partition, Ticket};
+    use picori::wii::{PartitionKind, SignatureKind, Tmd};
+    use picori::Wii;
+
+    use crate::common::put;
+
+    fn ticket(title_id: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 0x2A4];
+        put(&mut data, 0x000, &0x00010001_u32.to_be_bytes());
+        put(&mut data, 0x140, b"Root-CA00000001-XS00000003");
+        put(&mut data, 0x1BF, &[0xAA; 16]);
+        put(&mut data, 0x1D0, &0x0001000200030004_u64.to_be_bytes());
+        put(&mut data, 0x1DC, &title_id.to_be_bytes());
+        put(&mut data, 0x1E6, &0x0102_u16.to_be_bytes());
+        data[0x1F1] = 1;
+        put(&mut data, 0x264, &1_u32.to_be_bytes());
+        put(&mut data, 0x268, &3600_u32.to_be_bytes());
+        data
+    }
+
+    fn tmd(title_id: u64, ios: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 0x1E4 + 0x24 * 2];
+        put(&mut data, 0x000, &0x00010001_u32.to_be_bytes());
+        put(&mut data, 0x140, b"Root-CA00000001-CP00000004");
+        put(
+            &mut data,
+            0x184,
+            &(0x0000000100000000_u64 | ios as u64).to_be_bytes(),
+        );
+        put(&mut data, 0x18C, &title_id.to_be_bytes());
+        put(&mut data, 0x198, &0x3031_u16.to_be_bytes());
+        put(&mut data, 0x1DC, &0x0010_u16.to_be_bytes());
+        put(&mut data, 0x1DE, &2_u16.to_be_bytes());
+        put(&mut data, 0x1E0, &1_u16.to_be_bytes());
+        for i in 0..2 {
+            let base = 0x1E4 + 0x24 * i;
+            put(&mut data, base, &(0x10 + i as u32).to_be_bytes());
+            put(&mut data, base + 4, &(i as u16).to_be_bytes());
+            put(&mut data, base + 6, &0x0001_u16.to_be_bytes());
+            put(
+                &mut data,
+                base + 8,
+                &(0x8000_u64 * (i as u64 + 1)).to_be_bytes(),
+            );
+            put(&mut data, base + 16, &[i as u8; 20]);
+        }
+        data
+    }
+
+    pub fn disc() -> Vec<u8> {
+        let mut data = vec![0u8; 0x80000];
+        put(&mut data, 0x00, b"RZDE01");
+        put(&mut data, 0x18, &0x5D1C9EA3_u32.to_be_bytes());
+
+        // partition info: table 0 with two entries, table 1 empty
+        put(&mut data, 0x40000, &2_u32.to_be_bytes());
+        put(&mut data, 0x40004, &(0x40020_u32 >> 2).to_be_bytes());
+        put(&mut data, 0x40020, &(0x50000_u32 >> 2).to_be_bytes());
+        put(&mut data, 0x40024, &0_u32.to_be_bytes());
+        put(&mut data, 0x40028, &(0x58000_u32 >> 2).to_be_bytes());
+        put(&mut data, 0x4002C, &1_u32.to_be_bytes());
+
+        for (offset, title_id, ios) in [
+            (0x50000, 0x00010000_525A4445, 9),
+            (0x58000, 0x00000001_00000002, 0),
+        ] {
+            let ticket = ticket(title_id);
+            let tmd = tmd(title_id, ios);
+            put(&mut data, offset, &ticket);
+            put(&mut data, offset + 0x2A4, &(tmd.len() as u32).to_be_bytes());
+            put(&mut data, offset + 0x2A8, &(0x2C0_u32 >> 2).to_be_bytes());
+            put(&mut data, offset + 0x2B4, &(0x4000_u32 >> 2).to_be_bytes());
+            put(&mut data, offset + 0x2B8, &(0x20000_u32 >> 2).to_be_bytes());
+            put(&mut data, offset + 0x2BC, &(0x10000_u32 >> 2).to_be_bytes());
+            put(&mut data, offset + 0x2C0, &tmd);
+        }
+
+        data
+    }
+
+    #[test]
+    fn partitions() {
+        let wii = Wii::from_binary(&mut Cursor::new(disc())).unwrap();
+        assert_eq!(wii.partitions().len(), 2);
+
+        let data = wii.data_partition().unwrap();
+        assert_eq!(data.kind, PartitionKind::Data);
+        assert_eq!(data.table, 0);
+        assert_eq!(data.offset, 0x50000);
+        assert_eq!(data.tmd_offset, 0x2C0);
+        assert_eq!(data.h3_offset, 0x4000);
+        assert_eq!(data.data_offset, 0x20000);
+        assert_eq!(data.data_size, 0x10000);
+        assert_eq!(data.ios_version(), Some(9));
+
+        let update = wii.partition(PartitionKind::Update).unwrap();
+        assert_eq!(update.offset, 0x58000);
+        assert_eq!(update.ios_version(), Some(0));
+    }
+
+    #[test]
+    fn ticket_and_tmd() {
+        let wii = Wii::from_binary(&mut Cursor::new(disc())).unwrap();
+        let partition = wii.data_partition().unwrap();
+
+        let ticket = &partition.ticket;
+        assert_eq!(ticket.signature.kind, SignatureKind::Rsa2048);
+        assert_eq!(ticket.issuer, "Root-CA00000001-XS00000003");
+        assert_eq!(ticket.encrypted_title_key, [0xAA; 16]);
+        assert_eq!(ticket.ticket_id, 0x0001000200030004);
+        assert_eq!(ticket.title_id, 0x00010000_525A4445);
+        assert_eq!(ticket.title_version, 0x0102);
+        assert_eq!(ticket.common_key_index, 1);
+        assert!(ticket.time_limits[0].enabled);
+        assert_eq!(ticket.time_limits[0].seconds, 3600);
+
+        let tmd = &partition.tmd;
+        assert_eq!(tmd.issuer, "Root-CA00000001-CP00000004");
+        assert_eq!(tmd.title_id, 0x00010000_525A4445);
+        assert_eq!(tmd.group_id, 0x3031);
+        assert_eq!(tmd.title_version, 0x0010);
+        assert_eq!(tmd.boot_index, 1);
+        assert_eq!(tmd.contents.len(), 2);
+        let content = tmd.content(1).unwrap();
+        assert_eq!(content.id, 0x11);
+        assert_eq!(content.size, 0x10000);
+        assert_eq!(content.hash, [1; 20]);
+        assert!(!content.is_shared());
+    }
+
+    #[test]
+    fn tmd_to_binary() {
+        // title metadata followed by the certificate chain
+        let mut data = tmd(0x00010001_48414241, 56);
+        let size = data.len();
+        data.extend_from_slice(&[0xCC; 0x100]);
+
+        let tmd = Tmd::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(tmd.size(), size);
+        assert_eq!(tmd.ios_version(), Some(56));
+        assert_eq!(tmd.contents[1].filename(), "00000011.app");
+
+        let mut output = Vec::new();
+        tmd.to_binary(&mut output).unwrap();
+        assert_eq!(output, data[..size]);
+
+        let mut tmd = tmd;
+        tmd.signature.data.pop();
+        assert!(tmd.to_binary(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let mut data = disc();
+        data[0x18] = 0;
+        assert!(Wii::from_binary(&mut Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn invalid_signature_kind() {
+        let mut data = disc();
+        data[0x50003] = 0x07;
+        assert!(Wii::from_binary(&mut Cursor::new(data)).is_err());
+    }
+
+    #[cfg(feature = "crypto")]
+    mod crypto {
+        use std::io::{Cursor, Read, Seek, SeekFrom};
+
+        use aes::cipher::block_padding::NoPadding;
+        use aes::cipher::{BlockEncryptMut, KeyIvInit};
+        use picori::gcm::{fst, ConsoleType};
+        use picori::wii::{HashFailure, PartitionReader};
+        use picori::{Gcm, Wii};
+        use sha1::{Digest, Sha1};
+
+        use super::{disc, put};
+
+        const COMMON_KEY: [u8; 16] = [0x11; 16];
+
+        fn encrypt(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+            let len = data.len();
+            cbc::Encryptor::<aes::Aes128>::new(key.into(), iv.into())
+                .encrypt_padded_mut::<NoPadding>(data, len)
+                .unwrap();
+        }
+
+        fn partition_data() -> Vec<u8> {
+            let mut data = vec![0u8; 0x7C00 * 2];
+            put(&mut data, 0x000, b"RZDE01");
+            put(&mut data, 0x018, &0x5D1C9EA3_u32.to_be_bytes());
+            put(&mut data, 0x020, b"Wii Test Partition");
+            put(&mut data, 0x420, &(0x2800_u32 >> 2).to_be_bytes());
+            put(&mut data, 0x424, &(0x3000_u32 >> 2).to_be_bytes());
+            put(&mut data, 0x428, &(0x20_u32 >> 2).to_be_bytes());
+            put(&mut data, 0x42C, &(0x20_u32 >> 2).to_be_bytes());
+            put(&mut data, 0x2440, b"2008/01/01");
+
+            // main.dol with a single text section
+            put(&mut data, 0x2800, &0x100_u32.to_be_bytes());
+            put(&mut data, 0x2848, &0x80003100_u32.to_be_bytes());
+            put(&mut data, 0x2890, &0x20_u32.to_be_bytes());
+
+            // fst.bin with a single file that crosses a cluster boundary
+            put(&mut data, 0x3000, &0x01000000_u32.to_be_bytes());
+            put(&mut data, 0x3008, &2_u32.to_be_bytes());
+            put(&mut data, 0x300C, &0x00000000_u32.to_be_bytes());
+            put(&mut data, 0x3010, &(0x7BFC_u32 >> 2).to_be_bytes());
+            put(&mut data, 0x3014, &8_u32.to_be_bytes());
+            put(&mut data, 0x3018, b"a.bin\0");
+            put(&mut data, 0x7BFC, b"WiiFiles");
+            data
+        }
+
+        fn sha1(data: &[u8]) -> [u8; 20] { Sha1::digest(data).into() }
+
+        fn encrypted_disc() -> Vec<u8> {
+            let mut data = disc();
+            let wii = Wii::from_binary(&mut Cursor::new(&data)).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let title_key = partition.ticket.title_key(&COMMON_KEY);
+
+            let clusters = partition_data()
+                .chunks(0x7C00)
+                .map(|chunk| {
+                    let mut hashes = vec![0u8; 0x400];
+                    for (i, subblock) in chunk.chunks(0x400).enumerate() {
+                        put(&mut hashes, i * 20, &sha1(subblock));
+                    }
+                    (hashes, chunk.to_vec())
+                })
+                .collect::<Vec<_>>();
+
+            let h1 = clusters
+                .iter()
+                .map(|(hashes, _)| sha1(&hashes[..0x26C]))
+                .collect::<Vec<_>>();
+            let mut h1_table = vec![0u8; 0xA0];
+            for (i, hash) in h1.iter().enumerate() {
+                put(&mut h1_table, i * 20, hash);
+            }
+            let mut h2_table = vec![0u8; 0xA0];
+            put(&mut h2_table, 0, &sha1(&h1_table));
+
+            let partition_offset = partition.offset as usize;
+            let h3_offset = partition_offset + partition.h3_offset as usize;
+            put(&mut data, h3_offset, &sha1(&h2_table));
+
+            let base = partition_offset + partition.data_offset as usize;
+            for (i, (mut hashes, mut cluster)) in clusters.into_iter().enumerate() {
+                put(&mut hashes, 0x280, &h1_table);
+                put(&mut hashes, 0x340, &h2_table);
+                encrypt(&title_key, &[0; 16], &mut hashes);
+                let mut iv = [0u8; 16];
+                iv.copy_from_slice(&hashes[0x3D0..0x3E0]);
+                encrypt(&title_key, &iv, &mut cluster);
+                put(&mut data, base + i * 0x8000, &hashes);
+                put(&mut data, base + i * 0x8000 + 0x400, &cluster);
+            }
+
+            // content hash of the tmd is the hash of the h3 table
+            let h3_hash = sha1(&data[h3_offset..h3_offset + 0x18000]);
+            put(&mut data, partition_offset + 0x2C0 + 0x1E4 + 16, &h3_hash);
+            data
+        }
+
+        #[test]
+        fn title_key() {
+            let wii = Wii::from_binary(&mut Cursor::new(disc())).unwrap();
+            let ticket = &wii.data_partition().unwrap().ticket;
+            let title_key = ticket.title_key(&COMMON_KEY);
+
+            let mut encrypted = title_key;
+            encrypt(&COMMON_KEY, &ticket.title_key_iv(), &mut encrypted);
+            assert_eq!(encrypted, ticket.encrypted_title_key);
+        }
+
+        #[test]
+        fn decrypt() {
+            let mut disc = Cursor::new(encrypted_disc());
+            let wii = Wii::from_binary(&mut disc).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let mut reader = PartitionReader::new(&mut disc, partition, &COMMON_KEY).unwrap();
+            assert_eq!(reader.cluster_count(), 2);
+            assert_eq!(reader.data_size(), 0x7C00 * 2);
+
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).unwrap();
+            assert_eq!(data, partition_data());
+
+            let cluster = reader.read_cluster(1).unwrap();
+            assert_eq!(cluster.hashes[..20], sha1(&data[0x7C00..0x8000]));
+        }
+
+        #[test]
+        fn gcm() {
+            let mut disc = Cursor::new(encrypted_disc());
+            let wii = Wii::from_binary(&mut disc).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let mut reader = PartitionReader::new(&mut disc, partition, &COMMON_KEY).unwrap();
+            let gcm = Gcm::from_binary(&mut reader).unwrap();
+            assert_eq!(gcm.boot().console, ConsoleType::Wii);
+            assert_eq!(gcm.boot().game_name, "Wii Test Partition");
+            assert_eq!(gcm.executable().data().len(), 0x120);
+
+            let (path, entry) = gcm.fst().files().nth(1).unwrap();
+            assert_eq!(path.to_str(), Some("a.bin"));
+            let fst::Entry::File { offset, size, .. } = entry else {
+                panic!("expected file");
+            };
+            assert_eq!(offset, 0x7BFC);
+
+            let mut data = vec![0u8; size as usize];
+            reader.seek(SeekFrom::Start(offset)).unwrap();
+            reader.read_exact(&mut data).unwrap();
+            assert_eq!(data, b"WiiFiles");
+        }
+
+        #[test]
+        fn verify() {
+            let mut disc = Cursor::new(encrypted_disc());
+            let wii = Wii::from_binary(&mut disc).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let mut reader = PartitionReader::new(&mut disc, partition, &COMMON_KEY).unwrap();
+            assert_eq!(reader.verify().unwrap(), vec![]);
+        }
+
+        #[test]
+        fn verify_corrupt_subblock() {
+            let mut data = encrypted_disc();
+            // flip a byte in the user data of cluster 1, subblock 3
+            data[0x70000 + 0x8000 + 0x400 + 0xC10] ^= 0xFF;
+
+            let mut disc = Cursor::new(data);
+            let wii = Wii::from_binary(&mut disc).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let mut reader = PartitionReader::new(&mut disc, partition, &COMMON_KEY).unwrap();
+            let failures = reader.verify().unwrap();
+            assert_eq!(failures, vec![HashFailure::H0 {
+                cluster:  1,
+                subblock: 3,
+            }]);
+            assert_eq!(failures[0].data_offset(), Some(0x7C00 + 0xC00));
+        }
+
+        #[test]
+        fn verify_corrupt_h3_table() {
+            let mut data = encrypted_disc();
+            data[0x54000] ^= 0xFF;
+
+            let mut disc = Cursor::new(data);
+            let wii = Wii::from_binary(&mut disc).unwrap();
+            let partition = wii.data_partition().unwrap();
+            let mut reader = PartitionReader::new(&mut disc, partition, &COMMON_KEY).unwrap();
+            assert_eq!(reader.verify().unwrap(), vec![
+                HashFailure::H4,
+                HashFailure::H3 { cluster: 0 },
+                HashFailure::H3 { cluster: 1 },
+            ]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let wii = Wii::from_binary(&mut Cursor::new(disc())).unwrap();
+        let partition = wii.data_partition().unwrap();
+        let json = serde_json::to_string(partition).unwrap();
+        assert_eq!(serde_json::from_str::<Partition>(&json).unwrap(), *partition);
+
+        // the ticket has byte arrays longer than 32 bytes
+        let mut json = serde_json::to_value(&partition.ticket).unwrap();
+        assert_eq!(json["ecdh_data"].as_array().unwrap().len(), 0x3C);
+        assert_eq!(serde_json::from_value::<Ticket>(json.clone()).unwrap(), partition.ticket);
+        json["ecdh_data"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<Ticket>(json).is_err());
+    }
+}
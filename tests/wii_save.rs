@@ -0,0 +1,121 @@
+#[cfg(all(test, feature = "crypto"))]
+mod wii_save {
+    use std::io::Cursor;
+
+    use aes::cipher::block_padding::NoPadding;
+    use aes::cipher::{BlockEncryptMut, KeyIvInit};
+    use picori::wii_save::{SaveData, SaveFileKind};
+
+    const SD_KEY: [u8; 16] = [0x33; 16];
+    const SD_IV: [u8; 16] = [0x44; 16];
+
+    fn encrypt(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+        let len = data.len();
+        cbc::Encryptor::<aes::Aes128>::new(key.into(), iv.into())
+            .encrypt_padded_mut::<NoPadding>(data, len)
+            .unwrap();
+    }
+
+    fn file_header(kind: u8, name: &str, size: usize, iv: &[u8; 16]) -> Vec<u8> {
+        let mut header = vec![0u8; 0x80];
+        header[0x00..0x04].copy_from_slice(&0x03ADF17E_u32.to_be_bytes());
+        header[0x04..0x08].copy_from_slice(&(size as u32).to_be_bytes());
+        header[0x08] = 0x3C;
+        header[0x0A] = kind;
+        header[0x0B..0x0B + name.len()].copy_from_slice(name.as_bytes());
+        header[0x50..0x60].copy_from_slice(iv);
+        header
+    }
+
+    fn data_bin(name: &str) -> Vec<u8> {
+        let mut header = vec![0u8; 0xF0C0];
+        header[0x00..0x08].copy_from_slice(&0x00010000_525A4445_u64.to_be_bytes());
+        header[0x08..0x0C].copy_from_slice(&0x100_u32.to_be_bytes());
+        header[0x0C] = 0x3C;
+        header[0x0E..0x1E].copy_from_slice(&[0xAB; 16]);
+        header[0x20..0x24].copy_from_slice(b"WIBN");
+        encrypt(&SD_KEY, &SD_IV, &mut header);
+
+        let mut bk = vec![0u8; 0x80];
+        bk[0x00..0x04].copy_from_slice(&0x70_u32.to_be_bytes());
+        bk[0x04..0x06].copy_from_slice(b"Bk");
+        bk[0x06..0x08].copy_from_slice(&1_u16.to_be_bytes());
+        bk[0x08..0x0C].copy_from_slice(&0x0403AC68_u32.to_be_bytes());
+        bk[0x0C..0x10].copy_from_slice(&2_u32.to_be_bytes());
+        bk[0x10..0x14].copy_from_slice(&0x140_u32.to_be_bytes());
+        bk[0x1C..0x20].copy_from_slice(&0x780_u32.to_be_bytes());
+        bk[0x64..0x68].copy_from_slice(b"RZDE");
+        bk[0x68..0x6E].copy_from_slice(&[0, 0x17, 0xAB, 0x01, 0x02, 0x03]);
+
+        let mut data = header;
+        data.extend_from_slice(&bk);
+        data.extend_from_slice(&file_header(2, "dir", 0, &[0; 16]));
+        let iv = [0x55; 16];
+        let mut content = (0..0x80).map(|x| x as u8).collect::<Vec<_>>();
+        content[0x45..].fill(0);
+        encrypt(&SD_KEY, &iv, &mut content);
+        data.extend_from_slice(&file_header(1, name, 0x45, &iv));
+        data.extend_from_slice(&content);
+        data.extend_from_slice(&[0xCC; 0x40]);
+        data
+    }
+
+    #[test]
+    fn from_binary() {
+        let data = data_bin("dir/zeldaTp.dat");
+        let save = SaveData::from_binary(&mut Cursor::new(&data), &SD_KEY, &SD_IV).unwrap();
+        assert_eq!(save.title_id, 0x00010000_525A4445);
+        assert_eq!(save.permissions, 0x3C);
+        assert_eq!(save.hash, [0xAB; 16]);
+        assert_eq!(save.banner.len(), 0x100);
+        assert_eq!(save.banner[..4], *b"WIBN");
+
+        let bk = &save.bk_header;
+        assert_eq!(bk.version, 1);
+        assert_eq!(bk.ng_id, 0x0403AC68);
+        assert_eq!(bk.file_count, 2);
+        assert_eq!(bk.files_size, 0x140);
+        assert_eq!(bk.total_size, 0x780);
+        assert_eq!(bk.game_code, *b"RZDE");
+        assert_eq!(bk.mac_address, [0, 0x17, 0xAB, 0x01, 0x02, 0x03]);
+
+        assert_eq!(save.files.len(), 2);
+        assert_eq!(save.files[0].name, "dir");
+        assert_eq!(save.files[0].kind, SaveFileKind::Directory);
+        assert!(save.files[0].data.is_empty());
+        let file = &save.files[1];
+        assert_eq!(file.name, "dir/zeldaTp.dat");
+        assert_eq!(file.kind, SaveFileKind::File);
+        assert_eq!(file.permissions, 0x3C);
+        assert_eq!(file.data, (0..0x45).map(|x| x as u8).collect::<Vec<_>>());
+
+        // wrong SD key
+        assert!(SaveData::from_binary(&mut Cursor::new(&data), &[0; 16], &SD_IV).is_err());
+    }
+
+    #[test]
+    fn extract_to() {
+        let root = std::env::temp_dir().join(format!("picori-wii-save-{}", std::process::id()));
+        let save = SaveData::from_binary(
+            &mut Cursor::new(data_bin("dir/zeldaTp.dat")),
+            &SD_KEY,
+            &SD_IV,
+        )
+        .unwrap();
+        save.extract_to(&root).unwrap();
+        assert_eq!(
+            std::fs::read(root.join("dir/zeldaTp.dat")).unwrap(),
+            save.files[1].data
+        );
+
+        // file names outside of the directory
+        let save = SaveData::from_binary(
+            &mut Cursor::new(data_bin("../zeldaTp.dat")),
+            &SD_KEY,
+            &SD_IV,
+        )
+        .unwrap();
+        assert!(save.extract_to(&root).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod yay0 {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use picori::yay0::{self, is_yay0, Yay0Reader};
+
+    /// Convert Yaz0 compressed data to Yay0, both use the same tokens.
+    fn from_yaz0(data: &[u8]) -> Vec<u8> {
+        let size = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+        let (mut codes, mut links, mut chunks) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut input, mut output, mut code, mut bits) = (0x10, 0, 0_u32, 0);
+        while output < size {
+            if bits % 8 == 0 {
+                code = data[input] as u32;
+                input += 1;
+            }
+            if bits % 32 == 0 {
+                codes.extend_from_slice(&[0; 4]);
+            }
+            if code & 0x80 != 0 {
+                let index = codes.len() - 4 + bits % 32 / 8;
+                codes[index] |= 0x80 >> (bits % 8);
+                chunks.push(data[input]);
+                input += 1;
+                output += 1;
+            } else {
+                links.extend_from_slice(&data[input..input + 2]);
+                output += match data[input] >> 4 {
+                    0 => {
+                        chunks.push(data[input + 2]);
+                        input += 1;
+                        data[input + 1] as usize + 0x12
+                    },
+                    length => length as usize + 2,
+                };
+                input += 2;
+            }
+            code <<= 1;
+            bits += 1;
+        }
+
+        let link_offset = 0x10 + codes.len();
+        let chunk_offset = link_offset + links.len();
+        let mut result = b"Yay0".to_vec();
+        result.extend_from_slice(&(size as u32).to_be_bytes());
+        result.extend_from_slice(&(link_offset as u32).to_be_bytes());
+        result.extend_from_slice(&(chunk_offset as u32).to_be_bytes());
+        result.extend(codes);
+        result.extend(links);
+        result.extend(chunks);
+        result
+    }
+
+    #[test]
+    fn decompress() {
+        let data: &[u8] = &[
+            0x59, 0x61, 0x79, 0x30, // magic = 'Yay0'
+            0x00, 0x00, 0x00, 0x0A, // decompressed size = 10
+            0x00, 0x00, 0x00, 0x14, // link offset = 0x14
+            0x00, 0x00, 0x00, 0x16, // chunk offset = 0x16
+            0xE8, 0x00, 0x00, 0x00, // codes = 1110 1
+            0x40, 0x02, // links = (length 6, distance 3)
+            b'a', b'b', b'c', b'X', // chunks
+        ];
+
+        let mut cursor = Cursor::new(data);
+        assert!(is_yay0(&mut cursor));
+        let header = yay0::Header::from_binary(&mut cursor).unwrap();
+        assert_eq!(header.decompressed_size, 10);
+        assert_eq!(
+            yay0::decompress(&mut cursor, &header).unwrap(),
+            b"abcabcabcX"
+        );
+
+        // a too short destination fails
+        let mut cursor = Cursor::new(data);
+        let header = yay0::Header::from_binary(&mut cursor).unwrap();
+        let mut output = [0; 8];
+        assert!(yay0::decompress_into(&mut cursor, &header, &mut output).is_err());
+
+        // truncated chunks
+        let mut cursor = Cursor::new(&data[..data.len() - 1]);
+        let header = yay0::Header::from_binary(&mut cursor).unwrap();
+        assert!(yay0::decompress(&mut cursor, &header).is_err());
+    }
+
+    #[test]
+    fn long_back_reference() {
+        let data: &[u8] = &[
+            0x59, 0x61, 0x79, 0x30, // magic = 'Yay0'
+            0x00, 0x00, 0x00, 0x21, // decompressed size = 0x21
+            0x00, 0x00, 0x00, 0x14, // link offset = 0x14
+            0x00, 0x00, 0x00, 0x16, // chunk offset = 0x16
+            0x80, 0x00, 0x00, 0x00, // codes = 10
+            0x00, 0x00, // links = (length from chunks, distance 1)
+            b'a', 0x0E, // chunks
+        ];
+
+        let mut reader = Yay0Reader::new(Cursor::new(data)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, [b'a'; 0x21]);
+    }
+
+    #[test]
+    fn reader() {
+        let c = from_yaz0(include_bytes!("../assets/tests/yaz0/test1.input"));
+        let d = include_bytes!("../assets/tests/yaz0/test1.output");
+        let mut reader = Yay0Reader::new(Cursor::new(&c)).unwrap();
+        assert_eq!(reader.decompressed_size(), d.len());
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert!(buf == d.as_slice());
+
+        reader.seek(SeekFrom::End(-0x10)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, &d[d.len() - 0x10..]);
+        assert_eq!(reader.seek(SeekFrom::End(1)).unwrap(), d.len() as u64);
+        assert!(reader
+            .seek(SeekFrom::Current(-(d.len() as i64) - 1))
+            .is_err());
+    }
+
+    #[test]
+    fn bad_magic() {
+        let data = b"Yaz0 is not Yay0";
+        let mut reader = Yay0Reader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.decompressed_size(), 0);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, data);
+        assert!(!is_yay0(&mut Cursor::new(data)));
+    }
+}
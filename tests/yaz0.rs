@@ -1,8 +1,8 @@
 #[cfg(test)]
 mod yaz0 {
-    use std::io::{Cursor, Read, Seek};
+    use std::io::{Cursor, Read, Seek, Write};
 
-    use picori::yaz0::{self, is_yaz0, Yaz0Reader};
+    use picori::yaz0::{self, is_yaz0, CompressOptions, Strategy, Variant, Yaz0Reader, Yaz0Writer};
 
     #[test]
     fn test09() {
@@ -63,6 +63,74 @@ mod yaz0 {
         assert!(result.unwrap().decompressed_size() == 0);
     }
 
+    #[test]
+    fn back_reference_past_end() {
+        let data: &[u8] = &[
+            0x59, 0x61, 0x7A, 0x30, // magic = 'Yaz0'
+            0x00, 0x00, 0x00, 0x02, // length = 2
+            0x00, 0x00, 0x00, 0x00, // reserved0 = 0x00000000
+            0x00, 0x00, 0x00, 0x00, // reserved1 = 0x00000000
+            0x80, 0x41, // literal 'A'
+            0x10, 0x00, // back-reference of 3 bytes
+        ];
+
+        let mut cursor = Cursor::new(data);
+        assert!(picori::compression::decompress_auto(&mut cursor).is_err());
+        let mut destination = [0; 2];
+        let mut cursor = Cursor::new(&data[16..]);
+        assert!(yaz0::decompress_into(&mut cursor, &mut destination).is_err());
+    }
+
+    #[test]
+    fn header() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let mut cursor = Cursor::new(c);
+        let header = yaz0::header(&mut cursor).unwrap();
+        assert_eq!(header.decompressed_size, 0x126604);
+        assert_eq!(header.alignment, 0);
+        assert_eq!(cursor.position(), 0);
+
+        let data: &[u8] = &[
+            0x59, 0x61, 0x7A, 0x30, // magic = 'Yaz0'
+            0x00, 0x00, 0x01, 0x00, // length = 0x100
+            0x00, 0x00, 0x20, 0x00, // alignment = 0x2000
+            0x00, 0x00, 0x00, 0x00, // reserved = 0x00000000
+        ];
+        let header = yaz0::header(&mut Cursor::new(data)).unwrap();
+        assert_eq!(header.decompressed_size, 0x100);
+        assert_eq!(header.alignment, 0x2000);
+
+        let mut cursor = Cursor::new(&data[..0xC]);
+        assert!(yaz0::header(&mut cursor).is_err());
+        assert_eq!(cursor.position(), 0);
+        assert!(yaz0::header(&mut Cursor::new(b"Yay0............")).is_err());
+    }
+
+    #[test]
+    fn yaz1() {
+        let c = include_bytes!("../assets/tests/yaz0/test.input");
+        let d = include_bytes!("../assets/tests/yaz0/test.output");
+        let mut c = c.to_vec();
+        c[3] = b'1';
+
+        let header = yaz0::header(&mut Cursor::new(&c)).unwrap();
+        assert_eq!(header.variant(), Some(Variant::Yaz1));
+        assert!(is_yaz0(&mut Cursor::new(&c)));
+        let mut reader = Yaz0Reader::new(Cursor::new(&c)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), d);
+
+        let options = CompressOptions::new().variant(Variant::Yaz1);
+        let compressed = yaz0::compress_with(d, &options);
+        assert_eq!(&compressed[..4], b"Yaz1");
+        assert_eq!(compressed[4..], yaz0::compress(d)[4..]);
+
+        let mut writer = Yaz0Writer::with_options(Vec::new(), 6, &options).unwrap();
+        writer.write_all(b"abcabc").unwrap();
+        assert_eq!(&writer.finish().unwrap()[..4], b"Yaz1");
+    }
+
     #[test]
     fn check_compressed() {
         let data: &[u8] = &[
@@ -114,7 +182,7 @@ mod yaz0 {
         reader
             .seek(std::io::SeekFrom::Start((d.len() / 2) as u64))
             .unwrap();
-        reader.seek(std::io::SeekFrom::Current(0)).unwrap();
+        reader.stream_position().unwrap();
         reader
             .seek(std::io::SeekFrom::End(-((d.len() / 2) as i64)))
             .unwrap();
@@ -124,4 +192,160 @@ mod yaz0 {
         assert_eq!(result, d.len() / 2);
         assert_eq!(buf.as_slice(), &d[d.len() / 2..]);
     }
+
+    #[test]
+    fn seek_random() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let d = include_bytes!("../assets/tests/yaz0/test1.output");
+        let mut reader = Yaz0Reader::new(Cursor::new(c)).unwrap();
+        assert_eq!(reader.decompressed_size(), d.len());
+
+        let mut state = 0x12345678_u32;
+        for _ in 0..64 {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            let offset = state as usize % d.len();
+            let size = (state >> 20) as usize % 0x800;
+            reader
+                .seek(std::io::SeekFrom::Start(offset as u64))
+                .unwrap();
+            let mut buf = vec![0; size];
+            let n = reader.read(&mut buf).unwrap();
+            assert_eq!(n, size.min(d.len() - offset));
+            assert_eq!(&buf[..n], &d[offset..offset + n]);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let mut reader = Yaz0Reader::new(Cursor::new(&c[..0x1000])).unwrap();
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).is_err());
+    }
+
+    fn round_trip_with(data: &[u8], options: &CompressOptions) -> Vec<u8> {
+        let compressed = yaz0::compress_with(data, options);
+        let mut cursor = Cursor::new(&compressed);
+        let size = yaz0::Header::decompressed_size(&mut cursor).unwrap();
+        assert_eq!(size, data.len());
+        assert_eq!(yaz0::decompress(&mut cursor, size).unwrap(), data);
+        compressed
+    }
+
+    fn round_trip(data: &[u8]) -> Vec<u8> { round_trip_with(data, &CompressOptions::new()) }
+
+    #[test]
+    fn compress() {
+        assert_eq!(round_trip(&[]).len(), 0x10);
+        round_trip(b"a");
+        round_trip(include_bytes!("../assets/tests/yaz0/test.output"));
+        round_trip(include_bytes!("../assets/tests/yaz0/test1.output"));
+
+        let noise = (0..0x4000_u32)
+            .map(|i| (i.wrapping_mul(0x9E3779B1) >> 13) as u8)
+            .collect::<Vec<_>>();
+        round_trip(&noise);
+
+        // runs use overlapping back-references of the maximum length
+        let compressed = round_trip(&[0xAB; 0x10000]);
+        assert!(compressed.len() < 0x10 + 0x10000 / 0x111 * 3 + 0x200);
+    }
+
+    #[test]
+    fn compress_strategies() {
+        let data = include_bytes!("../assets/tests/yaz0/test.output");
+        let greedy = round_trip_with(data, &CompressOptions::new());
+        for strategy in [Strategy::Lazy, Strategy::Optimal, Strategy::Nintendo] {
+            let options = CompressOptions::new().strategy(strategy);
+            round_trip_with(b"", &options);
+            round_trip_with(b"abcabcabcabcabcd", &options);
+            round_trip_with(&[0; 0x1000], &options);
+            let compressed = round_trip_with(data, &options);
+            if strategy != Strategy::Nintendo {
+                assert!(compressed.len() <= greedy.len());
+            }
+        }
+
+        let optimal = CompressOptions::new().strategy(Strategy::Optimal);
+        let lazy = CompressOptions::new().strategy(Strategy::Lazy);
+        assert!(round_trip_with(data, &optimal).len() <= round_trip_with(data, &lazy).len());
+
+        // a smaller window finds fewer back-references
+        let small = round_trip_with(data, &CompressOptions::new().window(0x100));
+        assert!(small.len() > greedy.len());
+        round_trip_with(data, &CompressOptions::new().window(0));
+    }
+
+    #[test]
+    fn compress_nintendo() {
+        // test1.input was created by Nintendo's compressor
+        let original = include_bytes!("../assets/tests/yaz0/test1.input");
+        let data = include_bytes!("../assets/tests/yaz0/test1.output");
+        let options = CompressOptions::new().strategy(Strategy::Nintendo);
+        assert!(yaz0::compress_with(data, &options) == original.as_slice());
+    }
+
+    fn write(data: &[u8], chunk: usize, options: &CompressOptions) -> Vec<u8> {
+        let mut writer = Yaz0Writer::with_options(Vec::new(), data.len() as u32, options).unwrap();
+        for chunk in data.chunks(chunk) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn writer() {
+        let data = include_bytes!("../assets/tests/yaz0/test1.output");
+        for strategy in [Strategy::Greedy, Strategy::Lazy, Strategy::Nintendo] {
+            let options = CompressOptions::new().strategy(strategy);
+            let expected = yaz0::compress_with(data, &options);
+            assert!(write(data, 0x1234, &options) == expected);
+            assert!(
+                write(&data[..0x1000], 1, &options)
+                    == yaz0::compress_with(&data[..0x1000], &options)
+            );
+        }
+        assert_eq!(write(b"", 1, &CompressOptions::new()), yaz0::compress(b""));
+
+        let options = CompressOptions::new().strategy(Strategy::Optimal);
+        let compressed = write(data, 0x4321, &options);
+        let mut cursor = Cursor::new(&compressed);
+        let size = yaz0::Header::decompressed_size(&mut cursor).unwrap();
+        assert!(yaz0::decompress(&mut cursor, size).unwrap() == data.as_slice());
+    }
+
+    #[test]
+    fn writer_size() {
+        let mut writer = Yaz0Writer::new(Vec::new(), 4).unwrap();
+        writer.write_all(b"abc").unwrap();
+        assert!(writer.write_all(b"de").is_err());
+
+        let mut writer = Yaz0Writer::new(Vec::new(), 4).unwrap();
+        writer.write_all(b"abc").unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn compress_parallel() {
+        let data = include_bytes!("../assets/tests/yaz0/test1.output");
+        for strategy in [Strategy::Greedy, Strategy::Optimal, Strategy::Nintendo] {
+            let options = CompressOptions::new().strategy(strategy);
+            let compressed = yaz0::compress_parallel(data, &options);
+            let mut cursor = Cursor::new(&compressed);
+            let size = yaz0::Header::decompressed_size(&mut cursor).unwrap();
+            assert!(yaz0::decompress(&mut cursor, size).unwrap() == data.as_slice());
+
+            let serial = yaz0::compress_with(data, &options);
+            assert!(compressed.len() < serial.len() + serial.len() / 100);
+        }
+
+        // inputs of a single chunk give the same output
+        let options = CompressOptions::new();
+        assert!(
+            yaz0::compress_parallel(&data[..0x1000], &options)
+                == yaz0::compress_with(&data[..0x1000], &options)
+        );
+        assert_eq!(yaz0::compress_parallel(&[], &options), yaz0::compress(&[]));
+    }
 }